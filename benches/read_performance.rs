@@ -1,8 +1,9 @@
 use chrono::Utc;
 use criterion::{
     black_box, criterion_group, criterion_main,
-    BenchmarkId, Criterion,
+    BenchmarkId, Criterion, Throughput,
 };
+use pcapfile_io::foundation::utils::calculate_crc32;
 use pcapfile_io::{DataPacket, PcapReader, PcapWriter};
 use std::path::PathBuf;
 use tempfile::TempDir;
@@ -141,11 +142,36 @@ fn bench_read_all_sequential(c: &mut Criterion) {
     });
 }
 
+/// 基准测试：校验和计算
+fn bench_checksum(c: &mut Criterion) {
+    let mut group = c.benchmark_group("crc32");
+
+    for size in [64, 1024, 65536].iter() {
+        let data: Vec<u8> =
+            (0..*size).map(|i| i as u8).collect();
+        group.throughput(Throughput::Bytes(*size as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &data,
+            |b, data| {
+                b.iter(|| {
+                    black_box(calculate_crc32(black_box(
+                        data,
+                    )))
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_read_packet,
     bench_read_packets_batch,
     bench_read_packet_data_only,
-    bench_read_all_sequential
+    bench_read_all_sequential,
+    bench_checksum
 );
 criterion_main!(benches);
@@ -3,7 +3,9 @@ use criterion::{
     black_box, criterion_group, criterion_main,
     BenchmarkId, Criterion,
 };
-use pcapfile_io::{DataPacket, PcapWriter};
+use pcapfile_io::{
+    ByteSize, DataPacket, PcapWriter, WriterConfig,
+};
 use tempfile::TempDir;
 
 /// 创建测试数据包
@@ -125,6 +127,50 @@ fn bench_write_many_small_packets(c: &mut Criterion) {
     });
 }
 
+/// 基准测试：合并小数据包后按块写入
+fn bench_write_many_small_packets_coalesced(
+    c: &mut Criterion,
+) {
+    c.bench_function(
+        "write_1000_small_packets_coalesced",
+        |b| {
+            b.iter(|| {
+                let temp_dir = TempDir::new()
+                    .expect("创建临时目录失败");
+                let config = WriterConfig {
+                    min_write_block: ByteSize::kib(4),
+                    ..Default::default()
+                };
+                let mut writer =
+                    PcapWriter::new_with_config(
+                        temp_dir.path(),
+                        "bench_dataset",
+                        config,
+                    )
+                    .expect("创建Writer失败");
+
+                for i in 0..1000 {
+                    let data =
+                        format!("Small packet #{}", i)
+                            .into_bytes();
+                    let packet = DataPacket::from_datetime(
+                        Utc::now(),
+                        data,
+                    )
+                    .expect("创建失败");
+                    writer
+                        .write_packet(&packet)
+                        .expect("写入失败");
+                }
+
+                writer.flush().expect("刷新失败");
+
+                black_box(());
+            });
+        },
+    );
+}
+
 /// 基准测试：写入后立即刷新 vs 批量刷新
 fn bench_write_with_flush(c: &mut Criterion) {
     let mut group =
@@ -196,6 +242,7 @@ criterion_group!(
     bench_write_packet,
     bench_write_packets_batch,
     bench_write_many_small_packets,
+    bench_write_many_small_packets_coalesced,
     bench_write_with_flush
 );
 criterion_main!(benches);
@@ -0,0 +1,136 @@
+//! 时间窗口聚合模块
+//!
+//! 按固定时长把数据集切分为连续的时间窗口，直接遍历索引条目统计每个窗口的
+//! 数据包数量、字节总数和负载大小范围，无需读取数据文件，
+//! 适用于绘制长时间录制过程中的采集速率曲线。
+
+use crate::business::index::types::PidxIndex;
+
+/// 窗口聚合指标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Aggregation {
+    /// 数据包数量
+    Count,
+    /// 负载字节总数
+    Bytes,
+    /// 最小负载大小
+    MinSize,
+    /// 最大负载大小
+    MaxSize,
+}
+
+impl Aggregation {
+    /// 全部聚合指标
+    pub const ALL: [Aggregation; 4] = [
+        Aggregation::Count,
+        Aggregation::Bytes,
+        Aggregation::MinSize,
+        Aggregation::MaxSize,
+    ];
+}
+
+/// 单个时间窗口的聚合结果
+///
+/// 未请求的指标为 `None`；没有数据包的窗口中 `min_size` 和 `max_size` 也为 `None`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowAggregate {
+    /// 窗口起始时间戳（纳秒，包含）
+    pub start_ns: u64,
+    /// 窗口结束时间戳（纳秒，不包含）
+    pub end_ns: u64,
+    /// 数据包数量
+    pub packet_count: Option<u64>,
+    /// 负载字节总数
+    pub total_bytes: Option<u64>,
+    /// 最小负载大小（字节）
+    pub min_size: Option<u32>,
+    /// 最大负载大小（字节）
+    pub max_size: Option<u32>,
+}
+
+/// 窗口累积状态
+#[derive(Default, Clone, Copy)]
+struct Accumulator {
+    count: u64,
+    bytes: u64,
+    min_size: Option<u32>,
+    max_size: Option<u32>,
+}
+
+/// 遍历索引条目计算各时间窗口的聚合结果
+///
+/// 第一个窗口从数据集第一个数据包的时间戳开始，之后的窗口首尾相接直到覆盖
+/// 最后一个数据包，中间没有数据包的窗口同样保留，使结果构成连续的时间序列。
+pub(crate) fn aggregate_index(
+    index: &PidxIndex,
+    window_ns: u64,
+    aggregations: &[Aggregation],
+) -> Vec<WindowAggregate> {
+    let timestamps = index.sorted_timestamps();
+    let (Some(&first), Some(&last)) =
+        (timestamps.first(), timestamps.last())
+    else {
+        return Vec::new();
+    };
+    let window_count = (last - first) / window_ns + 1;
+
+    let mut windows =
+        vec![Accumulator::default(); window_count as usize];
+    for entry in index
+        .files()
+        .iter()
+        .flat_map(|file| file.data_packets.iter())
+    {
+        let Some(offset) =
+            entry.timestamp_ns.checked_sub(first)
+        else {
+            continue;
+        };
+        let Some(window) =
+            windows.get_mut((offset / window_ns) as usize)
+        else {
+            continue;
+        };
+        window.count += 1;
+        window.bytes += u64::from(entry.packet_size);
+        window.min_size = Some(
+            window
+                .min_size
+                .map_or(entry.packet_size, |min| {
+                    min.min(entry.packet_size)
+                }),
+        );
+        window.max_size = Some(
+            window
+                .max_size
+                .map_or(entry.packet_size, |max| {
+                    max.max(entry.packet_size)
+                }),
+        );
+    }
+
+    let wants = |aggregation: Aggregation| {
+        aggregations.contains(&aggregation)
+    };
+    windows
+        .into_iter()
+        .enumerate()
+        .map(|(i, window)| {
+            let start_ns = first + i as u64 * window_ns;
+            WindowAggregate {
+                start_ns,
+                end_ns: start_ns.saturating_add(window_ns),
+                packet_count: wants(Aggregation::Count)
+                    .then_some(window.count),
+                total_bytes: wants(Aggregation::Bytes)
+                    .then_some(window.bytes),
+                min_size: window.min_size.filter(|_| {
+                    wants(Aggregation::MinSize)
+                }),
+                max_size: window.max_size.filter(|_| {
+                    wants(Aggregation::MaxSize)
+                }),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,409 @@
+//! 组合数据集读取模块
+//!
+//! 把多个数据集按起始时间戳排列成一条连续的逻辑时间线，
+//! 用于把跨天拆分的记录作为一个数据流读取。
+
+use log::debug;
+
+use crate::api::reader::PcapReader;
+use crate::api::source::PacketSource;
+use crate::data::models::{DatasetInfo, ValidatedPacket};
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 组合方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompositeMode {
+    /// 依次读取各数据集，数据集的时间范围重叠时创建失败
+    #[default]
+    Concatenate,
+    /// 按时间戳合并各数据集的数据包，允许时间范围重叠
+    ///
+    /// 时间戳相同的数据包按数据集编号顺序输出。
+    Merge,
+}
+
+/// 组合读取中的单个数据集
+struct CompositeSource {
+    reader: PcapReader,
+    /// 时间范围，数据集没有数据包时为 None
+    time_range: Option<(u64, u64)>,
+    /// 已读出但尚未输出的数据包
+    pending: Option<ValidatedPacket>,
+    /// 数据集已读完
+    exhausted: bool,
+}
+
+impl CompositeSource {
+    /// 确保待输出数据包已就绪，数据集读完时返回 `None`
+    fn peek(&mut self) -> PcapResult<Option<u64>> {
+        if self.pending.is_none() && !self.exhausted {
+            self.pending = self.reader.read_packet()?;
+            self.exhausted = self.pending.is_none();
+        }
+        Ok(self
+            .pending
+            .as_ref()
+            .map(ValidatedPacket::get_timestamp_ns))
+    }
+}
+
+/// 组合数据集读取器
+///
+/// 数据集按起始时间戳排序后编号（没有数据包的数据集排在最后），
+/// 读取、跳转和按时间范围读取都作用于整条逻辑时间线，并实现 `PacketSource`。
+pub struct CompositeReader {
+    name: String,
+    mode: CompositeMode,
+    sources: Vec<CompositeSource>,
+    /// 最近输出的数据包所属的数据集编号
+    last_dataset: Option<usize>,
+}
+
+impl CompositeReader {
+    /// 以 `CompositeMode::Concatenate` 组合数据集
+    ///
+    /// # 错误
+    /// 没有数据集时返回 `PcapError::InvalidArgument`；
+    /// 两个数据集的时间范围重叠（只在边界时间戳相同时不视为重叠）时返回
+    /// `PcapError::InvalidArgument`，需要改用 `CompositeMode::Merge`
+    pub fn new(
+        readers: Vec<PcapReader>,
+    ) -> PcapResult<Self> {
+        Self::with_mode(readers, CompositeMode::Concatenate)
+    }
+
+    /// 以指定方式组合数据集
+    pub fn with_mode(
+        readers: Vec<PcapReader>,
+        mode: CompositeMode,
+    ) -> PcapResult<Self> {
+        if readers.is_empty() {
+            return Err(PcapError::InvalidArgument(
+                "组合读取至少需要一个数据集".to_string(),
+            ));
+        }
+
+        let mut sources = Vec::with_capacity(readers.len());
+        for mut reader in readers {
+            let info = reader.get_dataset_info()?;
+            let time_range = info
+                .start_timestamp
+                .zip(info.end_timestamp)
+                .filter(|_| info.total_packets > 0);
+            sources.push(CompositeSource {
+                reader,
+                time_range,
+                pending: None,
+                exhausted: false,
+            });
+        }
+        sources.sort_by_key(|source| {
+            (
+                source.time_range.is_none(),
+                source.time_range.map(|(start, _)| start),
+            )
+        });
+
+        if mode == CompositeMode::Concatenate {
+            for pair in sources.windows(2) {
+                if let (
+                    Some((_, previous_end)),
+                    Some((start, _)),
+                ) =
+                    (pair[0].time_range, pair[1].time_range)
+                {
+                    if start < previous_end {
+                        return Err(PcapError::InvalidArgument(
+                            format!(
+                                "数据集 {} 与 {} 的时间范围重叠，需使用合并模式",
+                                pair[0].reader.dataset_name(),
+                                pair[1].reader.dataset_name()
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let name = sources
+            .iter()
+            .map(|source| source.reader.dataset_name())
+            .collect::<Vec<_>>()
+            .join("+");
+        debug!("组合读取数据集: {name}, 方式 {mode:?}");
+        Ok(Self {
+            name,
+            mode,
+            sources,
+            last_dataset: None,
+        })
+    }
+
+    /// 组合方式
+    pub fn mode(&self) -> CompositeMode {
+        self.mode
+    }
+
+    /// 数据集数量
+    pub fn dataset_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// 获取数据集的读取器
+    pub fn reader(
+        &self,
+        dataset_id: usize,
+    ) -> Option<&PcapReader> {
+        self.sources
+            .get(dataset_id)
+            .map(|source| &source.reader)
+    }
+
+    /// 最近输出的数据包所属的数据集编号
+    pub fn last_dataset(&self) -> Option<usize> {
+        self.last_dataset
+    }
+
+    /// 所有数据集的数据包总数
+    pub fn total_packets(&self) -> usize {
+        self.sources
+            .iter()
+            .filter_map(|source| {
+                source.reader.total_packets()
+            })
+            .sum()
+    }
+
+    /// 读取逻辑时间线上的下一个数据包
+    ///
+    /// 所有数据集都读完时返回 `Ok(None)`。
+    pub fn read_packet(
+        &mut self,
+    ) -> PcapResult<Option<ValidatedPacket>> {
+        let mut next: Option<(usize, u64)> = None;
+        for (dataset_id, source) in
+            self.sources.iter_mut().enumerate()
+        {
+            let Some(timestamp) = source.peek()? else {
+                continue;
+            };
+            if self.mode == CompositeMode::Concatenate {
+                next = Some((dataset_id, timestamp));
+                break;
+            }
+            // 时间戳相同时保留编号较小的数据集
+            if next.is_none_or(|(_, best)| timestamp < best)
+            {
+                next = Some((dataset_id, timestamp));
+            }
+        }
+
+        let Some((dataset_id, _)) = next else {
+            return Ok(None);
+        };
+        self.last_dataset = Some(dataset_id);
+        Ok(self.sources[dataset_id].pending.take())
+    }
+
+    /// 批量读取最多 `count` 个数据包
+    pub fn read_packets(
+        &mut self,
+        count: usize,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        let mut packets = Vec::with_capacity(count);
+        while packets.len() < count {
+            match self.read_packet()? {
+                Some(packet) => packets.push(packet),
+                None => break,
+            }
+        }
+        Ok(packets)
+    }
+
+    /// 跳转到不早于指定时间戳的第一个数据包，返回实际定位到的时间戳
+    ///
+    /// # 错误
+    /// 所有数据集都没有满足条件的数据包时返回 `PcapError::InvalidArgument`
+    pub fn seek_to_timestamp(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> PcapResult<u64> {
+        for source in &mut self.sources {
+            source.pending = None;
+            source.exhausted = source
+                .time_range
+                .is_none_or(|(_, end)| end < timestamp_ns);
+            if !source.exhausted {
+                source
+                    .reader
+                    .seek_to_timestamp(timestamp_ns)?;
+            }
+        }
+        self.last_dataset = None;
+
+        let mut actual = None;
+        for source in &mut self.sources {
+            if let Some(timestamp) = source.peek()? {
+                actual = Some(
+                    actual
+                        .map_or(timestamp, |best: u64| {
+                            best.min(timestamp)
+                        }),
+                );
+                if self.mode == CompositeMode::Concatenate {
+                    break;
+                }
+            }
+        }
+        actual.ok_or_else(|| {
+            PcapError::InvalidArgument(format!(
+                "时间戳 {timestamp_ns} 之后没有数据包"
+            ))
+        })
+    }
+
+    /// 所有数据集回到起始位置
+    pub fn reset(&mut self) -> PcapResult<()> {
+        for source in &mut self.sources {
+            source.reader.reset()?;
+            source.pending = None;
+            source.exhausted = false;
+        }
+        self.last_dataset = None;
+        Ok(())
+    }
+
+    /// 读取时间范围内的所有数据包
+    ///
+    /// 依次读取时按数据集顺序拼接，合并读取时按时间戳排序。
+    pub fn read_packets_by_time_range(
+        &mut self,
+        start_timestamp_ns: u64,
+        end_timestamp_ns: u64,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        let mut packets = Vec::new();
+        for source in &mut self.sources {
+            let overlaps = source.time_range.is_some_and(
+                |(start, end)| {
+                    start <= end_timestamp_ns
+                        && end >= start_timestamp_ns
+                },
+            );
+            if overlaps {
+                packets.extend(
+                    source
+                        .reader
+                        .read_packets_by_time_range(
+                            start_timestamp_ns,
+                            end_timestamp_ns,
+                        )?,
+                );
+            }
+        }
+        if self.mode == CompositeMode::Merge {
+            packets.sort_by_key(
+                ValidatedPacket::get_timestamp_ns,
+            );
+        }
+        Ok(packets)
+    }
+
+    /// 获取组合后的数据集信息
+    ///
+    /// 名称为各数据集名称以 `+` 连接，路径为第一个数据集的路径，
+    /// 文件数量、数据包数量和大小为各数据集之和。
+    pub fn get_dataset_info(
+        &mut self,
+    ) -> PcapResult<DatasetInfo> {
+        let mut combined: Option<DatasetInfo> = None;
+        for source in &mut self.sources {
+            let info = source.reader.get_dataset_info()?;
+            combined = Some(match combined {
+                None => info,
+                Some(mut total) => {
+                    total.file_count += info.file_count;
+                    total.total_packets +=
+                        info.total_packets;
+                    total.total_size += info.total_size;
+                    total.start_timestamp = min_option(
+                        total.start_timestamp,
+                        info.start_timestamp,
+                    );
+                    total.end_timestamp = total
+                        .end_timestamp
+                        .max(info.end_timestamp);
+                    total
+                }
+            });
+        }
+        let mut info = combined.ok_or_else(|| {
+            PcapError::InvalidState(
+                "组合读取没有数据集".to_string(),
+            )
+        })?;
+        info.name = self.name.clone();
+        Ok(info)
+    }
+}
+
+impl PacketSource for CompositeReader {
+    fn dataset_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_dataset_info(
+        &mut self,
+    ) -> PcapResult<DatasetInfo> {
+        CompositeReader::get_dataset_info(self)
+    }
+
+    fn read_packet(
+        &mut self,
+    ) -> PcapResult<Option<ValidatedPacket>> {
+        CompositeReader::read_packet(self)
+    }
+
+    fn seek_to_timestamp(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> PcapResult<u64> {
+        CompositeReader::seek_to_timestamp(
+            self,
+            timestamp_ns,
+        )
+    }
+
+    fn reset(&mut self) -> PcapResult<()> {
+        CompositeReader::reset(self)
+    }
+
+    fn read_packets_by_time_range(
+        &mut self,
+        start_timestamp_ns: u64,
+        end_timestamp_ns: u64,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        CompositeReader::read_packets_by_time_range(
+            self,
+            start_timestamp_ns,
+            end_timestamp_ns,
+        )
+    }
+}
+
+impl Iterator for CompositeReader {
+    type Item = PcapResult<ValidatedPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_packet().transpose()
+    }
+}
+
+fn min_option(
+    a: Option<u64>,
+    b: Option<u64>,
+) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
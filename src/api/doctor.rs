@@ -0,0 +1,463 @@
+//! 数据集健康检查模块
+//!
+//! `DatasetDoctor::check` 只读地检查数据集目录，生成结构化的检查报告：索引是否存在、
+//! 是否与数据文件一致，是否有孤立文件、缺失文件、零字节文件、无法读取的文件，
+//! 文件之间的时间范围是否重叠，文件内的时间戳是否单调不减。
+//! 报告可以序列化为 JSON，供记录设备的集中监控系统定期采集。
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::business::config::ReaderConfig;
+use crate::business::index::{IndexManager, PidxIndex};
+use crate::data::file_reader::PcapFileReader;
+use crate::foundation::error::PcapResult;
+use crate::foundation::paths;
+
+/// 问题严重程度，按从轻到重排序
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum Severity {
+    /// 提示，不影响读取
+    Info,
+    /// 警告，读取结果可能不符合预期或索引需要重建
+    Warning,
+    /// 错误，部分数据无法读取
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let text = match self {
+            Severity::Info => "提示",
+            Severity::Warning => "警告",
+            Severity::Error => "错误",
+        };
+        f.write_str(text)
+    }
+}
+
+/// 检查发现的问题类型
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum FindingKind {
+    /// 索引文件不存在
+    IndexMissing,
+    /// 索引文件无法解析
+    IndexCorrupted,
+    /// 索引记录的文件大小或数据包数量与数据文件不一致
+    IndexStale,
+    /// 数据文件不在索引中
+    OrphanFile,
+    /// 索引中的数据文件不存在
+    MissingFile,
+    /// 零字节的数据文件
+    EmptyFile,
+    /// 数据文件无法读取
+    UnreadableFile,
+    /// 两个数据文件的时间范围重叠
+    TimeRangeOverlap,
+    /// 文件内的时间戳出现回退
+    NonMonotonicTimestamps,
+}
+
+impl FindingKind {
+    /// 问题类型的默认严重程度
+    pub fn severity(&self) -> Severity {
+        match self {
+            FindingKind::IndexMissing => Severity::Info,
+            FindingKind::IndexStale
+            | FindingKind::OrphanFile
+            | FindingKind::EmptyFile
+            | FindingKind::TimeRangeOverlap
+            | FindingKind::NonMonotonicTimestamps => {
+                Severity::Warning
+            }
+            FindingKind::IndexCorrupted
+            | FindingKind::MissingFile
+            | FindingKind::UnreadableFile => {
+                Severity::Error
+            }
+        }
+    }
+}
+
+/// 检查报告中的一条问题
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub struct Finding {
+    /// 问题类型
+    pub kind: FindingKind,
+    /// 严重程度
+    pub severity: Severity,
+    /// 问题描述
+    pub message: String,
+    /// 相关文件路径
+    pub path: Option<PathBuf>,
+}
+
+/// 数据集检查报告
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub struct DoctorReport {
+    /// 数据集目录
+    pub dataset_path: PathBuf,
+    /// 使用的索引文件，不存在或无法解析时为 None
+    pub index_path: Option<PathBuf>,
+    /// 检查的数据文件数量
+    pub files_checked: usize,
+    /// 检查的数据包数量
+    pub packets_checked: u64,
+    /// 发现的问题，按严重程度从重到轻排列
+    pub findings: Vec<Finding>,
+}
+
+impl DoctorReport {
+    /// 是否没有警告和错误
+    pub fn is_healthy(&self) -> bool {
+        self.findings.iter().all(|finding| {
+            finding.severity < Severity::Warning
+        })
+    }
+
+    /// 最高的严重程度，没有问题时为 None
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.findings
+            .iter()
+            .map(|finding| finding.severity)
+            .max()
+    }
+
+    /// 指定严重程度的问题数量
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .count()
+    }
+
+    /// 指定类型的问题
+    pub fn findings_of(
+        &self,
+        kind: FindingKind,
+    ) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(move |finding| finding.kind == kind)
+    }
+
+    /// 序列化为 JSON
+    pub fn to_json(&self) -> PcapResult<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    fn push(
+        &mut self,
+        kind: FindingKind,
+        message: String,
+        path: Option<PathBuf>,
+    ) {
+        self.findings.push(Finding {
+            kind,
+            severity: kind.severity(),
+            message,
+            path,
+        });
+    }
+}
+
+/// 单个数据文件的扫描结果
+struct FileScan {
+    path: PathBuf,
+    file_size: u64,
+    packet_count: u64,
+    /// 时间范围，没有数据包时为 None
+    time_range: Option<(u64, u64)>,
+}
+
+/// 数据集健康检查工具
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DatasetDoctor;
+
+impl DatasetDoctor {
+    /// 检查数据集
+    ///
+    /// 逐个读取数据文件的全部数据包，不修改数据集目录和索引。
+    /// 数据集的存储分片一并检查。
+    ///
+    /// # 错误
+    /// 数据集目录不存在时返回 `PcapError::DirectoryNotFound`；
+    /// 目录无法列出时返回 `PcapError::Io`
+    pub fn check<P: AsRef<Path>>(
+        base_path: P,
+        dataset_name: &str,
+    ) -> PcapResult<DoctorReport> {
+        let dataset_path = paths::dataset_path(
+            base_path.as_ref(),
+            dataset_name,
+        )?;
+        let manager =
+            IndexManager::new(base_path, dataset_name)?;
+        let mut report = DoctorReport {
+            dataset_path,
+            index_path: None,
+            files_checked: 0,
+            packets_checked: 0,
+            findings: Vec::new(),
+        };
+
+        let files = manager.scan_pcap_files()?;
+        let mut scans = Vec::new();
+        for path in &files {
+            report.files_checked += 1;
+            if let Some(scan) = scan_file(path, &mut report)
+            {
+                report.packets_checked += scan.packet_count;
+                scans.push(scan);
+            }
+        }
+
+        match manager.read_stored_index() {
+            Ok(Some((index_path, index))) => {
+                check_index(
+                    &index,
+                    &files,
+                    &scans,
+                    &mut report,
+                );
+                report.index_path = Some(index_path);
+            }
+            Ok(None) => report.push(
+                FindingKind::IndexMissing,
+                "索引文件不存在，首次读取时将自动生成"
+                    .to_string(),
+                None,
+            ),
+            Err(error) => report.push(
+                FindingKind::IndexCorrupted,
+                format!("索引文件无法解析: {error}"),
+                Some(manager.index_file_path()),
+            ),
+        }
+
+        check_overlaps(&scans, &mut report);
+        report.findings.sort_by_key(|finding| {
+            std::cmp::Reverse(finding.severity)
+        });
+        debug!(
+            "数据集检查完成: {:?}，发现 {} 个问题",
+            report.dataset_path,
+            report.findings.len()
+        );
+        Ok(report)
+    }
+}
+
+/// 读取数据文件的全部数据包，记录时间范围和时间戳回退
+///
+/// 文件为零字节或无法读取时记录问题并返回 None。
+fn scan_file(
+    path: &Path,
+    report: &mut DoctorReport,
+) -> Option<FileScan> {
+    let file_size = match fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(error) => {
+            report.push(
+                FindingKind::UnreadableFile,
+                format!("无法读取文件信息: {error}"),
+                Some(path.to_path_buf()),
+            );
+            return None;
+        }
+    };
+    if file_size == 0 {
+        report.push(
+            FindingKind::EmptyFile,
+            "数据文件为零字节".to_string(),
+            Some(path.to_path_buf()),
+        );
+        return None;
+    }
+
+    let mut reader =
+        PcapFileReader::new(ReaderConfig::default());
+    let mut scan = FileScan {
+        path: path.to_path_buf(),
+        file_size,
+        packet_count: 0,
+        time_range: None,
+    };
+    let mut previous = None;
+    let mut regressions = 0u64;
+    let mut first_regression = None;
+    let result = reader.open(path).and_then(|_| loop {
+        let Some(packet) = reader.read_packet()? else {
+            break Ok(());
+        };
+        let timestamp_ns = packet.get_timestamp_ns();
+        if previous.is_some_and(|prev| timestamp_ns < prev)
+        {
+            regressions += 1;
+            first_regression
+                .get_or_insert(scan.packet_count);
+        }
+        previous = Some(timestamp_ns);
+        scan.time_range = Some(match scan.time_range {
+            Some((start, end)) => (
+                start.min(timestamp_ns),
+                end.max(timestamp_ns),
+            ),
+            None => (timestamp_ns, timestamp_ns),
+        });
+        scan.packet_count += 1;
+    });
+    if let Err(error) = result {
+        report.push(
+            FindingKind::UnreadableFile,
+            format!(
+                "读取第 {} 个数据包失败: {error}",
+                scan.packet_count
+            ),
+            Some(path.to_path_buf()),
+        );
+        return None;
+    }
+    if let Some(position) = first_regression {
+        report.push(
+            FindingKind::NonMonotonicTimestamps,
+            format!(
+                "时间戳回退 {regressions} 次，首次位于第 {position} 个数据包"
+            ),
+            Some(path.to_path_buf()),
+        );
+    }
+    Some(scan)
+}
+
+/// 核对索引记录与数据文件
+fn check_index(
+    index: &PidxIndex,
+    files: &[PathBuf],
+    scans: &[FileScan],
+    report: &mut DoctorReport,
+) {
+    let scanned: HashMap<&Path, &FileScan> = scans
+        .iter()
+        .map(|scan| (scan.path.as_path(), scan))
+        .collect();
+    let dataset_path = report.dataset_path.clone();
+    let mut indexed = Vec::new();
+    for file in index.files() {
+        let path = file.resolve_path(&dataset_path);
+        if !path.is_file() {
+            report.push(
+                FindingKind::MissingFile,
+                format!(
+                    "索引中的数据文件不存在: {}",
+                    file.file_name
+                ),
+                Some(path),
+            );
+            continue;
+        }
+        if let Some(scan) = scanned.get(path.as_path()) {
+            if scan.file_size != file.file_size
+                || scan.packet_count != file.packet_count
+            {
+                report.push(
+                    FindingKind::IndexStale,
+                    format!(
+                        "索引记录 {} 字节、{} 个数据包，实际 {} 字节、{} 个数据包",
+                        file.file_size,
+                        file.packet_count,
+                        scan.file_size,
+                        scan.packet_count
+                    ),
+                    Some(path.clone()),
+                );
+            }
+        }
+        indexed.push(path);
+    }
+
+    for path in files {
+        if !indexed.contains(path) {
+            report.push(
+                FindingKind::OrphanFile,
+                "数据文件不在索引中".to_string(),
+                Some(path.clone()),
+            );
+        }
+    }
+}
+
+/// 检查数据文件之间的时间范围重叠
+///
+/// 相邻文件只在边界时间戳相同时不视为重叠。
+fn check_overlaps(
+    scans: &[FileScan],
+    report: &mut DoctorReport,
+) {
+    let mut ranges: Vec<(u64, u64, &Path)> = scans
+        .iter()
+        .filter_map(|scan| {
+            scan.time_range.map(|(start, end)| {
+                (start, end, scan.path.as_path())
+            })
+        })
+        .collect();
+    ranges.sort_unstable();
+    let mut latest: Option<(u64, &Path)> = None;
+    for (start, end, path) in ranges {
+        if let Some((latest_end, latest_path)) = latest {
+            if start < latest_end {
+                report.push(
+                    FindingKind::TimeRangeOverlap,
+                    format!(
+                        "时间范围 [{start}, {end}] 与 {} 的结束时间 {latest_end} 重叠",
+                        file_name(latest_path)
+                    ),
+                    Some(path.to_path_buf()),
+                );
+            }
+            if end <= latest_end {
+                continue;
+            }
+        }
+        latest = Some((end, path));
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,231 @@
+//! 导出模块
+//!
+//! 导出和格式转换共用的流式核心：从数据包来源逐包读取并交给一个或多个输出端
+//! （数据集写入器、标准 libpcap 文件、网络输出端或闭包）。输出端阻塞时读取随之暂停，
+//! 由此向来源施加背压；每个输出端的错误单独记录，按错误策略决定中止导出、
+//! 摘除出错的输出端还是继续写入。
+
+use log::{debug, info, warn};
+use std::time::{Duration, Instant};
+
+use crate::api::sink::PacketSink;
+use crate::api::source::PacketSource;
+use crate::business::rate_limit::RateLimiter;
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 输出端出错时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportErrorPolicy {
+    /// 立即中止导出并返回该错误
+    #[default]
+    Abort,
+    /// 记录错误并停止向该输出端写入，其余输出端继续
+    DetachSink,
+    /// 记录错误后继续向该输出端写入后续数据包
+    Continue,
+}
+
+/// 导出选项
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// 跳过校验失败的数据包，默认原样导出
+    pub skip_invalid: bool,
+    /// 输出端出错时的处理方式
+    pub error_policy: ExportErrorPolicy,
+    /// 每导出多少个数据包刷新一次输出端，0 表示只在结束时刷新
+    pub flush_interval: u64,
+    /// 最多导出的数据包数量，为空时导出到来源末尾
+    pub max_packets: Option<u64>,
+    /// 输出速率限制，默认不限制
+    pub rate_limiter: RateLimiter,
+}
+
+/// 单个输出端的导出结果
+#[derive(Debug, Default)]
+pub struct SinkReport {
+    /// 成功写入的数据包数量
+    pub packets_written: u64,
+    /// 成功写入的负载字节数
+    pub bytes_written: u64,
+    /// 写入和刷新时发生的错误，按发生顺序排列
+    pub errors: Vec<PcapError>,
+    /// 是否因出错被摘除
+    pub detached: bool,
+}
+
+impl SinkReport {
+    /// 是否没有发生错误
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// 导出结果统计
+#[derive(Debug, Default)]
+pub struct ExportSummary {
+    /// 从来源读取的数据包数量
+    pub packets_read: u64,
+    /// 交给输出端的数据包数量
+    pub packets_exported: u64,
+    /// 因校验失败被跳过的数据包数量
+    pub skipped_invalid: u64,
+    /// 各输出端的结果，顺序与传入的输出端一致
+    pub sinks: Vec<SinkReport>,
+    /// 因速率上限等待的总时长
+    pub throttled: Duration,
+    /// 导出耗时
+    pub elapsed: Duration,
+}
+
+impl ExportSummary {
+    /// 是否所有输出端都没有发生错误
+    pub fn is_clean(&self) -> bool {
+        self.sinks.iter().all(SinkReport::is_clean)
+    }
+
+    /// 所有输出端的错误总数
+    pub fn error_count(&self) -> usize {
+        self.sinks.iter().map(|s| s.errors.len()).sum()
+    }
+}
+
+/// 把来源中的数据包导出到一个输出端
+///
+/// 使用默认导出选项：从来源当前位置读到末尾，输出端出错时立即中止，结束后刷新输出端。
+pub fn pipe<S, K>(
+    source: &mut S,
+    sink: &mut K,
+) -> PcapResult<ExportSummary>
+where
+    S: PacketSource + ?Sized,
+    K: PacketSink,
+{
+    pipe_with(
+        source,
+        &mut [sink],
+        &ExportOptions::default(),
+    )
+}
+
+/// 按指定选项把来源中的数据包导出到多个输出端
+///
+/// 从来源当前位置开始读取，每个数据包依次写入各个输出端，全部写完后才读取下一个；
+/// 结束时刷新所有未被摘除的输出端。所有输出端都被摘除后提前结束。
+///
+/// # 错误
+/// 读取来源失败时返回对应错误；错误策略为 `Abort` 时返回输出端的第一个错误
+pub fn pipe_with<S>(
+    source: &mut S,
+    sinks: &mut [&mut dyn PacketSink],
+    options: &ExportOptions,
+) -> PcapResult<ExportSummary>
+where
+    S: PacketSource + ?Sized,
+{
+    let started = Instant::now();
+    let mut summary = ExportSummary {
+        sinks: sinks
+            .iter()
+            .map(|_| SinkReport::default())
+            .collect(),
+        ..ExportSummary::default()
+    };
+    let mut limiter = options.rate_limiter.clone();
+    limiter.reset();
+
+    while options
+        .max_packets
+        .is_none_or(|max| summary.packets_exported < max)
+        && summary.sinks.iter().any(|s| !s.detached)
+    {
+        let Some(packet) = source.read_packet()? else {
+            break;
+        };
+        summary.packets_read += 1;
+        if !packet.is_valid && options.skip_invalid {
+            summary.skipped_invalid += 1;
+            continue;
+        }
+
+        let bytes = packet.packet.data.len() as u64;
+        summary.throttled += limiter.acquire(bytes);
+        for (sink, report) in
+            sinks.iter_mut().zip(&mut summary.sinks)
+        {
+            if report.detached {
+                continue;
+            }
+            match sink.write(&packet) {
+                Ok(()) => {
+                    report.packets_written += 1;
+                    report.bytes_written += bytes;
+                }
+                Err(e) => record_error(
+                    options.error_policy,
+                    report,
+                    e,
+                )?,
+            }
+        }
+        summary.packets_exported += 1;
+
+        if options.flush_interval > 0
+            && summary
+                .packets_exported
+                .is_multiple_of(options.flush_interval)
+        {
+            flush_sinks(
+                sinks,
+                &mut summary.sinks,
+                options,
+            )?;
+        }
+    }
+    flush_sinks(sinks, &mut summary.sinks, options)?;
+
+    summary.elapsed = started.elapsed();
+    info!(
+        "导出完成: {} 个数据包, {} 个输出端, {} 个错误",
+        summary.packets_exported,
+        summary.sinks.len(),
+        summary.error_count()
+    );
+    Ok(summary)
+}
+
+/// 刷新所有未被摘除的输出端
+fn flush_sinks(
+    sinks: &mut [&mut dyn PacketSink],
+    reports: &mut [SinkReport],
+    options: &ExportOptions,
+) -> PcapResult<()> {
+    for (sink, report) in sinks.iter_mut().zip(reports) {
+        if report.detached {
+            continue;
+        }
+        if let Err(e) = sink.flush() {
+            record_error(options.error_policy, report, e)?;
+        }
+    }
+    Ok(())
+}
+
+/// 按错误策略处理输出端错误，策略为 `Abort` 时返回该错误
+fn record_error(
+    policy: ExportErrorPolicy,
+    report: &mut SinkReport,
+    error: PcapError,
+) -> PcapResult<()> {
+    match policy {
+        ExportErrorPolicy::Abort => return Err(error),
+        ExportErrorPolicy::DetachSink => {
+            warn!("输出端出错，已摘除: {error}");
+            report.detached = true;
+        }
+        ExportErrorPolicy::Continue => {
+            debug!("输出端出错，继续写入: {error}");
+        }
+    }
+    report.errors.push(error);
+    Ok(())
+}
@@ -0,0 +1,107 @@
+//! 通道写入模块
+//!
+//! 在独立线程中运行写入器，生产者通过有界通道提交数据包。通道满时发送方阻塞，
+//! 由此把磁盘写入速度反馈给上游，高速采集程序不需要自行搭建生产者/消费者结构。
+
+use log::{debug, info};
+use std::sync::mpsc::{
+    self, Receiver, RecvTimeoutError, SyncSender,
+};
+use std::thread::{self, JoinHandle};
+
+use crate::api::writer::PcapWriter;
+use crate::data::models::{DataPacket, DatasetInfo};
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 写入线程的数据包发送端
+///
+/// 可以克隆后分发给多个生产者线程；所有发送端释放后写入线程完成写入并退出。
+#[derive(Debug, Clone)]
+pub struct PacketSender {
+    sender: SyncSender<DataPacket>,
+}
+
+impl PacketSender {
+    /// 提交一个数据包，通道已满时阻塞等待
+    ///
+    /// # 错误
+    /// 写入线程因写入失败已经退出时返回 `PcapError::InvalidState`，
+    /// 具体原因通过写入线程的 `JoinHandle` 获取
+    pub fn send(
+        &self,
+        packet: DataPacket,
+    ) -> PcapResult<()> {
+        self.sender.send(packet).map_err(|_| {
+            PcapError::InvalidState(
+                "写入线程已停止".to_string(),
+            )
+        })
+    }
+
+    /// 批量提交数据包
+    pub fn send_all<I>(&self, packets: I) -> PcapResult<()>
+    where
+        I: IntoIterator<Item = DataPacket>,
+    {
+        for packet in packets {
+            self.send(packet)?;
+        }
+        Ok(())
+    }
+}
+
+/// 启动写入线程
+pub(crate) fn spawn(
+    writer: PcapWriter,
+    capacity: usize,
+) -> PcapResult<(
+    PacketSender,
+    JoinHandle<PcapResult<DatasetInfo>>,
+)> {
+    let (sender, receiver) = mpsc::sync_channel(capacity);
+    let handle = thread::Builder::new()
+        .name(format!(
+            "pcap-ingest-{}",
+            writer.dataset_name()
+        ))
+        .spawn(move || run(writer, receiver))
+        .map_err(PcapError::Io)?;
+    Ok((PacketSender { sender }, handle))
+}
+
+fn run(
+    mut writer: PcapWriter,
+    receiver: Receiver<DataPacket>,
+) -> PcapResult<DatasetInfo> {
+    debug!("写入线程已启动: {}", writer.dataset_name());
+    // 写入失败时直接返回，接收端随之释放，生产者的下一次发送会失败
+    let heartbeat_interval = writer.heartbeat_interval();
+    if heartbeat_interval.is_zero() {
+        for packet in receiver {
+            writer.write_packet(&packet)?;
+        }
+    } else {
+        // 等待超时说明通道空闲，按心跳间隔写入心跳包
+        loop {
+            match receiver.recv_timeout(heartbeat_interval)
+            {
+                Ok(packet) => {
+                    writer.write_packet(&packet)?
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    break
+                }
+            }
+            writer.write_heartbeat_if_idle()?;
+        }
+    }
+
+    writer.finalize()?;
+    let info = writer.get_dataset_info();
+    info!(
+        "写入线程已完成: {}，共 {} 个数据包",
+        info.name, info.total_packets
+    );
+    Ok(info)
+}
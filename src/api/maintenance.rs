@@ -0,0 +1,706 @@
+//! 数据集维护模块
+//!
+//! 提供重命名、移动、删除、截断等数据集目录级别的维护操作。直接 `mv` 数据集目录时，
+//! 索引描述和分片清单中仍记录着旧的名称和路径；这里的操作在移动目录后
+//! 一并改写这些字段，并核对数据文件与索引记录一致。
+//!
+//! 删除默认移入基础目录下的回收站（`.trash`），保留期内可以恢复，
+//! 超过保留期后由 `purge_trash` 彻底删除。
+
+use log::{debug, info};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::business::index::{
+    IndexManager, TruncationReport,
+};
+use crate::business::lock::{DatasetLock, LOCK_FILE_NAME};
+use crate::business::shards::ShardManifest;
+use crate::foundation::error::{PcapError, PcapResult};
+use crate::foundation::paths;
+
+/// 数据集迁移结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelocationReport {
+    /// 原数据集目录
+    pub old_path: PathBuf,
+    /// 新数据集目录
+    pub new_path: PathBuf,
+    /// 随数据集一起移动的分片目录数量
+    pub shards_moved: usize,
+    /// 是否因跨文件系统而通过复制后删除完成移动
+    pub copied: bool,
+    /// 是否改写了索引文件
+    pub index_rewritten: bool,
+    /// 是否因数据文件与索引不一致而重建了索引
+    pub index_rebuilt: bool,
+}
+
+/// 回收站目录名，位于基础目录和各分片根目录下
+pub const TRASH_DIR_NAME: &str = ".trash";
+
+/// 回收站默认保留期
+pub const DEFAULT_TRASH_RETENTION: Duration =
+    Duration::from_secs(7 * 24 * 60 * 60);
+
+/// 删除方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeletionMode {
+    /// 移入回收站，保留期内可以恢复
+    #[default]
+    Trash,
+    /// 覆写数据后彻底删除，无法恢复
+    Permanent,
+}
+
+/// 回收站中的数据集
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashEntry {
+    /// 数据集名称
+    pub name: String,
+    /// 回收站中的数据集目录
+    pub path: PathBuf,
+    /// 删除时间
+    pub deleted_at: SystemTime,
+}
+
+impl TrashEntry {
+    /// 删除后经过的时长，系统时钟回拨时为 0
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.deleted_at)
+            .unwrap_or_default()
+    }
+}
+
+/// 数据集删除结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletionReport {
+    /// 被删除的数据集目录
+    pub dataset_path: PathBuf,
+    /// 删除方式
+    pub mode: DeletionMode,
+    /// 移入回收站时的回收站条目
+    pub trash_entry: Option<TrashEntry>,
+    /// 随数据集一起处理的分片目录数量
+    pub shards: usize,
+    /// 彻底删除前覆写的文件数量
+    pub files_shredded: usize,
+    /// 彻底删除前覆写的字节数
+    pub bytes_shredded: u64,
+}
+
+impl DeletionReport {
+    fn new(
+        dataset_path: PathBuf,
+        mode: DeletionMode,
+    ) -> Self {
+        Self {
+            dataset_path,
+            mode,
+            trash_entry: None,
+            shards: 0,
+            files_shredded: 0,
+            bytes_shredded: 0,
+        }
+    }
+}
+
+/// 数据集维护工具
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DatasetMaintenance;
+
+impl DatasetMaintenance {
+    /// 重命名数据集
+    ///
+    /// 数据集目录改名后，同名的分片目录一并改名，分片清单和索引中的分片路径、
+    /// 索引描述中的数据集名称同步改写。
+    ///
+    /// # 错误
+    /// 新名称不合法时返回 `PcapError::InvalidDatasetName`；
+    /// 目标已存在时返回 `PcapError::InvalidArgument`；
+    /// 数据集正在写入时返回 `PcapError::DatasetLocked`
+    pub fn rename<P: AsRef<Path>>(
+        base_path: P,
+        old_name: &str,
+        new_name: &str,
+    ) -> PcapResult<RelocationReport> {
+        let base_path = base_path.as_ref();
+        Self::relocate(
+            base_path,
+            old_name,
+            base_path,
+            new_name,
+            &|directory| {
+                (directory.file_name()
+                    == Some(old_name.as_ref()))
+                .then(|| directory.with_file_name(new_name))
+            },
+        )
+    }
+
+    /// 把数据集移动到新的基础目录，名称保持不变
+    ///
+    /// 跨文件系统时先复制再删除原目录，复制后核对数据文件哈希。
+    /// 分片目录保留在原位置，分片清单不变。
+    ///
+    /// # 参数
+    /// - `dataset` - 数据集目录
+    /// - `new_base` - 新的基础目录，不存在时自动创建
+    pub fn move_to<P: AsRef<Path>, Q: AsRef<Path>>(
+        dataset: P,
+        new_base: Q,
+    ) -> PcapResult<RelocationReport> {
+        let (base_path, name) =
+            paths::split_dataset_path(dataset)?;
+        Self::relocate(
+            &base_path,
+            &name,
+            new_base.as_ref(),
+            &name,
+            &|_| None,
+        )
+    }
+
+    /// 删除数据集
+    ///
+    /// `DeletionMode::Trash` 把数据集移入 `<基础目录>/.trash/<删除时间>/<名称>`，
+    /// 分片目录移入各自分片根目录下的同名位置，可通过 `restore` 恢复。
+    /// `DeletionMode::Permanent` 先用零覆写数据集和分片目录中的所有文件再删除；
+    /// 固态硬盘和写时复制文件系统上覆写不保证抹除物理存储中的旧数据。
+    ///
+    /// # 错误
+    /// 数据集不存在时返回 `PcapError::DirectoryNotFound`；
+    /// 数据集正在写入时返回 `PcapError::DatasetLocked`
+    pub fn delete<P: AsRef<Path>>(
+        dataset: P,
+        mode: DeletionMode,
+    ) -> PcapResult<DeletionReport> {
+        let (base_path, name) =
+            paths::split_dataset_path(dataset)?;
+        let dataset_path =
+            paths::dataset_path(&base_path, &name)?;
+        match mode {
+            DeletionMode::Trash => {
+                let stamp = trash_stamp();
+                let trash_base = base_path
+                    .join(TRASH_DIR_NAME)
+                    .join(&stamp);
+                let relocation = Self::relocate(
+                    &base_path,
+                    &name,
+                    &trash_base,
+                    &name,
+                    &|directory| {
+                        directory
+                            .parent()
+                            .filter(|_| {
+                                directory.file_name()
+                                    == Some(name.as_ref())
+                            })
+                            .map(|root| {
+                                root.join(TRASH_DIR_NAME)
+                                    .join(&stamp)
+                                    .join(&name)
+                            })
+                    },
+                )?;
+                info!(
+                    "数据集已移入回收站: {:?}",
+                    relocation.new_path
+                );
+                let mut report =
+                    DeletionReport::new(dataset_path, mode);
+                report.shards = relocation.shards_moved;
+                report.trash_entry = Some(TrashEntry {
+                    name,
+                    path: relocation.new_path,
+                    deleted_at: parse_trash_stamp(&stamp)
+                        .unwrap_or_else(SystemTime::now),
+                });
+                Ok(report)
+            }
+            DeletionMode::Permanent => {
+                if !dataset_path.is_dir() {
+                    return Err(PcapError::DirectoryNotFound(
+                        format!("数据集目录不存在: {dataset_path:?}"),
+                    ));
+                }
+                let mut report = DeletionReport::new(
+                    dataset_path.clone(),
+                    mode,
+                );
+                Self::destroy(&dataset_path, &mut report)?;
+                info!("数据集已彻底删除: {dataset_path:?}");
+                Ok(report)
+            }
+        }
+    }
+
+    /// 列出基础目录回收站中的数据集，按删除时间从早到晚排列
+    pub fn list_trash<P: AsRef<Path>>(
+        base_path: P,
+    ) -> PcapResult<Vec<TrashEntry>> {
+        let trash_path =
+            base_path.as_ref().join(TRASH_DIR_NAME);
+        if !trash_path.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for stamp_entry in fs::read_dir(&trash_path)
+            .map_err(PcapError::Io)?
+        {
+            let stamp_dir =
+                stamp_entry.map_err(PcapError::Io)?.path();
+            let Some(deleted_at) = stamp_dir
+                .file_name()
+                .and_then(|stamp| stamp.to_str())
+                .and_then(parse_trash_stamp)
+            else {
+                continue;
+            };
+            if !stamp_dir.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&stamp_dir)
+                .map_err(PcapError::Io)?
+            {
+                let path =
+                    entry.map_err(PcapError::Io)?.path();
+                let Some(name) = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                else {
+                    continue;
+                };
+                if path.is_dir() {
+                    entries.push(TrashEntry {
+                        name: name.to_string(),
+                        path: path.clone(),
+                        deleted_at,
+                    });
+                }
+            }
+        }
+        entries.sort_by(|a, b| {
+            a.deleted_at
+                .cmp(&b.deleted_at)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(entries)
+    }
+
+    /// 把回收站中的数据集恢复到原基础目录
+    ///
+    /// 分片目录一并移回原位置。
+    ///
+    /// # 错误
+    /// 原位置已存在同名数据集时返回 `PcapError::InvalidArgument`
+    pub fn restore(
+        entry: &TrashEntry,
+    ) -> PcapResult<RelocationReport> {
+        let not_in_trash = || {
+            PcapError::InvalidArgument(format!(
+                "不是回收站中的数据集: {:?}",
+                entry.path
+            ))
+        };
+        let stamp_dir =
+            entry.path.parent().ok_or_else(not_in_trash)?;
+        let base_path = restored_shard_path(&entry.path)
+            .and_then(|path| {
+                path.parent().map(Path::to_path_buf)
+            })
+            .ok_or_else(not_in_trash)?;
+
+        // 分片目录移回后，回收站中对应的分组目录变空，一并删除
+        let shard_stamp_dirs: Vec<PathBuf> =
+            ShardManifest::load(&entry.path)
+                .directories
+                .iter()
+                .filter_map(|directory| {
+                    directory
+                        .parent()
+                        .map(Path::to_path_buf)
+                })
+                .collect();
+        let report = Self::relocate(
+            stamp_dir,
+            &entry.name,
+            &base_path,
+            &entry.name,
+            &restored_shard_path,
+        )?;
+        for stamp_dir in shard_stamp_dirs
+            .iter()
+            .map(PathBuf::as_path)
+            .chain([stamp_dir])
+        {
+            remove_empty_stamp_dir(stamp_dir);
+        }
+        info!(
+            "数据集已从回收站恢复: {:?}",
+            report.new_path
+        );
+        Ok(report)
+    }
+
+    /// 彻底删除回收站中超过保留期的数据集，返回被删除的条目
+    pub fn purge_trash<P: AsRef<Path>>(
+        base_path: P,
+        retention: Duration,
+    ) -> PcapResult<Vec<TrashEntry>> {
+        let mut purged = Vec::new();
+        for entry in Self::list_trash(base_path)? {
+            if entry.age() < retention {
+                continue;
+            }
+            let mut report = DeletionReport::new(
+                entry.path.clone(),
+                DeletionMode::Permanent,
+            );
+            Self::destroy(&entry.path, &mut report)?;
+            if let Some(stamp_dir) = entry.path.parent() {
+                remove_empty_stamp_dir(stamp_dir);
+            }
+            debug!("已清除回收站条目: {:?}", entry.path);
+            purged.push(entry);
+        }
+        if !purged.is_empty() {
+            info!(
+                "已清除回收站中 {} 个数据集",
+                purged.len()
+            );
+        }
+        Ok(purged)
+    }
+
+    /// 丢弃数据集中时间戳晚于 `timestamp_ns` 的全部数据
+    ///
+    /// 用于在发现故障后清除之后记录的错误数据：起始时间晚于截断时间戳的数据文件被删除，
+    /// 跨越截断时间戳的文件在数据包边界处截断，索引随之更新。文件按偏移顺序截断，
+    /// 第一个晚于截断时间戳的数据包之后乱序写入的较早数据包一并丢弃。
+    /// 书签、事件标记和 libpcap 副本保持不变。
+    ///
+    /// # 错误
+    /// 数据集不存在时返回 `PcapError::DirectoryNotFound`；
+    /// 数据集正在写入时返回 `PcapError::DatasetLocked`
+    pub fn truncate_after<P: AsRef<Path>>(
+        dataset: P,
+        timestamp_ns: u64,
+    ) -> PcapResult<TruncationReport> {
+        let (base_path, name) =
+            paths::split_dataset_path(dataset)?;
+        let dataset_path =
+            paths::dataset_path(&base_path, &name)?;
+        if !dataset_path.is_dir() {
+            return Err(PcapError::DirectoryNotFound(
+                format!(
+                    "数据集目录不存在: {dataset_path:?}"
+                ),
+            ));
+        }
+        // 截断期间持有锁，避免写入器同时追加数据
+        let _lock = DatasetLock::acquire(&dataset_path)?;
+        let mut manager =
+            IndexManager::new(&base_path, &name)?;
+        manager.truncate_after(timestamp_ns)
+    }
+
+    /// 覆写并删除数据集目录及其分片目录
+    fn destroy(
+        dataset_path: &Path,
+        report: &mut DeletionReport,
+    ) -> PcapResult<()> {
+        // 删除期间持有锁，避免写入器同时写入
+        let lock = DatasetLock::acquire(dataset_path)?;
+
+        let shards: Vec<PathBuf> =
+            ShardManifest::load(dataset_path)
+                .directories
+                .into_iter()
+                .filter(|directory| {
+                    directory != dataset_path
+                })
+                .collect();
+        for directory in &shards {
+            if !directory.is_dir() {
+                continue;
+            }
+            shred_directory(directory, report)?;
+            fs::remove_dir_all(directory)
+                .map_err(PcapError::Io)?;
+            report.shards += 1;
+            if let Some(stamp_dir) = directory.parent() {
+                remove_empty_stamp_dir(stamp_dir);
+            }
+        }
+        shred_directory(dataset_path, report)?;
+        remove_locked_dataset(lock, dataset_path)
+    }
+
+    /// 移动数据集目录并改写与路径相关的字段
+    ///
+    /// `shard_target` 给出分片目录的新位置，返回 `None` 的分片目录保持不动。
+    fn relocate(
+        src_base: &Path,
+        src_name: &str,
+        dst_base: &Path,
+        dst_name: &str,
+        shard_target: &dyn Fn(&Path) -> Option<PathBuf>,
+    ) -> PcapResult<RelocationReport> {
+        let src_path =
+            paths::dataset_path(src_base, src_name)?;
+        let dst_path =
+            paths::dataset_path(dst_base, dst_name)?;
+        if !src_path.is_dir() {
+            return Err(PcapError::DirectoryNotFound(
+                format!("数据集目录不存在: {src_path:?}"),
+            ));
+        }
+        if src_path == dst_path {
+            return Err(PcapError::InvalidArgument(
+                "目标数据集不能与源数据集相同".to_string(),
+            ));
+        }
+        if dst_path.exists() {
+            return Err(PcapError::InvalidArgument(
+                format!("目标数据集已存在: {dst_path:?}"),
+            ));
+        }
+        // 迁移期间持有锁，避免写入器同时写入
+        let lock = DatasetLock::acquire(&src_path)?;
+
+        let mut report = RelocationReport {
+            old_path: src_path.clone(),
+            new_path: dst_path.clone(),
+            ..RelocationReport::default()
+        };
+        let (copied, _lock) = move_locked_dataset(
+            lock, &src_path, &dst_path,
+        )?;
+        report.copied = copied;
+
+        let mut manifest = ShardManifest::load(&dst_path);
+        let mut shard_moves = Vec::new();
+        for directory in &mut manifest.directories {
+            let target = if *directory == src_path {
+                dst_path.clone()
+            } else if let Some(target) =
+                shard_target(directory)
+            {
+                if directory.is_dir() {
+                    move_directory(directory, &target)?;
+                    report.shards_moved += 1;
+                }
+                target
+            } else {
+                continue;
+            };
+            shard_moves
+                .push((directory.clone(), target.clone()));
+            *directory = target;
+        }
+        if !shard_moves.is_empty() {
+            manifest.save(&dst_path)?;
+        }
+
+        let mut manager =
+            IndexManager::new(dst_base, dst_name)?;
+        if let Some(rebuilt) = manager
+            .rewrite_relocated_index(
+                src_name,
+                &shard_moves,
+                report.copied,
+            )?
+        {
+            report.index_rewritten = !rebuilt;
+            report.index_rebuilt = rebuilt;
+        }
+
+        info!(
+            "数据集已迁移: {src_path:?} -> {dst_path:?}, 分片 {}",
+            report.shards_moved
+        );
+        Ok(report)
+    }
+}
+
+/// 在持有数据集锁的情况下删除数据集目录
+///
+/// 锁文件位于数据集目录中，Windows 上打开的锁文件会阻止删除目录，
+/// 锁在删除前一刻释放；其他平台持有锁直到删除完成。
+fn remove_locked_dataset(
+    lock: DatasetLock,
+    dataset_path: &Path,
+) -> PcapResult<()> {
+    #[cfg(windows)]
+    drop(lock);
+    fs::remove_dir_all(dataset_path)
+        .map_err(PcapError::Io)?;
+    #[cfg(not(windows))]
+    drop(lock);
+    Ok(())
+}
+
+/// 在持有数据集锁的情况下移动数据集目录，返回是否通过复制完成以及移动后的锁
+///
+/// 锁文件位于数据集目录中，改名时锁随目录一起移动。Windows 上打开的锁文件会阻止改名，
+/// 锁在移动前一刻释放；复制完成或在 Windows 上移动后，在新位置重新获取锁，
+/// 供调用方在改写分片清单和索引期间持有。
+fn move_locked_dataset(
+    lock: DatasetLock,
+    src: &Path,
+    dst: &Path,
+) -> PcapResult<(bool, DatasetLock)> {
+    #[cfg(windows)]
+    {
+        drop(lock);
+        let copied = move_directory(src, dst)?;
+        Ok((copied, DatasetLock::acquire(dst)?))
+    }
+    #[cfg(not(windows))]
+    {
+        if move_directory(src, dst)? {
+            Ok((true, DatasetLock::acquire(dst)?))
+        } else {
+            Ok((false, lock))
+        }
+    }
+}
+
+/// 移动目录，跨文件系统时复制后删除原目录
+///
+/// 目标的上级目录不存在时自动创建。返回是否通过复制完成。
+/// 复制失败时删除已复制的部分，原目录保持不变。
+fn move_directory(
+    src: &Path,
+    dst: &Path,
+) -> PcapResult<bool> {
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)
+            .map_err(PcapError::Io)?;
+    }
+    if fs::rename(src, dst).is_ok() {
+        return Ok(false);
+    }
+    debug!("无法直接改名，改为复制: {src:?} -> {dst:?}");
+    if let Err(e) = copy_directory(src, dst) {
+        let _ = fs::remove_dir_all(dst);
+        return Err(e);
+    }
+    fs::remove_dir_all(src).map_err(PcapError::Io)?;
+    Ok(true)
+}
+
+/// 回收站分片目录恢复后的位置：`<根目录>/.trash/<删除时间>/<名称>` 恢复为 `<根目录>/<名称>`
+fn restored_shard_path(
+    directory: &Path,
+) -> Option<PathBuf> {
+    let trash = directory.parent()?.parent()?;
+    if trash.file_name() != Some(TRASH_DIR_NAME.as_ref()) {
+        return None;
+    }
+    Some(trash.parent()?.join(directory.file_name()?))
+}
+
+/// 回收站中按删除时间分组的目录名：删除时的 Unix 毫秒时间戳
+fn trash_stamp() -> String {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+        .to_string()
+}
+
+/// 解析回收站分组目录名，不是时间戳时返回 `None`
+fn parse_trash_stamp(stamp: &str) -> Option<SystemTime> {
+    let millis = stamp.parse::<u64>().ok()?;
+    UNIX_EPOCH.checked_add(Duration::from_millis(millis))
+}
+
+/// 删除已空的回收站分组目录，以及随之变空的回收站目录
+///
+/// 不在回收站中的目录保持不动。
+fn remove_empty_stamp_dir(stamp_dir: &Path) {
+    let Some(trash) = stamp_dir.parent().filter(|trash| {
+        trash.file_name() == Some(TRASH_DIR_NAME.as_ref())
+    }) else {
+        return;
+    };
+    if fs::remove_dir(stamp_dir).is_ok() {
+        let _ = fs::remove_dir(trash);
+    }
+}
+
+/// 用零覆写目录中的所有文件并同步到磁盘
+fn shred_directory(
+    directory: &Path,
+    report: &mut DeletionReport,
+) -> PcapResult<()> {
+    for entry in
+        fs::read_dir(directory).map_err(PcapError::Io)?
+    {
+        let entry = entry.map_err(PcapError::Io)?;
+        let file_type =
+            entry.file_type().map_err(PcapError::Io)?;
+        if file_type.is_dir() {
+            shred_directory(&entry.path(), report)?;
+            continue;
+        }
+        // 锁文件只记录持有者信息，且正由本次删除持有
+        if !file_type.is_file()
+            || entry.file_name() == LOCK_FILE_NAME
+        {
+            continue;
+        }
+        report.bytes_shredded += zero_fill(&entry.path())?;
+        report.files_shredded += 1;
+    }
+    Ok(())
+}
+
+/// 用零覆写文件的全部内容并同步到磁盘，返回覆写的字节数
+fn zero_fill(path: &Path) -> PcapResult<u64> {
+    let zeros = vec![0u8; 64 * 1024];
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(PcapError::Io)?;
+    let length =
+        file.metadata().map_err(PcapError::Io)?.len();
+    let mut remaining = length;
+    while remaining > 0 {
+        let step = remaining.min(zeros.len() as u64);
+        file.write_all(&zeros[..step as usize])
+            .map_err(PcapError::Io)?;
+        remaining -= step;
+    }
+    file.sync_all().map_err(PcapError::Io)?;
+    Ok(length)
+}
+
+/// 递归复制目录
+fn copy_directory(
+    src: &Path,
+    dst: &Path,
+) -> PcapResult<()> {
+    fs::create_dir_all(dst).map_err(PcapError::Io)?;
+    for entry in fs::read_dir(src).map_err(PcapError::Io)? {
+        let entry = entry.map_err(PcapError::Io)?;
+        let target = dst.join(entry.file_name());
+        if entry
+            .file_type()
+            .map_err(PcapError::Io)?
+            .is_dir()
+        {
+            copy_directory(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)
+                .map_err(PcapError::Io)?;
+        }
+    }
+    Ok(())
+}
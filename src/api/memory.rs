@@ -0,0 +1,364 @@
+//! 内存读取模块
+//!
+//! 从内存中的字节流解析单个PCAP文件，不访问文件系统。字节流可以一次性给出，
+//! 也可以分块追加（如浏览器中按块读取的 `File`），适用于 WASM 等没有文件系统的环境。
+
+use log::{debug, warn};
+
+use crate::core::{
+    self as codec, ByteOrder, FileHeader, PacketHeader,
+};
+use crate::data::models::{
+    DataPacket, DataPacketHeader, HeaderExtension,
+    LinkType, PcapFileHeader, RecordFormat,
+    ValidatedPacket, ValidationStatus,
+};
+use crate::data::{checked_header, delta_header};
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 内存字节流读取器
+///
+/// `read_packet` 返回 `Ok(None)` 表示当前缓冲的字节不足以组成下一个数据包：
+/// 尚未调用 `finish` 时应继续追加数据块，调用后表示读取结束。
+#[derive(Debug, Default)]
+pub struct MemoryReader {
+    buffer: Vec<u8>,
+    /// 缓冲区中已解析的字节数
+    consumed: usize,
+    /// 已解析的字节数在整个文件中的偏移
+    position: u64,
+    header: Option<PcapFileHeader>,
+    /// 数据包记录格式，由文件头决定
+    record_format: RecordFormat,
+    /// 上一个数据包的时间戳，差值编码以此为基准
+    previous_timestamp_ns: u64,
+    packet_count: u64,
+    finished: bool,
+}
+
+impl MemoryReader {
+    /// 创建分块读取器，数据通过 `push_chunk` 追加
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从完整的文件内容创建读取器，并立即校验文件头
+    pub fn from_bytes<B: Into<Vec<u8>>>(
+        bytes: B,
+    ) -> PcapResult<Self> {
+        let mut reader = Self {
+            buffer: bytes.into(),
+            finished: true,
+            ..Self::default()
+        };
+        reader.ensure_header()?;
+        Ok(reader)
+    }
+
+    /// 追加一个数据块
+    pub fn push_chunk(
+        &mut self,
+        chunk: &[u8],
+    ) -> PcapResult<()> {
+        if self.finished {
+            return Err(PcapError::InvalidState(
+                "字节流已结束，不能继续追加数据"
+                    .to_string(),
+            ));
+        }
+        self.compact();
+        self.buffer.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    /// 标记字节流结束，之后不足一个数据包的剩余字节按截断处理
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// 字节流是否已结束
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// 文件头，尚未收到完整文件头时返回 `None`
+    pub fn header(&self) -> Option<&PcapFileHeader> {
+        self.header.as_ref()
+    }
+
+    /// 文件的字节序，文件头尚未解析时为小端序
+    pub fn byte_order(&self) -> ByteOrder {
+        self.header
+            .as_ref()
+            .map(|header| header.byte_order)
+            .unwrap_or_default()
+    }
+
+    /// 文件头中记录的链路类型
+    pub fn link_type(&self) -> LinkType {
+        self.header
+            .as_ref()
+            .map(|header| header.link_type)
+            .unwrap_or_default()
+    }
+
+    /// 已读取的数据包数量
+    pub fn packet_count(&self) -> u64 {
+        self.packet_count
+    }
+
+    /// 下一个数据包在文件中的字节偏移
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// 已缓冲但尚未解析的字节数
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffer.len() - self.consumed
+    }
+
+    /// 读取下一个数据包
+    pub fn read_packet(
+        &mut self,
+    ) -> PcapResult<Option<ValidatedPacket>> {
+        if !self.ensure_header()? {
+            return Ok(None);
+        }
+
+        // 不足一个包头：等待更多数据，或在结束时视为文件末尾
+        let Some((header, header_size)) =
+            self.decode_record_header()?
+        else {
+            return Ok(None);
+        };
+
+        let remaining = &self.buffer[self.consumed..];
+        let record_size =
+            header_size + header.packet_length as usize;
+        if remaining.len() < record_size {
+            if !self.finished {
+                return Ok(None);
+            }
+            let available =
+                (remaining.len() - header_size) as u64;
+            return Err(
+                PcapError::PacketSizeExceedsRemainingBytes {
+                    expected: header.packet_length,
+                    remaining: available,
+                    position: self.position
+                        + header_size as u64,
+                },
+            );
+        }
+
+        let data =
+            remaining[header_size..record_size].to_vec();
+        let status = ValidationStatus::evaluate(
+            &header, &data, true,
+        );
+        if !status.is_valid() {
+            warn!(
+                "数据包校验失败: {status:?}，位置: {}",
+                self.position
+            );
+        }
+
+        let packet = DataPacket::new(header, data)
+            .map_err(|e| PcapError::CorruptedData {
+                message: format!("数据包创建失败: {e}"),
+                position: self.position,
+            })?
+            .with_link_type(self.link_type());
+
+        self.previous_timestamp_ns =
+            packet.get_timestamp_ns();
+        self.consumed += record_size;
+        self.position += record_size as u64;
+        self.packet_count += 1;
+        Ok(Some(ValidatedPacket::with_status(
+            packet, status,
+        )))
+    }
+
+    /// 批量读取最多 `count` 个数据包
+    pub fn read_packets(
+        &mut self,
+        count: usize,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        let mut packets = Vec::with_capacity(count);
+        while packets.len() < count {
+            match self.read_packet()? {
+                Some(packet) => packets.push(packet),
+                None => break,
+            }
+        }
+        Ok(packets)
+    }
+
+    /// 按文件的记录格式解析下一个数据包头部，返回头部和头部占用的字节数
+    ///
+    /// 缓冲的字节不足一个头部时返回 `Ok(None)`。
+    fn decode_record_header(
+        &self,
+    ) -> PcapResult<Option<(DataPacketHeader, usize)>> {
+        let remaining = &self.buffer[self.consumed..];
+        let order = self.byte_order();
+        match self.record_format {
+            RecordFormat::Standard => {
+                Ok(PacketHeader::decode_with_order(
+                    remaining, order,
+                )
+                .ok()
+                .map(|header| {
+                    (
+                        header.into(),
+                        codec::PACKET_HEADER_SIZE,
+                    )
+                }))
+            }
+            RecordFormat::CheckedHeader => {
+                let Some(bytes) = remaining
+                    .get(..checked_header::HEADER_SIZE)
+                else {
+                    return Ok(None);
+                };
+                checked_header::decode(bytes, order)
+                    .map(|header| {
+                        Some((
+                            header,
+                            checked_header::HEADER_SIZE,
+                        ))
+                    })
+                    .map_err(|message| {
+                        PcapError::CorruptedData {
+                            message,
+                            position: self.position,
+                        }
+                    })
+            }
+            RecordFormat::DeltaTimestamp => {
+                let corrupted = |message: String| {
+                    PcapError::CorruptedData {
+                        message,
+                        position: self.position,
+                    }
+                };
+                let Some(delta) = delta_header::read(
+                    &mut &*remaining,
+                    order,
+                )
+                .map_err(|e| {
+                    corrupted(format!(
+                        "差值编码的包头无效: {e}"
+                    ))
+                })?
+                else {
+                    return Ok(None);
+                };
+                let mut header = DataPacketHeader {
+                    timestamp_seconds: 0,
+                    timestamp_nanoseconds: 0,
+                    packet_length: delta.packet_length,
+                    checksum: delta.checksum,
+                };
+                header
+                    .set_timestamp_ns(delta.timestamp_ns(
+                        self.previous_timestamp_ns,
+                    ))
+                    .map_err(corrupted)?;
+                Ok(Some((header, delta.encoded_len)))
+            }
+        }
+    }
+
+    /// 解析文件头，字节不足且字节流未结束时返回 `false`
+    fn ensure_header(&mut self) -> PcapResult<bool> {
+        if self.header.is_some() {
+            return Ok(true);
+        }
+
+        let header = match FileHeader::decode(&self.buffer)
+        {
+            Ok(header) => header,
+            Err(_) if !self.finished => return Ok(false),
+            Err(_) => {
+                return Err(PcapError::InvalidFormat(
+                    "文件太小，不是有效的PCAP文件"
+                        .to_string(),
+                ))
+            }
+        };
+        if header.has_valid_magic() && !header.is_valid() {
+            return Err(PcapError::UnsupportedVersion {
+                major: header.major_version,
+                minor: header.minor_version,
+            });
+        }
+        if !header.is_valid() {
+            return Err(PcapError::CorruptedHeader(
+                "无效的PCAP文件头".to_string(),
+            ));
+        }
+
+        // 扩展块区域需要完整到达后才能解析
+        let header_size = header.encoded_len();
+        if self.buffer.len() < header_size {
+            if !self.finished {
+                return Ok(false);
+            }
+            return Err(PcapError::CorruptedHeader(
+                "文件头扩展块不完整".to_string(),
+            ));
+        }
+        let header = PcapFileHeader::from_bytes(
+            &self.buffer[..header_size],
+        )
+        .map_err(PcapError::CorruptedHeader)?;
+        if header
+            .find_extension(HeaderExtension::PAYLOAD_STORE)
+            .is_some()
+        {
+            return Err(PcapError::InvalidFormat(
+                "文件的负载保存在数据集的负载存储中，内存读取器无法还原"
+                    .to_string(),
+            ));
+        }
+
+        if header
+            .find_extension(HeaderExtension::BLOCK_SIZE)
+            .is_some()
+        {
+            return Err(PcapError::InvalidFormat(
+                "内存读取器不支持分块布局的文件"
+                    .to_string(),
+            ));
+        }
+        self.record_format = header
+            .record_format()
+            .map_err(PcapError::CorruptedHeader)?;
+        self.previous_timestamp_ns = 0;
+        self.consumed = header_size;
+        self.position = header_size as u64;
+        self.header = Some(header);
+        debug!("已解析内存字节流的文件头");
+        Ok(true)
+    }
+
+    /// 丢弃已解析的字节，避免分块读取时缓冲区无限增长
+    fn compact(&mut self) {
+        if self.consumed > 0
+            && self.consumed * 2 >= self.buffer.len()
+        {
+            self.buffer.drain(..self.consumed);
+            self.consumed = 0;
+        }
+    }
+}
+
+impl Iterator for MemoryReader {
+    type Item = PcapResult<ValidatedPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_packet().transpose()
+    }
+}
@@ -0,0 +1,400 @@
+//! 数据集迁移模块
+//!
+//! 把旧格式版本的数据集升级到新版本：数据文件改写为目标版本的文件头，
+//! 旧结构版本的索引按当前结构重新生成。数据包头部和负载原样保留，
+//! 校验失败的数据包也不会被修正或丢弃。
+
+use log::{debug, info};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::business::config::{ReaderConfig, WriterConfig};
+use crate::business::index::{IndexManager, PidxIndex};
+use crate::business::lock::DatasetLock;
+use crate::core::ByteOrder;
+use crate::data::file_reader::PcapFileReader;
+use crate::data::file_writer::PcapFileWriter;
+use crate::data::models::{
+    FormatVersion, HeaderExtension, LinkType,
+    PcapFileHeader,
+};
+use crate::foundation::error::{PcapError, PcapResult};
+use crate::foundation::paths;
+
+/// 迁移时写入临时文件使用的扩展名
+const MIGRATING_EXTENSION: &str = "migrating";
+
+/// 复制到目标数据集的附属文件
+const SIDECAR_FILES: [&str; 3] = [
+    crate::business::bookmarks::BOOKMARKS_FILE_NAME,
+    crate::business::markers::MARKERS_FILE_NAME,
+    crate::data::payload_store::PAYLOAD_STORE_FILE_NAME,
+];
+
+/// 数据集迁移结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// 检查的数据文件数量
+    pub files_examined: usize,
+    /// 改写了文件头的数据文件数量
+    pub files_migrated: usize,
+    /// 改写的文件中包含的数据包数量
+    pub packets_migrated: u64,
+    /// 迁移前的索引结构版本，索引不存在或无法解析时为 `None`
+    pub previous_schema_version: Option<u32>,
+    /// 是否重新生成了索引
+    pub index_rebuilt: bool,
+}
+
+impl MigrationReport {
+    /// 数据集是否已是目标版本，迁移没有做任何改动
+    pub fn is_up_to_date(&self) -> bool {
+        self.files_migrated == 0 && !self.index_rebuilt
+    }
+}
+
+/// 数据集迁移工具
+///
+/// 默认把数据文件升级到 `FormatVersion::LATEST` 并保留原字节序。
+/// 旧文件头没有链路类型字段时写入 `link_type`，已有链路类型的文件保留原值；
+/// 文件头扩展块原样保留，目标版本不支持扩展块时丢弃。
+#[derive(Debug, Clone)]
+pub struct DatasetMigrator {
+    /// 目标文件格式版本
+    pub target_version: FormatVersion,
+    /// 升级不带链路类型的文件时写入的链路类型
+    pub link_type: LinkType,
+    /// 目标字节序，`None` 保留每个文件的原字节序
+    pub byte_order: Option<ByteOrder>,
+}
+
+impl Default for DatasetMigrator {
+    fn default() -> Self {
+        Self {
+            target_version: FormatVersion::LATEST,
+            link_type: LinkType::Unknown,
+            byte_order: None,
+        }
+    }
+}
+
+impl DatasetMigrator {
+    /// 使用默认设置创建迁移工具
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置目标文件格式版本
+    pub fn with_target_version(
+        mut self,
+        version: FormatVersion,
+    ) -> Self {
+        self.target_version = version;
+        self
+    }
+
+    /// 设置升级不带链路类型的文件时写入的链路类型
+    pub fn with_link_type(
+        mut self,
+        link_type: LinkType,
+    ) -> Self {
+        self.link_type = link_type;
+        self
+    }
+
+    /// 设置目标字节序
+    pub fn with_byte_order(
+        mut self,
+        byte_order: ByteOrder,
+    ) -> Self {
+        self.byte_order = Some(byte_order);
+        self
+    }
+
+    /// 原地迁移数据集
+    ///
+    /// 需要改写的文件先写入同目录的临时文件，完成后替换原文件，
+    /// 中途失败时原文件保持不变。已是目标版本的文件不会改写；
+    /// 有文件被改写或索引结构过时时重新生成索引。迁移期间持有数据集写入锁。
+    ///
+    /// # 错误
+    /// 数据集正在写入时返回 `PcapError::DatasetLocked`；
+    /// 数据文件无法完整读取（如末尾数据包被截断）时返回对应的读取错误
+    pub fn migrate_in_place<P: AsRef<Path>>(
+        &self,
+        dataset: P,
+    ) -> PcapResult<MigrationReport> {
+        let (base_path, dataset_name) =
+            paths::split_dataset_path(dataset)?;
+        let mut manager =
+            IndexManager::new(&base_path, &dataset_name)?;
+        let dataset_path =
+            paths::dataset_path(&base_path, &dataset_name)?;
+        let _lock = DatasetLock::acquire(&dataset_path)?;
+
+        let mut report = MigrationReport {
+            previous_schema_version: manager
+                .stored_schema_version(),
+            ..MigrationReport::default()
+        };
+        for file_path in manager.scan_pcap_files()? {
+            report.files_examined += 1;
+            let Some(plan) = self.plan_file(&file_path)?
+            else {
+                continue;
+            };
+
+            let mut temp_path =
+                file_path.clone().into_os_string();
+            temp_path.push(".");
+            temp_path.push(MIGRATING_EXTENSION);
+            let temp_path = PathBuf::from(temp_path);
+            match self
+                .rewrite_file(&file_path, &temp_path, &plan)
+            {
+                Ok(packets) => {
+                    fs::rename(&temp_path, &file_path)
+                        .map_err(PcapError::Io)?;
+                    report.files_migrated += 1;
+                    report.packets_migrated += packets;
+                }
+                Err(e) => {
+                    let _ = fs::remove_file(&temp_path);
+                    return Err(e);
+                }
+            }
+        }
+
+        if report.files_migrated > 0
+            || Self::needs_index_upgrade(
+                report.previous_schema_version,
+            )
+        {
+            manager.rebuild_index()?;
+            report.index_rebuilt = true;
+        }
+
+        info!(
+            "数据集迁移完成: {dataset_name}, 改写文件 {}/{}",
+            report.files_migrated, report.files_examined
+        );
+        Ok(report)
+    }
+
+    /// 把数据集迁移到新的目标数据集，源数据集保持不变
+    ///
+    /// 分片中的数据文件合并到目标数据集目录，书签和标记文件一并复制，
+    /// 已是目标版本的文件直接复制。目标数据集的索引总是重新生成。
+    ///
+    /// # 参数
+    /// - `src` - 源数据集目录
+    /// - `dst` - 目标数据集目录，不能与源相同且不能已包含数据
+    pub fn migrate_to_copy<
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    >(
+        &self,
+        src: P,
+        dst: Q,
+    ) -> PcapResult<MigrationReport> {
+        let (src_base, src_name) =
+            paths::split_dataset_path(src)?;
+        let (dst_base, dst_name) =
+            paths::split_dataset_path(dst)?;
+
+        let src_path =
+            paths::dataset_path(&src_base, &src_name)?;
+        let dst_path =
+            paths::dataset_path(&dst_base, &dst_name)?;
+        if src_path == dst_path {
+            return Err(PcapError::InvalidArgument(
+                "目标数据集不能与源数据集相同".to_string(),
+            ));
+        }
+        if dst_path
+            .read_dir()
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(false)
+        {
+            let message = format!(
+                "目标数据集已存在且不为空: {dst_path:?}"
+            );
+            return Err(PcapError::InvalidArgument(
+                message,
+            ));
+        }
+
+        let manager =
+            IndexManager::new(&src_base, &src_name)?;
+        fs::create_dir_all(&dst_path)
+            .map_err(PcapError::Io)?;
+
+        let mut report = MigrationReport {
+            previous_schema_version: manager
+                .stored_schema_version(),
+            ..MigrationReport::default()
+        };
+        for file_path in manager.scan_pcap_files()? {
+            report.files_examined += 1;
+            let Some(file_name) = file_path.file_name()
+            else {
+                continue;
+            };
+            let target_path = dst_path.join(file_name);
+            match self.plan_file(&file_path)? {
+                Some(plan) => {
+                    report.packets_migrated += self
+                        .rewrite_file(
+                            &file_path,
+                            &target_path,
+                            &plan,
+                        )?;
+                    report.files_migrated += 1;
+                }
+                None => {
+                    fs::copy(&file_path, &target_path)
+                        .map_err(PcapError::Io)?;
+                }
+            }
+        }
+
+        for sidecar in SIDECAR_FILES {
+            let path = src_path.join(sidecar);
+            if path.is_file() {
+                fs::copy(&path, dst_path.join(sidecar))
+                    .map_err(PcapError::Io)?;
+            }
+        }
+
+        IndexManager::new(&dst_base, &dst_name)?
+            .rebuild_index()?;
+        report.index_rebuilt = true;
+
+        info!(
+            "数据集迁移完成: {src_name} -> {dst_name}, 改写文件 {}/{}",
+            report.files_migrated, report.files_examined
+        );
+        Ok(report)
+    }
+
+    /// 确定文件的目标文件头和字节序，已是目标格式时返回 `None`
+    fn plan_file(
+        &self,
+        file_path: &Path,
+    ) -> PcapResult<Option<PcapFileHeader>> {
+        let mut reader =
+            PcapFileReader::new(ReaderConfig::default());
+        reader.open(file_path)?;
+        let source =
+            reader.header().cloned().ok_or_else(|| {
+                PcapError::InvalidState(
+                    "文件头未读取".to_string(),
+                )
+            })?;
+
+        let link_type = if source.has_link_type_field() {
+            source.link_type
+        } else {
+            self.link_type
+        };
+        let mut target = PcapFileHeader::for_version(
+            self.target_version,
+            source.timezone_offset,
+            link_type,
+        )
+        .with_byte_order(
+            self.byte_order.unwrap_or(source.byte_order),
+        );
+        target.timestamp_accuracy =
+            source.timestamp_accuracy;
+        // 降级到不支持扩展块的版本时丢弃扩展块；改写后的文件直接保存负载，
+        // 不再引用负载存储
+        if self.target_version.supports_extensions() {
+            target.extensions = source
+                .extensions
+                .iter()
+                .filter(|extension| {
+                    extension.kind()
+                        != HeaderExtension::PAYLOAD_STORE
+                })
+                .cloned()
+                .collect();
+        }
+
+        if source.format_version()
+            == Some(self.target_version)
+            && source.byte_order == target.byte_order
+            && source.link_type == target.link_type
+        {
+            debug!("文件已是目标格式: {file_path:?}");
+            return Ok(None);
+        }
+        Ok(Some(target))
+    }
+
+    /// 使用新的文件头把源文件的全部数据包写入目标路径，返回数据包数量
+    fn rewrite_file(
+        &self,
+        source_path: &Path,
+        target_path: &Path,
+        header: &PcapFileHeader,
+    ) -> PcapResult<u64> {
+        let mut reader =
+            PcapFileReader::new(ReaderConfig::default());
+        reader.open(source_path)?;
+
+        let (Some(directory), Some(file_name)) = (
+            target_path.parent(),
+            target_path
+                .file_name()
+                .and_then(|n| n.to_str()),
+        ) else {
+            return Err(PcapError::InvalidArgument(
+                format!("无效的目标路径: {target_path:?}"),
+            ));
+        };
+        let mut writer =
+            PcapFileWriter::new(WriterConfig {
+                byte_order: header.byte_order,
+                auto_flush: false,
+                ..WriterConfig::default()
+            });
+        writer
+            .create_with_header(
+                directory,
+                file_name,
+                header.clone(),
+            )
+            .map_err(PcapError::InvalidFormat)?;
+
+        let mut packets = 0u64;
+        while let Some(validated) = reader.read_packet()? {
+            writer
+                .write_record(
+                    &validated.packet.header,
+                    &validated.packet.data,
+                )
+                .map_err(PcapError::InvalidFormat)?;
+            packets += 1;
+        }
+        writer
+            .finish()
+            .map_err(PcapError::InvalidFormat)?;
+        writer.close();
+
+        debug!(
+            "文件已迁移到 {}: {source_path:?}, 数据包 {packets}",
+            self.target_version
+        );
+        Ok(packets)
+    }
+
+    /// 索引结构版本是否需要升级
+    fn needs_index_upgrade(
+        schema_version: Option<u32>,
+    ) -> bool {
+        schema_version.is_some_and(|version| {
+            version < PidxIndex::SCHEMA_VERSION
+        })
+    }
+}
@@ -2,9 +2,75 @@
 //!
 //! 提供用户友好的API接口，隐藏内部实现复杂性，实现资源的自动化管理。
 
+pub mod aggregate;
+pub mod composite;
+pub mod doctor;
+pub mod export;
+pub mod ingest;
+pub mod maintenance;
+pub mod memory;
+pub mod migrate;
+pub mod playback;
+pub mod query;
 pub mod reader;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod search;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "signal")]
+pub mod signal;
+pub mod sink;
+pub mod source;
+#[cfg(feature = "proptest-support")]
+pub mod strategies;
+pub mod synced;
+#[cfg(feature = "test-util")]
+pub mod testing;
+pub mod transform;
+pub mod triggered;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 pub mod writer;
 
 // 重新导出用户API
-pub use reader::PcapReader;
+pub use aggregate::{Aggregation, WindowAggregate};
+pub use composite::{CompositeMode, CompositeReader};
+pub use doctor::{
+    DatasetDoctor, DoctorReport, Finding, FindingKind,
+    Severity,
+};
+pub use export::{
+    ExportErrorPolicy, ExportOptions, ExportSummary,
+    SinkReport,
+};
+pub use ingest::PacketSender;
+pub use maintenance::{
+    DatasetMaintenance, DeletionMode, DeletionReport,
+    RelocationReport, TrashEntry,
+};
+pub use memory::MemoryReader;
+pub use migrate::{DatasetMigrator, MigrationReport};
+pub use playback::{PlaybackEngine, PlaybackSummary};
+pub use query::{PacketQuery, QueryDriver, QueryPlan};
+pub use reader::{
+    ChunkIter, ExtractFormat, ExtractedPacket, PacketPair,
+    PcapReader, TimeRangeIter, TryRead,
+};
+#[cfg(feature = "remote")]
+pub use remote::RemotePcapReader;
+#[cfg(feature = "regex")]
+pub use search::RegexMatcher;
+pub use search::{
+    BytesMatcher, MaskedMatcher, Matcher, PayloadMatch,
+    SearchOptions,
+};
+pub use sink::{PacketSink, RateLimitedSink, UdpSink};
+pub use source::PacketSource;
+pub use synced::{SyncedPacket, SyncedReader};
+pub use transform::{
+    DatasetTransformer, ScrubRule, Scrubber,
+    TimeCorrection, TransformSummary,
+};
+pub use triggered::TriggeredWriter;
 pub use writer::PcapWriter;
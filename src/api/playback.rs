@@ -0,0 +1,175 @@
+//! 回放模块
+//!
+//! 按数据包的原始时间间隔把数据集中的数据包依次交给输出端，可以按倍速加快或放慢，
+//! 也可以不等待、尽快发送。数据来源可以是本地或远程读取器，输出端可以是闭包或消息总线。
+//! 配置了速率上限时，数据包还要经过限速器放行，避免压垮下游。
+
+use log::{debug, info};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::api::sink::PacketSink;
+use crate::api::source::PacketSource;
+use crate::business::config::PlaybackConfig;
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 回放结果统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaybackSummary {
+    /// 已发送的数据包数量
+    pub packets: u64,
+    /// 已发送的负载字节数
+    pub bytes: u64,
+    /// 因校验失败被跳过的数据包数量
+    pub skipped_invalid: u64,
+    /// 因速率上限等待的总时长
+    pub throttled: Duration,
+    /// 回放耗时
+    pub elapsed: Duration,
+}
+
+/// 回放引擎
+#[derive(Debug, Clone, Default)]
+pub struct PlaybackEngine {
+    configuration: PlaybackConfig,
+}
+
+impl PlaybackEngine {
+    /// 使用默认配置创建回放引擎
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用指定配置创建回放引擎
+    pub fn new_with_config(
+        configuration: PlaybackConfig,
+    ) -> PcapResult<Self> {
+        configuration.validate().map_err(|e| {
+            PcapError::InvalidArgument(format!(
+                "回放配置无效: {e}"
+            ))
+        })?;
+        Ok(Self { configuration })
+    }
+
+    /// 获取回放配置
+    pub fn config(&self) -> &PlaybackConfig {
+        &self.configuration
+    }
+
+    /// 回放数据包来源中的数据包，直到到达结束时间戳或读完为止
+    ///
+    /// 开始前会把来源定位到开始时间戳（未设置时回到开头），结束后刷新输出端。
+    /// 输出端返回错误时立即停止回放并返回该错误。
+    pub fn run<S, K>(
+        &self,
+        source: &mut S,
+        sink: &mut K,
+    ) -> PcapResult<PlaybackSummary>
+    where
+        S: PacketSource + ?Sized,
+        K: PacketSink + ?Sized,
+    {
+        let started = Instant::now();
+        let mut summary = PlaybackSummary::default();
+
+        let positioned =
+            match self.configuration.start_timestamp_ns {
+                Some(start) => {
+                    match source.seek_to_timestamp(start) {
+                        Ok(_) => true,
+                        // 开始时间戳之后没有数据包
+                        Err(
+                            PcapError::InvalidArgument(_),
+                        ) => false,
+                        Err(e) => return Err(e),
+                    }
+                }
+                None => {
+                    source.reset()?;
+                    true
+                }
+            };
+
+        if positioned {
+            self.play(source, sink, started, &mut summary)?;
+        }
+        sink.flush()?;
+
+        summary.elapsed = started.elapsed();
+        info!(
+            "回放完成: {} 个数据包, {} 字节, 跳过 {} 个无效数据包",
+            summary.packets,
+            summary.bytes,
+            summary.skipped_invalid
+        );
+        Ok(summary)
+    }
+
+    fn play<S, K>(
+        &self,
+        source: &mut S,
+        sink: &mut K,
+        started: Instant,
+        summary: &mut PlaybackSummary,
+    ) -> PcapResult<()>
+    where
+        S: PacketSource + ?Sized,
+        K: PacketSink + ?Sized,
+    {
+        let mut first_timestamp_ns = None;
+        let mut limiter = self.configuration.rate_limiter();
+
+        while let Some(packet) = source.read_packet()? {
+            let timestamp_ns = packet.get_timestamp_ns();
+            if self
+                .configuration
+                .end_timestamp_ns
+                .is_some_and(|end| timestamp_ns > end)
+            {
+                debug!("已到达回放结束时间戳");
+                break;
+            }
+            if !packet.is_valid
+                && self.configuration.skip_invalid
+            {
+                summary.skipped_invalid += 1;
+                continue;
+            }
+
+            let first = *first_timestamp_ns
+                .get_or_insert(timestamp_ns);
+            if let Some(offset) = self.offset_for(
+                timestamp_ns.saturating_sub(first),
+            ) {
+                let due = started + offset;
+                let now = Instant::now();
+                if due > now {
+                    thread::sleep(due - now);
+                }
+            }
+            summary.throttled += limiter
+                .acquire(packet.packet.data.len() as u64);
+
+            sink.write(&packet)?;
+            summary.packets += 1;
+            summary.bytes +=
+                packet.packet.data.len() as u64;
+        }
+        Ok(())
+    }
+
+    /// 数据包相对第一个数据包的发送时刻，速度为 0 时不等待
+    fn offset_for(
+        &self,
+        delta_ns: u64,
+    ) -> Option<Duration> {
+        if self.configuration.speed == 0.0 {
+            return None;
+        }
+        let seconds = delta_ns as f64
+            / 1e9
+            / self.configuration.speed;
+        Duration::try_from_secs_f64(seconds).ok()
+    }
+}
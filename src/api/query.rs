@@ -0,0 +1,320 @@
+//! 组合条件查询模块
+//!
+//! `PacketQuery` 把时间范围、负载大小和通道条件组合成一次查询，
+//! 先用选择性最高的索引筛出候选数据包，再用其余条件过滤，只读取最终命中的数据包。
+
+use log::debug;
+
+use crate::api::reader::PcapReader;
+use crate::business::index::types::{
+    PacketIndexEntry, PidxIndex, TimestampPointer,
+};
+use crate::data::models::ValidatedPacket;
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 查询计划选用的候选来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryDriver {
+    /// 通过时间戳索引定位时间范围内的数据包
+    TimeIndex,
+    /// 通过大小索引定位负载大小范围内的数据包
+    SizeIndex,
+    /// 遍历通道条件保留的全部文件的索引条目
+    FullScan,
+}
+
+/// 查询计划
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryPlan {
+    /// 选用的候选来源
+    pub driver: QueryDriver,
+    /// 候选来源的预估数据包数量
+    pub estimated_candidates: usize,
+    /// 因通道条件被排除的文件数量
+    pub files_pruned: usize,
+    /// 满足全部条件的数据包数量（已应用数量上限）
+    pub matched: usize,
+}
+
+/// 组合条件数据包查询
+///
+/// 通过 `PcapReader::query()` 创建，条件之间为“与”关系，未设置的条件不参与过滤。
+/// 结果按全局序号升序排列。
+///
+/// # 示例
+///
+/// ```no_run
+/// use pcapfile_io::PcapReader;
+///
+/// let mut reader = PcapReader::new("./data", "my_dataset")?;
+/// let packets = reader
+///     .query()
+///     .time_range(1_000, 2_000)
+///     .size_between(64, 512)
+///     .channel(3)
+///     .limit(1000)
+///     .run()?;
+/// # Ok::<(), pcapfile_io::PcapError>(())
+/// ```
+pub struct PacketQuery<'a> {
+    reader: &'a mut PcapReader,
+    time_range: Option<(u64, u64)>,
+    size_range: Option<(u32, u32)>,
+    channel: Option<u32>,
+    limit: Option<usize>,
+}
+
+impl<'a> PacketQuery<'a> {
+    pub(crate) fn new(reader: &'a mut PcapReader) -> Self {
+        Self {
+            reader,
+            time_range: None,
+            size_range: None,
+            channel: None,
+            limit: None,
+        }
+    }
+
+    /// 只保留时间戳在 `[start_ns, end_ns]` 内的数据包
+    pub fn time_range(
+        mut self,
+        start_ns: u64,
+        end_ns: u64,
+    ) -> Self {
+        self.time_range = Some((start_ns, end_ns));
+        self
+    }
+
+    /// 只保留负载大小在 `[min_size, max_size]` 字节内的数据包
+    pub fn size_between(
+        mut self,
+        min_size: u32,
+        max_size: u32,
+    ) -> Self {
+        self.size_range = Some((min_size, max_size));
+        self
+    }
+
+    /// 只保留指定通道的数据包
+    ///
+    /// 通道为数据文件头中记录的链路类型数值，见 `LinkType::to_u32`。
+    pub fn channel(mut self, channel: u32) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// 最多返回 `limit` 个数据包
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// 生成查询计划而不读取数据包
+    pub fn explain(&mut self) -> PcapResult<QueryPlan> {
+        self.plan().map(|(plan, _)| plan)
+    }
+
+    /// 执行查询，返回满足全部条件的数据包
+    ///
+    /// # 错误
+    /// 读取命中的数据包失败时返回对应的错误
+    pub fn run(
+        mut self,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        let (plan, pointers) = self.plan()?;
+        debug!("执行查询: {plan:?}");
+
+        let mut packets =
+            Vec::with_capacity(pointers.len());
+        for pointer in &pointers {
+            packets.push(
+                self.reader
+                    .read_packet_at_pointer(pointer)?,
+            );
+        }
+        Ok(packets)
+    }
+
+    /// 选择候选来源并计算命中的索引指针
+    fn plan(
+        &mut self,
+    ) -> PcapResult<(QueryPlan, Vec<TimestampPointer>)>
+    {
+        self.reader.initialize()?;
+        let allowed = self.allowed_files()?;
+        let files_pruned =
+            allowed.iter().filter(|keep| !**keep).count();
+
+        let index =
+            self.reader.index().get_index().ok_or_else(
+                || {
+                    PcapError::InvalidState(
+                        "索引未加载".to_string(),
+                    )
+                },
+            )?;
+
+        // 估算各个候选来源的数据包数量，选择最少的一个
+        let scan_estimate = index
+            .files()
+            .iter()
+            .zip(&allowed)
+            .filter(|(_, keep)| **keep)
+            .map(|(file, _)| file.data_packets.len())
+            .sum::<usize>();
+        let mut driver =
+            (QueryDriver::FullScan, scan_estimate);
+        if let Some((start_ns, end_ns)) = self.time_range {
+            let timestamps = index.sorted_timestamps();
+            let estimate = timestamps
+                .partition_point(|&t| t <= end_ns)
+                .saturating_sub(
+                    timestamps
+                        .partition_point(|&t| t < start_ns),
+                );
+            if estimate <= driver.1 {
+                driver = (QueryDriver::TimeIndex, estimate);
+            }
+        }
+        if let Some((min_size, max_size)) = self.size_range
+        {
+            let estimate = index
+                .packets_with_size(min_size, max_size)
+                .len();
+            if estimate < driver.1 {
+                driver = (QueryDriver::SizeIndex, estimate);
+            }
+        }
+
+        let mut pointers =
+            self.candidates(index, driver.0, &allowed);
+        pointers.sort_unstable_by_key(|pointer| {
+            pointer.entry.global_index
+        });
+        if let Some(limit) = self.limit {
+            pointers.truncate(limit);
+        }
+
+        let plan = QueryPlan {
+            driver: driver.0,
+            estimated_candidates: driver.1,
+            files_pruned,
+            matched: pointers.len(),
+        };
+        Ok((plan, pointers))
+    }
+
+    /// 按通道条件确定参与查询的文件
+    fn allowed_files(&mut self) -> PcapResult<Vec<bool>> {
+        let file_count = self
+            .reader
+            .index()
+            .get_index()
+            .map_or(0, |index| index.file_count());
+        let Some(channel) = self.channel else {
+            return Ok(vec![true; file_count]);
+        };
+        (0..file_count)
+            .map(|file_index| {
+                self.reader.file_header(file_index).map(
+                    |header| {
+                        header.link_type.to_u32() == channel
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// 从候选来源取出满足全部条件的索引指针
+    fn candidates(
+        &self,
+        index: &PidxIndex,
+        driver: QueryDriver,
+        allowed: &[bool],
+    ) -> Vec<TimestampPointer> {
+        let matches =
+            |file_index: usize,
+             entry: &PacketIndexEntry| {
+                allowed
+                    .get(file_index)
+                    .copied()
+                    .unwrap_or(false)
+                    && self.time_range.is_none_or(
+                        |(start, end)| {
+                            (start..=end).contains(
+                                &entry.timestamp_ns,
+                            )
+                        },
+                    )
+                    && self.size_range.is_none_or(
+                        |(min, max)| {
+                            (min..=max).contains(
+                                &entry.packet_size,
+                            )
+                        },
+                    )
+            };
+        let pointer =
+            |file_index: usize,
+             entry: &PacketIndexEntry| {
+                TimestampPointer {
+                    file_index,
+                    entry: entry.clone(),
+                }
+            };
+
+        match driver {
+            QueryDriver::SizeIndex => {
+                let (min_size, max_size) = self
+                    .size_range
+                    .unwrap_or((0, u32::MAX));
+                index
+                    .packets_with_size(min_size, max_size)
+                    .iter()
+                    .filter_map(|&(_, global_index)| {
+                        index.locate_global_index(
+                            global_index,
+                        )
+                    })
+                    .filter(|(file_index, entry)| {
+                        matches(*file_index, entry)
+                    })
+                    .map(|(file_index, entry)| {
+                        pointer(file_index, entry)
+                    })
+                    .collect()
+            }
+            QueryDriver::TimeIndex
+            | QueryDriver::FullScan => {
+                let files = match self.time_range {
+                    Some((start_ns, end_ns))
+                        if driver
+                            == QueryDriver::TimeIndex =>
+                    {
+                        index.files_in_range(
+                            start_ns, end_ns,
+                        )
+                    }
+                    _ => index
+                        .files()
+                        .iter()
+                        .enumerate()
+                        .collect(),
+                };
+                files
+                    .into_iter()
+                    .flat_map(|(file_index, file)| {
+                        file.data_packets
+                            .iter()
+                            .filter(move |entry| {
+                                matches(file_index, entry)
+                            })
+                            .map(move |entry| {
+                                pointer(file_index, entry)
+                            })
+                    })
+                    .collect()
+            }
+        }
+    }
+}
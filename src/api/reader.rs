@@ -3,22 +3,169 @@
 //! 提供高级的数据集读取功能，支持多文件PCAP数据集的统一读取接口。
 
 use log::{debug, info, warn};
+use serde::Serialize;
 use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ops::{Bound, RangeBounds, RangeInclusive};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use crate::business::cache::{CacheStats, FileInfoCache};
+use crate::api::aggregate::{
+    self, Aggregation, WindowAggregate,
+};
+use crate::api::query::PacketQuery;
+use crate::api::search::{
+    self, BytesMatcher, Matcher, PayloadMatch,
+    SearchOptions,
+};
+use crate::business::bookmarks::{Bookmark, BookmarkStore};
+use crate::business::cache::{
+    CacheStats, FileInfoCache, PacketCache,
+};
 use crate::business::config::ReaderConfig;
-use crate::business::index::IndexManager;
+use crate::business::decode::{
+    DecoderRegistry, PayloadDecoder,
+};
+use crate::business::index::types::{
+    PacketIndexEntry, PcapFileIndex, TimestampPointer,
+};
+use crate::business::index::{
+    DatasetSubset, IndexManager, PacketLocation, SeekMode,
+};
+use crate::business::lock::DatasetLock;
+use crate::business::markers::{self, Marker};
+use crate::business::pool::{BufferPool, PooledPacket};
+use crate::business::progress::WriterProgress;
 use crate::data::file_reader::PcapFileReader;
+use crate::data::libpcap::LibpcapWriter;
 use crate::data::models::{
-    DataPacket, DatasetInfo, FileInfo, ValidatedPacket,
+    DataPacket, DatasetInfo, FileInfo, HeaderExtension,
+    LinkType, PcapFileHeader, ValidatedPacket,
 };
 use crate::foundation::error::{PcapError, PcapResult};
+use crate::foundation::paths;
+use crate::foundation::utils::duration_to_nanos;
 
 // 错误消息常量
 const ERROR_DATASET_NOT_FOUND: &str = "数据集目录不存在";
 const ERROR_INVALID_DATASET: &str = "无效的数据集目录";
 
+/// 并行读取时分配给工作线程的文件及其待读取的索引条目
+pub(crate) type FileGroup =
+    (PathBuf, Vec<PacketIndexEntry>);
+
+/// 非阻塞读取的结果
+///
+/// 由 `PcapReader::try_read_packet` 返回，区分暂时没有新数据包和数据集已读完。
+#[derive(Debug)]
+pub enum TryRead {
+    /// 读取到下一个数据包
+    Packet(ValidatedPacket),
+    /// 写入器仍在写入，暂时没有新数据包，稍后再试
+    WouldBlock,
+    /// 数据集已读完，且没有活动的写入器
+    EndOfDataset,
+}
+
+impl TryRead {
+    /// 取出读取到的数据包
+    pub fn into_packet(self) -> Option<ValidatedPacket> {
+        match self {
+            TryRead::Packet(packet) => Some(packet),
+            _ => None,
+        }
+    }
+
+    /// 是否暂时没有新数据包
+    pub fn is_would_block(&self) -> bool {
+        matches!(self, TryRead::WouldBlock)
+    }
+
+    /// 数据集是否已读完
+    pub fn is_end_of_dataset(&self) -> bool {
+        matches!(self, TryRead::EndOfDataset)
+    }
+}
+
+/// 目标时间戳前后相邻的两个数据包
+///
+/// 由 `PcapReader::packet_pair_around` 返回，用于在两个采样之间插值。
+#[derive(Debug, Clone)]
+pub struct PacketPair {
+    /// 目标时间戳（纳秒）
+    pub timestamp_ns: u64,
+    /// 时间戳不大于目标的最后一个数据包
+    pub before: Option<ValidatedPacket>,
+    /// 时间戳大于目标的第一个数据包
+    pub after: Option<ValidatedPacket>,
+}
+
+impl PacketPair {
+    /// 前一个数据包早于目标时间戳的纳秒数
+    pub fn before_offset_ns(&self) -> Option<u64> {
+        self.before.as_ref().map(|packet| {
+            self.timestamp_ns
+                - packet.packet.get_timestamp_ns()
+        })
+    }
+
+    /// 后一个数据包晚于目标时间戳的纳秒数
+    pub fn after_offset_ns(&self) -> Option<u64> {
+        self.after.as_ref().map(|packet| {
+            packet.packet.get_timestamp_ns()
+                - self.timestamp_ns
+        })
+    }
+
+    /// 目标时间戳在两个数据包之间的位置，0 对应前一个数据包，接近 1 对应后一个数据包
+    ///
+    /// 缺少任一侧的数据包时返回 None。
+    pub fn interpolation_factor(&self) -> Option<f64> {
+        let before = self.before_offset_ns()?;
+        let after = self.after_offset_ns()?;
+        Some(before as f64 / (before + after) as f64)
+    }
+}
+
+/// 单独提取数据包时的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExtractFormat {
+    /// 标准 libpcap 文件（`.pcap`），可直接用 Wireshark 打开
+    #[default]
+    Pcap,
+    /// 原始负载文件（`.bin`）和记录时间戳、来源位置等信息的 JSON 文件（`.json`）
+    Raw,
+}
+
+/// 单独提取的数据包
+///
+/// 由 `PcapReader::extract_packets` 返回，`Raw` 格式下以 JSON 形式写入元数据文件。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExtractedPacket {
+    /// 数据包在整个数据集中的全局序号
+    pub global_index: u64,
+    /// 数据包时间戳（纳秒）
+    pub timestamp_ns: u64,
+    /// 负载的链路类型
+    pub link_type: LinkType,
+    /// 负载大小（字节）
+    pub packet_size: u32,
+    /// 负载的 CRC32 校验和
+    pub checksum: u32,
+    /// 读取时校验是否通过
+    pub is_valid: bool,
+    /// 数据包所在的数据文件名称
+    pub source_file: String,
+    /// 数据包在数据文件中的字节偏移
+    pub byte_offset: u64,
+    /// 写入的数据文件
+    #[serde(skip)]
+    pub path: PathBuf,
+    /// 写入的元数据文件，仅 `Raw` 格式
+    #[serde(skip)]
+    pub metadata_path: Option<PathBuf>,
+}
+
 /// PCAP数据集读取器
 ///
 /// 提供对PCAP数据集的高性能读取功能，支持：
@@ -37,14 +184,22 @@ pub struct PcapReader {
     configuration: ReaderConfig,
     /// 当前文件读取器
     current_reader: Option<PcapFileReader>,
+    /// 空闲文件句柄池（LRU，末尾为最近使用）
+    idle_readers: Vec<(usize, PcapFileReader)>,
     /// 当前文件索引
     current_file_index: usize,
     /// 当前读取位置（全局数据包索引）
     current_position: u64,
     /// 文件信息缓存
     file_info_cache: FileInfoCache,
+    /// 数据包缓存（随机访问）
+    packet_cache: PacketCache,
     /// 总大小缓存
     total_size_cache: RefCell<Option<u64>>,
+    /// 负载解码器注册表
+    decoders: DecoderRegistry,
+    /// 负载缓冲区池（`read_pooled_packet` 使用）
+    buffer_pool: BufferPool,
     /// 是否已初始化
     is_initialized: bool,
 }
@@ -91,7 +246,7 @@ impl PcapReader {
         })?;
 
         let dataset_path =
-            base_path.as_ref().join(dataset_name);
+            paths::dataset_path(&base_path, dataset_name)?;
 
         // 验证数据集目录
         if !dataset_path.exists() {
@@ -107,11 +262,47 @@ impl PcapReader {
         }
 
         // 创建索引管理器
-        let index_manager =
+        let mut index_manager =
             IndexManager::new(base_path, dataset_name)?;
+        index_manager.set_auto_repair(
+            configuration.auto_repair_index,
+        );
+        index_manager.set_salvage_truncated(
+            configuration.salvage_truncated_packets
+                || configuration.tolerate_partial_tail,
+        );
+        index_manager
+            .set_read_only(configuration.read_only);
+        index_manager
+            .set_index_dir(configuration.index_dir.clone());
+        index_manager
+            .set_max_memory(configuration.max_index_memory);
+        index_manager.set_timestamp_table(
+            configuration.mmap_timestamp_index,
+        );
+        index_manager.set_sort_files_by_time(
+            configuration.sort_files_by_time,
+        );
+        index_manager.set_skip_files(
+            configuration.skip_files.clone(),
+        );
 
         // 获取缓存大小（在移动 configuration 之前）
         let cache_size = configuration.index_cache_size;
+        let file_info_cache =
+            FileInfoCache::new(cache_size);
+        if configuration.watch_dataset {
+            file_info_cache.watch(
+                &dataset_path,
+                configuration.watch_interval,
+            );
+        }
+        let packet_cache = PacketCache::new(
+            configuration.packet_cache_size,
+            configuration.packet_cache_max_bytes.as_usize(),
+        );
+        let buffer_pool =
+            BufferPool::new(configuration.buffer_pool_size);
 
         info!("PcapReader已创建 - 数据集: {dataset_name}");
 
@@ -121,14 +312,96 @@ impl PcapReader {
             index_manager,
             configuration,
             current_reader: None,
+            idle_readers: Vec::new(),
             current_file_index: 0,
             current_position: 0,
-            file_info_cache: FileInfoCache::new(cache_size),
+            file_info_cache,
+            packet_cache,
             total_size_cache: RefCell::new(None),
+            decoders: DecoderRegistry::new(),
+            buffer_pool,
             is_initialized: false,
         })
     }
 
+    /// 以只读模式打开数据集
+    ///
+    /// 只加载已有的有效索引，从不生成、隔离或改写索引文件，适用于只读挂载、
+    /// 网络共享和归档存储。其余配置使用默认值，需要调整时在
+    /// `new_with_config` 中设置 `ReaderConfig::read_only`。
+    ///
+    /// # 错误
+    /// 读取前初始化时，索引文件不存在返回 `PcapError::FileNotFound`，
+    /// 索引损坏返回 `PcapError::InvalidFormat`，
+    /// 索引与数据文件不一致返回 `PcapError::InvalidState`
+    pub fn open_read_only<P: AsRef<Path>>(
+        base_path: P,
+        dataset_name: &str,
+    ) -> PcapResult<Self> {
+        Self::new_with_config(
+            base_path,
+            dataset_name,
+            ReaderConfig {
+                read_only: true,
+                ..ReaderConfig::default()
+            },
+        )
+    }
+
+    /// 打开数据集的子集
+    ///
+    /// 按时间窗口（`TimeWindow`）或文件筛选条件（`FileFilter`）构建受限视图：
+    /// 全局序号、`total_packets`、`progress` 和数据集信息只反映所选部分，
+    /// 顺序读取从子集的第一个数据包开始、到最后一个数据包结束。
+    /// 磁盘上的索引文件仍包含全部数据文件，见 `IndexManager::set_subset`。
+    ///
+    /// # 错误
+    /// 时间窗口的起始时间戳大于结束时间戳或文件筛选条件为空时返回
+    /// `PcapError::InvalidArgument`
+    pub fn open_subset<P, S>(
+        base_path: P,
+        dataset_name: &str,
+        subset: S,
+    ) -> PcapResult<Self>
+    where
+        P: AsRef<Path>,
+        S: Into<DatasetSubset>,
+    {
+        let subset = subset.into();
+        subset.validate().map_err(|e| {
+            PcapError::InvalidArgument(format!(
+                "数据集子集无效: {e}"
+            ))
+        })?;
+        let mut reader =
+            Self::new(base_path, dataset_name)?;
+        reader.index_manager.set_subset(Some(subset));
+        Ok(reader)
+    }
+
+    /// 打开的数据集子集，读取完整数据集时为 None
+    pub fn subset(&self) -> Option<&DatasetSubset> {
+        self.index_manager.subset()
+    }
+
+    /// 是否以只读模式打开
+    pub fn is_read_only(&self) -> bool {
+        self.configuration.read_only
+    }
+
+    /// 只读模式下拒绝会写入数据集目录的操作
+    fn ensure_writable(
+        &self,
+        operation: &str,
+    ) -> PcapResult<()> {
+        if self.configuration.read_only {
+            return Err(PcapError::InvalidState(format!(
+                "只读模式下不能{operation}"
+            )));
+        }
+        Ok(())
+    }
+
     /// 初始化读取器
     ///
     /// 确保索引可用并准备好读取操作
@@ -139,8 +412,16 @@ impl PcapReader {
 
         info!("初始化PcapReader...");
 
-        // 确保索引可用
-        let _index = self.index_manager.ensure_index()?;
+        // 确保索引可用；写入器活动期间只构建内存中的实时索引
+        if DatasetLock::is_locked(&self.dataset_path) {
+            let progress =
+                WriterProgress::load(&self.dataset_path)
+                    .unwrap_or_default();
+            self.index_manager
+                .build_live_index(&progress)?;
+        } else {
+            self.index_manager.ensure_index()?;
+        }
 
         self.is_initialized = true;
         info!("PcapReader初始化完成");
@@ -167,7 +448,7 @@ impl PcapReader {
         Ok(DatasetInfo {
             name: self.dataset_name.clone(),
             path: self.dataset_path.clone(),
-            file_count: index.data_files.files.len(),
+            file_count: index.file_count(),
             total_packets: index.total_packets,
             total_size: self.get_total_size()?,
             start_timestamp: if index.start_timestamp > 0 {
@@ -201,61 +482,204 @@ impl PcapReader {
                 )
             })?;
 
-        use chrono::Utc;
-        let current_time = Utc::now().to_rfc3339();
+        Ok(index
+            .data_files
+            .files
+            .iter()
+            .map(|file_index| {
+                self.file_info_for(file_index)
+            })
+            .collect())
+    }
 
-        let mut file_infos = Vec::new();
-        for file_index in &index.data_files.files {
-            let file_path = self
-                .dataset_path
-                .join(&file_index.file_name);
+    /// 查找包含指定时间戳数据的文件
+    ///
+    /// 直接根据索引中的文件起止时间判断，可用于从数据集中手动拷贝所需文件。
+    ///
+    /// # 参数
+    /// - `timestamp_ns` - 目标时间戳（纳秒）
+    ///
+    /// # 返回
+    /// 返回时间范围覆盖该时间戳的文件信息，没有文件覆盖时返回 None
+    pub fn file_covering_timestamp(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> PcapResult<Option<FileInfo>> {
+        self.initialize()?;
 
-            // 尝试从缓存获取文件信息
-            let file_info = if let Some(cached_info) =
-                self.file_info_cache.get(&file_path)
-            {
-                cached_info
-            } else {
-                // 缓存未命中，创建新的文件信息并缓存
-                let file_info = FileInfo {
-                    file_name: file_index.file_name.clone(),
-                    file_path: file_path.clone(),
-                    file_size: file_index.file_size,
-                    packet_count: file_index.packet_count,
-                    start_timestamp: if file_index
-                        .start_timestamp
-                        > 0
-                    {
-                        Some(file_index.start_timestamp)
-                    } else {
-                        None
-                    },
-                    end_timestamp: if file_index
-                        .end_timestamp
-                        > 0
-                    {
-                        Some(file_index.end_timestamp)
-                    } else {
-                        None
-                    },
-                    file_hash: Some(
-                        file_index.file_hash.clone(),
-                    ),
-                    created_time: current_time.clone(),
-                    modified_time: current_time.clone(),
-                    is_valid: true,
-                };
-
-                // 将文件信息加入缓存
-                self.file_info_cache
-                    .insert(&file_path, file_info.clone());
-                file_info
-            };
+        let index = self
+            .index_manager
+            .get_index()
+            .ok_or_else(|| {
+                PcapError::InvalidState(
+                    "索引未加载".to_string(),
+                )
+            })?;
+
+        Ok(index.file_covering_timestamp(timestamp_ns).map(
+            |(_, file_index)| {
+                self.file_info_for(file_index)
+            },
+        ))
+    }
+
+    /// 查找包含指定时间范围数据的文件
+    ///
+    /// # 参数
+    /// - `start_timestamp_ns` - 开始时间戳（纳秒）
+    /// - `end_timestamp_ns` - 结束时间戳（纳秒）
+    ///
+    /// # 返回
+    /// 按文件顺序返回时间范围与 `[start, end]` 有交集的文件信息
+    pub fn files_in_range(
+        &mut self,
+        start_timestamp_ns: u64,
+        end_timestamp_ns: u64,
+    ) -> PcapResult<Vec<FileInfo>> {
+        self.initialize()?;
+
+        let index = self
+            .index_manager
+            .get_index()
+            .ok_or_else(|| {
+                PcapError::InvalidState(
+                    "索引未加载".to_string(),
+                )
+            })?;
+
+        Ok(index
+            .files_in_range(
+                start_timestamp_ns,
+                end_timestamp_ns,
+            )
+            .into_iter()
+            .map(|(_, file_index)| {
+                self.file_info_for(file_index)
+            })
+            .collect())
+    }
 
-            file_infos.push(file_info);
+    /// 读取指定数据文件的文件头
+    ///
+    /// # 参数
+    /// - `file_index` - 文件在索引中的序号（从0开始）
+    ///
+    /// # 错误
+    /// 序号超出文件数量时返回 `PcapError::InvalidArgument`
+    pub fn file_header(
+        &mut self,
+        file_index: usize,
+    ) -> PcapResult<PcapFileHeader> {
+        self.initialize()?;
+
+        let index = self
+            .index_manager
+            .get_index()
+            .ok_or_else(|| {
+                PcapError::InvalidState(
+                    "索引未加载".to_string(),
+                )
+            })?;
+        let file_path = index
+            .data_files
+            .files
+            .get(file_index)
+            .map(|file| {
+                file.resolve_path(&self.dataset_path)
+            })
+            .ok_or_else(|| {
+                PcapError::InvalidArgument(format!(
+                    "文件序号超出范围: {file_index}"
+                ))
+            })?;
+
+        let mut reader =
+            PcapFileReader::new(self.configuration.clone());
+        reader.open(&file_path)?;
+        reader.header().cloned().ok_or_else(|| {
+            PcapError::InvalidState(
+                "文件头未读取".to_string(),
+            )
+        })
+    }
+
+    /// 当前正在读取的数据文件的文件头
+    ///
+    /// 顺序读取或定位后返回所在文件的文件头，可用于显示版本号、魔术数、链路类型和字节序等
+    /// 格式信息；尚未打开任何数据文件时为 `None`。
+    pub fn current_file_header(
+        &self,
+    ) -> Option<&PcapFileHeader> {
+        self.current_reader
+            .as_ref()
+            .and_then(PcapFileReader::header)
+    }
+
+    /// 数据集的文件头扩展块
+    ///
+    /// 同一写入器生成的文件携带相同的扩展块，这里返回第一个数据文件的扩展块；
+    /// 数据集为空时返回空列表。
+    pub fn header_extensions(
+        &mut self,
+    ) -> PcapResult<Vec<HeaderExtension>> {
+        self.initialize()?;
+        if self.index_manager.get_index().is_none_or(
+            |index| index.data_files.files.is_empty(),
+        ) {
+            return Ok(Vec::new());
+        }
+        Ok(self.file_header(0)?.extensions)
+    }
+
+    /// 根据索引条目生成文件信息，优先使用缓存
+    fn file_info_for(
+        &self,
+        file_index: &PcapFileIndex,
+    ) -> FileInfo {
+        let file_path =
+            file_index.resolve_path(&self.dataset_path);
+
+        // 尝试从缓存获取文件信息
+        if let Some(cached_info) =
+            self.file_info_cache.get(&file_path)
+        {
+            return cached_info;
         }
 
-        Ok(file_infos)
+        // 缓存未命中，创建新的文件信息并缓存
+        use chrono::Utc;
+        let current_time = Utc::now().to_rfc3339();
+        let file_info = FileInfo {
+            file_name: file_index.file_name.clone(),
+            file_path: file_path.clone(),
+            file_size: file_index.file_size,
+            packet_count: file_index.packet_count,
+            start_timestamp: if file_index.start_timestamp
+                > 0
+            {
+                Some(file_index.start_timestamp)
+            } else {
+                None
+            },
+            end_timestamp: if file_index.end_timestamp > 0 {
+                Some(file_index.end_timestamp)
+            } else {
+                None
+            },
+            file_hash: Some(file_index.file_hash.clone()),
+            created_time: current_time.clone(),
+            modified_time: current_time,
+            is_valid: true,
+            header: PcapFileReader::read_header(&file_path)
+                .ok(),
+            rotation_policy: None,
+            rotation_reason: None,
+        };
+
+        // 将文件信息加入缓存
+        self.file_info_cache
+            .insert(&file_path, file_info.clone());
+        file_info
     }
 
     /// 获取数据集路径
@@ -268,6 +692,85 @@ impl PcapReader {
         &self.dataset_name
     }
 
+    /// 检查数据集当前是否有活动的写入器
+    ///
+    /// 写入器持有数据集锁期间，数据文件和索引可能仍在变化。
+    pub fn has_active_writer(&self) -> bool {
+        DatasetLock::is_locked(&self.dataset_path)
+    }
+
+    /// 当前是否使用写入期间构建的实时索引
+    pub fn is_live(&self) -> bool {
+        self.index_manager.is_live()
+    }
+
+    /// 刷新索引以获取写入器新写入的数据
+    ///
+    /// 保留当前读取位置；如果有新数据包，后续读取将从原位置继续。
+    /// 写入器完成后刷新会切换到完整的磁盘索引。
+    pub fn refresh_index(&mut self) -> PcapResult<()> {
+        let position = self.current_position;
+
+        // 已打开的文件句柄记录的是旧的文件大小，需要全部重新打开
+        self.is_initialized = false;
+        self.current_reader = None;
+        self.idle_readers.clear();
+        self.packet_cache.clear();
+        *self.total_size_cache.borrow_mut() = None;
+        self.initialize()?;
+
+        let index = self
+            .index_manager
+            .get_index()
+            .ok_or_else(|| {
+                PcapError::InvalidState(
+                    "索引未加载".to_string(),
+                )
+            })?;
+        let total = index.total_packets;
+        let position = position.min(total);
+
+        // 定位到已读取的最后一个数据包之后
+        let resume = if position > 0 {
+            index.locate_global_index(position - 1).map(
+                |(file_idx, entry)| {
+                    (
+                        file_idx,
+                        entry.byte_offset,
+                        entry.packet_size,
+                        entry.timestamp_ns,
+                    )
+                },
+            )
+        } else {
+            None
+        };
+
+        self.current_file_index = 0;
+        self.current_position = position;
+        if let Some((
+            file_idx,
+            byte_offset,
+            packet_size,
+            timestamp_ns,
+        )) = resume
+        {
+            self.open_file(file_idx)?;
+            if let Some(reader) =
+                self.current_reader.as_mut()
+            {
+                reader.seek_past_entry(
+                    byte_offset,
+                    packet_size,
+                    timestamp_ns,
+                )?;
+            }
+        }
+
+        debug!("索引已刷新 - 数据包总数: {total}");
+        Ok(())
+    }
+
     /// 读取下一个数据包（默认方法，带校验结果）
     ///
     /// 从当前位置读取下一个数据包，包含校验状态信息。如果当前文件读取完毕，
@@ -280,6 +783,84 @@ impl PcapReader {
     pub fn read_packet(
         &mut self,
     ) -> PcapResult<Option<ValidatedPacket>> {
+        self.read_next_with(|reader| reader.read_packet())
+    }
+
+    /// 非阻塞地读取下一个数据包，适合单线程事件循环轮询正在写入的数据集
+    ///
+    /// 读到当前索引末尾时，如果数据集有活动的写入器或正在使用实时索引，
+    /// 会刷新一次索引再尝试读取；仍没有新数据包时返回 `TryRead::WouldBlock`，
+    /// 调用方可以稍后再次调用。写入器完成后刷新切换到完整索引，读完剩余数据包后
+    /// 返回 `TryRead::EndOfDataset`。每次返回 `WouldBlock` 前都会刷新索引，
+    /// 轮询间隔由调用方控制。
+    ///
+    /// # 返回
+    /// - `Ok(TryRead::Packet(packet))` - 成功读取到数据包
+    /// - `Ok(TryRead::WouldBlock)` - 写入器仍在写入，暂无新数据包
+    /// - `Ok(TryRead::EndOfDataset)` - 数据集已读完
+    /// - `Err(error)` - 读取或刷新索引过程中发生错误
+    pub fn try_read_packet(
+        &mut self,
+    ) -> PcapResult<TryRead> {
+        if let Some(packet) = self.read_packet()? {
+            return Ok(TryRead::Packet(packet));
+        }
+        if !self.is_live() && !self.has_active_writer() {
+            return Ok(TryRead::EndOfDataset);
+        }
+
+        // 先判断写入器状态再刷新，避免刷新后写入器恰好完成而遗漏最后写入的数据包
+        let writer_active = self.has_active_writer();
+        self.refresh_index()?;
+        if let Some(packet) = self.read_packet()? {
+            return Ok(TryRead::Packet(packet));
+        }
+        if writer_active || self.is_live() {
+            Ok(TryRead::WouldBlock)
+        } else {
+            Ok(TryRead::EndOfDataset)
+        }
+    }
+
+    /// 读取下一个数据包，负载缓冲区来自读取器的缓冲区池
+    ///
+    /// 返回的数据包释放时负载缓冲区回到缓冲区池，供后续读取复用，
+    /// 适合逐个处理后即丢弃数据包的长时间读取循环。缓冲区池大小由
+    /// `ReaderConfig::buffer_pool_size` 控制。
+    ///
+    /// # 返回
+    /// - `Ok(Some(packet))` - 成功读取到数据包
+    /// - `Ok(None)` - 到达文件末尾，无更多数据包
+    /// - `Err(error)` - 读取过程中发生错误
+    pub fn read_pooled_packet(
+        &mut self,
+    ) -> PcapResult<Option<PooledPacket>> {
+        let pool = self.buffer_pool.clone();
+        let packet = self.read_next_with(|reader| {
+            reader.read_packet_with(|length| {
+                pool.acquire(length)
+            })
+        })?;
+        Ok(packet
+            .map(|packet| PooledPacket::new(packet, pool)))
+    }
+
+    /// 缓冲区池中空闲的负载缓冲区数量
+    pub fn pooled_buffer_count(&self) -> usize {
+        self.buffer_pool.available()
+    }
+
+    /// 使用指定的读取方式读取下一个数据包，当前文件读完时切换到下一个文件
+    fn read_next_with<F>(
+        &mut self,
+        mut read: F,
+    ) -> PcapResult<Option<ValidatedPacket>>
+    where
+        F: FnMut(
+            &mut PcapFileReader,
+        )
+            -> PcapResult<Option<ValidatedPacket>>,
+    {
         self.initialize()?;
 
         // 确保当前文件已打开
@@ -289,7 +870,7 @@ impl PcapReader {
             if let Some(ref mut reader) =
                 self.current_reader
             {
-                match reader.read_packet() {
+                match read(reader) {
                     Ok(Some(result)) => {
                         self.current_position += 1;
                         return Ok(Some(result));
@@ -329,21 +910,68 @@ impl PcapReader {
         }
     }
 
-    /// 批量读取多个数据包（默认方法，带校验结果）
+    /// 读取下一个数据包并解码为 `T`
     ///
-    /// # 参数
-    /// - `count` - 要读取的数据包数量
+    /// 根据数据包的链路类型从解码器注册表中选择输出类型为 `T` 的解码器。
     ///
     /// # 返回
-    pub fn read_packets(
+    /// - `Ok(Some(value))` - 成功读取并解码
+    /// - `Ok(None)` - 到达文件末尾，无更多数据包
+    /// - `Err(error)` - 读取失败、未注册解码器或解码失败
+    pub fn read_decoded<T: 'static>(
         &mut self,
-        count: usize,
-    ) -> PcapResult<Vec<ValidatedPacket>> {
-        self.initialize()?;
+    ) -> PcapResult<Option<T>> {
+        match self.read_packet()? {
+            Some(result) => self
+                .decoders
+                .decode(&result.packet)
+                .map(Some),
+            None => Ok(None),
+        }
+    }
 
-        let mut results = Vec::with_capacity(count);
+    /// 为指定链路类型登记负载解码器
+    pub fn register_decoder<D: PayloadDecoder + 'static>(
+        &mut self,
+        link_type: LinkType,
+        decoder: D,
+    ) {
+        self.decoders.register(link_type, decoder);
+    }
 
-        // 批量读取指定数量的数据包
+    /// 替换解码器注册表
+    pub fn set_decoder_registry(
+        &mut self,
+        registry: DecoderRegistry,
+    ) {
+        self.decoders = registry;
+    }
+
+    /// 获取解码器注册表
+    pub fn decoders(&self) -> &DecoderRegistry {
+        &self.decoders
+    }
+
+    /// 获取解码器注册表的可变引用
+    pub fn decoders_mut(&mut self) -> &mut DecoderRegistry {
+        &mut self.decoders
+    }
+
+    /// 批量读取多个数据包（默认方法，带校验结果）
+    ///
+    /// # 参数
+    /// - `count` - 要读取的数据包数量
+    ///
+    /// # 返回
+    pub fn read_packets(
+        &mut self,
+        count: usize,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        self.initialize()?;
+
+        let mut results = Vec::with_capacity(count);
+
+        // 批量读取指定数量的数据包
         for _ in 0..count {
             if let Some(result) = self.read_packet()? {
                 results.push(result);
@@ -383,6 +1011,95 @@ impl PcapReader {
         Ok(packets)
     }
 
+    /// 按内存预算读取一批数据包
+    ///
+    /// 从当前位置连续读取数据包，直到累计负载字节数将超过 `max_bytes`。
+    /// 下一个数据包的大小通过索引预先获取，因此不会多读。
+    /// 单个数据包超过预算时，该批次只包含这一个数据包。
+    ///
+    /// # 参数
+    /// - `max_bytes` - 单批次负载字节数上限
+    ///
+    /// # 返回
+    /// 返回读取到的数据包，空列表表示已到达末尾
+    pub fn read_chunk(
+        &mut self,
+        max_bytes: usize,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        self.initialize()?;
+
+        if max_bytes == 0 {
+            return Err(PcapError::InvalidArgument(
+                "批次内存预算必须大于0".to_string(),
+            ));
+        }
+
+        let mut chunk = Vec::new();
+        let mut chunk_bytes = 0usize;
+
+        loop {
+            // 通过索引预估下一个数据包大小
+            let next_size = self
+                .index_manager
+                .get_index()
+                .and_then(|index| {
+                    index.locate_global_index(
+                        self.current_position,
+                    )
+                })
+                .map(|(_, entry)| {
+                    entry.packet_size as usize
+                });
+
+            if let Some(size) = next_size {
+                if !chunk.is_empty()
+                    && chunk_bytes + size > max_bytes
+                {
+                    break;
+                }
+            }
+
+            match self.read_packet()? {
+                Some(packet) => {
+                    chunk_bytes += packet.packet_length();
+                    chunk.push(packet);
+                    if chunk_bytes >= max_bytes {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+
+        Ok(chunk)
+    }
+
+    /// 按内存预算分批迭代剩余数据包
+    ///
+    /// 每次迭代返回一批累计负载不超过 `max_bytes` 的数据包，
+    /// 适用于在有限内存中批量处理超大数据集。
+    ///
+    /// # 参数
+    /// - `max_bytes` - 单批次负载字节数上限
+    pub fn read_chunks(
+        &mut self,
+        max_bytes: usize,
+    ) -> PcapResult<ChunkIter<'_>> {
+        self.initialize()?;
+
+        if max_bytes == 0 {
+            return Err(PcapError::InvalidArgument(
+                "批次内存预算必须大于0".to_string(),
+            ));
+        }
+
+        Ok(ChunkIter {
+            reader: self,
+            max_bytes,
+            finished: false,
+        })
+    }
+
     /// 重置读取器到数据集开始位置
     ///
     /// 将读取器重置到数据集的开始位置，后续读取将从第一个数据包开始。
@@ -400,16 +1117,15 @@ impl PcapReader {
         self.current_reader = None;
 
         // 重新打开第一个文件（如果存在）
-        let index = self
-            .index_manager
-            .get_index()
-            .ok_or_else(|| {
+        let query = self.index_manager.query().ok_or_else(
+            || {
                 PcapError::InvalidState(
                     "索引未加载".to_string(),
                 )
-            })?;
+            },
+        )?;
 
-        if !index.data_files.files.is_empty() {
+        if query.file_count() > 0 {
             self.open_file(0)?;
         }
 
@@ -429,6 +1145,14 @@ impl PcapReader {
         &mut self.index_manager
     }
 
+    /// 创建组合条件查询
+    ///
+    /// 时间范围、负载大小和通道条件可以任意组合，查询时优先使用选择性最高的索引，
+    /// 见 [`PacketQuery`]。
+    pub fn query(&mut self) -> PacketQuery<'_> {
+        PacketQuery::new(self)
+    }
+
     /// 按时间戳查找数据包位置
     ///
     /// # 参数
@@ -446,29 +1170,32 @@ impl PcapReader {
     > {
         self.initialize()?;
 
-        let index = self
-            .index_manager
-            .get_index()
-            .ok_or_else(|| {
-                PcapError::InvalidState(
-                    "索引未加载".to_string(),
-                )
-            })?;
-
         // 在时间戳索引中查找最接近的条目
-        let mut closest_entry = None;
-        let mut min_diff = u64::MAX;
-
-        for (ts, pointer) in &index.timestamp_index {
-            let diff = (*ts).abs_diff(timestamp_ns);
+        self.locate_timestamp(
+            timestamp_ns,
+            SeekMode::Nearest,
+        )
+    }
 
-            if diff < min_diff {
-                min_diff = diff;
-                closest_entry = Some(pointer.clone());
-            }
+    /// 按查找模式定位时间戳
+    ///
+    /// 启用 `mmap_timestamp_index` 时在磁盘上的时间戳表中查找，否则使用内存索引。
+    fn locate_timestamp(
+        &mut self,
+        timestamp_ns: u64,
+        mode: SeekMode,
+    ) -> PcapResult<
+        Option<
+            crate::business::index::types::TimestampPointer,
+        >,
+    > {
+        if self.index_manager.get_index().is_none() {
+            return Err(PcapError::InvalidState(
+                "索引未加载".to_string(),
+            ));
         }
-
-        Ok(closest_entry)
+        self.index_manager
+            .find_packet_with_mode(timestamp_ns, mode)
     }
 
     /// 按时间范围读取数据包
@@ -479,14 +1206,62 @@ impl PcapReader {
     ///
     /// # 返回
     /// 返回指定时间范围内的所有数据包
+    ///
+    /// # 错误
+    /// 打开或切换数据文件失败时返回错误；单个数据包读取失败时记录警告后跳过
     pub fn read_packets_by_time_range(
         &mut self,
         start_timestamp_ns: u64,
         end_timestamp_ns: u64,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        let mut result_packets = Vec::new();
+
+        // 按时间顺序读取数据包
+        let mut packets = self.iter_time_range(
+            start_timestamp_ns,
+            end_timestamp_ns,
+        )?;
+        while let Some(result) = packets.read_next()? {
+            match result {
+                Ok(packet) => result_packets.push(packet),
+                Err(e) => {
+                    warn!("读取数据包失败: {}", e);
+                    // 继续处理其他数据包
+                }
+            }
+        }
+
+        Ok(result_packets)
+    }
+
+    /// 按时间范围并行读取数据包
+    ///
+    /// 将范围内的索引指针按文件分组，分配给多个工作线程，每个线程使用独立的
+    /// 文件读取器读取各自的文件，最后按时间戳顺序合并结果。适用于跨多个文件的
+    /// 大范围提取，结果与 `read_packets_by_time_range` 相同。
+    ///
+    /// # 参数
+    /// - `start_timestamp_ns` - 开始时间戳（纳秒）
+    /// - `end_timestamp_ns` - 结束时间戳（纳秒）
+    /// - `threads` - 工作线程数，0 表示使用可用的 CPU 核心数
+    ///
+    /// # 返回
+    /// 返回指定时间范围内按时间戳排序的所有数据包
+    ///
+    /// # 错误
+    /// 打开数据文件失败时返回第一个错误，工作线程异常退出时返回 `PcapError::Unknown`；
+    /// 单个数据包读取失败时记录警告后跳过
+    pub fn read_packets_by_time_range_parallel(
+        &mut self,
+        start_timestamp_ns: u64,
+        end_timestamp_ns: u64,
+        threads: usize,
     ) -> PcapResult<Vec<ValidatedPacket>> {
         self.initialize()?;
 
-        let pointers = {
+        // 按文件分组索引指针，组内保持字节偏移顺序以便顺序读取
+        let mut groups: Vec<FileGroup> = Vec::new();
+        {
             let index = self
                 .index_manager
                 .get_index()
@@ -496,104 +1271,217 @@ impl PcapReader {
                     )
                 })?;
 
-            index
-                .get_packets_in_range(
-                    start_timestamp_ns,
-                    end_timestamp_ns,
-                )
-                .into_iter()
-                .cloned()
-                .collect::<Vec<_>>()
-        };
-
-        let mut result_packets = Vec::new();
-        let mut current_file_index = None;
+            let mut by_file: BTreeMap<
+                usize,
+                Vec<PacketIndexEntry>,
+            > = BTreeMap::new();
+            for pointer in index.get_packets_in_range(
+                start_timestamp_ns,
+                end_timestamp_ns,
+            ) {
+                by_file
+                    .entry(pointer.file_index)
+                    .or_default()
+                    .push(pointer.entry.clone());
+            }
 
-        // 按时间顺序读取数据包
-        for pointer in pointers {
-            // 检查是否需要切换文件
-            if current_file_index
-                != Some(pointer.file_index)
-            {
-                self.open_file(pointer.file_index)?;
-                current_file_index =
-                    Some(pointer.file_index);
+            for (file_index, mut entries) in by_file {
+                let file = &index.files()[file_index];
+                entries
+                    .sort_by_key(|entry| entry.byte_offset);
+                groups.push((
+                    file.resolve_path(&self.dataset_path),
+                    entries,
+                ));
             }
+        }
 
-            // 确保文件已打开
-            self.ensure_current_file_open()?;
+        if groups.is_empty() {
+            return Ok(Vec::new());
+        }
 
-            // 读取指定位置的数据包
-            let reader = self
-                .current_reader
-                .as_mut()
-                .ok_or_else(|| {
-                    PcapError::InvalidState(
-                        "当前文件读取器未初始化"
-                            .to_string(),
-                    )
-                })?;
-            let packet_result = reader
-                .read_packet_at(pointer.entry.byte_offset);
+        let threads = if threads == 0 {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        } else {
+            threads
+        }
+        .min(groups.len());
 
-            match packet_result {
-                Ok(packet) => {
-                    // 验证时间戳是否在范围内
-                    let packet_timestamp =
-                        packet.packet.get_timestamp_ns();
-                    if packet_timestamp
-                        >= start_timestamp_ns
-                        && packet_timestamp
-                            <= end_timestamp_ns
-                    {
-                        result_packets.push(packet);
-                    }
+        // 轮流分配文件，使各线程的文件数大致相同
+        let mut assignments: Vec<Vec<FileGroup>> =
+            (0..threads).map(|_| Vec::new()).collect();
+        for (i, group) in groups.into_iter().enumerate() {
+            assignments[i % threads].push(group);
+        }
+
+        let configuration = &self.configuration;
+        let range =
+            &(start_timestamp_ns..=end_timestamp_ns);
+        let mut results: Vec<(u64, u64, ValidatedPacket)> =
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = assignments
+                    .into_iter()
+                    .map(|assignment| {
+                        scope.spawn(move || {
+                            Self::read_file_groups(
+                                configuration,
+                                assignment,
+                                range,
+                            )
+                        })
+                    })
+                    .collect();
+
+                let mut results = Vec::new();
+                for handle in handles {
+                    let packets =
+                        handle.join().map_err(|_| {
+                            PcapError::Unknown(
+                                "并行读取线程异常退出"
+                                    .to_string(),
+                            )
+                        })??;
+                    results.extend(packets);
                 }
-                Err(e) => {
-                    warn!("读取数据包失败: {}", e);
-                    // 继续处理其他数据包
+                Ok::<_, PcapError>(results)
+            })?;
+
+        // 按时间戳合并，时间戳相同时按全局序号保持写入顺序
+        results.sort_by_key(
+            |(timestamp_ns, global_index, _)| {
+                (*timestamp_ns, *global_index)
+            },
+        );
+
+        debug!(
+            "并行读取完成，{threads} 个线程共读取 {} 个数据包",
+            results.len()
+        );
+        Ok(results
+            .into_iter()
+            .map(|(_, _, packet)| packet)
+            .collect())
+    }
+
+    /// 并行读取的工作线程：依次读取分配到的文件
+    ///
+    /// 打开文件失败时返回错误；与顺序读取一样跳过读取失败和时间戳不在范围内的数据包。
+    fn read_file_groups(
+        configuration: &ReaderConfig,
+        groups: Vec<FileGroup>,
+        range: &RangeInclusive<u64>,
+    ) -> PcapResult<Vec<(u64, u64, ValidatedPacket)>> {
+        let mut packets = Vec::new();
+        for (file_path, entries) in groups {
+            let mut reader =
+                PcapFileReader::new(configuration.clone());
+            reader.open(&file_path)?;
+
+            for entry in entries {
+                match reader.read_packet_at(
+                    entry.byte_offset,
+                    entry.timestamp_ns,
+                ) {
+                    Ok(packet) => {
+                        if range.contains(
+                            &packet
+                                .packet
+                                .get_timestamp_ns(),
+                        ) {
+                            packets.push((
+                                entry.timestamp_ns,
+                                entry.global_index,
+                                packet,
+                            ));
+                        }
+                    }
+                    Err(e) => {
+                        warn!("读取数据包失败: {}", e);
+                    }
                 }
             }
         }
-
-        Ok(result_packets)
+        Ok(packets)
     }
 
-    /// 获取缓存统计信息
-    pub fn get_cache_stats(&self) -> CacheStats {
-        self.file_info_cache.get_cache_stats()
+    /// 在时间范围内的数据包负载中搜索字节序列
+    ///
+    /// 使用默认搜索选项（全部 CPU 核心并行、不抽样、不限制命中数量），
+    /// 见 [`PcapReader::search_payload_with`]。
+    pub fn search_payload<R: RangeBounds<u64>>(
+        &mut self,
+        pattern: &[u8],
+        range: R,
+    ) -> PcapResult<Vec<PayloadMatch>> {
+        self.search_payload_with(
+            pattern,
+            range,
+            &SearchOptions::default(),
+        )
     }
 
-    /// 清理缓存
-    pub fn clear_cache(&mut self) -> PcapResult<()> {
-        let _ = self.file_info_cache.clear();
-        debug!("缓存已清理");
-        Ok(())
+    /// 按指定选项在时间范围内的数据包负载中搜索字节序列
+    ///
+    /// 见 [`PcapReader::search_payload_matching`]。
+    ///
+    /// # 错误
+    /// `pattern` 为空时返回 `PcapError::InvalidArgument`
+    pub fn search_payload_with<R: RangeBounds<u64>>(
+        &mut self,
+        pattern: &[u8],
+        range: R,
+        options: &SearchOptions,
+    ) -> PcapResult<Vec<PayloadMatch>> {
+        let matcher = BytesMatcher::new(pattern)?;
+        self.search_payload_matching(
+            &matcher, range, options,
+        )
     }
 
-    /// 跳转到指定时间戳（纳秒）
+    /// 使用自定义匹配器在时间范围内的数据包负载中搜索
     ///
-    /// 返回实际定位到的时间戳。如果精确匹配不存在，返回时间戳后面最接近的数据包。
+    /// 各数据文件分配给多个线程并行扫描，每个线程使用独立的文件读取器，
+    /// 不改变读取器当前的读取位置。读取失败的数据包记录警告后跳过。
     ///
     /// # 参数
-    /// - `timestamp_ns` - 目标时间戳（纳秒）
+    /// - `matcher` - 负载匹配器，如 `BytesMatcher`、`MaskedMatcher`、`RegexMatcher`
+    /// - `range` - 时间戳范围（纳秒），`..` 表示整个数据集
+    /// - `options` - 搜索选项
     ///
     /// # 返回
-    /// - `Ok(actual_timestamp)` - 成功跳转，返回实际定位到的时间戳
-    /// - `Err(error)` - 未找到数据包或发生错误
-    pub fn seek_to_timestamp(
+    /// 按全局序号排序的命中位置，每个数据包最多命中一次
+    pub fn search_payload_matching<R: RangeBounds<u64>>(
         &mut self,
-        timestamp_ns: u64,
-    ) -> PcapResult<u64> {
+        matcher: &dyn Matcher,
+        range: R,
+        options: &SearchOptions,
+    ) -> PcapResult<Vec<PayloadMatch>> {
+        options
+            .validate()
+            .map_err(PcapError::InvalidArgument)?;
         self.initialize()?;
 
-        // 1. 先提取所需信息，避免借用冲突
-        let (
-            actual_ts,
-            file_index,
-            byte_offset,
-            packet_offset,
-        ) = {
+        let start_ns = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => {
+                start.saturating_add(1)
+            }
+            Bound::Unbounded => 0,
+        };
+        let end_ns = match range.end_bound() {
+            Bound::Included(&end) => end,
+            Bound::Excluded(&end) => {
+                match end.checked_sub(1) {
+                    Some(end) => end,
+                    None => return Ok(Vec::new()),
+                }
+            }
+            Bound::Unbounded => u64::MAX,
+        };
+
+        let groups: Vec<(usize, FileGroup)> = {
             let index = self
                 .index_manager
                 .get_index()
@@ -602,56 +1490,72 @@ impl PcapReader {
                         "索引未加载".to_string(),
                     )
                 })?;
-
-            // 尝试精确匹配
-            let (actual_ts, pointer) = if let Some(ptr) =
-                index.find_packet_by_timestamp(timestamp_ns)
-            {
-                (timestamp_ns, ptr.clone())
-            } else {
-                // 查找 >= target 的最小时间戳
-                Self::find_timestamp_ge(&index.timestamp_index, timestamp_ns)
-                    .ok_or_else(|| PcapError::InvalidArgument(
-                        format!("未找到时间戳 >= {timestamp_ns} 的数据包")
-                    ))?
-            };
-
-            // 计算文件内的序号
-            let file_index_data =
-                &index.data_files.files[pointer.file_index];
-            let packet_offset = file_index_data
-                .data_packets
-                .iter()
-                .position(|p| {
-                    p.timestamp_ns
-                        == pointer.entry.timestamp_ns
+            index
+                .files_in_range(start_ns, end_ns)
+                .into_iter()
+                .map(|(file_index, file)| {
+                    let entries = file
+                        .data_packets
+                        .iter()
+                        .filter(|entry| {
+                            (start_ns..=end_ns).contains(
+                                &entry.timestamp_ns,
+                            ) && entry.global_index
+                                % options.sample_interval
+                                == 0
+                        })
+                        .cloned()
+                        .collect();
+                    (
+                        file_index,
+                        (
+                            file.resolve_path(
+                                &self.dataset_path,
+                            ),
+                            entries,
+                        ),
+                    )
                 })
-                .unwrap_or(0);
-
-            (
-                actual_ts,
-                pointer.file_index,
-                pointer.entry.byte_offset,
-                packet_offset,
-            )
+                .collect()
         };
 
-        // 2. 打开对应文件
-        self.open_file(file_index)?;
+        let matches = search::scan_file_groups(
+            &self.configuration,
+            groups,
+            matcher,
+            options,
+        );
+        debug!(
+            "负载搜索完成，命中 {} 个数据包",
+            matches.len()
+        );
+        Ok(matches)
+    }
 
-        // 3. seek 到字节偏移
-        if let Some(reader) = self.current_reader.as_mut() {
-            reader.seek_to(byte_offset)?;
-        } else {
-            return Err(PcapError::InvalidState(
-                "文件未打开".to_string(),
+    /// 按固定时长的时间窗口聚合数据集
+    ///
+    /// 直接遍历索引条目计算，不读取数据文件，也不改变读取位置。
+    /// 第一个窗口从第一个数据包的时间戳开始，没有数据包的窗口同样保留。
+    ///
+    /// # 参数
+    /// - `window` - 窗口时长，必须大于 0
+    /// - `aggregations` - 需要计算的指标，未请求的指标在结果中为 `None`
+    ///
+    /// # 返回
+    /// 按时间顺序排列的窗口聚合结果，数据集为空时返回空列表
+    pub fn aggregate_windows(
+        &mut self,
+        window: Duration,
+        aggregations: &[Aggregation],
+    ) -> PcapResult<Vec<WindowAggregate>> {
+        let window_ns = duration_to_nanos(window);
+        if window_ns == 0 {
+            return Err(PcapError::InvalidArgument(
+                "聚合窗口时长必须大于0".to_string(),
             ));
         }
+        self.initialize()?;
 
-        // 4. 更新状态
-        self.current_file_index = file_index;
-
-        // 计算全局位置
         let index = self
             .index_manager
             .get_index()
@@ -660,31 +1564,283 @@ impl PcapReader {
                     "索引未加载".to_string(),
                 )
             })?;
-        self.current_position = self
-            .calculate_global_position(
-                index,
-                file_index,
-                packet_offset,
-            );
-
-        info!("已跳转到时间戳: {timestamp_ns}ns (实际: {actual_ts}ns), 全局位置: {}", 
-            self.current_position);
-
-        Ok(actual_ts)
+        Ok(aggregate::aggregate_index(
+            index,
+            window_ns,
+            aggregations,
+        ))
     }
 
-    /// 跳转到指定索引的数据包（从0开始）
+    /// 按时间范围惰性迭代数据包
+    ///
+    /// 与 `read_packets_by_time_range` 不同，迭代器仅预先收集索引指针，
+    /// 数据包在迭代时逐个读取，文件按需打开，适用于大范围查询。
     ///
     /// # 参数
-    /// - `packet_index` - 目标数据包的全局索引（从0开始）
-    pub fn seek_to_packet(
-        &mut self,
-        packet_index: usize,
-    ) -> PcapResult<()> {
+    /// - `start_timestamp_ns` - 开始时间戳（纳秒）
+    /// - `end_timestamp_ns` - 结束时间戳（纳秒）
+    ///
+    /// # 返回
+    /// 返回按时间顺序产出 `PcapResult<ValidatedPacket>` 的迭代器
+    pub fn iter_time_range(
+        &mut self,
+        start_timestamp_ns: u64,
+        end_timestamp_ns: u64,
+    ) -> PcapResult<TimeRangeIter<'_>> {
+        self.initialize()?;
+
+        let pointers = {
+            let index = self
+                .index_manager
+                .get_index()
+                .ok_or_else(|| {
+                    PcapError::InvalidState(
+                        "索引未加载".to_string(),
+                    )
+                })?;
+
+            index.get_packets_in_range(
+                start_timestamp_ns,
+                end_timestamp_ns,
+            )
+        };
+
+        Ok(TimeRangeIter {
+            reader: self,
+            pointers: pointers.into_iter(),
+            start_timestamp_ns,
+            end_timestamp_ns,
+        })
+    }
+
+    /// 获取缓存统计信息
+    pub fn get_cache_stats(&self) -> CacheStats {
+        self.file_info_cache.get_cache_stats()
+    }
+
+    /// 当前打开的数据文件数（含句柄池中的空闲文件）
+    pub fn open_file_count(&self) -> usize {
+        self.idle_readers.len()
+            + usize::from(self.current_reader.is_some())
+    }
+
+    /// 立即检查数据集目录变化，清除失效的文件信息缓存
+    ///
+    /// 仅在 `watch_dataset` 启用时生效，返回被清除的条目数。
+    pub fn poll_dataset_changes(&self) -> usize {
+        self.file_info_cache.poll_changes()
+    }
+
+    /// 获取数据包缓存统计信息
+    pub fn get_packet_cache_stats(&self) -> CacheStats {
+        self.packet_cache.get_cache_stats()
+    }
+
+    /// 清理缓存
+    pub fn clear_cache(&mut self) -> PcapResult<()> {
+        let _ = self.file_info_cache.clear();
+        self.packet_cache.clear();
+        debug!("缓存已清理");
+        Ok(())
+    }
+
+    /// 跳转到指定时间戳（纳秒）
+    ///
+    /// 返回实际定位到的时间戳。如果精确匹配不存在，返回时间戳后面最接近的数据包。
+    ///
+    /// # 参数
+    /// - `timestamp_ns` - 目标时间戳（纳秒）
+    ///
+    /// # 返回
+    /// - `Ok(actual_timestamp)` - 成功跳转，返回实际定位到的时间戳
+    /// - `Err(error)` - 未找到数据包或发生错误
+    pub fn seek_to_timestamp(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> PcapResult<u64> {
+        self.seek_to_timestamp_with_mode(
+            timestamp_ns,
+            SeekMode::Ceiling,
+        )
+    }
+
+    /// 按指定查找模式跳转到时间戳（纳秒）
+    ///
+    /// # 参数
+    /// - `timestamp_ns` - 目标时间戳（纳秒）
+    /// - `mode` - 查找模式，决定无精确匹配时的取舍方式
+    ///
+    /// # 返回
+    /// - `Ok(actual_timestamp)` - 成功跳转，返回实际定位到的时间戳
+    /// - `Err(error)` - 未找到满足模式的数据包或发生错误
+    pub fn seek_to_timestamp_with_mode(
+        &mut self,
+        timestamp_ns: u64,
+        mode: SeekMode,
+    ) -> PcapResult<u64> {
+        self.initialize()?;
+
+        // 1. 先提取所需信息，避免借用冲突
+        let (
+            actual_ts,
+            file_index,
+            byte_offset,
+            global_index,
+        ) = {
+            let pointer = self
+                .locate_timestamp(timestamp_ns, mode)?
+                .ok_or_else(|| {
+                    PcapError::InvalidArgument(format!(
+                        "未找到满足 {mode:?} 模式的时间戳 {timestamp_ns} 对应数据包"
+                    ))
+                })?;
+            let actual_ts = pointer.entry.timestamp_ns;
+
+            (
+                actual_ts,
+                pointer.file_index,
+                pointer.entry.byte_offset,
+                pointer.entry.global_index,
+            )
+        };
+
+        // 2. 打开对应文件
+        self.open_file(file_index)?;
+
+        // 3. seek 到字节偏移
+        if let Some(reader) = self.current_reader.as_mut() {
+            reader.seek_to_entry(byte_offset, actual_ts)?;
+        } else {
+            return Err(PcapError::InvalidState(
+                "文件未打开".to_string(),
+            ));
+        }
+
+        // 4. 更新状态（全局序号直接取自索引条目）
+        self.current_file_index = file_index;
+        self.current_position = global_index;
+
+        info!("已跳转到时间戳: {timestamp_ns}ns (实际: {actual_ts}ns), 全局位置: {}", 
+            self.current_position);
+
+        Ok(actual_ts)
+    }
+
+    /// 跳转到相对数据集起始时间的偏移处
+    ///
+    /// 以数据集第一个数据包的时间戳为起点，定位到偏移之后最近的数据包，
+    /// 适用于"跳到第 5 分钟"这类回放操作。
+    ///
+    /// # 参数
+    /// - `offset` - 相对数据集起始时间的偏移
+    ///
+    /// # 返回
+    /// - `Ok(actual_offset)` - 成功跳转，返回实际定位到的数据包相对起始时间的偏移
+    /// - `Err(error)` - 偏移超出数据集时间范围或发生错误
+    pub fn seek_to_offset_from_start(
+        &mut self,
+        offset: Duration,
+    ) -> PcapResult<Duration> {
+        let start_timestamp =
+            self.dataset_start_timestamp()?;
+        let target = start_timestamp
+            .saturating_add(duration_to_nanos(offset));
+
+        let actual_ts = self.seek_to_timestamp(target)?;
+        Ok(Duration::from_nanos(
+            actual_ts.saturating_sub(start_timestamp),
+        ))
+    }
+
+    /// 从当前位置读取指定时长内的数据包
+    ///
+    /// 以下一个数据包的时间戳为窗口起点，读取时间戳落在 `[起点, 起点 + duration)`
+    /// 内的连续数据包，读取位置停在窗口之后的第一个数据包。
+    ///
+    /// # 参数
+    /// - `duration` - 读取窗口时长
+    ///
+    /// # 返回
+    /// 返回窗口内的数据包，已到达末尾时返回空列表
+    pub fn read_packets_for(
+        &mut self,
+        duration: Duration,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        self.initialize()?;
+
+        let mut packets = Vec::new();
+        let mut window_end = None;
+
+        // 通过索引预先查看下一个数据包的时间戳，避免读过窗口
+        while let Some(next_timestamp) = self
+            .index_manager
+            .get_index()
+            .and_then(|index| {
+                index.locate_global_index(
+                    self.current_position,
+                )
+            })
+            .map(|(_, entry)| entry.timestamp_ns)
+        {
+            let end =
+                *window_end.get_or_insert_with(|| {
+                    next_timestamp.saturating_add(
+                        duration_to_nanos(duration),
+                    )
+                });
+            if next_timestamp >= end {
+                break;
+            }
+
+            match self.read_packet()? {
+                Some(packet) => packets.push(packet),
+                None => break,
+            }
+        }
+
+        debug!("按时长读取了 {} 个数据包", packets.len());
+        Ok(packets)
+    }
+
+    /// 数据集起始时间戳（纳秒）
+    fn dataset_start_timestamp(
+        &mut self,
+    ) -> PcapResult<u64> {
+        self.initialize()?;
+
+        let index = self
+            .index_manager
+            .get_index()
+            .ok_or_else(|| {
+                PcapError::InvalidState(
+                    "索引未加载".to_string(),
+                )
+            })?;
+        if index.total_packets == 0 {
+            return Err(PcapError::InvalidState(
+                "数据集中没有数据包".to_string(),
+            ));
+        }
+        Ok(index.start_timestamp)
+    }
+
+    /// 跳转到指定索引的数据包（从0开始）
+    ///
+    /// # 参数
+    /// - `packet_index` - 目标数据包的全局索引（从0开始）
+    pub fn seek_to_packet(
+        &mut self,
+        packet_index: usize,
+    ) -> PcapResult<()> {
         self.initialize()?;
 
         // 1. 先提取所需信息，避免借用冲突
-        let (target_file_idx, byte_offset, packet_offset) = {
+        let (
+            target_file_idx,
+            byte_offset,
+            timestamp_ns,
+            packet_offset,
+        ) = {
             let index = self
                 .index_manager
                 .get_index()
@@ -702,39 +1858,28 @@ impl PcapReader {
                 ));
             }
 
-            // 遍历文件，找到目标文件和文件内偏移
-            let mut accumulated = 0usize;
-            let mut target_file_idx = 0;
-            let mut packet_offset = 0;
-
-            for (file_idx, file) in
-                index.data_files.files.iter().enumerate()
-            {
-                let next_accumulated = accumulated
-                    + file.packet_count as usize;
-                if packet_index < next_accumulated {
-                    target_file_idx = file_idx;
-                    packet_offset =
-                        packet_index - accumulated;
-                    break;
-                }
-                accumulated = next_accumulated;
-            }
-
-            // 获取数据包条目
-            let file =
-                &index.data_files.files[target_file_idx];
-            let packet_entry =
-                &file.data_packets[packet_offset];
-            let byte_offset = packet_entry.byte_offset;
+            // 二分查找目标文件和文件内偏移
+            let (target_file_idx, packet_entry) = index
+                .locate_global_index(packet_index as u64)
+                .ok_or_else(|| {
+                    PcapError::InvalidState(format!(
+                        "索引中缺少数据包条目: {packet_index}"
+                    ))
+                })?;
 
-            (target_file_idx, byte_offset, packet_offset)
+            (
+                target_file_idx,
+                packet_entry.byte_offset,
+                packet_entry.timestamp_ns,
+                packet_entry.packet_index,
+            )
         };
 
         // 2. 打开文件并 seek
         self.open_file(target_file_idx)?;
         if let Some(reader) = self.current_reader.as_mut() {
-            reader.seek_to(byte_offset)?;
+            reader
+                .seek_to_entry(byte_offset, timestamp_ns)?;
         } else {
             return Err(PcapError::InvalidState(
                 "文件未打开".to_string(),
@@ -750,6 +1895,117 @@ impl PcapReader {
         Ok(())
     }
 
+    /// 为指定位置的数据包添加书签
+    ///
+    /// 书签保存在数据集目录的书签文件中，同名书签被替换。
+    ///
+    /// # 参数
+    /// - `name` - 书签名称
+    /// - `position` - 数据包的全局索引（从0开始）
+    pub fn add_bookmark(
+        &mut self,
+        name: &str,
+        position: u64,
+    ) -> PcapResult<Bookmark> {
+        if name.trim().is_empty() {
+            return Err(PcapError::InvalidArgument(
+                "书签名称不能为空".to_string(),
+            ));
+        }
+        self.ensure_writable("添加书签")?;
+        self.initialize()?;
+
+        let timestamp_ns = self
+            .index_manager
+            .query()
+            .and_then(|query| query.timestamp_at(position))
+            .ok_or_else(|| {
+                PcapError::InvalidArgument(format!(
+                    "书签位置 {position} 超出范围"
+                ))
+            })?;
+
+        let bookmark =
+            Bookmark::new(name, position, timestamp_ns);
+        let mut store =
+            BookmarkStore::load(&self.dataset_path)?;
+        store.insert(bookmark.clone());
+        store.save(&self.dataset_path)?;
+
+        info!("已添加书签: {name} -> 位置 {position}");
+        Ok(bookmark)
+    }
+
+    /// 删除书签，返回书签是否存在
+    pub fn remove_bookmark(
+        &mut self,
+        name: &str,
+    ) -> PcapResult<bool> {
+        self.ensure_writable("删除书签")?;
+        let mut store =
+            BookmarkStore::load(&self.dataset_path)?;
+        if store.remove(name).is_none() {
+            return Ok(false);
+        }
+        store.save(&self.dataset_path)?;
+        Ok(true)
+    }
+
+    /// 获取数据集的全部书签（按名称排序）
+    ///
+    /// 每次调用都重新读取书签文件，可以看到其他读取器添加的书签。
+    pub fn bookmarks(&self) -> PcapResult<Vec<Bookmark>> {
+        Ok(BookmarkStore::load(&self.dataset_path)?
+            .iter()
+            .cloned()
+            .collect())
+    }
+
+    /// 获取写入器插入的全部事件标记，按插入顺序返回
+    pub fn list_markers(&self) -> PcapResult<Vec<Marker>> {
+        markers::load_markers(&self.dataset_path)
+    }
+
+    /// 跳转到书签位置
+    ///
+    /// 书签位置的数据包时间戳与记录不一致（数据集被修改后索引重建）时，
+    /// 按书签记录的时间戳重新定位。
+    ///
+    /// # 返回
+    /// - `Ok(position)` - 成功跳转，返回实际定位到的全局索引
+    /// - `Err(error)` - 书签不存在或跳转失败
+    pub fn seek_to_bookmark(
+        &mut self,
+        name: &str,
+    ) -> PcapResult<u64> {
+        let bookmark =
+            BookmarkStore::load(&self.dataset_path)?
+                .get(name)
+                .cloned()
+                .ok_or_else(|| {
+                    PcapError::InvalidArgument(format!(
+                        "书签不存在: {name}"
+                    ))
+                })?;
+        self.initialize()?;
+
+        let timestamp_matches =
+            self.index_manager.query().and_then(|query| {
+                query.timestamp_at(bookmark.position)
+            }) == Some(bookmark.timestamp_ns);
+        if timestamp_matches {
+            self.seek_to_packet(
+                bookmark.position as usize,
+            )?;
+        } else {
+            warn!(
+                "书签 {name} 的位置与时间戳不一致，按时间戳重新定位"
+            );
+            self.seek_to_timestamp(bookmark.timestamp_ns)?;
+        }
+        Ok(self.current_position)
+    }
+
     /// 检查是否已到达文件末尾
     pub fn is_eof(&self) -> bool {
         if let Some(index) = self.index_manager.get_index()
@@ -821,55 +2077,6 @@ impl PcapReader {
     // 私有方法
     // =================================================================
 
-    /// 计算指定文件索引和文件内数据包偏移对应的全局数据包位置
-    fn calculate_global_position(
-        &self,
-        index: &crate::business::index::types::PidxIndex,
-        file_index: usize,
-        packet_offset_in_file: usize,
-    ) -> u64 {
-        let mut position = 0u64;
-        for (idx, file) in
-            index.data_files.files.iter().enumerate()
-        {
-            if idx < file_index {
-                position += file.packet_count;
-            } else if idx == file_index {
-                position += packet_offset_in_file as u64;
-                break;
-            }
-        }
-        position
-    }
-
-    /// 查找大于等于指定时间戳的最接近时间戳及其指针
-    fn find_timestamp_ge(
-        timestamp_index: &std::collections::HashMap<
-            u64,
-            crate::business::index::types::TimestampPointer,
-        >,
-        target_ns: u64,
-    ) -> Option<(
-        u64,
-        crate::business::index::types::TimestampPointer,
-    )> {
-        let mut candidates: Vec<u64> = timestamp_index
-            .keys()
-            .filter(|&&ts| ts >= target_ns)
-            .copied()
-            .collect();
-
-        if candidates.is_empty() {
-            return None;
-        }
-
-        candidates.sort_unstable();
-        let closest_ts = candidates[0];
-        timestamp_index
-            .get(&closest_ts)
-            .map(|ptr| (closest_ts, ptr.clone()))
-    }
-
     /// 获取数据集总大小
     fn get_total_size(&self) -> PcapResult<u64> {
         if let Some(cached_size) =
@@ -919,42 +2126,123 @@ impl PcapReader {
             ));
         }
 
-        // 关闭当前文件
-        if let Some(ref mut reader) = self.current_reader {
-            reader.close();
-        }
-
-        // 打开新文件
-        let file_info = &index.data_files.files[file_index];
+        let file_entry =
+            &index.data_files.files[file_index];
         let file_path =
-            self.dataset_path.join(&file_info.file_name);
+            file_entry.resolve_path(&self.dataset_path);
 
-        let mut reader =
-            crate::data::file_reader::PcapFileReader::new(
-                self.configuration.clone(),
-            );
-        reader.open(&file_path)?;
+        // 实时索引下只读取到索引中最后一个完整的数据包，
+        // 子集视图下只读取索引中的第一个到最后一个数据包
+        let start_offset = self
+            .index_manager
+            .subset()
+            .and_then(|_| file_entry.data_packets.first())
+            .map(|entry| {
+                (entry.byte_offset, entry.timestamp_ns)
+            });
+        let last_record = (self.index_manager.is_live()
+            || start_offset.is_some())
+        .then(|| {
+            file_entry.data_packets.last().map(|entry| {
+                (
+                    entry.byte_offset,
+                    entry.packet_size,
+                    entry.timestamp_ns,
+                )
+            })
+        });
+
+        // 将当前文件放回句柄池
+        if let Some(reader) = self.current_reader.take() {
+            self.idle_readers
+                .push((self.current_file_index, reader));
+        }
+
+        // 优先复用句柄池中已打开的文件
+        let reader = match self
+            .idle_readers
+            .iter()
+            .position(|(idx, _)| *idx == file_index)
+        {
+            Some(pos) => {
+                let (_, mut reader) =
+                    self.idle_readers.remove(pos);
+                reader.rewind()?;
+                if let Some((offset, timestamp_ns)) =
+                    start_offset
+                {
+                    reader.seek_to_entry(
+                        offset,
+                        timestamp_ns,
+                    )?;
+                }
+                debug!("复用已打开的文件: {file_path:?}");
+                reader
+            }
+            None => {
+                let mut reader = PcapFileReader::new(
+                    self.configuration.clone(),
+                );
+                reader.open(&file_path)?;
+                if let Some(last_record) = last_record {
+                    let end_offset = match last_record {
+                        Some((
+                            offset,
+                            size,
+                            timestamp_ns,
+                        )) => reader.record_end(
+                            offset,
+                            size,
+                            timestamp_ns,
+                        )?,
+                        None => {
+                            PcapFileHeader::HEADER_SIZE
+                                as u64
+                        }
+                    };
+                    reader.limit_to(end_offset);
+                }
+                if let Some((offset, timestamp_ns)) =
+                    start_offset
+                {
+                    reader.seek_to_entry(
+                        offset,
+                        timestamp_ns,
+                    )?;
+                }
+                debug!("已打开文件: {file_path:?}");
+                reader
+            }
+        };
 
         self.current_reader = Some(reader);
         self.current_file_index = file_index;
 
-        debug!("已打开文件: {file_path:?}");
+        // 淘汰最久未使用的句柄，当前文件占用一个名额
+        let max_idle = self
+            .configuration
+            .max_open_files
+            .saturating_sub(1);
+        while self.idle_readers.len() > max_idle {
+            let (_, mut evicted) =
+                self.idle_readers.remove(0);
+            evicted.close();
+        }
+
         Ok(())
     }
 
     /// 切换到下一个文件
     fn switch_to_next_file(&mut self) -> PcapResult<bool> {
-        let index = self
-            .index_manager
-            .get_index()
-            .ok_or_else(|| {
+        let query = self.index_manager.query().ok_or_else(
+            || {
                 PcapError::InvalidState(
                     "索引未加载".to_string(),
                 )
-            })?;
+            },
+        )?;
 
-        if self.current_file_index + 1
-            >= index.data_files.files.len()
+        if self.current_file_index + 1 >= query.file_count()
         {
             // 没有更多文件
             return Ok(false);
@@ -964,86 +2252,491 @@ impl PcapReader {
         Ok(true)
     }
 
+    /// 读取索引指针指向的数据包，必要时切换文件
+    pub(crate) fn read_packet_at_pointer(
+        &mut self,
+        pointer: &TimestampPointer,
+    ) -> PcapResult<ValidatedPacket> {
+        self.read_packet_at_offset(
+            pointer.file_index,
+            pointer.entry.byte_offset,
+            pointer.entry.timestamp_ns,
+        )
+    }
+
+    /// 读取指定文件中字节偏移处的数据包，必要时切换文件
+    fn read_packet_at_offset(
+        &mut self,
+        file_index: usize,
+        byte_offset: u64,
+        timestamp_ns: u64,
+    ) -> PcapResult<ValidatedPacket> {
+        let key = (file_index, byte_offset);
+        if let Some(packet) = self.packet_cache.get(&key) {
+            return Ok(packet);
+        }
+
+        if self.current_reader.is_none()
+            || self.current_file_index != file_index
+        {
+            self.open_file(file_index)?;
+        }
+
+        let reader = self
+            .current_reader
+            .as_mut()
+            .ok_or_else(|| {
+                PcapError::InvalidState(
+                    "当前文件读取器未初始化".to_string(),
+                )
+            })?;
+        let packet = reader
+            .read_packet_at(byte_offset, timestamp_ns)?;
+        if self.packet_cache.is_enabled() {
+            self.packet_cache.insert(key, packet.clone());
+        }
+        Ok(packet)
+    }
+
     /// 确保当前文件已打开
     fn ensure_current_file_open(
         &mut self,
     ) -> PcapResult<()> {
         if self.current_reader.is_none() {
-            let index = self
+            let query = self
                 .index_manager
-                .get_index()
+                .query()
                 .ok_or_else(|| {
                     PcapError::InvalidState(
                         "索引未加载".to_string(),
                     )
                 })?;
 
-            if !index.data_files.files.is_empty() {
+            if query.file_count() > 0 {
                 self.open_file(0)?;
             }
         }
         Ok(())
     }
 
-    /// 根据时间戳读取数据包
+    /// 根据时间戳读取数据包（精确匹配）
     pub fn read_packet_by_timestamp(
         &mut self,
         timestamp_ns: u64,
     ) -> PcapResult<Option<ValidatedPacket>> {
-        let pointer = {
-            let index = self
-                .index_manager
-                .get_index()
-                .ok_or_else(|| {
-                    PcapError::InvalidState(
-                        "索引未加载".to_string(),
-                    )
-                })?;
+        self.read_packet_by_timestamp_with_mode(
+            timestamp_ns,
+            SeekMode::Exact,
+        )
+    }
 
-            match index
-                .find_packet_by_timestamp(timestamp_ns)
-            {
-                Some(ptr) => ptr.clone(),
-                None => return Ok(None),
-            }
+    /// 按指定查找模式根据时间戳读取数据包
+    ///
+    /// # 参数
+    /// - `timestamp_ns` - 目标时间戳（纳秒）
+    /// - `mode` - 查找模式
+    ///
+    /// # 返回
+    /// - `Ok(Some(packet))` - 找到满足模式的数据包
+    /// - `Ok(None)` - 没有满足模式的数据包
+    /// - `Err(error)` - 读取过程中发生错误
+    pub fn read_packet_by_timestamp_with_mode(
+        &mut self,
+        timestamp_ns: u64,
+        mode: SeekMode,
+    ) -> PcapResult<Option<ValidatedPacket>> {
+        self.initialize()?;
+
+        let Some(pointer) =
+            self.locate_timestamp(timestamp_ns, mode)?
+        else {
+            return Ok(None);
         };
 
-        // 检查是否需要切换文件
-        if pointer.file_index != self.current_file_index {
-            self.open_file(pointer.file_index)?;
+        // 读取指定位置的数据包
+        let packet =
+            self.read_packet_at_pointer(&pointer)?;
+
+        // 验证时间戳是否与索引条目匹配
+        if packet.packet.get_timestamp_ns()
+            == pointer.entry.timestamp_ns
+        {
+            Ok(Some(packet))
+        } else {
+            Err(PcapError::InvalidState(
+                "读取的数据包时间戳不匹配".to_string(),
+            ))
         }
+    }
 
-        // 确保文件已打开
-        self.ensure_current_file_open()?;
+    /// 读取目标时间戳前后相邻的两个数据包
+    ///
+    /// 前一个数据包是时间戳不大于目标的最后一个时间戳对应的数据包，
+    /// 后一个数据包是时间戳大于目标的第一个时间戳对应的数据包；时间戳相同的多个数据包取写入顺序最早的一个。
+    /// 目标恰好落在某个数据包上时，该数据包作为前一个数据包，插值系数为 0。
+    /// 与 `read_packet_by_timestamp` 一样会改变读取器当前打开的文件。
+    ///
+    /// # 参数
+    /// - `timestamp_ns` - 目标时间戳（纳秒）
+    ///
+    /// # 返回
+    /// 前后两个数据包，目标早于数据集起始或晚于数据集结束时对应一侧为 None
+    pub fn packet_pair_around(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> PcapResult<PacketPair> {
+        self.initialize()?;
 
-        // 读取指定位置的数据包
-        let reader = self
-            .current_reader
-            .as_mut()
+        let before = self.locate_timestamp(
+            timestamp_ns,
+            SeekMode::Floor,
+        )?;
+        let after = match timestamp_ns.checked_add(1) {
+            Some(next) => self.locate_timestamp(
+                next,
+                SeekMode::Ceiling,
+            )?,
+            None => None,
+        };
+
+        let before = before
+            .map(|pointer| {
+                self.read_packet_at_pointer(&pointer)
+            })
+            .transpose()?;
+        let after = after
+            .map(|pointer| {
+                self.read_packet_at_pointer(&pointer)
+            })
+            .transpose()?;
+        Ok(PacketPair {
+            timestamp_ns,
+            before,
+            after,
+        })
+    }
+
+    /// 批量解析时间戳对应的数据包位置（精确匹配）
+    ///
+    /// 见 [`PcapReader::resolve_timestamps_with_mode`]。
+    pub fn resolve_timestamps(
+        &mut self,
+        timestamps: &[u64],
+    ) -> PcapResult<Vec<Option<PacketLocation>>> {
+        self.resolve_timestamps_with_mode(
+            timestamps,
+            SeekMode::Exact,
+        )
+    }
+
+    /// 按指定查找模式批量解析时间戳对应的数据包位置
+    ///
+    /// 先对请求排序，再沿索引单向查找一次，比逐个调用
+    /// `read_packet_by_timestamp` 快得多，适合一次获取大量标记位置的场景。
+    /// 只查询索引，不读取数据文件。
+    ///
+    /// # 参数
+    /// - `timestamps` - 目标时间戳（纳秒），无需有序，可以重复
+    /// - `mode` - 查找模式
+    ///
+    /// # 返回
+    /// 与输入顺序一一对应的位置，没有满足模式的数据包时为 None
+    pub fn resolve_timestamps_with_mode(
+        &mut self,
+        timestamps: &[u64],
+        mode: SeekMode,
+    ) -> PcapResult<Vec<Option<PacketLocation>>> {
+        self.initialize()?;
+
+        let pointers = self
+            .index_manager
+            .find_packets_with_mode(timestamps, mode)?;
+        let index = self
+            .index_manager
+            .get_index()
             .ok_or_else(|| {
                 PcapError::InvalidState(
-                    "当前文件读取器未初始化".to_string(),
+                    "索引未加载".to_string(),
                 )
             })?;
-        let packet_result = reader
-            .read_packet_at(pointer.entry.byte_offset);
-
-        match packet_result {
-            Ok(packet) => {
-                // 验证时间戳是否匹配
-                if packet.packet.get_timestamp_ns()
-                    == timestamp_ns
+        Ok(timestamps
+            .iter()
+            .zip(pointers)
+            .map(|(&requested, pointer)| {
+                let pointer = pointer?;
+                Some(PacketLocation {
+                    requested_timestamp_ns: requested,
+                    timestamp_ns: pointer
+                        .entry
+                        .timestamp_ns,
+                    global_index: pointer
+                        .entry
+                        .global_index,
+                    file_index: pointer.file_index,
+                    file_name: index.files()
+                        [pointer.file_index]
+                        .file_name
+                        .clone(),
+                    byte_offset: pointer.entry.byte_offset,
+                    packet_size: pointer.entry.packet_size,
+                })
+            })
+            .collect())
+    }
+
+    /// 读取批量解析出的数据包位置
+    ///
+    /// 按文件分组、组内按字节偏移排序后顺序读取，每个文件只打开一次，
+    /// 相同位置只读取一次。与 `read_packet_by_timestamp` 一样会改变读取器当前打开的文件。
+    ///
+    /// # 参数
+    /// - `locations` - `resolve_timestamps` 返回的位置
+    ///
+    /// # 返回
+    /// 与输入顺序一一对应的数据包，位置为 None 时对应 None；位置与当前索引不一致时返回错误
+    pub fn read_packets_at_locations(
+        &mut self,
+        locations: &[Option<PacketLocation>],
+    ) -> PcapResult<Vec<Option<ValidatedPacket>>> {
+        self.initialize()?;
+
+        let mut order: Vec<(usize, &PacketLocation)> =
+            locations
+                .iter()
+                .enumerate()
+                .filter_map(|(i, location)| {
+                    location.as_ref().map(|l| (i, l))
+                })
+                .collect();
+        order.sort_by_key(|(_, location)| {
+            (location.file_index, location.byte_offset)
+        });
+
+        let mut packets = vec![None; locations.len()];
+        let mut previous: Option<(
+            usize,
+            u64,
+            ValidatedPacket,
+        )> = None;
+        for (i, location) in order {
+            if let Some((file_index, byte_offset, packet)) =
+                &previous
+            {
+                if *file_index == location.file_index
+                    && *byte_offset == location.byte_offset
                 {
-                    Ok(Some(packet))
-                } else {
-                    Err(PcapError::InvalidState(
-                        "读取的数据包时间戳不匹配"
-                            .to_string(),
+                    packets[i] = Some(packet.clone());
+                    continue;
+                }
+            }
+
+            let matches_index = self
+                .index_manager
+                .get_index()
+                .and_then(|index| {
+                    index.files().get(location.file_index)
+                })
+                .is_some_and(|file| {
+                    file.file_name == location.file_name
+                });
+            if !matches_index {
+                return Err(PcapError::InvalidArgument(
+                    format!(
+                        "数据包位置与当前索引不一致: {}",
+                        location.file_name
+                    ),
+                ));
+            }
+
+            let packet = self.read_packet_at_offset(
+                location.file_index,
+                location.byte_offset,
+                location.timestamp_ns,
+            )?;
+            if packet.packet.get_timestamp_ns()
+                != location.timestamp_ns
+            {
+                return Err(PcapError::InvalidState(
+                    "读取的数据包时间戳不匹配".to_string(),
+                ));
+            }
+            packets[i] = Some(packet.clone());
+            previous = Some((
+                location.file_index,
+                location.byte_offset,
+                packet,
+            ));
+        }
+        Ok(packets)
+    }
+
+    /// 按指定查找模式批量读取时间戳对应的数据包
+    ///
+    /// 依次调用 `resolve_timestamps_with_mode` 和 `read_packets_at_locations`，
+    /// 结果与输入顺序一一对应。
+    pub fn read_packets_by_timestamps(
+        &mut self,
+        timestamps: &[u64],
+        mode: SeekMode,
+    ) -> PcapResult<Vec<Option<ValidatedPacket>>> {
+        let locations = self.resolve_timestamps_with_mode(
+            timestamps, mode,
+        )?;
+        self.read_packets_at_locations(&locations)
+    }
+
+    /// 把指定序号的数据包分别提取为独立的 libpcap 文件
+    ///
+    /// 等同于格式为 `ExtractFormat::Pcap` 的 `extract_packets_with_format`。
+    pub fn extract_packets<P: AsRef<Path>>(
+        &mut self,
+        indices: &[u64],
+        out_dir: P,
+    ) -> PcapResult<Vec<ExtractedPacket>> {
+        self.extract_packets_with_format(
+            indices,
+            out_dir,
+            ExtractFormat::Pcap,
+        )
+    }
+
+    /// 把指定序号的数据包分别提取为独立的文件，便于附在问题报告中
+    ///
+    /// 每个数据包写入 `packet_<全局序号>.pcap`，`Raw` 格式下写入 `packet_<全局序号>.bin`
+    /// 和 `packet_<全局序号>.json`，已存在的同名文件会被覆盖。输出目录不存在时自动创建。
+    /// 与 `read_packet_by_timestamp` 一样会改变读取器当前打开的文件。
+    ///
+    /// # 参数
+    /// - `indices` - 数据包的全局序号（从0开始）
+    /// - `out_dir` - 输出目录
+    ///
+    /// # 返回
+    /// 与输入顺序一一对应的提取结果
+    ///
+    /// # 错误
+    /// 序号超出范围时返回 `PcapError::InvalidArgument`，此时不写入任何文件
+    pub fn extract_packets_with_format<P: AsRef<Path>>(
+        &mut self,
+        indices: &[u64],
+        out_dir: P,
+        format: ExtractFormat,
+    ) -> PcapResult<Vec<ExtractedPacket>> {
+        self.initialize()?;
+
+        // 先定位全部数据包，序号无效时不留下部分输出
+        let locations = {
+            let index = self
+                .index_manager
+                .get_index()
+                .ok_or_else(|| {
+                    PcapError::InvalidState(
+                        "索引未加载".to_string(),
+                    )
+                })?;
+            indices
+                .iter()
+                .map(|&global_index| {
+                    let (file_index, entry) = index
+                        .locate_global_index(global_index)
+                        .ok_or_else(|| {
+                            PcapError::InvalidArgument(
+                                format!(
+                                    "数据包索引 {global_index} 超出范围 (总数: {})",
+                                    index.total_packets
+                                ),
+                            )
+                        })?;
+                    Ok((
+                        global_index,
+                        file_index,
+                        index.files()[file_index]
+                            .file_name
+                            .clone(),
+                        entry.byte_offset,
+                        entry.timestamp_ns,
                     ))
+                })
+                .collect::<PcapResult<Vec<_>>>()?
+        };
+
+        let out_dir = out_dir.as_ref();
+        std::fs::create_dir_all(out_dir)
+            .map_err(PcapError::Io)?;
+        let mut extracted =
+            Vec::with_capacity(locations.len());
+        for (
+            global_index,
+            file_index,
+            source_file,
+            byte_offset,
+            timestamp_ns,
+        ) in locations
+        {
+            let packet = self.read_packet_at_offset(
+                file_index,
+                byte_offset,
+                timestamp_ns,
+            )?;
+            let data = &packet.packet;
+            let stem = format!("packet_{global_index}");
+            let mut record = ExtractedPacket {
+                global_index,
+                timestamp_ns: data.get_timestamp_ns(),
+                link_type: data.link_type,
+                packet_size: data.packet_length() as u32,
+                checksum: data.checksum(),
+                is_valid: packet.is_valid(),
+                source_file,
+                byte_offset,
+                path: PathBuf::new(),
+                metadata_path: None,
+            };
+            match format {
+                ExtractFormat::Pcap => {
+                    record.path = out_dir
+                        .join(format!("{stem}.pcap"));
+                    let mut writer = LibpcapWriter::create(
+                        &record.path,
+                        data.link_type,
+                    )?;
+                    writer.write_packet(
+                        record.timestamp_ns,
+                        &data.data,
+                    )?;
+                    writer.flush()?;
+                }
+                ExtractFormat::Raw => {
+                    record.path =
+                        out_dir.join(format!("{stem}.bin"));
+                    std::fs::write(
+                        &record.path,
+                        &data.data,
+                    )
+                    .map_err(PcapError::Io)?;
+                    let metadata_path = out_dir
+                        .join(format!("{stem}.json"));
+                    std::fs::write(
+                        &metadata_path,
+                        serde_json::to_string_pretty(
+                            &record,
+                        )?,
+                    )
+                    .map_err(PcapError::Io)?;
+                    record.metadata_path =
+                        Some(metadata_path);
                 }
             }
-            Err(e) => Err(e),
+            debug!(
+                "已提取数据包 {global_index}: {:?}",
+                record.path
+            );
+            extracted.push(record);
         }
+        info!(
+            "已提取 {} 个数据包到 {out_dir:?}",
+            extracted.len()
+        );
+        Ok(extracted)
     }
 }
 
@@ -1053,6 +2746,107 @@ impl Drop for PcapReader {
         if let Some(ref mut reader) = self.current_reader {
             reader.close();
         }
+        for (_, reader) in self.idle_readers.iter_mut() {
+            reader.close();
+        }
         debug!("PcapReader已清理");
     }
 }
+
+/// 时间范围数据包迭代器
+///
+/// 由 `PcapReader::iter_time_range` 创建，逐个读取范围内的数据包。
+/// 迭代期间独占借用读取器，读取会改变读取器当前打开的文件。
+pub struct TimeRangeIter<'a> {
+    reader: &'a mut PcapReader,
+    pointers: std::vec::IntoIter<TimestampPointer>,
+    start_timestamp_ns: u64,
+    end_timestamp_ns: u64,
+}
+
+impl TimeRangeIter<'_> {
+    /// 剩余待读取的索引条目数量
+    pub fn remaining(&self) -> usize {
+        self.pointers.len()
+    }
+
+    /// 读取范围内的下一个数据包
+    ///
+    /// 外层错误表示打开或切换数据文件失败，内层错误表示单个数据包读取失败。
+    fn read_next(
+        &mut self,
+    ) -> PcapResult<Option<PcapResult<ValidatedPacket>>>
+    {
+        for pointer in self.pointers.by_ref() {
+            if self.reader.current_reader.is_none()
+                || self.reader.current_file_index
+                    != pointer.file_index
+            {
+                self.reader
+                    .open_file(pointer.file_index)?;
+            }
+            match self
+                .reader
+                .read_packet_at_pointer(&pointer)
+            {
+                Ok(packet) => {
+                    // 验证时间戳是否在范围内
+                    let packet_timestamp =
+                        packet.packet.get_timestamp_ns();
+                    if packet_timestamp
+                        >= self.start_timestamp_ns
+                        && packet_timestamp
+                            <= self.end_timestamp_ns
+                    {
+                        return Ok(Some(Ok(packet)));
+                    }
+                }
+                Err(e) => return Ok(Some(Err(e))),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Iterator for TimeRangeIter<'_> {
+    type Item = PcapResult<ValidatedPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_next().unwrap_or_else(|e| Some(Err(e)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.pointers.len()))
+    }
+}
+
+/// 按内存预算分批读取的迭代器
+///
+/// 由 `PcapReader::read_chunks` 创建，读取出错后迭代结束。
+pub struct ChunkIter<'a> {
+    reader: &'a mut PcapReader,
+    max_bytes: usize,
+    finished: bool,
+}
+
+impl Iterator for ChunkIter<'_> {
+    type Item = PcapResult<Vec<ValidatedPacket>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        match self.reader.read_chunk(self.max_bytes) {
+            Ok(chunk) if chunk.is_empty() => {
+                self.finished = true;
+                None
+            }
+            Ok(chunk) => Some(Ok(chunk)),
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! 远程读取器模块
+//!
+//! 通过数据集服务的 HTTP 接口读取远程数据集，提供与 `PcapReader` 相同的读取和跳转语义。
+//! 顺序读取使用服务的PCAP字节流接口，数据包在到达时逐个解析，不需要把整个数据集下载到本地。
+
+use log::debug;
+use serde::Deserialize;
+use std::io::{self, Read};
+use std::time::Duration;
+
+use crate::api::memory::MemoryReader;
+use crate::api::source::PacketSource;
+use crate::data::models::{DatasetInfo, ValidatedPacket};
+use crate::foundation::error::{PcapError, PcapResult};
+use crate::foundation::paths::validate_dataset_name;
+
+/// 默认的连接和读取超时
+pub const DEFAULT_REMOTE_TIMEOUT: Duration =
+    Duration::from_secs(30);
+
+/// 每次从字节流读取的字节数
+const READ_CHUNK_BYTES: usize = 64 * 1024;
+
+/// 服务返回的错误响应
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+}
+
+/// 远程数据集读取器
+pub struct RemotePcapReader {
+    agent: ureq::Agent,
+    base_url: String,
+    dataset_name: String,
+    /// 当前字节流的起始时间戳
+    start_timestamp_ns: u64,
+    /// 当前字节流，首次读取时打开
+    stream: Option<Box<dyn Read + Send + Sync>>,
+    decoder: MemoryReader,
+    /// 跳转时预读的数据包
+    pending: Option<ValidatedPacket>,
+    read_buffer: Vec<u8>,
+    packets_read: u64,
+}
+
+impl RemotePcapReader {
+    /// 连接数据集服务并打开数据集
+    ///
+    /// # 参数
+    /// - `base_url` - 服务地址，如 `http://127.0.0.1:8080`
+    /// - `dataset_name` - 数据集名称
+    ///
+    /// # 错误
+    /// 数据集不存在或服务无法访问时返回错误
+    pub fn new(
+        base_url: &str,
+        dataset_name: &str,
+    ) -> PcapResult<Self> {
+        Self::new_with_timeout(
+            base_url,
+            dataset_name,
+            DEFAULT_REMOTE_TIMEOUT,
+        )
+    }
+
+    /// 使用指定的连接和读取超时打开远程数据集
+    pub fn new_with_timeout(
+        base_url: &str,
+        dataset_name: &str,
+        timeout: Duration,
+    ) -> PcapResult<Self> {
+        validate_dataset_name(dataset_name)?;
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(timeout)
+            .timeout_read(timeout)
+            .build();
+
+        let mut reader = Self {
+            agent,
+            base_url: base_url
+                .trim_end_matches('/')
+                .to_string(),
+            dataset_name: dataset_name.to_string(),
+            start_timestamp_ns: 0,
+            stream: None,
+            decoder: MemoryReader::new(),
+            pending: None,
+            read_buffer: vec![0u8; READ_CHUNK_BYTES],
+            packets_read: 0,
+        };
+        // 立即确认数据集存在，与本地读取器打开时的行为一致
+        reader.get_dataset_info()?;
+        Ok(reader)
+    }
+
+    /// 服务地址
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// 数据集名称
+    pub fn dataset_name(&self) -> &str {
+        &self.dataset_name
+    }
+
+    /// 自上次打开、跳转或重置以来读取的数据包数量
+    pub fn packets_read(&self) -> u64 {
+        self.packets_read
+    }
+
+    /// 获取数据集信息
+    pub fn get_dataset_info(
+        &mut self,
+    ) -> PcapResult<DatasetInfo> {
+        let response = self.get(&self.dataset_url(""))?;
+        serde_json::from_reader(response.into_reader())
+            .map_err(PcapError::from)
+    }
+
+    /// 读取下一个数据包，读完时返回 `Ok(None)`
+    pub fn read_packet(
+        &mut self,
+    ) -> PcapResult<Option<ValidatedPacket>> {
+        if let Some(packet) = self.pending.take() {
+            self.packets_read += 1;
+            return Ok(Some(packet));
+        }
+
+        loop {
+            if let Some(packet) =
+                self.decoder.read_packet()?
+            {
+                self.packets_read += 1;
+                return Ok(Some(packet));
+            }
+            if self.decoder.is_finished() {
+                return Ok(None);
+            }
+
+            let stream = match self.stream.as_mut() {
+                Some(stream) => stream,
+                None => {
+                    let stream = self.open_stream()?;
+                    self.stream.insert(stream)
+                }
+            };
+            let length = match stream
+                .read(&mut self.read_buffer)
+            {
+                Ok(length) => length,
+                Err(e)
+                    if e.kind()
+                        == io::ErrorKind::Interrupted =>
+                {
+                    continue
+                }
+                Err(e) => return Err(PcapError::Io(e)),
+            };
+            if length == 0 {
+                self.decoder.finish();
+            } else {
+                self.decoder.push_chunk(
+                    &self.read_buffer[..length],
+                )?;
+            }
+        }
+    }
+
+    /// 批量读取最多 `count` 个数据包
+    pub fn read_packets(
+        &mut self,
+        count: usize,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        PacketSource::read_packets(self, count)
+    }
+
+    /// 跳转到不早于指定时间戳的第一个数据包，返回实际定位到的时间戳
+    pub fn seek_to_timestamp(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> PcapResult<u64> {
+        self.restart(timestamp_ns);
+        match self.read_packet()? {
+            Some(packet) => {
+                let actual = packet.get_timestamp_ns();
+                self.pending = Some(packet);
+                self.packets_read = 0;
+                debug!(
+                    "远程数据集已跳转到时间戳: {actual}"
+                );
+                Ok(actual)
+            }
+            None => {
+                let message = format!(
+                    "未找到不早于时间戳 {timestamp_ns} 的数据包"
+                );
+                Err(PcapError::InvalidArgument(message))
+            }
+        }
+    }
+
+    /// 回到数据集开始位置
+    pub fn reset(&mut self) -> PcapResult<()> {
+        self.restart(0);
+        Ok(())
+    }
+
+    /// 读取时间范围内的所有数据包，不影响当前读取位置
+    pub fn read_packets_by_time_range(
+        &mut self,
+        start_timestamp_ns: u64,
+        end_timestamp_ns: u64,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        let url = format!(
+            "{}?start_ns={start_timestamp_ns}&end_ns={end_timestamp_ns}",
+            self.dataset_url("/stream")
+        );
+        let mut bytes = Vec::new();
+        self.get(&url)?
+            .into_reader()
+            .read_to_end(&mut bytes)
+            .map_err(PcapError::Io)?;
+
+        let mut decoder = MemoryReader::from_bytes(bytes)?;
+        let mut packets = Vec::new();
+        while let Some(packet) = decoder.read_packet()? {
+            packets.push(packet);
+        }
+        Ok(packets)
+    }
+
+    /// 丢弃当前字节流，下次读取时从指定时间戳重新打开
+    fn restart(&mut self, start_timestamp_ns: u64) {
+        self.start_timestamp_ns = start_timestamp_ns;
+        self.stream = None;
+        self.decoder = MemoryReader::new();
+        self.pending = None;
+        self.packets_read = 0;
+    }
+
+    fn open_stream(
+        &self,
+    ) -> PcapResult<Box<dyn Read + Send + Sync>> {
+        let url = format!(
+            "{}?start_ns={}",
+            self.dataset_url("/stream"),
+            self.start_timestamp_ns
+        );
+        Ok(self.get(&url)?.into_reader())
+    }
+
+    fn dataset_url(&self, suffix: &str) -> String {
+        format!(
+            "{}/datasets/{}{suffix}",
+            self.base_url,
+            encode_path_segment(&self.dataset_name)
+        )
+    }
+
+    fn get(&self, url: &str) -> PcapResult<ureq::Response> {
+        match self.agent.get(url).call() {
+            Ok(response) => Ok(response),
+            Err(ureq::Error::Status(status, response)) => {
+                let message = response
+                    .into_string()
+                    .ok()
+                    .and_then(|body| {
+                        serde_json::from_str::<ErrorBody>(
+                            &body,
+                        )
+                        .ok()
+                    })
+                    .map(|body| body.message)
+                    .unwrap_or_else(|| {
+                        format!("HTTP {status}")
+                    });
+                Err(match status {
+                    404 => PcapError::DirectoryNotFound(message),
+                    400 => PcapError::InvalidArgument(message),
+                    _ => PcapError::Unknown(format!(
+                        "数据集服务返回错误 {status}: {message}"
+                    )),
+                })
+            }
+            Err(ureq::Error::Transport(e)) => {
+                Err(PcapError::Io(io::Error::other(
+                    e.to_string(),
+                )))
+            }
+        }
+    }
+}
+
+impl PacketSource for RemotePcapReader {
+    fn dataset_name(&self) -> &str {
+        RemotePcapReader::dataset_name(self)
+    }
+
+    fn get_dataset_info(
+        &mut self,
+    ) -> PcapResult<DatasetInfo> {
+        RemotePcapReader::get_dataset_info(self)
+    }
+
+    fn read_packet(
+        &mut self,
+    ) -> PcapResult<Option<ValidatedPacket>> {
+        RemotePcapReader::read_packet(self)
+    }
+
+    fn seek_to_timestamp(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> PcapResult<u64> {
+        RemotePcapReader::seek_to_timestamp(
+            self,
+            timestamp_ns,
+        )
+    }
+
+    fn reset(&mut self) -> PcapResult<()> {
+        RemotePcapReader::reset(self)
+    }
+
+    fn read_packets_by_time_range(
+        &mut self,
+        start_timestamp_ns: u64,
+        end_timestamp_ns: u64,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        RemotePcapReader::read_packets_by_time_range(
+            self,
+            start_timestamp_ns,
+            end_timestamp_ns,
+        )
+    }
+}
+
+/// 对URL路径段进行百分号编码
+fn encode_path_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric()
+            || matches!(byte, b'-' | b'_' | b'.' | b'~')
+        {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
@@ -0,0 +1,366 @@
+//! 负载搜索模块
+//!
+//! 在数据包负载中查找字节序列，返回命中数据包的位置，用于“找出包含某个 ID 的数据包”
+//! 一类排查。数据文件按线程分组并行扫描，负载的匹配规则由 [`Matcher`] 决定：
+//! 内置精确字节、带掩码字节和正则表达式（`regex` 特性）三种匹配器，
+//! 协议相关的检测逻辑也可以实现该 trait 接入同一个搜索引擎。
+
+use log::warn;
+
+use crate::api::reader::FileGroup;
+use crate::business::config::ReaderConfig;
+use crate::data::file_reader::PcapFileReader;
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 负载搜索选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// 扫描线程数，0 表示使用可用的 CPU 核心数
+    pub threads: usize,
+    /// 抽样间隔，只检查全局序号为该值整数倍的数据包，1 表示检查全部数据包
+    pub sample_interval: u64,
+    /// 最多返回的命中数量，`None` 表示不限制
+    pub max_matches: Option<usize>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            threads: 0,
+            sample_interval: 1,
+            max_matches: None,
+        }
+    }
+}
+
+impl SearchOptions {
+    /// 验证搜索选项的有效性
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sample_interval == 0 {
+            return Err("抽样间隔必须大于0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 负载搜索命中的数据包位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayloadMatch {
+    /// 数据包的全局序号
+    pub global_index: u64,
+    /// 数据包所在文件在索引中的序号
+    pub file_index: usize,
+    /// 数据包时间戳（纳秒）
+    pub timestamp_ns: u64,
+    /// 数据包头部在文件中的字节偏移
+    pub byte_offset: u64,
+    /// 第一次命中在负载中的字节偏移
+    pub payload_offset: usize,
+}
+
+/// 负载匹配器
+///
+/// 搜索时各线程共享同一个匹配器，因此要求 `Send + Sync`。
+/// 签名相同的闭包自动实现该 trait。
+pub trait Matcher: Send + Sync {
+    /// 返回负载中第一次命中的字节偏移，未命中时返回 `None`
+    fn find(&self, payload: &[u8]) -> Option<usize>;
+}
+
+impl<F> Matcher for F
+where
+    F: Fn(&[u8]) -> Option<usize> + Send + Sync,
+{
+    fn find(&self, payload: &[u8]) -> Option<usize> {
+        self(payload)
+    }
+}
+
+/// 精确字节序列匹配器
+///
+/// 启用 `fast-search` 特性时使用 memchr 的 SIMD 子串查找，
+/// 否则使用 Boyer-Moore-Horspool 算法。
+pub struct BytesMatcher {
+    #[cfg(feature = "fast-search")]
+    finder: memchr::memmem::Finder<'static>,
+    #[cfg(not(feature = "fast-search"))]
+    pattern: Vec<u8>,
+    /// 坏字符跳转表
+    #[cfg(not(feature = "fast-search"))]
+    shift: [usize; 256],
+}
+
+impl BytesMatcher {
+    /// 创建匹配器
+    ///
+    /// # 错误
+    /// `pattern` 为空时返回 `PcapError::InvalidArgument`
+    pub fn new(pattern: &[u8]) -> PcapResult<Self> {
+        if pattern.is_empty() {
+            return Err(PcapError::InvalidArgument(
+                "搜索的字节序列不能为空".to_string(),
+            ));
+        }
+        #[cfg(feature = "fast-search")]
+        {
+            Ok(Self {
+                finder: memchr::memmem::Finder::new(
+                    pattern,
+                )
+                .into_owned(),
+            })
+        }
+        #[cfg(not(feature = "fast-search"))]
+        {
+            let mut shift = [pattern.len(); 256];
+            let last = pattern.len().saturating_sub(1);
+            for (i, byte) in
+                pattern[..last].iter().enumerate()
+            {
+                shift[*byte as usize] = last - i;
+            }
+            Ok(Self {
+                pattern: pattern.to_vec(),
+                shift,
+            })
+        }
+    }
+}
+
+impl Matcher for BytesMatcher {
+    fn find(&self, haystack: &[u8]) -> Option<usize> {
+        #[cfg(feature = "fast-search")]
+        {
+            self.finder.find(haystack)
+        }
+        #[cfg(not(feature = "fast-search"))]
+        {
+            let pattern = self.pattern.as_slice();
+            let length = pattern.len();
+            let mut position = 0;
+            while position + length <= haystack.len() {
+                let window =
+                    &haystack[position..position + length];
+                if window == pattern {
+                    return Some(position);
+                }
+                position +=
+                    self.shift[window[length - 1] as usize];
+            }
+            None
+        }
+    }
+}
+
+/// 带掩码的字节序列匹配器
+///
+/// 只比较掩码中为 1 的位，用于匹配含可变字段的报文特征，
+/// 例如掩码 `[0xff, 0x00, 0xff]` 忽略第二个字节。
+pub struct MaskedMatcher {
+    /// 已按掩码清零无关位的字节序列
+    pattern: Vec<u8>,
+    mask: Vec<u8>,
+}
+
+impl MaskedMatcher {
+    /// 创建匹配器
+    ///
+    /// # 错误
+    /// `pattern` 为空或与 `mask` 长度不同时返回 `PcapError::InvalidArgument`
+    pub fn new(
+        pattern: &[u8],
+        mask: &[u8],
+    ) -> PcapResult<Self> {
+        if pattern.is_empty() {
+            return Err(PcapError::InvalidArgument(
+                "搜索的字节序列不能为空".to_string(),
+            ));
+        }
+        if pattern.len() != mask.len() {
+            return Err(PcapError::InvalidArgument(
+                format!(
+                    "掩码长度 {} 与字节序列长度 {} 不一致",
+                    mask.len(),
+                    pattern.len()
+                ),
+            ));
+        }
+        Ok(Self {
+            pattern: pattern
+                .iter()
+                .zip(mask)
+                .map(|(byte, mask)| byte & mask)
+                .collect(),
+            mask: mask.to_vec(),
+        })
+    }
+}
+
+impl Matcher for MaskedMatcher {
+    fn find(&self, haystack: &[u8]) -> Option<usize> {
+        haystack.windows(self.pattern.len()).position(
+            |window| {
+                window
+                    .iter()
+                    .zip(&self.mask)
+                    .map(|(byte, mask)| byte & mask)
+                    .eq(self.pattern.iter().copied())
+            },
+        )
+    }
+}
+
+/// 正则表达式匹配器
+///
+/// 基于 `regex::bytes`，负载不要求是有效的 UTF-8。
+/// 需要启用 `regex` 特性。
+#[cfg(feature = "regex")]
+pub struct RegexMatcher {
+    regex: regex::bytes::Regex,
+}
+
+#[cfg(feature = "regex")]
+impl RegexMatcher {
+    /// 编译正则表达式并创建匹配器
+    ///
+    /// # 错误
+    /// 表达式无效时返回 `PcapError::InvalidArgument`
+    pub fn new(pattern: &str) -> PcapResult<Self> {
+        regex::bytes::Regex::new(pattern)
+            .map(|regex| Self { regex })
+            .map_err(|e| {
+                PcapError::InvalidArgument(format!(
+                    "无效的正则表达式: {e}"
+                ))
+            })
+    }
+}
+
+#[cfg(feature = "regex")]
+impl From<regex::bytes::Regex> for RegexMatcher {
+    fn from(regex: regex::bytes::Regex) -> Self {
+        Self { regex }
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Matcher for RegexMatcher {
+    fn find(&self, haystack: &[u8]) -> Option<usize> {
+        self.regex.find(haystack).map(|m| m.start())
+    }
+}
+
+/// 并行扫描各文件中的数据包，返回按全局序号排序的命中位置
+///
+/// 读取失败的数据包记录警告后跳过。
+pub(crate) fn scan_file_groups(
+    configuration: &ReaderConfig,
+    groups: Vec<(usize, FileGroup)>,
+    matcher: &dyn Matcher,
+    options: &SearchOptions,
+) -> Vec<PayloadMatch> {
+    if groups.is_empty() {
+        return Vec::new();
+    }
+    let threads = if options.threads == 0 {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    } else {
+        options.threads
+    }
+    .min(groups.len());
+
+    // 轮流分配文件，使各线程的文件数大致相同
+    let mut assignments: Vec<Vec<(usize, FileGroup)>> =
+        (0..threads).map(|_| Vec::new()).collect();
+    for (i, group) in groups.into_iter().enumerate() {
+        assignments[i % threads].push(group);
+    }
+
+    let mut matches: Vec<PayloadMatch> =
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = assignments
+                .into_iter()
+                .map(|assignment| {
+                    scope.spawn(move || {
+                        scan_assignment(
+                            configuration,
+                            assignment,
+                            matcher,
+                            options.max_matches,
+                        )
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| {
+                    handle.join().unwrap_or_else(|_| {
+                        warn!("负载搜索线程异常退出");
+                        Vec::new()
+                    })
+                })
+                .collect()
+        });
+
+    matches.sort_unstable_by_key(|m| m.global_index);
+    if let Some(max_matches) = options.max_matches {
+        matches.truncate(max_matches);
+    }
+    matches
+}
+
+/// 搜索工作线程：依次扫描分配到的文件
+///
+/// 每个文件内的条目按全局序号升序排列，单个线程命中 `max_matches` 个后即可停止，
+/// 合并后全局序号最小的命中不会丢失。
+fn scan_assignment(
+    configuration: &ReaderConfig,
+    assignment: Vec<(usize, FileGroup)>,
+    matcher: &dyn Matcher,
+    max_matches: Option<usize>,
+) -> Vec<PayloadMatch> {
+    let mut matches = Vec::new();
+    for (file_index, (file_path, entries)) in assignment {
+        let mut reader =
+            PcapFileReader::new(configuration.clone());
+        if let Err(e) = reader.open(&file_path) {
+            warn!("打开文件失败: {file_path:?}: {e}");
+            continue;
+        }
+
+        for entry in entries {
+            if max_matches
+                .is_some_and(|max| matches.len() >= max)
+            {
+                return matches;
+            }
+            match reader.read_packet_at(
+                entry.byte_offset,
+                entry.timestamp_ns,
+            ) {
+                Ok(packet) => {
+                    if let Some(payload_offset) =
+                        matcher.find(&packet.packet.data)
+                    {
+                        matches.push(PayloadMatch {
+                            global_index: entry
+                                .global_index,
+                            file_index,
+                            timestamp_ns: entry
+                                .timestamp_ns,
+                            byte_offset: entry.byte_offset,
+                            payload_offset,
+                        });
+                    }
+                }
+                Err(e) => {
+                    warn!("读取数据包失败: {}", e);
+                }
+            }
+        }
+    }
+    matches
+}
@@ -0,0 +1,475 @@
+//! 数据集服务模块
+//!
+//! 基于 axum 的 HTTP 服务，通过网络提供数据集列表、时间范围查询和数据包流，
+//! 远程客户端无需访问记录所在的文件系统。每个请求在阻塞线程池中打开独立的 `PcapReader`。
+//!
+//! | 路径 | 说明 |
+//! | ---- | ---- |
+//! | `GET /datasets` | 数据集名称列表 |
+//! | `GET /datasets/{name}` | 数据集信息 |
+//! | `GET /datasets/{name}/packets?start_ns=&end_ns=&limit=` | 时间范围内的数据包（JSON，负载为 Base64） |
+//! | `GET /datasets/{name}/stream?start_ns=&end_ns=` | 时间范围内的数据包，以PCAP字节流输出 |
+
+use axum::body::Body;
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::api::reader::PcapReader;
+use crate::business::config::ReaderConfig;
+use crate::data::models::{
+    DatasetInfo, LinkType, PcapFileHeader, ValidatedPacket,
+    ValidationStatus,
+};
+use crate::foundation::error::{
+    ErrorDetail, PcapError, PcapResult,
+};
+use crate::foundation::paths::validate_dataset_name;
+use crate::foundation::utils::binary_converter;
+
+/// 单次 JSON 查询默认返回的最大数据包数量
+pub const DEFAULT_MAX_QUERY_PACKETS: usize = 10_000;
+
+/// 数据包流每个数据块的目标大小（字节）
+const STREAM_CHUNK_BYTES: usize = 64 * 1024;
+
+/// 数据包流中等待发送的数据块数量
+const STREAM_CHANNEL_CAPACITY: usize = 8;
+
+/// 时间范围查询参数
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RangeQuery {
+    /// 开始时间戳（纳秒），缺省为 0
+    pub start_ns: Option<u64>,
+    /// 结束时间戳（纳秒），缺省为最大值
+    pub end_ns: Option<u64>,
+    /// 最多返回的数据包数量，不能超过服务的上限
+    pub limit: Option<usize>,
+}
+
+impl RangeQuery {
+    fn range(&self) -> (u64, u64) {
+        (
+            self.start_ns.unwrap_or(0),
+            self.end_ns.unwrap_or(u64::MAX),
+        )
+    }
+}
+
+/// JSON 查询返回的数据包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PacketRecord {
+    /// 时间戳（纳秒）
+    pub timestamp_ns: u64,
+    /// 负载长度（字节）
+    pub length: usize,
+    /// 校验和
+    pub checksum: u32,
+    /// 校验是否通过
+    pub is_valid: bool,
+    /// 详细的校验结果
+    #[serde(default)]
+    pub status: ValidationStatus,
+    /// Base64 编码的负载
+    pub data: String,
+}
+
+impl From<&ValidatedPacket> for PacketRecord {
+    fn from(packet: &ValidatedPacket) -> Self {
+        Self {
+            timestamp_ns: packet.get_timestamp_ns(),
+            length: packet.packet_length(),
+            checksum: packet.checksum(),
+            is_valid: packet.is_valid(),
+            status: packet.status().clone(),
+            data: binary_converter::bytes_to_base64(
+                &packet.packet.data,
+            ),
+        }
+    }
+}
+
+/// 错误响应，结构与 `PcapError` 序列化的结果一致
+pub type ErrorBody = ErrorDetail;
+
+/// 数据集服务
+#[derive(Debug, Clone)]
+pub struct DatasetServer {
+    base_path: PathBuf,
+    reader_config: ReaderConfig,
+    max_query_packets: usize,
+}
+
+impl DatasetServer {
+    /// 创建服务，提供 `base_path` 下的所有数据集
+    pub fn new<P: AsRef<Path>>(base_path: P) -> Self {
+        Self {
+            base_path: base_path.as_ref().to_path_buf(),
+            reader_config: ReaderConfig::default(),
+            max_query_packets: DEFAULT_MAX_QUERY_PACKETS,
+        }
+    }
+
+    /// 设置打开数据集使用的读取配置
+    pub fn with_reader_config(
+        mut self,
+        reader_config: ReaderConfig,
+    ) -> Self {
+        self.reader_config = reader_config;
+        self
+    }
+
+    /// 设置单次 JSON 查询返回的最大数据包数量
+    pub fn with_max_query_packets(
+        mut self,
+        max_query_packets: usize,
+    ) -> Self {
+        self.max_query_packets = max_query_packets.max(1);
+        self
+    }
+
+    /// 数据集根目录
+    pub fn base_path(&self) -> &Path {
+        &self.base_path
+    }
+
+    /// 构建路由，可以嵌入到调用方自己的 axum 应用中
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/datasets", get(list_datasets))
+            .route("/datasets/:name", get(dataset_info))
+            .route(
+                "/datasets/:name/packets",
+                get(query_packets),
+            )
+            .route(
+                "/datasets/:name/stream",
+                get(stream_packets),
+            )
+            .with_state(Arc::new(self))
+    }
+
+    /// 在已绑定的监听器上运行服务，直到出错
+    pub async fn serve(
+        self,
+        listener: tokio::net::TcpListener,
+    ) -> PcapResult<()> {
+        axum::serve(listener, self.router())
+            .await
+            .map_err(PcapError::Io)
+    }
+
+    /// 在后台线程中启动服务，返回用于查询地址和关闭服务的句柄
+    ///
+    /// 适用于不使用异步运行时的调用方；地址端口为 0 时由系统分配。
+    pub fn spawn<A: ToSocketAddrs>(
+        self,
+        addr: A,
+    ) -> PcapResult<ServerHandle> {
+        let listener = std::net::TcpListener::bind(addr)
+            .map_err(PcapError::Io)?;
+        listener
+            .set_nonblocking(true)
+            .map_err(PcapError::Io)?;
+        let local_addr =
+            listener.local_addr().map_err(PcapError::Io)?;
+        let runtime =
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .map_err(PcapError::Io)?;
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let router = self.router();
+
+        let thread = std::thread::spawn(move || {
+            runtime.block_on(async move {
+                let listener =
+                    tokio::net::TcpListener::from_std(
+                        listener,
+                    )?;
+                axum::serve(listener, router)
+                    .with_graceful_shutdown(async {
+                        let _ = shutdown_rx.await;
+                    })
+                    .await
+            })
+        });
+
+        info!("数据集服务已启动: http://{local_addr}");
+        Ok(ServerHandle {
+            local_addr,
+            shutdown: Some(shutdown_tx),
+            thread: Some(thread),
+        })
+    }
+
+    fn open_reader(
+        &self,
+        name: &str,
+    ) -> PcapResult<PcapReader> {
+        validate_dataset_name(name)?;
+        PcapReader::new_with_config(
+            &self.base_path,
+            name,
+            self.reader_config.clone(),
+        )
+    }
+
+    /// 根目录下的数据集名称，按名称排序
+    fn dataset_names(&self) -> PcapResult<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.base_path)
+            .map_err(PcapError::Io)?
+        {
+            let entry = entry.map_err(PcapError::Io)?;
+            if !entry.path().is_dir() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                if validate_dataset_name(name).is_ok() {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+}
+
+/// 后台服务句柄，释放时关闭服务
+pub struct ServerHandle {
+    local_addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<std::io::Result<()>>>,
+}
+
+impl ServerHandle {
+    /// 服务监听的地址
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// 关闭服务并等待后台线程退出
+    pub fn shutdown(mut self) -> PcapResult<()> {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> PcapResult<()> {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        match self.thread.take() {
+            Some(thread) => match thread.join() {
+                Ok(result) => result.map_err(PcapError::Io),
+                Err(_) => Err(PcapError::Unknown(
+                    "数据集服务线程异常退出".to_string(),
+                )),
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for ServerHandle {
+    fn drop(&mut self) {
+        if let Err(e) = self.stop() {
+            warn!("关闭数据集服务失败: {e}");
+        }
+    }
+}
+
+/// 处理函数的错误响应
+struct ApiError(PcapError);
+
+impl From<PcapError> for ApiError {
+    fn from(error: PcapError) -> Self {
+        Self(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self.0 {
+            PcapError::FileNotFound(_)
+            | PcapError::DirectoryNotFound(_) => {
+                StatusCode::NOT_FOUND
+            }
+            PcapError::InvalidArgument(_)
+            | PcapError::InvalidDatasetName(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(self.0.to_detail())).into_response()
+    }
+}
+
+/// 在阻塞线程池中执行读取操作
+async fn run_blocking<T, F>(task: F) -> Result<T, ApiError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> PcapResult<T> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(task).await {
+        Ok(result) => result.map_err(ApiError),
+        Err(e) => Err(ApiError(PcapError::Unknown(
+            format!("读取任务异常退出: {e}"),
+        ))),
+    }
+}
+
+async fn list_datasets(
+    State(server): State<Arc<DatasetServer>>,
+) -> Result<Json<Vec<String>>, ApiError> {
+    run_blocking(move || server.dataset_names())
+        .await
+        .map(Json)
+}
+
+async fn dataset_info(
+    State(server): State<Arc<DatasetServer>>,
+    UrlPath(name): UrlPath<String>,
+) -> Result<Json<DatasetInfo>, ApiError> {
+    run_blocking(move || {
+        server.open_reader(&name)?.get_dataset_info()
+    })
+    .await
+    .map(Json)
+}
+
+async fn query_packets(
+    State(server): State<Arc<DatasetServer>>,
+    UrlPath(name): UrlPath<String>,
+    Query(query): Query<RangeQuery>,
+) -> Result<Json<Vec<PacketRecord>>, ApiError> {
+    run_blocking(move || {
+        let (start_ns, end_ns) = query.range();
+        let limit = query
+            .limit
+            .unwrap_or(server.max_query_packets)
+            .min(server.max_query_packets);
+        let mut reader = server.open_reader(&name)?;
+        let mut records = Vec::new();
+        for packet in
+            reader.iter_time_range(start_ns, end_ns)?
+        {
+            if records.len() >= limit {
+                break;
+            }
+            records.push(PacketRecord::from(&packet?));
+        }
+        Ok(records)
+    })
+    .await
+    .map(Json)
+}
+
+async fn stream_packets(
+    State(server): State<Arc<DatasetServer>>,
+    UrlPath(name): UrlPath<String>,
+    Query(query): Query<RangeQuery>,
+) -> Result<Response, ApiError> {
+    // 先在请求内打开数据集，数据集不存在等错误以状态码返回
+    let reader = {
+        let server = Arc::clone(&server);
+        let name = name.clone();
+        run_blocking(move || {
+            let mut reader = server.open_reader(&name)?;
+            reader.initialize()?;
+            Ok(reader)
+        })
+        .await?
+    };
+
+    let (sender, receiver) =
+        mpsc::channel(STREAM_CHANNEL_CAPACITY);
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) =
+            produce_stream(reader, query.range(), &sender)
+        {
+            warn!("数据集 {name} 的数据包流中断: {e}");
+            let _ = sender.blocking_send(Err(
+                std::io::Error::other(e.to_string()),
+            ));
+        }
+    });
+
+    let stream = futures_util::stream::unfold(
+        receiver,
+        |mut receiver| async move {
+            receiver
+                .recv()
+                .await
+                .map(|chunk| (chunk, receiver))
+        },
+    );
+    Ok((
+        [(
+            header::CONTENT_TYPE,
+            "application/octet-stream",
+        )],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
+
+/// 把时间范围内的数据包编码为PCAP字节流，按数据块发送
+///
+/// 文件头的链路类型取第一个数据包的链路类型；客户端断开时停止读取。
+fn produce_stream(
+    mut reader: PcapReader,
+    (start_ns, end_ns): (u64, u64),
+    sender: &mpsc::Sender<std::io::Result<Vec<u8>>>,
+) -> PcapResult<()> {
+    let mut chunk = Vec::with_capacity(STREAM_CHUNK_BYTES);
+    let mut header_written = false;
+
+    for packet in
+        reader.iter_time_range(start_ns, end_ns)?
+    {
+        let packet = packet?;
+        if !header_written {
+            chunk.extend_from_slice(
+                &PcapFileHeader::with_link_type(
+                    0,
+                    packet.link_type(),
+                )
+                .to_bytes(),
+            );
+            header_written = true;
+        }
+        chunk.extend_from_slice(&packet.packet.to_bytes());
+
+        if chunk.len() >= STREAM_CHUNK_BYTES {
+            let full = std::mem::replace(
+                &mut chunk,
+                Vec::with_capacity(STREAM_CHUNK_BYTES),
+            );
+            if sender.blocking_send(Ok(full)).is_err() {
+                return Ok(());
+            }
+        }
+    }
+
+    if !header_written {
+        chunk.extend_from_slice(
+            &PcapFileHeader::with_link_type(
+                0,
+                LinkType::Unknown,
+            )
+            .to_bytes(),
+        );
+    }
+    let _ = sender.blocking_send(Ok(chunk));
+    Ok(())
+}
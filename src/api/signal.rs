@@ -0,0 +1,94 @@
+//! 中断信号处理模块（需要启用 `signal` 特性）
+//!
+//! 进程收到 Ctrl-C（SIGINT）或 SIGTERM 时，依次完成所有已登记的写入器：
+//! 刷新缓冲区、生成索引并释放数据集锁，然后以退出码 130 结束进程，
+//! 避免中断录制后留下无法读取的数据集。
+
+use log::{info, warn};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+use crate::api::writer::PcapWriter;
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 收到中断信号后的进程退出码（128 + SIGINT）
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+type WriterSlot = Weak<Mutex<PcapWriter>>;
+
+static REGISTERED_WRITERS: OnceLock<
+    Mutex<Vec<WriterSlot>>,
+> = OnceLock::new();
+static HANDLER_INSTALLED: OnceLock<Result<(), String>> =
+    OnceLock::new();
+
+fn registered_writers() -> &'static Mutex<Vec<WriterSlot>> {
+    REGISTERED_WRITERS
+        .get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// 登记写入器，收到中断信号时自动完成写入
+///
+/// 首次调用时安装进程级的信号处理函数。登记表只保存弱引用，
+/// 写入器被丢弃后自动失效，无需手动注销。
+///
+/// # 错误
+/// 进程中已有其他代码安装了 Ctrl-C 处理函数时返回 `PcapError::InvalidState`
+pub fn finalize_on_interrupt(
+    writer: &Arc<Mutex<PcapWriter>>,
+) -> PcapResult<()> {
+    let installed = HANDLER_INSTALLED.get_or_init(|| {
+        ctrlc::set_handler(|| {
+            let count = finalize_registered();
+            info!("收到中断信号，已完成 {count} 个写入器");
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        })
+        .map_err(|e| e.to_string())
+    });
+    if let Err(e) = installed {
+        return Err(PcapError::InvalidState(format!(
+            "安装中断信号处理函数失败: {e}"
+        )));
+    }
+
+    let mut writers = match registered_writers().lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    writers.retain(|slot| slot.strong_count() > 0);
+    writers.push(Arc::downgrade(writer));
+    Ok(())
+}
+
+/// 完成所有已登记且仍然存活的写入器，返回完成的数量
+///
+/// 信号处理函数在退出进程前调用；也可以在自定义的关闭流程中直接调用。
+/// 某个写入器完成失败时记录警告并继续处理其余写入器。
+pub fn finalize_registered() -> usize {
+    let slots: Vec<WriterSlot> =
+        match registered_writers().lock() {
+            Ok(mut guard) => guard.drain(..).collect(),
+            Err(poisoned) => {
+                poisoned.into_inner().drain(..).collect()
+            }
+        };
+
+    let mut finalized = 0;
+    for writer in slots.iter().filter_map(Weak::upgrade) {
+        // 写入线程 panic 导致锁中毒时仍然尝试完成，尽量保住已写入的数据
+        let mut writer = match writer.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if writer.is_finalized() {
+            continue;
+        }
+        match writer.finalize() {
+            Ok(()) => finalized += 1,
+            Err(e) => warn!(
+                "中断时完成写入器失败: {:?}, 错误: {e}",
+                writer.dataset_path()
+            ),
+        }
+    }
+    finalized
+}
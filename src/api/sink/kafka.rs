@@ -0,0 +1,128 @@
+//! Kafka 输出端
+//!
+//! 每个数据包作为一条消息发送到指定主题，消息时间戳为数据包的捕获时间（毫秒），
+//! 负载为数据包内容。生产者队列满时轮询等待，向回放流程施加背压。
+
+use log::debug;
+use rdkafka::config::ClientConfig;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::producer::{
+    BaseProducer, BaseRecord, Producer,
+};
+use std::time::Duration;
+
+use crate::api::sink::PacketSink;
+use crate::data::models::ValidatedPacket;
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 生产者队列满时每次轮询的等待时间
+const QUEUE_FULL_POLL_INTERVAL: Duration =
+    Duration::from_millis(100);
+
+/// 默认的刷新超时
+pub const DEFAULT_FLUSH_TIMEOUT: Duration =
+    Duration::from_secs(30);
+
+/// Kafka 输出端
+pub struct KafkaSink {
+    producer: BaseProducer,
+    topic: String,
+    flush_timeout: Duration,
+}
+
+impl KafkaSink {
+    /// 连接 Kafka 集群
+    ///
+    /// # 参数
+    /// - `brokers` - 以逗号分隔的 broker 地址，如 `localhost:9092`
+    /// - `topic` - 目标主题
+    pub fn new(
+        brokers: &str,
+        topic: &str,
+    ) -> PcapResult<Self> {
+        let mut config = ClientConfig::new();
+        config.set("bootstrap.servers", brokers);
+        Self::from_config(&config, topic)
+    }
+
+    /// 使用自定义的客户端配置创建输出端
+    pub fn from_config(
+        config: &ClientConfig,
+        topic: &str,
+    ) -> PcapResult<Self> {
+        let producer =
+            config.create().map_err(sink_error)?;
+        Ok(Self {
+            producer,
+            topic: topic.to_string(),
+            flush_timeout: DEFAULT_FLUSH_TIMEOUT,
+        })
+    }
+
+    /// 设置刷新超时
+    pub fn with_flush_timeout(
+        mut self,
+        timeout: Duration,
+    ) -> Self {
+        self.flush_timeout = timeout;
+        self
+    }
+
+    /// 目标主题
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// 获取内部生产者
+    pub fn producer(&self) -> &BaseProducer {
+        &self.producer
+    }
+}
+
+impl PacketSink for KafkaSink {
+    fn write(
+        &mut self,
+        packet: &ValidatedPacket,
+    ) -> PcapResult<()> {
+        let timestamp_ms =
+            (packet.get_timestamp_ns() / 1_000_000) as i64;
+        let mut record =
+            BaseRecord::<(), [u8]>::to(&self.topic)
+                .payload(&packet.packet.data)
+                .timestamp(timestamp_ms);
+
+        loop {
+            match self.producer.send(record) {
+                Ok(()) => {
+                    // 处理已完成的投递回调，避免内部队列堆积
+                    self.producer.poll(Duration::ZERO);
+                    return Ok(());
+                }
+                Err((
+                    KafkaError::MessageProduction(
+                        RDKafkaErrorCode::QueueFull,
+                    ),
+                    returned,
+                )) => {
+                    debug!(
+                        "Kafka 生产者队列已满，等待投递"
+                    );
+                    self.producer
+                        .poll(QUEUE_FULL_POLL_INTERVAL);
+                    record = returned;
+                }
+                Err((e, _)) => return Err(sink_error(e)),
+            }
+        }
+    }
+
+    fn flush(&mut self) -> PcapResult<()> {
+        self.producer
+            .flush(self.flush_timeout)
+            .map_err(sink_error)
+    }
+}
+
+fn sink_error(error: KafkaError) -> PcapError {
+    PcapError::SinkFailed(format!("Kafka: {error}"))
+}
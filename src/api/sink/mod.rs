@@ -0,0 +1,136 @@
+//! 数据包输出端模块
+//!
+//! 定义回放等流程输出数据包的统一接口。闭包可以直接作为输出端使用，
+//! 消息总线的实现按特性启用：`kafka` 启用 [`KafkaSink`]，`mqtt` 启用 [`MqttSink`]。
+//! 数据集写入器、标准 libpcap 文件写入器和 [`UdpSink`] 也都是输出端，
+//! 任意输出端都可以用 [`RateLimitedSink`] 包装以限制输出速率。
+
+#[cfg(feature = "kafka")]
+pub mod kafka;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod udp;
+
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaSink;
+#[cfg(feature = "mqtt")]
+pub use mqtt::MqttSink;
+pub use udp::UdpSink;
+
+use std::time::Duration;
+
+use crate::api::writer::PcapWriter;
+use crate::business::rate_limit::RateLimiter;
+use crate::data::libpcap::LibpcapWriter;
+use crate::data::models::ValidatedPacket;
+use crate::foundation::error::PcapResult;
+
+/// 数据包输出端
+pub trait PacketSink {
+    /// 输出一个数据包
+    ///
+    /// 输出端暂时无法接收时应阻塞等待，由此向上游施加背压。
+    fn write(
+        &mut self,
+        packet: &ValidatedPacket,
+    ) -> PcapResult<()>;
+
+    /// 等待已输出的数据包全部送达
+    fn flush(&mut self) -> PcapResult<()> {
+        Ok(())
+    }
+}
+
+impl<F> PacketSink for F
+where
+    F: FnMut(&ValidatedPacket) -> PcapResult<()>,
+{
+    fn write(
+        &mut self,
+        packet: &ValidatedPacket,
+    ) -> PcapResult<()> {
+        self(packet)
+    }
+}
+
+/// 写入数据集，完成后由调用方调用 `finalize`
+impl PacketSink for PcapWriter {
+    fn write(
+        &mut self,
+        packet: &ValidatedPacket,
+    ) -> PcapResult<()> {
+        self.write_packet(&packet.packet)
+    }
+
+    fn flush(&mut self) -> PcapResult<()> {
+        PcapWriter::flush(self)
+    }
+}
+
+/// 写入标准 libpcap 文件
+impl PacketSink for LibpcapWriter {
+    fn write(
+        &mut self,
+        packet: &ValidatedPacket,
+    ) -> PcapResult<()> {
+        self.write_packet(
+            packet.get_timestamp_ns(),
+            &packet.packet.data,
+        )
+    }
+
+    fn flush(&mut self) -> PcapResult<()> {
+        LibpcapWriter::flush(self)
+    }
+}
+
+/// 限速输出端
+///
+/// 每个数据包先经过限速器放行再交给内部输出端。
+pub struct RateLimitedSink<K> {
+    inner: K,
+    limiter: RateLimiter,
+    throttled: Duration,
+}
+
+impl<K: PacketSink> RateLimitedSink<K> {
+    /// 使用限速器包装输出端
+    pub fn new(inner: K, limiter: RateLimiter) -> Self {
+        Self {
+            inner,
+            limiter,
+            throttled: Duration::ZERO,
+        }
+    }
+
+    /// 因速率上限等待的总时长
+    pub fn throttled(&self) -> Duration {
+        self.throttled
+    }
+
+    /// 获取内部输出端
+    pub fn get_ref(&self) -> &K {
+        &self.inner
+    }
+
+    /// 取回内部输出端
+    pub fn into_inner(self) -> K {
+        self.inner
+    }
+}
+
+impl<K: PacketSink> PacketSink for RateLimitedSink<K> {
+    fn write(
+        &mut self,
+        packet: &ValidatedPacket,
+    ) -> PcapResult<()> {
+        self.throttled += self
+            .limiter
+            .acquire(packet.packet.data.len() as u64);
+        self.inner.write(packet)
+    }
+
+    fn flush(&mut self) -> PcapResult<()> {
+        self.inner.flush()
+    }
+}
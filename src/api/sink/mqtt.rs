@@ -0,0 +1,149 @@
+//! MQTT 输出端
+//!
+//! 每个数据包作为一条消息发布到指定主题，负载为数据包内容。
+//! 网络事件在后台线程中处理，发布请求队列满时 `write` 阻塞，向回放流程施加背压。
+
+use log::{debug, warn};
+use rumqttc::{
+    Client, ConnectionError, Event, MqttOptions, Outgoing,
+    QoS,
+};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::api::sink::PacketSink;
+use crate::data::models::ValidatedPacket;
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 发布请求队列容量
+const REQUEST_QUEUE_CAPACITY: usize = 64;
+
+/// 连接失败后重连前的等待时间
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// MQTT 输出端
+pub struct MqttSink {
+    client: Client,
+    topic: String,
+    qos: QoS,
+    /// 后台连接最近一次的错误
+    last_error: Arc<Mutex<Option<String>>>,
+    connection: Option<JoinHandle<()>>,
+}
+
+impl MqttSink {
+    /// 连接 MQTT broker
+    ///
+    /// # 参数
+    /// - `host`、`port` - broker 地址
+    /// - `client_id` - 客户端标识
+    /// - `topic` - 目标主题
+    pub fn new(
+        host: &str,
+        port: u16,
+        client_id: &str,
+        topic: &str,
+    ) -> PcapResult<Self> {
+        Self::from_options(
+            MqttOptions::new(client_id, host, port),
+            topic,
+            QoS::AtLeastOnce,
+        )
+    }
+
+    /// 使用自定义的连接选项和服务质量创建输出端
+    pub fn from_options(
+        options: MqttOptions,
+        topic: &str,
+        qos: QoS,
+    ) -> PcapResult<Self> {
+        let (client, mut connection) =
+            Client::new(options, REQUEST_QUEUE_CAPACITY);
+        let last_error = Arc::new(Mutex::new(None));
+
+        let error_slot = Arc::clone(&last_error);
+        let handle = thread::Builder::new()
+            .name("pcap-mqtt-sink".to_string())
+            .spawn(move || {
+                for notification in connection.iter() {
+                    match notification {
+                        Ok(Event::Outgoing(
+                            Outgoing::Disconnect,
+                        )) => break,
+                        Ok(_) => {
+                            if let Ok(mut slot) =
+                                error_slot.lock()
+                            {
+                                slot.take();
+                            }
+                        }
+                        Err(
+                            ConnectionError::RequestsDone,
+                        ) => break,
+                        Err(e) => {
+                            warn!("MQTT 连接错误: {e}");
+                            if let Ok(mut slot) =
+                                error_slot.lock()
+                            {
+                                *slot = Some(e.to_string());
+                            }
+                            thread::sleep(RECONNECT_DELAY);
+                        }
+                    }
+                }
+                debug!("MQTT 连接线程已退出");
+            })
+            .map_err(PcapError::Io)?;
+
+        Ok(Self {
+            client,
+            topic: topic.to_string(),
+            qos,
+            last_error,
+            connection: Some(handle),
+        })
+    }
+
+    /// 目标主题
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// 后台连接最近一次的错误，连接恢复后清除
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error
+            .lock()
+            .ok()
+            .and_then(|slot| slot.clone())
+    }
+}
+
+impl PacketSink for MqttSink {
+    fn write(
+        &mut self,
+        packet: &ValidatedPacket,
+    ) -> PcapResult<()> {
+        self.client
+            .publish(
+                self.topic.as_str(),
+                self.qos,
+                false,
+                packet.packet.data.clone(),
+            )
+            .map_err(|e| {
+                PcapError::SinkFailed(format!("MQTT: {e}"))
+            })
+    }
+}
+
+impl Drop for MqttSink {
+    fn drop(&mut self) {
+        if let Err(e) = self.client.disconnect() {
+            warn!("断开 MQTT 连接失败: {e}");
+        }
+        if let Some(handle) = self.connection.take() {
+            let _ = handle.join();
+        }
+    }
+}
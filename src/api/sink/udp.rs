@@ -0,0 +1,83 @@
+//! UDP 输出端
+//!
+//! 每个数据包的负载作为一个 UDP 数据报发送到目标地址。
+//! 发送缓冲区满时 `send` 阻塞，向上游施加背压。
+
+use log::debug;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use crate::api::sink::PacketSink;
+use crate::data::models::ValidatedPacket;
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 单个 UDP 数据报的最大负载长度
+pub const MAX_DATAGRAM_SIZE: usize = 65_507;
+
+/// UDP 输出端
+pub struct UdpSink {
+    socket: UdpSocket,
+    target: SocketAddr,
+}
+
+impl UdpSink {
+    /// 创建发送到 `target` 的输出端，本地绑定到任意端口
+    pub fn new<A: ToSocketAddrs>(
+        target: A,
+    ) -> PcapResult<Self> {
+        let target = target
+            .to_socket_addrs()
+            .map_err(PcapError::Io)?
+            .next()
+            .ok_or_else(|| {
+                PcapError::InvalidArgument(
+                    "无法解析目标地址".to_string(),
+                )
+            })?;
+        let bind_address: SocketAddr = if target.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(bind_address)
+            .map_err(PcapError::Io)?;
+        Self::from_socket(socket, target)
+    }
+
+    /// 使用已创建的套接字发送到 `target`
+    ///
+    /// 可以事先设置广播、组播或发送缓冲区大小等选项。
+    pub fn from_socket(
+        socket: UdpSocket,
+        target: SocketAddr,
+    ) -> PcapResult<Self> {
+        socket.connect(target).map_err(PcapError::Io)?;
+        debug!("UDP 输出端目标: {target}");
+        Ok(Self { socket, target })
+    }
+
+    /// 目标地址
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+}
+
+impl PacketSink for UdpSink {
+    fn write(
+        &mut self,
+        packet: &ValidatedPacket,
+    ) -> PcapResult<()> {
+        let data = &packet.packet.data;
+        if data.len() > MAX_DATAGRAM_SIZE {
+            return Err(PcapError::SinkFailed(format!(
+                "负载长度 {} 超出 UDP 数据报上限",
+                data.len()
+            )));
+        }
+        self.socket.send(data).map_err(|e| {
+            PcapError::SinkFailed(format!(
+                "UDP 发送失败: {e}"
+            ))
+        })?;
+        Ok(())
+    }
+}
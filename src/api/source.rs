@@ -0,0 +1,103 @@
+//! 数据包来源模块
+//!
+//! 定义本地读取器和远程读取器共同实现的读取与跳转接口，
+//! 基于该接口编写的代码可以在本地数据集和远程数据集之间切换。
+
+use crate::api::reader::PcapReader;
+use crate::data::models::{DatasetInfo, ValidatedPacket};
+use crate::foundation::error::PcapResult;
+
+/// 数据包来源
+pub trait PacketSource {
+    /// 数据集名称
+    fn dataset_name(&self) -> &str;
+
+    /// 获取数据集信息
+    fn get_dataset_info(
+        &mut self,
+    ) -> PcapResult<DatasetInfo>;
+
+    /// 读取下一个数据包，读完时返回 `Ok(None)`
+    fn read_packet(
+        &mut self,
+    ) -> PcapResult<Option<ValidatedPacket>>;
+
+    /// 批量读取最多 `count` 个数据包
+    fn read_packets(
+        &mut self,
+        count: usize,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        let mut packets = Vec::with_capacity(count);
+        while packets.len() < count {
+            match self.read_packet()? {
+                Some(packet) => packets.push(packet),
+                None => break,
+            }
+        }
+        Ok(packets)
+    }
+
+    /// 跳转到不早于指定时间戳的第一个数据包，返回实际定位到的时间戳
+    fn seek_to_timestamp(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> PcapResult<u64>;
+
+    /// 回到数据集开始位置
+    fn reset(&mut self) -> PcapResult<()>;
+
+    /// 读取时间范围内的所有数据包
+    fn read_packets_by_time_range(
+        &mut self,
+        start_timestamp_ns: u64,
+        end_timestamp_ns: u64,
+    ) -> PcapResult<Vec<ValidatedPacket>>;
+}
+
+impl PacketSource for PcapReader {
+    fn dataset_name(&self) -> &str {
+        PcapReader::dataset_name(self)
+    }
+
+    fn get_dataset_info(
+        &mut self,
+    ) -> PcapResult<DatasetInfo> {
+        PcapReader::get_dataset_info(self)
+    }
+
+    fn read_packet(
+        &mut self,
+    ) -> PcapResult<Option<ValidatedPacket>> {
+        PcapReader::read_packet(self)
+    }
+
+    fn read_packets(
+        &mut self,
+        count: usize,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        PcapReader::read_packets(self, count)
+    }
+
+    fn seek_to_timestamp(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> PcapResult<u64> {
+        PcapReader::seek_to_timestamp(self, timestamp_ns)
+    }
+
+    fn reset(&mut self) -> PcapResult<()> {
+        PcapReader::reset(self)
+    }
+
+    fn read_packets_by_time_range(
+        &mut self,
+        start_timestamp_ns: u64,
+        end_timestamp_ns: u64,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        PcapReader::read_packets_by_time_range(
+            self,
+            start_timestamp_ns,
+            end_timestamp_ns,
+        )
+    }
+}
@@ -0,0 +1,286 @@
+//! 属性测试生成器模块（需要启用 `proptest-support` 特性）
+//!
+//! 提供 proptest 策略和 `arbitrary::Arbitrary` 实现，生成文件格式支持范围内的数据包、
+//! 数据包头部和能通过校验的写入器配置，供下游的集成测试和模糊测试目标使用，
+//! 不必各自重复编写生成逻辑。
+
+use arbitrary::{Arbitrary, Unstructured};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use std::time::Duration;
+
+use crate::business::config::{
+    DuplicateTimestampPolicy, WriteVerification,
+    WriterConfig,
+};
+use crate::business::units::ByteSize;
+use crate::core::ByteOrder;
+use crate::data::models::{
+    DataPacket, DataPacketHeader, LinkType,
+};
+use crate::foundation::utils::calculate_crc32;
+
+/// 文件格式支持的最大时间戳（纳秒），秒数为 32 位
+pub const MAX_TIMESTAMP_NS: u64 =
+    u32::MAX as u64 * 1_000_000_000 + 999_999_999;
+
+/// 生成的写入器配置中缓冲区大小的上限
+const MAX_GENERATED_BUFFER: u64 = 1024 * 1024;
+
+/// 文件格式支持范围内的纳秒时间戳
+pub fn timestamp_ns() -> impl Strategy<Value = u64> {
+    0..=MAX_TIMESTAMP_NS
+}
+
+/// 链路类型，自定义类型的值不小于 `LinkType::CUSTOM_BASE`
+pub fn link_type() -> impl Strategy<Value = LinkType> {
+    prop_oneof![
+        Just(LinkType::Unknown),
+        Just(LinkType::Ethernet),
+        Just(LinkType::RawIp),
+        Just(LinkType::Udp),
+        Just(LinkType::Can),
+        (LinkType::CUSTOM_BASE..=u32::MAX)
+            .prop_map(LinkType::Custom),
+    ]
+}
+
+/// 数据包头部，各字段相互独立
+///
+/// 长度和校验和不对应任何负载，适合测试头部的编解码和严格解析。
+pub fn data_packet_header(
+) -> impl Strategy<Value = DataPacketHeader> {
+    (
+        any::<u32>(),
+        0..1_000_000_000u32,
+        any::<u32>(),
+        any::<u32>(),
+    )
+        .prop_map(
+            |(seconds, nanoseconds, length, checksum)| {
+                DataPacketHeader {
+                    timestamp_seconds: seconds,
+                    timestamp_nanoseconds: nanoseconds,
+                    packet_length: length,
+                    checksum,
+                }
+            },
+        )
+}
+
+/// 头部与负载一致的数据包，负载长度不超过 `max_payload_len`
+pub fn data_packet(
+    max_payload_len: usize,
+) -> impl Strategy<Value = DataPacket> {
+    (
+        timestamp_ns(),
+        vec(any::<u8>(), 0..=max_payload_len),
+    )
+        .prop_map(|(timestamp_ns, data)| {
+            packet_from_parts(timestamp_ns, data)
+        })
+}
+
+/// 按时间戳排序的数据包序列，最多 `max_count` 个
+pub fn packet_sequence(
+    max_count: usize,
+    max_payload_len: usize,
+) -> impl Strategy<Value = Vec<DataPacket>> {
+    vec(data_packet(max_payload_len), 0..=max_count)
+        .prop_map(|mut packets| {
+            packets
+                .sort_by_key(DataPacket::get_timestamp_ns);
+            packets
+        })
+}
+
+/// 能通过 `WriterConfig::validate` 的写入器配置
+///
+/// 文件命名格式、存储分片、文件头扩展块、libpcap 副本和心跳间隔保持默认值，
+/// 生成的配置可以直接写入临时目录。
+pub fn writer_config() -> impl Strategy<Value = WriterConfig>
+{
+    let limits = (
+        1024..=MAX_GENERATED_BUFFER,
+        1..=10_000usize,
+        prop_oneof![Just(0u64), 1024..=64 * 1024 * 1024u64],
+        prop_oneof![Just(0u64), 1024..=64 * 1024 * 1024u64],
+        0..=3600u64,
+    );
+    let behavior = (
+        any::<bool>(),
+        link_type(),
+        0..=4096usize,
+        prop_oneof![
+            Just(0u64),
+            (0..=20u32).prop_map(|p| 1 << p)
+        ],
+        0..3usize,
+        any::<bool>(),
+        any::<bool>(),
+        0..3usize,
+    );
+    (limits, behavior).prop_map(
+        |(
+            (buffer, per_file, size, raw_size, seconds),
+            (
+                auto_flush,
+                link_type,
+                batch,
+                block,
+                verification,
+                big_endian,
+                payload_hash,
+                policy,
+            ),
+        )| WriterConfig {
+            buffer_size: ByteSize::b(buffer),
+            max_packets_per_file: per_file,
+            max_file_size: ByteSize::b(size),
+            max_file_raw_size: ByteSize::b(raw_size),
+            max_file_duration: Duration::from_secs(seconds),
+            auto_flush,
+            link_type,
+            index_batch_size: batch,
+            min_write_block: ByteSize::b(block),
+            verify_on_write: write_verification(
+                verification,
+            ),
+            byte_order: byte_order(big_endian),
+            index_payload_hash: payload_hash,
+            duplicate_timestamp_policy:
+                duplicate_timestamp_policy(policy),
+            ..Default::default()
+        },
+    )
+}
+
+impl<'a> Arbitrary<'a> for DataPacketHeader {
+    fn arbitrary(
+        u: &mut Unstructured<'a>,
+    ) -> arbitrary::Result<Self> {
+        Ok(DataPacketHeader {
+            timestamp_seconds: u.arbitrary()?,
+            timestamp_nanoseconds: u
+                .int_in_range(0..=999_999_999)?,
+            packet_length: u.arbitrary()?,
+            checksum: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for DataPacket {
+    fn arbitrary(
+        u: &mut Unstructured<'a>,
+    ) -> arbitrary::Result<Self> {
+        let timestamp_ns =
+            u.int_in_range(0..=MAX_TIMESTAMP_NS)?;
+        let data: Vec<u8> = u.arbitrary()?;
+        Ok(packet_from_parts(timestamp_ns, data))
+    }
+}
+
+impl<'a> Arbitrary<'a> for WriterConfig {
+    fn arbitrary(
+        u: &mut Unstructured<'a>,
+    ) -> arbitrary::Result<Self> {
+        let optional_limit =
+            |u: &mut Unstructured<'a>| -> arbitrary::Result<u64> {
+                Ok(if u.arbitrary()? {
+                    u.int_in_range(1024..=64 * 1024 * 1024)?
+                } else {
+                    0
+                })
+            };
+        let link_type = match u.int_in_range(0..=5)? {
+            0 => LinkType::Unknown,
+            1 => LinkType::Ethernet,
+            2 => LinkType::RawIp,
+            3 => LinkType::Udp,
+            4 => LinkType::Can,
+            _ => LinkType::Custom(u.int_in_range(
+                LinkType::CUSTOM_BASE..=u32::MAX,
+            )?),
+        };
+        let block = if u.arbitrary()? {
+            1u64 << u.int_in_range(0..=20u32)?
+        } else {
+            0
+        };
+        Ok(WriterConfig {
+            buffer_size: ByteSize::b(u.int_in_range(
+                1024..=MAX_GENERATED_BUFFER,
+            )?),
+            max_packets_per_file: u
+                .int_in_range(1..=10_000)?,
+            max_file_size: ByteSize::b(optional_limit(u)?),
+            max_file_raw_size: ByteSize::b(optional_limit(
+                u,
+            )?),
+            max_file_duration: Duration::from_secs(
+                u.int_in_range(0..=3600)?,
+            ),
+            auto_flush: u.arbitrary()?,
+            link_type,
+            index_batch_size: u.int_in_range(0..=4096)?,
+            min_write_block: ByteSize::b(block),
+            verify_on_write: write_verification(
+                u.int_in_range(0..=2)?,
+            ),
+            byte_order: byte_order(u.arbitrary()?),
+            index_payload_hash: u.arbitrary()?,
+            duplicate_timestamp_policy:
+                duplicate_timestamp_policy(
+                    u.int_in_range(0..=2)?,
+                ),
+            ..Default::default()
+        })
+    }
+}
+
+/// 由时间戳和负载组装头部一致的数据包
+fn packet_from_parts(
+    timestamp_ns: u64,
+    data: Vec<u8>,
+) -> DataPacket {
+    let header = DataPacketHeader {
+        timestamp_seconds: (timestamp_ns / 1_000_000_000)
+            as u32,
+        timestamp_nanoseconds: (timestamp_ns
+            % 1_000_000_000)
+            as u32,
+        packet_length: data.len() as u32,
+        checksum: calculate_crc32(&data),
+    };
+    DataPacket {
+        header,
+        data,
+        link_type: LinkType::Unknown,
+    }
+}
+
+fn write_verification(choice: usize) -> WriteVerification {
+    match choice {
+        0 => WriteVerification::Off,
+        1 => WriteVerification::Checksum,
+        _ => WriteVerification::ReadBack,
+    }
+}
+
+fn byte_order(big_endian: bool) -> ByteOrder {
+    if big_endian {
+        ByteOrder::Big
+    } else {
+        ByteOrder::Little
+    }
+}
+
+fn duplicate_timestamp_policy(
+    choice: usize,
+) -> DuplicateTimestampPolicy {
+    match choice {
+        0 => DuplicateTimestampPolicy::Allow,
+        1 => DuplicateTimestampPolicy::BumpByOne,
+        _ => DuplicateTimestampPolicy::Error,
+    }
+}
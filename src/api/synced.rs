@@ -0,0 +1,272 @@
+//! 多数据集同步读取模块
+//!
+//! 同时打开多个数据集，按对齐后的全局时间戳顺序合并输出数据包，
+//! 用于同步回放多个传感器的记录。
+
+use log::debug;
+use std::path::Path;
+
+use crate::api::reader::PcapReader;
+use crate::business::config::ReaderConfig;
+use crate::data::models::ValidatedPacket;
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 同步读取得到的数据包
+#[derive(Debug, Clone)]
+pub struct SyncedPacket {
+    /// 数据包所属数据集的编号（按添加顺序从 0 开始）
+    pub dataset_id: usize,
+    /// 加上数据集时间偏移后的时间戳（纳秒），数据包本身的时间戳保持不变
+    pub aligned_timestamp_ns: u64,
+    /// 数据包
+    pub packet: ValidatedPacket,
+}
+
+/// 同步读取中的单个数据集
+struct SyncedSource {
+    name: String,
+    reader: PcapReader,
+    offset_ns: i64,
+    /// 已读出但尚未输出的数据包
+    pending: Option<ValidatedPacket>,
+    /// 数据集已读完
+    exhausted: bool,
+}
+
+impl SyncedSource {
+    fn aligned(&self, timestamp_ns: u64) -> u64 {
+        apply_offset(timestamp_ns, self.offset_ns)
+    }
+
+    /// 确保待输出数据包已就绪，数据集读完时返回 `None`
+    fn peek(&mut self) -> PcapResult<Option<u64>> {
+        if self.pending.is_none() && !self.exhausted {
+            self.pending = self.reader.read_packet()?;
+            self.exhausted = self.pending.is_none();
+        }
+        Ok(self.pending.as_ref().map(|packet| {
+            self.aligned(packet.get_timestamp_ns())
+        }))
+    }
+}
+
+/// 多数据集同步读取器
+///
+/// 每个数据集可以设置时间偏移（纳秒，可为负），比较时使用"数据包时间戳 + 偏移"。
+/// 对齐后时间戳相同的数据包按数据集编号顺序输出，保证回放结果稳定。
+pub struct SyncedReader {
+    sources: Vec<SyncedSource>,
+}
+
+impl SyncedReader {
+    /// 创建空的同步读取器
+    pub fn new() -> Self {
+        Self {
+            sources: Vec::new(),
+        }
+    }
+
+    /// 打开数据集并加入同步读取，返回数据集编号
+    pub fn add_dataset<P: AsRef<Path>>(
+        &mut self,
+        base_path: P,
+        dataset_name: &str,
+        offset_ns: i64,
+    ) -> PcapResult<usize> {
+        self.add_dataset_with_config(
+            base_path,
+            dataset_name,
+            offset_ns,
+            ReaderConfig::default(),
+        )
+    }
+
+    /// 使用指定配置打开数据集并加入同步读取，返回数据集编号
+    pub fn add_dataset_with_config<P: AsRef<Path>>(
+        &mut self,
+        base_path: P,
+        dataset_name: &str,
+        offset_ns: i64,
+        configuration: ReaderConfig,
+    ) -> PcapResult<usize> {
+        let reader = PcapReader::new_with_config(
+            base_path,
+            dataset_name,
+            configuration,
+        )?;
+        Ok(self.add_reader(reader, offset_ns))
+    }
+
+    /// 加入已打开的读取器，从其当前位置开始同步读取，返回数据集编号
+    pub fn add_reader(
+        &mut self,
+        reader: PcapReader,
+        offset_ns: i64,
+    ) -> usize {
+        let name = reader.dataset_name().to_string();
+        debug!("同步读取加入数据集: {name}, 偏移 {offset_ns} 纳秒");
+        self.sources.push(SyncedSource {
+            name,
+            reader,
+            offset_ns,
+            pending: None,
+            exhausted: false,
+        });
+        self.sources.len() - 1
+    }
+
+    /// 数据集数量
+    pub fn dataset_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// 获取数据集名称
+    pub fn dataset_name(
+        &self,
+        dataset_id: usize,
+    ) -> Option<&str> {
+        self.sources
+            .get(dataset_id)
+            .map(|source| source.name.as_str())
+    }
+
+    /// 获取数据集的时间偏移（纳秒）
+    pub fn offset(&self, dataset_id: usize) -> Option<i64> {
+        self.sources
+            .get(dataset_id)
+            .map(|source| source.offset_ns)
+    }
+
+    /// 修改数据集的时间偏移（纳秒），对尚未输出的数据包立即生效
+    pub fn set_offset(
+        &mut self,
+        dataset_id: usize,
+        offset_ns: i64,
+    ) -> PcapResult<()> {
+        self.source_mut(dataset_id)?.offset_ns = offset_ns;
+        Ok(())
+    }
+
+    /// 获取数据集的读取器
+    pub fn reader(
+        &self,
+        dataset_id: usize,
+    ) -> Option<&PcapReader> {
+        self.sources
+            .get(dataset_id)
+            .map(|source| &source.reader)
+    }
+
+    /// 按对齐后的时间戳顺序读取下一个数据包
+    ///
+    /// 所有数据集都读完时返回 `Ok(None)`。
+    pub fn read_packet(
+        &mut self,
+    ) -> PcapResult<Option<SyncedPacket>> {
+        let mut next: Option<(usize, u64)> = None;
+        for (dataset_id, source) in
+            self.sources.iter_mut().enumerate()
+        {
+            if let Some(aligned) = source.peek()? {
+                // 时间戳相同时保留编号较小的数据集
+                if next
+                    .is_none_or(|(_, best)| aligned < best)
+                {
+                    next = Some((dataset_id, aligned));
+                }
+            }
+        }
+
+        Ok(next.and_then(|(dataset_id, aligned)| {
+            self.sources[dataset_id].pending.take().map(
+                |packet| SyncedPacket {
+                    dataset_id,
+                    aligned_timestamp_ns: aligned,
+                    packet,
+                },
+            )
+        }))
+    }
+
+    /// 批量读取最多 `count` 个数据包
+    pub fn read_packets(
+        &mut self,
+        count: usize,
+    ) -> PcapResult<Vec<SyncedPacket>> {
+        let mut packets = Vec::with_capacity(count);
+        while packets.len() < count {
+            match self.read_packet()? {
+                Some(packet) => packets.push(packet),
+                None => break,
+            }
+        }
+        Ok(packets)
+    }
+
+    /// 所有数据集跳转到对齐后的时间戳（纳秒）
+    ///
+    /// 每个数据集定位到对齐后不早于该时间戳的第一个数据包，
+    /// 没有满足条件的数据包时视为该数据集已读完。
+    pub fn seek_to_timestamp(
+        &mut self,
+        aligned_timestamp_ns: u64,
+    ) -> PcapResult<()> {
+        for source in &mut self.sources {
+            let target = apply_offset(
+                aligned_timestamp_ns,
+                source.offset_ns.saturating_neg(),
+            );
+            source.pending = None;
+            source.exhausted = false;
+            match source.reader.seek_to_timestamp(target) {
+                Ok(_) => {}
+                Err(PcapError::InvalidArgument(_)) => {
+                    source.exhausted = true;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// 所有数据集回到起始位置
+    pub fn reset(&mut self) -> PcapResult<()> {
+        for source in &mut self.sources {
+            source.reader.reset()?;
+            source.pending = None;
+            source.exhausted = false;
+        }
+        Ok(())
+    }
+
+    fn source_mut(
+        &mut self,
+        dataset_id: usize,
+    ) -> PcapResult<&mut SyncedSource> {
+        let count = self.sources.len();
+        self.sources.get_mut(dataset_id).ok_or_else(|| {
+            PcapError::InvalidArgument(format!(
+                "数据集编号 {dataset_id} 超出范围，共 {count} 个数据集"
+            ))
+        })
+    }
+}
+
+impl Default for SyncedReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Iterator for SyncedReader {
+    type Item = PcapResult<SyncedPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_packet().transpose()
+    }
+}
+
+/// 时间戳加上偏移，结果限制在 u64 范围内
+fn apply_offset(timestamp_ns: u64, offset_ns: i64) -> u64 {
+    timestamp_ns.saturating_add_signed(offset_ns)
+}
@@ -0,0 +1,461 @@
+//! 往返测试工具模块（需要启用 `test-util` 特性）
+//!
+//! `RoundTripHarness` 按种子生成确定的数据包序列，写入数据集后读回，
+//! 并逐个比较时间戳、负载和校验和。下游程序可以直接用它验证自己的写入配置，
+//! 不必重新实现本仓库测试中使用的生成和比较逻辑。
+
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use crate::api::reader::PcapReader;
+use crate::api::writer::PcapWriter;
+use crate::business::config::{ReaderConfig, WriterConfig};
+use crate::data::models::{DataPacket, DataPacketHeader};
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 报告中最多列出的差异数量
+const MAX_REPORTED_MISMATCHES: usize = 10;
+
+/// 读回的数据包与写入的数据包之间的差异
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PacketMismatch {
+    /// 写入的数据包没有读回
+    Missing {
+        /// 数据包序号
+        index: usize,
+        /// 写入的时间戳（纳秒）
+        timestamp_ns: u64,
+    },
+    /// 读回了多余的数据包
+    Unexpected {
+        /// 数据包序号
+        index: usize,
+        /// 读回的时间戳（纳秒）
+        timestamp_ns: u64,
+    },
+    /// 时间戳不同
+    Timestamp {
+        /// 数据包序号
+        index: usize,
+        /// 写入的时间戳（纳秒）
+        expected: u64,
+        /// 读回的时间戳（纳秒）
+        actual: u64,
+    },
+    /// 负载不同
+    Payload {
+        /// 数据包序号
+        index: usize,
+        /// 写入的负载长度
+        expected_len: usize,
+        /// 读回的负载长度
+        actual_len: usize,
+        /// 第一个不同字节的偏移，只有长度不同时为 None
+        first_difference: Option<usize>,
+    },
+    /// 校验和不同
+    Checksum {
+        /// 数据包序号
+        index: usize,
+        /// 写入的校验和
+        expected: u32,
+        /// 读回的校验和
+        actual: u32,
+    },
+}
+
+impl PacketMismatch {
+    /// 数据包序号
+    pub fn index(&self) -> usize {
+        match self {
+            PacketMismatch::Missing { index, .. }
+            | PacketMismatch::Unexpected {
+                index, ..
+            }
+            | PacketMismatch::Timestamp { index, .. }
+            | PacketMismatch::Payload { index, .. }
+            | PacketMismatch::Checksum { index, .. } => {
+                *index
+            }
+        }
+    }
+}
+
+impl fmt::Display for PacketMismatch {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            PacketMismatch::Missing {
+                index,
+                timestamp_ns,
+            } => write!(
+                f,
+                "数据包 {index}: 未读回（写入时间戳 {timestamp_ns}）"
+            ),
+            PacketMismatch::Unexpected {
+                index,
+                timestamp_ns,
+            } => write!(
+                f,
+                "数据包 {index}: 读回多余的数据包（时间戳 {timestamp_ns}）"
+            ),
+            PacketMismatch::Timestamp {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "数据包 {index}: 时间戳应为 {expected}，实际为 {actual}"
+            ),
+            PacketMismatch::Payload {
+                index,
+                expected_len,
+                actual_len,
+                first_difference,
+            } => match first_difference {
+                Some(offset) => write!(
+                    f,
+                    "数据包 {index}: 负载在偏移 {offset} 处不同（长度 {expected_len} / {actual_len}）"
+                ),
+                None => write!(
+                    f,
+                    "数据包 {index}: 负载长度应为 {expected_len}，实际为 {actual_len}"
+                ),
+            },
+            PacketMismatch::Checksum {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "数据包 {index}: 校验和应为 0x{expected:08X}，实际为 0x{actual:08X}"
+            ),
+        }
+    }
+}
+
+/// 一次往返测试的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundTripReport {
+    /// 生成数据包使用的种子
+    pub seed: u64,
+    /// 写入的数据包数量
+    pub written: usize,
+    /// 读回的数据包数量
+    pub read: usize,
+    /// 发现的差异，按数据包序号排列
+    pub mismatches: Vec<PacketMismatch>,
+}
+
+impl RoundTripReport {
+    /// 读回的数据包是否与写入的完全一致
+    pub fn is_identical(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for RoundTripReport {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(
+            f,
+            "往返测试（种子 {}）: 写入 {} 个数据包，读回 {} 个",
+            self.seed, self.written, self.read
+        )?;
+        if self.is_identical() {
+            return write!(f, "，完全一致");
+        }
+        write!(
+            f,
+            "，发现 {} 处差异",
+            self.mismatches.len()
+        )?;
+        for mismatch in self
+            .mismatches
+            .iter()
+            .take(MAX_REPORTED_MISMATCHES)
+        {
+            write!(f, "\n  {mismatch}")?;
+        }
+        if self.mismatches.len() > MAX_REPORTED_MISMATCHES {
+            write!(
+                f,
+                "\n  ……其余 {} 处差异未列出",
+                self.mismatches.len()
+                    - MAX_REPORTED_MISMATCHES
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 确定性往返测试工具
+///
+/// 相同的种子和参数总是生成相同的数据包序列：时间戳从 `start_timestamp_ns` 开始严格递增，
+/// 相邻间隔在 `1..=max_interval_ns` 内，负载长度在 `payload_len` 内，内容为伪随机字节。
+///
+/// ```no_run
+/// use pcapfile_io::testing::RoundTripHarness;
+/// use pcapfile_io::WriterConfig;
+///
+/// let harness = RoundTripHarness {
+///     packet_count: 500,
+///     writer_config: WriterConfig {
+///         max_packets_per_file: 64,
+///         ..Default::default()
+///     },
+///     ..RoundTripHarness::new(42)
+/// };
+/// harness.assert_round_trip("./output", "round_trip");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RoundTripHarness {
+    /// 伪随机种子
+    pub seed: u64,
+    /// 生成的数据包数量
+    pub packet_count: usize,
+    /// 负载长度范围（字节）
+    pub payload_len: RangeInclusive<usize>,
+    /// 第一个数据包的时间戳（纳秒）
+    pub start_timestamp_ns: u64,
+    /// 相邻数据包的最大时间间隔（纳秒），至少为 1
+    pub max_interval_ns: u64,
+    /// 写入使用的配置
+    pub writer_config: WriterConfig,
+    /// 读回使用的配置
+    pub reader_config: ReaderConfig,
+}
+
+impl RoundTripHarness {
+    /// 以默认参数创建：100 个数据包，负载 1~1500 字节，间隔不超过 1 毫秒
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            packet_count: 100,
+            payload_len: 1..=1500,
+            start_timestamp_ns: 1_700_000_000_000_000_000,
+            max_interval_ns: 1_000_000,
+            writer_config: WriterConfig::default(),
+            reader_config: ReaderConfig::default(),
+        }
+    }
+
+    /// 生成数据包序列
+    ///
+    /// # 错误
+    /// 负载长度范围为空或时间戳超出文件格式支持的范围时返回 `PcapError::InvalidArgument`
+    pub fn generate(&self) -> PcapResult<Vec<DataPacket>> {
+        if self.payload_len.is_empty() {
+            return Err(PcapError::InvalidArgument(
+                format!(
+                    "负载长度范围为空: {:?}",
+                    self.payload_len
+                ),
+            ));
+        }
+
+        let mut rng = SplitMix64(self.seed);
+        let (min_len, max_len) = (
+            *self.payload_len.start(),
+            *self.payload_len.end(),
+        );
+        let max_interval = self.max_interval_ns.max(1);
+        let mut timestamp_ns = self.start_timestamp_ns;
+        let mut packets =
+            Vec::with_capacity(self.packet_count);
+        for i in 0..self.packet_count {
+            if i > 0 {
+                timestamp_ns = timestamp_ns.saturating_add(
+                    1 + rng.below(max_interval),
+                );
+            }
+            let len = min_len
+                + rng.below((max_len - min_len) as u64 + 1)
+                    as usize;
+            let mut data = Vec::with_capacity(len + 8);
+            while data.len() < len {
+                data.extend_from_slice(
+                    &rng.next().to_le_bytes(),
+                );
+            }
+            data.truncate(len);
+
+            let header =
+                DataPacketHeader::from_timestamp_ns(
+                    timestamp_ns,
+                    &data,
+                )
+                .map_err(PcapError::InvalidArgument)?;
+            packets.push(
+                DataPacket::new(header, data)
+                    .map_err(PcapError::InvalidArgument)?,
+            );
+        }
+        Ok(packets)
+    }
+
+    /// 把生成的数据包写入数据集并完成写入，返回写入的数据包
+    pub fn write<P: AsRef<Path>>(
+        &self,
+        base_path: P,
+        dataset_name: &str,
+    ) -> PcapResult<Vec<DataPacket>> {
+        let packets = self.generate()?;
+        let mut writer = PcapWriter::new_with_config(
+            base_path,
+            dataset_name,
+            self.writer_config.clone(),
+        )?;
+        writer.write_packets(&packets)?;
+        writer.finalize()?;
+        Ok(packets)
+    }
+
+    /// 按顺序读回数据集中的全部数据包
+    pub fn read_back<P: AsRef<Path>>(
+        &self,
+        base_path: P,
+        dataset_name: &str,
+    ) -> PcapResult<Vec<DataPacket>> {
+        let mut reader = PcapReader::new_with_config(
+            base_path,
+            dataset_name,
+            self.reader_config.clone(),
+        )?;
+        let mut packets = Vec::new();
+        while let Some(packet) = reader.read_packet()? {
+            packets.push(packet.packet);
+        }
+        Ok(packets)
+    }
+
+    /// 写入后读回并比较
+    pub fn run<P: AsRef<Path>>(
+        &self,
+        base_path: P,
+        dataset_name: &str,
+    ) -> PcapResult<RoundTripReport> {
+        let base_path = base_path.as_ref();
+        let written =
+            self.write(base_path, dataset_name)?;
+        let read =
+            self.read_back(base_path, dataset_name)?;
+        Ok(RoundTripReport {
+            seed: self.seed,
+            written: written.len(),
+            read: read.len(),
+            mismatches: compare_packets(&written, &read),
+        })
+    }
+
+    /// 写入后读回，不一致时 panic 并输出差异详情
+    ///
+    /// # Panics
+    /// 写入或读取失败，或读回的数据包与写入的不一致时 panic
+    #[track_caller]
+    pub fn assert_round_trip<P: AsRef<Path>>(
+        &self,
+        base_path: P,
+        dataset_name: &str,
+    ) -> RoundTripReport {
+        let report = match self.run(base_path, dataset_name)
+        {
+            Ok(report) => report,
+            Err(e) => panic!(
+                "往返测试（种子 {}）失败: {e}",
+                self.seed
+            ),
+        };
+        assert!(report.is_identical(), "{report}");
+        report
+    }
+}
+
+/// 逐个比较两组数据包的时间戳、负载和校验和
+pub fn compare_packets(
+    expected: &[DataPacket],
+    actual: &[DataPacket],
+) -> Vec<PacketMismatch> {
+    let mut mismatches = Vec::new();
+    for (index, (expected, actual)) in
+        expected.iter().zip(actual).enumerate()
+    {
+        let (expected_ts, actual_ts) = (
+            expected.get_timestamp_ns(),
+            actual.get_timestamp_ns(),
+        );
+        if expected_ts != actual_ts {
+            mismatches.push(PacketMismatch::Timestamp {
+                index,
+                expected: expected_ts,
+                actual: actual_ts,
+            });
+        }
+        if expected.data != actual.data {
+            let first_difference = expected
+                .data
+                .iter()
+                .zip(&actual.data)
+                .position(|(a, b)| a != b);
+            mismatches.push(PacketMismatch::Payload {
+                index,
+                expected_len: expected.data.len(),
+                actual_len: actual.data.len(),
+                first_difference,
+            });
+        }
+        if expected.checksum() != actual.checksum() {
+            mismatches.push(PacketMismatch::Checksum {
+                index,
+                expected: expected.checksum(),
+                actual: actual.checksum(),
+            });
+        }
+    }
+    for (index, packet) in
+        expected.iter().enumerate().skip(actual.len())
+    {
+        mismatches.push(PacketMismatch::Missing {
+            index,
+            timestamp_ns: packet.get_timestamp_ns(),
+        });
+    }
+    for (index, packet) in
+        actual.iter().enumerate().skip(expected.len())
+    {
+        mismatches.push(PacketMismatch::Unexpected {
+            index,
+            timestamp_ns: packet.get_timestamp_ns(),
+        });
+    }
+    mismatches
+}
+
+/// SplitMix64 伪随机数生成器，输出只取决于种子，不随平台和依赖版本变化
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30))
+            .wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27))
+            .wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// `0..bound` 内的随机数，`bound` 为 0 时返回 0
+    fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            0
+        } else {
+            self.next() % bound
+        }
+    }
+}
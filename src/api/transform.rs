@@ -0,0 +1,501 @@
+//! 数据集变换模块
+//!
+//! 把源数据集逐包读出、变换后写入新的数据集，由写入器重新生成文件头和索引。
+//! 源数据集保持不变。读写流程基于 [`crate::api::export`] 的流式导出核心。
+
+use log::info;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::api::export::{self, ExportOptions};
+use crate::api::reader::PcapReader;
+use crate::api::writer::PcapWriter;
+use crate::business::config::{ReaderConfig, WriterConfig};
+use crate::business::rate_limit::RateLimiter;
+use crate::data::models::{
+    DataPacket, LinkType, ValidatedPacket,
+};
+use crate::foundation::error::{PcapError, PcapResult};
+use crate::foundation::paths;
+use crate::foundation::utils::calculate_crc32;
+
+/// 数据集变换结果统计
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransformSummary {
+    /// 写入的数据包数量
+    pub packets: u64,
+    /// 其中校验失败的数据包数量（原样复制）
+    pub invalid_packets: u64,
+    /// 目标数据集的文件数量
+    pub files: usize,
+    /// 负载被脱敏规则修改的数据包数量，仅 `scrub`
+    pub scrubbed_packets: u64,
+}
+
+/// 数据集变换工具
+///
+/// 读取和写入分别使用 `reader_config` 和 `writer_config`；
+/// 写入配置未指定链路类型时沿用源数据集的链路类型。
+#[derive(Debug, Clone, Default)]
+pub struct DatasetTransformer {
+    /// 读取源数据集的配置
+    pub reader_config: ReaderConfig,
+    /// 写入目标数据集的配置
+    pub writer_config: WriterConfig,
+    /// 写入目标数据集的速率限制，默认不限制
+    pub rate_limiter: RateLimiter,
+}
+
+impl DatasetTransformer {
+    /// 使用默认配置创建变换工具
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置读取配置
+    pub fn with_reader_config(
+        mut self,
+        config: ReaderConfig,
+    ) -> Self {
+        self.reader_config = config;
+        self
+    }
+
+    /// 设置写入配置
+    pub fn with_writer_config(
+        mut self,
+        config: WriterConfig,
+    ) -> Self {
+        self.writer_config = config;
+        self
+    }
+
+    /// 设置写入速率限制
+    pub fn with_rate_limiter(
+        mut self,
+        limiter: RateLimiter,
+    ) -> Self {
+        self.rate_limiter = limiter;
+        self
+    }
+
+    /// 平移数据集中所有数据包的时间戳
+    ///
+    /// 把 `src` 数据集的每个数据包时间戳加上 `delta_ns`（可为负）后写入 `dst` 数据集，
+    /// 负载和校验和不变，索引按新的时间戳重新生成。可用于隐藏真实采集时间，
+    /// 或把多台记录设备的数据集对齐到同一时钟。
+    ///
+    /// # 参数
+    /// - `src` - 源数据集目录
+    /// - `dst` - 目标数据集目录，不能与源相同且不能已包含数据
+    /// - `delta_ns` - 时间偏移（纳秒）
+    ///
+    /// # 错误
+    /// 任一数据包平移后超出文件格式支持的时间范围时返回 `PcapError::InvalidArgument`，
+    /// 此时目标数据集只包含已写入的部分数据
+    pub fn shift_time<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        src: P,
+        dst: Q,
+        delta_ns: i64,
+    ) -> PcapResult<TransformSummary> {
+        self.rewrite_packets(src, dst, |packet| {
+            packet.shift_timestamp(delta_ns)
+        })
+    }
+
+    /// 按参考点分段线性校正时间戳
+    ///
+    /// `reference_points` 为（记录时间戳，真实时间戳）对，相邻参考点之间按线性插值校正，
+    /// 首个参考点之前和最后一个参考点之后沿用最近一段的斜率外推；只有一个参考点时等同于平移。
+    /// 用于修正本地时钟漂移的记录设备产生的数据集。
+    ///
+    /// # 错误
+    /// 参考点为空、记录时间戳重复或校正后时间倒退时返回 `PcapError::InvalidArgument`
+    pub fn rescale_time<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        src: P,
+        dst: Q,
+        reference_points: &[(u64, u64)],
+    ) -> PcapResult<TransformSummary> {
+        let correction =
+            TimeCorrection::new(reference_points)?;
+        self.rewrite_packets(src, dst, |packet| {
+            let timestamp_ns = correction
+                .apply(packet.get_timestamp_ns())
+                .ok_or_else(|| {
+                    "校正后的时间戳超出范围".to_string()
+                })?;
+            packet.header.set_timestamp_ns(timestamp_ns)
+        })
+    }
+
+    /// 对负载脱敏后写入新的数据集，便于安全地共享包含敏感内容的数据集
+    ///
+    /// 每个数据包的负载依次应用 `scrubber` 的规则，负载长度和校验和随之更新，
+    /// 时间戳和链路类型不变。源数据集中校验失败的数据包同样按新负载重新计算校验和，
+    /// 目标数据集中不再能发现这些错误，数量记录在 `TransformSummary::invalid_packets` 中。
+    ///
+    /// # 错误
+    /// 脱敏规则无效时返回 `PcapError::InvalidArgument`，见 `Scrubber::validate`
+    pub fn scrub<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        src: P,
+        dst: Q,
+        scrubber: &Scrubber,
+    ) -> PcapResult<TransformSummary> {
+        scrubber
+            .validate()
+            .map_err(PcapError::InvalidArgument)?;
+        let mut scrubbed = 0;
+        let mut summary =
+            self.rewrite_packets(src, dst, |packet| {
+                if scrubber.scrub(&mut packet.data) {
+                    packet.header.packet_length =
+                        packet.data.len() as u32;
+                    packet.header.checksum =
+                        calculate_crc32(&packet.data);
+                    scrubbed += 1;
+                }
+                Ok(())
+            })?;
+        summary.scrubbed_packets = scrubbed;
+        Ok(summary)
+    }
+
+    /// 逐包改写后写入目标数据集
+    fn rewrite_packets<P, Q, F>(
+        &self,
+        src: P,
+        dst: Q,
+        mut rewrite: F,
+    ) -> PcapResult<TransformSummary>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+        F: FnMut(&mut DataPacket) -> Result<(), String>,
+    {
+        let (src_base, src_name) =
+            paths::split_dataset_path(src)?;
+        let (dst_base, dst_name) =
+            paths::split_dataset_path(dst)?;
+
+        let dst_path =
+            paths::dataset_path(&dst_base, &dst_name)?;
+        if paths::dataset_path(&src_base, &src_name)?
+            == dst_path
+        {
+            return Err(PcapError::InvalidArgument(
+                "目标数据集不能与源数据集相同".to_string(),
+            ));
+        }
+        if dst_path
+            .read_dir()
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(false)
+        {
+            let message = format!(
+                "目标数据集已存在且不为空: {dst_path:?}"
+            );
+            return Err(PcapError::InvalidArgument(
+                message,
+            ));
+        }
+
+        let mut reader = PcapReader::new_with_config(
+            &src_base,
+            &src_name,
+            self.reader_config.clone(),
+        )?;
+
+        let mut writer: Option<PcapWriter> = None;
+        let mut summary = TransformSummary::default();
+        let mut sink = |validated: &ValidatedPacket| {
+            let mut packet = validated.packet.clone();
+            rewrite(&mut packet)
+                .map_err(PcapError::InvalidArgument)?;
+
+            // 首个数据包确定链路类型后再创建写入器
+            let writer = match writer {
+                Some(ref mut writer) => writer,
+                None => writer.insert(self.create_writer(
+                    &dst_base,
+                    &dst_name,
+                    packet.link_type(),
+                )?),
+            };
+            writer.write_packet(&packet)?;
+
+            summary.packets += 1;
+            if !validated.is_valid {
+                summary.invalid_packets += 1;
+            }
+            Ok(())
+        };
+        export::pipe_with(
+            &mut reader,
+            &mut [&mut sink],
+            &ExportOptions {
+                rate_limiter: self.rate_limiter.clone(),
+                ..ExportOptions::default()
+            },
+        )?;
+
+        // 空数据集也生成目标数据集
+        let mut writer = match writer {
+            Some(writer) => writer,
+            None => self.create_writer(
+                &dst_base,
+                &dst_name,
+                LinkType::Unknown,
+            )?,
+        };
+        writer.finalize()?;
+        summary.files =
+            writer.get_dataset_info().file_count;
+
+        info!(
+            "数据集变换完成: {src_name} -> {dst_name}, 数据包 {}",
+            summary.packets
+        );
+        Ok(summary)
+    }
+
+    fn create_writer(
+        &self,
+        base_path: &Path,
+        dataset_name: &str,
+        link_type: LinkType,
+    ) -> PcapResult<PcapWriter> {
+        let mut config = self.writer_config.clone();
+        if config.link_type == LinkType::Unknown {
+            config.link_type = link_type;
+        }
+        PcapWriter::new_with_config(
+            base_path,
+            dataset_name,
+            config,
+        )
+    }
+}
+
+/// 分段线性时间校正
+///
+/// 由（记录时间戳，真实时间戳）参考点构成，按记录时间戳排序后相邻两点确定一段校正直线。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeCorrection {
+    points: Vec<(u64, u64)>,
+}
+
+impl TimeCorrection {
+    /// 由参考点创建时间校正
+    ///
+    /// # 错误
+    /// 参考点为空、记录时间戳重复或真实时间戳随记录时间戳倒退时返回
+    /// `PcapError::InvalidArgument`
+    pub fn new(
+        reference_points: &[(u64, u64)],
+    ) -> PcapResult<Self> {
+        if reference_points.is_empty() {
+            return Err(PcapError::InvalidArgument(
+                "至少需要一个时间参考点".to_string(),
+            ));
+        }
+
+        let mut points = reference_points.to_vec();
+        points.sort_unstable();
+        for pair in points.windows(2) {
+            if pair[0].0 == pair[1].0 {
+                return Err(PcapError::InvalidArgument(
+                    format!(
+                        "参考点记录时间戳重复: {}",
+                        pair[0].0
+                    ),
+                ));
+            }
+            if pair[1].1 < pair[0].1 {
+                return Err(PcapError::InvalidArgument(
+                    format!(
+                    "参考点真实时间戳倒退: {:?} -> {:?}",
+                    pair[0], pair[1]
+                ),
+                ));
+            }
+        }
+        Ok(Self { points })
+    }
+
+    /// 按记录时间戳排序的参考点
+    pub fn points(&self) -> &[(u64, u64)] {
+        &self.points
+    }
+
+    /// 校正一个记录时间戳，结果超出 u64 范围时返回 `None`
+    pub fn apply(&self, recorded_ns: u64) -> Option<u64> {
+        if self.points.len() == 1 {
+            let (recorded, actual) = self.points[0];
+            let corrected = recorded_ns as i128
+                + (actual as i128 - recorded as i128);
+            return u64::try_from(corrected).ok();
+        }
+
+        // 选取包含该时间戳的一段，两端之外沿用首段或末段外推
+        let segment = self
+            .points
+            .partition_point(|&(recorded, _)| {
+                recorded <= recorded_ns
+            })
+            .clamp(1, self.points.len() - 1);
+        let (x0, y0) = self.points[segment - 1];
+        let (x1, y1) = self.points[segment];
+
+        let numerator = (recorded_ns as i128 - x0 as i128)
+            .checked_mul(y1 as i128 - y0 as i128)?;
+        let corrected = y0 as i128
+            + numerator.div_euclid(x1 as i128 - x0 as i128);
+        u64::try_from(corrected).ok()
+    }
+}
+
+/// 负载脱敏规则
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScrubRule {
+    /// 把指定字节范围置零，超出负载长度的部分忽略
+    ZeroRange(Range<usize>),
+    /// 把负载中不重叠出现的字节序列依次替换为另一字节序列，两者长度可以不同
+    Replace {
+        /// 要替换的字节序列，不能为空
+        pattern: Vec<u8>,
+        /// 替换后的字节序列
+        replacement: Vec<u8>,
+    },
+    /// 只保留负载的前 N 个字节
+    KeepFirst(usize),
+}
+
+impl ScrubRule {
+    /// 对负载应用规则，返回负载是否被修改
+    pub fn apply(&self, payload: &mut Vec<u8>) -> bool {
+        match self {
+            ScrubRule::ZeroRange(range) => {
+                let end = range.end.min(payload.len());
+                let start = range.start.min(end);
+                let target = &mut payload[start..end];
+                let changed =
+                    target.iter().any(|&byte| byte != 0);
+                target.fill(0);
+                changed
+            }
+            ScrubRule::Replace {
+                pattern,
+                replacement,
+            } => {
+                if pattern.is_empty()
+                    || pattern == replacement
+                {
+                    return false;
+                }
+                let mut output = Vec::new();
+                let mut position = 0;
+                while let Some(found) = payload[position..]
+                    .windows(pattern.len())
+                    .position(|window| window == pattern)
+                {
+                    output.extend_from_slice(
+                        &payload
+                            [position..position + found],
+                    );
+                    output.extend_from_slice(replacement);
+                    position += found + pattern.len();
+                }
+                if position == 0 {
+                    return false;
+                }
+                output.extend_from_slice(
+                    &payload[position..],
+                );
+                *payload = output;
+                true
+            }
+            ScrubRule::KeepFirst(length) => {
+                let changed = payload.len() > *length;
+                payload.truncate(*length);
+                changed
+            }
+        }
+    }
+}
+
+/// 负载脱敏器
+///
+/// 按添加顺序对每个数据包的负载依次应用脱敏规则，由 `DatasetTransformer::scrub` 使用。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scrubber {
+    rules: Vec<ScrubRule>,
+}
+
+impl Scrubber {
+    /// 创建不包含任何规则的脱敏器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一条规则
+    pub fn with_rule(mut self, rule: ScrubRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// 把指定字节范围置零
+    pub fn zero_range(self, range: Range<usize>) -> Self {
+        self.with_rule(ScrubRule::ZeroRange(range))
+    }
+
+    /// 把出现的字节序列替换为另一字节序列
+    pub fn replace(
+        self,
+        pattern: impl Into<Vec<u8>>,
+        replacement: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.with_rule(ScrubRule::Replace {
+            pattern: pattern.into(),
+            replacement: replacement.into(),
+        })
+    }
+
+    /// 只保留负载的前 N 个字节
+    pub fn keep_first(self, length: usize) -> Self {
+        self.with_rule(ScrubRule::KeepFirst(length))
+    }
+
+    /// 按添加顺序排列的规则
+    pub fn rules(&self) -> &[ScrubRule] {
+        &self.rules
+    }
+
+    /// 验证规则是否有效
+    ///
+    /// 替换规则的字节序列为空时返回错误。
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if let ScrubRule::Replace { pattern, .. } = rule
+            {
+                if pattern.is_empty() {
+                    return Err(format!(
+                        "rules[{i}].pattern: 要替换的字节序列不能为空"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 对负载依次应用全部规则，返回负载是否被修改
+    pub fn scrub(&self, payload: &mut Vec<u8>) -> bool {
+        let mut changed = false;
+        for rule in &self.rules {
+            changed |= rule.apply(payload);
+        }
+        changed
+    }
+}
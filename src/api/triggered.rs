@@ -0,0 +1,210 @@
+//! 触发式记录模块
+//!
+//! 在内存中滚动保留最近一段时间的数据包，只有调用 `trigger()` 时才把触发前的缓冲数据
+//! 连同触发后一段时间内的数据写入磁盘，适用于无法保存全部数据、只记录异常前后的采集程序。
+//! 时间窗口以数据包时间戳计算，与系统时钟无关。
+
+use log::{debug, info};
+use std::collections::VecDeque;
+use std::path::Path;
+
+use crate::api::writer::PcapWriter;
+use crate::business::config::{
+    TriggerConfig, WriterConfig,
+};
+use crate::data::models::DataPacket;
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 触发式记录状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TriggerState {
+    /// 只缓冲，不写入磁盘
+    Buffering,
+    /// 正在记录触发后的窗口，截止时间戳在收到下一个数据包时确定
+    Recording { until_ns: Option<u64> },
+}
+
+/// 触发式写入器
+pub struct TriggeredWriter {
+    writer: PcapWriter,
+    configuration: TriggerConfig,
+    buffer: VecDeque<DataPacket>,
+    buffered_bytes: usize,
+    state: TriggerState,
+    trigger_count: usize,
+}
+
+impl TriggeredWriter {
+    /// 使用默认写入配置创建触发式写入器
+    pub fn new<P: AsRef<Path>>(
+        base_path: P,
+        dataset_name: &str,
+        trigger_config: TriggerConfig,
+    ) -> PcapResult<Self> {
+        Self::new_with_config(
+            base_path,
+            dataset_name,
+            WriterConfig::default(),
+            trigger_config,
+        )
+    }
+
+    /// 使用指定写入配置创建触发式写入器
+    pub fn new_with_config<P: AsRef<Path>>(
+        base_path: P,
+        dataset_name: &str,
+        writer_config: WriterConfig,
+        trigger_config: TriggerConfig,
+    ) -> PcapResult<Self> {
+        trigger_config.validate().map_err(|e| {
+            PcapError::InvalidArgument(format!(
+                "触发式记录配置无效: {e}"
+            ))
+        })?;
+        let writer = PcapWriter::new_with_config(
+            base_path,
+            dataset_name,
+            writer_config,
+        )?;
+
+        Ok(Self {
+            writer,
+            configuration: trigger_config,
+            buffer: VecDeque::new(),
+            buffered_bytes: 0,
+            state: TriggerState::Buffering,
+            trigger_count: 0,
+        })
+    }
+
+    /// 写入数据包
+    ///
+    /// 处于触发后窗口内时直接写入磁盘，否则放入预缓冲区，
+    /// 超出触发前时间窗口或字节上限的旧数据包被丢弃。
+    pub fn write_packet(
+        &mut self,
+        packet: &DataPacket,
+    ) -> PcapResult<()> {
+        let timestamp_ns = packet.get_timestamp_ns();
+
+        if let TriggerState::Recording { until_ns } =
+            self.state
+        {
+            let until_ns = until_ns.unwrap_or_else(|| {
+                timestamp_ns.saturating_add(
+                    self.configuration.post_trigger_ns,
+                )
+            });
+            if timestamp_ns <= until_ns {
+                self.state = TriggerState::Recording {
+                    until_ns: Some(until_ns),
+                };
+                return self.writer.write_packet(packet);
+            }
+
+            info!("触发后窗口结束，恢复预缓冲");
+            self.state = TriggerState::Buffering;
+            self.writer.flush()?;
+        }
+
+        self.buffered_bytes += packet.packet_length();
+        self.buffer.push_back(packet.clone());
+        self.evict(timestamp_ns);
+        Ok(())
+    }
+
+    /// 触发记录
+    ///
+    /// 把预缓冲区中的数据包写入磁盘，并从最新数据包的时间戳起继续记录
+    /// `post_trigger_ns` 纳秒。正在记录时再次触发会延长触发后窗口。
+    pub fn trigger(&mut self) -> PcapResult<()> {
+        let latest_ns = self
+            .buffer
+            .back()
+            .map(|packet| packet.get_timestamp_ns());
+
+        let flushed = self.buffer.len();
+        while let Some(packet) = self.buffer.pop_front() {
+            self.buffered_bytes -= packet.packet_length();
+            self.writer.write_packet(&packet)?;
+        }
+
+        // 缓冲区为空（如正在记录时再次触发）时无法得知最新时间，
+        // 截止时间交给下一个数据包确定
+        let until_ns = latest_ns.map(|latest| {
+            latest.saturating_add(
+                self.configuration.post_trigger_ns,
+            )
+        });
+        self.state = TriggerState::Recording { until_ns };
+        self.trigger_count += 1;
+
+        debug!(
+            "已触发记录，写入预缓冲数据包 {flushed} 个，触发后截止时间: {until_ns:?}"
+        );
+        Ok(())
+    }
+
+    /// 是否处于触发后的记录窗口
+    pub fn is_recording(&self) -> bool {
+        matches!(self.state, TriggerState::Recording { .. })
+    }
+
+    /// 预缓冲区中的数据包数量
+    pub fn buffered_packets(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// 预缓冲区中的负载字节数
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffered_bytes
+    }
+
+    /// 已触发的次数
+    pub fn trigger_count(&self) -> usize {
+        self.trigger_count
+    }
+
+    /// 触发式记录配置
+    pub fn trigger_config(&self) -> &TriggerConfig {
+        &self.configuration
+    }
+
+    /// 获取内部写入器
+    pub fn writer(&self) -> &PcapWriter {
+        &self.writer
+    }
+
+    /// 获取内部写入器的可变引用
+    pub fn writer_mut(&mut self) -> &mut PcapWriter {
+        &mut self.writer
+    }
+
+    /// 完成写入，未触发的预缓冲数据被丢弃
+    pub fn finalize(&mut self) -> PcapResult<()> {
+        self.buffer.clear();
+        self.buffered_bytes = 0;
+        self.writer.finalize()
+    }
+
+    /// 丢弃超出触发前时间窗口或字节上限的旧数据包
+    fn evict(&mut self, latest_ns: u64) {
+        let window_start = latest_ns.saturating_sub(
+            self.configuration.pre_trigger_ns,
+        );
+        let max_bytes =
+            self.configuration.max_buffered_bytes;
+
+        while let Some(front) = self.buffer.front() {
+            let expired =
+                front.get_timestamp_ns() < window_start;
+            let over_limit = max_bytes > 0
+                && self.buffered_bytes > max_bytes;
+            if !expired && !over_limit {
+                break;
+            }
+            self.buffered_bytes -= front.packet_length();
+            self.buffer.pop_front();
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! WASM 绑定模块
+//!
+//! 通过 `wasm-bindgen` 向 JavaScript 暴露内存字节流读取器，用于在浏览器中检查数据集。
+//! 浏览器中的 `File` 可以用 `file.slice(start, end).arrayBuffer()` 分块读取后逐块追加。
+//!
+//! ```javascript
+//! const reader = new WasmReader();
+//! for (let start = 0; start < file.size; start += CHUNK_SIZE) {
+//!     const chunk = await file.slice(start, start + CHUNK_SIZE).arrayBuffer();
+//!     reader.pushChunk(new Uint8Array(chunk));
+//!     let packet;
+//!     while ((packet = reader.nextPacket()) !== undefined) {
+//!         console.log(packet.timestampNs, packet.payload.length);
+//!     }
+//! }
+//! reader.finish();
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+use crate::api::memory::MemoryReader;
+use crate::data::models::ValidatedPacket;
+
+/// 暴露给 JavaScript 的读取器
+#[wasm_bindgen]
+pub struct WasmReader {
+    inner: MemoryReader,
+}
+
+#[wasm_bindgen]
+impl WasmReader {
+    /// 创建分块读取器
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmReader {
+        Self {
+            inner: MemoryReader::new(),
+        }
+    }
+
+    /// 从完整的文件内容创建读取器
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(
+        bytes: &[u8],
+    ) -> Result<WasmReader, JsError> {
+        let inner = MemoryReader::from_bytes(bytes)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(Self { inner })
+    }
+
+    /// 追加一个数据块
+    #[wasm_bindgen(js_name = pushChunk)]
+    pub fn push_chunk(
+        &mut self,
+        chunk: &[u8],
+    ) -> Result<(), JsError> {
+        self.inner
+            .push_chunk(chunk)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// 标记字节流结束
+    pub fn finish(&mut self) {
+        self.inner.finish();
+    }
+
+    /// 读取下一个数据包，当前数据不足时返回 `undefined`
+    #[wasm_bindgen(js_name = nextPacket)]
+    pub fn next_packet(
+        &mut self,
+    ) -> Result<Option<WasmPacket>, JsError> {
+        self.inner
+            .read_packet()
+            .map(|packet| packet.map(WasmPacket::from))
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// 已读取的数据包数量
+    #[wasm_bindgen(getter, js_name = packetCount)]
+    pub fn packet_count(&self) -> u64 {
+        self.inner.packet_count()
+    }
+
+    /// 文件头中记录的链路类型数值
+    #[wasm_bindgen(getter, js_name = linkType)]
+    pub fn link_type(&self) -> u32 {
+        self.inner.link_type().to_u32()
+    }
+}
+
+impl Default for WasmReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 暴露给 JavaScript 的数据包
+#[wasm_bindgen]
+pub struct WasmPacket {
+    packet: ValidatedPacket,
+}
+
+#[wasm_bindgen]
+impl WasmPacket {
+    /// 时间戳（纳秒）
+    #[wasm_bindgen(getter, js_name = timestampNs)]
+    pub fn timestamp_ns(&self) -> u64 {
+        self.packet.get_timestamp_ns()
+    }
+
+    /// 负载数据
+    #[wasm_bindgen(getter)]
+    pub fn payload(&self) -> Vec<u8> {
+        self.packet.packet.data.clone()
+    }
+
+    /// 校验和
+    #[wasm_bindgen(getter)]
+    pub fn checksum(&self) -> u32 {
+        self.packet.checksum()
+    }
+
+    /// 校验是否通过
+    #[wasm_bindgen(getter, js_name = isValid)]
+    pub fn is_valid(&self) -> bool {
+        self.packet.is_valid()
+    }
+}
+
+impl From<ValidatedPacket> for WasmPacket {
+    fn from(packet: ValidatedPacket) -> Self {
+        Self { packet }
+    }
+}
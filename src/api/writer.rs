@@ -3,19 +3,51 @@
 //! 提供高级的数据集写入功能，支持多文件自动切换、索引生成等功能。
 
 use log::{debug, info, warn};
+use std::collections::{HashSet, VecDeque};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
+use crate::api::ingest::{self, PacketSender};
 use crate::business::cache::{CacheStats, FileInfoCache};
-use crate::business::config::WriterConfig;
-use crate::business::index::IndexManager;
+use crate::business::clock::{ClockSource, SystemClock};
+use crate::business::config::{
+    DuplicateTimestampPolicy, WriteVerification,
+    WriterConfig,
+};
+use crate::business::disk::DiskGuard;
+use crate::business::index::{
+    IndexManager, PacketIndexEntry,
+};
+use crate::business::lock::DatasetLock;
+use crate::business::markers::{self, Marker};
+use crate::business::metrics::WriterMetrics;
+use crate::business::progress::WriterProgress;
+use crate::business::rotation::{
+    FileUsage, RotationPolicy, RotationReason,
+};
+use crate::business::shards::ShardManifest;
+use crate::core::ByteOrder;
+use crate::data::file_reader::PcapFileReader;
 use crate::data::file_writer::PcapFileWriter;
+use crate::data::libpcap::LibpcapWriter;
 use crate::data::models::{
-    DataPacket, DatasetInfo, FileInfo,
+    DataPacket, DataPacketHeader, DatasetInfo, FileInfo,
+    HeaderExtension, PcapFileHeader, RecordFormat,
+};
+use crate::data::payload_store::{
+    PayloadReference, PayloadStore, PAYLOAD_STORE_FILE_NAME,
 };
 use crate::foundation::error::{PcapError, PcapResult};
-use crate::foundation::utils::DateTimeExtensions;
-use chrono::Utc;
+use crate::foundation::paths;
+use crate::foundation::time_format::TimeFormatter;
+use crate::foundation::types::constants;
+use crate::foundation::utils::{
+    calculate_crc32, calculate_payload_hash,
+};
+use chrono::{Local, Utc};
 
 /// PCAP数据集写入器
 ///
@@ -37,20 +69,46 @@ pub struct PcapWriter {
     current_writer: Option<PcapFileWriter>,
     /// 当前文件索引
     current_file_index: usize,
-    /// 当前文件大小
-    current_file_size: u64,
+    /// 当前文件的写入统计
+    current_file: FileUsage,
+    /// 文件轮换策略
+    rotation_policy: RotationPolicy,
     /// 已创建的文件列表
     created_files: Vec<PathBuf>,
+    /// 已创建文件的关闭原因，与 `created_files` 一一对应
+    rotation_reasons: Vec<Option<RotationReason>>,
     /// 文件信息缓存
     file_info_cache: FileInfoCache,
     /// 总数据包计数
     total_packet_count: u64,
-    /// 当前文件数据包计数
-    current_file_packet_count: u64,
     /// 是否已初始化
     is_initialized: bool,
     /// 是否已完成
     is_finalized: bool,
+    /// 数据集写入锁（完成写入后释放）
+    dataset_lock: Option<DatasetLock>,
+    /// 写入进度（读写共存协议）
+    progress: WriterProgress,
+    /// 本次写入使用的存储分片目录，为空时写入数据集目录
+    shard_dirs: Vec<PathBuf>,
+    /// 磁盘空间保护，未设置时不检查可用空间
+    disk_guard: Option<DiskGuard>,
+    /// 析构时是否自动完成写入（刷新缓冲区并生成索引）
+    finalize_on_drop: bool,
+    /// 当前文件尚未合并到索引管理器的索引条目
+    index_batch: Vec<PacketIndexEntry>,
+    /// 最近写入的时间戳，重复时间戳策略不为 `Allow` 时记录
+    written_timestamps: RecentTimestamps,
+    /// 最近一次写入数据包（含心跳包）的时刻
+    last_write_at: Instant,
+    /// 心跳包、标记和 `write_payload_now` 使用的时钟源
+    clock: Arc<dyn ClockSource>,
+    /// 标准 libpcap 副本文件，未配置或写入失败后为 None
+    libpcap_mirror: Option<LibpcapWriter>,
+    /// 负载大小和时间间隔统计
+    metrics: WriterMetrics,
+    /// 负载去重存储，未启用负载去重时为 None
+    payload_store: Option<PayloadStore>,
 }
 
 impl PcapWriter {
@@ -85,7 +143,7 @@ impl PcapWriter {
     pub fn new_with_config<P: AsRef<Path>>(
         base_path: P,
         dataset_name: &str,
-        configuration: WriterConfig,
+        mut configuration: WriterConfig,
     ) -> PcapResult<Self> {
         // 验证配置有效性
         configuration.validate().map_err(|e| {
@@ -95,7 +153,7 @@ impl PcapWriter {
         })?;
 
         let dataset_path =
-            base_path.as_ref().join(dataset_name);
+            paths::dataset_path(&base_path, dataset_name)?;
 
         // 确保数据集目录存在
         if !dataset_path.exists() {
@@ -112,12 +170,100 @@ impl PcapWriter {
             ));
         }
 
+        // 获取数据集写入锁，防止多个写入器并发写入
+        let dataset_lock =
+            DatasetLock::acquire(&dataset_path)?;
+
+        // 准备存储分片目录并更新分片清单，索引管理器据此扫描所有分片
+        let mut shard_dirs = Vec::new();
+        if !configuration.storage_shards.is_empty() {
+            for shard in &configuration.storage_shards {
+                let shard_dir = paths::dataset_path(
+                    shard,
+                    dataset_name,
+                )?;
+                fs::create_dir_all(&shard_dir)
+                    .map_err(PcapError::Io)?;
+                shard_dirs.push(shard_dir);
+            }
+
+            let mut manifest =
+                ShardManifest::load(&dataset_path);
+            manifest.merge(&shard_dirs);
+            manifest.save(&dataset_path)?;
+            info!("已启用存储分片: {shard_dirs:?}");
+        }
+
+        // 启用负载去重时打开数据集的负载存储，并在每个数据文件头中记录存储文件名
+        let payload_store = if configuration.payload_dedup {
+            configuration.header_extensions.retain(
+                |extension| {
+                    extension.kind()
+                        != HeaderExtension::PAYLOAD_STORE
+                },
+            );
+            configuration.header_extensions.push(
+                HeaderExtension::PayloadStore(
+                    PAYLOAD_STORE_FILE_NAME.to_string(),
+                ),
+            );
+            Some(PayloadStore::open(&dataset_path)?)
+        } else {
+            None
+        };
+
+        // 非默认的记录格式记录在每个数据文件头中
+        configuration.header_extensions.retain(
+            |extension| {
+                extension.kind()
+                    != HeaderExtension::RECORD_FORMAT
+            },
+        );
+        if configuration.record_format
+            != RecordFormat::Standard
+        {
+            configuration.header_extensions.push(
+                HeaderExtension::RecordFormat(
+                    configuration
+                        .record_format
+                        .name()
+                        .to_string(),
+                ),
+            );
+        }
+
+        // 分块布局的块大小记录在每个数据文件头中
+        configuration.header_extensions.retain(
+            |extension| {
+                extension.kind()
+                    != HeaderExtension::BLOCK_SIZE
+            },
+        );
+        if !configuration.block_size.is_zero() {
+            configuration.header_extensions.push(
+                HeaderExtension::BlockSize(
+                    configuration
+                        .block_size
+                        .as_u64()
+                        .to_string(),
+                ),
+            );
+        }
+
         // 创建索引管理器（新签名：base_path + dataset_name）
-        let index_manager =
+        let mut index_manager =
             IndexManager::new(base_path, dataset_name)?;
+        index_manager.set_payload_hash(
+            configuration.index_payload_hash,
+        );
 
         // 获取缓存大小（在移动 configuration 之前）
         let cache_size = configuration.index_cache_size;
+        let index_batch_size =
+            configuration.index_batch_size;
+
+        let rotation_policy =
+            configuration.rotation_policy();
 
         info!("PcapWriter已创建 - 数据集: {dataset_name}");
 
@@ -128,13 +274,30 @@ impl PcapWriter {
             configuration,
             current_writer: None,
             current_file_index: 0,
-            current_file_size: 0,
+            current_file: FileUsage::default(),
+            rotation_policy,
             created_files: Vec::new(),
+            rotation_reasons: Vec::new(),
             file_info_cache: FileInfoCache::new(cache_size),
             total_packet_count: 0,
-            current_file_packet_count: 0,
             is_initialized: false,
             is_finalized: false,
+            dataset_lock: Some(dataset_lock),
+            progress: WriterProgress::default(),
+            shard_dirs,
+            disk_guard: None,
+            finalize_on_drop: true,
+            index_batch: Vec::with_capacity(
+                index_batch_size,
+            ),
+            written_timestamps: RecentTimestamps::new(
+                constants::DUPLICATE_TIMESTAMP_WINDOW,
+            ),
+            last_write_at: Instant::now(),
+            clock: Arc::new(SystemClock),
+            libpcap_mirror: None,
+            metrics: WriterMetrics::default(),
+            payload_store,
         })
     }
 
@@ -149,6 +312,23 @@ impl PcapWriter {
         // 创建第一个文件
         self.create_new_file()?;
 
+        if let Some(path) =
+            &self.configuration.mirror_libpcap
+        {
+            match LibpcapWriter::create(
+                path,
+                self.configuration.link_type,
+            ) {
+                Ok(mirror) => {
+                    info!("已启用 libpcap 副本: {path:?}");
+                    self.libpcap_mirror = Some(mirror);
+                }
+                Err(e) => {
+                    warn!("创建 libpcap 副本失败，不再写入副本: {path:?}, {e}");
+                }
+            }
+        }
+
         self.is_initialized = true;
         info!("PcapWriter初始化完成");
         Ok(())
@@ -162,15 +342,27 @@ impl PcapWriter {
 
         info!("正在完成PcapWriter...");
 
-        // 刷新并关闭当前文件
+        // 写完最后一个块并关闭当前文件
         if let Some(ref mut writer) = self.current_writer {
-            writer.flush()?;
+            writer.finish()?;
             writer.close();
         }
         self.current_writer = None;
+        self.finish_recorded_file();
+        self.flush_libpcap_mirror();
+        if let Some(ref mut store) = self.payload_store {
+            store.sync()?;
+        }
 
-        // 生成索引
-        self.index_manager.rebuild_index()?;
+        // 生成索引，无论成功与否都释放写入锁
+        let result = self.index_manager.rebuild_index();
+        if let Err(e) =
+            WriterProgress::clear(&self.dataset_path)
+        {
+            warn!("删除写入进度标记失败: {e}");
+        }
+        self.dataset_lock = None;
+        result?;
 
         self.is_finalized = true;
         info!(
@@ -182,6 +374,63 @@ impl PcapWriter {
         Ok(())
     }
 
+    /// 在独立线程中运行写入器，返回数据包发送端和写入线程句柄
+    ///
+    /// 写入线程从容量为 `capacity` 的有界通道中取出数据包并写入，通道满时发送方阻塞。
+    /// 所有发送端释放后写入线程完成写入并返回数据集信息；写入失败时线程提前退出并返回该错误，
+    /// 之后的发送会返回 `PcapError::InvalidState`。配置了 `heartbeat_interval` 时，
+    /// 通道空闲超过该间隔后写入线程自动写入心跳包。
+    ///
+    /// # 参数
+    /// - `capacity` - 通道容量（数据包个数），为 0 时每次发送都等待写入线程接收
+    ///
+    /// # 错误
+    /// 无法创建写入线程时返回 `PcapError::Io`
+    pub fn spawn_ingest(
+        self,
+        capacity: usize,
+    ) -> PcapResult<(
+        PacketSender,
+        JoinHandle<PcapResult<DatasetInfo>>,
+    )> {
+        ingest::spawn(self, capacity)
+    }
+
+    /// 设置析构时是否自动完成写入
+    ///
+    /// 默认启用：写入器被丢弃（包括发生 panic 展开时）会刷新缓冲区并生成索引，
+    /// 保证数据集可以直接读取。关闭后析构时只刷新缓冲区并释放锁，不生成索引，
+    /// 适用于需要自行决定何时完成写入的场景，读取器打开时会重建索引。
+    pub fn finalize_on_drop(&mut self, enabled: bool) {
+        self.finalize_on_drop = enabled;
+    }
+
+    /// 析构时是否自动完成写入
+    pub fn is_finalize_on_drop(&self) -> bool {
+        self.finalize_on_drop
+    }
+
+    /// 设置时钟源
+    ///
+    /// 默认使用系统时钟。心跳包、`write_marker` 和 `write_payload_now` 的时间戳取自该时钟，
+    /// 部署了 PTP 等驯服时钟的系统可以换成 `InjectedClock` 或自定义实现。
+    pub fn set_clock<C: ClockSource + 'static>(
+        &mut self,
+        clock: C,
+    ) {
+        self.clock = Arc::new(clock);
+    }
+
+    /// 当前的时钟源
+    pub fn clock(&self) -> &dyn ClockSource {
+        self.clock.as_ref()
+    }
+
+    /// 是否已完成写入
+    pub fn is_finalized(&self) -> bool {
+        self.is_finalized
+    }
+
     /// 获取数据集信息
     pub fn get_dataset_info(&self) -> DatasetInfo {
         use chrono::Utc;
@@ -215,7 +464,11 @@ impl PcapWriter {
         // 尝试从索引获取详细信息
         let index_info = self.index_manager.get_index();
 
-        for file_path in &self.created_files {
+        for (file_path, rotation_reason) in self
+            .created_files
+            .iter()
+            .zip(&self.rotation_reasons)
+        {
             if let Ok(metadata) = fs::metadata(file_path) {
                 let file_name = file_path
                     .file_name()
@@ -280,6 +533,14 @@ impl PcapWriter {
                     created_time: current_time.clone(),
                     modified_time: current_time.clone(),
                     is_valid: true,
+                    header: PcapFileReader::read_header(
+                        file_path,
+                    )
+                    .ok(),
+                    rotation_policy: Some(
+                        self.rotation_policy,
+                    ),
+                    rotation_reason: *rotation_reason,
                 };
                 file_infos.push(file_info);
             }
@@ -310,6 +571,39 @@ impl PcapWriter {
         &self.dataset_name
     }
 
+    /// 启用磁盘空间保护
+    ///
+    /// 创建新文件和写入数据前检查目标目录所在磁盘的可用空间，
+    /// 可用空间不足 `min_free_bytes` 加待写入数据大小时返回 `PcapError::DiskFull`，
+    /// 已写入的数据不受影响，可以继续调用 `finalize()` 完成数据集。
+    pub fn set_disk_guard(&mut self, min_free_bytes: u64) {
+        self.set_disk_guard_with(DiskGuard::new(
+            min_free_bytes,
+        ));
+    }
+
+    /// 使用自定义的磁盘空间保护（检查间隔、空间不足回调等）
+    pub fn set_disk_guard_with(
+        &mut self,
+        guard: DiskGuard,
+    ) {
+        info!(
+            "已启用磁盘空间保护，最小可用空间: {} 字节",
+            guard.min_free_bytes()
+        );
+        self.disk_guard = Some(guard);
+    }
+
+    /// 关闭磁盘空间保护
+    pub fn clear_disk_guard(&mut self) {
+        self.disk_guard = None;
+    }
+
+    /// 获取当前的磁盘空间保护
+    pub fn disk_guard(&self) -> Option<&DiskGuard> {
+        self.disk_guard.as_ref()
+    }
+
     /// 写入单个数据包
     ///
     /// # 参数
@@ -321,6 +615,111 @@ impl PcapWriter {
     pub fn write_packet(
         &mut self,
         packet: &DataPacket,
+    ) -> PcapResult<()> {
+        self.write_record(&packet.header, &packet.data)
+    }
+
+    /// 直接写入时间戳和负载
+    ///
+    /// 在写入器内部组装数据包头部，不需要为每次写入构造 `DataPacket`，
+    /// 也不会复制负载，适合已经持有纳秒时间戳和负载缓冲区的采集程序。
+    ///
+    /// # 参数
+    /// - `timestamp_ns` - 捕获时间（纳秒）
+    /// - `data` - 数据包负载
+    ///
+    /// # 错误
+    /// 时间戳超出文件格式支持的范围时返回 `PcapError::InvalidArgument`
+    pub fn write_payload(
+        &mut self,
+        timestamp_ns: u64,
+        data: &[u8],
+    ) -> PcapResult<()> {
+        let header = DataPacketHeader::from_timestamp_ns(
+            timestamp_ns,
+            data,
+        )
+        .map_err(PcapError::InvalidArgument)?;
+        self.write_record(&header, data)
+    }
+
+    /// 以指定时间戳写入心跳包
+    ///
+    /// 心跳包的负载长度为 0，头部带有心跳标记（见 `DataPacketHeader::HEARTBEAT_CHECKSUM`），
+    /// 读取时通过 `DataPacket::is_heartbeat` 识别并过滤。
+    pub fn write_heartbeat(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> PcapResult<()> {
+        let header =
+            DataPacketHeader::heartbeat(timestamp_ns)
+                .map_err(PcapError::InvalidArgument)?;
+        self.write_record(&header, &[])
+    }
+
+    /// 距上次写入超过心跳间隔时，以时钟源的当前时间写入一个心跳包
+    ///
+    /// 未配置 `heartbeat_interval` 时不写入。通道写入线程在空闲时自动调用，
+    /// 直接使用写入器时应以不大于心跳间隔的周期调用。
+    ///
+    /// # 返回
+    /// 是否写入了心跳包
+    pub fn write_heartbeat_if_idle(
+        &mut self,
+    ) -> PcapResult<bool> {
+        let interval =
+            self.configuration.heartbeat_interval;
+        if interval.is_zero()
+            || self.last_write_at.elapsed() < interval
+        {
+            return Ok(false);
+        }
+        let timestamp_ns = self.clock.now_ns();
+        self.write_heartbeat(timestamp_ns)?;
+        debug!("空闲期间已写入心跳包: {timestamp_ns}");
+        Ok(true)
+    }
+
+    /// 正在写入的 libpcap 副本
+    ///
+    /// 未配置 `mirror_libpcap`、尚未写入数据包或副本创建、写入失败后返回 None。
+    pub fn libpcap_mirror(&self) -> Option<&LibpcapWriter> {
+        self.libpcap_mirror.as_ref()
+    }
+
+    /// 已写入数据包的负载大小和时间间隔统计
+    ///
+    /// 统计从写入器创建或上次 `reset_metrics` 开始累计，见 [`WriterMetrics`]。
+    pub fn metrics(&self) -> &WriterMetrics {
+        &self.metrics
+    }
+
+    /// 取出当前统计并重新开始累计
+    ///
+    /// 适合按固定周期比较输入特征：每个周期调用一次，得到该周期内的分布。
+    pub fn reset_metrics(&mut self) -> WriterMetrics {
+        self.metrics.take()
+    }
+
+    /// 心跳间隔，0 表示不写入心跳包
+    pub fn heartbeat_interval(&self) -> Duration {
+        self.configuration.heartbeat_interval
+    }
+
+    /// 以时钟源的当前时间写入负载
+    pub fn write_payload_now(
+        &mut self,
+        data: &[u8],
+    ) -> PcapResult<()> {
+        let timestamp_ns = self.clock.now_ns();
+        self.write_payload(timestamp_ns, data)
+    }
+
+    /// 写入数据包头部和负载
+    fn write_record(
+        &mut self,
+        header: &DataPacketHeader,
+        data: &[u8],
     ) -> PcapResult<()> {
         if self.is_finalized {
             return Err(PcapError::InvalidState(
@@ -333,25 +732,155 @@ impl PcapWriter {
             self.initialize()?;
         }
 
-        // 检查是否需要切换文件
-        if self.should_switch_file() {
-            self.switch_to_new_file()?;
+        let policy =
+            self.configuration.duplicate_timestamp_policy;
+        let resolved;
+        let header =
+            if policy == DuplicateTimestampPolicy::Allow {
+                header
+            } else {
+                resolved = self
+                    .resolve_duplicate_timestamp(header)?;
+                &resolved
+            };
+
+        // 检查是否需要切换文件，头部大小取决于记录格式，负载去重时记录只保存负载引用
+        let raw_size = (DataPacketHeader::HEADER_SIZE
+            + data.len()) as u64;
+        let header_size =
+            self.current_writer.as_ref().map_or(
+                DataPacketHeader::HEADER_SIZE,
+                |writer| writer.header_size(header),
+            );
+        let stored_size = if self.payload_store.is_some() {
+            PayloadReference::SIZE
+        } else {
+            data.len()
+        };
+        let record_size =
+            (header_size + stored_size) as u64;
+        if let Some(capacity) = self
+            .current_writer
+            .as_ref()
+            .and_then(|writer| writer.block_capacity())
+            .filter(|capacity| record_size > *capacity)
+        {
+            return Err(PcapError::InvalidPacketSize {
+                message: format!(
+                    "数据包记录 {record_size} 字节超过块容量 {capacity} 字节"
+                ),
+                position: self.current_file.disk_bytes,
+            });
         }
+        // 分块布局下还要计入块填充和块尾，文件大小才不会超过上限
+        let block_overhead = self
+            .current_writer
+            .as_ref()
+            .map_or(0, |writer| {
+                writer.block_overhead(record_size)
+            });
+        if let Some(reason) = self.rotation_policy.check(
+            &self.current_file,
+            header.timestamp_ns(),
+            raw_size,
+            record_size + block_overhead,
+        ) {
+            self.switch_to_new_file(reason)?;
+        }
+
+        // 检查磁盘可用空间，负载去重时新负载还可能写入负载存储
+        if let Some(ref mut guard) = self.disk_guard {
+            if let Some(directory) = self
+                .created_files
+                .last()
+                .and_then(|path| path.parent())
+            {
+                guard.check_before_write(
+                    directory,
+                    record_size.max(raw_size),
+                )?;
+            }
+        }
+
+        let verification =
+            self.configuration.verify_on_write;
+        if verification != WriteVerification::Off {
+            verify_serialized_record(
+                header,
+                data,
+                self.configuration.byte_order,
+                self.current_file.disk_bytes,
+            )?;
+        }
+
+        // 负载去重时负载写入负载存储，记录中保存引用，校验和仍按原始负载计算
+        let stored_header;
+        let reference;
+        let (record_header, record_data) =
+            match self.payload_store.as_mut() {
+                Some(store) => {
+                    reference = store.put(data)?.to_bytes(
+                        self.configuration.byte_order,
+                    );
+                    let mut stored = header.clone();
+                    stored.packet_length =
+                        PayloadReference::SIZE as u32;
+                    stored_header = stored;
+                    (&stored_header, &reference[..])
+                }
+                None => (header, data),
+            };
 
         // 写入数据包
         if let Some(ref mut writer) = self.current_writer {
-            writer.write_packet(packet)?;
+            let expected_header = (verification
+                == WriteVerification::ReadBack)
+                .then(|| {
+                    writer.encode_header(record_header)
+                });
+            let offset = writer
+                .write_record(record_header, record_data)?;
+            let disk_bytes = writer.total_size();
+            self.record_index_entry(header, data, offset);
 
             // 更新统计信息
-            self.current_file_size +=
-                packet.packet_length() as u64 + 16; // 16字节包头
-            self.current_file_packet_count += 1;
+            self.current_file.disk_bytes = disk_bytes;
+            self.current_file.raw_bytes += raw_size;
+            self.current_file
+                .first_timestamp_ns
+                .get_or_insert(header.timestamp_ns());
+            self.current_file.packet_count += 1;
+            self.last_write_at = Instant::now();
             self.total_packet_count += 1;
+            self.metrics.record(
+                header.timestamp_ns(),
+                data.len(),
+                header.is_heartbeat(),
+            );
+            if policy != DuplicateTimestampPolicy::Allow {
+                self.written_timestamps
+                    .insert(header.timestamp_ns());
+            }
 
             debug!(
                 "已写入数据包，当前文件大小: {} 字节",
-                self.current_file_size
+                self.current_file.disk_bytes
             );
+
+            if let Some(expected_header) = expected_header {
+                self.verify_written_record(
+                    &expected_header,
+                    record_data,
+                    offset,
+                )?;
+            }
+
+            if !header.is_heartbeat() {
+                self.write_libpcap_mirror(
+                    header.timestamp_ns(),
+                    data,
+                );
+            }
         } else {
             return Err(PcapError::InvalidState(
                 "没有可用的写入器".to_string(),
@@ -361,6 +890,171 @@ impl PcapWriter {
         Ok(())
     }
 
+    /// 把数据包追加到 libpcap 副本，失败时记录警告并停止写入副本
+    ///
+    /// 启用自动刷新时副本也随每个数据包刷新，记录期间即可用其他工具打开。
+    fn write_libpcap_mirror(
+        &mut self,
+        timestamp_ns: u64,
+        data: &[u8],
+    ) {
+        if let Some(mirror) = self.libpcap_mirror.as_mut() {
+            if let Err(e) =
+                mirror.write_packet(timestamp_ns, data)
+            {
+                warn!(
+                    "写入 libpcap 副本失败，不再写入副本: {:?}, {e}",
+                    mirror.path()
+                );
+                self.libpcap_mirror = None;
+                return;
+            }
+        }
+        if self.configuration.auto_flush {
+            self.flush_libpcap_mirror();
+        }
+    }
+
+    /// 刷新 libpcap 副本，失败时记录警告并停止写入副本
+    fn flush_libpcap_mirror(&mut self) {
+        if let Some(mirror) = self.libpcap_mirror.as_mut() {
+            if let Err(e) = mirror.flush() {
+                warn!(
+                    "刷新 libpcap 副本失败，不再写入副本: {:?}, {e}",
+                    mirror.path()
+                );
+                self.libpcap_mirror = None;
+            }
+        }
+    }
+
+    /// 按重复时间戳策略处理与已写入数据包时间戳相同的头部
+    fn resolve_duplicate_timestamp(
+        &self,
+        header: &DataPacketHeader,
+    ) -> PcapResult<DataPacketHeader> {
+        let mut timestamp_ns = header.timestamp_ns();
+        if !self.written_timestamps.contains(&timestamp_ns)
+        {
+            return Ok(header.clone());
+        }
+        if self.configuration.duplicate_timestamp_policy
+            == DuplicateTimestampPolicy::Error
+        {
+            return Err(PcapError::DuplicateTimestamp {
+                timestamp_ns,
+            });
+        }
+
+        let original = timestamp_ns;
+        while self
+            .written_timestamps
+            .contains(&timestamp_ns)
+        {
+            timestamp_ns = timestamp_ns
+                .checked_add(1)
+                .ok_or_else(|| {
+                    PcapError::InvalidArgument(format!(
+                        "时间戳 {original} 顺延后超出范围"
+                    ))
+                })?;
+        }
+        let mut adjusted = header.clone();
+        adjusted
+            .set_timestamp_ns(timestamp_ns)
+            .map_err(PcapError::InvalidArgument)?;
+        debug!("时间戳重复，已调整: {original} -> {timestamp_ns}");
+        Ok(adjusted)
+    }
+
+    /// 从文件读回刚写入的数据包并与写入的字节比较
+    fn verify_written_record(
+        &mut self,
+        expected_header: &[u8],
+        data: &[u8],
+        offset: u64,
+    ) -> PcapResult<()> {
+        let writer = self
+            .current_writer
+            .as_mut()
+            .ok_or_else(|| {
+                PcapError::InvalidState(
+                    "没有可用的写入器".to_string(),
+                )
+            })?;
+        let stored = writer.read_back(
+            offset,
+            expected_header.len() + data.len(),
+        )?;
+        let (stored_header, stored_data) =
+            stored.split_at(expected_header.len());
+        if stored_header != expected_header
+            || stored_data != data
+        {
+            let message = format!(
+                "写入校验失败，读回的数据与写入的数据不一致: {:?}",
+                writer.file_path()
+            );
+            warn!("{message}，位置: {offset}");
+            return Err(PcapError::CorruptedData {
+                message,
+                position: offset,
+            });
+        }
+        Ok(())
+    }
+
+    /// 在数据流中插入事件标记
+    ///
+    /// 标记使用时钟源的当前时间，位置为已写入的数据包数量（即下一个数据包的全局位置），
+    /// 追加到数据集目录的标记文件中，不写入 PCAP 文件。
+    /// 适用于测试程序在连续记录中标注"场景开始/结束"等事件。
+    pub fn write_marker(
+        &mut self,
+        label: &str,
+    ) -> PcapResult<Marker> {
+        let marker = Marker::now_with(
+            label,
+            self.total_packet_count,
+            self.clock.as_ref(),
+        );
+        self.append_marker(marker)
+    }
+
+    /// 在数据流中插入指定时间戳（纳秒）的事件标记
+    pub fn write_marker_at(
+        &mut self,
+        label: &str,
+        timestamp_ns: u64,
+    ) -> PcapResult<Marker> {
+        let marker = Marker::new(
+            label,
+            timestamp_ns,
+            self.total_packet_count,
+        );
+        self.append_marker(marker)
+    }
+
+    fn append_marker(
+        &mut self,
+        marker: Marker,
+    ) -> PcapResult<Marker> {
+        if self.is_finalized {
+            return Err(PcapError::InvalidState(
+                "写入器已完成，无法继续写入".to_string(),
+            ));
+        }
+        markers::append_marker(
+            &self.dataset_path,
+            &marker,
+        )?;
+        debug!(
+            "已插入标记: {} (位置 {})",
+            marker.label, marker.position
+        );
+        Ok(marker)
+    }
+
     /// 批量写入多个数据包
     ///
     /// # 参数
@@ -385,9 +1079,51 @@ impl PcapWriter {
             writer.flush()?;
             debug!("缓冲区已刷新");
         }
+        self.flush_libpcap_mirror();
         Ok(())
     }
 
+    /// 刷新缓冲区并保存时间点索引快照，不结束写入
+    ///
+    /// 快照覆盖此前写入的全部数据包，包括正在写入的文件，并原子地替换索引文件。
+    /// 长时间录制时，其他进程的读取器可以据此读取快照之前的数据；写入器意外退出时，
+    /// 快照也保留了已写入数据的索引。每次快照都会重写整个索引文件，应按需调用。
+    ///
+    /// # 返回
+    /// 快照覆盖的数据包总数
+    ///
+    /// # 错误
+    /// 写入器已完成时返回 `PcapError::InvalidState`
+    pub fn snapshot_index(&mut self) -> PcapResult<u64> {
+        if self.is_finalized {
+            return Err(PcapError::InvalidState(
+                "写入器已完成，无需保存索引快照"
+                    .to_string(),
+            ));
+        }
+
+        self.flush()?;
+        if self.configuration.index_batch_size > 0 {
+            self.index_manager.merge_recorded_entries(
+                &mut self.index_batch,
+            );
+        }
+        let (active_file, completed_files) =
+            match self.created_files.split_last() {
+                Some((active, completed))
+                    if self.current_writer.is_some() =>
+                {
+                    (Some(active.as_path()), completed)
+                }
+                _ => (None, self.created_files.as_slice()),
+            };
+        let total = self
+            .index_manager
+            .save_snapshot(completed_files, active_file)?;
+        info!("已保存索引快照，共 {total} 个数据包");
+        Ok(total)
+    }
+
     /// 获取缓存统计信息
     pub fn get_cache_stats(&self) -> CacheStats {
         self.file_info_cache.get_cache_stats()
@@ -406,35 +1142,54 @@ impl PcapWriter {
 
     /// 创建新的PCAP文件
     fn create_new_file(&mut self) -> PcapResult<()> {
-        // 使用配置的文件命名格式生成文件名
-        let time_str = Utc::now().to_filename_string();
+        // 使用配置的文件命名格式按本地时间生成文件名
+        let now = Utc::now().with_timezone(&Local);
         let filename = if self
             .configuration
             .file_name_format
             .is_empty()
         {
             // 默认格式：data_yyMMdd_HHmmss_nnnnnnnnn.pcap
-            format!("data_{time_str}.pcap")
-        } else if self.configuration.file_name_format == crate::foundation::types::constants::DEFAULT_FILE_NAME_FORMAT {
-            // 如果使用默认格式，直接使用时间字符串
-            format!("{time_str}.pcap")
-        } else {
-            // 使用用户配置的格式
             format!(
-                "{}.pcap",
-                self.configuration
-                    .file_name_format
-                    .replace("{}", &time_str)
+                "data_{}.pcap",
+                TimeFormatter::default_file_name()
+                    .format(&now)
             )
+        } else {
+            let formatter = TimeFormatter::for_file_name(
+                &self.configuration.file_name_format,
+            )
+            .map_err(|e| {
+                PcapError::InvalidArgument(format!(
+                    "文件命名格式无效，{e}"
+                ))
+            })?;
+            format!("{}.pcap", formatter.format(&now))
+        };
+
+        // 启用分片时按轮询方式选择文件所在目录
+        let directory = if self.shard_dirs.is_empty() {
+            self.dataset_path.clone()
+        } else {
+            self.shard_dirs[self.created_files.len()
+                % self.shard_dirs.len()]
+            .clone()
         };
+        let file_path = directory.join(&filename);
 
-        let file_path = self.dataset_path.join(&filename);
+        // 创建文件前检查磁盘可用空间，避免留下只有文件头的空文件
+        if let Some(ref mut guard) = self.disk_guard {
+            guard.check(
+                &directory,
+                PcapFileHeader::EXTENDED_HEADER_SIZE as u64,
+            )?;
+        }
 
         // 创建新的写入器
         let mut writer =
             PcapFileWriter::new(self.configuration.clone());
         writer
-            .create(&self.dataset_path, &filename)
+            .create(&directory, &filename)
             .map_err(PcapError::InvalidFormat)?;
 
         // 关闭之前的写入器
@@ -442,44 +1197,145 @@ impl PcapWriter {
             self.current_writer
         {
             old_writer
-                .flush()
+                .finish()
                 .map_err(PcapError::InvalidFormat)?;
             old_writer.close();
         }
 
         // 更新状态
+        self.finish_recorded_file();
+        if self.configuration.index_batch_size > 0 {
+            self.index_manager
+                .begin_recorded_file(&file_path);
+        }
+        let completed_file =
+            self.created_files.last().cloned();
+        self.current_file = FileUsage {
+            disk_bytes: writer.total_size(),
+            ..FileUsage::default()
+        };
         self.current_writer = Some(writer);
-        self.current_file_size = 0;
-        self.current_file_packet_count = 0;
         self.created_files.push(file_path.clone());
+        self.rotation_reasons.push(None);
 
         info!("已创建新文件: {file_path:?}");
+
+        if self.configuration.publish_progress {
+            self.publish_progress(completed_file, filename);
+        }
         Ok(())
     }
 
-    /// 检查是否需要切换文件
-    fn should_switch_file(&self) -> bool {
-        // 检查数据包数量限制
-        if self.current_file_packet_count
-            >= self.configuration.max_packets_per_file
-                as u64
-        {
-            return true;
+    /// 发布写入进度并保存已完成文件的索引检查点
+    ///
+    /// 发布失败不影响写入，仅记录警告。
+    fn publish_progress(
+        &mut self,
+        completed_file: Option<PathBuf>,
+        active_file: String,
+    ) {
+        if let Some(path) = completed_file {
+            if let Some(name) =
+                path.file_name().and_then(|n| n.to_str())
+            {
+                self.progress
+                    .completed_files
+                    .push(name.to_string());
+            }
+
+            // 检查点需要重写整个索引文件，按间隔保存以控制开销
+            let interval = self
+                .configuration
+                .index_checkpoint_interval;
+            if interval > 0 {
+                let result = self
+                    .index_manager
+                    .append_completed_file(&path)
+                    .and_then(|_| {
+                        if self
+                            .progress
+                            .completed_files
+                            .len()
+                            .is_multiple_of(interval)
+                        {
+                            self.index_manager
+                                .save_checkpoint()
+                        } else {
+                            Ok(())
+                        }
+                    });
+                if let Err(e) = result {
+                    warn!("保存索引检查点失败: {path:?}, 错误: {e}");
+                }
+            }
         }
 
-        // 检查文件大小限制
-        if self.configuration.max_file_size_bytes > 0
-            && self.current_file_size
-                >= self.configuration.max_file_size_bytes
+        self.progress.active_file = Some(active_file);
+        if let Err(e) =
+            self.progress.publish(&self.dataset_path)
         {
-            return true;
+            warn!("发布写入进度失败: {e}");
+        }
+    }
+
+    /// 记录刚写入的数据包的索引条目，每满一批合并到索引管理器
+    fn record_index_entry(
+        &mut self,
+        header: &DataPacketHeader,
+        data: &[u8],
+        byte_offset: u64,
+    ) {
+        let batch_size =
+            self.configuration.index_batch_size;
+        if batch_size == 0 {
+            return;
         }
 
-        false
+        self.index_batch.push(PacketIndexEntry {
+            timestamp_ns: header.timestamp_ns(),
+            byte_offset,
+            packet_size: header.packet_length,
+            packet_index: self.current_file.packet_count,
+            // 全局序号在生成索引时统一分配
+            global_index: 0,
+            payload_hash: self
+                .configuration
+                .index_payload_hash
+                .then(|| calculate_payload_hash(data)),
+        });
+        if self.index_batch.len() >= batch_size {
+            self.index_manager.merge_recorded_entries(
+                &mut self.index_batch,
+            );
+        }
     }
 
-    /// 切换到新文件
-    fn switch_to_new_file(&mut self) -> PcapResult<()> {
+    /// 合并剩余的索引条目并完成已关闭文件的索引记录
+    ///
+    /// 失败不影响写入，生成索引时会重新分析该文件。
+    fn finish_recorded_file(&mut self) {
+        if self.configuration.index_batch_size == 0 {
+            return;
+        }
+
+        self.index_manager
+            .merge_recorded_entries(&mut self.index_batch);
+        if let Err(e) =
+            self.index_manager.finish_recorded_file()
+        {
+            warn!("记录文件索引失败，完成写入时将重新分析: {e}");
+        }
+    }
+
+    /// 按轮换原因关闭当前文件并切换到新文件
+    fn switch_to_new_file(
+        &mut self,
+        reason: RotationReason,
+    ) -> PcapResult<()> {
+        if let Some(last) = self.rotation_reasons.last_mut()
+        {
+            *last = Some(reason);
+        }
         self.current_file_index += 1;
         self.create_new_file()
     }
@@ -499,10 +1355,102 @@ impl PcapWriter {
 
 impl Drop for PcapWriter {
     fn drop(&mut self) {
-        if !self.is_finalized {
+        if self.is_finalized {
+            return;
+        }
+
+        if self.finalize_on_drop {
             if let Err(e) = self.finalize() {
                 warn!("完成PcapWriter时出错: {e}");
             }
+        } else {
+            if let Some(ref mut writer) =
+                self.current_writer
+            {
+                if let Err(e) = writer.flush() {
+                    warn!(
+                        "刷新PcapWriter缓冲区时出错: {e}"
+                    );
+                }
+                writer.close();
+            }
+            self.flush_libpcap_mirror();
+            if let Err(e) =
+                WriterProgress::clear(&self.dataset_path)
+            {
+                warn!("删除写入进度标记失败: {e}");
+            }
+        }
+    }
+}
+
+/// 最近写入的时间戳
+///
+/// 只保留最近 `capacity` 个时间戳，超出时丢弃最早写入的，内存占用不随写入的数据包数量增长。
+struct RecentTimestamps {
+    capacity: usize,
+    set: HashSet<u64>,
+    order: VecDeque<u64>,
+}
+
+impl RecentTimestamps {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            set: HashSet::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    fn contains(&self, timestamp_ns: &u64) -> bool {
+        self.set.contains(timestamp_ns)
+    }
+
+    fn insert(&mut self, timestamp_ns: u64) {
+        if !self.set.insert(timestamp_ns) {
+            return;
+        }
+        self.order.push_back(timestamp_ns);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.set.remove(&oldest);
+            }
         }
     }
 }
+
+/// 序列化头部后重新解析，确认头部与负载一致
+fn verify_serialized_record(
+    header: &DataPacketHeader,
+    data: &[u8],
+    order: ByteOrder,
+    position: u64,
+) -> PcapResult<()> {
+    let decoded = DataPacketHeader::from_bytes_with_order(
+        &header.to_bytes_with_order(order),
+        order,
+    )
+    .map_err(|e| PcapError::CorruptedData {
+        message: format!("数据包头部序列化失败: {e}"),
+        position,
+    })?;
+    if decoded.packet_length as usize != data.len() {
+        return Err(PcapError::InvalidPacketSize {
+            message: format!(
+                "头部记录的长度 {} 与负载长度 {} 不一致",
+                decoded.packet_length,
+                data.len()
+            ),
+            position,
+        });
+    }
+    let actual = calculate_crc32(data);
+    if !decoded.checksum_matches(data) {
+        return Err(PcapError::ChecksumMismatch {
+            expected: format!("0x{:08X}", decoded.checksum),
+            actual: format!("0x{actual:08X}"),
+            position,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,126 @@
+//! 书签模块
+//!
+//! 书签为数据集中的数据包位置命名，保存在数据集目录下的书签文件中，
+//! 随数据集一起复制即可与他人共享。
+
+use chrono::Utc;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 书签文件名
+pub const BOOKMARKS_FILE_NAME: &str = ".bookmarks";
+
+/// 书签
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub struct Bookmark {
+    /// 书签名称
+    pub name: String,
+    /// 数据包的全局位置（从0开始）
+    pub position: u64,
+    /// 该位置数据包的时间戳（纳秒），用于在索引重建后核对书签
+    pub timestamp_ns: u64,
+    /// 创建时间（RFC 3339）
+    pub created_time: String,
+}
+
+impl Bookmark {
+    /// 创建书签，创建时间取当前时间
+    pub fn new(
+        name: &str,
+        position: u64,
+        timestamp_ns: u64,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            position,
+            timestamp_ns,
+            created_time: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// 书签集合，按名称排序
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BookmarkStore {
+    bookmarks: BTreeMap<String, Bookmark>,
+}
+
+impl BookmarkStore {
+    /// 读取数据集的书签文件，不存在时返回空集合
+    ///
+    /// # 错误
+    /// 书签文件存在但无法读取或解析时返回错误，避免覆盖他人的书签
+    pub fn load<P: AsRef<Path>>(
+        dataset_path: P,
+    ) -> PcapResult<Self> {
+        let path =
+            dataset_path.as_ref().join(BOOKMARKS_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(PcapError::Io)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// 保存书签文件
+    ///
+    /// 先写入临时文件再重命名，避免其他读取器看到写了一半的文件。
+    pub fn save<P: AsRef<Path>>(
+        &self,
+        dataset_path: P,
+    ) -> PcapResult<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        let path =
+            dataset_path.as_ref().join(BOOKMARKS_FILE_NAME);
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, content)
+            .map_err(PcapError::Io)?;
+        fs::rename(&temp_path, &path)
+            .map_err(PcapError::Io)?;
+
+        debug!("已保存 {} 个书签", self.bookmarks.len());
+        Ok(())
+    }
+
+    /// 添加书签，同名书签被替换
+    pub fn insert(&mut self, bookmark: Bookmark) {
+        self.bookmarks
+            .insert(bookmark.name.clone(), bookmark);
+    }
+
+    /// 删除书签，返回被删除的书签
+    pub fn remove(
+        &mut self,
+        name: &str,
+    ) -> Option<Bookmark> {
+        self.bookmarks.remove(name)
+    }
+
+    /// 按名称查找书签
+    pub fn get(&self, name: &str) -> Option<&Bookmark> {
+        self.bookmarks.get(name)
+    }
+
+    /// 按名称顺序遍历书签
+    pub fn iter(&self) -> impl Iterator<Item = &Bookmark> {
+        self.bookmarks.values()
+    }
+
+    /// 书签数量
+    pub fn len(&self) -> usize {
+        self.bookmarks.len()
+    }
+
+    /// 是否没有书签
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+}
@@ -1,12 +1,13 @@
-//! 文件信息缓存管理模块
+//! 缓存管理模块
 //!
-//! 提供高效的文件信息缓存策略，减少重复的文件系统访问，提升性能。
+//! 提供高效的文件信息缓存和数据包缓存策略，减少重复的文件系统访问，提升性能。
 
 use chrono::{DateTime, Duration, Utc};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use crate::data::models::FileInfo;
+use crate::data::models::{FileInfo, ValidatedPacket};
 
 /// 缓存统计信息
 #[derive(Debug, Clone)]
@@ -52,18 +53,47 @@ impl Default for CacheStats {
     }
 }
 
+/// 文件状态戳（大小和修改时间）
+///
+/// 用于判断磁盘上的文件在缓存之后是否被改写。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    pub file_size: u64,
+    pub write_time: DateTime<Utc>,
+}
+
+impl FileStamp {
+    /// 读取文件当前的状态戳，文件不存在或无法访问时返回 None
+    pub fn from_path<P: AsRef<Path>>(
+        path: P,
+    ) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?;
+        Some(Self {
+            file_size: metadata.len(),
+            write_time: DateTime::<Utc>::from(modified),
+        })
+    }
+}
+
 /// 文件信息缓存项
 #[derive(Debug, Clone)]
 pub struct FileInfoCacheItem {
     pub file_info: FileInfo,
     pub cache_time: DateTime<Utc>,
+    /// 缓存时文件的状态戳
+    pub stamp: FileStamp,
 }
 
 impl FileInfoCacheItem {
-    pub fn new(file_info: FileInfo) -> Self {
+    pub fn new(
+        file_info: FileInfo,
+        stamp: FileStamp,
+    ) -> Self {
         Self {
             file_info,
             cache_time: Utc::now(),
+            stamp,
         }
     }
 
@@ -72,9 +102,8 @@ impl FileInfoCacheItem {
         current_file_size: u64,
         current_write_time: DateTime<Utc>,
     ) -> bool {
-        self.file_info.file_size == current_file_size
-            && self.file_info.modified_time
-                == current_write_time.to_rfc3339()
+        self.stamp.file_size == current_file_size
+            && self.stamp.write_time == current_write_time
     }
 
     pub fn is_expired(
@@ -93,6 +122,8 @@ pub struct CacheStatistics {
     pub max_cache_size: usize,
     pub expired_entries: usize,
     pub last_cleanup_time: DateTime<Utc>,
+    /// 因文件变化而失效的条目累计数
+    pub invalidated_entries: u64,
 }
 
 impl CacheStatistics {
@@ -107,7 +138,43 @@ impl CacheStatistics {
     }
 }
 
+/// 目录监视状态
+///
+/// 通过轮询目录中文件的状态戳检测变化，无需平台相关的文件系统通知。
+#[derive(Debug)]
+struct DirectoryWatch {
+    directory: PathBuf,
+    snapshot: HashMap<PathBuf, FileStamp>,
+    poll_interval: Duration,
+    last_poll: DateTime<Utc>,
+}
+
+impl DirectoryWatch {
+    fn scan(
+        directory: &Path,
+    ) -> HashMap<PathBuf, FileStamp> {
+        let mut snapshot = HashMap::new();
+        if let Ok(entries) = std::fs::read_dir(directory) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if !path.is_file() {
+                    continue;
+                }
+                if let Some(stamp) =
+                    FileStamp::from_path(&path)
+                {
+                    snapshot.insert(path, stamp);
+                }
+            }
+        }
+        snapshot
+    }
+}
+
 /// 文件信息缓存
+///
+/// 每次命中前都会比较文件的大小和修改时间，文件被改写后对应条目自动失效。
+/// 启用监视模式（`watch`）后，还会定期扫描数据集目录，主动清除已变化或已删除文件的条目。
 pub struct FileInfoCache {
     cache: Arc<Mutex<HashMap<String, FileInfoCacheItem>>>,
     max_cache_size: usize,
@@ -116,6 +183,8 @@ pub struct FileInfoCache {
     last_cleanup: Arc<Mutex<DateTime<Utc>>>,
     hit_count: Arc<Mutex<u64>>,
     miss_count: Arc<Mutex<u64>>,
+    invalidated_count: Arc<Mutex<u64>>,
+    watch_state: Arc<Mutex<Option<DirectoryWatch>>>,
 }
 
 impl FileInfoCache {
@@ -128,6 +197,112 @@ impl FileInfoCache {
             last_cleanup: Arc::new(Mutex::new(Utc::now())),
             hit_count: Arc::new(Mutex::new(0)),
             miss_count: Arc::new(Mutex::new(0)),
+            invalidated_count: Arc::new(Mutex::new(0)),
+            watch_state: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// 启用监视模式
+    ///
+    /// 记录目录当前的文件状态，此后每隔 `poll_interval` 在访问缓存时
+    /// 重新扫描目录，清除已变化或已删除文件对应的缓存条目。
+    pub fn watch<P: AsRef<Path>>(
+        &self,
+        directory: P,
+        poll_interval: std::time::Duration,
+    ) {
+        let directory = directory.as_ref().to_path_buf();
+        let snapshot = DirectoryWatch::scan(&directory);
+        if let Ok(mut state) = self.watch_state.lock() {
+            *state = Some(DirectoryWatch {
+                directory,
+                snapshot,
+                poll_interval: Duration::from_std(
+                    poll_interval,
+                )
+                .unwrap_or_else(|_| Duration::zero()),
+                last_poll: Utc::now(),
+            });
+        }
+    }
+
+    /// 停止监视模式
+    pub fn unwatch(&self) {
+        if let Ok(mut state) = self.watch_state.lock() {
+            *state = None;
+        }
+    }
+
+    /// 是否处于监视模式
+    pub fn is_watching(&self) -> bool {
+        self.watch_state
+            .lock()
+            .map(|state| state.is_some())
+            .unwrap_or(false)
+    }
+
+    /// 立即扫描被监视的目录并清除受影响的条目
+    ///
+    /// # 返回
+    /// 被清除的缓存条目数，未启用监视模式时返回 0
+    pub fn poll_changes(&self) -> usize {
+        match self.cache.lock() {
+            Ok(mut cache) => {
+                self.poll_directory(&mut cache, true)
+            }
+            Err(_) => 0,
+        }
+    }
+
+    /// 扫描目录并清除已变化文件的缓存条目
+    fn poll_directory(
+        &self,
+        cache: &mut HashMap<String, FileInfoCacheItem>,
+        force: bool,
+    ) -> usize {
+        let mut state = match self.watch_state.lock() {
+            Ok(state) => state,
+            Err(_) => return 0,
+        };
+        let watch = match state.as_mut() {
+            Some(watch) => watch,
+            None => return 0,
+        };
+
+        let now = Utc::now();
+        if !force
+            && now.signed_duration_since(watch.last_poll)
+                < watch.poll_interval
+        {
+            return 0;
+        }
+        watch.last_poll = now;
+
+        let current =
+            DirectoryWatch::scan(&watch.directory);
+        let mut removed = 0;
+        for (path, stamp) in &watch.snapshot {
+            if current.get(path) != Some(stamp) {
+                let key =
+                    path.to_string_lossy().to_string();
+                if cache.remove(&key).is_some() {
+                    removed += 1;
+                }
+            }
+        }
+        watch.snapshot = current;
+
+        if removed > 0 {
+            self.record_invalidations(removed as u64);
+        }
+        removed
+    }
+
+    fn record_invalidations(&self, count: u64) {
+        if let Ok(mut invalidated) =
+            self.invalidated_count.lock()
+        {
+            *invalidated += count;
         }
     }
 
@@ -145,34 +320,34 @@ impl FileInfoCache {
         // 执行定期清理
         let _ = self.perform_periodic_cleanup(&mut cache);
 
+        // 监视模式下检查目录变化
+        self.poll_directory(&mut cache, false);
+
         if let Some(item) = cache.get(&path_str) {
             // 检查文件是否已修改
-            if let Ok(metadata) =
-                std::fs::metadata(&file_path)
-            {
-                if let Ok(modified_time) =
-                    metadata.modified()
+            let unchanged =
+                FileStamp::from_path(&file_path)
+                    .map(|stamp| {
+                        item.is_valid(
+                            stamp.file_size,
+                            stamp.write_time,
+                        )
+                    })
+                    .unwrap_or(false);
+
+            if unchanged {
+                // 缓存命中
+                if let Ok(mut hit_count) =
+                    self.hit_count.lock()
                 {
-                    let modified_datetime =
-                        DateTime::<Utc>::from(
-                            modified_time,
-                        );
-                    if item.is_valid(
-                        metadata.len(),
-                        modified_datetime,
-                    ) {
-                        // 缓存命中
-                        if let Ok(mut hit_count) =
-                            self.hit_count.lock()
-                        {
-                            *hit_count += 1;
-                        }
-                        return Some(
-                            item.file_info.clone(),
-                        );
-                    }
+                    *hit_count += 1;
                 }
+                return Some(item.file_info.clone());
             }
+
+            // 文件已改写或删除，移除失效条目
+            cache.remove(&path_str);
+            self.record_invalidations(1);
         }
 
         // 缓存未命中
@@ -184,6 +359,8 @@ impl FileInfoCache {
     }
 
     /// 向缓存中插入文件信息
+    ///
+    /// 文件不存在或无法读取元数据时不缓存。
     pub fn insert<P: AsRef<std::path::Path>>(
         &self,
         file_path: P,
@@ -193,9 +370,14 @@ impl FileInfoCache {
             .as_ref()
             .to_string_lossy()
             .to_string();
+        let stamp = match FileStamp::from_path(&file_path) {
+            Some(stamp) => stamp,
+            None => return,
+        };
 
         if let Ok(mut cache) = self.cache.lock() {
-            let item = FileInfoCacheItem::new(file_info);
+            let item =
+                FileInfoCacheItem::new(file_info, stamp);
             cache.insert(path_str, item);
 
             // 检查缓存大小限制
@@ -330,11 +512,18 @@ impl FileInfoCache {
             .lock()
             .map_err(|_| "清理时间锁定失败")?;
 
+        let invalidated_entries = self
+            .invalidated_count
+            .lock()
+            .map(|guard| *guard)
+            .unwrap_or(0);
+
         Ok(CacheStatistics {
             cache_entries: cache.len(),
             max_cache_size: self.max_cache_size,
             expired_entries,
             last_cleanup_time: last_cleanup,
+            invalidated_entries,
         })
     }
 }
@@ -344,3 +533,152 @@ impl Default for FileInfoCache {
         Self::new(1000)
     }
 }
+
+/// 数据包缓存键：（文件索引，字节偏移）
+pub type PacketCacheKey = (usize, u64);
+
+/// 已解码数据包的LRU缓存
+///
+/// 用于随机访问场景（如按时间戳反复定位），避免重复读取和校验同一数据包。
+/// 同时受条目数和负载字节数两个上限约束，任一超限时淘汰最久未使用的条目。
+pub struct PacketCache {
+    entries:
+        HashMap<PacketCacheKey, (ValidatedPacket, u64)>,
+    /// 访问序号 -> 缓存键，用于按最近使用顺序淘汰
+    recency: BTreeMap<u64, PacketCacheKey>,
+    max_entries: usize,
+    max_bytes: usize,
+    current_bytes: usize,
+    tick: u64,
+    hit_count: u64,
+    miss_count: u64,
+}
+
+impl PacketCache {
+    /// 创建数据包缓存
+    ///
+    /// # 参数
+    /// - `max_entries` - 最大缓存条目数，0表示禁用缓存
+    /// - `max_bytes` - 最大缓存负载字节数，0表示不限制
+    pub fn new(
+        max_entries: usize,
+        max_bytes: usize,
+    ) -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            max_entries,
+            max_bytes,
+            current_bytes: 0,
+            tick: 0,
+            hit_count: 0,
+            miss_count: 0,
+        }
+    }
+
+    /// 缓存是否启用
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.max_entries > 0
+    }
+
+    /// 获取缓存的数据包
+    pub fn get(
+        &mut self,
+        key: &PacketCacheKey,
+    ) -> Option<ValidatedPacket> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        self.tick += 1;
+        let tick = self.tick;
+        match self.entries.get_mut(key) {
+            Some((packet, last_used)) => {
+                self.recency.remove(last_used);
+                self.recency.insert(tick, *key);
+                *last_used = tick;
+                self.hit_count += 1;
+                Some(packet.clone())
+            }
+            None => {
+                self.miss_count += 1;
+                None
+            }
+        }
+    }
+
+    /// 插入数据包到缓存
+    pub fn insert(
+        &mut self,
+        key: PacketCacheKey,
+        packet: ValidatedPacket,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        // 单个数据包超过字节上限时不缓存
+        let size = packet.packet_length();
+        if self.max_bytes > 0 && size > self.max_bytes {
+            return;
+        }
+
+        self.remove(&key);
+
+        self.tick += 1;
+        self.recency.insert(self.tick, key);
+        self.entries.insert(key, (packet, self.tick));
+        self.current_bytes += size;
+
+        self.evict();
+    }
+
+    /// 移除指定条目
+    pub fn remove(&mut self, key: &PacketCacheKey) {
+        if let Some((packet, last_used)) =
+            self.entries.remove(key)
+        {
+            self.recency.remove(&last_used);
+            self.current_bytes -= packet.packet_length();
+        }
+    }
+
+    /// 清空缓存（保留统计信息）
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.current_bytes = 0;
+    }
+
+    /// 当前缓存的负载字节数
+    pub fn current_bytes(&self) -> usize {
+        self.current_bytes
+    }
+
+    /// 获取缓存统计信息
+    pub fn get_cache_stats(&self) -> CacheStats {
+        let mut stats = CacheStats {
+            cache_entries: self.entries.len(),
+            hit_count: self.hit_count,
+            miss_count: self.miss_count,
+            hit_rate: 0.0,
+        };
+        stats.update_hit_rate();
+        stats
+    }
+
+    /// 淘汰最久未使用的条目直到满足容量限制
+    fn evict(&mut self) {
+        while self.entries.len() > self.max_entries
+            || (self.max_bytes > 0
+                && self.current_bytes > self.max_bytes)
+        {
+            let oldest = match self.recency.iter().next() {
+                Some((_, key)) => *key,
+                None => break,
+            };
+            self.remove(&oldest);
+        }
+    }
+}
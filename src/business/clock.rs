@@ -0,0 +1,135 @@
+//! 时钟源模块
+//!
+//! 采集时间戳来自可替换的时钟源，而不是固定使用 `SystemTime::now()`。
+//! 部署了 PTP 或 GPS 驯服时钟的系统可以注入硬件时间戳，
+//! 需要避免系统时间跳变的场景可以使用单调时钟加固定偏移。
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// 时钟源
+///
+/// 返回自 UNIX 纪元以来的纳秒数。实现需要是线程安全的，写入线程和采集线程可能同时读取。
+/// 任意 `Fn() -> u64` 闭包也实现了该 trait，便于接入第三方 PTP 库。
+pub trait ClockSource: Send + Sync {
+    /// 当前时间（纳秒）
+    fn now_ns(&self) -> u64;
+}
+
+impl<F> ClockSource for F
+where
+    F: Fn() -> u64 + Send + Sync,
+{
+    fn now_ns(&self) -> u64 {
+        self()
+    }
+}
+
+/// 系统时钟，读取 `SystemTime::now()`
+///
+/// 系统时间早于 UNIX 纪元时返回 0。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl ClockSource for SystemClock {
+    fn now_ns(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| {
+                u64::try_from(elapsed.as_nanos())
+                    .unwrap_or(u64::MAX)
+            })
+            .unwrap_or(0)
+    }
+}
+
+/// 单调时钟加固定偏移
+///
+/// 以创建时刻的 `Instant` 为起点计时，加上起点对应的纪元纳秒数。
+/// 系统时间被 NTP 调整或手动修改时时间戳仍然单调递增，长时间运行后会与系统时间逐渐偏离。
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicClock {
+    start: Instant,
+    start_ns: u64,
+}
+
+impl MonotonicClock {
+    /// 以当前系统时间作为起点
+    pub fn new() -> Self {
+        Self::with_offset(SystemClock.now_ns())
+    }
+
+    /// 以指定的纪元纳秒数作为起点，例如启动时从 PTP 读取的时间
+    pub fn with_offset(start_ns: u64) -> Self {
+        Self {
+            start: Instant::now(),
+            start_ns,
+        }
+    }
+}
+
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClockSource for MonotonicClock {
+    fn now_ns(&self) -> u64 {
+        let elapsed =
+            u64::try_from(self.start.elapsed().as_nanos())
+                .unwrap_or(u64::MAX);
+        self.start_ns.saturating_add(elapsed)
+    }
+}
+
+/// 注入时钟，返回外部最近一次设置的时间戳
+///
+/// 用于网卡硬件时间戳或 PTP 守护进程提供的时间：采集线程收到时间戳后调用 `set_ns`，
+/// 写入器和数据包构造函数读取同一个值。克隆的实例共享同一个时间。
+#[derive(Clone, Default)]
+pub struct InjectedClock {
+    timestamp_ns: Arc<AtomicU64>,
+}
+
+impl InjectedClock {
+    /// 以指定的初始时间创建
+    pub fn new(timestamp_ns: u64) -> Self {
+        Self {
+            timestamp_ns: Arc::new(AtomicU64::new(
+                timestamp_ns,
+            )),
+        }
+    }
+
+    /// 设置当前时间
+    pub fn set_ns(&self, timestamp_ns: u64) {
+        self.timestamp_ns
+            .store(timestamp_ns, Ordering::Release);
+    }
+
+    /// 把当前时间向后推进指定的纳秒数
+    pub fn advance_ns(&self, delta_ns: u64) {
+        self.timestamp_ns
+            .fetch_add(delta_ns, Ordering::AcqRel);
+    }
+}
+
+impl fmt::Debug for InjectedClock {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("InjectedClock")
+            .field("timestamp_ns", &self.now_ns())
+            .finish()
+    }
+}
+
+impl ClockSource for InjectedClock {
+    fn now_ns(&self) -> u64 {
+        self.timestamp_ns.load(Ordering::Acquire)
+    }
+}
@@ -1,43 +1,317 @@
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
 
+use crate::business::rate_limit::RateLimiter;
+use crate::business::rotation::RotationPolicy;
+use crate::business::units::{self, ByteSize};
+use crate::core::ByteOrder;
+use crate::data::block_layout;
+use crate::data::models::{
+    HeaderExtension, LinkType, RecordFormat,
+};
+use crate::foundation::time_format::TimeFormatter;
 use crate::foundation::types::constants;
 
+/// 读取时的校验和验证方式
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+)]
+pub enum VerifyMode {
+    /// 验证每个数据包
+    #[default]
+    Always,
+    /// 不验证，数据包标记为未验证
+    Never,
+    /// 每 N 个数据包验证一个，其余标记为未验证
+    Sampled(u32),
+}
+
+impl VerifyMode {
+    /// 第 `sequence` 个读取的数据包（从0开始）是否需要验证
+    #[inline]
+    pub fn should_verify(&self, sequence: u64) -> bool {
+        match *self {
+            VerifyMode::Always => true,
+            VerifyMode::Never => false,
+            VerifyMode::Sampled(interval) => {
+                interval != 0
+                    && sequence
+                        .is_multiple_of(interval as u64)
+            }
+        }
+    }
+}
+
+/// 写入时的完整性校验方式
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+)]
+pub enum WriteVerification {
+    /// 不校验
+    #[default]
+    Off,
+    /// 序列化头部后重新解析，并按负载重新计算校验和与头部对比
+    Checksum,
+    /// 在 `Checksum` 的基础上，每个数据包写入后刷新到文件并读回逐字节比较
+    ///
+    /// 每个数据包都会刷新一次缓冲区，写入速度明显下降，适合写入不可靠的移动存储介质。
+    ReadBack,
+}
+
+/// 写入时间戳与已写入数据包重复的数据包时的处理方式
+///
+/// 重复检查是尽力而为的近期窗口检查：只与本写入器最近写入的
+/// `constants::DUPLICATE_TIMESTAMP_WINDOW` 个时间戳比较，内存占用不随写入量增长。
+/// 与更早写入的数据包（包括乱序到达时超出窗口的，以及数据集中之前的写入器写入的）
+/// 时间戳相同时不会被发现，按原样写入。需要保证整个数据集内时间戳唯一时，
+/// 应在写入前自行去重，或在完成写入后通过索引的 `duplicate_timestamp_count()` 检查。
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+)]
+pub enum DuplicateTimestampPolicy {
+    /// 原样写入，索引为同一时间戳保留全部数据包
+    #[default]
+    Allow,
+    /// 时间戳逐次加 1 纳秒，直到不与窗口内的时间戳重复
+    BumpByOne,
+    /// 与窗口内的时间戳重复时返回 `PcapError::DuplicateTimestamp`，不写入该数据包
+    Error,
+}
+
 /// 读取器配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReaderConfig {
-    /// 缓冲区大小（字节）
-    pub buffer_size: usize,
+    /// 缓冲区大小
+    pub buffer_size: ByteSize,
     /// 索引缓存大小（条目数）
     pub index_cache_size: usize,
+    /// 索引文件损坏时是否自动隔离并重建
+    #[serde(default = "default_auto_repair_index")]
+    pub auto_repair_index: bool,
+    /// 数据包缓存条目数，0表示禁用
+    #[serde(default)]
+    pub packet_cache_size: usize,
+    /// 数据包缓存负载字节上限，0表示不限制
+    #[serde(default)]
+    pub packet_cache_max_bytes: ByteSize,
+    /// 最大同时打开的数据文件数（文件句柄池大小）
+    #[serde(default = "default_max_open_files")]
+    pub max_open_files: usize,
+    /// 是否监视数据集目录变化并清除失效的文件信息缓存
+    #[serde(default)]
+    pub watch_dataset: bool,
+    /// 监视模式下的目录扫描间隔
+    #[serde(
+        default = "default_watch_interval",
+        with = "units::serde_duration"
+    )]
+    pub watch_interval: Duration,
+    /// 校验和验证方式，批量分析时可以跳过或抽样验证以提高读取速度
+    #[serde(default)]
+    pub verify_checksums: VerifyMode,
+    /// `read_pooled_packet` 最多保留的空闲负载缓冲区数量，0表示不回收
+    #[serde(default = "default_buffer_pool_size")]
+    pub buffer_pool_size: usize,
+    /// 文件末尾的数据包不完整时是否返回剩余的负载，而不是报错
+    ///
+    /// 启用后该数据包的校验结果为 `ValidationStatus::TruncatedPayload`，
+    /// 用于从异常中断的写入中抢救数据。
+    #[serde(default)]
+    pub salvage_truncated_packets: bool,
+    /// 读取正在写入的文件时是否容忍末尾未写完的数据包
+    ///
+    /// 启用后文件末尾不完整的数据包记录视为暂时没有更多数据：读取返回 `Ok(None)`
+    /// 而不是 `PcapError::PacketSizeExceedsRemainingBytes`，读取位置停在该记录的起始位置，
+    /// 再次读取时重新获取文件大小并从该位置重试，生成索引时末尾不完整的数据包视为文件结束。
+    /// 优先于 `salvage_truncated_packets`。用于跟随不持有数据集写入锁的外部程序正在写入的文件；
+    /// 持有写入锁的 `PcapWriter` 写入期间读取器使用实时索引，本来就只读取到最后一个完整的数据包。
+    #[serde(default)]
+    pub tolerate_partial_tail: bool,
+    /// 只读模式：从不在数据集目录中生成、隔离或改写索引文件
+    ///
+    /// 用于只读挂载、网络共享或一次写入多次读取的归档。未设置 `index_dir` 时，
+    /// 索引文件缺失、损坏或与数据文件不一致直接返回错误，而不是尝试写入索引后
+    /// 以难以理解的 IO 错误失败。
+    #[serde(default)]
+    pub read_only: bool,
+    /// 保存生成和重建的索引的可写目录，为空时保存在数据集目录中
+    ///
+    /// 索引文件以数据集路径哈希命名，多个数据集可以共用同一目录。
+    /// 与 `read_only` 配合使用时，只读数据集也能生成索引获得快速随机访问。
+    #[serde(default)]
+    pub index_dir: Option<PathBuf>,
+    /// 内存索引的字节上限，0表示不限制
+    ///
+    /// 数据包条目加上查找表的估算大小超过上限时，索引自动使用稀疏模式：不构建按时间戳和
+    /// 负载大小的查找表，时间戳定位和范围查询改为按文件时间范围扫描条目。
+    /// 用于内存受限的设备打开大型数据集，见 `IndexManager::memory_usage`。
+    #[serde(default)]
+    pub max_index_memory: ByteSize,
+    /// 是否使用内存映射的磁盘时间戳表定位时间戳，需要启用 `mmap-index` 特性
+    ///
+    /// 启用后内存索引不构建时间戳查找表，按时间戳定位时在索引文件旁的 `.ptidx`
+    /// 有序表中二分查找，见 `IndexManager::set_timestamp_table`。
+    #[serde(default)]
+    pub mmap_timestamp_index: bool,
+    /// 是否按起始时间戳而不是文件名排序数据文件
+    ///
+    /// 用于时钟回拨后文件名顺序与时间顺序不一致的数据集，顺序读取按排序后的文件切换。
+    /// 时间范围重叠的文件记录在 `IndexManager::health` 中，见 `PidxIndex::time_segments`。
+    #[serde(default)]
+    pub sort_files_by_time: bool,
+    /// 跳过的数据文件名模式，支持 `*` 和 `?` 通配符
+    ///
+    /// 匹配任一模式的数据文件不参与索引，读取时不可见，用于排除已知损坏或无关的文件。
+    /// 文件头校验失败的文件无需列出，会自动隔离并记录在 `IndexManager::health` 中。
+    #[serde(default)]
+    pub skip_files: Vec<String>,
+}
+
+fn default_auto_repair_index() -> bool {
+    true
+}
+
+fn default_max_open_files() -> usize {
+    constants::DEFAULT_MAX_OPEN_FILES
+}
+
+fn default_watch_interval() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_buffer_pool_size() -> usize {
+    constants::DEFAULT_BUFFER_POOL_SIZE
 }
 
 impl Default for ReaderConfig {
     fn default() -> Self {
         Self {
-            buffer_size: 8192,
+            buffer_size: ByteSize::kib(8),
             index_cache_size: 1000,
+            auto_repair_index: true,
+            packet_cache_size: 0,
+            packet_cache_max_bytes: ByteSize::ZERO,
+            max_open_files:
+                constants::DEFAULT_MAX_OPEN_FILES,
+            watch_dataset: false,
+            watch_interval: default_watch_interval(),
+            verify_checksums: VerifyMode::Always,
+            buffer_pool_size:
+                constants::DEFAULT_BUFFER_POOL_SIZE,
+            salvage_truncated_packets: false,
+            tolerate_partial_tail: false,
+            read_only: false,
+            index_dir: None,
+            max_index_memory: ByteSize::ZERO,
+            mmap_timestamp_index: false,
+            sort_files_by_time: false,
+            skip_files: Vec::new(),
         }
     }
 }
 
 impl ReaderConfig {
     /// 验证读取器配置的有效性
+    ///
+    /// 错误信息以出错的字段名开头。
     pub fn validate(&self) -> Result<(), String> {
-        if self.buffer_size < 1024 {
+        validate_buffer_size(self.buffer_size)?;
+
+        if self.index_cache_size == 0 {
             return Err(
-                "缓冲区大小不能小于1024字节".to_string()
+                "index_cache_size: 索引缓存大小必须大于0"
+                    .to_string(),
             );
         }
 
-        if self.buffer_size > constants::MAX_BUFFER_SIZE {
+        if self.packet_cache_max_bytes.as_u64()
+            > constants::MAX_PACKET_CACHE_BYTES as u64
+        {
             return Err(format!(
-                "缓冲区大小不能超过{}字节",
-                constants::MAX_BUFFER_SIZE
+                "packet_cache_max_bytes: 数据包缓存字节上限不能超过{}",
+                ByteSize::from(
+                    constants::MAX_PACKET_CACHE_BYTES
+                )
             ));
         }
 
-        if self.index_cache_size == 0 {
-            return Err("索引缓存大小必须大于0".to_string());
+        if self.max_open_files == 0 {
+            return Err(
+                "max_open_files: 最大打开文件数必须大于0"
+                    .to_string(),
+            );
+        }
+
+        if self.max_open_files > constants::MAX_OPEN_FILES {
+            return Err(format!(
+                "max_open_files: 最大打开文件数不能超过{}",
+                constants::MAX_OPEN_FILES
+            ));
+        }
+
+        if self.watch_dataset
+            && self.watch_interval.is_zero()
+        {
+            return Err(
+                "watch_interval: 目录扫描间隔必须大于0"
+                    .to_string(),
+            );
+        }
+
+        if self.verify_checksums == VerifyMode::Sampled(0) {
+            return Err(
+                "verify_checksums: 抽样验证间隔必须大于0"
+                    .to_string(),
+            );
+        }
+
+        if self
+            .index_dir
+            .as_ref()
+            .is_some_and(|dir| dir.as_os_str().is_empty())
+        {
+            return Err("index_dir: 索引目录不能为空路径"
+                .to_string());
+        }
+
+        if self.mmap_timestamp_index
+            && !cfg!(feature = "mmap-index")
+        {
+            return Err(
+                "mmap_timestamp_index: 需要启用 mmap-index 特性"
+                    .to_string(),
+            );
+        }
+
+        if self
+            .skip_files
+            .iter()
+            .any(|pattern| pattern.is_empty())
+        {
+            return Err("skip_files: 文件名模式不能为空"
+                .to_string());
         }
 
         Ok(())
@@ -52,71 +326,422 @@ impl ReaderConfig {
 /// 写入器配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WriterConfig {
-    /// 缓冲区大小（字节）
-    pub buffer_size: usize,
+    /// 缓冲区大小
+    pub buffer_size: ByteSize,
     /// 索引缓存大小（条目数）
     pub index_cache_size: usize,
     /// 每个PCAP文件最大数据包数量
     pub max_packets_per_file: usize,
-    /// 每个PCAP文件最大大小（字节），0表示不限制
-    pub max_file_size_bytes: u64,
+    /// 每个PCAP文件在磁盘上的最大大小（含文件头），0表示不限制
+    #[serde(default, alias = "max_file_size_bytes")]
+    pub max_file_size: ByteSize,
+    /// 每个PCAP文件未压缩记录（数据包头部和负载）的最大字节数，0表示不限制
+    #[serde(default)]
+    pub max_file_raw_size: ByteSize,
+    /// 每个PCAP文件覆盖的最大时间跨度，按数据包时间戳计算，0表示不限制
+    #[serde(default, with = "units::serde_duration")]
+    pub max_file_duration: Duration,
     /// 文件命名格式
+    ///
+    /// 支持 chrono strftime 格式（例如 `"capture_%Y%m%d_%H%M%S_%9f"`）、.NET 风格格式
+    /// （默认 `"yyMMdd_HHmmss_fffffffff"`）和 `{}` 模板，按本地时间生成，见 `TimeFormatter`。
     pub file_name_format: String,
     /// 是否启用自动刷新
     pub auto_flush: bool,
+    /// 是否发布写入进度和索引检查点，供读取器在写入期间读取数据集
+    #[serde(default = "default_publish_progress")]
+    pub publish_progress: bool,
+    /// 每完成多少个文件保存一次索引检查点，0表示不保存
+    #[serde(default)]
+    pub index_checkpoint_interval: usize,
+    /// 负载的链路类型，写入文件头供下游解析器识别
+    #[serde(default)]
+    pub link_type: LinkType,
+    /// 存储分片根目录，非空时轮换产生的文件按轮询方式分布到各分片，空表示全部写入数据集目录
+    #[serde(default)]
+    pub storage_shards: Vec<PathBuf>,
+    /// 写入时每累积多少个索引条目合并一次，0表示不在写入时记录索引，完成写入时重新分析数据文件
+    #[serde(default = "default_index_batch_size")]
+    pub index_batch_size: usize,
+    /// 自动刷新时小数据包的最小提交块大小（须为2的幂），0表示每个数据包都立即刷新
+    ///
+    /// 负载小于 `constants::SMALL_WRITE_THRESHOLD` 的数据包先合并，累积到该大小的整数倍时
+    /// 才按块提交给操作系统，其余数据留到下一块；较大的数据包写入前会先提交已合并的数据。
+    #[serde(default)]
+    pub min_write_block: ByteSize,
+    /// 写入时的完整性校验方式，校验失败时写入立即返回错误
+    #[serde(default)]
+    pub verify_on_write: WriteVerification,
+    /// 写入文件的字节序，读取时根据魔术数自动识别
+    #[serde(default)]
+    pub byte_order: ByteOrder,
+    /// 写入每个文件头的扩展块，非空时文件头升级到 2.6 版本
+    #[serde(default)]
+    pub header_extensions: Vec<HeaderExtension>,
+    /// 是否在索引中记录每个数据包的负载内容哈希，用于检测重复数据包和比较数据集
+    #[serde(default)]
+    pub index_payload_hash: bool,
+    /// 本次写入中时间戳重复的数据包的处理方式，只检查最近写入的时间戳，
+    /// 见 [`DuplicateTimestampPolicy`]
+    #[serde(default)]
+    pub duplicate_timestamp_policy:
+        DuplicateTimestampPolicy,
+    /// 空闲时写入心跳包的间隔，0表示不写入
+    ///
+    /// 心跳包是以写入器时钟源的当前时间为时间戳、负载长度为 0 的数据包，用于区分“没有数据到达”
+    /// 和“记录程序已停止”。通道写入线程自动写入，直接使用写入器时需要定期调用
+    /// `PcapWriter::write_heartbeat_if_idle`。
+    #[serde(default, with = "units::serde_duration")]
+    pub heartbeat_interval: Duration,
+    /// 同时写入的标准 libpcap 文件路径，None 表示不写入
+    ///
+    /// 每个写入数据集的数据包（心跳包除外）同时追加到该文件，便于直接用 Wireshark 打开记录。
+    /// 该文件只是尽力而为的副本：创建或写入失败时记录警告并停止写入副本，数据集写入不受影响。
+    #[serde(default)]
+    pub mirror_libpcap: Option<PathBuf>,
+    /// 是否启用负载去重存储
+    ///
+    /// 启用后内容相同的负载只在数据集目录下的 `.payloads` 文件中保存一次，数据包记录只保存
+    /// 12 字节的引用，读取时自动还原，适合大量重复的遥测帧。数据文件头记录
+    /// `HeaderExtension::PayloadStore` 扩展块，不能与存储分片同时使用。
+    #[serde(default)]
+    pub payload_dedup: bool,
+    /// 数据包记录格式
+    ///
+    /// `RecordFormat::DeltaTimestamp` 把时间戳按与前一个数据包之差以变长整数存储，
+    /// 高速率的小数据包记录每个数据包可节省约一半的头部开销；`RecordFormat::CheckedHeader`
+    /// 为每个头部追加校验和，负载长度损坏时不会读错后续记录。数据文件头记录
+    /// `HeaderExtension::RecordFormat` 扩展块，读取时自动识别。
+    #[serde(default)]
+    pub record_format: RecordFormat,
+    /// 分块布局的块大小，0表示不分块
+    ///
+    /// 启用后数据文件按固定大小分块，每块末尾有记录数据包数量、校验和与时间范围的块尾，
+    /// 损坏只影响所在的块，按时间范围读取时无需索引即可跳过不相关的块。数据文件头记录
+    /// `HeaderExtension::BlockSize` 扩展块，单个数据包记录不能超过块大小减去块尾。
+    #[serde(default)]
+    pub block_size: ByteSize,
+}
+
+/// 校验读取器和写入器共用的缓冲区大小
+fn validate_buffer_size(
+    buffer_size: ByteSize,
+) -> Result<(), String> {
+    if buffer_size < ByteSize::kib(1) {
+        return Err(format!(
+            "buffer_size: 缓冲区大小不能小于1KiB: {buffer_size}"
+        ));
+    }
+    if buffer_size.as_u64()
+        > constants::MAX_BUFFER_SIZE as u64
+    {
+        return Err(format!(
+            "buffer_size: 缓冲区大小不能超过{}: {buffer_size}",
+            ByteSize::from(constants::MAX_BUFFER_SIZE)
+        ));
+    }
+    Ok(())
+}
+
+fn default_publish_progress() -> bool {
+    true
+}
+
+fn default_index_batch_size() -> usize {
+    constants::DEFAULT_INDEX_BATCH_SIZE
 }
 
 impl Default for WriterConfig {
     fn default() -> Self {
         Self {
-            buffer_size: 8192,
+            buffer_size: ByteSize::kib(8),
             index_cache_size: 1000,
             max_packets_per_file:
                 constants::DEFAULT_MAX_PACKETS_PER_FILE,
-            max_file_size_bytes: 0, // 默认不限制文件大小
+            max_file_size: ByteSize::ZERO, // 默认不限制文件大小
+            max_file_raw_size: ByteSize::ZERO,
+            max_file_duration: Duration::ZERO,
             file_name_format:
                 constants::DEFAULT_FILE_NAME_FORMAT
                     .to_string(),
             auto_flush: true,
+            publish_progress: true,
+            index_checkpoint_interval: 0,
+            link_type: LinkType::Unknown,
+            storage_shards: Vec::new(),
+            index_batch_size:
+                constants::DEFAULT_INDEX_BATCH_SIZE,
+            min_write_block: ByteSize::ZERO,
+            verify_on_write: WriteVerification::Off,
+            byte_order: ByteOrder::Little,
+            header_extensions: Vec::new(),
+            index_payload_hash: false,
+            duplicate_timestamp_policy:
+                DuplicateTimestampPolicy::Allow,
+            heartbeat_interval: Duration::ZERO,
+            mirror_libpcap: None,
+            payload_dedup: false,
+            record_format: RecordFormat::Standard,
+            block_size: ByteSize::ZERO,
         }
     }
 }
 
 impl WriterConfig {
+    /// 由数据包数量、大小和时间跨度上限组成的文件轮换策略
+    pub fn rotation_policy(&self) -> RotationPolicy {
+        RotationPolicy {
+            max_packets: self.max_packets_per_file as u64,
+            max_duration: self.max_file_duration,
+            max_disk_size: self.max_file_size,
+            max_raw_size: self.max_file_raw_size,
+        }
+    }
+
     /// 验证写入器配置的有效性
+    ///
+    /// 错误信息以出错的字段名开头。
     pub fn validate(&self) -> Result<(), String> {
-        if self.buffer_size < 1024 {
+        validate_buffer_size(self.buffer_size)?;
+
+        if self.index_cache_size == 0 {
             return Err(
-                "缓冲区大小不能小于1024字节".to_string()
+                "index_cache_size: 索引缓存大小必须大于0"
+                    .to_string(),
             );
         }
 
-        if self.buffer_size > constants::MAX_BUFFER_SIZE {
+        if self.max_packets_per_file == 0 {
+            return Err("max_packets_per_file: 每个文件最大数据包数量必须大于0"
+                .to_string());
+        }
+
+        if !self.max_file_size.is_zero()
+            && self.max_file_size < ByteSize::kib(1)
+        {
             return Err(format!(
-                "缓冲区大小不能超过{}字节",
-                constants::MAX_BUFFER_SIZE
+                "max_file_size: 文件大小限制不能小于1KiB: {}",
+                self.max_file_size
             ));
         }
 
-        if self.index_cache_size == 0 {
-            return Err("索引缓存大小必须大于0".to_string());
+        if !self.max_file_raw_size.is_zero()
+            && self.max_file_raw_size < ByteSize::kib(1)
+        {
+            return Err(format!(
+                "max_file_raw_size: 文件原始大小限制不能小于1KiB: {}",
+                self.max_file_raw_size
+            ));
         }
 
-        if self.max_packets_per_file == 0 {
-            return Err("每个文件最大数据包数量必须大于0"
-                .to_string());
+        if self.file_name_format.is_empty() {
+            return Err(
+                "file_name_format: 文件命名格式不能为空"
+                    .to_string(),
+            );
+        }
+        TimeFormatter::for_file_name(&self.file_name_format)
+            .map_err(|e| {
+                format!(
+                    "file_name_format: 文件命名格式 \"{}\" 无效，{e}",
+                    self.file_name_format
+                )
+            })?;
+
+        if !self.min_write_block.is_zero() {
+            if !self
+                .min_write_block
+                .as_u64()
+                .is_power_of_two()
+            {
+                return Err(format!(
+                    "min_write_block: 最小写入块大小必须是2的幂: {}",
+                    self.min_write_block
+                ));
+            }
+            if self.min_write_block.as_u64()
+                > constants::MAX_BUFFER_SIZE as u64
+            {
+                return Err(format!(
+                    "min_write_block: 最小写入块大小不能超过{}",
+                    ByteSize::from(constants::MAX_BUFFER_SIZE)
+                ));
+            }
+        }
+
+        for (i, shard) in
+            self.storage_shards.iter().enumerate()
+        {
+            if shard.as_os_str().is_empty() {
+                return Err(format!(
+                    "storage_shards: 存储分片 {i} 路径为空"
+                ));
+            }
+            if self.storage_shards[..i].contains(shard) {
+                return Err(format!(
+                    "storage_shards: 存储分片重复: {shard:?}"
+                ));
+            }
+        }
+
+        for extension in &self.header_extensions {
+            if extension.value().len()
+                > HeaderExtension::MAX_VALUE_LEN
+            {
+                return Err(format!(
+                    "header_extensions: 文件头扩展块 {} 的值不能超过{}字节",
+                    extension.kind(),
+                    HeaderExtension::MAX_VALUE_LEN
+                ));
+            }
         }
 
-        if self.max_file_size_bytes > 0
-            && self.max_file_size_bytes < 1024
+        if self
+            .mirror_libpcap
+            .as_ref()
+            .is_some_and(|path| path.as_os_str().is_empty())
         {
             return Err(
-                "文件大小限制不能小于1024字节".to_string()
+                "mirror_libpcap: libpcap 副本文件路径为空"
+                    .to_string(),
             );
         }
 
-        if self.file_name_format.is_empty() {
-            return Err("文件命名格式不能为空".to_string());
+        if self.payload_dedup
+            && !self.storage_shards.is_empty()
+        {
+            return Err(
+                "payload_dedup: 负载去重存储不能与存储分片同时使用"
+                    .to_string(),
+            );
+        }
+
+        if !self.block_size.is_zero()
+            && !(block_layout::MIN_BLOCK_SIZE
+                ..=block_layout::MAX_BLOCK_SIZE)
+                .contains(&self.block_size.as_u64())
+        {
+            return Err(format!(
+                "block_size: 块大小必须在{}到{}之间: {}",
+                ByteSize::b(block_layout::MIN_BLOCK_SIZE),
+                ByteSize::b(block_layout::MAX_BLOCK_SIZE),
+                self.block_size
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// 重置为默认值
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// 触发式记录配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerConfig {
+    /// 触发前保留的时间窗口（纳秒）
+    pub pre_trigger_ns: u64,
+    /// 触发后继续记录的时间窗口（纳秒）
+    pub post_trigger_ns: u64,
+    /// 预缓冲区负载字节上限，0表示只按时间窗口限制
+    pub max_buffered_bytes: usize,
+}
+
+impl Default for TriggerConfig {
+    fn default() -> Self {
+        Self {
+            pre_trigger_ns: 10_000_000_000,
+            post_trigger_ns: 10_000_000_000,
+            max_buffered_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+impl TriggerConfig {
+    /// 验证触发式记录配置的有效性
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pre_trigger_ns == 0
+            && self.post_trigger_ns == 0
+        {
+            return Err(
+                "触发前后时间窗口不能同时为0".to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// 重置为默认值
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// 回放配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaybackConfig {
+    /// 回放速度倍数，1.0 为原始速度，0 表示不等待、尽快发送
+    pub speed: f64,
+    /// 开始时间戳（纳秒），为空时从数据集开头回放
+    pub start_timestamp_ns: Option<u64>,
+    /// 结束时间戳（纳秒，包含），为空时回放到数据集末尾
+    pub end_timestamp_ns: Option<u64>,
+    /// 跳过校验失败的数据包
+    pub skip_invalid: bool,
+    /// 每秒最多发送的数据包数量，为空时不限制
+    ///
+    /// 与回放速度同时生效，数据包按两者中较晚的时刻发送。
+    #[serde(default)]
+    pub max_packets_per_second: Option<u64>,
+    /// 每秒最多发送的负载字节数，为空时不限制
+    #[serde(default)]
+    pub max_bytes_per_second: Option<u64>,
+}
+
+impl Default for PlaybackConfig {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            start_timestamp_ns: None,
+            end_timestamp_ns: None,
+            skip_invalid: true,
+            max_packets_per_second: None,
+            max_bytes_per_second: None,
+        }
+    }
+}
+
+impl PlaybackConfig {
+    /// 验证回放配置的有效性
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.speed.is_finite() || self.speed < 0.0 {
+            return Err(format!(
+                "回放速度必须是非负有限数: {}",
+                self.speed
+            ));
+        }
+
+        if let (Some(start), Some(end)) =
+            (self.start_timestamp_ns, self.end_timestamp_ns)
+        {
+            if start > end {
+                return Err(format!(
+                    "开始时间戳 {start} 晚于结束时间戳 {end}"
+                ));
+            }
+        }
+
+        if self.max_packets_per_second == Some(0) {
+            return Err(
+                "每秒最大数据包数量必须大于0".to_string()
+            );
+        }
+        if self.max_bytes_per_second == Some(0) {
+            return Err(
+                "每秒最大字节数必须大于0".to_string()
+            );
         }
 
         Ok(())
@@ -126,4 +751,12 @@ impl WriterConfig {
     pub fn reset(&mut self) {
         *self = Self::default();
     }
+
+    /// 由速率上限创建限速器
+    pub fn rate_limiter(&self) -> RateLimiter {
+        RateLimiter::new(
+            self.max_packets_per_second,
+            self.max_bytes_per_second,
+        )
+    }
 }
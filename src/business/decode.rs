@@ -0,0 +1,334 @@
+//! 负载解码模块
+//!
+//! 提供可插拔的负载解码器：
+//! - `PayloadDecoder` 定义把原始数据包负载转换为类型化结构的解码器；
+//! - `DecoderRegistry` 按链路类型和输出类型登记解码器，读取时根据文件头中的
+//!   链路类型自动选择解码器；
+//! - 内置 UDP 头剥离和长度前缀分帧两个示例解码器。
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+use crate::data::models::{DataPacket, LinkType};
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 负载解码器
+///
+/// 把数据包负载解码为 `Output` 类型。解码器需要线程安全，以便注册表在读取器之间共享。
+pub trait PayloadDecoder: Send + Sync {
+    /// 解码输出类型
+    type Output: 'static;
+
+    /// 解码一个数据包
+    fn decode(
+        &self,
+        packet: &DataPacket,
+    ) -> PcapResult<Self::Output>;
+}
+
+/// 擦除输出类型后的解码器，供注册表统一存储
+trait ErasedDecoder: Send + Sync {
+    fn decode_any(
+        &self,
+        packet: &DataPacket,
+    ) -> PcapResult<Box<dyn Any>>;
+}
+
+impl<D: PayloadDecoder> ErasedDecoder for D {
+    fn decode_any(
+        &self,
+        packet: &DataPacket,
+    ) -> PcapResult<Box<dyn Any>> {
+        let output = self.decode(packet)?;
+        Ok(Box::new(output))
+    }
+}
+
+/// 解码器注册表
+///
+/// 以（链路类型，输出类型）为键登记解码器。查找时优先匹配数据包的链路类型，
+/// 未找到时使用为该输出类型登记的通用解码器。
+#[derive(Default)]
+pub struct DecoderRegistry {
+    decoders:
+        HashMap<(LinkType, TypeId), Box<dyn ErasedDecoder>>,
+    fallbacks: HashMap<TypeId, Box<dyn ErasedDecoder>>,
+}
+
+impl DecoderRegistry {
+    /// 创建空的注册表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为指定链路类型登记解码器，已存在时替换
+    pub fn register<D: PayloadDecoder + 'static>(
+        &mut self,
+        link_type: LinkType,
+        decoder: D,
+    ) {
+        self.decoders.insert(
+            (link_type, TypeId::of::<D::Output>()),
+            Box::new(decoder),
+        );
+    }
+
+    /// 登记通用解码器，在没有匹配链路类型的解码器时使用
+    pub fn register_fallback<
+        D: PayloadDecoder + 'static,
+    >(
+        &mut self,
+        decoder: D,
+    ) {
+        self.fallbacks.insert(
+            TypeId::of::<D::Output>(),
+            Box::new(decoder),
+        );
+    }
+
+    /// 是否存在能把指定链路类型解码为 `T` 的解码器
+    pub fn contains<T: 'static>(
+        &self,
+        link_type: LinkType,
+    ) -> bool {
+        self.find(link_type, TypeId::of::<T>()).is_some()
+    }
+
+    /// 注册表中的解码器数量（含通用解码器）
+    pub fn len(&self) -> usize {
+        self.decoders.len() + self.fallbacks.len()
+    }
+
+    /// 注册表是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 按数据包的链路类型选择解码器并解码为 `T`
+    ///
+    /// # 错误
+    /// 未登记对应解码器时返回 `PcapError::DecoderNotFound`，
+    /// 解码器自身的错误原样返回
+    pub fn decode<T: 'static>(
+        &self,
+        packet: &DataPacket,
+    ) -> PcapResult<T> {
+        let decoder = self
+            .find(packet.link_type(), TypeId::of::<T>())
+            .ok_or_else(|| {
+                PcapError::DecoderNotFound(format!(
+                    "链路类型 {:?}，输出类型 {}",
+                    packet.link_type(),
+                    std::any::type_name::<T>()
+                ))
+            })?;
+
+        let output = decoder.decode_any(packet)?;
+        match output.downcast::<T>() {
+            Ok(value) => Ok(*value),
+            Err(_) => Err(PcapError::InvalidState(
+                "解码器输出类型不匹配".to_string(),
+            )),
+        }
+    }
+
+    fn find(
+        &self,
+        link_type: LinkType,
+        type_id: TypeId,
+    ) -> Option<&dyn ErasedDecoder> {
+        self.decoders
+            .get(&(link_type, type_id))
+            .or_else(|| self.fallbacks.get(&type_id))
+            .map(|decoder| decoder.as_ref())
+    }
+}
+
+impl std::fmt::Debug for DecoderRegistry {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("DecoderRegistry")
+            .field("decoders", &self.decoders.len())
+            .field("fallbacks", &self.fallbacks.len())
+            .finish()
+    }
+}
+
+/// UDP 数据报
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UdpDatagram {
+    /// 源端口
+    pub source_port: u16,
+    /// 目的端口
+    pub destination_port: u16,
+    /// 去掉 UDP 头后的负载
+    pub payload: Vec<u8>,
+}
+
+/// UDP 头剥离解码器
+///
+/// 负载以 8 字节 UDP 头开头时，解析端口并返回去掉头部的数据。
+/// 头部中的长度字段小于实际负载时按长度字段截断。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UdpHeaderStripDecoder;
+
+impl UdpHeaderStripDecoder {
+    /// UDP 头部大小（字节）
+    pub const HEADER_SIZE: usize = 8;
+}
+
+impl PayloadDecoder for UdpHeaderStripDecoder {
+    type Output = UdpDatagram;
+
+    fn decode(
+        &self,
+        packet: &DataPacket,
+    ) -> PcapResult<UdpDatagram> {
+        let data = &packet.data;
+        if data.len() < Self::HEADER_SIZE {
+            return Err(PcapError::DecodeFailed(format!(
+                "负载长度 {} 小于UDP头部大小",
+                data.len()
+            )));
+        }
+
+        let length =
+            u16::from_be_bytes([data[4], data[5]]) as usize;
+        if length < Self::HEADER_SIZE || length > data.len()
+        {
+            return Err(PcapError::DecodeFailed(format!(
+                "UDP长度字段 {length} 无效，负载长度 {}",
+                data.len()
+            )));
+        }
+
+        Ok(UdpDatagram {
+            source_port: u16::from_be_bytes([
+                data[0], data[1],
+            ]),
+            destination_port: u16::from_be_bytes([
+                data[2], data[3],
+            ]),
+            payload: data[Self::HEADER_SIZE..length]
+                .to_vec(),
+        })
+    }
+}
+
+/// 长度前缀格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthPrefix {
+    /// 1 字节长度
+    U8,
+    /// 2 字节小端长度
+    U16Le,
+    /// 2 字节大端长度
+    U16Be,
+    /// 4 字节小端长度
+    U32Le,
+    /// 4 字节大端长度
+    U32Be,
+}
+
+impl LengthPrefix {
+    /// 长度前缀占用的字节数
+    pub fn width(self) -> usize {
+        match self {
+            LengthPrefix::U8 => 1,
+            LengthPrefix::U16Le | LengthPrefix::U16Be => 2,
+            LengthPrefix::U32Le | LengthPrefix::U32Be => 4,
+        }
+    }
+
+    fn read(self, bytes: &[u8]) -> usize {
+        match self {
+            LengthPrefix::U8 => bytes[0] as usize,
+            LengthPrefix::U16Le => {
+                u16::from_le_bytes([bytes[0], bytes[1]])
+                    as usize
+            }
+            LengthPrefix::U16Be => {
+                u16::from_be_bytes([bytes[0], bytes[1]])
+                    as usize
+            }
+            LengthPrefix::U32Le => u32::from_le_bytes([
+                bytes[0], bytes[1], bytes[2], bytes[3],
+            ]) as usize,
+            LengthPrefix::U32Be => u32::from_be_bytes([
+                bytes[0], bytes[1], bytes[2], bytes[3],
+            ]) as usize,
+        }
+    }
+}
+
+/// 长度前缀分帧解码器
+///
+/// 负载由若干"长度前缀 + 帧内容"顺序拼接而成，解码为帧列表。
+/// 负载末尾不完整的帧视为解码失败。
+#[derive(Debug, Clone, Copy)]
+pub struct LengthPrefixedDecoder {
+    prefix: LengthPrefix,
+}
+
+impl LengthPrefixedDecoder {
+    /// 创建指定长度前缀格式的解码器
+    pub fn new(prefix: LengthPrefix) -> Self {
+        Self { prefix }
+    }
+
+    /// 长度前缀格式
+    pub fn prefix(&self) -> LengthPrefix {
+        self.prefix
+    }
+}
+
+impl Default for LengthPrefixedDecoder {
+    fn default() -> Self {
+        Self::new(LengthPrefix::U32Le)
+    }
+}
+
+impl PayloadDecoder for LengthPrefixedDecoder {
+    type Output = Vec<Vec<u8>>;
+
+    fn decode(
+        &self,
+        packet: &DataPacket,
+    ) -> PcapResult<Vec<Vec<u8>>> {
+        let data = &packet.data;
+        let width = self.prefix.width();
+        let mut frames = Vec::new();
+        let mut offset = 0usize;
+
+        while offset < data.len() {
+            if data.len() - offset < width {
+                return Err(PcapError::DecodeFailed(
+                    format!(
+                        "偏移 {offset} 处长度前缀不完整"
+                    ),
+                ));
+            }
+            let length = self
+                .prefix
+                .read(&data[offset..offset + width]);
+            offset += width;
+
+            if data.len() - offset < length {
+                return Err(PcapError::DecodeFailed(
+                    format!(
+                        "偏移 {offset} 处帧长度 {length} 超出负载剩余 {} 字节",
+                        data.len() - offset
+                    ),
+                ));
+            }
+            frames.push(
+                data[offset..offset + length].to_vec(),
+            );
+            offset += length;
+        }
+
+        Ok(frames)
+    }
+}
@@ -0,0 +1,197 @@
+//! 磁盘空间保护模块
+//!
+//! 写入器启用磁盘保护后，在创建新文件和写入数据前检查目标目录所在磁盘的可用空间。
+//! 可用空间低于阈值时返回 `PcapError::DiskFull`（或交由回调决定是否继续），
+//! 避免写到一半才因磁盘写满得到难以处理的 IO 错误。
+
+use log::{debug, warn};
+use std::path::{Path, PathBuf};
+
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 磁盘空间状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskSpaceStatus {
+    /// 检查的目录
+    pub path: PathBuf,
+    /// 可用空间（字节）
+    pub available_bytes: u64,
+    /// 继续写入所需的空间（保留空间 + 即将写入的数据）
+    pub required_bytes: u64,
+}
+
+/// 磁盘空间不足时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiskGuardAction {
+    /// 中止写入并返回 `PcapError::DiskFull`
+    Abort,
+    /// 继续写入（例如回调已清理出空间）
+    Continue,
+}
+
+/// 磁盘空间不足时的回调
+pub type DiskGuardHandler = Box<
+    dyn FnMut(&DiskSpaceStatus) -> DiskGuardAction + Send,
+>;
+
+/// 磁盘空间保护
+///
+/// 查询可用空间需要一次系统调用，因此写入数据时按累计字节数间隔检查，
+/// 创建新文件时总是检查。
+pub struct DiskGuard {
+    min_free_bytes: u64,
+    check_interval_bytes: u64,
+    pending_bytes: u64,
+    handler: Option<DiskGuardHandler>,
+}
+
+impl DiskGuard {
+    /// 默认检查间隔（字节）
+    pub const DEFAULT_CHECK_INTERVAL_BYTES: u64 =
+        1024 * 1024;
+
+    /// 创建磁盘保护，要求磁盘至少保留 `min_free_bytes` 字节可用空间
+    pub fn new(min_free_bytes: u64) -> Self {
+        Self {
+            min_free_bytes,
+            check_interval_bytes:
+                Self::DEFAULT_CHECK_INTERVAL_BYTES,
+            // 首次写入时立即检查
+            pending_bytes: u64::MAX,
+            handler: None,
+        }
+    }
+
+    /// 设置写入数据时的检查间隔（字节），0 表示每次写入都检查
+    pub fn with_check_interval(
+        mut self,
+        check_interval_bytes: u64,
+    ) -> Self {
+        self.check_interval_bytes = check_interval_bytes;
+        self
+    }
+
+    /// 设置空间不足时的回调
+    pub fn with_handler<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&DiskSpaceStatus) -> DiskGuardAction
+            + Send
+            + 'static,
+    {
+        self.handler = Some(Box::new(handler));
+        self
+    }
+
+    /// 要求保留的最小可用空间（字节）
+    pub fn min_free_bytes(&self) -> u64 {
+        self.min_free_bytes
+    }
+
+    /// 写入数据时的检查间隔（字节）
+    pub fn check_interval_bytes(&self) -> u64 {
+        self.check_interval_bytes
+    }
+
+    /// 写入数据前检查，累计写入量未达到检查间隔时跳过
+    pub(crate) fn check_before_write(
+        &mut self,
+        directory: &Path,
+        incoming_bytes: u64,
+    ) -> PcapResult<()> {
+        self.pending_bytes = self
+            .pending_bytes
+            .saturating_add(incoming_bytes);
+        if self.pending_bytes < self.check_interval_bytes {
+            return Ok(());
+        }
+        self.check(directory, incoming_bytes)
+    }
+
+    /// 立即检查可用空间
+    pub(crate) fn check(
+        &mut self,
+        directory: &Path,
+        incoming_bytes: u64,
+    ) -> PcapResult<()> {
+        let available_bytes = match available_space(
+            directory,
+        ) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                // 无法查询时不阻止写入，由后续 IO 操作暴露真实错误
+                warn!("查询磁盘可用空间失败: {directory:?}, 错误: {e}");
+                return Ok(());
+            }
+        };
+        self.pending_bytes = 0;
+
+        let required_bytes = self
+            .min_free_bytes
+            .saturating_add(incoming_bytes);
+        if available_bytes >= required_bytes {
+            return Ok(());
+        }
+
+        let status = DiskSpaceStatus {
+            path: directory.to_path_buf(),
+            available_bytes,
+            required_bytes,
+        };
+        let action = match self.handler.as_mut() {
+            Some(handler) => handler(&status),
+            None => DiskGuardAction::Abort,
+        };
+
+        match action {
+            DiskGuardAction::Continue => {
+                debug!("磁盘空间不足，回调要求继续写入: {status:?}");
+                Ok(())
+            }
+            DiskGuardAction::Abort => {
+                Err(PcapError::DiskFull {
+                    path: directory.display().to_string(),
+                    available: available_bytes,
+                    required: required_bytes,
+                })
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for DiskGuard {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("DiskGuard")
+            .field("min_free_bytes", &self.min_free_bytes)
+            .field(
+                "check_interval_bytes",
+                &self.check_interval_bytes,
+            )
+            .field("has_handler", &self.handler.is_some())
+            .finish()
+    }
+}
+
+/// 查询目录所在磁盘的可用空间（字节）
+#[cfg(not(target_arch = "wasm32"))]
+pub fn available_space<P: AsRef<Path>>(
+    path: P,
+) -> PcapResult<u64> {
+    fs4::available_space(path.as_ref())
+        .map_err(PcapError::Io)
+}
+
+/// 查询目录所在磁盘的可用空间（字节）
+///
+/// WASM 目标没有可查询的磁盘，总是返回 `Unsupported` 错误。
+#[cfg(target_arch = "wasm32")]
+pub fn available_space<P: AsRef<Path>>(
+    path: P,
+) -> PcapResult<u64> {
+    Err(PcapError::Io(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("不支持查询磁盘空间: {:?}", path.as_ref()),
+    )))
+}
@@ -0,0 +1,121 @@
+//! 索引健康报告模块
+//!
+//! 记录索引加载、验证和修复过程中发现的问题，供调用方检查数据集状态。
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// 索引健康问题类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexHealthKind {
+    /// 索引文件损坏（无法解析）
+    CorruptedIndex,
+    /// 两个数据文件的时间范围重叠
+    TimeRangeOverlap,
+    /// 数据文件无法分析（如文件头校验失败），已从索引中隔离
+    QuarantinedFile,
+}
+
+/// 重新生成索引的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexRebuildReason {
+    /// 索引文件不存在
+    Missing,
+    /// 索引文件损坏：无法解析或校验和不匹配
+    Corrupted(String),
+    /// 索引文件完好，但与当前数据文件不一致
+    Stale,
+    /// 调用 `rebuild_index` 强制重建
+    Forced,
+}
+
+impl fmt::Display for IndexRebuildReason {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            IndexRebuildReason::Missing => {
+                f.write_str("索引文件不存在")
+            }
+            IndexRebuildReason::Corrupted(problem) => {
+                write!(f, "索引文件损坏: {problem}")
+            }
+            IndexRebuildReason::Stale => {
+                f.write_str("索引与数据文件不一致")
+            }
+            IndexRebuildReason::Forced => {
+                f.write_str("强制重建")
+            }
+        }
+    }
+}
+
+/// 索引健康报告条目
+#[derive(Debug, Clone)]
+pub struct IndexHealthEntry {
+    /// 问题类型
+    pub kind: IndexHealthKind,
+    /// 问题描述
+    pub message: String,
+    /// 相关文件路径（如被隔离的索引文件）
+    pub path: Option<PathBuf>,
+}
+
+/// 索引健康报告
+///
+/// 由 `IndexManager` 在 `ensure_index` 过程中填充，
+/// 不影响索引的正常使用，仅用于诊断。
+#[derive(Debug, Clone, Default)]
+pub struct IndexHealth {
+    entries: Vec<IndexHealthEntry>,
+}
+
+impl IndexHealth {
+    /// 创建空的健康报告
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一条问题记录
+    pub fn push(
+        &mut self,
+        kind: IndexHealthKind,
+        message: String,
+        path: Option<PathBuf>,
+    ) {
+        self.entries.push(IndexHealthEntry {
+            kind,
+            message,
+            path,
+        });
+    }
+
+    /// 获取所有问题记录
+    pub fn entries(&self) -> &[IndexHealthEntry] {
+        &self.entries
+    }
+
+    /// 是否未发现任何问题
+    pub fn is_healthy(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 是否包含指定类型的问题
+    pub fn has(&self, kind: &IndexHealthKind) -> bool {
+        self.entries.iter().any(|e| &e.kind == kind)
+    }
+
+    /// 清空所有记录
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// 移除指定类型的记录
+    pub(crate) fn remove(
+        &mut self,
+        kind: &IndexHealthKind,
+    ) {
+        self.entries.retain(|e| &e.kind != kind);
+    }
+}
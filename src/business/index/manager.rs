@@ -10,6 +10,8 @@
 //!
 //! 索引文件使用 XML 格式存储，文件扩展名为 `.pidx`。
 //! 包含数据集的元数据和每个数据包的位置信息。
+//! 文件末尾的注释 `<!-- pidx-sha256:... -->` 记录其之前内容的 SHA-256 摘要，
+//! 加载时校验以区分内容损坏与索引过时；没有摘要的旧版索引文件跳过校验。
 //!
 //! # 使用示例
 //!
@@ -31,140 +33,1589 @@
 use log::{debug, info, warn};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
-use std::fs::{self, File};
+use std::fs::{self, File, OpenOptions};
 use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use crate::business::config::ReaderConfig;
+use crate::business::index::health::{
+    IndexHealth, IndexHealthKind, IndexRebuildReason,
+};
+#[cfg(feature = "mmap-index")]
+use crate::business::index::mmap::MmapTimestampIndex;
+use crate::business::index::query::IndexQuery;
+use crate::business::index::schema::validate_schema;
+use crate::business::index::shared::SharedIndex;
+use crate::business::index::subset::DatasetSubset;
 use crate::business::index::types::{
-    PacketIndexEntry, PcapFileIndex, PidxIndex,
+    BackfillReport, CompactionReport, IndexMemoryUsage,
+    PacketIndexEntry, PcapFileIndex, PidxIndex, SeekMode,
+    TimestampPointer, TruncationReport,
 };
+use crate::business::progress::WriterProgress;
+use crate::business::shards::ShardManifest;
+use crate::business::units::ByteSize;
 use crate::data::file_reader::PcapFileReader;
 use crate::foundation::error::{PcapError, PcapResult};
+use crate::foundation::paths;
+use crate::foundation::utils::calculate_payload_hash;
+
+/// PIDX索引管理器
+///
+/// 提供索引的完整生命周期管理，包括：
+/// - 自动加载和验证现有索引
+/// - 检测数据变化并重建索引
+/// - 生成和保存新索引
+///
+/// 这是一个统一的入口，封装了用户的典型使用流程：
+/// 加载索引 -> 验证有效性 -> 重新生成（如果需要）
+pub struct IndexManager {
+    /// 数据集目录路径
+    dataset_path: PathBuf,
+    /// 数据集名称
+    dataset_name: String,
+    /// 当前索引，与快照和共享句柄共享同一份数据
+    index: Option<Arc<PidxIndex>>,
+    /// 发布给其他线程的索引
+    shared: SharedIndex,
+    /// 内存索引的字节上限，0表示不限制
+    max_memory: ByteSize,
+    /// 是否按起始时间戳排序数据文件
+    sort_files_by_time: bool,
+    /// 扫描数据文件时跳过的文件名模式
+    skip_patterns: Vec<String>,
+    /// 加载后把索引限制为的数据集子集
+    subset: Option<DatasetSubset>,
+    /// 是否使用磁盘上的时间戳表定位时间戳
+    timestamp_table_enabled: bool,
+    /// 已打开的时间戳表，索引变化时清除
+    #[cfg(feature = "mmap-index")]
+    timestamp_table: Option<Arc<MmapTimestampIndex>>,
+    /// 是否自动隔离并重建损坏的索引
+    auto_repair: bool,
+    /// 分析数据文件时是否容忍文件末尾不完整的数据包
+    salvage_truncated: bool,
+    /// 分析数据文件时是否记录负载内容哈希
+    payload_hash: bool,
+    /// 只读模式，不在数据集目录中生成或改写索引文件
+    read_only: bool,
+    /// 索引目录中本数据集的索引文件，为空时索引保存在数据集目录中
+    external_index: Option<PathBuf>,
+    /// 索引健康报告
+    health: IndexHealth,
+    /// 最近一次重新生成索引的原因
+    rebuild_reason: Option<IndexRebuildReason>,
+    /// 当前索引是否为活动写入期间构建的实时索引
+    live: bool,
+    /// 存储分片上的数据集目录（来自分片清单）
+    shard_dirs: Vec<PathBuf>,
+    /// 写入器正在记录的文件索引
+    recording: Option<(PathBuf, PcapFileIndex)>,
+    /// 写入器已记录完成的文件索引，生成索引时代替重新分析数据文件
+    recorded: HashMap<PathBuf, PcapFileIndex>,
+    /// 独立索引模式，只管理索引文件，没有对应的数据集目录
+    standalone: bool,
+}
+
+impl IndexManager {
+    /// 创建新的索引管理器
+    ///
+    /// # 参数
+    /// - `base_path` - 基础目录路径
+    /// - `dataset_name` - 数据集名称
+    ///
+    /// # 返回
+    /// 返回初始化后的管理器实例
+    pub fn new<P: AsRef<Path>>(
+        base_path: P,
+        dataset_name: &str,
+    ) -> PcapResult<Self> {
+        let path =
+            paths::dataset_path(base_path, dataset_name)?;
+
+        if !path.exists() {
+            return Err(PcapError::DirectoryNotFound(
+                format!("数据集目录不存在: {path:?}"),
+            ));
+        }
+
+        if !path.is_dir() {
+            return Err(PcapError::InvalidArgument(
+                format!("指定路径不是目录: {path:?}"),
+            ));
+        }
+
+        let shard_dirs =
+            ShardManifest::load(&path).directories;
+
+        Ok(Self {
+            dataset_path: path,
+            dataset_name: dataset_name.to_string(),
+            index: None,
+            shared: SharedIndex::default(),
+            max_memory: ByteSize::ZERO,
+            sort_files_by_time: false,
+            skip_patterns: Vec::new(),
+            subset: None,
+            timestamp_table_enabled: false,
+            #[cfg(feature = "mmap-index")]
+            timestamp_table: None,
+            auto_repair: true,
+            salvage_truncated: false,
+            payload_hash: false,
+            read_only: false,
+            external_index: None,
+            health: IndexHealth::new(),
+            rebuild_reason: None,
+            live: false,
+            shard_dirs,
+            recording: None,
+            recorded: HashMap::new(),
+            standalone: false,
+        })
+    }
+
+    /// 以独立索引模式打开PIDX文件
+    ///
+    /// 不需要数据集目录，适合只同步索引文件的目录服务等元数据工具：可以查询索引、
+    /// 通过 `transform_index` 修改并用 `save_index` 改写原文件。加载时校验摘要和索引结构，
+    /// 不检查数据文件；需要数据文件的操作（重新生成、压缩、验证索引和构建实时索引）
+    /// 返回 `PcapError::InvalidState`。
+    ///
+    /// # 错误
+    /// 文件不存在时返回 `PcapError::FileNotFound`；摘要不匹配或无法解析时返回
+    /// `PcapError::InvalidFormat`；不符合索引结构时返回 `PcapError::InvalidIndexSchema`
+    pub fn open_standalone<P: AsRef<Path>>(
+        pidx_path: P,
+    ) -> PcapResult<Self> {
+        let pidx_path = pidx_path.as_ref();
+        if !pidx_path.is_file() {
+            return Err(PcapError::FileNotFound(format!(
+                "{pidx_path:?}"
+            )));
+        }
+
+        let mut manager = Self {
+            dataset_path: pidx_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+            dataset_name: pidx_path
+                .file_name()
+                .map(|name| {
+                    name.to_string_lossy().into_owned()
+                })
+                .unwrap_or_default(),
+            index: None,
+            shared: SharedIndex::default(),
+            max_memory: ByteSize::ZERO,
+            sort_files_by_time: false,
+            skip_patterns: Vec::new(),
+            subset: None,
+            timestamp_table_enabled: false,
+            #[cfg(feature = "mmap-index")]
+            timestamp_table: None,
+            auto_repair: false,
+            salvage_truncated: false,
+            payload_hash: false,
+            read_only: false,
+            external_index: Some(pidx_path.to_path_buf()),
+            health: IndexHealth::new(),
+            rebuild_reason: None,
+            live: false,
+            shard_dirs: Vec::new(),
+            recording: None,
+            recorded: HashMap::new(),
+            standalone: true,
+        };
+        let index = manager.load_index(pidx_path)?;
+        manager.set_index(Some(index));
+        Ok(manager)
+    }
+
+    /// 是否为独立索引模式
+    pub fn is_standalone(&self) -> bool {
+        self.standalone
+    }
+
+    /// 独立索引模式下拒绝需要数据文件的操作
+    fn require_dataset(
+        &self,
+        operation: &str,
+    ) -> PcapResult<()> {
+        if self.standalone {
+            return Err(PcapError::InvalidState(format!(
+                "独立索引模式下没有数据集目录，不能{operation}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// 设置是否自动修复损坏的索引文件
+    ///
+    /// 启用时，损坏的索引文件会被重命名隔离并重新生成；
+    /// 禁用时，`ensure_index` 遇到损坏索引将直接返回错误。
+    pub fn set_auto_repair(&mut self, enabled: bool) {
+        self.auto_repair = enabled;
+    }
+
+    /// 设置分析数据文件时是否容忍文件末尾不完整的数据包
+    ///
+    /// 启用时，末尾数据包不完整的文件仍然加入索引，索引只包含完整的数据包；
+    /// 禁用时，这样的文件（正在写入的文件除外）被视为无法分析而不加入索引。
+    pub fn set_salvage_truncated(&mut self, enabled: bool) {
+        self.salvage_truncated = enabled;
+    }
+
+    /// 设置分析数据文件时是否记录负载内容哈希
+    ///
+    /// 写入器按 `WriterConfig::index_payload_hash` 设置；加载到带有负载哈希的旧索引时
+    /// 自动启用，重新生成索引不会丢失哈希。
+    pub fn set_payload_hash(&mut self, enabled: bool) {
+        self.payload_hash = enabled;
+    }
+
+    /// 设置是否以只读模式管理索引
+    ///
+    /// 只读模式下不在数据集目录中写入索引。未设置索引目录时 `ensure_index`
+    /// 只加载已有的有效索引，索引缺失、损坏或过时时返回错误；
+    /// 写入数据集目录中索引文件的操作都返回 `PcapError::InvalidState`。
+    pub fn set_read_only(&mut self, enabled: bool) {
+        self.read_only = enabled;
+    }
+
+    /// 是否为只读模式
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// 设置保存索引的目录
+    ///
+    /// 设置后生成和重建的索引保存到该目录下以数据集路径哈希命名的文件中，
+    /// 数据集目录保持不变；加载时优先使用该文件，不存在时使用数据集目录中的索引。
+    /// 多个数据集可以共用同一个索引目录。
+    pub fn set_index_dir(
+        &mut self,
+        index_dir: Option<PathBuf>,
+    ) {
+        self.external_index = index_dir.map(|dir| {
+            dir.join(external_index_file_name(
+                &self.dataset_path,
+                &self.dataset_name,
+            ))
+        });
+    }
+
+    /// 生成和重建的索引保存的位置
+    pub fn index_file_path(&self) -> PathBuf {
+        self.get_pidx_file_path()
+    }
+
+    /// 设置存储分片上的数据集目录
+    ///
+    /// 扫描数据文件时除主数据集目录外还会扫描这些目录。
+    pub fn set_storage_shards(
+        &mut self,
+        shard_dirs: Vec<PathBuf>,
+    ) {
+        self.shard_dirs = shard_dirs;
+    }
+
+    /// 存储分片上的数据集目录
+    pub fn storage_shards(&self) -> &[PathBuf] {
+        &self.shard_dirs
+    }
+
+    /// 获取索引健康报告
+    pub fn health(&self) -> &IndexHealth {
+        &self.health
+    }
+
+    /// 最近一次 `ensure_index` 或 `rebuild_index` 重新生成索引的原因
+    ///
+    /// 使用了磁盘上的有效索引时为 None。
+    pub fn rebuild_reason(
+        &self,
+    ) -> Option<&IndexRebuildReason> {
+        self.rebuild_reason.as_ref()
+    }
+
+    /// 确保索引可用
+    ///
+    /// 这是主要的入口方法，实现了完整的索引管理流程：
+    /// 1. 尝试加载现有索引
+    /// 2. 验证索引有效性
+    /// 3. 如果无效则重新生成
+    /// 4. 返回可用的索引
+    ///
+    /// 设置了数据集子集时返回限制为子集的索引，见 `set_subset`。
+    pub fn ensure_index(
+        &mut self,
+    ) -> PcapResult<&PidxIndex> {
+        self.ensure_full_index()?;
+        self.apply_subset();
+        self.index.as_deref().ok_or_else(|| {
+            PcapError::InvalidState(
+                "索引未正确初始化".to_string(),
+            )
+        })
+    }
+
+    /// 加载、验证或生成包含全部数据文件的索引
+    fn ensure_full_index(
+        &mut self,
+    ) -> PcapResult<&PidxIndex> {
+        if self.standalone {
+            return self.index.as_deref().ok_or_else(
+                || {
+                    PcapError::InvalidState(
+                        "索引未正确初始化".to_string(),
+                    )
+                },
+            );
+        }
+        info!("正在检查数据集索引: {}", self.dataset_name);
+        self.health.clear();
+        self.live = false;
+        self.rebuild_reason = None;
+        if self.read_only && self.external_index.is_none() {
+            return self.load_index_read_only();
+        }
+
+        // 1. 尝试加载现有索引，只有内容损坏时才隔离，读取失败直接返回错误
+        let reason = match self.find_pidx_file()? {
+            Some(pidx_path) => {
+                info!("找到索引文件: {pidx_path:?}");
+                match self.parse_pidx_file(&pidx_path)? {
+                    Err(problem) => {
+                        self.handle_corrupted_index(
+                            &pidx_path, &problem,
+                        )?;
+                        IndexRebuildReason::Corrupted(
+                            problem,
+                        )
+                    }
+                    Ok(index) => {
+                        // 验证索引有效性
+                        if self.is_index_valid(&index)? {
+                            info!("使用现有的有效索引文件");
+                            self.set_index(Some(index));
+                            return self
+                                .index
+                                .as_deref()
+                                .ok_or_else(|| {
+                                    PcapError::InvalidState(
+                                        "索引未正确初始化"
+                                            .to_string(),
+                                    )
+                                });
+                        }
+                        if index.has_payload_hashes() {
+                            self.payload_hash = true;
+                        }
+                        IndexRebuildReason::Stale
+                    }
+                }
+            }
+            None => IndexRebuildReason::Missing,
+        };
+
+        // 2. 生成新索引
+        info!("重新生成索引: {reason}");
+        self.rebuild_reason = Some(reason);
+        self.generate_index()?;
+        self.index.as_deref().ok_or_else(|| {
+            PcapError::InvalidState(
+                "索引生成后未正确初始化".to_string(),
+            )
+        })
+    }
+
+    /// 只读模式下加载索引，不满足使用条件时返回错误而不是重新生成
+    fn load_index_read_only(
+        &mut self,
+    ) -> PcapResult<&PidxIndex> {
+        let pidx_path =
+            self.find_pidx_file()?.ok_or_else(|| {
+                PcapError::FileNotFound(format!(
+                    "只读模式下索引文件不存在，需先在可写位置生成索引或设置索引目录: {:?}",
+                    self.get_pidx_file_path()
+                ))
+            })?;
+        let index = self
+            .parse_pidx_file(&pidx_path)?
+            .map_err(|problem| {
+                PcapError::InvalidFormat(format!(
+                    "只读模式下索引文件损坏: {pidx_path:?}, {problem}"
+                ))
+            })?;
+        if !self.is_index_valid(&index)? {
+            return Err(PcapError::InvalidState(format!(
+                "只读模式下索引与数据文件不一致，需先在可写位置重新生成索引: {pidx_path:?}"
+            )));
+        }
+        info!("只读模式，使用现有索引文件: {pidx_path:?}");
+        self.set_index(Some(index));
+        self.index.as_deref().ok_or_else(|| {
+            PcapError::InvalidState(
+                "索引未正确初始化".to_string(),
+            )
+        })
+    }
+
+    /// 已保存的索引文件的结构版本
+    ///
+    /// 索引文件不存在或无法解析时返回 `None`。
+    pub fn stored_schema_version(&self) -> Option<u32> {
+        self.load_stored_index()
+            .map(|index| index.schema_version)
+    }
+
+    /// 按索引结构校验外部生成的PIDX文件
+    ///
+    /// 适合在持续集成中检查由其他工具生成的索引，不需要对应的数据集目录，
+    /// 也不会检查数据文件的哈希。
+    ///
+    /// # 错误
+    /// 文件不存在时返回 `PcapError::FileNotFound`；
+    /// 不符合索引结构时返回 `PcapError::InvalidIndexSchema`，包含行号、列号和出错的元素路径
+    pub fn validate_index_schema<P: AsRef<Path>>(
+        pidx_path: P,
+    ) -> PcapResult<()> {
+        let pidx_path = pidx_path.as_ref();
+        if !pidx_path.is_file() {
+            return Err(PcapError::FileNotFound(format!(
+                "{pidx_path:?}"
+            )));
+        }
+        let xml_content = fs::read_to_string(pidx_path)
+            .map_err(PcapError::Io)?;
+        validate_schema(verify_checksum(&xml_content)?)
+            .map_err(PcapError::InvalidIndexSchema)
+    }
+
+    /// 强制重建索引
+    pub fn rebuild_index(&mut self) -> PcapResult<PathBuf> {
+        self.require_dataset("重新生成索引")?;
+        // 保留已有索引中的负载哈希
+        if !self.payload_hash {
+            self.payload_hash = match self.index.as_deref()
+            {
+                Some(index) => index.has_payload_hashes(),
+                None => {
+                    self.load_stored_index().is_some_and(
+                        |index| index.has_payload_hashes(),
+                    )
+                }
+            };
+        }
+        self.set_index(None);
+        self.live = false;
+        info!(
+            "重新生成索引: {}",
+            IndexRebuildReason::Forced
+        );
+        self.rebuild_reason =
+            Some(IndexRebuildReason::Forced);
+        let pidx_path = self.generate_index()?;
+        self.apply_subset();
+        Ok(pidx_path)
+    }
+
+    /// 压缩索引文件
+    ///
+    /// 在不重新分析数据文件的前提下清理多次局部重建后积累的冗余内容：
+    /// 移除数据文件已不存在的文件条目和重复的文件条目，按文件偏移排序各文件的
+    /// 数据包条目并去除重复条目，重新编号后以当前结构版本改写索引文件。
+    ///
+    /// # 错误
+    /// 索引文件不存在时返回 `PcapError::FileNotFound`；
+    /// 当前使用实时索引（写入器仍在写入）或处于独立索引模式时返回 `PcapError::InvalidState`
+    pub fn compact(
+        &mut self,
+    ) -> PcapResult<CompactionReport> {
+        self.require_dataset("压缩索引")?;
+        if self.live {
+            return Err(PcapError::InvalidState(
+                "数据集正在写入，不能压缩实时索引"
+                    .to_string(),
+            ));
+        }
+        let pidx_path =
+            self.find_pidx_file()?.ok_or_else(|| {
+                PcapError::FileNotFound(format!(
+                    "{:?}",
+                    self.get_pidx_file_path()
+                ))
+            })?;
+        let mut report = CompactionReport {
+            bytes_before: fs::metadata(&pidx_path)
+                .map_err(PcapError::Io)?
+                .len(),
+            ..CompactionReport::default()
+        };
+        let mut index = self.load_index(&pidx_path)?;
+
+        let mut seen = std::collections::HashSet::new();
+        let mut files = Vec::new();
+        for mut file_index in
+            std::mem::take(&mut index.data_files.files)
+        {
+            if !file_index
+                .resolve_path(&self.dataset_path)
+                .is_file()
+            {
+                debug!(
+                    "移除数据文件已不存在的索引条目: {}",
+                    file_index.file_name
+                );
+                report.orphan_files_removed += 1;
+                continue;
+            }
+            if !seen.insert((
+                file_index.file_name.clone(),
+                file_index.shard.clone(),
+            )) {
+                report.duplicate_files_removed += 1;
+                continue;
+            }
+            let (removed, resorted) =
+                file_index.compact_entries();
+            report.duplicate_entries_removed += removed;
+            report.files_resorted += usize::from(resorted);
+            files.push(file_index);
+        }
+        files.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        index.data_files.files = files;
+
+        index.schema_version = PidxIndex::SCHEMA_VERSION;
+        index.update_time_range();
+        self.build_lookup(&mut index);
+        index.update_total_packets();
+
+        self.set_index(Some(index));
+        self.save_index_to_file(&pidx_path)?;
+        report.bytes_after = fs::metadata(&pidx_path)
+            .map_err(PcapError::Io)?
+            .len();
+
+        info!(
+            "索引压缩完成: {}, 移除孤立文件 {}，重复文件 {}，重复条目 {}",
+            self.dataset_name,
+            report.orphan_files_removed,
+            report.duplicate_files_removed,
+            report.duplicate_entries_removed
+        );
+        Ok(report)
+    }
+
+    /// 补全早期版本索引中缺少的文件元数据
+    ///
+    /// 缺少文件哈希或大小时按数据文件计算；已有数据包条目的文件由条目推算数据包数量和时间范围，
+    /// 不重新分析数据文件；没有数据包条目且缺少数量或时间范围的文件才重新分析。
+    /// 随后以当前结构版本改写索引文件，之后加载索引时不会因为哈希缺失而整体重建。
+    /// 已有的元数据不做核对，怀疑与数据文件不一致时应使用 `rebuild_index`。
+    ///
+    /// # 错误
+    /// 索引文件不存在时返回 `PcapError::FileNotFound`；
+    /// 当前使用实时索引或处于独立索引模式时返回 `PcapError::InvalidState`
+    pub fn backfill_file_metadata(
+        &mut self,
+    ) -> PcapResult<BackfillReport> {
+        self.require_dataset("补全文件元数据")?;
+        if self.live {
+            return Err(PcapError::InvalidState(
+                "数据集正在写入，不能补全实时索引"
+                    .to_string(),
+            ));
+        }
+        let pidx_path =
+            self.find_pidx_file()?.ok_or_else(|| {
+                PcapError::FileNotFound(format!(
+                    "{:?}",
+                    self.get_pidx_file_path()
+                ))
+            })?;
+        let mut index = self.load_index(&pidx_path)?;
+        let mut report = BackfillReport {
+            schema_upgraded: index.is_legacy_schema(),
+            ..BackfillReport::default()
+        };
+
+        for file_index in &mut index.data_files.files {
+            let path =
+                file_index.resolve_path(&self.dataset_path);
+            if !path.is_file() {
+                warn!("数据文件不存在，无法补全元数据: {path:?}");
+                report.missing_files += 1;
+                continue;
+            }
+
+            let entries = &file_index.data_packets;
+            if entries.is_empty()
+                && (file_index.packet_count == 0
+                    || file_index.start_timestamp == 0
+                    || file_index.end_timestamp == 0)
+            {
+                debug!("重新分析缺少数据包条目的文件: {path:?}");
+                let shard = file_index.shard.take();
+                *file_index =
+                    self.index_pcap_file(&path, false)?;
+                file_index.shard = shard;
+                report.files_rescanned += 1;
+                continue;
+            }
+
+            if file_index.packet_count == 0
+                && !entries.is_empty()
+            {
+                file_index.packet_count =
+                    entries.len() as u64;
+                report.packet_counts_filled += 1;
+            }
+            if file_index.start_timestamp == 0
+                || file_index.end_timestamp == 0
+            {
+                let timestamps = file_index
+                    .data_packets
+                    .iter()
+                    .map(|entry| entry.timestamp_ns);
+                if let (Some(start), Some(end)) = (
+                    timestamps.clone().min(),
+                    timestamps.max(),
+                ) {
+                    file_index.start_timestamp = start;
+                    file_index.end_timestamp = end;
+                    report.time_ranges_filled += 1;
+                }
+            }
+            if file_index.file_hash.is_empty() {
+                file_index.file_hash =
+                    self.calculate_file_hash(&path)?;
+                report.hashes_filled += 1;
+            }
+            if file_index.file_size == 0 {
+                file_index.file_size = fs::metadata(&path)
+                    .map_err(PcapError::Io)?
+                    .len();
+                report.sizes_filled += 1;
+            }
+        }
+
+        index.schema_version = PidxIndex::SCHEMA_VERSION;
+        index.update_time_range();
+        self.build_lookup(&mut index);
+        index.update_total_packets();
+
+        self.set_index(Some(index));
+        self.save_index_to_file(&pidx_path)?;
+        info!(
+            "文件元数据补全完成: {}, 补全哈希 {}，数量 {}，时间范围 {}，重新分析 {} 个文件",
+            self.dataset_name,
+            report.hashes_filled,
+            report.packet_counts_filled,
+            report.time_ranges_filled,
+            report.files_rescanned
+        );
+        Ok(report)
+    }
+
+    /// 数据集目录迁移后改写索引中与路径相关的字段
+    ///
+    /// 描述中的旧数据集名称替换为当前名称，文件记录的分片目录按 `shard_moves`
+    /// （旧目录，新目录）改写。随后核对每个数据文件都能在新位置找到，
+    /// `verify_hashes` 为真时还核对内容哈希；有文件缺失或哈希不一致时重建索引。
+    ///
+    /// # 返回
+    /// 索引不存在时返回 `Ok(None)`，否则返回是否重建了索引
+    pub(crate) fn rewrite_relocated_index(
+        &mut self,
+        old_name: &str,
+        shard_moves: &[(PathBuf, PathBuf)],
+        verify_hashes: bool,
+    ) -> PcapResult<Option<bool>> {
+        let Some(pidx_path) = self.find_pidx_file()? else {
+            return Ok(None);
+        };
+        let mut index = self.load_index(&pidx_path)?;
+
+        if index.description
+            == format!("数据集: {old_name}")
+        {
+            index.description =
+                format!("数据集: {}", self.dataset_name);
+        }
+        for file_index in &mut index.data_files.files {
+            let moved = file_index.shard.as_ref().and_then(
+                |shard| {
+                    shard_moves
+                        .iter()
+                        .find(|(old, _)| old == shard)
+                },
+            );
+            if let Some((_, new)) = moved {
+                file_index.shard = Some(new.clone());
+            }
+        }
+
+        let mut consistent = true;
+        for file_index in &index.data_files.files {
+            let path =
+                file_index.resolve_path(&self.dataset_path);
+            let matches = path.is_file()
+                && (!verify_hashes
+                    || self.verify_file_hash(
+                        &path,
+                        &file_index.file_hash,
+                    )?);
+            if !matches {
+                warn!("迁移后的数据文件与索引不一致: {path:?}");
+                consistent = false;
+                break;
+            }
+        }
+
+        self.set_index(Some(index));
+        if !consistent {
+            self.rebuild_index()?;
+            return Ok(Some(true));
+        }
+        self.save_index_to_file(&pidx_path)?;
+        debug!("已改写迁移后的索引: {pidx_path:?}");
+        Ok(Some(false))
+    }
+
+    /// 丢弃时间戳晚于 `timestamp_ns` 的全部数据包
+    ///
+    /// 起始时间晚于截断时间戳的数据文件整个删除；跨越截断时间戳的文件在按文件偏移
+    /// 第一个晚于截断时间戳的数据包处截断，其后乱序写入的较早数据包一并丢弃。
+    /// 随后更新被截断文件的元数据并改写索引文件。调用方需持有数据集锁。
+    pub(crate) fn truncate_after(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> PcapResult<TruncationReport> {
+        self.require_dataset("截断数据集")?;
+        let mut index = self.ensure_full_index()?.clone();
+        let pidx_path = self
+            .find_pidx_file()?
+            .unwrap_or_else(|| self.get_pidx_file_path());
+        let mut report = TruncationReport {
+            cutoff_ns: timestamp_ns,
+            ..TruncationReport::default()
+        };
+
+        let mut files = Vec::new();
+        for mut file_index in
+            std::mem::take(&mut index.data_files.files)
+        {
+            if file_index.end_timestamp <= timestamp_ns {
+                files.push(file_index);
+                continue;
+            }
+            let path =
+                file_index.resolve_path(&self.dataset_path);
+            if file_index.data_packets.is_empty() {
+                let shard = file_index.shard.take();
+                file_index =
+                    self.index_pcap_file(&path, false)?;
+                file_index.shard = shard;
+            }
+            file_index
+                .data_packets
+                .sort_by_key(|entry| entry.byte_offset);
+            let Some(cut) = file_index
+                .data_packets
+                .iter()
+                .position(|entry| {
+                    entry.timestamp_ns > timestamp_ns
+                })
+            else {
+                files.push(file_index);
+                continue;
+            };
+
+            let offset =
+                file_index.data_packets[cut].byte_offset;
+            let file_size = fs::metadata(&path)
+                .map_err(PcapError::Io)?
+                .len();
+            report.packets_removed +=
+                (file_index.data_packets.len() - cut)
+                    as u64;
+            report.bytes_removed +=
+                file_size.saturating_sub(offset);
+            if cut == 0 {
+                fs::remove_file(&path)
+                    .map_err(PcapError::Io)?;
+                debug!("已删除截断时间戳之后的数据文件: {path:?}");
+                report.files_removed += 1;
+                continue;
+            }
+
+            let file = OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .map_err(PcapError::Io)?;
+            file.set_len(offset).map_err(PcapError::Io)?;
+            file.sync_all().map_err(PcapError::Io)?;
+            file_index.data_packets.truncate(cut);
+            file_index.compact_entries();
+            file_index.file_size = offset;
+            file_index.file_hash =
+                self.calculate_file_hash(&path)?;
+            debug!(
+                "已截断数据文件: {path:?}，保留 {} 个数据包",
+                file_index.packet_count
+            );
+            report.files_truncated += 1;
+            files.push(file_index);
+        }
+        if report.is_unchanged() {
+            return Ok(report);
+        }
+
+        index.data_files.files = files;
+        index.update_time_range();
+        self.build_lookup(&mut index);
+        index.update_total_packets();
+        self.set_index(Some(index));
+        self.save_index_to_file(&pidx_path)?;
+        info!(
+            "数据集已截断: {}, 删除文件 {}，截断文件 {}，丢弃 {} 个数据包",
+            self.dataset_name,
+            report.files_removed,
+            report.files_truncated,
+            report.packets_removed
+        );
+        Ok(report)
+    }
+
+    /// 当前索引是否为实时索引
+    ///
+    /// 实时索引在写入器仍在写入时构建，只存在于内存中，
+    /// 其中正在写入的文件只包含已完整写入的数据包。
+    pub fn is_live(&self) -> bool {
+        self.live
+    }
+
+    /// 在写入器活动期间构建实时索引
+    ///
+    /// 除正在写入的文件外，其余文件均视为已完成：优先复用索引文件中的检查点条目，
+    /// 缺失或大小不符时重新分析。正在写入的文件与快照中的大小一致时复用快照条目，
+    /// 否则只索引到最后一个完整的数据包。
+    /// 实时索引不会写回磁盘，以免覆盖写入器的检查点。
+    pub fn build_live_index(
+        &mut self,
+        progress: &WriterProgress,
+    ) -> PcapResult<&PidxIndex> {
+        self.require_dataset("构建实时索引")?;
+        info!(
+            "检测到活动写入器，构建实时索引: {}",
+            self.dataset_name
+        );
+        self.health.clear();
+
+        // 读取写入器的检查点（不验证哈希，不修复）
+        let checkpoint = self
+            .find_pidx_file()?
+            .and_then(|path| self.load_index(path).ok());
+
+        let mut index = PidxIndex::new(Some(format!(
+            "数据集: {}",
+            self.dataset_name
+        )));
+
+        let pcap_files = self.scan_pcap_files()?;
+
+        // 未发布进度时，按文件名排序的最后一个文件即为正在写入的文件
+        let active_file =
+            progress.active_file.clone().or_else(|| {
+                pcap_files
+                    .last()
+                    .and_then(|path| path.file_name())
+                    .and_then(|name| name.to_str())
+                    .map(|name| name.to_string())
+            });
+
+        for file_path in pcap_files {
+            let file_name = file_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("unknown")
+                .to_string();
+            let is_active = active_file.as_deref()
+                == Some(file_name.as_str());
+
+            // 正在写入的文件大小与快照一致时说明快照之后没有新数据，同样可以复用
+            let file_size = fs::metadata(&file_path)
+                .map(|m| m.len())
+                .unwrap_or(0);
+            let cached =
+                checkpoint.as_ref().and_then(|c| {
+                    c.data_files.files.iter().find(|f| {
+                        f.file_name == file_name
+                            && f.file_size == file_size
+                    })
+                });
+            if let Some(file_index) = cached {
+                index
+                    .data_files
+                    .files
+                    .push(file_index.clone());
+                continue;
+            }
+
+            match self
+                .index_pcap_file(&file_path, is_active)
+            {
+                Ok(file_index) => {
+                    index.data_files.files.push(file_index)
+                }
+                Err(e) => {
+                    warn!(
+                        "分析PCAP文件失败: {file_path:?}, 错误: {e}"
+                    );
+                }
+            }
+        }
+
+        index.update_time_range();
+        self.build_lookup(&mut index);
+        index.update_total_packets();
+        if let Some(subset) = &self.subset {
+            subset.restrict(&mut index);
+        }
+
+        info!(
+            "实时索引构建完成 - 文件数: {}, 总数据包: {}",
+            index.data_files.files.len(),
+            index.total_packets
+        );
+
+        self.set_index(Some(index));
+        self.live = true;
+        self.index.as_deref().ok_or_else(|| {
+            PcapError::InvalidState(
+                "实时索引未正确初始化".to_string(),
+            )
+        })
+    }
+
+    /// 将已完成的文件追加到内存索引
+    ///
+    /// 由写入器在切换文件时调用，配合 `save_checkpoint` 使读取器无需重新分析已完成的文件。
+    pub fn append_completed_file<P: AsRef<Path>>(
+        &mut self,
+        file_path: P,
+    ) -> PcapResult<()> {
+        let path = file_path.as_ref();
+        let file_index = match self.recorded_file(path) {
+            Some(file_index) => file_index.clone(),
+            None => self.index_pcap_file(path, false)?,
+        };
+
+        self.ensure_checkpoint_index()?;
+        self.update_index(|index| {
+            Self::replace_file_entry(index, file_index);
+        });
+        Ok(())
+    }
+
+    /// 准备写入器维护的内存索引
+    fn ensure_checkpoint_index(
+        &mut self,
+    ) -> PcapResult<()> {
+        if self.index.is_none() {
+            // 沿用已有的索引文件（例如向已有数据集追加写入）
+            let existing =
+                self.find_pidx_file()?.and_then(|path| {
+                    self.load_index(path).ok()
+                });
+            let mut index = existing.unwrap_or_else(|| {
+                PidxIndex::new(Some(format!(
+                    "数据集: {}",
+                    self.dataset_name
+                )))
+            });
+            index
+                .set_memory_limit(self.max_memory.as_u64());
+            self.set_index(Some(index));
+        }
+        Ok(())
+    }
+
+    /// 保存写入期间的时间点索引快照
+    ///
+    /// 已完成的文件按 `append_completed_file` 并入内存索引；正在写入的文件必须已经刷新，
+    /// 使用写入器记录的条目（未记录时重新分析到最后一个完整的数据包），只写入快照，
+    /// 不并入内存索引。快照原子地替换索引文件，读取器打开后可以读取快照之前写入的全部数据包。
+    ///
+    /// # 返回
+    /// 快照覆盖的数据包总数
+    pub fn save_snapshot<P: AsRef<Path>>(
+        &mut self,
+        completed_files: &[P],
+        active_file: Option<&Path>,
+    ) -> PcapResult<u64> {
+        self.ensure_checkpoint_index()?;
+        for path in completed_files {
+            self.append_completed_file(path)?;
+        }
+        self.update_index(PidxIndex::build_timestamp_index);
+
+        // 没有正在写入的文件时直接保存内存索引，不复制索引
+        let mut snapshot =
+            self.index_snapshot().ok_or_else(|| {
+                PcapError::InvalidState(
+                    "索引未初始化".to_string(),
+                )
+            })?;
+        if let Some(path) = active_file {
+            let file_index =
+                self.active_file_index(path)?;
+            // 空文件没有时间范围，快照中不记录
+            if file_index.packet_count > 0 {
+                let snapshot = Arc::make_mut(&mut snapshot);
+                Self::replace_file_entry(
+                    snapshot, file_index,
+                );
+                snapshot.build_timestamp_index();
+            }
+        }
+
+        let pidx_file_path = self.get_pidx_file_path();
+        self.write_index_file(&snapshot, &pidx_file_path)?;
+        debug!(
+            "已保存索引快照: {pidx_file_path:?}, {} 个数据包",
+            snapshot.total_packets
+        );
+        Ok(snapshot.total_packets)
+    }
+
+    /// 正在写入的文件截至当前的索引条目
+    fn active_file_index(
+        &self,
+        file_path: &Path,
+    ) -> PcapResult<PcapFileIndex> {
+        match &self.recording {
+            Some((path, recorded)) if path == file_path => {
+                let mut file_index = recorded.clone();
+                file_index.file_size = fs::metadata(path)
+                    .map_err(PcapError::Io)?
+                    .len();
+                file_index.file_hash =
+                    self.calculate_file_hash(path)?;
+                Ok(file_index)
+            }
+            _ => self.index_pcap_file(file_path, true),
+        }
+    }
+
+    /// 用新的文件条目替换索引中的同名文件，并更新时间范围和数据包总数
+    fn replace_file_entry(
+        index: &mut PidxIndex,
+        file_index: PcapFileIndex,
+    ) {
+        index.data_files.files.retain(|f| {
+            f.file_name != file_index.file_name
+        });
+        index.data_files.files.push(file_index);
+        index
+            .data_files
+            .files
+            .sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        index.update_time_range();
+        index.update_total_packets();
+    }
+
+    /// 开始记录写入器新建文件的索引
+    ///
+    /// 写入器按批调用 `merge_recorded_entries` 合并索引条目，
+    /// 文件写完后调用 `finish_recorded_file`，之后生成索引时不再重新分析该文件。
+    pub(crate) fn begin_recorded_file<P: AsRef<Path>>(
+        &mut self,
+        file_path: P,
+    ) {
+        let path = file_path.as_ref().to_path_buf();
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let shard = path
+            .parent()
+            .filter(|parent| *parent != self.dataset_path)
+            .map(|parent| parent.to_path_buf());
+        let file_index = PcapFileIndex {
+            file_name,
+            file_hash: String::new(),
+            file_size: 0,
+            packet_count: 0,
+            start_timestamp: u64::MAX,
+            end_timestamp: 0,
+            shard,
+            data_packets: Vec::new(),
+        };
+        self.recording = Some((path, file_index));
+    }
+
+    /// 将一批索引条目合并到正在记录的文件，合并后清空 `entries` 并保留其容量
+    pub(crate) fn merge_recorded_entries(
+        &mut self,
+        entries: &mut Vec<PacketIndexEntry>,
+    ) {
+        let Some((_, file_index)) = self.recording.as_mut()
+        else {
+            entries.clear();
+            return;
+        };
+
+        for entry in entries.iter() {
+            file_index.start_timestamp = file_index
+                .start_timestamp
+                .min(entry.timestamp_ns);
+            file_index.end_timestamp = file_index
+                .end_timestamp
+                .max(entry.timestamp_ns);
+        }
+        file_index.packet_count += entries.len() as u64;
+        // 按批次预留空间，避免逐条追加时反复扩容
+        file_index.data_packets.reserve(entries.len());
+        file_index.data_packets.append(entries);
+    }
+
+    /// 完成正在记录的文件，计算文件大小和哈希
+    ///
+    /// 文件必须已经刷新到磁盘。失败时丢弃记录，生成索引时重新分析该文件。
+    pub(crate) fn finish_recorded_file(
+        &mut self,
+    ) -> PcapResult<()> {
+        let Some((path, mut file_index)) =
+            self.recording.take()
+        else {
+            return Ok(());
+        };
+
+        file_index.file_size = fs::metadata(&path)
+            .map_err(PcapError::Io)?
+            .len();
+        file_index.file_hash =
+            self.calculate_file_hash(&path)?;
+        debug!(
+            "已记录文件索引: {path:?}, {} 个数据包",
+            file_index.packet_count
+        );
+        self.recorded.insert(path, file_index);
+        Ok(())
+    }
+
+    /// 写入器已记录且与磁盘上文件大小一致的文件索引
+    fn recorded_file(
+        &self,
+        file_path: &Path,
+    ) -> Option<&PcapFileIndex> {
+        self.recorded.get(file_path).filter(|file_index| {
+            Self::matches_file_size(file_index, file_path)
+        })
+    }
+
+    fn matches_file_size(
+        file_index: &PcapFileIndex,
+        file_path: &Path,
+    ) -> bool {
+        fs::metadata(file_path)
+            .map(|metadata| {
+                metadata.len() == file_index.file_size
+            })
+            .unwrap_or(false)
+    }
+
+    /// 将当前内存索引保存为检查点
+    pub fn save_checkpoint(&mut self) -> PcapResult<()> {
+        self.update_index(PidxIndex::build_timestamp_index);
+
+        let pidx_file_path = self.get_pidx_file_path();
+        self.save_index_to_file(&pidx_file_path)?;
+        debug!("已保存索引检查点: {pidx_file_path:?}");
+        Ok(())
+    }
+
+    /// 获取当前索引的引用
+    pub fn get_index(&self) -> Option<&PidxIndex> {
+        self.index.as_deref()
+    }
+
+    /// 修改当前索引
+    ///
+    /// 修改后按文件列表重新计算时间范围和数据包总数，并重新构建查找表；
+    /// 修改只作用于内存中的索引，需要调用 `save_index` 或 `save_index_as` 写回文件。
+    ///
+    /// # 错误
+    /// 索引尚未加载时返回 `PcapError::InvalidState`
+    pub fn transform_index<R>(
+        &mut self,
+        transform: impl FnOnce(&mut PidxIndex) -> R,
+    ) -> PcapResult<R> {
+        let build_lookup = self.lookup_builder();
+        self.update_index(|index| {
+            let result = transform(index);
+            index.update_time_range();
+            index.update_total_packets();
+            build_lookup(index);
+            result
+        })
+        .ok_or_else(|| {
+            PcapError::InvalidState(
+                "索引未加载".to_string(),
+            )
+        })
+    }
+
+    /// 把当前索引写回索引文件，返回写入的路径
+    ///
+    /// 独立索引模式下写回打开的PIDX文件，否则写入生成索引时使用的位置。
+    pub fn save_index(&self) -> PcapResult<PathBuf> {
+        let pidx_path = self.get_pidx_file_path();
+        self.save_index_as(&pidx_path)?;
+        Ok(pidx_path)
+    }
+
+    /// 把当前索引写入指定文件
+    ///
+    /// 与生成的索引一样原子地写入并在末尾附加摘要。
+    ///
+    /// # 错误
+    /// 索引尚未加载时返回 `PcapError::InvalidState`
+    pub fn save_index_as<P: AsRef<Path>>(
+        &self,
+        pidx_path: P,
+    ) -> PcapResult<()> {
+        let index =
+            self.index.as_deref().ok_or_else(|| {
+                PcapError::InvalidState(
+                    "索引未加载".to_string(),
+                )
+            })?;
+        self.write_index_file(
+            index,
+            &pidx_path.as_ref().to_path_buf(),
+        )?;
+        debug!("已保存索引: {:?}", pidx_path.as_ref());
+        Ok(())
+    }
 
-/// PIDX索引管理器
-///
-/// 提供索引的完整生命周期管理，包括：
-/// - 自动加载和验证现有索引
-/// - 检测数据变化并重建索引
-/// - 生成和保存新索引
-///
-/// 这是一个统一的入口，封装了用户的典型使用流程：
-/// 加载索引 -> 验证有效性 -> 重新生成（如果需要）
-pub struct IndexManager {
-    /// 数据集目录路径
-    dataset_path: PathBuf,
-    /// 数据集名称
-    dataset_name: String,
-    /// 当前索引
-    index: Option<PidxIndex>,
-}
+    /// 获取当前索引的快照
+    ///
+    /// 快照与索引管理器共享同一份索引，不复制条目；持有快照期间读取不需要加锁，
+    /// 索引管理器之后的修改写入新的副本，不影响已取得的快照。
+    pub fn index_snapshot(&self) -> Option<Arc<PidxIndex>> {
+        self.index.clone()
+    }
 
-impl IndexManager {
-    /// 创建新的索引管理器
+    /// 获取可跨线程共享的索引句柄
     ///
-    /// # 参数
-    /// - `base_path` - 基础目录路径
-    /// - `dataset_name` - 数据集名称
+    /// 句柄始终指向索引管理器最新发布的索引，适合读取游标和后台任务在其他线程中
+    /// 读取索引；重建或追加文件后通过句柄取得的快照即为新的索引。
+    pub fn shared_index(&self) -> SharedIndex {
+        self.shared.clone()
+    }
+
+    /// 设置内存索引的字节上限，0表示不限制
     ///
-    /// # 返回
-    /// 返回初始化后的管理器实例
-    pub fn new<P: AsRef<Path>>(
-        base_path: P,
-        dataset_name: &str,
-    ) -> PcapResult<Self> {
-        let path = base_path.as_ref().join(dataset_name);
+    /// 超过上限的索引使用稀疏模式，见 `PidxIndex::set_memory_limit`。已加载的索引立即按新的上限重新构建。
+    pub fn set_max_memory(&mut self, limit: ByteSize) {
+        self.max_memory = limit;
+        self.update_index(|index| {
+            index.set_memory_limit(limit.as_u64());
+            index.build_timestamp_index();
+        });
+    }
 
-        if !path.exists() {
-            return Err(PcapError::DirectoryNotFound(
-                format!("数据集目录不存在: {path:?}"),
-            ));
-        }
+    /// 估算当前内存索引占用的字节数，索引尚未加载时为 0
+    pub fn memory_usage(&self) -> IndexMemoryUsage {
+        self.index
+            .as_deref()
+            .map(PidxIndex::memory_usage)
+            .unwrap_or_default()
+    }
 
-        if !path.is_dir() {
-            return Err(PcapError::InvalidArgument(
-                format!("指定路径不是目录: {path:?}"),
-            ));
-        }
+    /// 设置扫描数据文件时跳过的文件名模式
+    ///
+    /// 文件名匹配任一模式（支持 `*` 和 `?` 通配符）的数据文件不参与索引生成和验证，
+    /// 读取时不可见。修改后需要重新调用 `ensure_index` 生效。
+    pub fn set_skip_files(
+        &mut self,
+        patterns: Vec<String>,
+    ) {
+        self.skip_patterns = patterns;
+    }
 
-        Ok(Self {
-            dataset_path: path,
-            dataset_name: dataset_name.to_string(),
-            index: None,
-        })
+    /// 扫描数据文件时跳过的文件名模式
+    pub fn skip_files(&self) -> &[String] {
+        &self.skip_patterns
     }
 
-    /// 确保索引可用
+    /// 设置数据集子集
     ///
-    /// 这是主要的入口方法，实现了完整的索引管理流程：
-    /// 1. 尝试加载现有索引
-    /// 2. 验证索引有效性
-    /// 3. 如果无效则重新生成
-    /// 4. 返回可用的索引
-    pub fn ensure_index(
+    /// 设置后 `ensure_index` 和 `build_live_index` 在加载或生成完整索引后把内存索引限制为子集，
+    /// 全局序号、数据包总数和时间范围只反映子集；保存的索引文件仍包含全部数据文件。
+    /// 修改后需要重新调用 `ensure_index` 生效。
+    pub fn set_subset(
         &mut self,
-    ) -> PcapResult<&PidxIndex> {
-        info!("正在检查数据集索引: {}", self.dataset_name);
+        subset: Option<DatasetSubset>,
+    ) {
+        self.subset = subset;
+    }
 
-        // 1. 尝试加载现有索引
-        if let Some(pidx_path) = self.find_pidx_file()? {
-            info!("找到索引文件: {pidx_path:?}");
+    /// 当前的数据集子集
+    pub fn subset(&self) -> Option<&DatasetSubset> {
+        self.subset.as_ref()
+    }
 
-            // 验证格式并加载
-            if self.validate_pidx_format(&pidx_path)? {
-                match self.load_index(&pidx_path) {
-                    Ok(index) => {
-                        // 验证索引有效性
-                        if self.is_index_valid(&index)? {
-                            info!("使用现有的有效索引文件");
-                            self.index = Some(index);
-                            return self
-                                .index
-                                .as_ref()
-                                .ok_or_else(|| {
-                                    PcapError::InvalidState(
-                                        "索引未正确初始化"
-                                            .to_string(),
-                                    )
-                                });
-                        } else {
-                            info!("索引文件无效或过时，需要重新生成");
+    /// 设置是否按起始时间戳排序数据文件
+    ///
+    /// 启用后顺序读取按文件起始时间戳而不是文件名的顺序切换文件，
+    /// 见 `PidxIndex::set_sort_files_by_time`。已加载的索引立即重新排序；
+    /// 文件顺序只影响内存索引，保存的索引文件中的条目不变。
+    pub fn set_sort_files_by_time(
+        &mut self,
+        enabled: bool,
+    ) {
+        self.sort_files_by_time = enabled;
+        self.update_index(|index| {
+            index.set_sort_files_by_time(enabled);
+            index.build_timestamp_index();
+        });
+    }
+
+    /// 设置是否使用磁盘上的时间戳表定位时间戳
+    ///
+    /// 启用后内存索引使用稀疏模式，不构建时间戳查找表；`find_packet_with_mode`
+    /// 在索引文件旁的 `.ptidx` 文件中二分查找，表不存在或已过期时重新生成。
+    /// 只读模式下未设置索引目录时只使用已有的有效表，否则回退到扫描内存索引。
+    /// 未启用 `mmap-index` 特性时总是回退到扫描内存索引。
+    pub fn set_timestamp_table(&mut self, enabled: bool) {
+        self.timestamp_table_enabled = enabled;
+        self.update_index(|index| {
+            index.set_force_sparse(enabled);
+            index.build_timestamp_index();
+        });
+    }
+
+    /// 时间戳表的保存位置，与索引文件位于同一目录
+    pub fn timestamp_table_path(&self) -> PathBuf {
+        self.get_pidx_file_path().with_extension("ptidx")
+    }
+
+    /// 打开与当前索引一致的时间戳表
+    ///
+    /// 索引尚未加载时返回 None。已有的表与当前索引不一致时重新生成，
+    /// 只读模式下未设置索引目录且没有可用的表时返回 None。
+    #[cfg(feature = "mmap-index")]
+    pub fn timestamp_table(
+        &mut self,
+    ) -> PcapResult<Option<Arc<MmapTimestampIndex>>> {
+        let Some(index) = self.index.clone() else {
+            return Ok(None);
+        };
+        if let Some(table) = &self.timestamp_table {
+            return Ok(Some(Arc::clone(table)));
+        }
+
+        let path = self.timestamp_table_path();
+        match MmapTimestampIndex::open(&path) {
+            Ok(table) if table.is_current(&index) => {
+                let table = Arc::new(table);
+                self.timestamp_table =
+                    Some(Arc::clone(&table));
+                return Ok(Some(table));
+            }
+            Ok(_) => debug!("时间戳表已过期: {path:?}"),
+            Err(error) => {
+                debug!("无法打开时间戳表 {path:?}: {error}")
+            }
+        }
+
+        if self.read_only && self.external_index.is_none() {
+            warn!(
+                "只读模式下没有可用的时间戳表，改为扫描内存索引: {path:?}"
+            );
+            return Ok(None);
+        }
+        MmapTimestampIndex::write(&index, &path)?;
+        info!(
+            "已生成时间戳表: {path:?}，包含 {} 个数据包",
+            index.timestamp_count()
+        );
+        let table =
+            Arc::new(MmapTimestampIndex::open(&path)?);
+        self.timestamp_table = Some(Arc::clone(&table));
+        Ok(Some(table))
+    }
+
+    /// 按查找模式定位时间戳
+    ///
+    /// 启用时间戳表时在表中查找，否则使用内存索引，语义见 `PidxIndex::find_packet_with_mode`。
+    /// 索引尚未加载时返回 None。
+    pub fn find_packet_with_mode(
+        &mut self,
+        timestamp_ns: u64,
+        mode: SeekMode,
+    ) -> PcapResult<Option<TimestampPointer>> {
+        #[cfg(feature = "mmap-index")]
+        if self.timestamp_table_enabled {
+            if let Some(table) = self.timestamp_table()? {
+                let index = self.index.as_deref();
+                return Ok(table
+                    .find_with_mode(timestamp_ns, mode)
+                    .and_then(|record| {
+                        index?.locate_global_index(
+                            record.global_index,
+                        )
+                    })
+                    .map(|(file_index, entry)| {
+                        TimestampPointer {
+                            file_index,
+                            entry: entry.clone(),
                         }
-                    }
-                    Err(e) => {
-                        warn!("加载索引文件失败: {e}, 将重新生成");
-                    }
-                }
-            } else {
-                warn!("索引文件格式无效，将重新生成");
+                    }));
             }
-        } else {
-            info!("未找到索引文件，将自动生成");
         }
+        Ok(self.index.as_deref().and_then(|index| {
+            index.find_packet_with_mode(timestamp_ns, mode)
+        }))
+    }
 
-        // 2. 生成新索引
-        self.generate_index()?;
-        self.index.as_ref().ok_or_else(|| {
-            PcapError::InvalidState(
-                "索引生成后未正确初始化".to_string(),
-            )
+    /// 按查找模式批量定位时间戳，结果与输入顺序一一对应
+    ///
+    /// 启用时间戳表时逐个在表中查找，否则使用内存索引单次遍历，
+    /// 见 `PidxIndex::find_packets_with_mode`。索引尚未加载时全部为 None。
+    pub fn find_packets_with_mode(
+        &mut self,
+        timestamps: &[u64],
+        mode: SeekMode,
+    ) -> PcapResult<Vec<Option<TimestampPointer>>> {
+        #[cfg(feature = "mmap-index")]
+        if self.timestamp_table_enabled {
+            return timestamps
+                .iter()
+                .map(|&timestamp_ns| {
+                    self.find_packet_with_mode(
+                        timestamp_ns,
+                        mode,
+                    )
+                })
+                .collect();
+        }
+        Ok(match self.index.as_deref() {
+            Some(index) => index
+                .find_packets_with_mode(timestamps, mode),
+            None => vec![None; timestamps.len()],
         })
     }
 
-    /// 强制重建索引
-    pub fn rebuild_index(&mut self) -> PcapResult<PathBuf> {
-        self.index = None;
-        self.generate_index()
+    /// 按内存上限构建索引的查找表
+    fn build_lookup(&self, index: &mut PidxIndex) {
+        self.lookup_builder()(index);
     }
 
-    /// 获取当前索引的引用
-    pub fn get_index(&self) -> Option<&PidxIndex> {
-        self.index.as_ref()
+    /// 按当前设置构建查找表的函数，不借用索引管理器
+    fn lookup_builder(&self) -> impl Fn(&mut PidxIndex) {
+        let max_memory = self.max_memory.as_u64();
+        let force_sparse = self.timestamp_table_enabled;
+        let sort_files_by_time = self.sort_files_by_time;
+        move |index| {
+            index.set_memory_limit(max_memory);
+            index.set_force_sparse(force_sparse);
+            index
+                .set_sort_files_by_time(sort_files_by_time);
+            index.build_timestamp_index();
+        }
+    }
+
+    /// 把已加载的完整索引限制为数据集子集
+    fn apply_subset(&mut self) {
+        if let Some(subset) = self.subset.clone() {
+            self.update_index(|index| {
+                subset.restrict(index)
+            });
+        }
+    }
+
+    /// 在健康报告中记录时间范围重叠的文件
+    fn report_overlaps(&mut self) {
+        self.health
+            .remove(&IndexHealthKind::TimeRangeOverlap);
+        let Some(index) = self.index.as_deref() else {
+            return;
+        };
+        for (first, second) in index.overlapping_files() {
+            let files = index.files();
+            let (a, b) = (&files[first], &files[second]);
+            debug!(
+                "数据文件时间范围重叠: {} 与 {}",
+                a.file_name, b.file_name
+            );
+            self.health.push(
+                IndexHealthKind::TimeRangeOverlap,
+                format!(
+                    "{} [{}, {}] 与 {} [{}, {}] 的时间范围重叠",
+                    a.file_name,
+                    a.start_timestamp,
+                    a.end_timestamp,
+                    b.file_name,
+                    b.start_timestamp,
+                    b.end_timestamp
+                ),
+                Some(b.resolve_path(&self.dataset_path)),
+            );
+        }
+    }
+
+    /// 替换当前索引并发布到共享句柄
+    fn set_index(&mut self, index: Option<PidxIndex>) {
+        self.index = index.map(Arc::new);
+        self.shared.publish(self.index.clone());
+        #[cfg(feature = "mmap-index")]
+        {
+            self.timestamp_table = None;
+        }
+        self.report_overlaps();
+    }
+
+    /// 原地修改当前索引并发布到共享句柄
+    ///
+    /// 修改期间持有共享句柄的写锁并暂时撤下已发布的索引，没有外部快照时不复制索引。
+    fn update_index<R>(
+        &mut self,
+        update: impl FnOnce(&mut PidxIndex) -> R,
+    ) -> Option<R> {
+        let index = self.index.as_mut()?;
+        let mut published = self.shared.write();
+        published.take();
+        let result = update(Arc::make_mut(index));
+        *published = Some(Arc::clone(index));
+        drop(published);
+        #[cfg(feature = "mmap-index")]
+        {
+            self.timestamp_table = None;
+        }
+        self.report_overlaps();
+        Some(result)
+    }
+
+    /// 获取当前索引的查询门面
+    ///
+    /// 索引尚未加载时返回 None。
+    pub fn query(&self) -> Option<IndexQuery<'_>> {
+        self.index.as_deref().map(IndexQuery::new)
     }
 
     /// 验证索引是否需要重建
     pub fn needs_rebuild(&self) -> PcapResult<bool> {
-        if let Some(index) = &self.index {
+        self.require_dataset("检查数据文件")?;
+        if let Some(index) = self.index.as_deref() {
             let current_files = self.scan_pcap_files()?;
 
             // 检查文件数量是否匹配
@@ -207,13 +1658,13 @@ impl IndexManager {
 
     /// 验证索引的有效性
     pub fn validate_index(&self) -> PcapResult<bool> {
-        if let Some(index) = &self.index {
+        self.require_dataset("验证索引")?;
+        if let Some(index) = self.index.as_deref() {
             info!("验证索引文件有效性...");
 
             for file_index in &index.data_files.files {
-                let file_path = self
-                    .dataset_path
-                    .join(&file_index.file_name);
+                let file_path = file_index
+                    .resolve_path(&self.dataset_path);
 
                 if !file_path.exists() {
                     warn!("PCAP文件不存在: {file_path:?}");
@@ -281,7 +1732,7 @@ impl IndexManager {
             index.total_duration = 0;
 
             // 保存空索引到文件
-            self.index = Some(index);
+            self.set_index(Some(index));
             let pidx_file_path = self.get_pidx_file_path();
             self.save_index_to_file(&pidx_file_path)?;
 
@@ -296,11 +1747,28 @@ impl IndexManager {
 
         let mut global_start_timestamp = u64::MAX;
         let mut global_end_timestamp = 0u64;
-        let mut timestamp_index = HashMap::new();
 
-        // 分析每个PCAP文件
+        // 分析每个PCAP文件，写入器已记录的文件直接使用记录的索引
+        let mut recorded =
+            std::mem::take(&mut self.recorded);
         for file_path in pcap_files {
-            match self.index_pcap_file(&file_path) {
+            let file_index = match recorded
+                .remove(&file_path)
+            {
+                Some(file_index)
+                    if Self::matches_file_size(
+                        &file_index,
+                        &file_path,
+                    ) =>
+                {
+                    debug!("使用写入时记录的索引: {file_path:?}");
+                    Ok(file_index)
+                }
+                _ => {
+                    self.index_pcap_file(&file_path, false)
+                }
+            };
+            match file_index {
                 Ok(file_index) => {
                     // 更新全局时间戳
                     if file_index.start_timestamp
@@ -316,29 +1784,20 @@ impl IndexManager {
                             file_index.end_timestamp;
                     }
 
-                    // 获取当前文件在索引中的位置
-                    let file_index_position =
-                        index.data_files.files.len();
-
-                    // 构建时间戳索引（带文件引用）
-                    for packet in &file_index.data_packets {
-                        let pointer = crate::business::index::types::TimestampPointer {
-                            file_index: file_index_position,
-                            entry: packet.clone(),
-                        };
-                        timestamp_index.insert(
-                            packet.timestamp_ns,
-                            pointer,
-                        );
-                    }
-
                     index.data_files.files.push(file_index);
                 }
                 Err(e) => {
                     warn!(
                         "分析PCAP文件失败: {file_path:?}, 错误: {e}"
                     );
-                    // 继续处理其他文件
+                    // 隔离该文件，继续处理其他文件
+                    self.health.push(
+                        IndexHealthKind::QuarantinedFile,
+                        format!(
+                            "数据文件无法分析，已跳过: {e}"
+                        ),
+                        Some(file_path),
+                    );
                 }
             }
         }
@@ -355,20 +1814,25 @@ impl IndexManager {
 
         // 更新统计信息
         index.update_time_range();
-        index.timestamp_index = timestamp_index;
+        // 构建时间戳索引并分配全局序号
+        self.build_lookup(&mut index);
         index.update_total_packets();
 
         // 保存索引
-        self.index = Some(index);
+        let (index_files, index_packets, index_duration) = (
+            index.data_files.files.len(),
+            index.total_packets,
+            index.total_duration,
+        );
+        self.set_index(Some(index));
         let pidx_file_path = self.get_pidx_file_path();
         self.save_index_to_file(&pidx_file_path)?;
 
         info!(
             "索引生成完成 - 文件数: {}, 总数据包: {}, 时长: {:.2}秒",
-            self.index.as_ref().unwrap().data_files.files.len(),
-            self.index.as_ref().unwrap().total_packets,
-            (self.index.as_ref().unwrap().end_timestamp
-                - self.index.as_ref().unwrap().start_timestamp) as f64
+            index_files,
+            index_packets,
+            index_duration as f64
                 / 1_000_000_000.0
         );
 
@@ -377,9 +1841,12 @@ impl IndexManager {
     }
 
     /// 为单个PCAP文件生成索引
+    ///
+    /// `partial_tail` 为 true 时（正在写入的文件），末尾不完整的数据包视为文件结束。
     fn index_pcap_file<P: AsRef<Path>>(
         &self,
         file_path: P,
+        partial_tail: bool,
     ) -> PcapResult<PcapFileIndex> {
         let path = file_path.as_ref();
         let file_name = path
@@ -402,13 +1869,25 @@ impl IndexManager {
         reader.open(path)?;
         let mut packets = Vec::new();
         let mut packet_count = 0u64;
-        let mut current_position = 16u64; // PCAP文件头后的位置
 
         let mut start_timestamp = u64::MAX;
         let mut end_timestamp = 0u64;
 
         // 读取所有数据包并记录位置
-        while let Some(packet) = reader.read_packet()? {
+        loop {
+            let packet = match reader.read_packet() {
+                Ok(Some(packet)) => packet,
+                Ok(None) => break,
+                Err(PcapError::PacketSizeExceedsRemainingBytes {
+                    ..
+                }) if partial_tail
+                    || self.salvage_truncated =>
+                {
+                    debug!("文件末尾存在未写完的数据包: {file_name}");
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
             let timestamp_ns = packet.get_timestamp_ns();
 
             // 更新时间范围
@@ -422,18 +1901,30 @@ impl IndexManager {
             // 创建索引条目
             let index_entry = PacketIndexEntry {
                 timestamp_ns,
-                byte_offset: current_position,
+                byte_offset: reader.record_offset(),
                 packet_size: packet.packet_length() as u32,
+                packet_index: packet_count,
+                // 全局序号在汇总所有文件后统一分配
+                global_index: 0,
+                payload_hash: self.payload_hash.then(
+                    || {
+                        calculate_payload_hash(
+                            &packet.packet.data,
+                        )
+                    },
+                ),
             };
 
             packets.push(index_entry);
             packet_count += 1;
-
-            // 更新当前位置（16字节包头 + 数据内容）
-            current_position +=
-                16 + packet.packet_length() as u64;
         }
 
+        // 位于分片中的文件记录其所在目录
+        let shard = path
+            .parent()
+            .filter(|parent| *parent != self.dataset_path)
+            .map(|parent| parent.to_path_buf());
+
         let file_index = PcapFileIndex {
             file_name,
             file_hash,
@@ -441,6 +1932,7 @@ impl IndexManager {
             packet_count,
             start_timestamp,
             end_timestamp,
+            shard,
             data_packets: packets,
         };
 
@@ -464,9 +1956,8 @@ impl IndexManager {
             fs::read_to_string(pidx_file_path.as_ref())
                 .map_err(PcapError::Io)?;
 
-        let mut index =
+        let index =
             self.deserialize_from_xml(&xml_content)?;
-        index.build_timestamp_index();
 
         info!(
             "PIDX索引文件已加载: {:?}",
@@ -475,12 +1966,42 @@ impl IndexManager {
         Ok(index)
     }
 
-    /// 从数据集目录查找PIDX文件
+    /// 加载磁盘上已保存的索引，不存在或无法解析时返回 `None`
+    fn load_stored_index(&self) -> Option<PidxIndex> {
+        let pidx_path = self.find_pidx_file().ok()??;
+        let xml_content =
+            fs::read_to_string(pidx_path).ok()?;
+        self.deserialize_from_xml(&xml_content).ok()
+    }
+
+    /// 读取磁盘上已保存的索引及其路径，不修改数据集
+    ///
+    /// 索引文件不存在时返回 `Ok(None)`，无法读取或解析时返回错误。
+    pub(crate) fn read_stored_index(
+        &self,
+    ) -> PcapResult<Option<(PathBuf, PidxIndex)>> {
+        let Some(pidx_path) = self.find_pidx_file()? else {
+            return Ok(None);
+        };
+        let index = self.load_index(&pidx_path)?;
+        Ok(Some((pidx_path, index)))
+    }
+
+    /// 查找PIDX文件
+    ///
+    /// 设置了索引目录时优先使用其中的索引文件，其次使用数据集目录中的索引文件。
     fn find_pidx_file(
         &self,
     ) -> PcapResult<Option<PathBuf>> {
+        if let Some(path) = self
+            .external_index
+            .as_ref()
+            .filter(|path| path.is_file())
+        {
+            return Ok(Some(path.clone()));
+        }
         let pidx_path = self.dataset_path.join(".pidx");
-        if pidx_path.exists() && pidx_path.is_file() {
+        if pidx_path.is_file() {
             Ok(Some(pidx_path))
         } else {
             Ok(None)
@@ -488,12 +2009,40 @@ impl IndexManager {
     }
 
     /// 验证索引是否有效
+    ///
+    /// 不在索引中且文件头校验失败的数据文件视为已隔离，不使索引失效，
+    /// 索引有效时记录到健康报告中。
     fn is_index_valid(
-        &self,
+        &mut self,
         index: &PidxIndex,
     ) -> PcapResult<bool> {
         // 检查是否需要重建
-        let current_files = self.scan_pcap_files()?;
+        let mut current_files = self.scan_pcap_files()?;
+        let mut quarantined = Vec::new();
+        let mut unindexed = false;
+        current_files.retain(|path| {
+            let indexed =
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| {
+                        index.data_files.files.iter().any(
+                            |file| file.file_name == name,
+                        )
+                    });
+            if indexed {
+                return true;
+            }
+            match PcapFileReader::read_header(path) {
+                Err(error) => {
+                    quarantined.push((path.clone(), error))
+                }
+                Ok(_) => unindexed = true,
+            }
+            false
+        });
+        if unindexed {
+            return Ok(false);
+        }
 
         // 检查文件数量是否匹配
         if current_files.len()
@@ -524,32 +2073,161 @@ impl IndexManager {
             }
         }
 
+        for (path, error) in quarantined {
+            warn!("数据文件无法分析，已跳过: {path:?}, 错误: {error}");
+            self.health.push(
+                IndexHealthKind::QuarantinedFile,
+                format!(
+                    "数据文件无法分析，已跳过: {error}"
+                ),
+                Some(path),
+            );
+        }
         Ok(true)
     }
 
-    /// 快速验证PIDX文件格式
-    fn validate_pidx_format<P: AsRef<Path>>(
+    /// 读取并解析PIDX文件
+    ///
+    /// 内容损坏（不是UTF-8文本、校验和不匹配或无法解析）时返回 `Ok(Err(问题描述))`，
+    /// 文件读取失败时返回错误。
+    fn parse_pidx_file<P: AsRef<Path>>(
         &self,
         pidx_file_path: P,
-    ) -> PcapResult<bool> {
-        let xml_content =
-            fs::read_to_string(pidx_file_path.as_ref())
-                .map_err(PcapError::Io)?;
+    ) -> PcapResult<Result<PidxIndex, String>> {
+        let xml_content = match fs::read_to_string(
+            pidx_file_path.as_ref(),
+        ) {
+            Ok(content) => content,
+            // 非UTF8内容视为格式损坏
+            Err(e)
+                if e.kind()
+                    == std::io::ErrorKind::InvalidData =>
+            {
+                return Ok(Err(
+                    "索引文件不是有效的UTF-8文本"
+                        .to_string(),
+                ));
+            }
+            Err(e) => return Err(PcapError::Io(e)),
+        };
 
         match self.deserialize_from_xml(&xml_content) {
-            Ok(_) => Ok(true),
-            Err(_) => Ok(false),
+            Ok(index) => Ok(Ok(index)),
+            Err(e) => {
+                warn!(
+                    "索引文件格式无效: {:?}, {e}",
+                    pidx_file_path.as_ref()
+                );
+                Ok(Err(e.to_string()))
+            }
+        }
+    }
+
+    /// 处理损坏的索引文件
+    ///
+    /// 自动修复开启时将损坏文件重命名隔离并记录到健康报告，
+    /// 否则返回错误。
+    fn handle_corrupted_index(
+        &mut self,
+        pidx_file_path: &Path,
+        problem: &str,
+    ) -> PcapResult<()> {
+        if !self.auto_repair {
+            return Err(PcapError::InvalidFormat(format!(
+                "索引文件损坏且未启用自动修复: {pidx_file_path:?}, {problem}"
+            )));
+        }
+
+        // 数据集目录中的索引损坏但索引保存在索引目录时，不改动数据集目录
+        if pidx_file_path != self.get_pidx_file_path() {
+            warn!("索引文件格式无效，将在索引目录中重新生成: {pidx_file_path:?}");
+            self.health.push(
+                IndexHealthKind::CorruptedIndex,
+                format!(
+                    "索引文件无法解析，已在索引目录中重新生成: {problem}"
+                ),
+                Some(pidx_file_path.to_path_buf()),
+            );
+            return Ok(());
         }
+
+        let quarantine_path = pidx_file_path
+            .with_file_name(format!(
+                ".pidx.corrupt-{}",
+                chrono::Utc::now().format("%Y%m%d%H%M%S%f")
+            ));
+        fs::rename(pidx_file_path, &quarantine_path)
+            .map_err(PcapError::Io)?;
+
+        warn!(
+            "索引文件格式无效，已隔离至 {quarantine_path:?}，将重新生成"
+        );
+        self.health.push(
+            IndexHealthKind::CorruptedIndex,
+            format!(
+                "索引文件无法解析，已隔离并重新生成: {problem}"
+            ),
+            Some(quarantine_path),
+        );
+        Ok(())
     }
 
     // =================================================================
     // 私有方法 - 工具函数
     // =================================================================
 
-    /// 扫描目录中的PCAP文件
-    fn scan_pcap_files(&self) -> PcapResult<Vec<PathBuf>> {
+    /// 扫描数据集目录及各存储分片中的PCAP文件
+    pub(crate) fn scan_pcap_files(
+        &self,
+    ) -> PcapResult<Vec<PathBuf>> {
         let mut pcap_files = Vec::new();
-        let entries = fs::read_dir(&self.dataset_path)
+        Self::scan_directory(
+            &self.dataset_path,
+            &mut pcap_files,
+        )?;
+
+        for shard_dir in &self.shard_dirs {
+            if *shard_dir == self.dataset_path {
+                continue;
+            }
+            if !shard_dir.is_dir() {
+                warn!("存储分片目录不可用: {shard_dir:?}");
+                continue;
+            }
+            Self::scan_directory(
+                shard_dir,
+                &mut pcap_files,
+            )?;
+        }
+
+        // 跳过匹配排除模式的文件
+        if !self.skip_patterns.is_empty() {
+            pcap_files.retain(|path| {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_default();
+                !self.skip_patterns.iter().any(|pattern| {
+                    paths::matches_file_pattern(
+                        &name, pattern,
+                    )
+                })
+            });
+        }
+
+        // 按文件名排序（文件名包含创建时间，跨分片保持时间顺序）
+        pcap_files.sort_by(|a, b| {
+            a.file_name().cmp(&b.file_name())
+        });
+        Ok(pcap_files)
+    }
+
+    /// 收集单个目录中的PCAP文件
+    fn scan_directory(
+        directory: &Path,
+        pcap_files: &mut Vec<PathBuf>,
+    ) -> PcapResult<()> {
+        let entries = fs::read_dir(directory)
             .map_err(PcapError::Io)?;
 
         for entry in entries {
@@ -564,10 +2242,7 @@ impl IndexManager {
                 }
             }
         }
-
-        // 按文件名排序
-        pcap_files.sort();
-        Ok(pcap_files)
+        Ok(())
     }
 
     /// 计算文件的SHA256哈希值
@@ -607,10 +2282,16 @@ impl IndexManager {
     }
 
     /// 从XML格式反序列化索引
+    ///
+    /// 先校验文件末尾记录的摘要，不匹配时返回 `PcapError::InvalidFormat`；
+    /// 再按索引结构校验，出错时返回带位置信息的 `PcapError::InvalidIndexSchema`
     fn deserialize_from_xml(
         &self,
         xml_content: &str,
     ) -> PcapResult<PidxIndex> {
+        let xml_content = verify_checksum(xml_content)?;
+        validate_schema(xml_content)
+            .map_err(PcapError::InvalidIndexSchema)?;
         let mut index: PidxIndex = serde_xml_rs::from_str(
             xml_content,
         )
@@ -619,7 +2300,7 @@ impl IndexManager {
                 "XML反序列化失败: {e}"
             ))
         })?;
-        index.build_timestamp_index();
+        self.build_lookup(&mut index);
         Ok(index)
     }
 
@@ -642,17 +2323,142 @@ impl IndexManager {
         &self,
         pidx_file_path: &PathBuf,
     ) -> PcapResult<()> {
-        if let Some(index) = &self.index {
-            let xml_content =
-                self.serialize_to_xml(index)?;
-            fs::write(pidx_file_path, xml_content)
-                .map_err(PcapError::Io)?;
+        match self.index.as_deref() {
+            Some(index) => {
+                self.write_index_file(index, pidx_file_path)
+            }
+            None => Ok(()),
         }
-        Ok(())
+    }
+
+    /// 将索引原子地写入指定文件
+    fn write_index_file(
+        &self,
+        index: &PidxIndex,
+        pidx_file_path: &PathBuf,
+    ) -> PcapResult<()> {
+        if self.read_only
+            && pidx_file_path
+                .starts_with(&self.dataset_path)
+        {
+            return Err(PcapError::InvalidState(format!(
+                "只读模式下不能写入索引文件: {pidx_file_path:?}"
+            )));
+        }
+        if let Some(parent) = pidx_file_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                index_write_error(pidx_file_path, e)
+            })?;
+        }
+        let xml_content =
+            append_checksum(if self.sort_files_by_time {
+                // 按起始时间戳排序只用于内存索引，保存时恢复文件名顺序
+                let mut saved = index.clone();
+                saved.data_files.files.sort_by(|a, b| {
+                    a.file_name.cmp(&b.file_name)
+                });
+                self.serialize_to_xml(&saved)?
+            } else {
+                self.serialize_to_xml(index)?
+            });
+        // 先写临时文件再重命名，避免并发读取到写了一半的索引
+        let mut temp_path =
+            pidx_file_path.clone().into_os_string();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, xml_content)
+            .and_then(|_| {
+                fs::rename(&temp_path, pidx_file_path)
+            })
+            .map_err(|e| {
+                index_write_error(pidx_file_path, e)
+            })
     }
 
     /// 获取PIDX文件路径
     fn get_pidx_file_path(&self) -> PathBuf {
-        self.dataset_path.join(".pidx")
+        self.external_index.clone().unwrap_or_else(|| {
+            self.dataset_path.join(".pidx")
+        })
+    }
+}
+
+/// 索引文件末尾摘要注释的前缀
+const CHECKSUM_PREFIX: &str = "\n<!-- pidx-sha256:";
+
+/// 索引文件末尾摘要注释的后缀
+const CHECKSUM_SUFFIX: &str = " -->\n";
+
+/// 在索引内容末尾追加其 SHA-256 摘要注释
+fn append_checksum(xml_content: String) -> String {
+    let digest = hex_digest(&xml_content);
+    format!("{xml_content}{CHECKSUM_PREFIX}{digest}{CHECKSUM_SUFFIX}")
+}
+
+/// 校验索引内容末尾的摘要，返回摘要之前的内容
+///
+/// 没有摘要注释的旧版索引原样返回；摘要不匹配时返回 `PcapError::InvalidFormat`。
+fn verify_checksum(xml_content: &str) -> PcapResult<&str> {
+    let Some(position) = xml_content.rfind(CHECKSUM_PREFIX)
+    else {
+        return Ok(xml_content);
+    };
+    let (body, trailer) = xml_content.split_at(position);
+    let expected = trailer[CHECKSUM_PREFIX.len()..]
+        .trim_end()
+        .trim_end_matches("-->")
+        .trim();
+    let actual = hex_digest(body);
+    if expected != actual {
+        return Err(PcapError::InvalidFormat(format!(
+            "索引文件校验和不匹配（记录 {expected}，实际 {actual}），内容已损坏"
+        )));
+    }
+    Ok(body)
+}
+
+fn hex_digest(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// 索引目录中数据集索引文件的名称：`<数据集名称>-<路径哈希>.pidx`
+///
+/// 哈希取规范化后的数据集路径，不同位置的同名数据集互不冲突。
+fn external_index_file_name(
+    dataset_path: &Path,
+    dataset_name: &str,
+) -> String {
+    let canonical = fs::canonicalize(dataset_path)
+        .unwrap_or_else(|_| dataset_path.to_path_buf());
+    let digest = Sha256::digest(
+        canonical.to_string_lossy().as_bytes(),
+    );
+    let key: String = digest[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("{dataset_name}-{key}.pidx")
+}
+
+/// 把写入索引文件时的 IO 错误转换为说明原因的错误
+///
+/// 数据集位于只读位置时，提示改用只读模式打开。
+fn index_write_error(
+    pidx_file_path: &Path,
+    error: std::io::Error,
+) -> PcapError {
+    match error.kind() {
+        std::io::ErrorKind::PermissionDenied
+        | std::io::ErrorKind::ReadOnlyFilesystem => {
+            PcapError::Io(std::io::Error::new(
+                error.kind(),
+                format!(
+                    "无法写入索引文件 {pidx_file_path:?}: {error}；只读位置上的数据集请使用 PcapReader::open_read_only 打开"
+                ),
+            ))
+        }
+        _ => PcapError::Io(error),
     }
 }
@@ -0,0 +1,350 @@
+//! 内存映射时间戳表模块
+//!
+//! 把 (时间戳, 文件, 偏移) 有序表写入磁盘，读取时通过内存映射二分查找。
+//! 数亿数据包的数据集定位时间戳时不需要在内存中构建时间戳查找表，
+//! 映射的页面由操作系统按需加载和回收。
+//!
+//! 文件格式（小端序）：
+//!
+//! - 文件头 32 字节：魔数 `PTIDX\0\0\0`、版本 u32、条目大小 u32、条目数量 u64、索引指纹 u64
+//! - 条目 32 字节：时间戳 u64、文件内偏移 u64、全局序号 u64、文件索引 u32、负载大小 u32，
+//!   按 (时间戳, 文件索引, 文件内偏移) 排序
+
+use memmap2::Mmap;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::business::index::types::{PidxIndex, SeekMode};
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 文件魔数
+const MAGIC: [u8; 8] = *b"PTIDX\0\0\0";
+
+/// 文件格式版本
+const VERSION: u32 = 1;
+
+/// 文件头大小
+const HEADER_SIZE: usize = 32;
+
+/// 条目大小
+const ENTRY_SIZE: usize = 32;
+
+/// 时间戳表中的一条记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampRecord {
+    /// 时间戳（纳秒）
+    pub timestamp_ns: u64,
+    /// 数据包在文件中的字节偏移
+    pub byte_offset: u64,
+    /// 数据包在数据集中的全局序号
+    pub global_index: u64,
+    /// 文件在索引中的序号
+    pub file_index: u32,
+    /// 负载大小
+    pub packet_size: u32,
+}
+
+impl TimestampRecord {
+    fn encode(&self) -> [u8; ENTRY_SIZE] {
+        let mut bytes = [0u8; ENTRY_SIZE];
+        bytes[0..8].copy_from_slice(
+            &self.timestamp_ns.to_le_bytes(),
+        );
+        bytes[8..16].copy_from_slice(
+            &self.byte_offset.to_le_bytes(),
+        );
+        bytes[16..24].copy_from_slice(
+            &self.global_index.to_le_bytes(),
+        );
+        bytes[24..28].copy_from_slice(
+            &self.file_index.to_le_bytes(),
+        );
+        bytes[28..32].copy_from_slice(
+            &self.packet_size.to_le_bytes(),
+        );
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        Self {
+            timestamp_ns: read_u64(bytes, 0),
+            byte_offset: read_u64(bytes, 8),
+            global_index: read_u64(bytes, 16),
+            file_index: read_u32(bytes, 24),
+            packet_size: read_u32(bytes, 28),
+        }
+    }
+}
+
+/// 内存映射的时间戳表
+///
+/// 由 `write` 根据索引生成，`open` 打开后只读。表中记录生成时索引的指纹，
+/// 数据文件增删或重写后通过 `is_current` 判断是否需要重新生成。
+#[derive(Debug)]
+pub struct MmapTimestampIndex {
+    path: PathBuf,
+    map: Mmap,
+    len: usize,
+    fingerprint: u64,
+}
+
+impl MmapTimestampIndex {
+    /// 根据索引生成时间戳表
+    ///
+    /// 先写入临时文件再重命名，读取者不会看到写了一半的表。
+    /// 索引需要已调用 `build_timestamp_index` 编号全局序号。
+    pub fn write<P: AsRef<Path>>(
+        index: &PidxIndex,
+        path: P,
+    ) -> PcapResult<()> {
+        let path = path.as_ref();
+        let mut records: Vec<TimestampRecord> = index
+            .files()
+            .iter()
+            .enumerate()
+            .flat_map(|(file_idx, file)| {
+                file.data_packets.iter().map(move |entry| {
+                    TimestampRecord {
+                        timestamp_ns: entry.timestamp_ns,
+                        byte_offset: entry.byte_offset,
+                        global_index: entry.global_index,
+                        file_index: file_idx as u32,
+                        packet_size: entry.packet_size,
+                    }
+                })
+            })
+            .collect();
+        records.sort_unstable_by_key(|record| {
+            (
+                record.timestamp_ns,
+                record.file_index,
+                record.byte_offset,
+            )
+        });
+
+        let temp_path = path.with_extension("ptidx.tmp");
+        {
+            let mut writer =
+                BufWriter::new(File::create(&temp_path)?);
+            writer.write_all(&MAGIC)?;
+            writer.write_all(&VERSION.to_le_bytes())?;
+            writer.write_all(
+                &(ENTRY_SIZE as u32).to_le_bytes(),
+            )?;
+            writer.write_all(
+                &(records.len() as u64).to_le_bytes(),
+            )?;
+            writer.write_all(
+                &index_fingerprint(index).to_le_bytes(),
+            )?;
+            for record in &records {
+                writer.write_all(&record.encode())?;
+            }
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+        }
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// 打开时间戳表并校验文件头
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+    ) -> PcapResult<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        // SAFETY: 表文件只由 `write` 通过重命名整体替换，不会被原地修改；
+        // 映射期间其他进程截断文件属于外部破坏，与读取普通文件的风险相同。
+        let map = unsafe { Mmap::map(&file)? };
+        let invalid = |message: &str| {
+            PcapError::InvalidFormat(format!(
+                "时间戳表 {path:?} {message}"
+            ))
+        };
+        if map.len() < HEADER_SIZE || map[0..8] != MAGIC {
+            return Err(invalid("不是有效的时间戳表"));
+        }
+        let version = read_u32(&map, 8);
+        if version != VERSION {
+            return Err(invalid(&format!(
+                "版本 {version} 不受支持"
+            )));
+        }
+        if read_u32(&map, 12) as usize != ENTRY_SIZE {
+            return Err(invalid("条目大小不匹配"));
+        }
+        let len = usize::try_from(read_u64(&map, 16))
+            .map_err(|_| invalid("条目数量超出范围"))?;
+        let expected = len
+            .checked_mul(ENTRY_SIZE)
+            .and_then(|size| size.checked_add(HEADER_SIZE));
+        if expected != Some(map.len()) {
+            return Err(invalid(
+                "文件大小与条目数量不一致",
+            ));
+        }
+        let fingerprint = read_u64(&map, 24);
+        Ok(Self {
+            path: path.to_path_buf(),
+            map,
+            len,
+            fingerprint,
+        })
+    }
+
+    /// 表文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 条目数量
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 是否没有条目
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 表是否由当前索引生成
+    ///
+    /// 比较条目数量和数据文件列表（名称、大小、数据包数量）的指纹。
+    pub fn is_current(&self, index: &PidxIndex) -> bool {
+        self.len as u64 == index.timestamp_count() as u64
+            && self.fingerprint == index_fingerprint(index)
+    }
+
+    /// 按位置读取记录
+    pub fn get(
+        &self,
+        position: usize,
+    ) -> Option<TimestampRecord> {
+        (position < self.len)
+            .then(|| self.record_at(position))
+    }
+
+    /// 按查找模式定位时间戳
+    ///
+    /// 与 `PidxIndex::find_packet_with_mode` 的语义相同，
+    /// 目标时间戳有多个数据包时返回写入顺序最早的一个。
+    pub fn find_with_mode(
+        &self,
+        timestamp_ns: u64,
+        mode: SeekMode,
+    ) -> Option<TimestampRecord> {
+        let pos = self.lower_bound(timestamp_ns);
+        let ceiling = self.get(pos);
+        let floor = if ceiling.is_some_and(|record| {
+            record.timestamp_ns == timestamp_ns
+        }) {
+            ceiling
+        } else {
+            // 取前一个时间戳的首条记录
+            pos.checked_sub(1).map(|prev| {
+                let timestamp = self.timestamp_at(prev);
+                self.record_at(self.lower_bound(timestamp))
+            })
+        };
+
+        match mode {
+            SeekMode::Exact => ceiling.filter(|record| {
+                record.timestamp_ns == timestamp_ns
+            }),
+            SeekMode::Floor => floor,
+            SeekMode::Ceiling => ceiling,
+            SeekMode::Nearest => match (floor, ceiling) {
+                (Some(f), Some(c)) => {
+                    if timestamp_ns - f.timestamp_ns
+                        <= c.timestamp_ns - timestamp_ns
+                    {
+                        Some(f)
+                    } else {
+                        Some(c)
+                    }
+                }
+                (f, c) => f.or(c),
+            },
+        }
+    }
+
+    /// 时间戳在 `[start_ns, end_ns]` 内的记录，按表中顺序返回
+    pub fn range(
+        &self,
+        start_ns: u64,
+        end_ns: u64,
+    ) -> impl Iterator<Item = TimestampRecord> + '_ {
+        let lower = self.lower_bound(start_ns);
+        let upper = if start_ns > end_ns {
+            lower
+        } else {
+            end_ns.checked_add(1).map_or(self.len, |next| {
+                self.lower_bound(next)
+            })
+        };
+        (lower..upper)
+            .map(|position| self.record_at(position))
+    }
+
+    /// 第一个时间戳不小于 `timestamp_ns` 的位置
+    fn lower_bound(&self, timestamp_ns: u64) -> usize {
+        let (mut low, mut high) = (0, self.len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.timestamp_at(mid) < timestamp_ns {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    fn timestamp_at(&self, position: usize) -> u64 {
+        read_u64(
+            &self.map,
+            HEADER_SIZE + position * ENTRY_SIZE,
+        )
+    }
+
+    fn record_at(
+        &self,
+        position: usize,
+    ) -> TimestampRecord {
+        let start = HEADER_SIZE + position * ENTRY_SIZE;
+        TimestampRecord::decode(
+            &self.map[start..start + ENTRY_SIZE],
+        )
+    }
+}
+
+/// 数据文件列表的指纹，取 SHA-256 摘要的前 8 字节
+fn index_fingerprint(index: &PidxIndex) -> u64 {
+    let mut hasher = Sha256::new();
+    for file in index.files() {
+        hasher.update(file.file_name.as_bytes());
+        hasher.update([0]);
+        hasher.update(file.file_size.to_le_bytes());
+        hasher.update(
+            (file.data_packets.len() as u64).to_le_bytes(),
+        );
+    }
+    let digest = hasher.finalize();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[offset..offset + 8]);
+    u64::from_le_bytes(buf)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(buf)
+}
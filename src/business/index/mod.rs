@@ -2,13 +2,44 @@
 //!
 //! 提供PCAP文件的索引生成、读取和管理功能，支持快速时间戳查找和范围查询。
 
+pub mod health;
 pub mod manager;
+#[cfg(feature = "mmap-index")]
+pub mod mmap;
+pub mod query;
+pub mod schema;
+pub mod shared;
+pub mod subset;
 pub mod types;
 
 // 重新导出主要类型 - 统一使用IndexManager
 pub use manager::IndexManager;
 
+// 重新导出索引查询门面
+pub use query::{IndexQuery, PayloadDiff};
+
+// 重新导出健康报告类型
+pub use health::{
+    IndexHealth, IndexHealthEntry, IndexHealthKind,
+    IndexRebuildReason,
+};
+
+// 重新导出内存映射时间戳表
+#[cfg(feature = "mmap-index")]
+pub use mmap::{MmapTimestampIndex, TimestampRecord};
+
+// 重新导出跨线程共享的索引句柄
+pub use shared::SharedIndex;
+
+// 重新导出数据集子集
+pub use subset::{DatasetSubset, FileFilter, TimeWindow};
+
+// 重新导出索引结构校验
+pub use schema::{validate_schema, IndexSchemaError};
+
 // 重新导出数据结构
 pub use types::{
-    PacketIndexEntry, PcapFileIndex, PidxIndex,
+    BackfillReport, CompactionReport, IndexMemoryUsage,
+    PacketIndexEntry, PacketLocation, PcapFileIndex,
+    PidxIndex, SeekMode, TruncationReport,
 };
@@ -0,0 +1,309 @@
+//! 索引查询模块
+//!
+//! `IndexQuery` 是对已加载索引的只读查询门面，提供按文件、按全局序号和按时间戳的
+//! 常用查询，调用方无需直接遍历 `data_files.files` 等内部结构，
+//! 索引的内部表示可以在不影响调用方的情况下调整。
+
+use std::collections::HashMap;
+
+use super::types::{
+    PacketIndexEntry, PcapFileIndex, PidxIndex, SeekMode,
+};
+
+/// 两个数据集按负载内容哈希比较的结果
+///
+/// 按多重集合比较：同一负载在一侧出现的次数多于另一侧时，多出的数据包计入差异。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PayloadDiff {
+    /// 只存在于当前数据集的数据包全局序号
+    pub only_in_self: Vec<u64>,
+    /// 只存在于另一数据集的数据包全局序号
+    pub only_in_other: Vec<u64>,
+}
+
+impl PayloadDiff {
+    /// 两个数据集的负载内容是否相同
+    pub fn is_identical(&self) -> bool {
+        self.only_in_self.is_empty()
+            && self.only_in_other.is_empty()
+    }
+}
+
+/// 索引查询门面
+///
+/// 通过 `IndexManager::query()` 获取，借用当前加载的索引。
+#[derive(Debug, Clone, Copy)]
+pub struct IndexQuery<'a> {
+    index: &'a PidxIndex,
+}
+
+impl<'a> IndexQuery<'a> {
+    /// 基于已构建时间戳索引的 `PidxIndex` 创建查询
+    pub fn new(index: &'a PidxIndex) -> Self {
+        Self { index }
+    }
+
+    /// 数据文件数量
+    pub fn file_count(&self) -> usize {
+        self.index.data_files.files.len()
+    }
+
+    /// 数据包总数
+    pub fn total_packets(&self) -> u64 {
+        self.index.total_packets
+    }
+
+    /// 索引中是否没有数据包
+    pub fn is_empty(&self) -> bool {
+        self.index.total_packets == 0
+    }
+
+    /// 数据集的时间范围（纳秒），没有数据包时返回 None
+    pub fn time_range(&self) -> Option<(u64, u64)> {
+        if self.is_empty() {
+            return None;
+        }
+        Some((
+            self.index.start_timestamp,
+            self.index.end_timestamp,
+        ))
+    }
+
+    /// 获取指定文件的索引条目
+    pub fn file(
+        &self,
+        file_index: usize,
+    ) -> Option<&'a PcapFileIndex> {
+        self.index.data_files.files.get(file_index)
+    }
+
+    /// 获取指定文件的文件名
+    pub fn file_name(
+        &self,
+        file_index: usize,
+    ) -> Option<&'a str> {
+        self.file(file_index)
+            .map(|file| file.file_name.as_str())
+    }
+
+    /// 按文件名查找文件序号
+    pub fn file_position(
+        &self,
+        file_name: &str,
+    ) -> Option<usize> {
+        self.index
+            .data_files
+            .files
+            .iter()
+            .position(|file| file.file_name == file_name)
+    }
+
+    /// 指定文件中的数据包数量
+    pub fn packet_count_in_file(
+        &self,
+        file_index: usize,
+    ) -> Option<u64> {
+        self.file(file_index)
+            .map(|file| file.data_packets.len() as u64)
+    }
+
+    /// 每个文件的数据包数量，按文件顺序排列
+    pub fn packet_counts_per_file(&self) -> Vec<u64> {
+        self.index
+            .data_files
+            .files
+            .iter()
+            .map(|file| file.data_packets.len() as u64)
+            .collect()
+    }
+
+    /// 指定文件首个数据包的全局序号
+    pub fn file_start_index(
+        &self,
+        file_index: usize,
+    ) -> Option<u64> {
+        self.index.file_start_index(file_index)
+    }
+
+    /// 获取全局序号对应的数据包条目
+    pub fn nth_packet(
+        &self,
+        global_index: u64,
+    ) -> Option<&'a PacketIndexEntry> {
+        self.index
+            .locate_global_index(global_index)
+            .map(|(_, entry)| entry)
+    }
+
+    /// 获取指定文件内第 `n` 个数据包条目
+    pub fn nth_packet_in_file(
+        &self,
+        file_index: usize,
+        n: u64,
+    ) -> Option<&'a PacketIndexEntry> {
+        self.file(file_index).and_then(|file| {
+            file.data_packets.get(n as usize)
+        })
+    }
+
+    /// 全局序号对应数据包所在的文件序号
+    pub fn file_index_of(
+        &self,
+        global_index: u64,
+    ) -> Option<usize> {
+        self.index
+            .locate_global_index(global_index)
+            .map(|(file_index, _)| file_index)
+    }
+
+    /// 全局序号对应数据包的时间戳（纳秒）
+    pub fn timestamp_at(
+        &self,
+        global_index: u64,
+    ) -> Option<u64> {
+        self.nth_packet(global_index)
+            .map(|entry| entry.timestamp_ns)
+    }
+
+    /// 按查找模式定位时间戳，返回数据包的全局序号
+    pub fn find_global_index(
+        &self,
+        timestamp_ns: u64,
+        mode: SeekMode,
+    ) -> Option<u64> {
+        self.index
+            .find_packet_with_mode(timestamp_ns, mode)
+            .map(|pointer| pointer.entry.global_index)
+    }
+
+    /// 时间戳等于 `timestamp_ns` 的全部数据包的全局序号，按写入顺序排列
+    pub fn global_indices_at(
+        &self,
+        timestamp_ns: u64,
+    ) -> Vec<u64> {
+        self.index
+            .find_packets_by_timestamp(timestamp_ns)
+            .into_iter()
+            .map(|pointer| pointer.entry.global_index)
+            .collect()
+    }
+
+    /// 时间戳不早于 `timestamp_ns` 的第一个数据包的全局序号
+    pub fn first_at_or_after(
+        &self,
+        timestamp_ns: u64,
+    ) -> Option<u64> {
+        self.find_global_index(
+            timestamp_ns,
+            SeekMode::Ceiling,
+        )
+    }
+
+    /// 时间戳不晚于 `timestamp_ns` 的最后一个数据包的全局序号
+    pub fn last_at_or_before(
+        &self,
+        timestamp_ns: u64,
+    ) -> Option<u64> {
+        self.find_global_index(
+            timestamp_ns,
+            SeekMode::Floor,
+        )
+    }
+
+    /// 时间戳落在 `[start_ns, end_ns]` 内的不同时间戳数量
+    ///
+    /// 基于有序时间戳二分查找，不需要遍历数据包。
+    pub fn count_in_range(
+        &self,
+        start_ns: u64,
+        end_ns: u64,
+    ) -> usize {
+        if start_ns > end_ns {
+            return 0;
+        }
+        let timestamps = self.index.sorted_timestamps();
+        let lower =
+            timestamps.partition_point(|&t| t < start_ns);
+        let upper =
+            timestamps.partition_point(|&t| t <= end_ns);
+        upper - lower
+    }
+
+    /// 索引是否记录了负载内容哈希
+    pub fn has_payload_hashes(&self) -> bool {
+        self.index.has_payload_hashes()
+    }
+
+    /// 按负载内容哈希分组的重复数据包
+    ///
+    /// 每组包含负载相同的两个及以上数据包的全局序号，组内和组间都按全局序号升序排列。
+    /// 没有记录负载哈希的数据包不参与比较。
+    pub fn duplicate_payloads(&self) -> Vec<Vec<u64>> {
+        let mut groups: HashMap<u64, Vec<u64>> =
+            HashMap::new();
+        for (hash, global_index) in self.payload_hashes() {
+            groups
+                .entry(hash)
+                .or_default()
+                .push(global_index);
+        }
+        let mut duplicates: Vec<Vec<u64>> = groups
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .collect();
+        duplicates.sort_unstable_by_key(|group| group[0]);
+        duplicates
+    }
+
+    /// 按负载内容哈希与另一数据集的索引比较，不需要读取负载
+    ///
+    /// 没有记录负载哈希的数据包不参与比较。
+    pub fn diff_payloads(
+        &self,
+        other: &IndexQuery<'_>,
+    ) -> PayloadDiff {
+        let mut remaining: HashMap<u64, Vec<u64>> =
+            HashMap::new();
+        for (hash, global_index) in other.payload_hashes() {
+            remaining
+                .entry(hash)
+                .or_default()
+                .push(global_index);
+        }
+        // 从后往前取出匹配项，剩余的序号保持升序
+        for indices in remaining.values_mut() {
+            indices.reverse();
+        }
+
+        let mut diff = PayloadDiff::default();
+        for (hash, global_index) in self.payload_hashes() {
+            let matched = remaining
+                .get_mut(&hash)
+                .and_then(|indices| indices.pop());
+            if matched.is_none() {
+                diff.only_in_self.push(global_index);
+            }
+        }
+        diff.only_in_other =
+            remaining.into_values().flatten().collect();
+        diff.only_in_other.sort_unstable();
+        diff
+    }
+
+    /// 按全局序号遍历记录了负载哈希的数据包
+    fn payload_hashes(
+        &self,
+    ) -> impl Iterator<Item = (u64, u64)> + 'a {
+        self.index.data_files.files.iter().flat_map(
+            |file| {
+                file.data_packets.iter().filter_map(
+                    |entry| {
+                        entry.payload_hash.map(|hash| {
+                            (hash, entry.global_index)
+                        })
+                    },
+                )
+            },
+        )
+    }
+}
@@ -0,0 +1,399 @@
+//! 索引结构校验模块
+//!
+//! 在反序列化之前按 PIDX 索引结构逐个检查 XML 元素和属性，
+//! 出错时报告所在的行号、列号和元素路径，便于定位外部生成的索引中的问题。
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use xml::common::{Position, TextPosition};
+use xml::reader::{EventReader, XmlEvent};
+
+/// 索引结构校验错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexSchemaError {
+    /// 出错位置的行号，从 1 开始
+    pub line: u64,
+    /// 出错位置的列号，从 1 开始
+    pub column: u64,
+    /// 出错元素的路径，如 `index/data_files/file`，XML 语法错误时可能为空
+    pub element: Option<String>,
+    /// 错误描述
+    pub message: String,
+}
+
+impl IndexSchemaError {
+    fn new(
+        position: TextPosition,
+        element: Option<String>,
+        message: String,
+    ) -> Self {
+        Self {
+            line: position.row + 1,
+            column: position.column + 1,
+            element,
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for IndexSchemaError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "第 {} 行第 {} 列",
+            self.line, self.column
+        )?;
+        if let Some(element) = &self.element {
+            write!(f, " <{element}>")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for IndexSchemaError {}
+
+/// 属性值类型
+#[derive(Clone, Copy)]
+enum ValueKind {
+    Text,
+    U32,
+    U64,
+}
+
+impl ValueKind {
+    fn check(self, value: &str) -> Result<(), String> {
+        let valid = match self {
+            ValueKind::Text => true,
+            ValueKind::U32 => u32::from_str(value).is_ok(),
+            ValueKind::U64 => u64::from_str(value).is_ok(),
+        };
+        if valid {
+            Ok(())
+        } else {
+            let expected = match self {
+                ValueKind::Text => "文本",
+                ValueKind::U32 => "u32 整数",
+                ValueKind::U64 => "u64 整数",
+            };
+            Err(format!(
+                "应为{expected}，实际为 \"{value}\""
+            ))
+        }
+    }
+}
+
+/// 元素的结构定义
+struct ElementRule {
+    /// 属性名称、类型以及是否必需
+    attributes:
+        &'static [(&'static str, ValueKind, bool)],
+    /// 允许的子元素及其是否必需且只能出现一次
+    children: &'static [(&'static str, bool)],
+    /// 文本内容类型，`None` 表示不允许文本内容
+    text: Option<ValueKind>,
+}
+
+const INDEX_RULE: ElementRule = ElementRule {
+    attributes: &[(
+        "schema_version",
+        ValueKind::U32,
+        false,
+    )],
+    children: &[
+        ("description", true),
+        ("created_time", true),
+        ("start_timestamp", true),
+        ("end_timestamp", true),
+        ("total_packets", true),
+        ("total_duration", true),
+        ("data_files", true),
+    ],
+    text: None,
+};
+
+const DATA_FILES_RULE: ElementRule = ElementRule {
+    attributes: &[],
+    children: &[("file", false)],
+    text: None,
+};
+
+const FILE_RULE: ElementRule = ElementRule {
+    attributes: &[
+        ("name", ValueKind::Text, true),
+        // 早期版本的索引可能缺少以下文件元数据，见 `IndexManager::backfill_file_metadata`
+        ("hash", ValueKind::Text, false),
+        ("size", ValueKind::U64, false),
+        ("packet_count", ValueKind::U64, false),
+        ("start_timestamp", ValueKind::U64, false),
+        ("end_timestamp", ValueKind::U64, false),
+        ("shard", ValueKind::Text, false),
+    ],
+    children: &[("packet", false)],
+    text: None,
+};
+
+const PACKET_RULE: ElementRule = ElementRule {
+    attributes: &[
+        ("timestamp_ns", ValueKind::U64, true),
+        ("byte_offset", ValueKind::U64, true),
+        ("packet_size", ValueKind::U32, true),
+        ("index", ValueKind::U64, false),
+        ("global_index", ValueKind::U64, false),
+        ("payload_hash", ValueKind::U64, false),
+    ],
+    children: &[],
+    text: None,
+};
+
+const TEXT_RULE: ElementRule = ElementRule {
+    attributes: &[],
+    children: &[],
+    text: Some(ValueKind::Text),
+};
+
+const U64_RULE: ElementRule = ElementRule {
+    attributes: &[],
+    children: &[],
+    text: Some(ValueKind::U64),
+};
+
+/// 查找子元素的结构定义
+fn child_rule(
+    parent: Option<&str>,
+    name: &str,
+) -> Option<&'static ElementRule> {
+    match (parent, name) {
+        (None, "index") => Some(&INDEX_RULE),
+        (Some("index"), "description" | "created_time") => {
+            Some(&TEXT_RULE)
+        }
+        (
+            Some("index"),
+            "start_timestamp" | "end_timestamp"
+            | "total_packets" | "total_duration",
+        ) => Some(&U64_RULE),
+        (Some("index"), "data_files") => {
+            Some(&DATA_FILES_RULE)
+        }
+        (Some("data_files"), "file") => Some(&FILE_RULE),
+        (Some("file"), "packet") => Some(&PACKET_RULE),
+        _ => None,
+    }
+}
+
+/// 正在检查的元素
+struct OpenElement {
+    name: String,
+    rule: &'static ElementRule,
+    /// 已出现的子元素名称
+    seen: HashSet<String>,
+    /// 累积的文本内容
+    text: String,
+    /// 元素开始位置
+    position: TextPosition,
+}
+
+/// 按 PIDX 索引结构校验 XML 内容
+///
+/// 检查根元素、必需的子元素和属性、属性和文本的数值类型，
+/// 未知的元素或属性同样视为错误。
+///
+/// # 错误
+/// 返回第一个发现的问题及其位置
+pub fn validate_schema(
+    xml_content: &str,
+) -> Result<(), IndexSchemaError> {
+    let mut reader =
+        EventReader::new(xml_content.as_bytes());
+    let mut stack: Vec<OpenElement> = Vec::new();
+    let mut root_seen = false;
+
+    let path = |stack: &[OpenElement],
+                name: Option<&str>| {
+        let mut parts: Vec<&str> =
+            stack.iter().map(|e| e.name.as_str()).collect();
+        parts.extend(name);
+        parts.join("/")
+    };
+
+    loop {
+        let event = reader.next().map_err(|e| {
+            let element = (!stack.is_empty())
+                .then(|| path(&stack, None));
+            IndexSchemaError::new(
+                e.position(),
+                element,
+                format!("XML 语法错误: {}", e.msg()),
+            )
+        })?;
+        // 事件的起始位置
+        let position = reader.position();
+
+        match event {
+            XmlEvent::StartElement {
+                name,
+                attributes,
+                ..
+            } => {
+                let name = name.local_name;
+                let element_path =
+                    path(&stack, Some(&name));
+                let fail = |message: String| {
+                    Err(IndexSchemaError::new(
+                        position,
+                        Some(element_path.clone()),
+                        message,
+                    ))
+                };
+
+                let parent = stack.last_mut();
+                if parent.is_none() && root_seen {
+                    return fail(
+                        "根元素之后存在多余的元素"
+                            .to_string(),
+                    );
+                }
+                let rule = match child_rule(
+                    parent
+                        .as_ref()
+                        .map(|p| p.name.as_str()),
+                    &name,
+                ) {
+                    Some(rule) => rule,
+                    None => {
+                        return fail(match parent {
+                            Some(_) => {
+                                "不允许的元素".to_string()
+                            }
+                            None => "根元素应为 <index>"
+                                .to_string(),
+                        })
+                    }
+                };
+                if let Some(parent) = parent {
+                    let once =
+                        parent.rule.children.iter().any(
+                            |(child, once)| {
+                                *once && *child == name
+                            },
+                        );
+                    if !parent.seen.insert(name.clone())
+                        && once
+                    {
+                        return fail(
+                            "元素重复出现".to_string(),
+                        );
+                    }
+                }
+                root_seen = true;
+
+                let mut present = HashSet::new();
+                for attribute in &attributes {
+                    let key =
+                        attribute.name.local_name.as_str();
+                    let Some((_, kind, _)) = rule
+                        .attributes
+                        .iter()
+                        .find(|(attr, _, _)| *attr == key)
+                    else {
+                        return fail(format!(
+                            "不允许的属性 \"{key}\""
+                        ));
+                    };
+                    if let Err(message) =
+                        kind.check(&attribute.value)
+                    {
+                        return fail(format!(
+                            "属性 \"{key}\" {message}"
+                        ));
+                    }
+                    present.insert(key);
+                }
+                if let Some((missing, _, _)) = rule
+                    .attributes
+                    .iter()
+                    .find(|(attr, _, required)| {
+                        *required && !present.contains(attr)
+                    })
+                {
+                    return fail(format!(
+                        "缺少必需的属性 \"{missing}\""
+                    ));
+                }
+
+                stack.push(OpenElement {
+                    name,
+                    rule,
+                    seen: HashSet::new(),
+                    text: String::new(),
+                    position,
+                });
+            }
+            XmlEvent::EndElement { .. } => {
+                let element_path = path(&stack, None);
+                let Some(element) = stack.pop() else {
+                    continue;
+                };
+                if let Some(kind) = element.rule.text {
+                    kind.check(element.text.trim())
+                        .map_err(|message| {
+                            IndexSchemaError::new(
+                                element.position,
+                                Some(element_path.clone()),
+                                format!(
+                                    "文本内容{message}"
+                                ),
+                            )
+                        })?;
+                }
+                if let Some((missing, _)) =
+                    element.rule.children.iter().find(
+                        |(child, required)| {
+                            *required
+                                && !element
+                                    .seen
+                                    .contains(*child)
+                        },
+                    )
+                {
+                    return Err(IndexSchemaError::new(
+                        position,
+                        Some(element_path),
+                        format!(
+                            "缺少必需的子元素 <{missing}>"
+                        ),
+                    ));
+                }
+            }
+            XmlEvent::Characters(text)
+            | XmlEvent::CData(text) => {
+                let Some(element) = stack.last_mut() else {
+                    continue;
+                };
+                if element.rule.text.is_none() {
+                    return Err(IndexSchemaError::new(
+                        position,
+                        Some(path(&stack, None)),
+                        "不允许文本内容".to_string(),
+                    ));
+                }
+                element.text.push_str(&text);
+            }
+            XmlEvent::EndDocument => break,
+            _ => {}
+        }
+    }
+
+    if !root_seen {
+        return Err(IndexSchemaError::new(
+            reader.position(),
+            None,
+            "缺少根元素 <index>".to_string(),
+        ));
+    }
+    Ok(())
+}
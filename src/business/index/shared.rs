@@ -0,0 +1,56 @@
+//! 共享索引模块
+//!
+//! 索引管理器把当前索引发布到 `SharedIndex`，读取游标和后台任务持有句柄的克隆，
+//! 在其他线程中取得索引快照。快照是 `Arc<PidxIndex>`，取得后读取不需要加锁，
+//! 也不会复制包含大量条目的索引结构。
+
+use std::sync::{
+    Arc, PoisonError, RwLock, RwLockWriteGuard,
+};
+
+use crate::business::index::types::PidxIndex;
+
+/// 已发布的索引
+type Published = Option<Arc<PidxIndex>>;
+
+/// 可跨线程共享的索引句柄
+///
+/// 由 `IndexManager::shared_index` 创建，克隆的句柄指向同一个索引。
+/// 索引管理器重建或修改索引时发布新的索引，句柄之后取得的快照即为新的索引，
+/// 已取得的快照保持不变。
+#[derive(Debug, Clone, Default)]
+pub struct SharedIndex {
+    published: Arc<RwLock<Published>>,
+}
+
+impl SharedIndex {
+    /// 获取最新发布的索引快照，索引尚未加载时返回 `None`
+    pub fn snapshot(&self) -> Option<Arc<PidxIndex>> {
+        self.published
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// 是否已发布索引
+    pub fn is_loaded(&self) -> bool {
+        self.published
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    /// 发布新的索引
+    pub(crate) fn publish(&self, index: Published) {
+        *self.write() = index;
+    }
+
+    /// 获取写锁，用于原地修改已发布的索引
+    pub(crate) fn write(
+        &self,
+    ) -> RwLockWriteGuard<'_, Published> {
+        self.published
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
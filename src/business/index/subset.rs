@@ -0,0 +1,171 @@
+//! 数据集子集模块
+//!
+//! 按时间窗口或文件名筛选数据集，构建只包含所选部分的受限索引视图。
+//! 视图中的全局序号、数据包总数和读取进度只反映所选部分，
+//! 适用于只关心长时间记录中某一段的场景。
+
+use crate::business::index::types::PidxIndex;
+use crate::foundation::paths;
+
+/// 时间窗口，包含两端的时间戳（纳秒）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeWindow {
+    /// 起始时间戳（纳秒）
+    pub start_ns: u64,
+    /// 结束时间戳（纳秒）
+    pub end_ns: u64,
+}
+
+impl TimeWindow {
+    /// 创建时间窗口
+    pub fn new(start_ns: u64, end_ns: u64) -> Self {
+        Self { start_ns, end_ns }
+    }
+
+    /// 时间戳是否位于窗口内
+    pub fn contains(&self, timestamp_ns: u64) -> bool {
+        (self.start_ns..=self.end_ns)
+            .contains(&timestamp_ns)
+    }
+}
+
+/// 文件筛选条件，按文件名模式选择数据文件
+///
+/// 文件名匹配任一模式（支持 `*` 和 `?` 通配符）的数据文件包含在子集中，
+/// 不含通配符的模式即为精确的文件名。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileFilter {
+    /// 文件名模式
+    pub patterns: Vec<String>,
+}
+
+impl FileFilter {
+    /// 创建文件筛选条件
+    pub fn new<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            patterns: patterns
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+
+    /// 文件名是否被选中
+    pub fn matches(&self, file_name: &str) -> bool {
+        self.patterns.iter().any(|pattern| {
+            paths::matches_file_pattern(file_name, pattern)
+        })
+    }
+}
+
+/// 数据集子集
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatasetSubset {
+    /// 时间窗口内的数据包
+    TimeWindow(TimeWindow),
+    /// 选中文件中的数据包
+    Files(FileFilter),
+}
+
+impl From<TimeWindow> for DatasetSubset {
+    fn from(window: TimeWindow) -> Self {
+        DatasetSubset::TimeWindow(window)
+    }
+}
+
+impl From<FileFilter> for DatasetSubset {
+    fn from(filter: FileFilter) -> Self {
+        DatasetSubset::Files(filter)
+    }
+}
+
+impl DatasetSubset {
+    /// 验证子集条件
+    ///
+    /// 时间窗口的起始时间戳大于结束时间戳、文件筛选条件为空或包含空模式时返回错误信息。
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            DatasetSubset::TimeWindow(window) => {
+                if window.start_ns > window.end_ns {
+                    return Err(format!(
+                        "时间窗口起始时间戳 {} 大于结束时间戳 {}",
+                        window.start_ns, window.end_ns
+                    ));
+                }
+            }
+            DatasetSubset::Files(filter) => {
+                if filter.patterns.is_empty()
+                    || filter
+                        .patterns
+                        .iter()
+                        .any(|pattern| pattern.is_empty())
+                {
+                    return Err(
+                        "文件名模式不能为空".to_string()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 把索引限制为子集并重新编号
+    ///
+    /// 时间窗口保留每个文件中第一个到最后一个位于窗口内的数据包（按文件内顺序），
+    /// 文件内时间戳回退时其间窗口外的数据包也保留，使顺序读取与索引一致。
+    /// 没有剩余数据包的文件从索引中移除。
+    pub(crate) fn restrict(&self, index: &mut PidxIndex) {
+        match self {
+            DatasetSubset::TimeWindow(window) => {
+                index.data_files.files.retain_mut(|file| {
+                    let packets = &file.data_packets;
+                    let Some(first) =
+                        packets.iter().position(|entry| {
+                            window.contains(
+                                entry.timestamp_ns,
+                            )
+                        })
+                    else {
+                        return false;
+                    };
+                    let last = packets
+                        .iter()
+                        .rposition(|entry| {
+                            window.contains(
+                                entry.timestamp_ns,
+                            )
+                        })
+                        .unwrap_or(first);
+                    file.data_packets.truncate(last + 1);
+                    file.data_packets.drain(..first);
+                    file.packet_count =
+                        file.data_packets.len() as u64;
+                    let timestamps = file
+                        .data_packets
+                        .iter()
+                        .map(|entry| entry.timestamp_ns);
+                    file.start_timestamp = timestamps
+                        .clone()
+                        .min()
+                        .unwrap_or_default();
+                    file.end_timestamp = timestamps
+                        .max()
+                        .unwrap_or_default();
+                    true
+                });
+            }
+            DatasetSubset::Files(filter) => {
+                index.data_files.files.retain(|file| {
+                    filter.matches(&file.file_name)
+                });
+            }
+        }
+        index.update_time_range();
+        index.build_timestamp_index();
+        index.update_total_packets();
+    }
+}
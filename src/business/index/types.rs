@@ -1,4 +1,8 @@
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::mem::size_of;
+use std::path::{Path, PathBuf};
 
 // 索引相关结构体和实现，从 structures.rs 移动而来
 use serde::{Deserialize, Serialize};
@@ -12,6 +16,36 @@ pub struct PacketIndexEntry {
     pub byte_offset: u64,
     #[serde(rename = "@packet_size")]
     pub packet_size: u32,
+    /// 数据包在所属文件内的序号（从0开始）
+    #[serde(rename = "@index", default)]
+    pub packet_index: u64,
+    /// 数据包在整个数据集中的全局序号（从0开始）
+    #[serde(rename = "@global_index", default)]
+    pub global_index: u64,
+    /// 负载内容哈希，见 `calculate_payload_hash`；写入时未启用
+    /// `WriterConfig::index_payload_hash` 则为空
+    #[serde(
+        rename = "@payload_hash",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub payload_hash: Option<u64>,
+}
+
+/// 时间戳查找模式
+///
+/// 控制按时间戳定位数据包时，目标时间戳不存在精确匹配的处理方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeekMode {
+    /// 仅精确匹配
+    Exact,
+    /// 小于等于目标的最大时间戳
+    Floor,
+    /// 大于等于目标的最小时间戳
+    #[default]
+    Ceiling,
+    /// 距离目标最近的时间戳（距离相同时取较早者）
+    Nearest,
 }
 
 /// 时间戳指针结构（仅用于内存索引，不参与序列化）
@@ -23,28 +57,216 @@ pub struct TimestampPointer {
     pub entry: PacketIndexEntry,
 }
 
+impl TimestampPointer {
+    /// 写入顺序键：(文件索引, 文件内字节偏移)
+    ///
+    /// 时间戳相同的数据包按该键排序即为写入顺序。
+    #[inline]
+    pub fn write_order(&self) -> (usize, u64) {
+        (self.file_index, self.entry.byte_offset)
+    }
+}
+
+/// 按时间戳解析出的数据包位置
+///
+/// 由 `PcapReader::resolve_timestamps` 返回，可交给
+/// `PcapReader::read_packets_at_locations` 按文件分组批量读取。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PacketLocation {
+    /// 请求的目标时间戳（纳秒）
+    pub requested_timestamp_ns: u64,
+    /// 定位到的数据包时间戳（纳秒）
+    pub timestamp_ns: u64,
+    /// 数据包在整个数据集中的全局序号
+    pub global_index: u64,
+    /// 文件在索引文件列表中的序号
+    pub file_index: usize,
+    /// 数据文件名称
+    pub file_name: String,
+    /// 数据包在文件中的字节偏移
+    pub byte_offset: u64,
+    /// 负载大小（字节）
+    pub packet_size: u32,
+}
+
+/// 数据文件的索引
+///
+/// 早期版本生成的索引可能缺少文件哈希、大小、数据包数量或时间范围，
+/// 加载后缺少的字段为空字符串或 0，可以通过 `IndexManager::backfill_file_metadata` 补全。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename = "file")]
 pub struct PcapFileIndex {
     #[serde(rename = "@name")]
     pub file_name: String,
-    #[serde(rename = "@hash")]
+    #[serde(rename = "@hash", default)]
     pub file_hash: String,
-    #[serde(rename = "@size")]
+    #[serde(rename = "@size", default)]
     pub file_size: u64,
-    #[serde(rename = "@packet_count")]
+    #[serde(rename = "@packet_count", default)]
     pub packet_count: u64,
-    #[serde(rename = "@start_timestamp")]
+    #[serde(rename = "@start_timestamp", default)]
     pub start_timestamp: u64,
-    #[serde(rename = "@end_timestamp")]
+    #[serde(rename = "@end_timestamp", default)]
     pub end_timestamp: u64,
+    /// 文件所在的分片数据集目录，位于主数据集目录时为空
+    #[serde(
+        rename = "@shard",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub shard: Option<PathBuf>,
     #[serde(rename = "packet", default)]
     pub data_packets: Vec<PacketIndexEntry>,
 }
 
+impl PcapFileIndex {
+    /// 解析文件的完整路径
+    ///
+    /// 记录了分片目录时位于分片中，否则位于主数据集目录。
+    pub fn resolve_path(
+        &self,
+        dataset_path: &Path,
+    ) -> PathBuf {
+        self.shard
+            .as_deref()
+            .unwrap_or(dataset_path)
+            .join(&self.file_name)
+    }
+
+    /// 按文件偏移排序数据包条目并移除重复条目
+    ///
+    /// 同一偏移只可能存在一个数据包，偏移和时间戳都相同的条目视为重复记录；
+    /// 同时按剩余条目更新数据包数量和时间范围。
+    /// 返回移除的条目数量以及条目原本是否未排序。
+    pub(crate) fn compact_entries(
+        &mut self,
+    ) -> (usize, bool) {
+        let unsorted =
+            self.data_packets.windows(2).any(|pair| {
+                pair[0].byte_offset > pair[1].byte_offset
+            });
+        if unsorted {
+            self.data_packets
+                .sort_by_key(|entry| entry.byte_offset);
+        }
+        let before = self.data_packets.len();
+        self.data_packets.dedup_by(|a, b| {
+            a.byte_offset == b.byte_offset
+                && a.timestamp_ns == b.timestamp_ns
+        });
+        let removed = before - self.data_packets.len();
+
+        self.packet_count = self.data_packets.len() as u64;
+        if let (Some(start), Some(end)) = (
+            self.data_packets
+                .iter()
+                .map(|entry| entry.timestamp_ns)
+                .min(),
+            self.data_packets
+                .iter()
+                .map(|entry| entry.timestamp_ns)
+                .max(),
+        ) {
+            self.start_timestamp = start;
+            self.end_timestamp = end;
+        }
+        (removed, unsorted)
+    }
+}
+
+/// 索引压缩结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompactionReport {
+    /// 移除的数据文件已不存在的文件条目数量
+    pub orphan_files_removed: usize,
+    /// 移除的重复文件条目数量
+    pub duplicate_files_removed: usize,
+    /// 移除的重复数据包条目数量
+    pub duplicate_entries_removed: usize,
+    /// 数据包条目未按文件偏移排序而重新排序的文件数量
+    pub files_resorted: usize,
+    /// 压缩前的索引文件大小（字节）
+    pub bytes_before: u64,
+    /// 压缩后的索引文件大小（字节）
+    pub bytes_after: u64,
+}
+
+impl CompactionReport {
+    /// 索引中是否没有需要清理的内容
+    pub fn is_clean(&self) -> bool {
+        self.orphan_files_removed == 0
+            && self.duplicate_files_removed == 0
+            && self.duplicate_entries_removed == 0
+            && self.files_resorted == 0
+    }
+}
+
+/// 文件元数据补全结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackfillReport {
+    /// 补全文件哈希的文件数量
+    pub hashes_filled: usize,
+    /// 补全文件大小的文件数量
+    pub sizes_filled: usize,
+    /// 由数据包条目补全数据包数量的文件数量
+    pub packet_counts_filled: usize,
+    /// 由数据包条目补全时间范围的文件数量
+    pub time_ranges_filled: usize,
+    /// 没有数据包条目、需要重新分析的文件数量
+    pub files_rescanned: usize,
+    /// 数据文件已不存在而跳过的文件数量
+    pub missing_files: usize,
+    /// 索引是否从旧的结构版本升级
+    pub schema_upgraded: bool,
+}
+
+impl BackfillReport {
+    /// 索引是否已完整，无需补全任何内容
+    pub fn is_clean(&self) -> bool {
+        self.hashes_filled == 0
+            && self.sizes_filled == 0
+            && self.packet_counts_filled == 0
+            && self.time_ranges_filled == 0
+            && self.files_rescanned == 0
+            && !self.schema_upgraded
+    }
+}
+
+/// 数据集截断结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TruncationReport {
+    /// 截断时间戳（纳秒），不晚于该时间戳的数据包被保留
+    pub cutoff_ns: u64,
+    /// 整个删除的数据文件数量
+    pub files_removed: usize,
+    /// 在数据包边界截断的数据文件数量
+    pub files_truncated: usize,
+    /// 丢弃的数据包数量
+    pub packets_removed: u64,
+    /// 丢弃的字节数
+    pub bytes_removed: u64,
+}
+
+impl TruncationReport {
+    /// 截断时间戳之后是否没有数据，数据集未被修改
+    pub fn is_unchanged(&self) -> bool {
+        self.files_removed == 0 && self.files_truncated == 0
+    }
+}
+
+/// PIDX 索引
+///
+/// 公开字段将在下一个主版本改为私有，请使用 `total_packets()`、`files()`、
+/// `timestamp_entries()` 等访问方法，或通过 `IndexManager::query()` 查询。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename = "index")]
 pub struct PidxIndex {
+    /// 索引结构版本，早期版本的索引没有该属性
+    #[serde(
+        rename = "@schema_version",
+        default = "legacy_schema_version"
+    )]
+    pub schema_version: u32,
     #[serde(rename = "description")]
     pub description: String,
     #[serde(rename = "created_time")]
@@ -59,8 +281,35 @@ pub struct PidxIndex {
     pub total_duration: u64,
     #[serde(rename = "data_files")]
     pub data_files: DataFiles,
+    /// 每个时间戳对应的第一个数据包（写入顺序最早），同一时间戳的其余数据包
+    /// 保存在重复时间戳表中
     #[serde(skip)]
     pub timestamp_index: HashMap<u64, TimestampPointer>,
+    /// 时间戳与前面的数据包重复的数据包，按写入顺序排列（仅用于内存索引）
+    #[serde(skip)]
+    duplicate_timestamps:
+        HashMap<u64, Vec<TimestampPointer>>,
+    /// 每个文件首个数据包的全局序号（仅用于内存索引）
+    #[serde(skip)]
+    file_start_indices: Vec<u64>,
+    /// 升序排列的时间戳（仅用于内存索引）
+    #[serde(skip)]
+    sorted_timestamps: Vec<u64>,
+    /// 按负载大小排序的 (大小, 全局序号)（仅用于内存索引）
+    #[serde(skip)]
+    sorted_sizes: Vec<(u32, u64)>,
+    /// 内存索引的字节上限，0表示不限制
+    #[serde(skip)]
+    memory_limit: u64,
+    /// 是否不论内存上限都使用稀疏模式
+    #[serde(skip)]
+    force_sparse: bool,
+    /// 构建内存索引前是否按起始时间戳排序文件
+    #[serde(skip)]
+    sort_files_by_time: bool,
+    /// 是否为稀疏模式（不构建查找表）
+    #[serde(skip)]
+    sparse: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,10 +318,23 @@ pub struct DataFiles {
     pub files: Vec<PcapFileIndex>,
 }
 
+fn legacy_schema_version() -> u32 {
+    PidxIndex::LEGACY_SCHEMA_VERSION
+}
+
 impl PidxIndex {
+    /// 当前的索引结构版本
+    ///
+    /// 版本 2 起为数据包条目记录文件内序号和全局序号。
+    pub const SCHEMA_VERSION: u32 = 2;
+
+    /// 没有版本属性的早期索引结构版本
+    pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+
     pub fn new(description: Option<String>) -> Self {
         use chrono::Utc;
         Self {
+            schema_version: Self::SCHEMA_VERSION,
             description: description.unwrap_or_default(),
             created_time: Utc::now().to_rfc3339(),
             start_timestamp: 0,
@@ -81,8 +343,229 @@ impl PidxIndex {
             total_duration: 0,
             data_files: DataFiles { files: Vec::new() },
             timestamp_index: HashMap::new(),
+            duplicate_timestamps: HashMap::new(),
+            file_start_indices: Vec::new(),
+            sorted_timestamps: Vec::new(),
+            sorted_sizes: Vec::new(),
+            memory_limit: 0,
+            force_sparse: false,
+            sort_files_by_time: false,
+            sparse: false,
         }
     }
+
+    /// 索引结构版本
+    #[inline]
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    /// 索引是否使用旧的结构版本，需要通过迁移升级
+    #[inline]
+    pub fn is_legacy_schema(&self) -> bool {
+        self.schema_version < Self::SCHEMA_VERSION
+    }
+
+    /// 索引描述
+    #[inline]
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// 索引创建时间（RFC3339）
+    #[inline]
+    pub fn created_time(&self) -> &str {
+        &self.created_time
+    }
+
+    /// 数据集起始时间戳（纳秒）
+    #[inline]
+    pub fn start_timestamp(&self) -> u64 {
+        self.start_timestamp
+    }
+
+    /// 数据集结束时间戳（纳秒）
+    #[inline]
+    pub fn end_timestamp(&self) -> u64 {
+        self.end_timestamp
+    }
+
+    /// 数据集时长（纳秒）
+    #[inline]
+    pub fn total_duration(&self) -> u64 {
+        self.total_duration
+    }
+
+    /// 数据包总数
+    #[inline]
+    pub fn total_packets(&self) -> u64 {
+        self.total_packets
+    }
+
+    /// 数据文件索引列表，按文件名排序
+    #[inline]
+    pub fn files(&self) -> &[PcapFileIndex] {
+        &self.data_files.files
+    }
+
+    /// 数据文件数量
+    #[inline]
+    pub fn file_count(&self) -> usize {
+        self.data_files.files.len()
+    }
+
+    /// 按时间戳升序遍历全部数据包
+    ///
+    /// 同一时间戳的多个数据包按写入顺序依次返回。
+    pub fn timestamp_entries(
+        &self,
+    ) -> impl Iterator<Item = (u64, TimestampPointer)> + '_
+    {
+        self.get_packets_in_range(0, u64::MAX)
+            .into_iter()
+            .map(|pointer| {
+                (pointer.entry.timestamp_ns, pointer)
+            })
+    }
+
+    /// 索引中的数据包条目数，同一时间戳的多个数据包分别计数
+    #[inline]
+    pub fn timestamp_count(&self) -> usize {
+        self.data_files
+            .files
+            .iter()
+            .map(|file| file.data_packets.len())
+            .sum()
+    }
+
+    /// 不同时间戳的数量
+    #[inline]
+    pub fn distinct_timestamp_count(&self) -> usize {
+        self.sorted_timestamps.len()
+    }
+
+    /// 时间戳与前面的数据包重复的数据包数量
+    pub fn duplicate_timestamp_count(&self) -> usize {
+        self.timestamp_count()
+            .saturating_sub(self.distinct_timestamp_count())
+    }
+
+    /// 索引是否记录了负载内容哈希
+    pub fn has_payload_hashes(&self) -> bool {
+        self.data_files.files.iter().any(|file| {
+            file.data_packets
+                .iter()
+                .any(|entry| entry.payload_hash.is_some())
+        })
+    }
+
+    /// 索引中是否存在指定时间戳
+    #[inline]
+    pub fn contains_timestamp(
+        &self,
+        timestamp_ns: u64,
+    ) -> bool {
+        self.sorted_timestamps
+            .binary_search(&timestamp_ns)
+            .is_ok()
+    }
+
+    /// 设置内存索引的字节上限，0表示不限制
+    ///
+    /// 在下一次 `build_timestamp_index` 时生效：数据包条目加上查找表的估算大小超过上限时
+    /// 进入稀疏模式，不构建按时间戳和负载大小的查找表，查询改为按文件时间范围扫描条目。
+    pub fn set_memory_limit(&mut self, limit_bytes: u64) {
+        self.memory_limit = limit_bytes;
+    }
+
+    /// 设置是否不论内存上限都使用稀疏模式
+    ///
+    /// 在下一次 `build_timestamp_index` 时生效，用于时间戳定位由磁盘上的时间戳表完成的场景。
+    pub fn set_force_sparse(&mut self, enabled: bool) {
+        self.force_sparse = enabled;
+    }
+
+    /// 设置构建内存索引前是否按起始时间戳排序文件
+    ///
+    /// 在下一次 `build_timestamp_index` 时生效。默认按文件名（即创建时间）排列，
+    /// 时钟回拨后创建较晚的文件时间戳反而较早，按起始时间戳排序后顺序读取的时间戳恢复递增；
+    /// 时间范围部分重叠的文件见 `time_segments`。没有数据包的文件排在最后。
+    pub fn set_sort_files_by_time(
+        &mut self,
+        enabled: bool,
+    ) {
+        self.sort_files_by_time = enabled;
+    }
+
+    /// 是否为稀疏模式，见 `set_memory_limit`
+    #[inline]
+    pub fn is_sparse(&self) -> bool {
+        self.sparse
+    }
+
+    /// 估算内存索引占用的字节数
+    pub fn memory_usage(&self) -> IndexMemoryUsage {
+        let files = &self.data_files.files;
+        let entry_bytes = size_of::<Self>()
+            + self.description.capacity()
+            + self.created_time.capacity()
+            + files.capacity() * size_of::<PcapFileIndex>()
+            + files
+                .iter()
+                .map(|file| {
+                    file.file_name.capacity()
+                        + file.file_hash.capacity()
+                        + file
+                            .shard
+                            .as_ref()
+                            .map_or(0, |shard| {
+                                shard.as_os_str().len()
+                            })
+                        + file.data_packets.capacity()
+                            * size_of::<PacketIndexEntry>()
+                })
+                .sum::<usize>()
+            + self.file_start_indices.capacity()
+                * size_of::<u64>()
+            + self.sorted_timestamps.capacity()
+                * size_of::<u64>();
+        let lookup_bytes = hash_map_bytes(
+            self.timestamp_index.capacity(),
+            size_of::<(u64, TimestampPointer)>(),
+        ) + hash_map_bytes(
+            self.duplicate_timestamps.capacity(),
+            size_of::<(u64, Vec<TimestampPointer>)>(),
+        ) + self
+            .duplicate_timestamps
+            .values()
+            .map(|pointers| {
+                pointers.capacity()
+                    * size_of::<TimestampPointer>()
+            })
+            .sum::<usize>()
+            + self.sorted_sizes.capacity()
+                * size_of::<(u32, u64)>();
+        IndexMemoryUsage {
+            entry_bytes: entry_bytes as u64,
+            lookup_bytes: lookup_bytes as u64,
+            sparse: self.sparse,
+        }
+    }
+
+    /// 按当前条目估算完整查找表的字节数
+    fn estimated_lookup_bytes(&self) -> u64 {
+        let packets = self.timestamp_count();
+        let distinct = self.distinct_timestamp_count();
+        let bytes = hash_map_bytes(
+            distinct,
+            size_of::<(u64, TimestampPointer)>(),
+        ) + (packets - distinct.min(packets))
+            * (size_of::<TimestampPointer>()
+                + size_of::<(u64, Vec<TimestampPointer>)>(
+                ))
+            + packets * size_of::<(u32, u64)>();
+        bytes as u64
+    }
     pub fn update_time_range(&mut self) {
         if self.data_files.files.is_empty() {
             self.start_timestamp = 0;
@@ -118,49 +601,522 @@ impl PidxIndex {
             .sum();
     }
 
+    /// 构建内存时间戳索引
+    ///
+    /// 同时为每个条目重新编号文件内序号和全局序号，
+    /// 兼容不包含序号字段的旧版索引文件，并构建按负载大小排序的大小索引。
+    /// 设置了内存上限且估算大小超过上限时进入稀疏模式，只保留有序时间戳，见 `set_memory_limit`。
+    /// 启用 `set_sort_files_by_time` 时先按起始时间戳排序文件。
     pub fn build_timestamp_index(&mut self) {
+        if self.sort_files_by_time {
+            self.data_files.files.sort_by_key(|file| {
+                (
+                    file.packet_count == 0,
+                    file.start_timestamp,
+                )
+            });
+        }
         self.timestamp_index.clear();
+        self.duplicate_timestamps.clear();
+        self.file_start_indices.clear();
+        self.sorted_sizes.clear();
+        let mut timestamps = Vec::new();
+        let mut global_index = 0u64;
+        for file_index in self.data_files.files.iter_mut() {
+            self.file_start_indices.push(global_index);
+            for (packet_idx, packet) in file_index
+                .data_packets
+                .iter_mut()
+                .enumerate()
+            {
+                packet.packet_index = packet_idx as u64;
+                packet.global_index = global_index;
+                timestamps.push(packet.timestamp_ns);
+                global_index += 1;
+            }
+        }
+        timestamps.sort_unstable();
+        timestamps.dedup();
+        timestamps.shrink_to_fit();
+        self.sorted_timestamps = timestamps;
+
+        self.sparse = self.force_sparse
+            || self.memory_limit > 0 && {
+                let usage = self.memory_usage();
+                usage.entry_bytes
+                    + self.estimated_lookup_bytes()
+                    > self.memory_limit
+            };
+        if self.sparse {
+            self.timestamp_index.shrink_to_fit();
+            self.duplicate_timestamps.shrink_to_fit();
+            self.sorted_sizes.shrink_to_fit();
+            if self.force_sparse {
+                log::debug!(
+                    "内存索引使用稀疏模式，包含 {global_index} 个数据包"
+                );
+            } else {
+                log::info!(
+                    "内存索引超过上限 {} 字节，使用稀疏模式，包含 {} 个数据包",
+                    self.memory_limit,
+                    global_index
+                );
+            }
+            return;
+        }
+
         for (file_idx, file_index) in
             self.data_files.files.iter().enumerate()
         {
             for packet in &file_index.data_packets {
+                self.sorted_sizes.push((
+                    packet.packet_size,
+                    packet.global_index,
+                ));
                 let pointer = TimestampPointer {
                     file_index: file_idx,
                     entry: packet.clone(),
                 };
-                self.timestamp_index
-                    .insert(packet.timestamp_ns, pointer);
+                match self
+                    .timestamp_index
+                    .entry(packet.timestamp_ns)
+                {
+                    Entry::Vacant(slot) => {
+                        slot.insert(pointer);
+                    }
+                    Entry::Occupied(mut slot) => {
+                        // 条目未按偏移排序时保证首个数据包写入顺序最早
+                        let later = if pointer.write_order()
+                            < slot.get().write_order()
+                        {
+                            slot.insert(pointer)
+                        } else {
+                            pointer
+                        };
+                        self.duplicate_timestamps
+                            .entry(packet.timestamp_ns)
+                            .or_default()
+                            .push(later);
+                    }
+                }
             }
         }
+        for pointers in
+            self.duplicate_timestamps.values_mut()
+        {
+            pointers
+                .sort_by_key(TimestampPointer::write_order);
+        }
+        self.sorted_sizes.sort_unstable();
         log::debug!(
-            "构建时间戳索引完成，包含 {} 条目",
-            self.timestamp_index.len()
+            "构建时间戳索引完成，包含 {} 条目，其中 {} 个时间戳重复",
+            self.timestamp_count(),
+            self.duplicate_timestamp_count()
         );
     }
 
+    /// 根据全局序号定位数据包
+    ///
+    /// 通过二分查找定位所属文件，返回文件索引和数据包条目。
+    pub fn locate_global_index(
+        &self,
+        global_index: u64,
+    ) -> Option<(usize, &PacketIndexEntry)> {
+        let file_idx = self
+            .file_start_indices
+            .partition_point(|&start| start <= global_index)
+            .checked_sub(1)?;
+        let offset = global_index
+            - self.file_start_indices[file_idx];
+        self.data_files.files[file_idx]
+            .data_packets
+            .get(offset as usize)
+            .map(|entry| (file_idx, entry))
+    }
+
+    /// 指定文件首个数据包的全局序号
+    pub(crate) fn file_start_index(
+        &self,
+        file_idx: usize,
+    ) -> Option<u64> {
+        self.file_start_indices.get(file_idx).copied()
+    }
+
+    /// 升序排列的时间戳
+    pub(crate) fn sorted_timestamps(&self) -> &[u64] {
+        &self.sorted_timestamps
+    }
+
+    /// 负载大小在 `[min_size, max_size]` 内的 (大小, 全局序号)，按大小升序排列
+    ///
+    /// 稀疏模式下扫描全部条目。
+    pub(crate) fn packets_with_size(
+        &self,
+        min_size: u32,
+        max_size: u32,
+    ) -> Cow<'_, [(u32, u64)]> {
+        if min_size > max_size {
+            return Cow::Borrowed(&[]);
+        }
+        if self.sparse {
+            let mut sizes: Vec<(u32, u64)> = self
+                .data_files
+                .files
+                .iter()
+                .flat_map(|file| &file.data_packets)
+                .filter(|entry| {
+                    (min_size..=max_size)
+                        .contains(&entry.packet_size)
+                })
+                .map(|entry| {
+                    (entry.packet_size, entry.global_index)
+                })
+                .collect();
+            sizes.sort_unstable();
+            return Cow::Owned(sizes);
+        }
+        let lower = self
+            .sorted_sizes
+            .partition_point(|&(size, _)| size < min_size);
+        let upper = self
+            .sorted_sizes
+            .partition_point(|&(size, _)| size <= max_size);
+        Cow::Borrowed(&self.sorted_sizes[lower..upper])
+    }
+
+    /// 查找时间范围覆盖指定时间戳的文件
+    ///
+    /// 直接使用文件级的起止时间戳判断，不需要读取数据文件。
+    /// 多个文件时间范围重叠时返回第一个。
+    pub fn file_covering_timestamp(
+        &self,
+        timestamp_ns: u64,
+    ) -> Option<(usize, &PcapFileIndex)> {
+        self.data_files.files.iter().enumerate().find(
+            |(_, file)| {
+                file.packet_count > 0
+                    && file.start_timestamp <= timestamp_ns
+                    && timestamp_ns <= file.end_timestamp
+            },
+        )
+    }
+
+    /// 查找时间范围与 `[start_ns, end_ns]` 有交集的文件
+    ///
+    /// 按文件在索引中的顺序返回。
+    pub fn files_in_range(
+        &self,
+        start_ns: u64,
+        end_ns: u64,
+    ) -> Vec<(usize, &PcapFileIndex)> {
+        self.data_files
+            .files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| {
+                file.packet_count > 0
+                    && file.start_timestamp <= end_ns
+                    && start_ns <= file.end_timestamp
+            })
+            .collect()
+    }
+
+    /// 时间范围互相重叠的文件对
+    ///
+    /// 返回 (先开始的文件索引, 后开始的文件索引)，按后开始的文件排序；
+    /// 只有边界时间戳相同的相邻文件不视为重叠。文件时间范围重叠通常是时钟回拨
+    /// 或合并了同时记录的数据集导致的，此时按文件顺序读取的时间戳不再单调。
+    pub fn overlapping_files(&self) -> Vec<(usize, usize)> {
+        let mut overlaps = Vec::new();
+        let mut active: Vec<(u64, usize)> = Vec::new();
+        for file_idx in self.files_by_start_time() {
+            let file = &self.data_files.files[file_idx];
+            active.retain(|&(end, _)| {
+                end > file.start_timestamp
+            });
+            overlaps.extend(
+                active
+                    .iter()
+                    .map(|&(_, other)| (other, file_idx)),
+            );
+            active.push((file.end_timestamp, file_idx));
+        }
+        overlaps
+    }
+
+    /// 按时间范围把文件分段
+    ///
+    /// 时间范围互相重叠（含间接重叠）的文件归为一段，段按起始时间排列，
+    /// 段内文件按起始时间排列。包含多个文件的段需要按时间戳合并读取，
+    /// 例如使用 `PcapReader::iter_time_range`。没有数据包的文件不参与分段。
+    pub fn time_segments(&self) -> Vec<Vec<usize>> {
+        let mut segments: Vec<Vec<usize>> = Vec::new();
+        let mut segment_end = 0u64;
+        for file_idx in self.files_by_start_time() {
+            let file = &self.data_files.files[file_idx];
+            match segments.last_mut() {
+                Some(segment)
+                    if file.start_timestamp
+                        < segment_end =>
+                {
+                    segment.push(file_idx);
+                    segment_end =
+                        segment_end.max(file.end_timestamp);
+                }
+                _ => {
+                    segments.push(vec![file_idx]);
+                    segment_end = file.end_timestamp;
+                }
+            }
+        }
+        segments
+    }
+
+    /// 包含数据包的文件，按 (起始时间, 文件索引) 排序
+    fn files_by_start_time(&self) -> Vec<usize> {
+        let mut files: Vec<usize> = self
+            .data_files
+            .files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| file.packet_count > 0)
+            .map(|(file_idx, _)| file_idx)
+            .collect();
+        files.sort_by_key(|&file_idx| {
+            self.data_files.files[file_idx].start_timestamp
+        });
+        files
+    }
+
+    /// 查找指定时间戳的数据包
+    ///
+    /// 同一时间戳有多个数据包时返回写入顺序最早的一个。
     #[inline]
     pub fn find_packet_by_timestamp(
         &self,
         timestamp_ns: u64,
-    ) -> Option<&TimestampPointer> {
-        self.timestamp_index.get(&timestamp_ns)
+    ) -> Option<TimestampPointer> {
+        if self.sparse {
+            return self
+                .find_packets_by_timestamp(timestamp_ns)
+                .into_iter()
+                .next();
+        }
+        self.timestamp_index.get(&timestamp_ns).cloned()
+    }
+
+    /// 查找指定时间戳的全部数据包，按写入顺序（文件索引、文件内偏移）排列
+    pub fn find_packets_by_timestamp(
+        &self,
+        timestamp_ns: u64,
+    ) -> Vec<TimestampPointer> {
+        if self.sparse {
+            return self.scan_packets_in_range(
+                timestamp_ns,
+                timestamp_ns,
+            );
+        }
+        self.timestamp_index
+            .get(&timestamp_ns)
+            .into_iter()
+            .chain(
+                self.duplicate_timestamps
+                    .get(&timestamp_ns)
+                    .into_iter()
+                    .flatten(),
+            )
+            .cloned()
+            .collect()
+    }
+
+    /// 按查找模式定位时间戳
+    ///
+    /// 基于有序时间戳数组二分查找，返回满足模式的数据包指针；
+    /// 目标时间戳有多个数据包时返回写入顺序最早的一个。
+    pub fn find_packet_with_mode(
+        &self,
+        timestamp_ns: u64,
+        mode: SeekMode,
+    ) -> Option<TimestampPointer> {
+        let timestamps = &self.sorted_timestamps;
+        let pos = timestamps
+            .partition_point(|&t| t < timestamp_ns);
+        select_timestamp(
+            timestamps,
+            pos,
+            timestamp_ns,
+            mode,
+        )
+        .and_then(|t| self.find_packet_by_timestamp(t))
+    }
+
+    /// 按查找模式批量定位时间戳，结果与输入顺序一一对应
+    ///
+    /// 先对目标时间戳排序，再沿有序时间戳数组单向推进一次完成全部查找，
+    /// 定位到同一时间戳的请求共享同一次查找结果。
+    pub fn find_packets_with_mode(
+        &self,
+        timestamps: &[u64],
+        mode: SeekMode,
+    ) -> Vec<Option<TimestampPointer>> {
+        let mut order: Vec<usize> =
+            (0..timestamps.len()).collect();
+        order.sort_by_key(|&i| timestamps[i]);
+
+        let sorted = &self.sorted_timestamps;
+        let mut results = vec![None; timestamps.len()];
+        let mut pos = 0;
+        let mut previous: Option<(
+            u64,
+            Option<TimestampPointer>,
+        )> = None;
+        for i in order {
+            let target = timestamps[i];
+            pos += sorted[pos..]
+                .partition_point(|&t| t < target);
+            let found =
+                select_timestamp(sorted, pos, target, mode);
+            let pointer = match (&previous, found) {
+                (Some((last, pointer)), Some(t))
+                    if *last == t =>
+                {
+                    pointer.clone()
+                }
+                (_, Some(t)) => {
+                    let pointer =
+                        self.find_packet_by_timestamp(t);
+                    previous = Some((t, pointer.clone()));
+                    pointer
+                }
+                (_, None) => None,
+            };
+            results[i] = pointer;
+        }
+        results
     }
 
+    /// 时间戳在 `[start_ns, end_ns]` 内的全部数据包
+    ///
+    /// 按 (时间戳, 文件索引, 文件内偏移) 排序：时间戳相同的数据包保证按写入顺序返回，
+    /// 回放等依赖顺序的场景可以直接使用。
     pub fn get_packets_in_range(
         &self,
         start_ns: u64,
         end_ns: u64,
-    ) -> Vec<&TimestampPointer> {
-        let mut packets = Vec::new();
-        for (timestamp_ns, pointer) in &self.timestamp_index
-        {
-            if *timestamp_ns >= start_ns
-                && *timestamp_ns <= end_ns
-            {
-                packets.push(pointer);
-            }
+    ) -> Vec<TimestampPointer> {
+        if start_ns > end_ns {
+            return Vec::new();
+        }
+        if self.sparse {
+            return self
+                .scan_packets_in_range(start_ns, end_ns);
+        }
+        let timestamps = &self.sorted_timestamps;
+        let lower =
+            timestamps.partition_point(|&t| t < start_ns);
+        let upper =
+            timestamps.partition_point(|&t| t <= end_ns);
+        timestamps[lower..upper]
+            .iter()
+            .flat_map(|ts| {
+                self.find_packets_by_timestamp(*ts)
+            })
+            .collect()
+    }
+
+    /// 稀疏模式下按文件时间范围扫描条目，按 (时间戳, 文件索引, 文件内偏移) 排序
+    fn scan_packets_in_range(
+        &self,
+        start_ns: u64,
+        end_ns: u64,
+    ) -> Vec<TimestampPointer> {
+        let mut pointers: Vec<TimestampPointer> = self
+            .files_in_range(start_ns, end_ns)
+            .into_iter()
+            .flat_map(|(file_idx, file)| {
+                file.data_packets
+                    .iter()
+                    .filter(|entry| {
+                        (start_ns..=end_ns)
+                            .contains(&entry.timestamp_ns)
+                    })
+                    .map(move |entry| TimestampPointer {
+                        file_index: file_idx,
+                        entry: entry.clone(),
+                    })
+            })
+            .collect();
+        pointers.sort_by_key(|pointer| {
+            (
+                pointer.entry.timestamp_ns,
+                pointer.write_order(),
+            )
+        });
+        pointers
+    }
+}
+
+/// 在有序时间戳数组中按查找模式选取时间戳
+///
+/// `pos` 为第一个不小于 `timestamp_ns` 的时间戳的位置。
+fn select_timestamp(
+    timestamps: &[u64],
+    pos: usize,
+    timestamp_ns: u64,
+    mode: SeekMode,
+) -> Option<u64> {
+    let ceiling = timestamps.get(pos).copied();
+    let floor = if ceiling == Some(timestamp_ns) {
+        ceiling
+    } else {
+        pos.checked_sub(1).map(|p| timestamps[p])
+    };
+
+    match mode {
+        SeekMode::Exact => {
+            ceiling.filter(|&t| t == timestamp_ns)
         }
-        packets.sort_by_key(|p| p.entry.timestamp_ns);
-        packets
+        SeekMode::Floor => floor,
+        SeekMode::Ceiling => ceiling,
+        SeekMode::Nearest => match (floor, ceiling) {
+            (Some(f), Some(c)) => {
+                if timestamp_ns - f <= c - timestamp_ns {
+                    Some(f)
+                } else {
+                    Some(c)
+                }
+            }
+            (f, c) => f.or(c),
+        },
+    }
+}
+
+/// 按容量估算哈希表占用的字节数（桶数组加每个桶一个控制字节）
+fn hash_map_bytes(
+    capacity: usize,
+    entry_size: usize,
+) -> usize {
+    if capacity == 0 {
+        return 0;
+    }
+    let buckets = (capacity * 8 / 7).next_power_of_two();
+    buckets * (entry_size + 1)
+}
+
+/// 内存索引占用的字节数估算
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexMemoryUsage {
+    /// 数据文件和数据包条目，以及全局序号和有序时间戳
+    pub entry_bytes: u64,
+    /// 按时间戳和负载大小的查找表，稀疏模式下为 0
+    pub lookup_bytes: u64,
+    /// 是否为稀疏模式
+    pub sparse: bool,
+}
+
+impl IndexMemoryUsage {
+    /// 总字节数
+    pub fn total_bytes(&self) -> u64 {
+        self.entry_bytes + self.lookup_bytes
     }
 }
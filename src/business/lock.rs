@@ -0,0 +1,129 @@
+//! 数据集锁模块
+//!
+//! 通过数据集目录下的锁文件加操作系统文件锁，保证同一数据集同时只有一个写入器。
+//! 操作系统锁在进程退出（包括崩溃）时自动释放，不会遗留失效锁。
+
+use log::{debug, warn};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 锁文件名
+pub const LOCK_FILE_NAME: &str = ".lock";
+
+/// 数据集写入锁
+///
+/// 持有期间其他写入器无法获取同一数据集的锁，释放（Drop）时自动解锁。
+/// 锁文件本身会保留在数据集目录中，避免删除文件与加锁之间的竞争。
+#[derive(Debug)]
+pub struct DatasetLock {
+    file: File,
+    lock_path: PathBuf,
+}
+
+impl DatasetLock {
+    /// 获取数据集的独占写入锁
+    ///
+    /// # 错误
+    /// 数据集已被其他写入器锁定时返回 `PcapError::DatasetLocked`
+    pub fn acquire<P: AsRef<Path>>(
+        dataset_path: P,
+    ) -> PcapResult<Self> {
+        let lock_path =
+            dataset_path.as_ref().join(LOCK_FILE_NAME);
+        let mut file = Self::open_lock_file(&lock_path)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let owner = Self::read_owner(&mut file);
+                return Err(PcapError::DatasetLocked(
+                    format!("{lock_path:?} ({owner})"),
+                ));
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(PcapError::Io(e));
+            }
+        }
+
+        // 记录持有者信息，便于诊断
+        let owner = format!(
+            "pid={} since={}",
+            std::process::id(),
+            chrono::Utc::now().to_rfc3339()
+        );
+        file.set_len(0).map_err(PcapError::Io)?;
+        file.seek(SeekFrom::Start(0))
+            .map_err(PcapError::Io)?;
+        file.write_all(owner.as_bytes())
+            .map_err(PcapError::Io)?;
+        file.flush().map_err(PcapError::Io)?;
+
+        debug!("已获取数据集锁: {lock_path:?}");
+        Ok(Self { file, lock_path })
+    }
+
+    /// 检查数据集当前是否被写入器锁定
+    pub fn is_locked<P: AsRef<Path>>(
+        dataset_path: P,
+    ) -> bool {
+        let lock_path =
+            dataset_path.as_ref().join(LOCK_FILE_NAME);
+        if !lock_path.exists() {
+            return false;
+        }
+
+        let file = match File::open(&lock_path) {
+            Ok(file) => file,
+            Err(_) => return false,
+        };
+
+        match file.try_lock_shared() {
+            Ok(()) => {
+                let _ = file.unlock();
+                false
+            }
+            Err(TryLockError::WouldBlock) => true,
+            Err(TryLockError::Error(_)) => false,
+        }
+    }
+
+    /// 锁文件路径
+    pub fn lock_path(&self) -> &Path {
+        &self.lock_path
+    }
+
+    fn open_lock_file(
+        lock_path: &Path,
+    ) -> PcapResult<File> {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(lock_path)
+            .map_err(PcapError::Io)
+    }
+
+    fn read_owner(file: &mut File) -> String {
+        let mut owner = String::new();
+        if file.read_to_string(&mut owner).is_err()
+            || owner.is_empty()
+        {
+            return "未知持有者".to_string();
+        }
+        owner
+    }
+}
+
+impl Drop for DatasetLock {
+    fn drop(&mut self) {
+        if let Err(e) = self.file.unlock() {
+            warn!("释放数据集锁失败: {e}");
+        } else {
+            debug!("已释放数据集锁: {:?}", self.lock_path);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! 标记模块
+//!
+//! 写入器在数据流中插入的事件标记（如"场景开始/结束"），以 JSON Lines 格式追加到
+//! 数据集目录下的标记文件中。标记不写入 PCAP 文件，不影响数据包的读取和索引。
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use crate::business::clock::{ClockSource, SystemClock};
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 标记文件名
+pub const MARKERS_FILE_NAME: &str = ".markers";
+
+/// 数据流中的事件标记
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub struct Marker {
+    /// 标记内容
+    pub label: String,
+    /// 标记时间戳（纳秒）
+    pub timestamp_ns: u64,
+    /// 标记之后第一个数据包的全局位置，即插入标记时已写入的数据包数量
+    pub position: u64,
+}
+
+impl Marker {
+    /// 创建标记
+    pub fn new(
+        label: &str,
+        timestamp_ns: u64,
+        position: u64,
+    ) -> Self {
+        Self {
+            label: label.to_string(),
+            timestamp_ns,
+            position,
+        }
+    }
+
+    /// 以当前系统时间创建标记
+    pub fn now(label: &str, position: u64) -> Self {
+        Self::now_with(label, position, &SystemClock)
+    }
+
+    /// 以时钟源的当前时间创建标记
+    pub fn now_with<C: ClockSource + ?Sized>(
+        label: &str,
+        position: u64,
+        clock: &C,
+    ) -> Self {
+        Self::new(label, clock.now_ns(), position)
+    }
+}
+
+/// 追加一条标记到数据集的标记文件
+///
+/// 每条标记单独一行并立即刷新，进程中断时最多丢失正在写入的一条。
+pub fn append_marker<P: AsRef<Path>>(
+    dataset_path: P,
+    marker: &Marker,
+) -> PcapResult<()> {
+    let path =
+        dataset_path.as_ref().join(MARKERS_FILE_NAME);
+    let mut line = serde_json::to_string(marker)?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(PcapError::Io)?;
+    file.write_all(line.as_bytes())
+        .and_then(|_| file.flush())
+        .map_err(PcapError::Io)
+}
+
+/// 读取数据集的全部标记，按写入顺序返回
+///
+/// 标记文件不存在时返回空列表；无法解析的行（如中断时写了一半的最后一行）被跳过。
+pub fn load_markers<P: AsRef<Path>>(
+    dataset_path: P,
+) -> PcapResult<Vec<Marker>> {
+    let path =
+        dataset_path.as_ref().join(MARKERS_FILE_NAME);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(PcapError::Io)?;
+    let mut markers = Vec::new();
+    for (line_number, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(marker) => markers.push(marker),
+            Err(e) => warn!(
+                "跳过无法解析的标记: 第 {} 行, 错误: {e}",
+                line_number + 1
+            ),
+        }
+    }
+    Ok(markers)
+}
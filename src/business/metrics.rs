@@ -0,0 +1,206 @@
+//! 写入统计模块
+//!
+//! 写入器在写入过程中以固定的 2 的幂分桶直方图统计负载大小和相邻数据包的时间间隔，
+//! 内存占用固定且与写入数量无关。记录程序可以定期读取统计结果，
+//! 在输入特征（报文大小、发送周期）发生漂移时告警，而无需事后分析数据文件。
+
+use serde::Serialize;
+
+/// 分桶数量：值 0 单独一个桶，其余按最高有效位分为 64 个桶
+const BUCKET_COUNT: usize = 65;
+
+/// 以 2 的幂分桶的流式直方图
+///
+/// 第 0 个桶只统计值 0，第 i 个桶统计 `[2^(i-1), 2^i - 1]` 内的值。
+/// 分位数按所在桶的上界估算，相对误差不超过一倍，适合监控分布的变化而不是精确统计。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Histogram {
+    buckets: Vec<u64>,
+    count: u64,
+    sum: u64,
+    min: Option<u64>,
+    max: Option<u64>,
+}
+
+/// 直方图中的一个非空桶
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct HistogramBucket {
+    /// 桶的下界（含）
+    pub lower: u64,
+    /// 桶的上界（含）
+    pub upper: u64,
+    /// 落入该桶的值的数量
+    pub count: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Histogram {
+    /// 创建空的直方图
+    pub fn new() -> Self {
+        Self {
+            buckets: vec![0; BUCKET_COUNT],
+            count: 0,
+            sum: 0,
+            min: None,
+            max: None,
+        }
+    }
+
+    /// 记录一个值
+    pub fn record(&mut self, value: u64) {
+        self.buckets[bucket_index(value)] += 1;
+        self.count += 1;
+        self.sum = self.sum.saturating_add(value);
+        self.min =
+            Some(self.min.map_or(value, |m| m.min(value)));
+        self.max =
+            Some(self.max.map_or(value, |m| m.max(value)));
+    }
+
+    /// 已记录的值的数量
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 已记录的值之和，溢出时饱和
+    pub fn sum(&self) -> u64 {
+        self.sum
+    }
+
+    /// 最小值，没有记录时为 None
+    pub fn min(&self) -> Option<u64> {
+        self.min
+    }
+
+    /// 最大值，没有记录时为 None
+    pub fn max(&self) -> Option<u64> {
+        self.max
+    }
+
+    /// 平均值，没有记录时为 None
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0)
+            .then(|| self.sum as f64 / self.count as f64)
+    }
+
+    /// 估算分位数，`quantile` 取值 `[0, 1]`，超出范围时截断
+    ///
+    /// 返回分位数所在桶的上界，并限制在最小值和最大值之间；没有记录时为 None。
+    pub fn value_at_quantile(
+        &self,
+        quantile: f64,
+    ) -> Option<u64> {
+        let (min, max) = (self.min?, self.max?);
+        let quantile = quantile.clamp(0.0, 1.0);
+        let rank = ((quantile * self.count as f64).ceil()
+            as u64)
+            .max(1);
+        let mut seen = 0;
+        for (index, &count) in
+            self.buckets.iter().enumerate()
+        {
+            seen += count;
+            if seen >= rank {
+                return Some(
+                    bucket_bounds(index).1.clamp(min, max),
+                );
+            }
+        }
+        Some(max)
+    }
+
+    /// 非空的桶，按值从小到大排列
+    pub fn buckets(
+        &self,
+    ) -> impl Iterator<Item = HistogramBucket> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(index, &count)| {
+                let (lower, upper) = bucket_bounds(index);
+                HistogramBucket {
+                    lower,
+                    upper,
+                    count,
+                }
+            })
+    }
+
+    /// 清空全部记录
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// 值所在的桶
+fn bucket_index(value: u64) -> usize {
+    (u64::BITS - value.leading_zeros()) as usize
+}
+
+/// 桶的上下界（均含）
+fn bucket_bounds(index: usize) -> (u64, u64) {
+    match index {
+        0 => (0, 0),
+        64 => (1 << 63, u64::MAX),
+        _ => (1 << (index - 1), (1 << index) - 1),
+    }
+}
+
+/// 写入器的输入统计
+///
+/// 由 `PcapWriter::metrics` 返回。心跳包不计入分布，单独计数；
+/// 时间间隔按数据包时间戳计算，时间戳早于前一个数据包时计为乱序并按 0 记录。
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, Serialize,
+)]
+pub struct WriterMetrics {
+    /// 负载大小（字节）的分布
+    pub packet_sizes: Histogram,
+    /// 相邻数据包时间戳间隔（纳秒）的分布
+    pub intervals_ns: Histogram,
+    /// 心跳包数量
+    pub heartbeat_count: u64,
+    /// 时间戳早于前一个数据包的数据包数量
+    pub out_of_order_count: u64,
+    /// 上一个数据包的时间戳，重置统计后保留以便继续计算间隔
+    #[serde(skip)]
+    last_timestamp_ns: Option<u64>,
+}
+
+impl WriterMetrics {
+    /// 记录一个写入的数据包
+    pub(crate) fn record(
+        &mut self,
+        timestamp_ns: u64,
+        payload_len: usize,
+        heartbeat: bool,
+    ) {
+        if heartbeat {
+            self.heartbeat_count += 1;
+            return;
+        }
+        self.packet_sizes.record(payload_len as u64);
+        if let Some(last) = self.last_timestamp_ns {
+            if timestamp_ns < last {
+                self.out_of_order_count += 1;
+            }
+            self.intervals_ns
+                .record(timestamp_ns.saturating_sub(last));
+        }
+        self.last_timestamp_ns = Some(timestamp_ns);
+    }
+
+    /// 取出当前统计并重新开始，时间间隔从上一个数据包继续计算
+    pub(crate) fn take(&mut self) -> Self {
+        let last_timestamp_ns = self.last_timestamp_ns;
+        let taken = std::mem::take(self);
+        self.last_timestamp_ns = last_timestamp_ns;
+        taken
+    }
+}
@@ -2,16 +2,61 @@
 //!
 //! 实现核心业务规则和算法，包括配置管理、索引系统和性能优化策略。
 
+pub mod bookmarks;
 pub mod cache;
+pub mod clock;
 pub mod config;
+pub mod decode;
+pub mod disk;
 pub mod index;
+pub mod lock;
+pub mod markers;
+pub mod metrics;
+pub mod pool;
+pub mod progress;
+pub mod rate_limit;
+pub mod rotation;
+pub mod shards;
+pub mod units;
 
 // 重新导出核心配置和索引类型
-pub use cache::{CacheStats, FileInfoCache};
-pub use config::{ReaderConfig, WriterConfig};
+pub use bookmarks::{Bookmark, BookmarkStore};
+pub use cache::{CacheStats, FileInfoCache, PacketCache};
+pub use clock::{
+    ClockSource, InjectedClock, MonotonicClock, SystemClock,
+};
+pub use config::{
+    DuplicateTimestampPolicy, PlaybackConfig, ReaderConfig,
+    TriggerConfig, VerifyMode, WriteVerification,
+    WriterConfig,
+};
+pub use decode::{DecoderRegistry, PayloadDecoder};
+pub use disk::{
+    DiskGuard, DiskGuardAction, DiskSpaceStatus,
+};
 pub use index::{
-    PacketIndexEntry, PcapFileIndex, PidxIndex,
+    BackfillReport, CompactionReport, DatasetSubset,
+    FileFilter, IndexHealth, IndexHealthEntry,
+    IndexHealthKind, IndexMemoryUsage, IndexQuery,
+    IndexRebuildReason, IndexSchemaError, PacketIndexEntry,
+    PacketLocation, PayloadDiff, PcapFileIndex, PidxIndex,
+    SeekMode, SharedIndex, TimeWindow, TruncationReport,
+};
+#[cfg(feature = "mmap-index")]
+pub use index::{MmapTimestampIndex, TimestampRecord};
+pub use lock::DatasetLock;
+pub use markers::Marker;
+pub use metrics::{
+    Histogram, HistogramBucket, WriterMetrics,
+};
+pub use pool::PooledPacket;
+pub use progress::WriterProgress;
+pub use rate_limit::RateLimiter;
+pub use rotation::{
+    FileUsage, RotationPolicy, RotationReason,
 };
+pub use shards::ShardManifest;
+pub use units::ByteSize;
 
 // IndexManager作为内部实现细节，不对外暴露
 // 用户应该通过 PcapReader.index() 或 PcapWriter.index() 来访问索引功能
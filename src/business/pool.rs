@@ -0,0 +1,120 @@
+//! 缓冲区池模块
+//!
+//! 在读取之间回收数据包负载缓冲区，长时间运行、每分钟处理数百万个数据包的读取器
+//! 不必为每个数据包重新分配内存。
+
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+use crate::data::models::ValidatedPacket;
+
+/// 负载缓冲区池
+///
+/// 克隆得到的实例共享同一组缓冲区。
+#[derive(Debug, Clone)]
+pub(crate) struct BufferPool {
+    buffers: Arc<Mutex<Vec<Vec<u8>>>>,
+    max_buffers: usize,
+}
+
+impl BufferPool {
+    /// 创建最多保留 `max_buffers` 个空闲缓冲区的池，0表示不保留
+    pub(crate) fn new(max_buffers: usize) -> Self {
+        Self {
+            buffers: Arc::new(Mutex::new(Vec::new())),
+            max_buffers,
+        }
+    }
+
+    /// 取出长度为 `length` 的缓冲区，池为空时新分配
+    pub(crate) fn acquire(&self, length: usize) -> Vec<u8> {
+        let recycled = self
+            .buffers
+            .lock()
+            .ok()
+            .and_then(|mut buffers| buffers.pop());
+        match recycled {
+            Some(mut buffer) => {
+                buffer.clear();
+                buffer.resize(length, 0);
+                buffer
+            }
+            None => vec![0u8; length],
+        }
+    }
+
+    /// 归还缓冲区，池已满时直接释放
+    pub(crate) fn release(&self, buffer: Vec<u8>) {
+        if buffer.capacity() == 0 {
+            return;
+        }
+        if let Ok(mut buffers) = self.buffers.lock() {
+            if buffers.len() < self.max_buffers {
+                buffers.push(buffer);
+            }
+        }
+    }
+
+    /// 当前空闲的缓冲区数量
+    pub(crate) fn available(&self) -> usize {
+        self.buffers
+            .lock()
+            .map(|buffers| buffers.len())
+            .unwrap_or(0)
+    }
+}
+
+/// 负载缓冲区来自缓冲区池的数据包
+///
+/// 通过 `Deref` 当作 [`ValidatedPacket`] 使用，释放时负载缓冲区回到读取器的缓冲区池。
+/// 需要长期保留数据包时调用 [`PooledPacket::into_inner`] 取出，缓冲区不再回收。
+#[derive(Debug)]
+pub struct PooledPacket {
+    packet: Option<ValidatedPacket>,
+    pool: BufferPool,
+}
+
+impl PooledPacket {
+    pub(crate) fn new(
+        packet: ValidatedPacket,
+        pool: BufferPool,
+    ) -> Self {
+        Self {
+            packet: Some(packet),
+            pool,
+        }
+    }
+
+    /// 取出数据包，负载缓冲区不再归还缓冲区池
+    pub fn into_inner(mut self) -> ValidatedPacket {
+        self.packet
+            .take()
+            .expect("PooledPacket 中的数据包已被取出")
+    }
+}
+
+impl Deref for PooledPacket {
+    type Target = ValidatedPacket;
+
+    fn deref(&self) -> &ValidatedPacket {
+        self.packet
+            .as_ref()
+            .expect("PooledPacket 中的数据包已被取出")
+    }
+}
+
+impl DerefMut for PooledPacket {
+    fn deref_mut(&mut self) -> &mut ValidatedPacket {
+        self.packet
+            .as_mut()
+            .expect("PooledPacket 中的数据包已被取出")
+    }
+}
+
+impl Drop for PooledPacket {
+    fn drop(&mut self) {
+        if let Some(packet) = self.packet.take() {
+            self.pool.release(packet.packet.data);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! 写入进度模块
+//!
+//! 定义读写共存协议中写入器发布的进度标记：
+//! - 写入器每次创建新文件时发布进度，记录已完成的文件和正在写入的文件；
+//! - 同时在索引文件中写入已完成文件的检查点；
+//! - 读取器检测到活动写入器时，依据进度标记构建内存中的实时索引，
+//!   对正在写入的文件只读取到最后一个完整的数据包。
+//!
+//! 写入器完成（`finalize`）后删除进度标记并生成完整索引。
+
+use chrono::Utc;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 进度标记文件名
+pub const PROGRESS_FILE_NAME: &str = ".progress";
+
+/// 写入器进度标记
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WriterProgress {
+    /// 正在写入的文件名
+    pub active_file: Option<String>,
+    /// 本次写入会话中已完成的文件名
+    pub completed_files: Vec<String>,
+    /// 最近一次更新时间
+    pub updated_time: String,
+}
+
+impl WriterProgress {
+    /// 读取数据集的进度标记，不存在或无法解析时返回 None
+    pub fn load<P: AsRef<Path>>(
+        dataset_path: P,
+    ) -> Option<Self> {
+        let path =
+            dataset_path.as_ref().join(PROGRESS_FILE_NAME);
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// 发布进度标记
+    ///
+    /// 先写入临时文件再重命名，保证读取器不会看到写了一半的内容。
+    pub(crate) fn publish<P: AsRef<Path>>(
+        &mut self,
+        dataset_path: P,
+    ) -> PcapResult<()> {
+        self.updated_time = Utc::now().to_rfc3339();
+        let content = serde_json::to_string(self)?;
+
+        let path =
+            dataset_path.as_ref().join(PROGRESS_FILE_NAME);
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, content)
+            .map_err(PcapError::Io)?;
+        fs::rename(&temp_path, &path)
+            .map_err(PcapError::Io)?;
+
+        debug!("已发布写入进度: {:?}", self.active_file);
+        Ok(())
+    }
+
+    /// 删除进度标记
+    pub(crate) fn clear<P: AsRef<Path>>(
+        dataset_path: P,
+    ) -> PcapResult<()> {
+        let path =
+            dataset_path.as_ref().join(PROGRESS_FILE_NAME);
+        if path.exists() {
+            fs::remove_file(path).map_err(PcapError::Io)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+//! 速率限制模块
+//!
+//! 回放和导出密集数据集时按数据包数量和字节数限制输出速率，
+//! 避免下游消费者或网络被瞬时流量压垮。限速器按平均间隔逐包放行，不允许突发。
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 数据包速率限制器
+///
+/// 每个数据包占用 `1 / max_packets_per_second` 和 `字节数 / max_bytes_per_second`
+/// 中较长的一段发送时间，下一个数据包要等上一个的发送时间结束后才放行。
+/// 两个上限都未设置时不做任何限制。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimiter {
+    max_packets_per_second: Option<u64>,
+    max_bytes_per_second: Option<u64>,
+    /// 下一个数据包最早的放行时刻
+    next_release: Option<Instant>,
+}
+
+impl Default for RateLimiter {
+    /// 不做任何限制的限速器
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+impl RateLimiter {
+    /// 创建限速器，`None` 或 0 表示不限制对应指标
+    pub fn new(
+        max_packets_per_second: Option<u64>,
+        max_bytes_per_second: Option<u64>,
+    ) -> Self {
+        Self {
+            max_packets_per_second: max_packets_per_second
+                .filter(|rate| *rate > 0),
+            max_bytes_per_second: max_bytes_per_second
+                .filter(|rate| *rate > 0),
+            next_release: None,
+        }
+    }
+
+    /// 每秒最多放行的数据包数量
+    pub fn max_packets_per_second(&self) -> Option<u64> {
+        self.max_packets_per_second
+    }
+
+    /// 每秒最多放行的负载字节数
+    pub fn max_bytes_per_second(&self) -> Option<u64> {
+        self.max_bytes_per_second
+    }
+
+    /// 是否未设置任何上限
+    pub fn is_unlimited(&self) -> bool {
+        self.max_packets_per_second.is_none()
+            && self.max_bytes_per_second.is_none()
+    }
+
+    /// 等待直到可以发送一个 `bytes` 字节的数据包，返回实际等待的时长
+    pub fn acquire(&mut self, bytes: u64) -> Duration {
+        let wait = self.reserve(bytes, Instant::now());
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+        wait
+    }
+
+    /// 在 `now` 时刻为一个 `bytes` 字节的数据包预留发送时间，返回需要等待的时长
+    ///
+    /// 不阻塞，调用方负责等待返回的时长后再发送。
+    pub fn reserve(
+        &mut self,
+        bytes: u64,
+        now: Instant,
+    ) -> Duration {
+        if self.is_unlimited() {
+            return Duration::ZERO;
+        }
+        let release = self
+            .next_release
+            .map_or(now, |next| next.max(now));
+        self.next_release =
+            Some(release + self.cost_of(bytes));
+        release - now
+    }
+
+    /// 清除已预留的发送时间，下一个数据包立即放行
+    pub fn reset(&mut self) {
+        self.next_release = None;
+    }
+
+    /// 一个数据包占用的发送时间
+    fn cost_of(&self, bytes: u64) -> Duration {
+        let per_packet = self
+            .max_packets_per_second
+            .map_or(Duration::ZERO, |rate| {
+                Duration::from_secs_f64(1.0 / rate as f64)
+            });
+        let per_bytes = self.max_bytes_per_second.map_or(
+            Duration::ZERO,
+            |rate| {
+                Duration::from_secs_f64(
+                    bytes as f64 / rate as f64,
+                )
+            },
+        );
+        per_packet.max(per_bytes)
+    }
+}
@@ -0,0 +1,141 @@
+//! 文件轮换策略模块
+//!
+//! 写入器在每个数据包写入前检查当前文件是否需要轮换。轮换条件包括数据包数量、
+//! 文件覆盖的时间跨度、磁盘上的字节数（含文件头，启用压缩时为压缩后的字节数）
+//! 和未压缩的记录字节数，任一条件达到上限即切换到新文件。
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+use crate::business::units::{self, ByteSize};
+
+/// 文件轮换原因，按检查的优先级排列
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum RotationReason {
+    /// 数据包数量达到上限
+    PacketCount,
+    /// 文件覆盖的时间跨度达到上限
+    Duration,
+    /// 磁盘上的文件大小将超过上限
+    DiskSize,
+    /// 未压缩的记录字节数将超过上限
+    RawSize,
+}
+
+impl fmt::Display for RotationReason {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let text = match self {
+            RotationReason::PacketCount => "数据包数量",
+            RotationReason::Duration => "时间跨度",
+            RotationReason::DiskSize => "磁盘大小",
+            RotationReason::RawSize => "原始大小",
+        };
+        f.write_str(text)
+    }
+}
+
+/// 当前文件的写入统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileUsage {
+    /// 已写入的数据包数量
+    pub packet_count: u64,
+    /// 磁盘上的字节数，含文件头
+    pub disk_bytes: u64,
+    /// 未压缩的记录字节数（数据包头部和负载），不含文件头
+    pub raw_bytes: u64,
+    /// 第一个数据包的时间戳（纳秒）
+    pub first_timestamp_ns: Option<u64>,
+}
+
+/// 文件轮换策略
+///
+/// 各项上限为 0 表示不限制。检查按数据包数量、时间跨度、磁盘大小、原始大小的顺序进行，
+/// 多个条件同时满足时报告排在前面的原因。大小上限按写入后的大小预先判断，
+/// 因此文件不会超过上限；空文件总是接受下一个数据包，单个超过上限的数据包独占一个文件。
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub struct RotationPolicy {
+    /// 每个文件最大数据包数量
+    #[serde(default)]
+    pub max_packets: u64,
+    /// 每个文件覆盖的最大时间跨度，按数据包时间戳计算
+    #[serde(default, with = "units::serde_duration")]
+    pub max_duration: Duration,
+    /// 磁盘上每个文件的最大字节数，含文件头
+    #[serde(default)]
+    pub max_disk_size: ByteSize,
+    /// 每个文件未压缩记录的最大字节数，不含文件头
+    #[serde(default)]
+    pub max_raw_size: ByteSize,
+}
+
+impl RotationPolicy {
+    /// 检查写入下一个数据包前是否需要轮换
+    ///
+    /// # 参数
+    /// - `usage` - 当前文件的写入统计
+    /// - `timestamp_ns` - 下一个数据包的时间戳
+    /// - `raw_bytes` - 下一个数据包未压缩的记录字节数
+    /// - `disk_bytes` - 下一个数据包在磁盘上占用的字节数，含分块布局的块填充和块尾
+    pub fn check(
+        &self,
+        usage: &FileUsage,
+        timestamp_ns: u64,
+        raw_bytes: u64,
+        disk_bytes: u64,
+    ) -> Option<RotationReason> {
+        if usage.packet_count == 0 {
+            return None;
+        }
+        if self.max_packets > 0
+            && usage.packet_count >= self.max_packets
+        {
+            return Some(RotationReason::PacketCount);
+        }
+        if !self.max_duration.is_zero() {
+            let span = usage
+                .first_timestamp_ns
+                .map_or(0, |first| {
+                    timestamp_ns.saturating_sub(first)
+                });
+            if u128::from(span)
+                >= self.max_duration.as_nanos()
+            {
+                return Some(RotationReason::Duration);
+            }
+        }
+        if !self.max_disk_size.is_zero()
+            && usage.disk_bytes.saturating_add(disk_bytes)
+                > self.max_disk_size.as_u64()
+        {
+            return Some(RotationReason::DiskSize);
+        }
+        if !self.max_raw_size.is_zero()
+            && usage.raw_bytes.saturating_add(raw_bytes)
+                > self.max_raw_size.as_u64()
+        {
+            return Some(RotationReason::RawSize);
+        }
+        None
+    }
+}
@@ -0,0 +1,67 @@
+//! 存储分片模块
+//!
+//! 写入器配置了多个存储分片（不同目录或磁盘）时，轮换产生的数据文件按轮询方式
+//! 分布到各分片下的同名数据集目录中。主数据集目录保存索引、锁和分片清单，
+//! 读取器和索引管理器依据分片清单定位所有数据文件，无需额外配置。
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 分片清单文件名
+pub const SHARDS_FILE_NAME: &str = ".shards";
+
+/// 分片清单
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShardManifest {
+    /// 各分片上的数据集目录
+    pub directories: Vec<PathBuf>,
+}
+
+impl ShardManifest {
+    /// 读取数据集的分片清单，不存在或无法解析时返回空清单
+    pub fn load<P: AsRef<Path>>(dataset_path: P) -> Self {
+        let path =
+            dataset_path.as_ref().join(SHARDS_FILE_NAME);
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| {
+                serde_json::from_str(&content).ok()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 合并分片目录，已存在的目录不重复添加
+    ///
+    /// 向已分片的数据集追加写入时保留旧分片，保证其中的文件仍可被找到。
+    pub fn merge(&mut self, directories: &[PathBuf]) {
+        for directory in directories {
+            if !self.directories.contains(directory) {
+                self.directories.push(directory.clone());
+            }
+        }
+    }
+
+    /// 保存分片清单
+    ///
+    /// 先写入临时文件再重命名，避免读取器看到写了一半的清单。
+    pub(crate) fn save<P: AsRef<Path>>(
+        &self,
+        dataset_path: P,
+    ) -> PcapResult<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        let path =
+            dataset_path.as_ref().join(SHARDS_FILE_NAME);
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, content)
+            .map_err(PcapError::Io)?;
+        fs::rename(&temp_path, &path)
+            .map_err(PcapError::Io)?;
+
+        debug!("已保存分片清单: {:?}", self.directories);
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+//! 配置单位模块
+//!
+//! 配置中的字节大小和时间长度使用带单位的类型：代码中使用 `ByteSize` 和 `Duration`，
+//! 通过 serde 反序列化时接受 `"64MB"`、`"10s"` 等带单位的字符串，避免把毫秒当作秒、
+//! 把 KB 当作字节之类的单位错误。
+//!
+//! 字节单位不区分大小写，`K`/`KB`/`KiB` 均按 1024 计算，`M`、`G`、`T` 同理；
+//! 时间单位支持 `ns`、`us`（`µs`）、`ms`、`s`、`m`（`min`）和 `h`，数值可以带小数。
+
+use serde::de::{self, Visitor};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+const KIB: u64 = 1024;
+const MIB: u64 = KIB * 1024;
+const GIB: u64 = MIB * 1024;
+const TIB: u64 = GIB * 1024;
+
+/// 字节大小单位，按从大到小排列
+const BYTE_UNITS: [(u64, &str); 4] = [
+    (TIB, "TiB"),
+    (GIB, "GiB"),
+    (MIB, "MiB"),
+    (KIB, "KiB"),
+];
+
+/// 时间单位（纳秒数, 名称），按从大到小排列
+const DURATION_UNITS: [(u64, &str); 6] = [
+    (3_600_000_000_000, "h"),
+    (60_000_000_000, "m"),
+    (1_000_000_000, "s"),
+    (1_000_000, "ms"),
+    (1_000, "us"),
+    (1, "ns"),
+];
+
+/// 字节大小
+///
+/// 反序列化时接受整数（字节）或带单位的字符串，例如 `"64MB"`、`"1.5 GiB"`；
+/// 序列化为能精确表示该值的最大单位，例如 `"64MiB"`。
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Default,
+)]
+pub struct ByteSize(u64);
+
+impl ByteSize {
+    /// 零字节
+    pub const ZERO: Self = Self(0);
+
+    /// 以字节为单位创建
+    pub const fn b(bytes: u64) -> Self {
+        Self(bytes)
+    }
+
+    /// 以 KiB（1024 字节）为单位创建
+    pub const fn kib(kib: u64) -> Self {
+        Self(kib * KIB)
+    }
+
+    /// 以 MiB 为单位创建
+    pub const fn mib(mib: u64) -> Self {
+        Self(mib * MIB)
+    }
+
+    /// 以 GiB 为单位创建
+    pub const fn gib(gib: u64) -> Self {
+        Self(gib * GIB)
+    }
+
+    /// 字节数
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    /// 字节数，超出 `usize` 范围时取 `usize::MAX`
+    pub fn as_usize(self) -> usize {
+        usize::try_from(self.0).unwrap_or(usize::MAX)
+    }
+
+    /// 是否为零
+    pub const fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<usize> for ByteSize {
+    fn from(bytes: usize) -> Self {
+        Self(bytes as u64)
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        for (size, unit) in BYTE_UNITS {
+            if self.0 >= size && self.0.is_multiple_of(size)
+            {
+                return write!(
+                    f,
+                    "{}{unit}",
+                    self.0 / size
+                );
+            }
+        }
+        write!(f, "{}B", self.0)
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (number, unit) = split_unit(s);
+        let multiplier =
+            match unit.to_ascii_lowercase().as_str() {
+                "" | "b" => 1,
+                "k" | "kb" | "kib" => KIB,
+                "m" | "mb" | "mib" => MIB,
+                "g" | "gb" | "gib" => GIB,
+                "t" | "tb" | "tib" => TIB,
+                _ => {
+                    return Err(format!(
+                        "无效的字节单位: \"{s}\""
+                    ))
+                }
+            };
+        scale(number, multiplier).map(Self).ok_or_else(
+            || format!("无效的字节大小: \"{s}\""),
+        )
+    }
+}
+
+impl Serialize for ByteSize {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        struct ByteSizeVisitor;
+
+        impl Visitor<'_> for ByteSizeVisitor {
+            type Value = ByteSize;
+
+            fn expecting(
+                &self,
+                f: &mut fmt::Formatter<'_>,
+            ) -> fmt::Result {
+                f.write_str(
+                    "字节数或带单位的字符串，例如 \"64MB\"",
+                )
+            }
+
+            fn visit_u64<E: de::Error>(
+                self,
+                value: u64,
+            ) -> Result<ByteSize, E> {
+                Ok(ByteSize(value))
+            }
+
+            fn visit_i64<E: de::Error>(
+                self,
+                value: i64,
+            ) -> Result<ByteSize, E> {
+                u64::try_from(value).map(ByteSize).map_err(
+                    |_| {
+                        E::custom(format!(
+                            "字节大小不能为负数: {value}"
+                        ))
+                    },
+                )
+            }
+
+            fn visit_str<E: de::Error>(
+                self,
+                value: &str,
+            ) -> Result<ByteSize, E> {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(ByteSizeVisitor)
+    }
+}
+
+/// 解析带单位的时间长度，例如 `"10s"`、`"500ms"`、`"1.5h"`
+///
+/// 时间长度必须带单位，不带单位的数值无法判断是秒还是毫秒，返回错误。
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let (number, unit) = split_unit(s);
+    let multiplier = match unit {
+        "ns" => 1,
+        "us" | "µs" => 1_000,
+        "ms" => 1_000_000,
+        "s" | "sec" => 1_000_000_000,
+        "m" | "min" => 60_000_000_000,
+        "h" => 3_600_000_000_000,
+        "" => {
+            return Err(format!(
+                "时间长度缺少单位: \"{s}\"（例如 \"10s\"、\"500ms\"）"
+            ))
+        }
+        _ => {
+            return Err(format!("无效的时间单位: \"{s}\""))
+        }
+    };
+    scale(number, multiplier)
+        .map(Duration::from_nanos)
+        .ok_or_else(|| format!("无效的时间长度: \"{s}\""))
+}
+
+/// 把时间长度格式化为能精确表示该值的最大单位，例如 `"10s"`、`"1500ms"`
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = u64::try_from(duration.as_nanos())
+        .unwrap_or(u64::MAX);
+    if nanos == 0 {
+        return "0s".to_string();
+    }
+    DURATION_UNITS
+        .iter()
+        .find(|(size, _)| nanos.is_multiple_of(*size))
+        .map(|(size, unit)| {
+            format!("{}{unit}", nanos / size)
+        })
+        .unwrap_or_else(|| format!("{nanos}ns"))
+}
+
+/// `Duration` 字段的 serde 适配，配合 `#[serde(with = "...")]` 使用
+///
+/// 序列化为 `format_duration` 的结果，反序列化时用 `parse_duration` 解析。
+pub mod serde_duration {
+    use super::{format_duration, parse_duration};
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        duration: &Duration,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer
+            .serialize_str(&format_duration(*duration))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Duration, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse_duration(&text)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// 拆分数值和单位，忽略两端及中间的空白
+fn split_unit(s: &str) -> (&str, &str) {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    (s[..split].trim(), s[split..].trim())
+}
+
+/// 数值乘以单位倍数，整数精确计算，小数向下取整；溢出或格式错误时返回 `None`
+fn scale(number: &str, multiplier: u64) -> Option<u64> {
+    if let Ok(value) = number.parse::<u64>() {
+        return value.checked_mul(multiplier);
+    }
+    let value = number.parse::<f64>().ok()?;
+    let scaled = value * multiplier as f64;
+    (value.is_finite() && scaled < u64::MAX as f64)
+        .then_some(scaled as u64)
+}
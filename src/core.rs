@@ -0,0 +1,590 @@
+//! 核心编解码模块
+//!
+//! 提供文件头、数据包头和校验和的序列化与解析，不依赖标准库和内存分配。
+//! 关闭 `std` 特性时库中只保留本模块和格式常量，嵌入式记录设备可以用它直接生成
+//! 与本库兼容的字节流，再交给使用完整库的主机读取。
+
+use crate::foundation::types::constants;
+
+/// 基础文件头大小（字节）
+pub const FILE_HEADER_SIZE: usize = 16; // 4 + 2 + 2 + 4 + 4
+
+/// 携带链路类型的文件头大小（字节）
+pub const EXTENDED_FILE_HEADER_SIZE: usize = 20; // 16 + 4
+
+/// 携带扩展块区域的文件头固定部分大小（字节）
+pub const EXTENSIBLE_FILE_HEADER_SIZE: usize = 24; // 20 + 4
+
+/// 扩展块头部大小（字节）
+pub const EXTENSION_BLOCK_HEADER_SIZE: usize = 4; // 2 + 2
+
+/// 数据包头大小（字节）
+pub const PACKET_HEADER_SIZE: usize = 16; // 4 + 4 + 4 + 4
+
+/// 心跳包头部的校验和字段取值
+///
+/// 空负载的 CRC32 为 0，心跳包的校验和字段改为保存该保留值（ASCII `HBRT`），
+/// 与用户写入的空数据包区分。
+pub const HEARTBEAT_CHECKSUM: u32 = 0x4842_5254;
+
+/// 默认时间戳精度（纳秒）
+pub const DEFAULT_TIMESTAMP_ACCURACY: u32 = 1;
+
+/// 编解码错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecError {
+    /// 缓冲区长度不足
+    BufferTooSmall {
+        /// 需要的字节数
+        required: usize,
+        /// 实际的字节数
+        available: usize,
+    },
+    /// 负载长度超出数据包长度字段的范围
+    PayloadTooLarge(usize),
+}
+
+impl core::fmt::Display for CodecError {
+    fn fmt(
+        &self,
+        f: &mut core::fmt::Formatter<'_>,
+    ) -> core::fmt::Result {
+        match self {
+            CodecError::BufferTooSmall {
+                required,
+                available,
+            } => write!(
+                f,
+                "字节数组长度不足: 需要 {required} 字节, 实际 {available} 字节"
+            ),
+            CodecError::PayloadTooLarge(length) => {
+                write!(f, "负载过大: {length} 字节")
+            }
+        }
+    }
+}
+
+/// 文件头和数据包头的字节序
+///
+/// 魔术数按文件的字节序存储，读取时根据魔术数的字节排列自动识别。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "std",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum ByteOrder {
+    /// 小端序（默认）
+    #[default]
+    Little,
+    /// 大端序，用于与大端嵌入式设备生成的文件互通
+    Big,
+}
+
+impl ByteOrder {
+    /// 根据文件开头的魔术数识别字节序，不是有效的魔术数时返回 `None`
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        let magic = bytes.get(0..4)?;
+        if magic
+            == constants::PCAP_MAGIC_NUMBER.to_le_bytes()
+        {
+            Some(ByteOrder::Little)
+        } else if magic
+            == constants::PCAP_MAGIC_NUMBER.to_be_bytes()
+        {
+            Some(ByteOrder::Big)
+        } else {
+            None
+        }
+    }
+
+    /// 按字节序读取 u16，调用方保证长度足够
+    pub fn read_u16(
+        self,
+        bytes: &[u8],
+        offset: usize,
+    ) -> u16 {
+        let value = [bytes[offset], bytes[offset + 1]];
+        match self {
+            ByteOrder::Little => u16::from_le_bytes(value),
+            ByteOrder::Big => u16::from_be_bytes(value),
+        }
+    }
+
+    /// 按字节序读取 u32，调用方保证长度足够
+    pub fn read_u32(
+        self,
+        bytes: &[u8],
+        offset: usize,
+    ) -> u32 {
+        let value = [
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ];
+        match self {
+            ByteOrder::Little => u32::from_le_bytes(value),
+            ByteOrder::Big => u32::from_be_bytes(value),
+        }
+    }
+
+    fn u16_bytes(self, value: u16) -> [u8; 2] {
+        match self {
+            ByteOrder::Little => value.to_le_bytes(),
+            ByteOrder::Big => value.to_be_bytes(),
+        }
+    }
+
+    fn u32_bytes(self, value: u32) -> [u8; 4] {
+        match self {
+            ByteOrder::Little => value.to_le_bytes(),
+            ByteOrder::Big => value.to_be_bytes(),
+        }
+    }
+}
+
+/// 文件头字段
+///
+/// 链路类型以文件中存储的数值表示，0 表示未指定；
+/// 次版本号达到 `LINK_TYPE_MINOR_VERSION` 时才写出链路类型字段。
+/// 次版本号达到 `EXTENSION_MINOR_VERSION` 时链路类型之后是扩展块区域的长度，
+/// 扩展块区域紧跟在固定部分之后，由 `ExtensionBlocks` 逐个解析。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileHeader {
+    /// 魔术数
+    pub magic_number: u32,
+    /// 主版本号
+    pub major_version: u16,
+    /// 次版本号
+    pub minor_version: u16,
+    /// 时区偏移量（秒）
+    pub timezone_offset: i32,
+    /// 时间戳精度（纳秒）
+    pub timestamp_accuracy: u32,
+    /// 链路类型数值
+    pub link_type: u32,
+    /// 扩展块区域的长度（字节）
+    pub extensions_length: u32,
+    /// 文件的字节序
+    pub byte_order: ByteOrder,
+}
+
+impl FileHeader {
+    /// 创建不带链路类型的基础文件头
+    pub const fn new(timezone_offset: i32) -> Self {
+        Self {
+            magic_number: constants::PCAP_MAGIC_NUMBER,
+            major_version: constants::MAJOR_VERSION,
+            minor_version: constants::MINOR_VERSION,
+            timezone_offset,
+            timestamp_accuracy: DEFAULT_TIMESTAMP_ACCURACY,
+            link_type: 0,
+            extensions_length: 0,
+            byte_order: ByteOrder::Little,
+        }
+    }
+
+    /// 创建带链路类型的文件头，链路类型为 0 时等同于 `new`
+    pub const fn with_link_type(
+        timezone_offset: i32,
+        link_type: u32,
+    ) -> Self {
+        let mut header = Self::new(timezone_offset);
+        if link_type != 0 {
+            header.minor_version =
+                constants::LINK_TYPE_MINOR_VERSION;
+            header.link_type = link_type;
+        }
+        header
+    }
+
+    /// 设置文件的字节序
+    pub const fn with_byte_order(
+        mut self,
+        byte_order: ByteOrder,
+    ) -> Self {
+        self.byte_order = byte_order;
+        self
+    }
+
+    /// 文件头是否包含链路类型字段
+    #[inline]
+    pub const fn has_link_type_field(&self) -> bool {
+        self.minor_version
+            >= constants::LINK_TYPE_MINOR_VERSION
+    }
+
+    /// 文件头是否包含扩展块区域
+    ///
+    /// 只有 2.6 版本携带扩展块区域，更高的未知版本按 2.5 的布局解析。
+    #[inline]
+    pub const fn has_extension_area(&self) -> bool {
+        self.minor_version
+            == constants::EXTENSION_MINOR_VERSION
+    }
+
+    /// 固定部分的长度（字节），不含扩展块区域
+    #[inline]
+    pub const fn fixed_len(&self) -> usize {
+        if self.has_extension_area() {
+            EXTENSIBLE_FILE_HEADER_SIZE
+        } else if self.has_link_type_field() {
+            EXTENDED_FILE_HEADER_SIZE
+        } else {
+            FILE_HEADER_SIZE
+        }
+    }
+
+    /// 编码后的长度（字节），包含扩展块区域，即数据区的起始偏移
+    #[inline]
+    pub const fn encoded_len(&self) -> usize {
+        if self.has_extension_area() {
+            self.fixed_len()
+                + self.extensions_length as usize
+        } else {
+            self.fixed_len()
+        }
+    }
+
+    /// 把固定部分写入缓冲区开头，返回写入的字节数
+    ///
+    /// 扩展块区域由调用方通过 `encode_extension_block` 紧接着写入，
+    /// 总长度需与 `extensions_length` 一致。
+    pub fn encode(
+        &self,
+        out: &mut [u8],
+    ) -> Result<usize, CodecError> {
+        let length = self.fixed_len();
+        ensure_len(out, length)?;
+
+        let order = self.byte_order;
+        out[0..4].copy_from_slice(
+            &order.u32_bytes(self.magic_number),
+        );
+        out[4..6].copy_from_slice(
+            &order.u16_bytes(self.major_version),
+        );
+        out[6..8].copy_from_slice(
+            &order.u16_bytes(self.minor_version),
+        );
+        out[8..12].copy_from_slice(
+            &order.u32_bytes(self.timezone_offset as u32),
+        );
+        out[12..16].copy_from_slice(
+            &order.u32_bytes(self.timestamp_accuracy),
+        );
+        if self.has_link_type_field() {
+            out[16..20].copy_from_slice(
+                &order.u32_bytes(self.link_type),
+            );
+        }
+        if self.has_extension_area() {
+            out[20..24].copy_from_slice(
+                &order.u32_bytes(self.extensions_length),
+            );
+        }
+        Ok(length)
+    }
+
+    /// 从字节数组开头解析文件头的固定部分，不检查魔术数和版本
+    ///
+    /// 字节序由魔术数识别，魔术数无效时按小端序解析。
+    /// 扩展块区域不在此解析，可通过 `extension_blocks` 遍历。
+    pub fn decode(
+        bytes: &[u8],
+    ) -> Result<Self, CodecError> {
+        ensure_len(bytes, FILE_HEADER_SIZE)?;
+
+        let order = ByteOrder::detect(bytes)
+            .unwrap_or(ByteOrder::Little);
+        let mut header = Self {
+            magic_number: order.read_u32(bytes, 0),
+            major_version: order.read_u16(bytes, 4),
+            minor_version: order.read_u16(bytes, 6),
+            timezone_offset: order.read_u32(bytes, 8)
+                as i32,
+            timestamp_accuracy: order.read_u32(bytes, 12),
+            link_type: 0,
+            extensions_length: 0,
+            byte_order: order,
+        };
+        if header.has_link_type_field() {
+            ensure_len(bytes, EXTENDED_FILE_HEADER_SIZE)?;
+            header.link_type = order.read_u32(bytes, 16);
+        }
+        if header.has_extension_area() {
+            ensure_len(bytes, EXTENSIBLE_FILE_HEADER_SIZE)?;
+            header.extensions_length =
+                order.read_u32(bytes, 20);
+        }
+        Ok(header)
+    }
+
+    /// 遍历扩展块区域
+    ///
+    /// `bytes` 为从文件开头起的字节，需包含完整的扩展块区域；
+    /// 不足时遍历到截断处返回错误。没有扩展块区域时不产生任何扩展块。
+    pub fn extension_blocks<'a>(
+        &self,
+        bytes: &'a [u8],
+    ) -> ExtensionBlocks<'a> {
+        if !self.has_extension_area() {
+            return ExtensionBlocks {
+                bytes: &[],
+                truncated: None,
+                order: self.byte_order,
+            };
+        }
+
+        let start = self.fixed_len().min(bytes.len());
+        let end = self.encoded_len().min(bytes.len());
+        let truncated =
+            ensure_len(bytes, self.encoded_len()).err();
+        ExtensionBlocks {
+            bytes: &bytes[start..end],
+            truncated,
+            order: self.byte_order,
+        }
+    }
+
+    /// 魔术数是否有效
+    ///
+    /// 魔术数有效而版本号无效时，文件来自不支持的格式版本，而不是损坏的文件。
+    pub const fn has_valid_magic(&self) -> bool {
+        self.magic_number == constants::PCAP_MAGIC_NUMBER
+    }
+
+    /// 魔术数和版本号是否有效
+    pub const fn is_valid(&self) -> bool {
+        self.magic_number == constants::PCAP_MAGIC_NUMBER
+            && self.major_version
+                == constants::MAJOR_VERSION
+            && (self.minor_version
+                == constants::MINOR_VERSION
+                || self.minor_version
+                    == constants::LINK_TYPE_MINOR_VERSION
+                || self.minor_version
+                    == constants::EXTENSION_MINOR_VERSION)
+    }
+}
+
+/// 文件头扩展块
+///
+/// 由 2 字节类型码、2 字节值长度和值组成，按文件的字节序存储。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtensionBlock<'a> {
+    /// 类型码
+    pub kind: u16,
+    /// 值
+    pub value: &'a [u8],
+}
+
+impl ExtensionBlock<'_> {
+    /// 编码后的长度（字节）
+    #[inline]
+    pub const fn encoded_len(&self) -> usize {
+        EXTENSION_BLOCK_HEADER_SIZE + self.value.len()
+    }
+}
+
+/// 扩展块区域的迭代器
+///
+/// 扩展块不完整时产生一个错误后结束。
+#[derive(Debug, Clone)]
+pub struct ExtensionBlocks<'a> {
+    bytes: &'a [u8],
+    /// 给定的字节不包含完整的扩展块区域时，遍历结束前报告的错误
+    truncated: Option<CodecError>,
+    order: ByteOrder,
+}
+
+impl<'a> Iterator for ExtensionBlocks<'a> {
+    type Item = Result<ExtensionBlock<'a>, CodecError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.bytes;
+        if bytes.is_empty() {
+            return self.truncated.take().map(Err);
+        }
+
+        let end =
+            if bytes.len() < EXTENSION_BLOCK_HEADER_SIZE {
+                EXTENSION_BLOCK_HEADER_SIZE
+            } else {
+                EXTENSION_BLOCK_HEADER_SIZE
+                    + self.order.read_u16(bytes, 2) as usize
+            };
+        if let Err(e) = ensure_len(bytes, end) {
+            self.bytes = &[];
+            self.truncated = None;
+            return Some(Err(e));
+        }
+        let kind = self.order.read_u16(bytes, 0);
+        self.bytes = &bytes[end..];
+        Some(Ok(ExtensionBlock {
+            kind,
+            value: &bytes[EXTENSION_BLOCK_HEADER_SIZE..end],
+        }))
+    }
+}
+
+/// 把扩展块写入缓冲区开头，返回写入的字节数
+///
+/// 值超过 65535 字节时返回 `CodecError::PayloadTooLarge`。
+pub fn encode_extension_block(
+    kind: u16,
+    value: &[u8],
+    order: ByteOrder,
+    out: &mut [u8],
+) -> Result<usize, CodecError> {
+    let length =
+        u16::try_from(value.len()).map_err(|_| {
+            CodecError::PayloadTooLarge(value.len())
+        })?;
+    let total = EXTENSION_BLOCK_HEADER_SIZE + value.len();
+    ensure_len(out, total)?;
+
+    out[0..2].copy_from_slice(&order.u16_bytes(kind));
+    out[2..4].copy_from_slice(&order.u16_bytes(length));
+    out[EXTENSION_BLOCK_HEADER_SIZE..total]
+        .copy_from_slice(value);
+    Ok(total)
+}
+
+/// 数据包头字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketHeader {
+    /// 时间戳（秒）
+    pub timestamp_seconds: u32,
+    /// 时间戳（纳秒）
+    pub timestamp_nanoseconds: u32,
+    /// 数据包长度
+    pub packet_length: u32,
+    /// 校验和
+    pub checksum: u32,
+}
+
+impl PacketHeader {
+    /// 为负载创建数据包头，计算长度和校验和
+    pub fn for_payload(
+        timestamp_seconds: u32,
+        timestamp_nanoseconds: u32,
+        payload: &[u8],
+    ) -> Result<Self, CodecError> {
+        let packet_length = u32::try_from(payload.len())
+            .map_err(|_| {
+                CodecError::PayloadTooLarge(payload.len())
+            })?;
+        Ok(Self {
+            timestamp_seconds,
+            timestamp_nanoseconds,
+            packet_length,
+            checksum: crc32(payload),
+        })
+    }
+
+    /// 按小端序编码为字节数组
+    pub fn to_bytes(&self) -> [u8; PACKET_HEADER_SIZE] {
+        self.to_bytes_with_order(ByteOrder::Little)
+    }
+
+    /// 按指定字节序编码为字节数组
+    pub fn to_bytes_with_order(
+        &self,
+        order: ByteOrder,
+    ) -> [u8; PACKET_HEADER_SIZE] {
+        let mut bytes = [0u8; PACKET_HEADER_SIZE];
+        bytes[0..4].copy_from_slice(
+            &order.u32_bytes(self.timestamp_seconds),
+        );
+        bytes[4..8].copy_from_slice(
+            &order.u32_bytes(self.timestamp_nanoseconds),
+        );
+        bytes[8..12].copy_from_slice(
+            &order.u32_bytes(self.packet_length),
+        );
+        bytes[12..16].copy_from_slice(
+            &order.u32_bytes(self.checksum),
+        );
+        bytes
+    }
+
+    /// 从字节数组开头按小端序解析数据包头
+    pub fn decode(
+        bytes: &[u8],
+    ) -> Result<Self, CodecError> {
+        Self::decode_with_order(bytes, ByteOrder::Little)
+    }
+
+    /// 从字节数组开头按指定字节序解析数据包头
+    pub fn decode_with_order(
+        bytes: &[u8],
+        order: ByteOrder,
+    ) -> Result<Self, CodecError> {
+        ensure_len(bytes, PACKET_HEADER_SIZE)?;
+        Ok(Self {
+            timestamp_seconds: order.read_u32(bytes, 0),
+            timestamp_nanoseconds: order.read_u32(bytes, 4),
+            packet_length: order.read_u32(bytes, 8),
+            checksum: order.read_u32(bytes, 12),
+        })
+    }
+
+    /// 是否为心跳包：负载长度为 0 且校验和字段为 [`HEARTBEAT_CHECKSUM`]
+    pub fn is_heartbeat(&self) -> bool {
+        self.packet_length == 0
+            && self.checksum == HEARTBEAT_CHECKSUM
+    }
+
+    /// 负载的长度和校验和是否与头部一致，心跳包的负载须为空
+    pub fn matches(&self, payload: &[u8]) -> bool {
+        payload.len() == self.packet_length as usize
+            && (self.is_heartbeat()
+                || crc32(payload) == self.checksum)
+    }
+}
+
+/// 把数据包头和负载写入缓冲区开头，返回写入的字节数
+pub fn encode_packet(
+    timestamp_seconds: u32,
+    timestamp_nanoseconds: u32,
+    payload: &[u8],
+    out: &mut [u8],
+) -> Result<usize, CodecError> {
+    let header = PacketHeader::for_payload(
+        timestamp_seconds,
+        timestamp_nanoseconds,
+        payload,
+    )?;
+    let length = PACKET_HEADER_SIZE + payload.len();
+    ensure_len(out, length)?;
+
+    out[..PACKET_HEADER_SIZE]
+        .copy_from_slice(&header.to_bytes());
+    out[PACKET_HEADER_SIZE..length]
+        .copy_from_slice(payload);
+    Ok(length)
+}
+
+/// 计算CRC32校验和（IEEE 802.3 多项式）
+///
+/// 由 crc32fast 选择实现：启用 `std` 特性时在运行时检测 CPU，x86_64 上使用 PCLMULQDQ、
+/// aarch64 上使用 CRC 指令，不支持时回退到查表实现；`no_std` 下只能按编译目标的
+/// `target-feature` 选择。各实现的结果完全一致。
+#[inline]
+pub fn crc32(data: &[u8]) -> u32 {
+    crc32fast::hash(data)
+}
+
+fn ensure_len(
+    bytes: &[u8],
+    required: usize,
+) -> Result<(), CodecError> {
+    if bytes.len() < required {
+        return Err(CodecError::BufferTooSmall {
+            required,
+            available: bytes.len(),
+        });
+    }
+    Ok(())
+}
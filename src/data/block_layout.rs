@@ -0,0 +1,329 @@
+//! 分块文件布局
+//!
+//! 启用分块布局的数据文件在文件头之后按固定大小划分为块，第 k 个块从数据区起始偏移加 k 倍块大小处开始。
+//! 每个块依次存放若干完整的数据包记录、填充字节和 32 字节的块尾，数据包记录不跨块；
+//! 最后一个块不填充，块尾紧跟在最后一个数据包记录之后，正在写入的块还没有块尾。
+//!
+//! 块尾依次为 4 字节魔术数 `PBLK`、数据包数量、记录字节数、记录字节的 CRC32、最小时间戳和最大时间戳，
+//! 除魔术数外按文件的字节序存储。损坏只影响所在的块：读取时跳过结构损坏的块，从下一个块继续；
+//! 按时间范围读取时只读取各块的块尾，无需外部索引即可跳过不相关的块。
+
+use log::warn;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::business::config::ReaderConfig;
+use crate::core::ByteOrder;
+use crate::data::file_reader::PcapFileReader;
+use crate::data::models::ValidatedPacket;
+use crate::foundation::error::{PcapError, PcapResult};
+use crate::foundation::utils::calculate_crc32;
+
+/// 块尾大小（字节）
+pub const BLOCK_FOOTER_SIZE: usize = 32;
+/// 块尾魔术数
+pub const BLOCK_FOOTER_MAGIC: [u8; 4] = *b"PBLK";
+/// 最小块大小（字节）
+pub const MIN_BLOCK_SIZE: u64 = 4 * 1024;
+/// 最大块大小（字节）
+pub const MAX_BLOCK_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// 块尾
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockFooter {
+    /// 块中的数据包数量
+    pub packet_count: u32,
+    /// 块中数据包记录的字节数，不含填充和块尾
+    pub data_length: u32,
+    /// 数据包记录字节的 CRC32
+    pub checksum: u32,
+    /// 块中最小的时间戳（纳秒）
+    pub min_timestamp_ns: u64,
+    /// 块中最大的时间戳（纳秒）
+    pub max_timestamp_ns: u64,
+}
+
+impl BlockFooter {
+    /// 按字节序编码
+    pub fn to_bytes(
+        &self,
+        order: ByteOrder,
+    ) -> [u8; BLOCK_FOOTER_SIZE] {
+        let mut bytes = [0u8; BLOCK_FOOTER_SIZE];
+        bytes[..4].copy_from_slice(&BLOCK_FOOTER_MAGIC);
+        let (u32s, u64s) = match order {
+            ByteOrder::Little => (
+                [
+                    self.packet_count.to_le_bytes(),
+                    self.data_length.to_le_bytes(),
+                    self.checksum.to_le_bytes(),
+                ],
+                [
+                    self.min_timestamp_ns.to_le_bytes(),
+                    self.max_timestamp_ns.to_le_bytes(),
+                ],
+            ),
+            ByteOrder::Big => (
+                [
+                    self.packet_count.to_be_bytes(),
+                    self.data_length.to_be_bytes(),
+                    self.checksum.to_be_bytes(),
+                ],
+                [
+                    self.min_timestamp_ns.to_be_bytes(),
+                    self.max_timestamp_ns.to_be_bytes(),
+                ],
+            ),
+        };
+        for (i, field) in u32s.iter().enumerate() {
+            bytes[4 + i * 4..8 + i * 4]
+                .copy_from_slice(field);
+        }
+        for (i, field) in u64s.iter().enumerate() {
+            bytes[16 + i * 8..24 + i * 8]
+                .copy_from_slice(field);
+        }
+        bytes
+    }
+
+    /// 按字节序解码，长度不足或魔术数不匹配时返回 `None`
+    pub fn from_bytes(
+        bytes: &[u8],
+        order: ByteOrder,
+    ) -> Option<Self> {
+        if bytes.len() < BLOCK_FOOTER_SIZE
+            || bytes[..4] != BLOCK_FOOTER_MAGIC
+        {
+            return None;
+        }
+        let read_u64 = |offset: usize| {
+            let mut field = [0u8; 8];
+            field.copy_from_slice(
+                &bytes[offset..offset + 8],
+            );
+            match order {
+                ByteOrder::Little => {
+                    u64::from_le_bytes(field)
+                }
+                ByteOrder::Big => u64::from_be_bytes(field),
+            }
+        };
+        Some(Self {
+            packet_count: order.read_u32(bytes, 4),
+            data_length: order.read_u32(bytes, 8),
+            checksum: order.read_u32(bytes, 12),
+            min_timestamp_ns: read_u64(16),
+            max_timestamp_ns: read_u64(24),
+        })
+    }
+
+    /// 块的时间范围是否与 `[start_ns, end_ns]` 重叠
+    pub fn overlaps(
+        &self,
+        start_ns: u64,
+        end_ns: u64,
+    ) -> bool {
+        self.min_timestamp_ns <= end_ns
+            && self.max_timestamp_ns >= start_ns
+    }
+}
+
+/// 已写完的块
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSummary {
+    /// 块在文件中的序号
+    pub index: u64,
+    /// 块的起始偏移
+    pub offset: u64,
+    /// 块尾
+    pub footer: BlockFooter,
+}
+
+impl BlockSummary {
+    /// 块中数据包记录的结束偏移
+    pub fn data_end(&self) -> u64 {
+        self.offset + self.footer.data_length as u64
+    }
+}
+
+/// 读取位置所在块的范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BlockBounds {
+    /// 块的起始偏移
+    pub start: u64,
+    /// 块中数据包记录的结束偏移
+    pub data_end: u64,
+    /// 块尾，正在写入的块为 `None`
+    pub footer: Option<BlockFooter>,
+}
+
+/// 读取从 `block_start` 开始的块的范围，读取后读取位置不确定
+///
+/// 完整的块在块大小的末尾有块尾；文件中最后一个块的块尾紧跟在数据包记录之后，
+/// 没有块尾（正在写入）时记录一直到 `file_size`。完整的块的块尾无效时返回
+/// `io::ErrorKind::InvalidData` 错误。
+pub(crate) fn read_bounds<R: Read + Seek>(
+    reader: &mut R,
+    block_start: u64,
+    block_size: u64,
+    file_size: u64,
+    order: ByteOrder,
+) -> io::Result<BlockBounds> {
+    let footer_size = BLOCK_FOOTER_SIZE as u64;
+    let mut read_footer = |position: u64| {
+        let mut bytes = [0u8; BLOCK_FOOTER_SIZE];
+        reader.seek(SeekFrom::Start(position))?;
+        reader.read_exact(&mut bytes)?;
+        Ok::<_, io::Error>(BlockFooter::from_bytes(
+            &bytes, order,
+        ))
+    };
+
+    if block_start + block_size <= file_size {
+        let footer = read_footer(
+            block_start + block_size - footer_size,
+        )?
+        .filter(|footer| {
+            footer.data_length as u64
+                <= block_size - footer_size
+        })
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "块尾无效，块起始偏移 {block_start}"
+                ),
+            )
+        })?;
+        return Ok(BlockBounds {
+            start: block_start,
+            data_end: block_start
+                + footer.data_length as u64,
+            footer: Some(footer),
+        });
+    }
+
+    // 最后一个块：块尾的记录字节数与块尾位置吻合时才视为已写完
+    if file_size >= block_start + footer_size {
+        let footer_start = file_size - footer_size;
+        if let Some(footer) = read_footer(footer_start)?
+            .filter(|footer| {
+                block_start + footer.data_length as u64
+                    == footer_start
+            })
+        {
+            return Ok(BlockBounds {
+                start: block_start,
+                data_end: footer_start,
+                footer: Some(footer),
+            });
+        }
+    }
+    Ok(BlockBounds {
+        start: block_start,
+        data_end: file_size,
+        footer: None,
+    })
+}
+
+/// 列出数据文件中已写完的块，只读取块尾
+///
+/// 没有启用分块布局的文件返回空列表。块尾无效的块记录警告后跳过。
+pub fn scan_blocks<P: AsRef<Path>>(
+    path: P,
+) -> PcapResult<Vec<BlockSummary>> {
+    let mut reader =
+        PcapFileReader::new(ReaderConfig::default());
+    reader.open(path)?;
+    reader.blocks()
+}
+
+/// 校验数据文件中每个已写完的块的 CRC32，返回校验失败的块
+///
+/// 只读取各块的数据包记录字节，不解析数据包。
+pub fn verify_blocks<P: AsRef<Path>>(
+    path: P,
+) -> PcapResult<Vec<BlockSummary>> {
+    let path = path.as_ref();
+    let blocks = scan_blocks(path)?;
+    let mut file = BufReader::new(
+        File::open(path).map_err(PcapError::Io)?,
+    );
+    let mut data = Vec::new();
+    let mut corrupted = Vec::new();
+    for block in blocks {
+        data.resize(block.footer.data_length as usize, 0);
+        file.seek(SeekFrom::Start(block.offset))
+            .and_then(|_| file.read_exact(&mut data))
+            .map_err(PcapError::Io)?;
+        if calculate_crc32(&data) != block.footer.checksum {
+            warn!(
+                "块 {} 的校验和不匹配: {path:?}，起始偏移 {}",
+                block.index, block.offset
+            );
+            corrupted.push(block);
+        }
+    }
+    Ok(corrupted)
+}
+
+/// 读取数据文件中时间戳位于 `[start_ns, end_ns]` 内的数据包，不需要索引
+///
+/// 启用分块布局的文件按块尾记录的时间范围跳过不相关的块，正在写入、还没有块尾的块逐个读取；
+/// 没有启用分块布局的文件顺序读取全部数据包。
+pub fn read_blocks_in_range<P: AsRef<Path>>(
+    path: P,
+    start_ns: u64,
+    end_ns: u64,
+) -> PcapResult<Vec<ValidatedPacket>> {
+    let mut reader =
+        PcapFileReader::new(ReaderConfig::default());
+    reader.open(path)?;
+    let in_range = |packet: &ValidatedPacket| {
+        (start_ns..=end_ns)
+            .contains(&packet.packet.get_timestamp_ns())
+    };
+
+    let mut packets = Vec::new();
+    let Some(block_size) = reader.block_size() else {
+        while let Some(packet) = reader.read_packet()? {
+            if in_range(&packet) {
+                packets.push(packet);
+            }
+        }
+        return Ok(packets);
+    };
+
+    let blocks = reader.blocks()?;
+    for block in &blocks {
+        if !block.footer.overlaps(start_ns, end_ns) {
+            continue;
+        }
+        reader.seek_to_block(block.offset)?;
+        while reader.position() < block.data_end() {
+            match reader.read_packet()? {
+                Some(packet) if in_range(&packet) => {
+                    packets.push(packet)
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+    }
+
+    // 最后一个已写完的块之后的数据包还没有块尾
+    let tail_start = blocks
+        .last()
+        .map(|block| block.offset + block_size)
+        .unwrap_or(reader.data_offset());
+    if tail_start < reader.file_size() {
+        reader.seek_to_block(tail_start)?;
+        while let Some(packet) = reader.read_packet()? {
+            if in_range(&packet) {
+                packets.push(packet);
+            }
+        }
+    }
+    Ok(packets)
+}
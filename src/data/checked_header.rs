@@ -0,0 +1,55 @@
+//! 带头部校验和的数据包头部
+//!
+//! 第 3 版记录格式（`RecordFormat::CheckedHeader`）在 16 字节固定头部之后追加 4 字节头部校验和，
+//! 即固定头部（时间戳、负载长度和负载校验和）的 CRC32，按文件的字节序存储。
+//! 负载长度字段的位翻转会在读取头部时被发现，不会把后续数据错当成数据包记录。
+
+use crate::core::ByteOrder;
+use crate::data::models::DataPacketHeader;
+use crate::foundation::utils::calculate_crc32;
+
+/// 头部校验和长度
+const HEADER_CHECKSUM_LEN: usize = 4;
+
+/// 头部大小（字节）
+pub(crate) const HEADER_SIZE: usize =
+    DataPacketHeader::HEADER_SIZE + HEADER_CHECKSUM_LEN;
+
+/// 编码头部
+pub(crate) fn encode(
+    header: &DataPacketHeader,
+    order: ByteOrder,
+) -> Vec<u8> {
+    let mut bytes = header.to_bytes_with_order(order);
+    let checksum = calculate_crc32(&bytes);
+    bytes.extend_from_slice(&match order {
+        ByteOrder::Little => checksum.to_le_bytes(),
+        ByteOrder::Big => checksum.to_be_bytes(),
+    });
+    bytes
+}
+
+/// 校验并解码头部，`bytes` 须为 `HEADER_SIZE` 字节
+///
+/// 头部校验和不匹配时返回错误说明。
+pub(crate) fn decode(
+    bytes: &[u8],
+    order: ByteOrder,
+) -> Result<DataPacketHeader, String> {
+    if bytes.len() != HEADER_SIZE {
+        return Err(format!(
+            "包头长度应为 {HEADER_SIZE} 字节: {}",
+            bytes.len()
+        ));
+    }
+    let fixed = &bytes[..DataPacketHeader::HEADER_SIZE];
+    let expected = order
+        .read_u32(bytes, DataPacketHeader::HEADER_SIZE);
+    let actual = calculate_crc32(fixed);
+    if expected != actual {
+        return Err(format!(
+            "包头校验和不匹配: 期望 0x{expected:08X}，实际 0x{actual:08X}"
+        ));
+    }
+    DataPacketHeader::from_bytes_with_order(fixed, order)
+}
@@ -0,0 +1,172 @@
+//! 时间戳差值编码的数据包头部
+//!
+//! 第 2 版记录格式（`RecordFormat::DeltaTimestamp`）的数据包头部依次为：时间戳与同一文件中
+//! 前一个数据包时间戳之差（ZigZag 编码的 LEB128 变长整数，第一个数据包与 0 相比）、
+//! 负载长度（LEB128 变长整数）和 4 字节 CRC32 校验和（按文件的字节序），之后是负载。
+//! 数据包间隔在毫秒级以下、负载小于 128 字节时头部只占 7~8 字节，而固定头部为 16 字节。
+
+use std::io::{self, Read};
+
+use crate::core::ByteOrder;
+
+/// 时间戳差值的最大编码长度
+const MAX_DELTA_LEN: usize = 10;
+/// 负载长度的最大编码长度
+const MAX_LENGTH_LEN: usize = 5;
+/// 校验和长度
+const CHECKSUM_LEN: usize = 4;
+
+/// 头部的最大编码长度（字节）
+pub(crate) const MAX_HEADER_SIZE: usize =
+    MAX_DELTA_LEN + MAX_LENGTH_LEN + CHECKSUM_LEN;
+
+/// 解码后的差值头部
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DeltaHeader {
+    /// 与前一个数据包时间戳之差（纳秒）
+    pub delta_ns: i64,
+    /// 负载长度
+    pub packet_length: u32,
+    /// 校验和
+    pub checksum: u32,
+    /// 头部的编码长度
+    pub encoded_len: usize,
+}
+
+impl DeltaHeader {
+    /// 由前一个数据包的时间戳还原本数据包的时间戳
+    pub(crate) fn timestamp_ns(
+        &self,
+        previous_ns: u64,
+    ) -> u64 {
+        previous_ns.wrapping_add(self.delta_ns as u64)
+    }
+}
+
+/// 编码头部
+pub(crate) fn encode(
+    timestamp_ns: u64,
+    previous_ns: u64,
+    packet_length: u32,
+    checksum: u32,
+    order: ByteOrder,
+) -> Vec<u8> {
+    let delta =
+        timestamp_ns.wrapping_sub(previous_ns) as i64;
+    let mut bytes = Vec::with_capacity(MAX_HEADER_SIZE);
+    write_varint(&mut bytes, zigzag_encode(delta));
+    write_varint(&mut bytes, packet_length as u64);
+    bytes.extend_from_slice(&match order {
+        ByteOrder::Little => checksum.to_le_bytes(),
+        ByteOrder::Big => checksum.to_be_bytes(),
+    });
+    bytes
+}
+
+/// 编码后的头部长度（字节）
+pub(crate) fn encoded_len(
+    timestamp_ns: u64,
+    previous_ns: u64,
+    packet_length: u32,
+) -> usize {
+    let delta =
+        timestamp_ns.wrapping_sub(previous_ns) as i64;
+    varint_len(zigzag_encode(delta))
+        + varint_len(packet_length as u64)
+        + CHECKSUM_LEN
+}
+
+/// 读取并解码头部
+///
+/// 没有剩余字节或头部不完整时返回 `Ok(None)`；变长整数超出长度上限时返回
+/// `io::ErrorKind::InvalidData` 错误。
+pub(crate) fn read<R: Read>(
+    reader: &mut R,
+    order: ByteOrder,
+) -> io::Result<Option<DeltaHeader>> {
+    let Some((delta, delta_len)) =
+        read_varint(reader, MAX_DELTA_LEN)?
+    else {
+        return Ok(None);
+    };
+    let Some((length, length_len)) =
+        read_varint(reader, MAX_LENGTH_LEN)?
+    else {
+        return Ok(None);
+    };
+    let packet_length =
+        u32::try_from(length).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("负载长度超出范围: {length}"),
+            )
+        })?;
+
+    let mut checksum = [0u8; CHECKSUM_LEN];
+    match reader.read_exact(&mut checksum) {
+        Ok(()) => {}
+        Err(e)
+            if e.kind() == io::ErrorKind::UnexpectedEof =>
+        {
+            return Ok(None)
+        }
+        Err(e) => return Err(e),
+    }
+
+    Ok(Some(DeltaHeader {
+        delta_ns: zigzag_decode(delta),
+        packet_length,
+        checksum: order.read_u32(&checksum, 0),
+        encoded_len: delta_len + length_len + CHECKSUM_LEN,
+    }))
+}
+
+fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn zigzag_decode(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn varint_len(value: u64) -> usize {
+    let bits = 64 - value.leading_zeros() as usize;
+    bits.max(1).div_ceil(7)
+}
+
+/// 读取 LEB128 变长整数，返回数值和编码长度，字节不足时返回 `Ok(None)`
+fn read_varint<R: Read>(
+    reader: &mut R,
+    max_len: usize,
+) -> io::Result<Option<(u64, usize)>> {
+    let mut value = 0u64;
+    for index in 0..max_len {
+        let mut byte = [0u8; 1];
+        match reader.read_exact(&mut byte) {
+            Ok(()) => {}
+            Err(e)
+                if e.kind()
+                    == io::ErrorKind::UnexpectedEof =>
+            {
+                return Ok(None)
+            }
+            Err(e) => return Err(e),
+        }
+        value |= ((byte[0] & 0x7F) as u64) << (7 * index);
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some((value, index + 1)));
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("变长整数超过 {max_len} 字节"),
+    ))
+}
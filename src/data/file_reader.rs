@@ -1,15 +1,24 @@
-use log::{debug, info};
+use log::{debug, info, warn};
 use std::fs::File;
 use std::io::{self, BufReader, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 
 use crate::business::config::ReaderConfig;
+use crate::core::{ByteOrder, FileHeader};
+use crate::data::block_layout::{
+    self, BlockBounds, BlockSummary,
+};
 use crate::data::models::{
-    DataPacket, DataPacketHeader, PcapFileHeader,
-    ValidatedPacket,
+    DataPacket, DataPacketHeader, HeaderExtension,
+    LinkType, PcapFileHeader, RecordFormat,
+    ValidatedPacket, ValidationStatus,
+};
+use crate::data::payload_store::{
+    PayloadReference, PayloadStoreReader,
 };
+use crate::data::{checked_header, delta_header};
 use crate::foundation::error::{PcapError, PcapResult};
-use crate::foundation::utils::calculate_crc32;
+use crate::foundation::types::constants;
 
 // 错误消息常量
 const ERR_FILE_NOT_OPEN: &str = "文件未打开";
@@ -27,6 +36,28 @@ pub struct PcapFileReader {
     configuration: ReaderConfig,
     /// 当前读取位置（字节偏移）
     current_position: u64,
+    /// 已读取的数据包序号，用于抽样验证
+    read_sequence: u64,
+    /// 文件头引用的负载存储路径，文件的负载未去重时为 None
+    payload_store_path: Option<PathBuf>,
+    /// 负载存储，读取第一个数据包时打开
+    payload_store: Option<PayloadStoreReader>,
+    /// 数据包记录格式，由文件头决定
+    record_format: RecordFormat,
+    /// 上一个数据包的时间戳，差值编码以此为基准，未知时为 None
+    previous_timestamp_ns: Option<u64>,
+    /// 下一个数据包的时间戳，按索引条目定位时由索引给出
+    timestamp_hint: Option<u64>,
+    /// 分块布局的块大小，由文件头决定，未启用时为 None
+    block_size: Option<u64>,
+    /// 当前读取位置所在块的范围，跳转后重新确定
+    block: Option<BlockBounds>,
+    /// 最近读取的数据包记录的起始偏移
+    record_offset: u64,
+    /// 是否把末尾未写完的数据包视为暂时没有更多数据，见 `ReaderConfig::tolerate_partial_tail`
+    tolerate_partial_tail: bool,
+    /// 上次读取已到达文件末尾，下次读取前重新获取文件大小
+    at_tail: bool,
 }
 
 impl PcapFileReader {
@@ -39,8 +70,20 @@ impl PcapFileReader {
             file_size: 0,
             header: None,
             header_position: 0,
-            configuration,
             current_position: 0,
+            read_sequence: 0,
+            payload_store_path: None,
+            payload_store: None,
+            record_format: RecordFormat::Standard,
+            previous_timestamp_ns: None,
+            timestamp_hint: None,
+            block_size: None,
+            block: None,
+            record_offset: 0,
+            tolerate_partial_tail: configuration
+                .tolerate_partial_tail,
+            at_tail: false,
+            configuration,
         }
     }
 
@@ -70,13 +113,24 @@ impl PcapFileReader {
         }
 
         let mut reader = BufReader::with_capacity(
-            self.configuration.buffer_size,
+            self.configuration.buffer_size.as_usize(),
             file,
         );
 
         // 读取并验证文件头
-        let header =
-            self.read_and_validate_header(&mut reader)?;
+        let header = self.read_and_validate_header(
+            &mut reader,
+            file_size,
+        )?;
+
+        let payload_store_path =
+            Self::payload_store_path(path, &header)?;
+        let record_format = header
+            .record_format()
+            .map_err(PcapError::CorruptedHeader)?;
+        let block_size = header
+            .block_size()
+            .map_err(PcapError::CorruptedHeader)?;
 
         self.file = Some(
             reader
@@ -85,13 +139,22 @@ impl PcapFileReader {
                 .map_err(PcapError::Io)?,
         );
         self.reader = Some(reader);
+        self.payload_store_path = payload_store_path;
+        self.payload_store = None;
+        self.record_format = record_format;
+        self.previous_timestamp_ns = Some(0);
+        self.timestamp_hint = None;
+        self.block_size = block_size;
+        self.block = None;
+        self.tolerate_partial_tail =
+            self.configuration.tolerate_partial_tail;
+        self.at_tail = false;
         self.file_path = Some(path.to_path_buf());
         self.file_size = file_size;
+        self.current_position = header.header_size() as u64; // 文件头后的位置
         self.header = Some(header);
         self.packet_count = 0;
         self.header_position = 0;
-        self.current_position =
-            PcapFileHeader::HEADER_SIZE as u64; // 文件头后的位置
 
         info!("成功打开PCAP文件: {path:?}");
         Ok(())
@@ -101,13 +164,53 @@ impl PcapFileReader {
     fn read_and_validate_header(
         &self,
         reader: &mut BufReader<File>,
+        file_size: u64,
     ) -> PcapResult<PcapFileHeader> {
         let mut header_bytes =
-            [0u8; PcapFileHeader::HEADER_SIZE];
+            vec![0u8; PcapFileHeader::HEADER_SIZE];
         reader
             .read_exact(&mut header_bytes)
             .map_err(PcapError::Io)?;
 
+        // 次版本号 5 及以上的文件头追加了链路类型字段，次版本号 6 再追加扩展块区域，
+        // 字节序由魔术数识别
+        let minor_version =
+            ByteOrder::detect(&header_bytes)
+                .unwrap_or_default()
+                .read_u16(&header_bytes, 6);
+        let fixed_size = if minor_version
+            == constants::EXTENSION_MINOR_VERSION
+        {
+            PcapFileHeader::EXTENSIBLE_HEADER_SIZE
+        } else if minor_version
+            >= constants::LINK_TYPE_MINOR_VERSION
+        {
+            PcapFileHeader::EXTENDED_HEADER_SIZE
+        } else {
+            PcapFileHeader::HEADER_SIZE
+        };
+        Self::read_header_part(
+            reader,
+            &mut header_bytes,
+            fixed_size,
+        )?;
+
+        let header_size = FileHeader::decode(&header_bytes)
+            .map(|header| header.encoded_len())
+            .unwrap_or(fixed_size);
+        if header_size as u64 > file_size {
+            return Err(PcapError::CorruptedHeader(
+                format!(
+                    "文件头扩展块长度超出文件大小: {header_size} 字节"
+                ),
+            ));
+        }
+        Self::read_header_part(
+            reader,
+            &mut header_bytes,
+            header_size,
+        )?;
+
         let header =
             PcapFileHeader::from_bytes(&header_bytes)
                 .map_err(|e| {
@@ -117,6 +220,15 @@ impl PcapFileReader {
                     ))
                 })?;
 
+        let codec_header = FileHeader::from(&header);
+        if codec_header.has_valid_magic()
+            && !codec_header.is_valid()
+        {
+            return Err(PcapError::UnsupportedVersion {
+                major: header.major_version,
+                minor: header.minor_version,
+            });
+        }
         if !header.is_valid() {
             return Err(PcapError::CorruptedHeader(
                 "无效的PCAP文件头".to_string(),
@@ -126,110 +238,480 @@ impl PcapFileReader {
         Ok(header)
     }
 
+    /// 文件头中负载存储扩展块指向的存储文件路径
+    ///
+    /// 存储文件须与数据文件位于同一目录，扩展块的值不是单纯的文件名时视为文件头损坏。
+    fn payload_store_path(
+        path: &Path,
+        header: &PcapFileHeader,
+    ) -> PcapResult<Option<PathBuf>> {
+        let Some(extension) = header
+            .find_extension(HeaderExtension::PAYLOAD_STORE)
+        else {
+            return Ok(None);
+        };
+        let name = std::str::from_utf8(extension.value())
+            .ok()
+            .filter(|name| {
+                !name.is_empty()
+                    && Path::new(name).file_name()
+                        == Some(name.as_ref())
+            })
+            .ok_or_else(|| {
+                PcapError::CorruptedHeader(
+                    "负载存储扩展块的文件名无效"
+                        .to_string(),
+                )
+            })?;
+        let directory =
+            path.parent().unwrap_or(Path::new(""));
+        Ok(Some(directory.join(name)))
+    }
+
+    /// 把文件头缓冲区补读到指定长度
+    fn read_header_part(
+        reader: &mut BufReader<File>,
+        header_bytes: &mut Vec<u8>,
+        length: usize,
+    ) -> PcapResult<()> {
+        let start = header_bytes.len();
+        if length > start {
+            header_bytes.resize(length, 0);
+            reader
+                .read_exact(&mut header_bytes[start..])
+                .map_err(PcapError::Io)?;
+        }
+        Ok(())
+    }
+
     /// 读取下一个数据包
     pub(crate) fn read_packet(
         &mut self,
     ) -> PcapResult<Option<ValidatedPacket>> {
-        let reader =
-            self.reader.as_mut().ok_or_else(|| {
-                PcapError::InvalidState(
-                    ERR_FILE_NOT_OPEN.to_string(),
-                )
-            })?;
+        self.read_packet_with(|length| vec![0u8; length])
+    }
 
-        // 检查是否还有足够空间读取包头
-        let remaining_bytes =
-            self.file_size - self.current_position;
-        if remaining_bytes
-            < DataPacketHeader::HEADER_SIZE as u64
-        {
-            return Ok(None); // 到达文件末尾
+    /// 读取下一个数据包，负载缓冲区由 `allocate` 按长度提供
+    ///
+    /// 容忍末尾未写完的数据包时，读到不完整的记录返回 `Ok(None)` 并停在该记录的起始位置，
+    /// 下次读取前重新获取文件大小，写入器写完该记录后即可读出。
+    pub(crate) fn read_packet_with<F>(
+        &mut self,
+        allocate: F,
+    ) -> PcapResult<Option<ValidatedPacket>>
+    where
+        F: FnOnce(usize) -> Vec<u8>,
+    {
+        if !self.tolerate_partial_tail {
+            return self.read_record_with(allocate);
+        }
+        if self.at_tail {
+            self.refresh_file_size()?;
         }
 
-        // 读取数据包头部
-        let mut header_bytes =
-            [0u8; DataPacketHeader::HEADER_SIZE];
-        match reader.read_exact(&mut header_bytes) {
-            Ok(_) => {}
-            Err(ref e)
-                if e.kind()
-                    == io::ErrorKind::UnexpectedEof =>
-            {
-                return Ok(None); // 到达文件末尾
+        let position = self.current_position;
+        let previous_timestamp_ns =
+            self.previous_timestamp_ns;
+        let timestamp_hint = self.timestamp_hint;
+        match self.read_record_with(allocate) {
+            Ok(Some(packet)) => {
+                self.at_tail = false;
+                Ok(Some(packet))
             }
-            Err(e) => return Err(PcapError::Io(e)),
+            Ok(None)
+            | Err(PcapError::PacketSizeExceedsRemainingBytes {
+                ..
+            }) => {
+                // 回到记录的起始位置，丢弃已读取的部分头部
+                self.seek_to(position)?;
+                self.previous_timestamp_ns =
+                    previous_timestamp_ns;
+                self.timestamp_hint = timestamp_hint;
+                self.at_tail = true;
+                Ok(None)
+            }
+            Err(e) => Err(e),
         }
+    }
 
-        let header =
-            DataPacketHeader::from_bytes(&header_bytes)
-                .map_err(|e| {
-                    PcapError::TimestampParseError {
-                        message: format!(
-                            "包头解析失败: {}",
-                            e
-                        ),
-                        position: self.current_position,
-                    }
-                })?;
+    /// 重新获取文件大小，读取正在写入的文件时用于发现新写入的数据
+    fn refresh_file_size(&mut self) -> PcapResult<()> {
+        let file = self.file.as_ref().ok_or_else(|| {
+            PcapError::InvalidState(
+                ERR_FILE_NOT_OPEN.to_string(),
+            )
+        })?;
+        let file_size =
+            file.metadata().map_err(PcapError::Io)?.len();
+        if file_size != self.file_size {
+            debug!(
+                "文件大小已变化: {} -> {file_size}",
+                self.file_size
+            );
+            self.file_size = file_size;
+            self.block = None;
+        }
+        Ok(())
+    }
+
+    /// 读取下一个数据包记录
+    fn read_record_with<F>(
+        &mut self,
+        allocate: F,
+    ) -> PcapResult<Option<ValidatedPacket>>
+    where
+        F: FnOnce(usize) -> Vec<u8>,
+    {
+        let byte_order = self.byte_order();
+
+        // 读取数据包头部，差值编码的头部长度可变；分块布局下跳过结构损坏的块
+        let (mut header, header_size) = loop {
+            if !self.prepare_block()? {
+                return Ok(None); // 到达文件末尾
+            }
+            self.record_offset = self.current_position;
+            let result =
+                self.read_record_header(byte_order);
+            let Some(block) = self
+                .block
+                .filter(|block| block.footer.is_some())
+            else {
+                match result? {
+                    Some(header) => break header,
+                    None => return Ok(None), // 到达文件末尾
+                }
+            };
+
+            // 已写完的块中数据包记录须完整位于块内
+            let problem = match result {
+                Ok(Some((header, header_size)))
+                    if self.record_offset
+                        + header_size
+                        + header.packet_length as u64
+                        <= block.data_end =>
+                {
+                    break (header, header_size);
+                }
+                Ok(Some(_)) => {
+                    "数据包记录超出块的范围".to_string()
+                }
+                Ok(None) => "数据包头部不完整".to_string(),
+                Err(PcapError::Io(e)) => {
+                    return Err(PcapError::Io(e))
+                }
+                Err(e) => e.to_string(),
+            };
+            warn!(
+                "跳过损坏的块: {problem}，块起始偏移 {}",
+                block.start
+            );
+            self.skip_block(block)?;
+        };
+        let read_limit = self.read_limit();
+        let reader =
+            self.reader.as_mut().ok_or_else(|| {
+                PcapError::InvalidState(
+                    ERR_FILE_NOT_OPEN.to_string(),
+                )
+            })?;
 
         // 检查数据包长度是否超出文件剩余空间
-        let remaining_after_header = self.file_size
-            - self.current_position
-            - DataPacketHeader::HEADER_SIZE as u64;
+        let remaining_after_header = read_limit
+            .saturating_sub(
+                self.current_position + header_size,
+            );
+        let mut truncated_from = None;
         if header.packet_length as u64
             > remaining_after_header
         {
-            return Err(PcapError::PacketSizeExceedsRemainingBytes {
-                expected: header.packet_length,
-                remaining: remaining_after_header,
-                position: self.current_position + DataPacketHeader::HEADER_SIZE as u64,
-            });
+            if !self.configuration.salvage_truncated_packets
+                || self.tolerate_partial_tail
+            {
+                return Err(PcapError::PacketSizeExceedsRemainingBytes {
+                    expected: header.packet_length,
+                    remaining: remaining_after_header,
+                    position: self.current_position + header_size,
+                });
+            }
+            // 保留文件末尾不完整的数据包，头部长度改为实际可用的长度
+            truncated_from = Some(header.packet_length);
+            header.packet_length =
+                remaining_after_header as u32;
         }
 
-        // 读取数据包内容
-        let mut data =
-            vec![0u8; header.packet_length as usize];
-        reader
-            .read_exact(&mut data)
-            .map_err(PcapError::Io)?;
+        // 读取数据包内容，负载去重的文件按记录中的引用从负载存储取回负载
+        let stored_length = header.packet_length as u64;
+        let data = match &self.payload_store_path {
+            Some(store_path)
+                if truncated_from.is_none() =>
+            {
+                let mut reference = vec![
+                    0u8;
+                    header.packet_length
+                        as usize
+                ];
+                reader
+                    .read_exact(&mut reference)
+                    .map_err(PcapError::Io)?;
+                let position =
+                    self.current_position + header_size;
+                let reference =
+                    PayloadReference::from_bytes(
+                        &reference, byte_order,
+                    )
+                    .ok_or_else(
+                        || PcapError::CorruptedData {
+                            message: format!(
+                                "负载引用长度无效: {} 字节",
+                                header.packet_length
+                            ),
+                            position,
+                        },
+                    )?;
+                let store = match &mut self.payload_store {
+                    Some(store) => store,
+                    store => store.insert(
+                        PayloadStoreReader::open(
+                            store_path,
+                        )?,
+                    ),
+                };
+                let mut data =
+                    allocate(reference.length as usize);
+                store.read_into(reference, &mut data)?;
+                header.packet_length = reference.length;
+                data
+            }
+            _ => {
+                let mut data =
+                    allocate(header.packet_length as usize);
+                reader
+                    .read_exact(&mut data)
+                    .map_err(PcapError::Io)?;
+                data
+            }
+        };
 
-        // 验证校验和
-        let calculated_checksum = calculate_crc32(&data);
-        let is_valid =
-            calculated_checksum == header.checksum;
+        // 按配置验证校验和
+        let verified = self
+            .configuration
+            .verify_checksums
+            .should_verify(self.read_sequence);
+        self.read_sequence += 1;
+        let status = match truncated_from {
+            Some(expected) => {
+                ValidationStatus::TruncatedPayload {
+                    expected,
+                    available: header.packet_length,
+                }
+            }
+            None => ValidationStatus::evaluate(
+                &header, &data, verified,
+            ),
+        };
 
         // 如果校验失败，记录警告日志
-        if !is_valid {
-            log::warn!(
+        match status {
+            ValidationStatus::ChecksumMismatch {
+                expected,
+                actual,
+            } => log::warn!(
                 "{}。期望: 0x{:08X}, 实际: 0x{:08X}",
                 ERR_CHECKSUM_MISMATCH,
-                header.checksum,
-                calculated_checksum
-            );
+                expected,
+                actual
+            ),
+            ValidationStatus::HeaderAnomalies {
+                ref anomalies,
+                ..
+            } => log::warn!(
+                "数据包头部异常: {anomalies:?}，位置: {}",
+                self.current_position
+            ),
+            ValidationStatus::TruncatedPayload {
+                expected,
+                available,
+            } => log::warn!(
+                "文件末尾的数据包不完整，期望 {expected} 字节，可用 {available} 字节，位置: {}",
+                self.current_position
+            ),
+            _ => {}
         }
 
         self.packet_count += 1;
         self.current_position +=
-            DataPacketHeader::HEADER_SIZE as u64
-                + header.packet_length as u64;
+            header_size + stored_length;
+        self.previous_timestamp_ns =
+            Some(header.timestamp_ns());
 
         let packet = DataPacket::new(header, data)
             .map_err(|e| PcapError::CorruptedData {
                 message: format!("数据包创建失败: {}", e),
                 position: self.current_position,
-            })?;
+            })?
+            .with_link_type(self.link_type());
 
-        let result = ValidatedPacket::new(packet, is_valid);
+        let result =
+            ValidatedPacket::with_status(packet, status);
 
         debug!(
-            "已读取数据包，当前计数: {}, 校验状态: {}, 位置: {}",
+            "已读取数据包，当前计数: {}, 校验状态: {:?}, 位置: {}",
             self.packet_count,
-            if is_valid { "有效" } else { "无效" },
+            result.status,
             self.current_position
         );
         Ok(Some(result))
     }
 
+    /// 按文件的记录格式读取下一个数据包头部，返回头部和头部占用的字节数
+    ///
+    /// 剩余字节不足一个头部时返回 `Ok(None)`。
+    fn read_record_header(
+        &mut self,
+        byte_order: ByteOrder,
+    ) -> PcapResult<Option<(DataPacketHeader, u64)>> {
+        let position = self.current_position;
+        let timestamp_hint = self.timestamp_hint.take();
+        let remaining_bytes =
+            self.read_limit().saturating_sub(position);
+        let reader =
+            self.reader.as_mut().ok_or_else(|| {
+                PcapError::InvalidState(
+                    ERR_FILE_NOT_OPEN.to_string(),
+                )
+            })?;
+
+        match self.record_format {
+            RecordFormat::Standard => {
+                if remaining_bytes
+                    < DataPacketHeader::HEADER_SIZE as u64
+                {
+                    return Ok(None);
+                }
+
+                let mut header_bytes =
+                    [0u8; DataPacketHeader::HEADER_SIZE];
+                match reader.read_exact(&mut header_bytes) {
+                    Ok(_) => {}
+                    Err(ref e)
+                        if e.kind()
+                            == io::ErrorKind::UnexpectedEof =>
+                    {
+                        return Ok(None);
+                    }
+                    Err(e) => return Err(PcapError::Io(e)),
+                }
+
+                let header =
+                    DataPacketHeader::from_bytes_with_order(
+                        &header_bytes,
+                        byte_order,
+                    )
+                    .map_err(|e| {
+                        PcapError::TimestampParseError {
+                            message: format!(
+                                "包头解析失败: {}",
+                                e
+                            ),
+                            position,
+                        }
+                    })?;
+                Ok(Some((
+                    header,
+                    DataPacketHeader::HEADER_SIZE as u64,
+                )))
+            }
+            RecordFormat::CheckedHeader => {
+                if remaining_bytes
+                    < checked_header::HEADER_SIZE as u64
+                {
+                    return Ok(None);
+                }
+
+                let mut header_bytes =
+                    [0u8; checked_header::HEADER_SIZE];
+                match reader.read_exact(&mut header_bytes) {
+                    Ok(_) => {}
+                    Err(ref e)
+                        if e.kind()
+                            == io::ErrorKind::UnexpectedEof =>
+                    {
+                        return Ok(None);
+                    }
+                    Err(e) => return Err(PcapError::Io(e)),
+                }
+
+                let header = checked_header::decode(
+                    &header_bytes,
+                    byte_order,
+                )
+                .map_err(|message| {
+                    PcapError::CorruptedData {
+                        message,
+                        position,
+                    }
+                })?;
+                Ok(Some((
+                    header,
+                    checked_header::HEADER_SIZE as u64,
+                )))
+            }
+            RecordFormat::DeltaTimestamp => {
+                let delta = match delta_header::read(
+                    &mut reader.by_ref().take(remaining_bytes),
+                    byte_order,
+                ) {
+                    Ok(Some(delta)) => delta,
+                    Ok(None) => return Ok(None),
+                    Err(e)
+                        if e.kind()
+                            == io::ErrorKind::InvalidData =>
+                    {
+                        return Err(PcapError::CorruptedData {
+                            message: format!(
+                                "差值编码的包头无效: {e}"
+                            ),
+                            position,
+                        });
+                    }
+                    Err(e) => return Err(PcapError::Io(e)),
+                };
+
+                // 按索引定位时使用索引记录的时间戳，顺序读取时以前一个数据包为基准
+                let timestamp_ns = match (
+                    timestamp_hint,
+                    self.previous_timestamp_ns,
+                ) {
+                    (Some(timestamp_ns), _) => timestamp_ns,
+                    (None, Some(previous)) => {
+                        delta.timestamp_ns(previous)
+                    }
+                    (None, None) => {
+                        return Err(PcapError::InvalidState(
+                            "差值编码的数据包只能从文件开头顺序读取或按索引条目定位"
+                                .to_string(),
+                        ))
+                    }
+                };
+                let mut header = DataPacketHeader {
+                    timestamp_seconds: 0,
+                    timestamp_nanoseconds: 0,
+                    packet_length: delta.packet_length,
+                    checksum: delta.checksum,
+                };
+                header
+                    .set_timestamp_ns(timestamp_ns)
+                    .map_err(|message| {
+                        PcapError::TimestampParseError {
+                            message,
+                            position,
+                        }
+                    })?;
+                Ok(Some((header, delta.encoded_len as u64)))
+            }
+        }
+    }
+
     /// 跳转到指定字节偏移位置
     pub(crate) fn seek_to(
         &mut self,
@@ -247,20 +729,341 @@ impl PcapFileReader {
             .seek(SeekFrom::Start(offset))
             .map_err(PcapError::Io)?;
 
-        // 更新当前位置
+        // 更新当前位置，只有数据区起始位置的时间戳基准是已知的
         self.current_position = offset;
+        self.block = None;
+        self.previous_timestamp_ns =
+            (offset == self.data_offset()).then_some(0);
+        self.timestamp_hint = None;
 
         debug!("已跳转到位置: {}", offset);
         Ok(())
     }
 
-    /// 在指定偏移位置读取数据包
+    /// 跳转到索引条目记录的数据包，`timestamp_ns` 为该数据包的时间戳
+    ///
+    /// 差值编码的记录需要已知的时间戳作为基准，按索引条目定位时应使用此方法而不是 `seek_to`。
+    pub(crate) fn seek_to_entry(
+        &mut self,
+        offset: u64,
+        timestamp_ns: u64,
+    ) -> PcapResult<()> {
+        self.seek_to(offset)?;
+        self.timestamp_hint = Some(timestamp_ns);
+        Ok(())
+    }
+
+    /// 跳转到索引条目记录的数据包之后，从下一个数据包继续顺序读取
+    pub(crate) fn seek_past_entry(
+        &mut self,
+        offset: u64,
+        packet_size: u32,
+        timestamp_ns: u64,
+    ) -> PcapResult<()> {
+        let end_offset = self.record_end(
+            offset,
+            packet_size,
+            timestamp_ns,
+        )?;
+        self.seek_to(end_offset)?;
+        self.previous_timestamp_ns = Some(timestamp_ns);
+        Ok(())
+    }
+
+    /// 将可读范围限制在指定字节偏移之前
+    ///
+    /// 用于读取正在写入的文件，只读取到索引中最后一个完整的数据包，之后不再重新获取文件大小。
+    pub(crate) fn limit_to(&mut self, end_offset: u64) {
+        self.file_size = self
+            .file_size
+            .min(end_offset.max(self.data_offset()));
+        self.block = None;
+        self.tolerate_partial_tail = false;
+    }
+
+    /// 当前可读取的结束偏移：分块布局下为当前块中数据包记录的结尾
+    fn read_limit(&self) -> u64 {
+        self.block.map_or(self.file_size, |block| {
+            block.data_end.min(self.file_size)
+        })
+    }
+
+    /// 分块布局下确定当前读取位置所在的块，块中的记录已读完时跳到下一个块
+    ///
+    /// 没有更多数据时返回 `false`；未启用分块布局时总是返回 `true`。
+    fn prepare_block(&mut self) -> PcapResult<bool> {
+        let Some(block_size) = self.block_size else {
+            return Ok(true);
+        };
+        loop {
+            if self.current_position >= self.file_size {
+                return Ok(false);
+            }
+            let block = match self.block {
+                Some(block) => block,
+                None => self.locate_block(block_size)?,
+            };
+            if self.current_position < self.read_limit() {
+                return Ok(true);
+            }
+            self.skip_block(block)?;
+        }
+    }
+
+    /// 读取当前读取位置所在块的块尾，确定块的范围
+    ///
+    /// 块尾无效的块视为没有数据包记录，读取时直接跳过。
+    fn locate_block(
+        &mut self,
+        block_size: u64,
+    ) -> PcapResult<BlockBounds> {
+        let data_offset = self.data_offset();
+        let position = self.current_position;
+        let start = data_offset
+            + (position - data_offset) / block_size
+                * block_size;
+        let order = self.byte_order();
+        let file_size = self.file_size;
+        let reader =
+            self.reader.as_mut().ok_or_else(|| {
+                PcapError::InvalidState(
+                    ERR_FILE_NOT_OPEN.to_string(),
+                )
+            })?;
+
+        let bounds = match block_layout::read_bounds(
+            reader, start, block_size, file_size, order,
+        ) {
+            Ok(bounds) => bounds,
+            Err(e)
+                if e.kind()
+                    == io::ErrorKind::InvalidData =>
+            {
+                warn!("跳过损坏的块: {e}");
+                BlockBounds {
+                    start,
+                    data_end: start,
+                    footer: None,
+                }
+            }
+            Err(e) => return Err(PcapError::Io(e)),
+        };
+        reader
+            .seek(SeekFrom::Start(position))
+            .map_err(PcapError::Io)?;
+        self.block = Some(bounds);
+        Ok(bounds)
+    }
+
+    /// 跳到指定块的下一个块
+    fn skip_block(
+        &mut self,
+        block: BlockBounds,
+    ) -> PcapResult<()> {
+        let block_size =
+            self.block_size.unwrap_or_default();
+        self.seek_to_block(block.start + block_size)
+    }
+
+    /// 跳转到块的起始偏移，差值编码的时间戳基准在每个块开头重置
+    pub(crate) fn seek_to_block(
+        &mut self,
+        offset: u64,
+    ) -> PcapResult<()> {
+        self.seek_to(offset)?;
+        self.previous_timestamp_ns = Some(0);
+        Ok(())
+    }
+
+    /// 列出已写完的块，只读取块尾，不改变读取位置
+    ///
+    /// 未启用分块布局时返回空列表，块尾无效的块记录警告后跳过。
+    pub(crate) fn blocks(
+        &mut self,
+    ) -> PcapResult<Vec<BlockSummary>> {
+        let Some(block_size) = self.block_size else {
+            return Ok(Vec::new());
+        };
+        let order = self.byte_order();
+        let file_size = self.file_size;
+        let position = self.current_position;
+        let mut start = self.data_offset();
+        let reader =
+            self.reader.as_mut().ok_or_else(|| {
+                PcapError::InvalidState(
+                    ERR_FILE_NOT_OPEN.to_string(),
+                )
+            })?;
+
+        let mut blocks = Vec::new();
+        let mut index = 0;
+        while start < file_size {
+            match block_layout::read_bounds(
+                reader, start, block_size, file_size, order,
+            ) {
+                Ok(BlockBounds {
+                    footer: Some(footer),
+                    ..
+                }) => blocks.push(BlockSummary {
+                    index,
+                    offset: start,
+                    footer,
+                }),
+                Ok(_) => {}
+                Err(e)
+                    if e.kind()
+                        == io::ErrorKind::InvalidData =>
+                {
+                    warn!("跳过损坏的块 {index}: {e}")
+                }
+                Err(e) => return Err(PcapError::Io(e)),
+            }
+            start += block_size;
+            index += 1;
+        }
+        reader
+            .seek(SeekFrom::Start(position))
+            .map_err(PcapError::Io)?;
+        Ok(blocks)
+    }
+
+    /// 分块布局的块大小，未启用分块布局时为 `None`
+    pub(crate) fn block_size(&self) -> Option<u64> {
+        self.block_size
+    }
+
+    /// 文件大小，读取正在写入的文件时为可读范围的结尾
+    pub(crate) fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// 索引条目记录的数据包（偏移、负载长度和时间戳）的结束偏移
+    ///
+    /// 负载去重的文件中记录只保存固定长度的负载引用，与负载长度无关；差值编码的头部长度可变，
+    /// 需要读取头部，读取后恢复原来的读取位置。
+    pub(crate) fn record_end(
+        &mut self,
+        byte_offset: u64,
+        packet_size: u32,
+        timestamp_ns: u64,
+    ) -> PcapResult<u64> {
+        let stored = if self.payload_store_path.is_some() {
+            PayloadReference::SIZE as u64
+        } else {
+            packet_size as u64
+        };
+        match self.record_format {
+            RecordFormat::Standard => {
+                return Ok(byte_offset
+                    + DataPacketHeader::HEADER_SIZE as u64
+                    + stored)
+            }
+            RecordFormat::CheckedHeader => {
+                return Ok(byte_offset
+                    + checked_header::HEADER_SIZE as u64
+                    + stored)
+            }
+            RecordFormat::DeltaTimestamp => {}
+        }
+
+        let position = self.current_position;
+        let previous = self.previous_timestamp_ns;
+        let hint = self.timestamp_hint;
+        self.seek_to_entry(byte_offset, timestamp_ns)?;
+        let header =
+            self.read_record_header(self.byte_order());
+        self.seek_to(position)?;
+        self.previous_timestamp_ns = previous;
+        self.timestamp_hint = hint;
+
+        let (_, header_size) =
+            header?.ok_or_else(|| {
+                PcapError::CorruptedData {
+                    message:
+                        "索引条目指向的数据包头部不完整"
+                            .to_string(),
+                    position: byte_offset,
+                }
+            })?;
+        Ok(byte_offset + header_size + stored)
+    }
+
+    /// 当前读取位置（字节偏移），即下一个数据包记录的起始偏移
+    ///
+    /// 分块布局下读完一个块的记录后，读取位置停在块中记录的结尾，读取下一个数据包时才跳到下一个块。
+    pub(crate) fn position(&self) -> u64 {
+        self.current_position
+    }
+
+    /// 最近读取的数据包记录的起始偏移
+    pub(crate) fn record_offset(&self) -> u64 {
+        self.record_offset
+    }
+
+    /// 数据区起始偏移（文件头大小）
+    pub(crate) fn data_offset(&self) -> u64 {
+        self.header
+            .as_ref()
+            .map(|header| header.header_size())
+            .unwrap_or(PcapFileHeader::HEADER_SIZE)
+            as u64
+    }
+
+    /// 当前文件的文件头，未打开文件时为 `None`
+    ///
+    /// 包含魔术数、版本号、时区偏移、时间戳精度和扩展块等格式信息。
+    pub fn header(&self) -> Option<&PcapFileHeader> {
+        self.header.as_ref()
+    }
+
+    /// 读取并校验指定文件的文件头
+    ///
+    /// 只读取文件头部分，不读取数据包。
+    pub(crate) fn read_header<P: AsRef<Path>>(
+        file_path: P,
+    ) -> PcapResult<PcapFileHeader> {
+        let mut reader = Self::new(ReaderConfig::default());
+        reader.open(file_path)?;
+        reader.header.take().ok_or_else(|| {
+            PcapError::InvalidState(
+                "文件头未读取".to_string(),
+            )
+        })
+    }
+
+    /// 文件头中记录的链路类型
+    pub fn link_type(&self) -> LinkType {
+        self.header
+            .as_ref()
+            .map(|header| header.link_type)
+            .unwrap_or_default()
+    }
+
+    /// 当前文件的字节序，未打开文件时为小端序
+    pub fn byte_order(&self) -> ByteOrder {
+        self.header
+            .as_ref()
+            .map(|header| header.byte_order)
+            .unwrap_or_default()
+    }
+
+    /// 回到数据区起始位置（文件头之后）
+    ///
+    /// 用于复用已打开的文件句柄重新顺序读取。
+    pub(crate) fn rewind(&mut self) -> PcapResult<()> {
+        self.seek_to(self.data_offset())?;
+        self.packet_count = 0;
+        Ok(())
+    }
+
+    /// 在索引条目记录的偏移位置读取数据包，`timestamp_ns` 为该数据包的时间戳
     pub(crate) fn read_packet_at(
         &mut self,
         offset: u64,
+        timestamp_ns: u64,
     ) -> PcapResult<ValidatedPacket> {
         // 先跳转到指定位置
-        self.seek_to(offset)?;
+        self.seek_to_entry(offset, timestamp_ns)?;
 
         // 然后读取数据包
         match self.read_packet()? {
@@ -280,6 +1083,13 @@ impl PcapFileReader {
         self.file_size = 0;
         self.header = None;
         self.current_position = 0;
+        self.payload_store_path = None;
+        self.payload_store = None;
+        self.previous_timestamp_ns = None;
+        self.timestamp_hint = None;
+        self.block_size = None;
+        self.block = None;
+        self.at_tail = false;
         debug!("文件已关闭");
     }
 }
@@ -1,10 +1,28 @@
 use log::info;
 use std::fs::{File, OpenOptions};
-use std::io::{BufWriter, Write};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 
 use crate::business::config::WriterConfig;
-use crate::data::models::{DataPacket, PcapFileHeader};
+use crate::data::block_layout::{
+    BlockFooter, BLOCK_FOOTER_SIZE,
+};
+use crate::data::models::{
+    DataPacketHeader, PcapFileHeader, RecordFormat,
+};
+use crate::data::{checked_header, delta_header};
+use crate::foundation::types::constants;
+
+/// 分块布局下正在写入的块
+#[derive(Default)]
+struct OpenBlock {
+    /// 块中数据包记录的字节数
+    data_length: u64,
+    packet_count: u32,
+    hasher: crc32fast::Hasher,
+    min_timestamp_ns: u64,
+    max_timestamp_ns: u64,
+}
 
 /// PCAP文件写入器
 pub struct PcapFileWriter {
@@ -13,7 +31,17 @@ pub struct PcapFileWriter {
     file_path: Option<PathBuf>,
     packet_count: u64,
     total_size: u64,
+    /// 已写入但尚未提交给缓冲写入器的小数据包
+    pending: Vec<u8>,
     configuration: WriterConfig,
+    /// 当前文件的数据包记录格式，由文件头决定
+    record_format: RecordFormat,
+    /// 上一个数据包的时间戳，差值编码以此为基准
+    previous_timestamp_ns: u64,
+    /// 分块布局的块大小，由文件头决定，未启用时为 None
+    block_size: Option<u64>,
+    /// 正在写入的块
+    block: OpenBlock,
 }
 
 impl PcapFileWriter {
@@ -24,7 +52,12 @@ impl PcapFileWriter {
             file_path: None,
             packet_count: 0,
             total_size: 0,
+            pending: Vec::new(),
             configuration,
+            record_format: RecordFormat::Standard,
+            previous_timestamp_ns: 0,
+            block_size: None,
+            block: OpenBlock::default(),
         }
     }
 
@@ -33,8 +66,29 @@ impl PcapFileWriter {
         &mut self,
         base_dir: P,
         filename: &str,
+    ) -> Result<(), String> {
+        let header = PcapFileHeader::with_link_type(
+            0,
+            self.configuration.link_type,
+        )
+        .with_extensions(
+            self.configuration.header_extensions.clone(),
+        );
+        self.create_with_header(base_dir, filename, header)
+    }
+
+    /// 使用指定的文件头创建新的PCAP文件
+    ///
+    /// 文件头的字节序以写入配置为准，数据包记录格式以文件头的扩展块为准。
+    pub(crate) fn create_with_header<P: AsRef<Path>>(
+        &mut self,
+        base_dir: P,
+        filename: &str,
+        header: PcapFileHeader,
     ) -> Result<(), String> {
         let path = base_dir.as_ref().join(filename);
+        let record_format = header.record_format()?;
+        let block_size = header.block_size()?;
 
         // 确保目录存在
         if let Some(parent) = path.parent() {
@@ -54,12 +108,13 @@ impl PcapFileWriter {
             })?;
 
         let mut writer = BufWriter::with_capacity(
-            self.configuration.buffer_size,
+            self.configuration.buffer_size.as_usize(),
             file,
         );
 
         // 写入文件头
-        let header = PcapFileHeader::new(0);
+        let header = header
+            .with_byte_order(self.configuration.byte_order);
         writer
             .write_all(&header.to_bytes())
             .map_err(|e| format!("写入文件头失败: {e}"))?;
@@ -77,32 +132,64 @@ impl PcapFileWriter {
         self.writer = Some(writer);
         self.file_path = Some(path.to_path_buf());
         self.packet_count = 0;
-        self.total_size =
-            PcapFileHeader::HEADER_SIZE as u64;
+        self.total_size = header.header_size() as u64;
+        self.pending.clear();
+        self.record_format = record_format;
+        self.previous_timestamp_ns = 0;
+        self.block_size = block_size;
+        self.block = OpenBlock::default();
 
         info!("成功创建PCAP文件: {path:?}");
         Ok(())
     }
 
-    /// 写入数据包
-    pub(crate) fn write_packet(
+    /// 写入数据包头部和负载，负载直接写入缓冲区而不复制
+    pub(crate) fn write_record(
         &mut self,
-        packet: &DataPacket,
+        header: &DataPacketHeader,
+        data: &[u8],
     ) -> Result<u64, String> {
-        let writer =
-            self.writer.as_mut().ok_or("文件未打开")?;
+        // 分块布局下当前块放不下时先写完当前块
+        if self.starts_new_block(header) {
+            self.seal_block(true)?;
+        }
 
         // 获取当前位置作为偏移量
         let offset = self.total_size;
+        let header_bytes = self.encode_header(header);
+        if let Some(capacity) = self.block_capacity() {
+            let record_size =
+                header_bytes.len() + data.len();
+            if record_size as u64 > capacity {
+                return Err(format!(
+                    "数据包记录 {record_size} 字节超过块容量 {capacity} 字节"
+                ));
+            }
+        }
+        self.update_block(header, &header_bytes, data);
+
+        if self.coalesces_small_writes() {
+            return self.write_coalesced(
+                header,
+                &header_bytes,
+                data,
+                offset,
+            );
+        }
+
+        let writer =
+            self.writer.as_mut().ok_or("文件未打开")?;
 
         // 写入数据包
-        let packet_bytes = packet.to_bytes();
         writer
-            .write_all(&packet_bytes)
+            .write_all(&header_bytes)
+            .and_then(|_| writer.write_all(data))
             .map_err(|e| format!("写入数据包失败: {e}"))?;
 
         self.packet_count += 1;
-        self.total_size += packet_bytes.len() as u64;
+        self.total_size +=
+            (header_bytes.len() + data.len()) as u64;
+        self.previous_timestamp_ns = header.timestamp_ns();
 
         if self.configuration.auto_flush {
             writer.flush().map_err(|e| {
@@ -113,8 +200,317 @@ impl PcapFileWriter {
         Ok(offset)
     }
 
+    /// 刷新缓冲区后从文件读回指定位置的字节
+    ///
+    /// 重新打开文件读取，不影响写入位置。
+    pub(crate) fn read_back(
+        &mut self,
+        offset: u64,
+        length: usize,
+    ) -> Result<Vec<u8>, String> {
+        self.flush()?;
+        let path =
+            self.file_path.as_ref().ok_or("文件未打开")?;
+        let mut file = File::open(path)
+            .map_err(|e| format!("打开文件失败: {e}"))?;
+        let mut bytes = vec![0u8; length];
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_exact(&mut bytes))
+            .map_err(|e| format!("读回数据失败: {e}"))?;
+        Ok(bytes)
+    }
+
+    /// 按当前文件的记录格式编码数据包头部
+    ///
+    /// 差值编码以上一个写入的数据包为基准，分块布局下每个块的第一个数据包以 0 为基准，
+    /// 编码结果对应下一次写入的头部。
+    pub(crate) fn encode_header(
+        &self,
+        header: &DataPacketHeader,
+    ) -> Vec<u8> {
+        match self.record_format {
+            RecordFormat::Standard => header
+                .to_bytes_with_order(
+                    self.configuration.byte_order,
+                ),
+            RecordFormat::CheckedHeader => {
+                checked_header::encode(
+                    header,
+                    self.configuration.byte_order,
+                )
+            }
+            RecordFormat::DeltaTimestamp => {
+                delta_header::encode(
+                    header.timestamp_ns(),
+                    self.delta_base(header),
+                    header.packet_length,
+                    header.checksum,
+                    self.configuration.byte_order,
+                )
+            }
+        }
+    }
+
+    /// 下一次写入该头部时头部占用的字节数
+    pub(crate) fn header_size(
+        &self,
+        header: &DataPacketHeader,
+    ) -> usize {
+        match self.record_format {
+            RecordFormat::Standard => {
+                DataPacketHeader::HEADER_SIZE
+            }
+            RecordFormat::CheckedHeader => {
+                checked_header::HEADER_SIZE
+            }
+            RecordFormat::DeltaTimestamp => {
+                delta_header::encoded_len(
+                    header.timestamp_ns(),
+                    self.delta_base(header),
+                    header.packet_length,
+                )
+            }
+        }
+    }
+
+    /// 分块布局下块中可以存放的数据包记录字节数，未启用分块布局时为 `None`
+    pub(crate) fn block_capacity(&self) -> Option<u64> {
+        self.block_size
+            .map(|size| size - BLOCK_FOOTER_SIZE as u64)
+    }
+
+    /// 分块布局下写入 `record_size` 字节的记录在记录之外额外占用的磁盘字节数
+    ///
+    /// 包括当前块放不下该记录时填充和写完当前块的字节数，以及文件结束时
+    /// 最后一个块的块尾。未启用分块布局时为 0。
+    pub(crate) fn block_overhead(
+        &self,
+        record_size: u64,
+    ) -> u64 {
+        let Some(capacity) = self.block_capacity() else {
+            return 0;
+        };
+        let data_length = self.block.data_length;
+        let sealed = if data_length > 0
+            && data_length + record_size > capacity
+        {
+            capacity - data_length
+                + BLOCK_FOOTER_SIZE as u64
+        } else {
+            0
+        };
+        sealed + BLOCK_FOOTER_SIZE as u64
+    }
+
+    /// 差值编码的时间戳基准
+    fn delta_base(&self, header: &DataPacketHeader) -> u64 {
+        if self.block_size.is_some()
+            && (self.block.data_length == 0
+                || self.starts_new_block(header))
+        {
+            0
+        } else {
+            self.previous_timestamp_ns
+        }
+    }
+
+    /// 写入该头部的数据包前是否需要先写完当前块
+    ///
+    /// 以当前的时间戳基准估算记录大小，块中已有记录且放不下该数据包时需要写完当前块。
+    fn starts_new_block(
+        &self,
+        header: &DataPacketHeader,
+    ) -> bool {
+        let Some(capacity) = self.block_capacity() else {
+            return false;
+        };
+        if self.block.data_length == 0 {
+            return false;
+        }
+        let header_size = match self.record_format {
+            RecordFormat::Standard => {
+                DataPacketHeader::HEADER_SIZE
+            }
+            RecordFormat::CheckedHeader => {
+                checked_header::HEADER_SIZE
+            }
+            RecordFormat::DeltaTimestamp => {
+                delta_header::encoded_len(
+                    header.timestamp_ns(),
+                    self.previous_timestamp_ns,
+                    header.packet_length,
+                )
+            }
+        };
+        self.block.data_length
+            + header_size as u64
+            + header.packet_length as u64
+            > capacity
+    }
+
+    /// 把数据包记录计入正在写入的块
+    fn update_block(
+        &mut self,
+        header: &DataPacketHeader,
+        header_bytes: &[u8],
+        data: &[u8],
+    ) {
+        if self.block_size.is_none() {
+            return;
+        }
+        let timestamp_ns = header.timestamp_ns();
+        let block = &mut self.block;
+        if block.packet_count == 0 {
+            block.min_timestamp_ns = timestamp_ns;
+            block.max_timestamp_ns = timestamp_ns;
+        }
+        block.min_timestamp_ns =
+            block.min_timestamp_ns.min(timestamp_ns);
+        block.max_timestamp_ns =
+            block.max_timestamp_ns.max(timestamp_ns);
+        block.hasher.update(header_bytes);
+        block.hasher.update(data);
+        block.data_length +=
+            (header_bytes.len() + data.len()) as u64;
+        block.packet_count += 1;
+    }
+
+    /// 写完正在写入的块：按需填充到块大小，然后写入块尾
+    ///
+    /// 块中没有数据包记录时不写入任何内容。
+    fn seal_block(
+        &mut self,
+        pad: bool,
+    ) -> Result<(), String> {
+        let Some(block_size) = self.block_size else {
+            return Ok(());
+        };
+        if self.block.data_length == 0 {
+            return Ok(());
+        }
+        let block = std::mem::take(&mut self.block);
+        let footer = BlockFooter {
+            packet_count: block.packet_count,
+            data_length: block.data_length as u32,
+            checksum: block.hasher.finalize(),
+            min_timestamp_ns: block.min_timestamp_ns,
+            max_timestamp_ns: block.max_timestamp_ns,
+        };
+        let padding = if pad {
+            block_size
+                - BLOCK_FOOTER_SIZE as u64
+                - block.data_length
+        } else {
+            0
+        };
+
+        let mut bytes = vec![0u8; padding as usize];
+        bytes.extend_from_slice(
+            &footer.to_bytes(self.configuration.byte_order),
+        );
+        self.commit_pending(self.pending.len())?;
+        let writer =
+            self.writer.as_mut().ok_or("文件未打开")?;
+        writer
+            .write_all(&bytes)
+            .map_err(|e| format!("写入块尾失败: {e}"))?;
+        self.total_size += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// 写完正在写入的块并刷新缓冲区，关闭文件前调用
+    ///
+    /// 最后一个块不填充，块尾紧跟在最后一个数据包记录之后。
+    pub(crate) fn finish(&mut self) -> Result<(), String> {
+        self.seal_block(false)?;
+        self.flush()
+    }
+
+    /// 已写入的字节数，含文件头
+    pub(crate) fn total_size(&self) -> u64 {
+        self.total_size
+    }
+
+    /// 当前文件路径
+    pub(crate) fn file_path(&self) -> Option<&Path> {
+        self.file_path.as_deref()
+    }
+
+    /// 是否合并小数据包后再按块提交
+    fn coalesces_small_writes(&self) -> bool {
+        self.configuration.auto_flush
+            && !self.configuration.min_write_block.is_zero()
+    }
+
+    /// 合并写入：小数据包先累积，跨越块边界时提交到最后一个完整块为止
+    fn write_coalesced(
+        &mut self,
+        header: &DataPacketHeader,
+        header_bytes: &[u8],
+        data: &[u8],
+        offset: u64,
+    ) -> Result<u64, String> {
+        if self.writer.is_none() {
+            return Err("文件未打开".to_string());
+        }
+
+        let record_size = header_bytes.len() + data.len();
+        if data.len() >= constants::SMALL_WRITE_THRESHOLD {
+            // 较大的数据包直接写入，先提交已合并的数据保持顺序
+            self.commit_pending(self.pending.len())?;
+            let writer =
+                self.writer.as_mut().ok_or("文件未打开")?;
+            writer
+                .write_all(header_bytes)
+                .and_then(|_| writer.write_all(data))
+                .and_then(|_| writer.flush())
+                .map_err(|e| {
+                    format!("写入数据包失败: {e}")
+                })?;
+        } else {
+            self.pending.extend_from_slice(header_bytes);
+            self.pending.extend_from_slice(data);
+
+            // 按文件偏移对齐：只提交到最后一个完整块的末尾
+            let block =
+                self.configuration.min_write_block.as_u64();
+            let end = self.total_size + record_size as u64;
+            let committed = end - self.pending.len() as u64;
+            let boundary = end / block * block;
+            if boundary > committed {
+                self.commit_pending(
+                    (boundary - committed) as usize,
+                )?;
+            }
+        }
+
+        self.packet_count += 1;
+        self.total_size += record_size as u64;
+        self.previous_timestamp_ns = header.timestamp_ns();
+        Ok(offset)
+    }
+
+    /// 将合并缓冲区开头的 `length` 字节提交给操作系统
+    fn commit_pending(
+        &mut self,
+        length: usize,
+    ) -> Result<(), String> {
+        if length == 0 {
+            return Ok(());
+        }
+        let writer =
+            self.writer.as_mut().ok_or("文件未打开")?;
+        writer
+            .write_all(&self.pending[..length])
+            .and_then(|_| writer.flush())
+            .map_err(|e| format!("写入数据块失败: {e}"))?;
+        self.pending.drain(..length);
+        Ok(())
+    }
+
     /// 刷新缓冲区
     pub(crate) fn flush(&mut self) -> Result<(), String> {
+        self.commit_pending(self.pending.len())?;
         if let Some(writer) = &mut self.writer {
             writer.flush().map_err(|e| {
                 format!("刷新缓冲区失败: {e}")
@@ -124,7 +520,11 @@ impl PcapFileWriter {
     }
 
     /// 关闭文件
+    ///
+    /// 分块布局下会写完正在写入的块，出错时只能忽略，需要处理错误时先调用 `finish`。
     pub(crate) fn close(&mut self) {
+        let _ = self.seal_block(false);
+        let _ = self.commit_pending(self.pending.len());
         if let Some(writer) = &mut self.writer {
             let _ = writer.flush();
         }
@@ -133,6 +533,10 @@ impl PcapFileWriter {
         self.file_path = None;
         self.packet_count = 0;
         self.total_size = 0;
+        self.pending.clear();
+        self.previous_timestamp_ns = 0;
+        self.block_size = None;
+        self.block = OpenBlock::default();
     }
 }
 
@@ -2,9 +2,17 @@
 //!
 //! 负责PCAP文件格式的序列化和反序列化操作，提供底层数据格式处理功能。
 
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::core::FileHeader;
+use crate::data::block_layout;
 use crate::data::models::{
     DataPacket, DataPacketHeader, PcapFileHeader,
+    RecordFormat,
 };
+use crate::data::{checked_header, delta_header};
 use crate::foundation::error::{PcapError, PcapResult};
 
 /// PCAP格式处理器
@@ -86,9 +94,8 @@ impl PcapFormatProcessor {
             ));
         }
 
-        let header = Self::parse_file_header(
-            &data[..PcapFileHeader::HEADER_SIZE],
-        )?;
+        // 扩展头部（携带链路类型）由解析函数按版本读取额外字段
+        let header = Self::parse_file_header(data)?;
 
         if !header.is_valid() {
             return Err(PcapError::InvalidFormat(
@@ -99,3 +106,331 @@ impl PcapFormatProcessor {
         Ok(())
     }
 }
+
+/// 快速结构检查的结果
+#[derive(Debug, Clone)]
+pub struct FileStructure {
+    /// 文件头
+    pub header: PcapFileHeader,
+    /// 文件大小（字节）
+    pub file_size: u64,
+    /// 数据区的起始偏移
+    pub data_offset: u64,
+    /// 数据包数量
+    pub packet_count: u64,
+    /// 第一个数据包的时间戳（纳秒）
+    pub first_timestamp_ns: Option<u64>,
+    /// 最后一个数据包的时间戳（纳秒）
+    pub last_timestamp_ns: Option<u64>,
+}
+
+/// 只检查文件结构，不读取负载
+///
+/// 严格解析文件头和每个数据包头部，确认数据包长度与文件大小吻合，
+/// 负载直接跳过，不计算校验和。适合在导入前快速过滤损坏的文件。
+///
+/// # 错误
+/// - 头部字段无效时返回 `PcapError::InvalidHeaderField`，位置为头部在文件中的偏移
+/// - 数据包长度超出文件剩余空间时返回 `PcapError::PacketSizeExceedsRemainingBytes`
+/// - 差值编码的数据包头部不完整或无效时返回 `PcapError::CorruptedData`
+/// - 分块布局的块尾无效或与块中的记录不一致时返回 `PcapError::CorruptedData`
+pub fn validate_file_quick<P: AsRef<Path>>(
+    path: P,
+) -> PcapResult<FileStructure> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| {
+        if e.kind() == io::ErrorKind::NotFound {
+            PcapError::FileNotFound(format!("{path:?}"))
+        } else {
+            PcapError::Io(e)
+        }
+    })?;
+    let file_size = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let (header, available) =
+        read_header_strict(&mut reader, file_size)?;
+    let data_offset = header.header_size() as u64;
+    // 较短的文件头多读的部分属于第一个数据包
+    reader.seek_relative(
+        data_offset as i64 - available as i64,
+    )?;
+
+    let mut structure = FileStructure {
+        header,
+        file_size,
+        data_offset,
+        packet_count: 0,
+        first_timestamp_ns: None,
+        last_timestamp_ns: None,
+    };
+    let record_format = structure
+        .header
+        .record_format()
+        .map_err(PcapError::CorruptedHeader)?;
+    let block_size = structure
+        .header
+        .block_size()
+        .map_err(PcapError::CorruptedHeader)?;
+    let Some(block_size) = block_size else {
+        validate_records(
+            &mut reader,
+            &mut structure,
+            record_format,
+            data_offset,
+            file_size,
+        )?;
+        return Ok(structure);
+    };
+
+    // 分块布局：逐块检查记录，块尾记录的数据包数量须与块中的记录一致
+    let mut start = data_offset;
+    while start < file_size {
+        let bounds = block_layout::read_bounds(
+            &mut reader,
+            start,
+            block_size,
+            file_size,
+            structure.header.byte_order,
+        )
+        .map_err(|e| {
+            if e.kind() == io::ErrorKind::InvalidData {
+                PcapError::CorruptedData {
+                    message: e.to_string(),
+                    position: start,
+                }
+            } else {
+                PcapError::Io(e)
+            }
+        })?;
+        reader.seek(SeekFrom::Start(start))?;
+        let packets = validate_records(
+            &mut reader,
+            &mut structure,
+            record_format,
+            start,
+            bounds.data_end,
+        )?;
+        if let Some(footer) = bounds.footer {
+            if footer.packet_count as u64 != packets {
+                return Err(PcapError::CorruptedData {
+                    message: format!(
+                        "块尾记录的数据包数量 {} 与块中的 {packets} 个数据包不一致",
+                        footer.packet_count
+                    ),
+                    position: start,
+                });
+            }
+        }
+        start += block_size;
+    }
+
+    Ok(structure)
+}
+
+/// 严格检查 `[start, end)` 范围内依次存放的数据包记录，返回记录数量
+///
+/// 差值编码的时间戳基准在范围开头为 0。
+fn validate_records(
+    reader: &mut BufReader<File>,
+    structure: &mut FileStructure,
+    record_format: RecordFormat,
+    start: u64,
+    end: u64,
+) -> PcapResult<u64> {
+    let byte_order = structure.header.byte_order;
+    let mut position = start;
+    let mut packets = 0u64;
+    let mut previous_timestamp_ns = 0u64;
+    let mut packet_header =
+        [0u8; DataPacketHeader::HEADER_SIZE];
+    while position < end {
+        let (timestamp_ns, packet_length, header_size) =
+            match record_format {
+                RecordFormat::Standard => {
+                    let available = read_up_to(
+                        reader,
+                        &mut packet_header,
+                    )?;
+                    let header =
+                        DataPacketHeader::parse_strict_with_order(
+                            &packet_header[..available],
+                            byte_order,
+                        )
+                        .map_err(|error| {
+                            PcapError::InvalidHeaderField {
+                                error,
+                                position,
+                            }
+                        })?;
+                    (
+                        header.timestamp_ns(),
+                        header.packet_length,
+                        DataPacketHeader::HEADER_SIZE
+                            as u64,
+                    )
+                }
+                RecordFormat::CheckedHeader => {
+                    let corrupted = |message: String| {
+                        PcapError::CorruptedData {
+                            message,
+                            position,
+                        }
+                    };
+                    let mut header_bytes =
+                        [0u8; checked_header::HEADER_SIZE];
+                    let available = read_up_to(
+                        reader,
+                        &mut header_bytes,
+                    )?;
+                    if available < header_bytes.len() {
+                        return Err(corrupted(
+                            "数据包头部不完整".to_string(),
+                        ));
+                    }
+                    checked_header::decode(
+                        &header_bytes,
+                        byte_order,
+                    )
+                    .map_err(corrupted)?;
+                    let header =
+                        DataPacketHeader::parse_strict_with_order(
+                            &header_bytes[..DataPacketHeader::HEADER_SIZE],
+                            byte_order,
+                        )
+                        .map_err(|error| {
+                            PcapError::InvalidHeaderField {
+                                error,
+                                position,
+                            }
+                        })?;
+                    (
+                        header.timestamp_ns(),
+                        header.packet_length,
+                        checked_header::HEADER_SIZE as u64,
+                    )
+                }
+                RecordFormat::DeltaTimestamp => {
+                    let corrupted = |message: String| {
+                        PcapError::CorruptedData {
+                            message,
+                            position,
+                        }
+                    };
+                    let delta = delta_header::read(
+                        &mut reader.take(end - position),
+                        byte_order,
+                    )
+                    .map_err(|e| {
+                        if e.kind()
+                            == io::ErrorKind::InvalidData
+                        {
+                            corrupted(format!(
+                                "差值编码的包头无效: {e}"
+                            ))
+                        } else {
+                            PcapError::Io(e)
+                        }
+                    })?
+                    .ok_or_else(|| {
+                        corrupted(
+                            "差值编码的包头不完整"
+                                .to_string(),
+                        )
+                    })?;
+                    (
+                        delta.timestamp_ns(
+                            previous_timestamp_ns,
+                        ),
+                        delta.packet_length,
+                        delta.encoded_len as u64,
+                    )
+                }
+            };
+
+        let payload_position = position + header_size;
+        if payload_position > end {
+            return Err(PcapError::CorruptedData {
+                message: "数据包头部超出块的范围"
+                    .to_string(),
+                position,
+            });
+        }
+        let remaining = end - payload_position;
+        if packet_length as u64 > remaining {
+            return Err(PcapError::PacketSizeExceedsRemainingBytes {
+                expected: packet_length,
+                remaining,
+                position: payload_position,
+            });
+        }
+        reader.seek_relative(packet_length as i64)?;
+
+        structure
+            .first_timestamp_ns
+            .get_or_insert(timestamp_ns);
+        structure.last_timestamp_ns = Some(timestamp_ns);
+        structure.packet_count += 1;
+        packets += 1;
+        previous_timestamp_ns = timestamp_ns;
+        position = payload_position + packet_length as u64;
+    }
+
+    Ok(packets)
+}
+
+/// 读取文件头（包括扩展块区域）并严格解析
+///
+/// 返回文件头和实际读取的字节数，读取的字节可能多于文件头。
+fn read_header_strict<R: Read>(
+    reader: &mut R,
+    file_size: u64,
+) -> PcapResult<(PcapFileHeader, usize)> {
+    let mut header_bytes =
+        vec![0u8; PcapFileHeader::EXTENSIBLE_HEADER_SIZE];
+    let mut available =
+        read_up_to(reader, &mut header_bytes)?;
+
+    // 扩展块区域的长度不超过文件大小时才补读，超出时由严格解析报告字节不足
+    if let Ok(header) =
+        FileHeader::decode(&header_bytes[..available])
+    {
+        let header_size = header.encoded_len();
+        if header_size > available
+            && header_size as u64 <= file_size
+        {
+            header_bytes.resize(header_size, 0);
+            available += read_up_to(
+                reader,
+                &mut header_bytes[available..],
+            )?;
+        }
+    }
+
+    let header = PcapFileHeader::parse_strict(
+        &header_bytes[..available],
+    )
+    .map_err(|error| {
+        PcapError::InvalidHeaderField { error, position: 0 }
+    })?;
+    Ok((header, available))
+}
+
+/// 尽量填满缓冲区，返回实际读取的字节数
+fn read_up_to<R: Read>(
+    reader: &mut R,
+    buffer: &mut [u8],
+) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(length) => filled += length,
+            Err(e)
+                if e.kind()
+                    == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
@@ -0,0 +1,138 @@
+//! 标准 libpcap 文件写入模块
+//!
+//! 把数据包写入 Wireshark、tcpdump 等工具可以直接打开的标准 pcap 文件。
+//! 使用纳秒精度的魔术数 `0xa1b23c4d` 和小端字节序，不生成索引。
+
+use log::debug;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::data::models::LinkType;
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 纳秒精度 pcap 文件的魔术数
+pub const LIBPCAP_NANOSECOND_MAGIC: u32 = 0xa1b2_3c4d;
+
+/// 写入文件头的最大捕获长度，超出部分被截断
+pub const LIBPCAP_SNAPLEN: u32 = 262_144;
+
+/// libpcap 链路类型：用户自定义 0
+const LINKTYPE_USER0: u32 = 147;
+
+/// 把链路类型转换为 libpcap 链路类型（DLT）数值
+///
+/// 没有对应标准值的类型使用 `LINKTYPE_USER0`，可在 Wireshark 中配置解析方式。
+pub fn libpcap_link_type(link_type: LinkType) -> u32 {
+    match link_type {
+        LinkType::Ethernet => 1,
+        LinkType::RawIp => 101,
+        LinkType::Can => 227,
+        LinkType::Unknown
+        | LinkType::Udp
+        | LinkType::Custom(_) => LINKTYPE_USER0,
+    }
+}
+
+/// 标准 libpcap 文件写入器
+pub struct LibpcapWriter {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    packet_count: u64,
+}
+
+impl LibpcapWriter {
+    /// 创建文件并写入文件头，已存在的文件会被覆盖
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        link_type: LinkType,
+    ) -> PcapResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file =
+            File::create(&path).map_err(PcapError::Io)?;
+        let mut writer = BufWriter::new(file);
+
+        let mut header = [0u8; 24];
+        header[0..4].copy_from_slice(
+            &LIBPCAP_NANOSECOND_MAGIC.to_le_bytes(),
+        );
+        header[4..6].copy_from_slice(&2u16.to_le_bytes());
+        header[6..8].copy_from_slice(&4u16.to_le_bytes());
+        // thiszone 和 sigfigs 为 0
+        header[16..20].copy_from_slice(
+            &LIBPCAP_SNAPLEN.to_le_bytes(),
+        );
+        header[20..24].copy_from_slice(
+            &libpcap_link_type(link_type).to_le_bytes(),
+        );
+        writer.write_all(&header).map_err(PcapError::Io)?;
+
+        debug!("已创建 libpcap 文件: {path:?}");
+        Ok(Self {
+            writer,
+            path,
+            packet_count: 0,
+        })
+    }
+
+    /// 写入一个数据包
+    ///
+    /// # 错误
+    /// 时间戳的秒数超出 `u32` 范围时返回 `PcapError::InvalidArgument`
+    pub fn write_packet(
+        &mut self,
+        timestamp_ns: u64,
+        data: &[u8],
+    ) -> PcapResult<()> {
+        let seconds = u32::try_from(
+            timestamp_ns / 1_000_000_000,
+        )
+        .map_err(|_| {
+            PcapError::InvalidArgument(format!(
+                "时间戳超出 libpcap 格式支持的范围: {timestamp_ns}"
+            ))
+        })?;
+        let nanoseconds =
+            (timestamp_ns % 1_000_000_000) as u32;
+        let original_length = data.len() as u32;
+        let captured_length =
+            original_length.min(LIBPCAP_SNAPLEN);
+
+        let mut record = [0u8; 16];
+        record[0..4]
+            .copy_from_slice(&seconds.to_le_bytes());
+        record[4..8]
+            .copy_from_slice(&nanoseconds.to_le_bytes());
+        record[8..12].copy_from_slice(
+            &captured_length.to_le_bytes(),
+        );
+        record[12..16].copy_from_slice(
+            &original_length.to_le_bytes(),
+        );
+        self.writer
+            .write_all(&record)
+            .and_then(|_| {
+                self.writer.write_all(
+                    &data[..captured_length as usize],
+                )
+            })
+            .map_err(PcapError::Io)?;
+        self.packet_count += 1;
+        Ok(())
+    }
+
+    /// 把缓冲区写入文件
+    pub fn flush(&mut self) -> PcapResult<()> {
+        self.writer.flush().map_err(PcapError::Io)
+    }
+
+    /// 文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 已写入的数据包数量
+    pub fn packet_count(&self) -> u64 {
+        self.packet_count
+    }
+}
@@ -2,16 +2,30 @@
 //!
 //! 负责底层文件读写操作、数据序列化/反序列化和格式解析生成。
 
+pub mod block_layout;
+pub(crate) mod checked_header;
+pub(crate) mod delta_header;
 pub mod file_reader;
 pub mod file_writer;
 pub mod formats;
+pub mod libpcap;
 pub mod models;
+pub mod payload_store;
 
 // 重新导出核心数据结构
+pub use block_layout::{
+    read_blocks_in_range, scan_blocks, verify_blocks,
+    BlockFooter, BlockSummary,
+};
 pub use file_reader::PcapFileReader;
 pub use file_writer::PcapFileWriter;
-pub use formats::PcapFormatProcessor;
+pub use formats::{
+    validate_file_quick, FileStructure, PcapFormatProcessor,
+};
+pub use libpcap::LibpcapWriter;
 pub use models::{
     DataPacket, DataPacketHeader, DatasetInfo, FileInfo,
-    PcapFileHeader,
+    FormatVersion, HeaderAnomaly, HeaderExtension,
+    HeaderFieldError, LinkType, PcapFileHeader,
+    RecordFormat, ValidatedPacket, ValidationStatus,
 };
@@ -1,21 +1,188 @@
+use crate::business::clock::ClockSource;
+use crate::business::rotation::{
+    RotationPolicy, RotationReason,
+};
+use crate::core::{
+    self as codec, ByteOrder, FileHeader, PacketHeader,
+};
+use crate::data::block_layout;
+use crate::format::{self, FieldLayout};
 use crate::foundation::types::constants;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// 数据包头部的异常
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub enum HeaderAnomaly {
+    /// 纳秒字段不小于 1 秒
+    NanosecondsOutOfRange(u32),
+}
+
+/// 数据包的校验结果
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+)]
+pub enum ValidationStatus {
+    /// 校验通过
+    #[default]
+    Valid,
+    /// 未验证校验和（读取器配置为跳过或抽样验证）
+    Unverified,
+    /// 校验和不匹配
+    ChecksumMismatch {
+        /// 头部记录的校验和
+        expected: u32,
+        /// 按负载计算的校验和
+        actual: u32,
+    },
+    /// 负载不完整，文件在数据包中途结束
+    TruncatedPayload {
+        /// 头部记录的负载长度
+        expected: u32,
+        /// 实际可用的负载长度
+        available: u32,
+    },
+    /// 校验和正确（或未验证）但头部字段异常
+    HeaderAnomalies {
+        /// 发现的异常
+        anomalies: Vec<HeaderAnomaly>,
+        /// 是否验证了校验和
+        checksum_verified: bool,
+    },
+}
+
+impl ValidationStatus {
+    /// 根据头部和负载判定校验结果
+    ///
+    /// `verify_checksum` 为 false 时不计算校验和，头部正常时结果为 `Unverified`。
+    pub fn evaluate(
+        header: &DataPacketHeader,
+        data: &[u8],
+        verify_checksum: bool,
+    ) -> Self {
+        if verify_checksum && !header.checksum_matches(data)
+        {
+            return ValidationStatus::ChecksumMismatch {
+                expected: header.checksum,
+                actual: codec::crc32(data),
+            };
+        }
+
+        let mut anomalies = Vec::new();
+        if header.timestamp_nanoseconds >= 1_000_000_000 {
+            anomalies.push(
+                HeaderAnomaly::NanosecondsOutOfRange(
+                    header.timestamp_nanoseconds,
+                ),
+            );
+        }
+
+        if !anomalies.is_empty() {
+            ValidationStatus::HeaderAnomalies {
+                anomalies,
+                checksum_verified: verify_checksum,
+            }
+        } else if verify_checksum {
+            ValidationStatus::Valid
+        } else {
+            ValidationStatus::Unverified
+        }
+    }
+
+    /// 数据包是否可以视为有效（校验通过或未验证）
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        matches!(
+            self,
+            ValidationStatus::Valid
+                | ValidationStatus::Unverified
+        )
+    }
+}
+
 /// 带校验结果的数据包
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidatedPacket {
     /// 数据包
     pub packet: DataPacket,
-    /// 校验是否通过
+    /// 校验是否通过，未验证的数据包视为通过
     pub is_valid: bool,
+    /// 详细的校验结果
+    #[serde(default)]
+    pub status: ValidationStatus,
 }
 
 impl ValidatedPacket {
     /// 创建带校验结果的数据包
+    ///
+    /// 校验失败时重新计算校验和以记录不匹配的具体值，需要更精确的结果时使用
+    /// [`ValidatedPacket::with_status`]。
     #[inline]
     pub fn new(packet: DataPacket, is_valid: bool) -> Self {
-        Self { packet, is_valid }
+        let status = if is_valid {
+            ValidationStatus::Valid
+        } else {
+            ValidationStatus::ChecksumMismatch {
+                expected: packet.header.checksum,
+                actual: codec::crc32(&packet.data),
+            }
+        };
+        Self::with_status(packet, status)
+    }
+
+    /// 使用指定的校验结果创建数据包
+    #[inline]
+    pub fn with_status(
+        packet: DataPacket,
+        status: ValidationStatus,
+    ) -> Self {
+        Self {
+            packet,
+            is_valid: status.is_valid(),
+            status,
+        }
+    }
+
+    /// 创建未验证校验和的数据包
+    #[inline]
+    pub fn unverified(packet: DataPacket) -> Self {
+        Self::with_status(
+            packet,
+            ValidationStatus::Unverified,
+        )
+    }
+
+    /// 获取校验结果
+    #[inline]
+    pub fn status(&self) -> &ValidationStatus {
+        &self.status
+    }
+
+    /// 是否实际验证了校验和
+    #[inline]
+    pub fn is_checksum_verified(&self) -> bool {
+        match self.status {
+            ValidationStatus::Unverified => false,
+            ValidationStatus::HeaderAnomalies {
+                checksum_verified,
+                ..
+            } => checksum_verified,
+            _ => true,
+        }
     }
 
     /// 检查是否有效
@@ -42,6 +209,12 @@ impl ValidatedPacket {
         self.packet.packet_length()
     }
 
+    /// 是否为心跳包 - 委托给内部数据包
+    #[inline]
+    pub fn is_heartbeat(&self) -> bool {
+        self.packet.is_heartbeat()
+    }
+
     /// 获取捕获时间 - 委托给内部数据包
     #[inline]
     pub fn capture_time(&self) -> DateTime<Utc> {
@@ -59,9 +232,346 @@ impl ValidatedPacket {
     pub fn total_size(&self) -> usize {
         self.packet.total_size()
     }
+
+    /// 获取链路类型 - 委托给内部数据包
+    #[inline]
+    pub fn link_type(&self) -> LinkType {
+        self.packet.link_type()
+    }
+}
+
+/// 链路类型（数据包负载类型）
+///
+/// 记录在文件头中，告知下游解析器负载是以太网帧、IP 报文、UDP 数据报还是 CAN 帧等，
+/// 以便自动选择解析方式。自定义类型建议使用不小于 `CUSTOM_BASE` 的值。
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Default,
+    Serialize,
+    Deserialize,
+)]
+pub enum LinkType {
+    /// 未指定
+    #[default]
+    Unknown,
+    /// 以太网帧
+    Ethernet,
+    /// 原始 IP 报文
+    RawIp,
+    /// UDP 数据报负载
+    Udp,
+    /// CAN 帧
+    Can,
+    /// 自定义类型
+    Custom(u32),
+}
+
+impl LinkType {
+    /// 自定义类型的建议起始值
+    pub const CUSTOM_BASE: u32 = 0x1000;
+
+    /// 转换为文件头中存储的数值
+    pub fn to_u32(self) -> u32 {
+        match self {
+            LinkType::Unknown => 0,
+            LinkType::Ethernet => 1,
+            LinkType::RawIp => 2,
+            LinkType::Udp => 3,
+            LinkType::Can => 4,
+            LinkType::Custom(value) => value,
+        }
+    }
+
+    /// 从文件头中存储的数值解析
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            0 => LinkType::Unknown,
+            1 => LinkType::Ethernet,
+            2 => LinkType::RawIp,
+            3 => LinkType::Udp,
+            4 => LinkType::Can,
+            other => LinkType::Custom(other),
+        }
+    }
+}
+
+/// 文件格式版本
+///
+/// 由文件头的主、次版本号确定。读取器支持全部已知版本，新版本的文件头可以通过
+/// `migrate` 模块从旧版本升级。
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum FormatVersion {
+    /// 2.4，16 字节基础文件头
+    V2_4,
+    /// 2.5，20 字节文件头，末尾携带链路类型
+    V2_5,
+    /// 2.6，在 2.5 的基础上追加扩展块区域长度和扩展块，固定部分 24 字节
+    V2_6,
+}
+
+impl FormatVersion {
+    /// 当前最新的格式版本
+    pub const LATEST: FormatVersion = FormatVersion::V2_6;
+
+    /// 由主、次版本号识别格式版本，未知版本返回 `None`
+    pub fn from_version(
+        major_version: u16,
+        minor_version: u16,
+    ) -> Option<Self> {
+        if major_version != constants::MAJOR_VERSION {
+            return None;
+        }
+        match minor_version {
+            constants::MINOR_VERSION => Some(Self::V2_4),
+            constants::LINK_TYPE_MINOR_VERSION => {
+                Some(Self::V2_5)
+            }
+            constants::EXTENSION_MINOR_VERSION => {
+                Some(Self::V2_6)
+            }
+            _ => None,
+        }
+    }
+
+    /// 主版本号
+    pub fn major_version(&self) -> u16 {
+        constants::MAJOR_VERSION
+    }
+
+    /// 次版本号
+    pub fn minor_version(&self) -> u16 {
+        match self {
+            Self::V2_4 => constants::MINOR_VERSION,
+            Self::V2_5 => {
+                constants::LINK_TYPE_MINOR_VERSION
+            }
+            Self::V2_6 => {
+                constants::EXTENSION_MINOR_VERSION
+            }
+        }
+    }
+
+    /// 该版本文件头固定部分的大小（字节），不含扩展块
+    pub fn header_size(&self) -> usize {
+        match self {
+            Self::V2_4 => PcapFileHeader::HEADER_SIZE,
+            Self::V2_5 => {
+                PcapFileHeader::EXTENDED_HEADER_SIZE
+            }
+            Self::V2_6 => {
+                PcapFileHeader::EXTENSIBLE_HEADER_SIZE
+            }
+        }
+    }
+
+    /// 该版本的文件头能否携带扩展块
+    pub fn supports_extensions(&self) -> bool {
+        *self >= Self::V2_6
+    }
+}
+
+impl std::fmt::Display for FormatVersion {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{}",
+            self.major_version(),
+            self.minor_version()
+        )
+    }
+}
+
+/// 数据包记录格式
+///
+/// 非默认格式记录在文件头的 `HeaderExtension::RecordFormat` 扩展块中，读取时自动识别。
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+)]
+pub enum RecordFormat {
+    /// 16 字节固定头部（默认）
+    #[default]
+    Standard,
+    /// 第 2 版记录格式：时间戳按与前一个数据包之差以变长整数存储，负载长度也按变长整数存储，
+    /// 适合高速率的小数据包记录，读取时透明还原为完整时间戳
+    DeltaTimestamp,
+    /// 第 3 版记录格式：16 字节固定头部之后追加 4 字节头部校验和，负载长度等头部字段损坏时
+    /// 读取立即报告，不会按错误的长度继续解析后续数据
+    CheckedHeader,
+}
+
+impl RecordFormat {
+    /// 记录在扩展块中的格式名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::DeltaTimestamp => "delta-timestamp",
+            Self::CheckedHeader => "checked-header",
+        }
+    }
+
+    /// 由格式名称识别记录格式，未知名称返回 `None`
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Standard,
+            Self::DeltaTimestamp,
+            Self::CheckedHeader,
+        ]
+        .into_iter()
+        .find(|format| format.name() == name)
+    }
+}
+
+/// 文件头扩展块
+///
+/// 记录在 2.6 版本文件头的扩展块区域，每个扩展块的值不超过 65535 字节。
+/// 未识别的类型码和值不是有效 UTF-8 的已知类型读取为 `Custom`，写回时保持原样。
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum HeaderExtension {
+    /// 负载的压缩算法
+    Compression(String),
+    /// 负载的加密算法
+    Encryption(String),
+    /// 链路类型的补充描述，如自定义链路类型的名称
+    LinkType(String),
+    /// 生成文件的程序
+    Creator(String),
+    /// 负载去重存储的文件名，位于数据文件所在目录
+    ///
+    /// 存在该扩展块时，数据包记录保存的是负载在存储文件中的引用而不是负载本身，
+    /// 读取时自动取回负载，见 `WriterConfig::payload_dedup`。
+    PayloadStore(String),
+    /// 数据包记录格式的名称，见 `RecordFormat::name`
+    RecordFormat(String),
+    /// 分块布局的块大小（字节，十进制文本），见 [`crate::data::block_layout`]
+    BlockSize(String),
+    /// 自定义扩展块
+    Custom {
+        /// 类型码
+        kind: u16,
+        /// 值
+        value: Vec<u8>,
+    },
+}
+
+impl HeaderExtension {
+    /// 压缩算法扩展块的类型码
+    pub const COMPRESSION: u16 = 1;
+    /// 加密算法扩展块的类型码
+    pub const ENCRYPTION: u16 = 2;
+    /// 链路类型描述扩展块的类型码
+    pub const LINK_TYPE: u16 = 3;
+    /// 生成程序扩展块的类型码
+    pub const CREATOR: u16 = 4;
+    /// 负载去重存储扩展块的类型码
+    pub const PAYLOAD_STORE: u16 = 5;
+    /// 记录格式扩展块的类型码
+    pub const RECORD_FORMAT: u16 = 6;
+    /// 块大小扩展块的类型码
+    pub const BLOCK_SIZE: u16 = 7;
+
+    /// 值的最大长度（字节）
+    pub const MAX_VALUE_LEN: usize = u16::MAX as usize;
+
+    /// 扩展块头部各字段的布局，见 [`format::EXTENSION_BLOCK_FIELDS`]
+    pub const FIELDS: &'static [FieldLayout] =
+        format::EXTENSION_BLOCK_FIELDS;
+
+    /// 由类型码和值创建扩展块
+    pub fn from_block(kind: u16, value: &[u8]) -> Self {
+        let text =
+            || String::from_utf8(value.to_vec()).ok();
+        let known = match kind {
+            Self::COMPRESSION => {
+                text().map(Self::Compression)
+            }
+            Self::ENCRYPTION => {
+                text().map(Self::Encryption)
+            }
+            Self::LINK_TYPE => text().map(Self::LinkType),
+            Self::CREATOR => text().map(Self::Creator),
+            Self::PAYLOAD_STORE => {
+                text().map(Self::PayloadStore)
+            }
+            Self::RECORD_FORMAT => {
+                text().map(Self::RecordFormat)
+            }
+            Self::BLOCK_SIZE => text().map(Self::BlockSize),
+            _ => None,
+        };
+        known.unwrap_or_else(|| Self::Custom {
+            kind,
+            value: value.to_vec(),
+        })
+    }
+
+    /// 类型码
+    pub fn kind(&self) -> u16 {
+        match self {
+            Self::Compression(_) => Self::COMPRESSION,
+            Self::Encryption(_) => Self::ENCRYPTION,
+            Self::LinkType(_) => Self::LINK_TYPE,
+            Self::Creator(_) => Self::CREATOR,
+            Self::PayloadStore(_) => Self::PAYLOAD_STORE,
+            Self::RecordFormat(_) => Self::RECORD_FORMAT,
+            Self::BlockSize(_) => Self::BLOCK_SIZE,
+            Self::Custom { kind, .. } => *kind,
+        }
+    }
+
+    /// 值的字节
+    pub fn value(&self) -> &[u8] {
+        match self {
+            Self::Compression(text)
+            | Self::Encryption(text)
+            | Self::LinkType(text)
+            | Self::Creator(text)
+            | Self::PayloadStore(text)
+            | Self::RecordFormat(text)
+            | Self::BlockSize(text) => text.as_bytes(),
+            Self::Custom { value, .. } => value,
+        }
+    }
+
+    /// 编码后的长度（字节）
+    pub fn encoded_len(&self) -> usize {
+        codec::EXTENSION_BLOCK_HEADER_SIZE
+            + self.value().len()
+    }
 }
 
 /// PCAP文件头结构
+///
+/// 次版本号为 4 时为 16 字节基础头部；次版本号为 5 时在末尾追加 4 字节链路类型，共 20 字节；
+/// 次版本号为 6 时再追加 4 字节扩展块区域长度，之后是扩展块。
+/// 未指定链路类型和扩展块时仍写出基础头部，保持与旧版本的兼容。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PcapFileHeader {
     /// 魔术数，固定值 0xD4C3B2A1
@@ -74,14 +584,40 @@ pub struct PcapFileHeader {
     pub timezone_offset: i32,
     /// 时间戳精度（纳秒）
     pub timestamp_accuracy: u32,
+    /// 链路类型（仅次版本号 5 及以上写入文件）
+    #[serde(default)]
+    pub link_type: LinkType,
+    /// 文件的字节序，由魔术数的字节排列识别
+    #[serde(default)]
+    pub byte_order: ByteOrder,
+    /// 扩展块（仅次版本号 6 写入文件）
+    #[serde(default)]
+    pub extensions: Vec<HeaderExtension>,
 }
 
 impl PcapFileHeader {
     /// 头部大小（字节）
-    pub const HEADER_SIZE: usize = 16; // 4 + 2 + 2 + 4 + 4
+    pub const HEADER_SIZE: usize = codec::FILE_HEADER_SIZE;
+
+    /// 携带链路类型的头部大小（字节）
+    pub const EXTENDED_HEADER_SIZE: usize =
+        codec::EXTENDED_FILE_HEADER_SIZE;
+
+    /// 携带扩展块区域的头部固定部分大小（字节）
+    pub const EXTENSIBLE_HEADER_SIZE: usize =
+        codec::EXTENSIBLE_FILE_HEADER_SIZE;
 
     /// 默认时间戳精度（纳秒）
-    pub const DEFAULT_TIMESTAMP_ACCURACY: u32 = 1;
+    pub const DEFAULT_TIMESTAMP_ACCURACY: u32 =
+        codec::DEFAULT_TIMESTAMP_ACCURACY;
+
+    /// 魔术数
+    pub const MAGIC_NUMBER: u32 =
+        constants::PCAP_MAGIC_NUMBER;
+
+    /// 文件头各字段的布局，见 [`format::FILE_HEADER_FIELDS`]
+    pub const FIELDS: &'static [FieldLayout] =
+        format::FILE_HEADER_FIELDS;
 
     /// 创建新的PCAP文件头
     pub fn new(timezone_offset: i32) -> Self {
@@ -92,69 +628,366 @@ impl PcapFileHeader {
             timezone_offset,
             timestamp_accuracy:
                 Self::DEFAULT_TIMESTAMP_ACCURACY,
+            link_type: LinkType::Unknown,
+            byte_order: ByteOrder::Little,
+            extensions: Vec::new(),
+        }
+    }
+
+    /// 设置文件的字节序
+    pub fn with_byte_order(
+        mut self,
+        byte_order: ByteOrder,
+    ) -> Self {
+        self.byte_order = byte_order;
+        self
+    }
+
+    /// 设置扩展块
+    ///
+    /// 扩展块不为空时把文件头升级到 2.6 版本，以便写出扩展块区域。
+    pub fn with_extensions(
+        mut self,
+        extensions: Vec<HeaderExtension>,
+    ) -> Self {
+        if !extensions.is_empty()
+            && !self.has_extension_area()
+        {
+            self.minor_version =
+                constants::EXTENSION_MINOR_VERSION;
         }
+        self.extensions = extensions;
+        self
+    }
+
+    /// 扩展块
+    pub fn extensions(&self) -> &[HeaderExtension] {
+        &self.extensions
+    }
+
+    /// 查找指定类型码的第一个扩展块
+    pub fn find_extension(
+        &self,
+        kind: u16,
+    ) -> Option<&HeaderExtension> {
+        self.extensions
+            .iter()
+            .find(|extension| extension.kind() == kind)
+    }
+
+    /// 数据包记录格式，没有记录格式扩展块时为 `RecordFormat::Standard`
+    ///
+    /// # 错误
+    /// 扩展块记录了无法识别的格式名称时返回错误
+    pub fn record_format(
+        &self,
+    ) -> Result<RecordFormat, String> {
+        let Some(extension) = self
+            .find_extension(HeaderExtension::RECORD_FORMAT)
+        else {
+            return Ok(RecordFormat::Standard);
+        };
+        std::str::from_utf8(extension.value())
+            .ok()
+            .and_then(RecordFormat::from_name)
+            .ok_or_else(|| {
+                format!(
+                    "不支持的记录格式: {}",
+                    String::from_utf8_lossy(
+                        extension.value()
+                    )
+                )
+            })
+    }
+
+    /// 分块布局的块大小，没有块大小扩展块时为 `None`
+    ///
+    /// # 错误
+    /// 块大小不是十进制整数或超出允许范围时返回错误
+    pub fn block_size(
+        &self,
+    ) -> Result<Option<u64>, String> {
+        let Some(extension) = self
+            .find_extension(HeaderExtension::BLOCK_SIZE)
+        else {
+            return Ok(None);
+        };
+        std::str::from_utf8(extension.value())
+            .ok()
+            .and_then(|text| text.parse::<u64>().ok())
+            .filter(|size| {
+                (block_layout::MIN_BLOCK_SIZE
+                    ..=block_layout::MAX_BLOCK_SIZE)
+                    .contains(size)
+            })
+            .map(Some)
+            .ok_or_else(|| {
+                format!(
+                    "无效的块大小: {}",
+                    String::from_utf8_lossy(
+                        extension.value()
+                    )
+                )
+            })
+    }
+
+    /// 创建带链路类型的文件头
+    ///
+    /// 链路类型为 `Unknown` 时等同于 `new`，写出兼容旧版本的基础头部。
+    pub fn with_link_type(
+        timezone_offset: i32,
+        link_type: LinkType,
+    ) -> Self {
+        let mut header = Self::new(timezone_offset);
+        if link_type != LinkType::Unknown {
+            header.minor_version =
+                constants::LINK_TYPE_MINOR_VERSION;
+            header.link_type = link_type;
+        }
+        header
+    }
+
+    /// 创建指定格式版本的文件头
+    ///
+    /// 2.4 版本不携带链路类型，传入的链路类型被忽略；2.5 及以上版本即使链路类型为
+    /// `Unknown` 也写出链路类型字段。
+    pub fn for_version(
+        version: FormatVersion,
+        timezone_offset: i32,
+        link_type: LinkType,
+    ) -> Self {
+        let mut header = Self::new(timezone_offset);
+        header.minor_version = version.minor_version();
+        if version >= FormatVersion::V2_5 {
+            header.link_type = link_type;
+        }
+        header
+    }
+
+    /// 文件头对应的格式版本，版本号未知时返回 `None`
+    pub fn format_version(&self) -> Option<FormatVersion> {
+        FormatVersion::from_version(
+            self.major_version,
+            self.minor_version,
+        )
+    }
+
+    /// 当前版本文件头的实际大小（字节），包含扩展块，即数据区的起始偏移
+    pub fn header_size(&self) -> usize {
+        FileHeader::from(self).encoded_len()
+    }
+
+    /// 文件头是否包含扩展块区域
+    ///
+    /// 只有 2.6 版本携带扩展块区域，更高的未知版本按 2.5 的布局解析。
+    #[inline]
+    pub fn has_extension_area(&self) -> bool {
+        self.minor_version
+            == constants::EXTENSION_MINOR_VERSION
+    }
+
+    /// 写入文件的扩展块
+    ///
+    /// 没有扩展块区域的版本不写出扩展块，值超过 `HeaderExtension::MAX_VALUE_LEN`
+    /// 的扩展块被跳过。
+    fn encoded_extensions(
+        &self,
+    ) -> impl Iterator<Item = &HeaderExtension> {
+        let enabled = self.has_extension_area();
+        self.extensions.iter().filter(move |extension| {
+            enabled
+                && extension.value().len()
+                    <= HeaderExtension::MAX_VALUE_LEN
+        })
+    }
+
+    /// 文件头是否包含链路类型字段
+    #[inline]
+    pub fn has_link_type_field(&self) -> bool {
+        self.minor_version
+            >= constants::LINK_TYPE_MINOR_VERSION
     }
 
     /// 从字节数组创建文件头
+    ///
+    /// 携带扩展块区域时 `bytes` 需包含完整的扩展块。
     pub fn from_bytes(
         bytes: &[u8],
     ) -> Result<Self, String> {
-        if bytes.len() < Self::HEADER_SIZE {
-            return Err("字节数组长度不足".to_string());
-        }
-
-        let magic_number = u32::from_le_bytes([
-            bytes[0], bytes[1], bytes[2], bytes[3],
-        ]);
-        let major_version =
-            u16::from_le_bytes([bytes[4], bytes[5]]);
-        let minor_version =
-            u16::from_le_bytes([bytes[6], bytes[7]]);
-        let timezone_offset = i32::from_le_bytes([
-            bytes[8], bytes[9], bytes[10], bytes[11],
-        ]);
-        let timestamp_accuracy = u32::from_le_bytes([
-            bytes[12], bytes[13], bytes[14], bytes[15],
-        ]);
+        let header = FileHeader::decode(bytes)
+            .map_err(|e| e.to_string())?;
+        let extensions = header
+            .extension_blocks(bytes)
+            .map(|block| {
+                block.map(|block| {
+                    HeaderExtension::from_block(
+                        block.kind,
+                        block.value,
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("扩展块解析失败: {e}"))?;
 
-        Ok(Self {
-            magic_number,
-            major_version,
-            minor_version,
-            timezone_offset,
-            timestamp_accuracy,
-        })
+        let mut parsed = Self::from(header);
+        parsed.extensions = extensions;
+        Ok(parsed)
     }
 
     /// 转换为字节数组
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes =
-            Vec::with_capacity(Self::HEADER_SIZE);
-        bytes.extend_from_slice(
-            &self.magic_number.to_le_bytes(),
-        );
-        bytes.extend_from_slice(
-            &self.major_version.to_le_bytes(),
-        );
-        bytes.extend_from_slice(
-            &self.minor_version.to_le_bytes(),
-        );
-        bytes.extend_from_slice(
-            &self.timezone_offset.to_le_bytes(),
-        );
-        bytes.extend_from_slice(
-            &self.timestamp_accuracy.to_le_bytes(),
-        );
+        let header = FileHeader::from(self);
+        let mut bytes = vec![0u8; header.encoded_len()];
+        // 缓冲区按编码长度分配，编码不会失败
+        let mut offset =
+            header.encode(&mut bytes).unwrap_or_default();
+        for extension in self.encoded_extensions() {
+            offset += codec::encode_extension_block(
+                extension.kind(),
+                extension.value(),
+                self.byte_order,
+                &mut bytes[offset..],
+            )
+            .unwrap_or_default();
+        }
         bytes
     }
 
     /// 验证文件头是否有效
     pub fn is_valid(&self) -> bool {
-        self.magic_number == constants::PCAP_MAGIC_NUMBER
-            && self.major_version
-                == constants::MAJOR_VERSION
-            && self.minor_version
-                == constants::MINOR_VERSION
+        FileHeader::from(self).is_valid()
+    }
+
+    /// 严格解析文件头，逐个检查字段并报告第一个无效的字段
+    ///
+    /// 与 `from_bytes` 不同，魔术数和版本号无效时也返回错误。字节序由魔术数识别，
+    /// 任意输入都不会导致 panic，可以直接作为模糊测试的目标。
+    pub fn parse_strict(
+        bytes: &[u8],
+    ) -> Result<Self, HeaderFieldError> {
+        HeaderFieldError::check_len(
+            bytes,
+            Self::HEADER_SIZE,
+        )?;
+
+        let Some(order) = ByteOrder::detect(bytes) else {
+            let magic_number =
+                ByteOrder::Little.read_u32(bytes, 0);
+            return Err(HeaderFieldError::new(
+                "magic_number",
+                0,
+                format!(
+                    "0x{:08X}",
+                    constants::PCAP_MAGIC_NUMBER
+                ),
+                format!("0x{magic_number:08X}"),
+            ));
+        };
+        let major_version = order.read_u16(bytes, 4);
+        if major_version != constants::MAJOR_VERSION {
+            return Err(HeaderFieldError::new(
+                "major_version",
+                4,
+                constants::MAJOR_VERSION.to_string(),
+                major_version.to_string(),
+            ));
+        }
+        let minor_version = order.read_u16(bytes, 6);
+        let Some(version) = FormatVersion::from_version(
+            major_version,
+            minor_version,
+        ) else {
+            return Err(HeaderFieldError::new(
+                "minor_version",
+                6,
+                format!(
+                    "{}、{} 或 {}",
+                    constants::MINOR_VERSION,
+                    constants::LINK_TYPE_MINOR_VERSION,
+                    constants::EXTENSION_MINOR_VERSION
+                ),
+                minor_version.to_string(),
+            ));
+        };
+        HeaderFieldError::check_len(
+            bytes,
+            version.header_size(),
+        )?;
+
+        let header =
+            FileHeader::decode(bytes).map_err(|e| {
+                HeaderFieldError::new(
+                    "bytes",
+                    0,
+                    "可解析的文件头".to_string(),
+                    e.to_string(),
+                )
+            })?;
+        HeaderFieldError::check_len(
+            bytes,
+            header.encoded_len(),
+        )?;
+
+        let mut extensions = Vec::new();
+        let mut offset = header.fixed_len();
+        for block in header.extension_blocks(bytes) {
+            let block = block.map_err(|e| {
+                HeaderFieldError::new(
+                    "extensions",
+                    offset,
+                    "完整的扩展块".to_string(),
+                    e.to_string(),
+                )
+            })?;
+            offset += block.encoded_len();
+            extensions.push(HeaderExtension::from_block(
+                block.kind,
+                block.value,
+            ));
+        }
+
+        let mut parsed = Self::from(header);
+        parsed.extensions = extensions;
+        Ok(parsed)
+    }
+}
+
+/// 只转换固定部分，扩展块需另行解析
+impl From<FileHeader> for PcapFileHeader {
+    fn from(header: FileHeader) -> Self {
+        Self {
+            magic_number: header.magic_number,
+            major_version: header.major_version,
+            minor_version: header.minor_version,
+            timezone_offset: header.timezone_offset,
+            timestamp_accuracy: header.timestamp_accuracy,
+            link_type: if header.has_link_type_field() {
+                LinkType::from_u32(header.link_type)
+            } else {
+                LinkType::Unknown
+            },
+            byte_order: header.byte_order,
+            extensions: Vec::new(),
+        }
+    }
+}
+
+impl From<&PcapFileHeader> for FileHeader {
+    fn from(header: &PcapFileHeader) -> Self {
+        Self {
+            magic_number: header.magic_number,
+            major_version: header.major_version,
+            minor_version: header.minor_version,
+            timezone_offset: header.timezone_offset,
+            timestamp_accuracy: header.timestamp_accuracy,
+            link_type: header.link_type.to_u32(),
+            extensions_length: header
+                .encoded_extensions()
+                .map(HeaderExtension::encoded_len)
+                .sum::<usize>()
+                as u32,
+            byte_order: header.byte_order,
+        }
     }
 }
 
@@ -173,7 +1006,16 @@ pub struct DataPacketHeader {
 
 impl DataPacketHeader {
     /// 头部大小（字节）
-    pub const HEADER_SIZE: usize = 16; // 4 + 4 + 4 + 4
+    pub const HEADER_SIZE: usize =
+        codec::PACKET_HEADER_SIZE;
+
+    /// 头部各字段的布局，见 [`format::PACKET_HEADER_FIELDS`]
+    pub const FIELDS: &'static [FieldLayout] =
+        format::PACKET_HEADER_FIELDS;
+
+    /// 心跳包头部的校验和字段取值，见 [`codec::HEARTBEAT_CHECKSUM`]
+    pub const HEARTBEAT_CHECKSUM: u32 =
+        codec::HEARTBEAT_CHECKSUM;
 
     /// 创建新的数据包头部
     pub fn new(
@@ -191,15 +1033,17 @@ impl DataPacketHeader {
     }
 
     /// 从DateTime创建数据包头部
+    ///
+    /// # 错误
+    /// 捕获时间早于 Unix 纪元或晚于 2106 年（32 位秒数上限）时返回错误。
+    /// 闰秒（`23:59:60`）按该分钟最后一秒的最后一纳秒记录，保持时间戳不回退。
     pub fn from_datetime(
         capture_time: DateTime<Utc>,
         packet_length: u32,
         checksum: u32,
     ) -> Result<Self, String> {
-        let timestamp_seconds =
-            capture_time.timestamp() as u32;
-        let timestamp_nanoseconds =
-            capture_time.timestamp_subsec_nanos();
+        let (timestamp_seconds, timestamp_nanoseconds) =
+            split_datetime(capture_time)?;
 
         Self::new(
             timestamp_seconds,
@@ -227,52 +1071,130 @@ impl DataPacketHeader {
         )
     }
 
-    /// 从字节数组创建头部
-    pub fn from_bytes(
-        bytes: &[u8],
+    /// 从纳秒时间戳和数据包数据创建头部
+    pub fn from_timestamp_ns(
+        timestamp_ns: u64,
+        packet_data: &[u8],
     ) -> Result<Self, String> {
-        if bytes.len() < Self::HEADER_SIZE {
-            return Err("字节数组长度不足".to_string());
-        }
-
-        let timestamp_seconds = u32::from_le_bytes([
-            bytes[0], bytes[1], bytes[2], bytes[3],
-        ]);
-        let timestamp_nanoseconds = u32::from_le_bytes([
-            bytes[4], bytes[5], bytes[6], bytes[7],
-        ]);
-        let packet_length = u32::from_le_bytes([
-            bytes[8], bytes[9], bytes[10], bytes[11],
-        ]);
-        let checksum = u32::from_le_bytes([
-            bytes[12], bytes[13], bytes[14], bytes[15],
-        ]);
+        let (timestamp_seconds, timestamp_nanoseconds) =
+            split_timestamp_ns(timestamp_ns)?;
+        let checksum =
+            crate::foundation::utils::calculate_crc32(
+                packet_data,
+            );
 
         Self::new(
             timestamp_seconds,
             timestamp_nanoseconds,
-            packet_length,
+            packet_data.len() as u32,
             checksum,
         )
     }
 
+    /// 以纳秒时间戳创建心跳包头部
+    ///
+    /// 心跳包的负载长度为 0，校验和字段为 [`Self::HEARTBEAT_CHECKSUM`]。
+    pub fn heartbeat(
+        timestamp_ns: u64,
+    ) -> Result<Self, String> {
+        let (timestamp_seconds, timestamp_nanoseconds) =
+            split_timestamp_ns(timestamp_ns)?;
+        Self::new(
+            timestamp_seconds,
+            timestamp_nanoseconds,
+            0,
+            Self::HEARTBEAT_CHECKSUM,
+        )
+    }
+
+    /// 从有符号纳秒时间戳和数据包数据创建头部
+    ///
+    /// 适合来自 `i64`/`i128` 时间源的时间戳：负数（早于 Unix 纪元）和超出文件格式范围的值
+    /// 返回错误，而不是截断后写入错误的时间。
+    pub fn try_from_unix_ns(
+        timestamp_ns: i128,
+        packet_data: &[u8],
+    ) -> Result<Self, String> {
+        Self::from_timestamp_ns(
+            unix_ns_to_u64(timestamp_ns)?,
+            packet_data,
+        )
+    }
+
+    /// 从字节数组创建头部
+    pub fn from_bytes(
+        bytes: &[u8],
+    ) -> Result<Self, String> {
+        Self::from_bytes_with_order(
+            bytes,
+            ByteOrder::Little,
+        )
+    }
+
+    /// 按指定字节序从字节数组创建头部
+    pub fn from_bytes_with_order(
+        bytes: &[u8],
+        order: ByteOrder,
+    ) -> Result<Self, String> {
+        PacketHeader::decode_with_order(bytes, order)
+            .map(Self::from)
+            .map_err(|e| e.to_string())
+    }
+
+    /// 严格解析数据包头部，逐个检查字段并报告第一个无效的字段
+    ///
+    /// 检查字节数和纳秒字段的范围，不检查负载。任意输入都不会导致 panic。
+    pub fn parse_strict(
+        bytes: &[u8],
+    ) -> Result<Self, HeaderFieldError> {
+        Self::parse_strict_with_order(
+            bytes,
+            ByteOrder::Little,
+        )
+    }
+
+    /// 按指定字节序严格解析数据包头部
+    pub fn parse_strict_with_order(
+        bytes: &[u8],
+        order: ByteOrder,
+    ) -> Result<Self, HeaderFieldError> {
+        HeaderFieldError::check_len(
+            bytes,
+            Self::HEADER_SIZE,
+        )?;
+
+        let timestamp_nanoseconds =
+            order.read_u32(bytes, 4);
+        if timestamp_nanoseconds >= 1_000_000_000 {
+            return Err(HeaderFieldError::new(
+                "timestamp_nanoseconds",
+                4,
+                "小于 1000000000".to_string(),
+                timestamp_nanoseconds.to_string(),
+            ));
+        }
+
+        Ok(Self {
+            timestamp_seconds: order.read_u32(bytes, 0),
+            timestamp_nanoseconds,
+            packet_length: order.read_u32(bytes, 8),
+            checksum: order.read_u32(bytes, 12),
+        })
+    }
+
     /// 转换为字节数组
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes =
-            Vec::with_capacity(Self::HEADER_SIZE);
-        bytes.extend_from_slice(
-            &self.timestamp_seconds.to_le_bytes(),
-        );
-        bytes.extend_from_slice(
-            &self.timestamp_nanoseconds.to_le_bytes(),
-        );
-        bytes.extend_from_slice(
-            &self.packet_length.to_le_bytes(),
-        );
-        bytes.extend_from_slice(
-            &self.checksum.to_le_bytes(),
-        );
-        bytes
+        PacketHeader::from(self).to_bytes().to_vec()
+    }
+
+    /// 按指定字节序转换为字节数组
+    pub fn to_bytes_with_order(
+        &self,
+        order: ByteOrder,
+    ) -> Vec<u8> {
+        PacketHeader::from(self)
+            .to_bytes_with_order(order)
+            .to_vec()
     }
 
     /// 获取捕获时间
@@ -286,6 +1208,198 @@ impl DataPacketHeader {
             Utc::now()
         })
     }
+
+    /// 获取时间戳（纳秒）
+    #[inline]
+    pub fn timestamp_ns(&self) -> u64 {
+        self.timestamp_seconds as u64 * 1_000_000_000
+            + self.timestamp_nanoseconds as u64
+    }
+
+    /// 是否为心跳包
+    ///
+    /// 心跳包由写入器在空闲期间写入，表示记录程序仍在运行。心跳包的负载长度为 0，
+    /// 校验和字段为 [`Self::HEARTBEAT_CHECKSUM`]；用户写入的空数据包校验和为 0，不是心跳包。
+    #[inline]
+    pub fn is_heartbeat(&self) -> bool {
+        self.packet_length == 0
+            && self.checksum == Self::HEARTBEAT_CHECKSUM
+    }
+
+    /// 负载是否与头部的校验和一致，心跳包的负载须为空
+    pub fn checksum_matches(&self, data: &[u8]) -> bool {
+        if self.is_heartbeat() {
+            data.is_empty()
+        } else {
+            crate::foundation::utils::calculate_crc32(data)
+                == self.checksum
+        }
+    }
+
+    /// 设置时间戳（纳秒），长度和校验和不变
+    pub fn set_timestamp_ns(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> Result<(), String> {
+        let (seconds, nanoseconds) =
+            split_timestamp_ns(timestamp_ns)?;
+        self.timestamp_seconds = seconds;
+        self.timestamp_nanoseconds = nanoseconds;
+        Ok(())
+    }
+}
+
+impl From<PacketHeader> for DataPacketHeader {
+    fn from(header: PacketHeader) -> Self {
+        Self {
+            timestamp_seconds: header.timestamp_seconds,
+            timestamp_nanoseconds: header
+                .timestamp_nanoseconds,
+            packet_length: header.packet_length,
+            checksum: header.checksum,
+        }
+    }
+}
+
+impl From<&DataPacketHeader> for PacketHeader {
+    fn from(header: &DataPacketHeader) -> Self {
+        Self {
+            timestamp_seconds: header.timestamp_seconds,
+            timestamp_nanoseconds: header
+                .timestamp_nanoseconds,
+            packet_length: header.packet_length,
+            checksum: header.checksum,
+        }
+    }
+}
+
+/// 严格解析头部时发现的无效字段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderFieldError {
+    /// 字段名称，字节数不足时为 `bytes`
+    pub field: &'static str,
+    /// 字段在头部中的字节偏移
+    pub offset: usize,
+    /// 期望的值
+    pub expected: String,
+    /// 实际的值
+    pub found: String,
+}
+
+impl HeaderFieldError {
+    /// 创建字段错误
+    pub fn new(
+        field: &'static str,
+        offset: usize,
+        expected: String,
+        found: String,
+    ) -> Self {
+        Self {
+            field,
+            offset,
+            expected,
+            found,
+        }
+    }
+
+    /// 检查字节数是否足够
+    fn check_len(
+        bytes: &[u8],
+        required: usize,
+    ) -> Result<(), Self> {
+        if bytes.len() < required {
+            return Err(Self::new(
+                "bytes",
+                0,
+                format!("至少 {required} 字节"),
+                format!("{} 字节", bytes.len()),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for HeaderFieldError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "字段 {}（偏移 {}）: 期望 {}, 实际 {}",
+            self.field,
+            self.offset,
+            self.expected,
+            self.found
+        )
+    }
+}
+
+impl std::error::Error for HeaderFieldError {}
+
+/// 把纳秒时间戳拆分为文件格式中的秒和纳秒字段
+fn split_timestamp_ns(
+    timestamp_ns: u64,
+) -> Result<(u32, u32), String> {
+    let seconds = timestamp_ns / 1_000_000_000;
+    match u32::try_from(seconds) {
+        Ok(seconds) => Ok((
+            seconds,
+            (timestamp_ns % 1_000_000_000) as u32,
+        )),
+        Err(_) => Err(format!(
+            "时间戳 {timestamp_ns} 超出文件格式支持的范围"
+        )),
+    }
+}
+
+/// 把 DateTime 拆分为文件格式的秒和纳秒
+///
+/// chrono 用 1_000_000_000 以上的纳秒数表示闰秒，这里截断为 999_999_999。
+fn split_datetime(
+    capture_time: DateTime<Utc>,
+) -> Result<(u32, u32), String> {
+    let seconds = capture_time.timestamp();
+    if seconds < 0 {
+        return Err(format!(
+            "捕获时间 {capture_time} 早于 Unix 纪元"
+        ));
+    }
+    let seconds = u32::try_from(seconds).map_err(|_| {
+        format!("捕获时间 {capture_time} 超出文件格式支持的范围")
+    })?;
+    let nanoseconds = capture_time
+        .timestamp_subsec_nanos()
+        .min(999_999_999);
+    Ok((seconds, nanoseconds))
+}
+
+/// 把有符号纳秒时间戳转换为无符号时间戳，负数视为早于 Unix 纪元
+fn unix_ns_to_u64(
+    timestamp_ns: i128,
+) -> Result<u64, String> {
+    if timestamp_ns < 0 {
+        return Err(format!(
+            "时间戳 {timestamp_ns} 早于 Unix 纪元"
+        ));
+    }
+    u64::try_from(timestamp_ns).map_err(|_| {
+        format!(
+            "时间戳 {timestamp_ns} 超出文件格式支持的范围"
+        )
+    })
+}
+
+/// 按偏移量平移纳秒时间戳，结果不能早于 Unix 纪元
+fn offset_timestamp_ns(
+    timestamp_ns: u64,
+    delta_ns: i64,
+) -> Result<u64, String> {
+    timestamp_ns
+        .checked_add_signed(delta_ns)
+        .ok_or_else(|| {
+            format!("时间戳 {timestamp_ns} 平移 {delta_ns} 纳秒后超出范围")
+        })
 }
 
 /// 数据包结构
@@ -295,6 +1409,9 @@ pub struct DataPacket {
     pub header: DataPacketHeader,
     /// 数据包内容
     pub data: Vec<u8>,
+    /// 链路类型（读取时取自文件头，不写入数据包头部）
+    #[serde(default)]
+    pub link_type: LinkType,
 }
 
 impl DataPacket {
@@ -309,10 +1426,33 @@ impl DataPacket {
             );
         }
 
-        Ok(Self { header, data })
+        Ok(Self {
+            header,
+            data,
+            link_type: LinkType::Unknown,
+        })
+    }
+
+    /// 设置链路类型
+    pub fn with_link_type(
+        mut self,
+        link_type: LinkType,
+    ) -> Self {
+        self.link_type = link_type;
+        self
+    }
+
+    /// 获取链路类型
+    #[inline]
+    pub fn link_type(&self) -> LinkType {
+        self.link_type
     }
 
     /// 从DateTime和数据创建数据包
+    ///
+    /// # 错误
+    /// 捕获时间早于 Unix 纪元或超出文件格式支持的范围时返回错误，
+    /// 见 [`DataPacketHeader::from_datetime`]
     pub fn from_datetime(
         capture_time: DateTime<Utc>,
         data: Vec<u8>,
@@ -324,6 +1464,46 @@ impl DataPacket {
         Self::new(header, data)
     }
 
+    /// 以时钟源的当前时间和数据创建数据包
+    ///
+    /// ```
+    /// use pcapfile_io::{DataPacket, InjectedClock};
+    ///
+    /// let clock = InjectedClock::new(1_700_000_000_123_456_789);
+    /// let packet = DataPacket::from_now(&clock, vec![1, 2, 3]).unwrap();
+    /// assert_eq!(packet.get_timestamp_ns(), 1_700_000_000_123_456_789);
+    /// ```
+    pub fn from_now<C: ClockSource + ?Sized>(
+        clock: &C,
+        data: Vec<u8>,
+    ) -> Result<Self, String> {
+        let header = DataPacketHeader::from_timestamp_ns(
+            clock.now_ns(),
+            &data,
+        )?;
+        Self::new(header, data)
+    }
+
+    /// 从有符号纳秒时间戳和数据创建数据包
+    ///
+    /// ```
+    /// use pcapfile_io::DataPacket;
+    ///
+    /// let packet = DataPacket::try_from_unix_ns(1_700_000_000_000_000_000, vec![1]).unwrap();
+    /// assert_eq!(packet.get_timestamp_ns(), 1_700_000_000_000_000_000);
+    /// assert!(DataPacket::try_from_unix_ns(-1, vec![1]).is_err());
+    /// ```
+    pub fn try_from_unix_ns(
+        timestamp_ns: i128,
+        data: Vec<u8>,
+    ) -> Result<Self, String> {
+        let header = DataPacketHeader::try_from_unix_ns(
+            timestamp_ns,
+            &data,
+        )?;
+        Self::new(header, data)
+    }
+
     /// 从时间戳和数据创建数据包
     pub fn from_timestamp(
         timestamp_seconds: u32,
@@ -358,6 +1538,12 @@ impl DataPacket {
         self.data.len()
     }
 
+    /// 是否为心跳包，见 [`DataPacketHeader::is_heartbeat`]
+    #[inline]
+    pub fn is_heartbeat(&self) -> bool {
+        self.header.is_heartbeat()
+    }
+
     /// 获取总大小（头部 + 数据）
     #[inline]
     pub fn total_size(&self) -> usize {
@@ -373,19 +1559,78 @@ impl DataPacket {
     /// 获取时间戳（纳秒）
     #[inline]
     pub fn get_timestamp_ns(&self) -> u64 {
-        let capture_time = self.capture_time();
-        capture_time.timestamp() as u64 * 1_000_000_000
-            + capture_time.timestamp_subsec_nanos() as u64
+        self.header.timestamp_ns()
+    }
+
+    /// 替换时间戳（纳秒），负载和校验和保持不变
+    pub fn with_timestamp(
+        mut self,
+        timestamp_ns: u64,
+    ) -> Result<Self, String> {
+        self.header.set_timestamp_ns(timestamp_ns)?;
+        Ok(self)
+    }
+
+    /// 设置捕获时间
+    ///
+    /// # 错误
+    /// 捕获时间早于 Unix 纪元或超出文件格式支持的范围时返回错误
+    pub fn set_capture_time(
+        &mut self,
+        capture_time: DateTime<Utc>,
+    ) -> Result<(), String> {
+        let (seconds, nanoseconds) =
+            split_datetime(capture_time)?;
+        self.header.timestamp_seconds = seconds;
+        self.header.timestamp_nanoseconds = nanoseconds;
+        Ok(())
+    }
+
+    /// 按偏移量平移时间戳（纳秒，可为负）
+    pub fn shift_timestamp(
+        &mut self,
+        delta_ns: i64,
+    ) -> Result<(), String> {
+        let shifted = offset_timestamp_ns(
+            self.header.timestamp_ns(),
+            delta_ns,
+        )?;
+        self.header.set_timestamp_ns(shifted)
+    }
+
+    /// 批量平移一组数据包的时间戳（纳秒，可为负）
+    ///
+    /// 先检查所有数据包平移后的时间戳是否有效再统一修改，
+    /// 任一数据包超出范围时返回错误且不修改任何数据包。
+    pub fn shift_timestamps(
+        packets: &mut [DataPacket],
+        delta_ns: i64,
+    ) -> Result<(), String> {
+        let shifted = packets
+            .iter()
+            .map(|packet| {
+                offset_timestamp_ns(
+                    packet.header.timestamp_ns(),
+                    delta_ns,
+                )
+                .and_then(split_timestamp_ns)
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        for (packet, (seconds, nanoseconds)) in
+            packets.iter_mut().zip(shifted)
+        {
+            packet.header.timestamp_seconds = seconds;
+            packet.header.timestamp_nanoseconds =
+                nanoseconds;
+        }
+        Ok(())
     }
 
     /// 验证数据包是否有效
     #[inline]
     pub fn is_valid(&self) -> bool {
-        let calculated_checksum =
-            crate::foundation::utils::calculate_crc32(
-                &self.data,
-            );
-        calculated_checksum == self.header.checksum
+        self.header.checksum_matches(&self.data)
     }
 
     /// 转换为字节数组（头部 + 数据）
@@ -517,6 +1762,15 @@ pub struct FileInfo {
     pub modified_time: String,
     /// 是否有效
     pub is_valid: bool,
+    /// 文件头（版本号、魔术数、链路类型、扩展块等），未读取到时为 `None`
+    #[serde(default)]
+    pub header: Option<PcapFileHeader>,
+    /// 写入该文件时使用的轮换策略，只有写入器返回的文件信息携带
+    #[serde(default)]
+    pub rotation_policy: Option<RotationPolicy>,
+    /// 该文件被关闭的原因，正在写入或写入器完成时关闭的文件为 `None`
+    #[serde(default)]
+    pub rotation_reason: Option<RotationReason>,
 }
 
 impl FileInfo {
@@ -544,6 +1798,9 @@ impl FileInfo {
             created_time: Utc::now().to_rfc3339(),
             modified_time: Utc::now().to_rfc3339(),
             is_valid: false,
+            header: None,
+            rotation_policy: None,
+            rotation_reason: None,
         }
     }
 
@@ -585,6 +1842,9 @@ impl FileInfo {
             created_time,
             modified_time,
             is_valid: path.exists() && metadata.is_file(),
+            header: None,
+            rotation_policy: None,
+            rotation_reason: None,
         })
     }
 
@@ -0,0 +1,284 @@
+//! 负载去重存储模块
+//!
+//! 启用负载去重时，内容相同的负载只在数据集目录下的存储文件中保存一次，
+//! 数据文件中的数据包记录只保存 12 字节的引用（8 字节偏移量和 4 字节长度，按数据文件的字节序），
+//! 读取时按引用取回负载。存储文件由若干负载块依次组成，每块为 4 字节长度（小端）和负载字节，
+//! 只追加不修改，删除或截断数据文件后不再被引用的负载块不会回收。
+
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::core::ByteOrder;
+use crate::foundation::error::{PcapError, PcapResult};
+use crate::foundation::utils::{
+    calculate_crc32, calculate_payload_hash,
+};
+
+/// 负载存储文件名
+pub const PAYLOAD_STORE_FILE_NAME: &str = ".payloads";
+
+/// 负载块头部（长度字段）大小
+const BLOCK_HEADER_SIZE: u64 = 4;
+
+/// 数据包记录中的负载引用
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayloadReference {
+    /// 负载在存储文件中的起始偏移（负载块头部之后）
+    pub offset: u64,
+    /// 负载长度
+    pub length: u32,
+}
+
+impl PayloadReference {
+    /// 编码后的大小（字节）
+    pub const SIZE: usize = 12;
+
+    /// 按字节序编码
+    pub fn to_bytes(
+        &self,
+        order: ByteOrder,
+    ) -> [u8; Self::SIZE] {
+        let (offset, length) = match order {
+            ByteOrder::Little => (
+                self.offset.to_le_bytes(),
+                self.length.to_le_bytes(),
+            ),
+            ByteOrder::Big => (
+                self.offset.to_be_bytes(),
+                self.length.to_be_bytes(),
+            ),
+        };
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..8].copy_from_slice(&offset);
+        bytes[8..].copy_from_slice(&length);
+        bytes
+    }
+
+    /// 按字节序解码，长度不是 12 字节时返回 `None`
+    pub fn from_bytes(
+        bytes: &[u8],
+        order: ByteOrder,
+    ) -> Option<Self> {
+        if bytes.len() != Self::SIZE {
+            return None;
+        }
+        let mut offset = [0u8; 8];
+        offset.copy_from_slice(&bytes[..8]);
+        let offset = match order {
+            ByteOrder::Little => u64::from_le_bytes(offset),
+            ByteOrder::Big => u64::from_be_bytes(offset),
+        };
+        Some(Self {
+            offset,
+            length: order.read_u32(bytes, 8),
+        })
+    }
+}
+
+/// 负载的去重键：内容哈希、CRC32 和长度
+type PayloadKey = (u64, u32, u32);
+
+fn payload_key(data: &[u8]) -> PayloadKey {
+    (
+        calculate_payload_hash(data),
+        calculate_crc32(data),
+        data.len() as u32,
+    )
+}
+
+/// 负载存储的写入端
+///
+/// 打开时扫描已有的负载块重建去重表，同一数据集的多次写入共享同一个存储文件。
+/// 负载块直接写入文件而不经过缓冲区，读取器随时可以按引用取回已写入的负载。
+pub(crate) struct PayloadStore {
+    file: File,
+    /// 去重键到负载起始偏移的映射
+    offsets: HashMap<PayloadKey, u64>,
+    /// 存储文件当前大小
+    size: u64,
+}
+
+impl PayloadStore {
+    /// 打开或创建目录下的负载存储
+    ///
+    /// 文件末尾不完整的负载块（写入中断留下）会被截掉。
+    pub(crate) fn open(
+        directory: &Path,
+    ) -> PcapResult<Self> {
+        let path = directory.join(PAYLOAD_STORE_FILE_NAME);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(PcapError::Io)?;
+        let file_size =
+            file.metadata().map_err(PcapError::Io)?.len();
+
+        let mut offsets = HashMap::new();
+        let mut reader = BufReader::new(&mut file);
+        let mut position = 0u64;
+        let mut data = Vec::new();
+        while position + BLOCK_HEADER_SIZE <= file_size {
+            let mut length = [0u8; 4];
+            reader
+                .read_exact(&mut length)
+                .map_err(PcapError::Io)?;
+            let length = u32::from_le_bytes(length) as u64;
+            let start = position + BLOCK_HEADER_SIZE;
+            if start + length > file_size {
+                break;
+            }
+            data.resize(length as usize, 0);
+            reader
+                .read_exact(&mut data)
+                .map_err(PcapError::Io)?;
+            offsets
+                .entry(payload_key(&data))
+                .or_insert(start);
+            position = start + length;
+        }
+        drop(reader);
+
+        if position < file_size {
+            warn!(
+                "负载存储末尾有不完整的负载块，已截断: {path:?}, {} 字节",
+                file_size - position
+            );
+            file.set_len(position)
+                .map_err(PcapError::Io)?;
+        }
+        file.seek(SeekFrom::Start(position))
+            .map_err(PcapError::Io)?;
+
+        info!(
+            "已打开负载存储: {path:?}, 负载 {} 个",
+            offsets.len()
+        );
+        Ok(Self {
+            file,
+            offsets,
+            size: position,
+        })
+    }
+
+    /// 保存负载并返回引用，内容相同的负载只保存一次
+    pub(crate) fn put(
+        &mut self,
+        data: &[u8],
+    ) -> PcapResult<PayloadReference> {
+        let length =
+            u32::try_from(data.len()).map_err(|_| {
+                PcapError::InvalidPacketSize {
+                    message: format!(
+                        "负载长度超出负载存储的上限: {}",
+                        data.len()
+                    ),
+                    position: self.size,
+                }
+            })?;
+        let key = payload_key(data);
+        if let Some(&offset) = self.offsets.get(&key) {
+            return Ok(PayloadReference { offset, length });
+        }
+
+        let mut block = Vec::with_capacity(
+            BLOCK_HEADER_SIZE as usize + data.len(),
+        );
+        block.extend_from_slice(&length.to_le_bytes());
+        block.extend_from_slice(data);
+        // 部分写入的负载块在下次打开时截掉
+        self.file
+            .write_all(&block)
+            .map_err(PcapError::Io)?;
+
+        let offset = self.size + BLOCK_HEADER_SIZE;
+        self.size += block.len() as u64;
+        self.offsets.insert(key, offset);
+        debug!(
+            "已保存新负载: 偏移 {offset}, {length} 字节"
+        );
+        Ok(PayloadReference { offset, length })
+    }
+
+    /// 把负载存储同步到磁盘
+    pub(crate) fn sync(&mut self) -> PcapResult<()> {
+        self.file.sync_data().map_err(PcapError::Io)
+    }
+}
+
+/// 负载存储的读取端
+pub(crate) struct PayloadStoreReader {
+    file: File,
+    path: PathBuf,
+}
+
+impl PayloadStoreReader {
+    /// 打开负载存储文件
+    pub(crate) fn open(path: &Path) -> PcapResult<Self> {
+        if !path.is_file() {
+            return Err(PcapError::FileNotFound(format!(
+                "负载存储文件不存在: {path:?}"
+            )));
+        }
+        let file =
+            File::open(path).map_err(PcapError::Io)?;
+        Ok(Self {
+            file,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// 按引用把负载读入 `data`，`data` 的长度须与引用的长度相同
+    ///
+    /// 引用指向的负载块长度与引用不一致或超出文件范围时返回 `PcapError::CorruptedData`。
+    pub(crate) fn read_into(
+        &mut self,
+        reference: PayloadReference,
+        data: &mut [u8],
+    ) -> PcapResult<()> {
+        let corrupted =
+            |message: String| PcapError::CorruptedData {
+                message,
+                position: reference.offset,
+            };
+        let block_start = reference
+            .offset
+            .checked_sub(BLOCK_HEADER_SIZE)
+            .ok_or_else(|| {
+                corrupted(format!(
+                    "负载引用的偏移无效: {:?}",
+                    self.path
+                ))
+            })?;
+
+        let mut length = [0u8; 4];
+        self.file
+            .seek(SeekFrom::Start(block_start))
+            .and_then(|_| self.file.read_exact(&mut length))
+            .map_err(|_| {
+                corrupted(format!(
+                    "负载引用超出负载存储范围: {:?}",
+                    self.path
+                ))
+            })?;
+        if u32::from_le_bytes(length) != reference.length
+            || data.len() != reference.length as usize
+        {
+            return Err(corrupted(format!(
+                "负载引用的长度 {} 与负载存储不一致: {:?}",
+                reference.length, self.path
+            )));
+        }
+        self.file.read_exact(data).map_err(|_| {
+            corrupted(format!(
+                "负载存储中的负载不完整: {:?}",
+                self.path
+            ))
+        })
+    }
+}
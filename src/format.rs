@@ -0,0 +1,244 @@
+//! 文件格式布局模块
+//!
+//! 以常量表描述文件头、数据包头和扩展块头部的字段布局（名称、偏移、类型、出现的版本），
+//! 与 `core` 编解码模块使用同一组常量，供十六进制查看器模板、其他语言的读取器等外部工具
+//! 生成代码或校验实现。所有多字节字段按文件的字节序存储，字节序由魔术数的字节排列识别。
+//! 本模块不依赖标准库。
+
+pub use crate::core::{
+    EXTENDED_FILE_HEADER_SIZE, EXTENSIBLE_FILE_HEADER_SIZE,
+    EXTENSION_BLOCK_HEADER_SIZE, FILE_HEADER_SIZE,
+    PACKET_HEADER_SIZE,
+};
+pub use crate::foundation::types::constants::{
+    EXTENSION_MINOR_VERSION, LINK_TYPE_MINOR_VERSION,
+    MAJOR_VERSION, MINOR_VERSION, PCAP_MAGIC_NUMBER,
+};
+
+/// 小端序文件开头的魔术数字节
+pub const MAGIC_BYTES_LITTLE_ENDIAN: [u8; 4] =
+    PCAP_MAGIC_NUMBER.to_le_bytes();
+
+/// 大端序文件开头的魔术数字节
+pub const MAGIC_BYTES_BIG_ENDIAN: [u8; 4] =
+    PCAP_MAGIC_NUMBER.to_be_bytes();
+
+/// 字段的存储类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize))]
+pub enum FieldType {
+    /// 16 位无符号整数
+    U16,
+    /// 32 位无符号整数
+    U32,
+    /// 32 位有符号整数
+    I32,
+}
+
+impl FieldType {
+    /// 字段大小（字节）
+    pub const fn size(self) -> usize {
+        match self {
+            FieldType::U16 => 2,
+            FieldType::U32 | FieldType::I32 => 4,
+        }
+    }
+}
+
+/// 头部中一个字段的布局
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize))]
+pub struct FieldLayout {
+    /// 字段名称，与严格解析错误中的字段名称一致
+    pub name: &'static str,
+    /// 相对头部开头的字节偏移
+    pub offset: usize,
+    /// 存储类型
+    pub field_type: FieldType,
+    /// 字段出现的最低次版本号，数据包头和扩展块头部的字段为 `MINOR_VERSION`
+    pub since_minor_version: u16,
+    /// 字段说明
+    pub description: &'static str,
+}
+
+impl FieldLayout {
+    /// 字段大小（字节）
+    pub const fn size(&self) -> usize {
+        self.field_type.size()
+    }
+
+    /// 字段结束位置（不含）
+    pub const fn end(&self) -> usize {
+        self.offset + self.size()
+    }
+
+    /// 字段是否出现在指定次版本号的文件中
+    ///
+    /// 扩展块区域长度只出现在 2.6 版本中，更高的未知版本按 2.5 的布局解析。
+    pub const fn present_in(
+        &self,
+        minor_version: u16,
+    ) -> bool {
+        if self.since_minor_version
+            == EXTENSION_MINOR_VERSION
+        {
+            minor_version == EXTENSION_MINOR_VERSION
+        } else {
+            minor_version >= self.since_minor_version
+        }
+    }
+}
+
+/// 文件头字段，按偏移排列
+pub const FILE_HEADER_FIELDS: &[FieldLayout] = &[
+    FieldLayout {
+        name: "magic_number",
+        offset: 0,
+        field_type: FieldType::U32,
+        since_minor_version: MINOR_VERSION,
+        description: "魔术数 0xD4C3B2A1，字节排列决定文件的字节序",
+    },
+    FieldLayout {
+        name: "major_version",
+        offset: 4,
+        field_type: FieldType::U16,
+        since_minor_version: MINOR_VERSION,
+        description: "主版本号，固定为 2",
+    },
+    FieldLayout {
+        name: "minor_version",
+        offset: 6,
+        field_type: FieldType::U16,
+        since_minor_version: MINOR_VERSION,
+        description: "次版本号：4 基础头部，5 携带链路类型，6 携带扩展块区域",
+    },
+    FieldLayout {
+        name: "timezone_offset",
+        offset: 8,
+        field_type: FieldType::I32,
+        since_minor_version: MINOR_VERSION,
+        description: "时区偏移量（秒）",
+    },
+    FieldLayout {
+        name: "timestamp_accuracy",
+        offset: 12,
+        field_type: FieldType::U32,
+        since_minor_version: MINOR_VERSION,
+        description: "时间戳精度（纳秒）",
+    },
+    FieldLayout {
+        name: "link_type",
+        offset: 16,
+        field_type: FieldType::U32,
+        since_minor_version: LINK_TYPE_MINOR_VERSION,
+        description: "负载的链路类型数值，0 表示未指定",
+    },
+    FieldLayout {
+        name: "extensions_length",
+        offset: 20,
+        field_type: FieldType::U32,
+        since_minor_version: EXTENSION_MINOR_VERSION,
+        description: "紧跟在固定部分之后的扩展块区域长度（字节）",
+    },
+];
+
+/// 数据包头字段，按偏移排列，负载紧跟在头部之后
+pub const PACKET_HEADER_FIELDS: &[FieldLayout] = &[
+    FieldLayout {
+        name: "timestamp_seconds",
+        offset: 0,
+        field_type: FieldType::U32,
+        since_minor_version: MINOR_VERSION,
+        description: "捕获时间的 Unix 秒数",
+    },
+    FieldLayout {
+        name: "timestamp_nanoseconds",
+        offset: 4,
+        field_type: FieldType::U32,
+        since_minor_version: MINOR_VERSION,
+        description:
+            "捕获时间的纳秒部分，小于 1_000_000_000",
+    },
+    FieldLayout {
+        name: "packet_length",
+        offset: 8,
+        field_type: FieldType::U32,
+        since_minor_version: MINOR_VERSION,
+        description: "负载长度（字节）",
+    },
+    FieldLayout {
+        name: "checksum",
+        offset: 12,
+        field_type: FieldType::U32,
+        since_minor_version: MINOR_VERSION,
+        description: "负载的 CRC32 校验和",
+    },
+];
+
+/// 扩展块头部字段，按偏移排列，值紧跟在头部之后
+pub const EXTENSION_BLOCK_FIELDS: &[FieldLayout] = &[
+    FieldLayout {
+        name: "kind",
+        offset: 0,
+        field_type: FieldType::U16,
+        since_minor_version: MINOR_VERSION,
+        description: "扩展块类型码",
+    },
+    FieldLayout {
+        name: "length",
+        offset: 2,
+        field_type: FieldType::U16,
+        since_minor_version: MINOR_VERSION,
+        description: "值的长度（字节）",
+    },
+];
+
+// 布局表与编解码模块的头部大小保持一致
+const _: () = {
+    assert!(
+        FILE_HEADER_FIELDS[4].end() == FILE_HEADER_SIZE
+    );
+    assert!(
+        FILE_HEADER_FIELDS[5].end()
+            == EXTENDED_FILE_HEADER_SIZE
+    );
+    assert!(
+        FILE_HEADER_FIELDS[6].end()
+            == EXTENSIBLE_FILE_HEADER_SIZE
+    );
+    assert!(
+        PACKET_HEADER_FIELDS[3].end() == PACKET_HEADER_SIZE
+    );
+    assert!(
+        EXTENSION_BLOCK_FIELDS[1].end()
+            == EXTENSION_BLOCK_HEADER_SIZE
+    );
+};
+
+/// 指定次版本号的文件头固定部分大小（字节），不含扩展块区域
+pub const fn file_header_size(minor_version: u16) -> usize {
+    if minor_version == EXTENSION_MINOR_VERSION {
+        EXTENSIBLE_FILE_HEADER_SIZE
+    } else if minor_version >= LINK_TYPE_MINOR_VERSION {
+        EXTENDED_FILE_HEADER_SIZE
+    } else {
+        FILE_HEADER_SIZE
+    }
+}
+
+/// 指定次版本号的文件中出现的文件头字段
+pub fn file_header_fields(
+    minor_version: u16,
+) -> impl Iterator<Item = &'static FieldLayout> {
+    FILE_HEADER_FIELDS.iter().filter(move |field| {
+        field.present_in(minor_version)
+    })
+}
+
+/// 按名称查找字段
+pub fn find_field(
+    fields: &'static [FieldLayout],
+    name: &str,
+) -> Option<&'static FieldLayout> {
+    fields.iter().find(|field| field.name == name)
+}
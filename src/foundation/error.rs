@@ -1,60 +1,308 @@
-use crate::foundation::types::PcapErrorCode;
+use crate::business::index::IndexSchemaError;
+use crate::data::models::HeaderFieldError;
+use crate::foundation::types::{Locale, PcapErrorCode};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
 use thiserror::Error;
 
 /// PCAP操作错误
+///
+/// 新版本可能追加错误变体，跨语言传递错误时使用 `error_code` 返回的稳定代码。
+/// `Display` 按 `Locale::current()` 选择中文或英文模板，`message_zh` 和
+/// `message_en` 返回指定语言的错误信息。
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum PcapError {
-    #[error("文件未找到: {0}")]
     FileNotFound(String),
-
-    #[error("目录不存在: {0}")]
     DirectoryNotFound(String),
-
-    #[error("无效的文件格式: {0}")]
+    DiskFull {
+        path: String,
+        available: u64,
+        required: u64,
+    },
     InvalidFormat(String),
-
-    #[error("文件头损坏: {0}")]
     CorruptedHeader(String),
-
-    #[error("数据包损坏: {message}，位置 {position}")]
-    CorruptedData { message: String, position: u64 },
-
-    #[error(
-        "校验和不匹配: 期望 {expected}, 实际 {actual}，位置 {position}"
-    )]
+    UnsupportedVersion {
+        major: u16,
+        minor: u16,
+    },
+    InvalidHeaderField {
+        error: HeaderFieldError,
+        position: u64,
+    },
+    InvalidIndexSchema(IndexSchemaError),
+    CorruptedData {
+        message: String,
+        position: u64,
+    },
     ChecksumMismatch {
         expected: String,
         actual: String,
         position: u64,
     },
-
-    #[error("数据包大小无效: {message}，位置 {position}")]
-    InvalidPacketSize { message: String, position: u64 },
-
-    #[error("数据包长度超出文件剩余空间: 期望 {expected} 字节，剩余 {remaining} 字节，位置 {position}")]
+    InvalidPacketSize {
+        message: String,
+        position: u64,
+    },
     PacketSizeExceedsRemainingBytes {
         expected: u32,
         remaining: u64,
         position: u64,
     },
+    TimestampParseError {
+        message: String,
+        position: u64,
+    },
+    InvalidArgument(String),
+    InvalidState(String),
+    InvalidDatasetName(String),
+    DatasetLocked(String),
+    DecodeFailed(String),
+    DecoderNotFound(String),
+    SinkFailed(String),
+    DuplicateTimestamp {
+        timestamp_ns: u64,
+    },
+    Cancelled(String),
+    Io(#[from] std::io::Error),
+    Serialization(String),
+    Unknown(String),
+}
 
-    #[error("时间戳解析错误: {message}，位置 {position}")]
-    TimestampParseError { message: String, position: u64 },
+impl fmt::Display for PcapError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match Locale::current() {
+            Locale::Chinese => self.fmt_zh(f),
+            Locale::English => self.fmt_en(f),
+        }
+    }
+}
 
-    #[error("参数无效: {0}")]
-    InvalidArgument(String),
+/// 固定语言的错误信息
+struct LocalizedMessage<'a>(&'a PcapError, Locale);
 
-    #[error("操作状态无效: {0}")]
-    InvalidState(String),
+impl fmt::Display for LocalizedMessage<'_> {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self.1 {
+            Locale::Chinese => self.0.fmt_zh(f),
+            Locale::English => self.0.fmt_en(f),
+        }
+    }
+}
 
-    #[error("IO错误: {0}")]
-    Io(#[from] std::io::Error),
+impl PcapError {
+    /// 指定语言的错误信息，不受全局语言影响
+    pub fn message(&self, locale: Locale) -> String {
+        LocalizedMessage(self, locale).to_string()
+    }
 
-    #[error("序列化错误: {0}")]
-    Serialization(String),
+    /// 中文错误信息
+    pub fn message_zh(&self) -> String {
+        self.message(Locale::Chinese)
+    }
 
-    #[error("未知错误: {0}")]
-    Unknown(String),
+    /// 英文错误信息
+    pub fn message_en(&self) -> String {
+        self.message(Locale::English)
+    }
+
+    fn fmt_zh(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            PcapError::FileNotFound(path) => {
+                write!(f, "文件未找到: {path}")
+            }
+            PcapError::DirectoryNotFound(path) => {
+                write!(f, "目录不存在: {path}")
+            }
+            PcapError::DiskFull {
+                path,
+                available,
+                required,
+            } => write!(
+                f,
+                "磁盘空间不足: 可用 {available} 字节，需要 {required} 字节，路径 {path}"
+            ),
+            PcapError::InvalidFormat(message) => {
+                write!(f, "无效的文件格式: {message}")
+            }
+            PcapError::CorruptedHeader(message) => {
+                write!(f, "文件头损坏: {message}")
+            }
+            PcapError::UnsupportedVersion { major, minor } => {
+                write!(f, "不支持的文件格式版本: {major}.{minor}")
+            }
+            PcapError::InvalidHeaderField { error, position } => {
+                write!(f, "头部字段无效: {error}，位置 {position}")
+            }
+            PcapError::InvalidIndexSchema(error) => {
+                write!(f, "索引结构无效: {error}")
+            }
+            PcapError::CorruptedData { message, position } => {
+                write!(f, "数据包损坏: {message}，位置 {position}")
+            }
+            PcapError::ChecksumMismatch {
+                expected,
+                actual,
+                position,
+            } => write!(
+                f,
+                "校验和不匹配: 期望 {expected}, 实际 {actual}，位置 {position}"
+            ),
+            PcapError::InvalidPacketSize { message, position } => {
+                write!(f, "数据包大小无效: {message}，位置 {position}")
+            }
+            PcapError::PacketSizeExceedsRemainingBytes {
+                expected,
+                remaining,
+                position,
+            } => write!(
+                f,
+                "数据包长度超出文件剩余空间: 期望 {expected} 字节，剩余 {remaining} 字节，位置 {position}"
+            ),
+            PcapError::TimestampParseError { message, position } => {
+                write!(f, "时间戳解析错误: {message}，位置 {position}")
+            }
+            PcapError::InvalidArgument(message) => {
+                write!(f, "参数无效: {message}")
+            }
+            PcapError::InvalidState(message) => {
+                write!(f, "操作状态无效: {message}")
+            }
+            PcapError::InvalidDatasetName(message) => {
+                write!(f, "数据集名称无效: {message}")
+            }
+            PcapError::DatasetLocked(message) => {
+                write!(f, "数据集已被其他写入器锁定: {message}")
+            }
+            PcapError::DecodeFailed(message) => {
+                write!(f, "负载解码失败: {message}")
+            }
+            PcapError::DecoderNotFound(message) => {
+                write!(f, "未注册解码器: {message}")
+            }
+            PcapError::SinkFailed(message) => {
+                write!(f, "数据包发送失败: {message}")
+            }
+            PcapError::DuplicateTimestamp { timestamp_ns } => {
+                write!(f, "时间戳重复: {timestamp_ns}")
+            }
+            PcapError::Cancelled(message) => {
+                write!(f, "操作已取消: {message}")
+            }
+            PcapError::Io(error) => write!(f, "IO错误: {error}"),
+            PcapError::Serialization(message) => {
+                write!(f, "序列化错误: {message}")
+            }
+            PcapError::Unknown(message) => {
+                write!(f, "未知错误: {message}")
+            }
+        }
+    }
+
+    fn fmt_en(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            PcapError::FileNotFound(path) => {
+                write!(f, "file not found: {path}")
+            }
+            PcapError::DirectoryNotFound(path) => {
+                write!(f, "directory not found: {path}")
+            }
+            PcapError::DiskFull {
+                path,
+                available,
+                required,
+            } => write!(
+                f,
+                "disk full: {available} bytes available, {required} bytes required, path {path}"
+            ),
+            PcapError::InvalidFormat(message) => {
+                write!(f, "invalid file format: {message}")
+            }
+            PcapError::CorruptedHeader(message) => {
+                write!(f, "corrupted file header: {message}")
+            }
+            PcapError::UnsupportedVersion { major, minor } => {
+                write!(f, "unsupported file format version: {major}.{minor}")
+            }
+            PcapError::InvalidHeaderField { error, position } => {
+                write!(f, "invalid header field: {error}, at offset {position}")
+            }
+            PcapError::InvalidIndexSchema(error) => {
+                write!(f, "invalid index schema: {error}")
+            }
+            PcapError::CorruptedData { message, position } => {
+                write!(f, "corrupted packet data: {message}, at offset {position}")
+            }
+            PcapError::ChecksumMismatch {
+                expected,
+                actual,
+                position,
+            } => write!(
+                f,
+                "checksum mismatch: expected {expected}, actual {actual}, at offset {position}"
+            ),
+            PcapError::InvalidPacketSize { message, position } => {
+                write!(f, "invalid packet size: {message}, at offset {position}")
+            }
+            PcapError::PacketSizeExceedsRemainingBytes {
+                expected,
+                remaining,
+                position,
+            } => write!(
+                f,
+                "packet length exceeds remaining file bytes: expected {expected} bytes, {remaining} bytes remaining, at offset {position}"
+            ),
+            PcapError::TimestampParseError { message, position } => {
+                write!(f, "timestamp parse error: {message}, at offset {position}")
+            }
+            PcapError::InvalidArgument(message) => {
+                write!(f, "invalid argument: {message}")
+            }
+            PcapError::InvalidState(message) => {
+                write!(f, "invalid operation state: {message}")
+            }
+            PcapError::InvalidDatasetName(message) => {
+                write!(f, "invalid dataset name: {message}")
+            }
+            PcapError::DatasetLocked(message) => {
+                write!(f, "dataset locked by another writer: {message}")
+            }
+            PcapError::DecodeFailed(message) => {
+                write!(f, "payload decode failed: {message}")
+            }
+            PcapError::DecoderNotFound(message) => {
+                write!(f, "decoder not registered: {message}")
+            }
+            PcapError::SinkFailed(message) => {
+                write!(f, "packet sink failed: {message}")
+            }
+            PcapError::DuplicateTimestamp { timestamp_ns } => {
+                write!(f, "duplicate timestamp: {timestamp_ns}")
+            }
+            PcapError::Cancelled(message) => {
+                write!(f, "operation cancelled: {message}")
+            }
+            PcapError::Io(error) => write!(f, "I/O error: {error}"),
+            PcapError::Serialization(message) => {
+                write!(f, "serialization error: {message}")
+            }
+            PcapError::Unknown(message) => {
+                write!(f, "unknown error: {message}")
+            }
+        }
+    }
 }
 
 impl PcapError {
@@ -67,12 +315,24 @@ impl PcapError {
             PcapError::DirectoryNotFound(_) => {
                 PcapErrorCode::DirectoryNotFound
             }
+            PcapError::DiskFull { .. } => {
+                PcapErrorCode::DiskFull
+            }
             PcapError::InvalidFormat(_) => {
                 PcapErrorCode::InvalidFormat
             }
             PcapError::CorruptedHeader(_) => {
                 PcapErrorCode::CorruptedHeader
             }
+            PcapError::UnsupportedVersion { .. } => {
+                PcapErrorCode::UnsupportedVersion
+            }
+            PcapError::InvalidHeaderField { .. } => {
+                PcapErrorCode::InvalidHeaderField
+            }
+            PcapError::InvalidIndexSchema(_) => {
+                PcapErrorCode::InvalidIndexSchema
+            }
             PcapError::CorruptedData { .. } => {
                 PcapErrorCode::CorruptedData
             }
@@ -94,6 +354,27 @@ impl PcapError {
             PcapError::InvalidState(_) => {
                 PcapErrorCode::InvalidState
             }
+            PcapError::InvalidDatasetName(_) => {
+                PcapErrorCode::InvalidDatasetName
+            }
+            PcapError::DatasetLocked(_) => {
+                PcapErrorCode::DatasetLocked
+            }
+            PcapError::DecodeFailed(_) => {
+                PcapErrorCode::DecodeFailed
+            }
+            PcapError::DecoderNotFound(_) => {
+                PcapErrorCode::DecoderNotFound
+            }
+            PcapError::SinkFailed(_) => {
+                PcapErrorCode::SinkFailed
+            }
+            PcapError::DuplicateTimestamp { .. } => {
+                PcapErrorCode::DuplicateTimestamp
+            }
+            PcapError::Cancelled(_) => {
+                PcapErrorCode::Cancelled
+            }
             PcapError::Io(_) => PcapErrorCode::Unknown,
             PcapError::Serialization(_) => {
                 PcapErrorCode::InvalidFormat
@@ -102,13 +383,162 @@ impl PcapError {
         }
     }
 
+    /// 是否为可重试的暂时性错误
+    ///
+    /// 被中断、超时、资源忙等 IO 错误，数据集被其他写入器锁定、磁盘空间不足以及
+    /// 输出端发送失败都可能在稍后恢复，适合退避重试；其余错误重试也不会成功。
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            PcapError::Io(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::WouldBlock
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::ResourceBusy
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+            ),
+            PcapError::DatasetLocked(_)
+            | PcapError::DiskFull { .. }
+            | PcapError::SinkFailed(_) => true,
+            _ => false,
+        }
+    }
+
+    /// 是否为文件结构损坏
+    ///
+    /// 文件头、数据包头部、负载校验和或索引结构无效，以及文件意外截断时返回 `true`。
+    /// 结构损坏不会通过重试恢复，应立即失败或隔离对应的文件。
+    pub fn is_corruption(&self) -> bool {
+        match self {
+            PcapError::Io(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::InvalidData
+                    | std::io::ErrorKind::UnexpectedEof
+            ),
+            PcapError::InvalidFormat(_)
+            | PcapError::CorruptedHeader(_)
+            | PcapError::InvalidHeaderField { .. }
+            | PcapError::InvalidIndexSchema(_)
+            | PcapError::CorruptedData { .. }
+            | PcapError::ChecksumMismatch { .. }
+            | PcapError::InvalidPacketSize { .. }
+            | PcapError::PacketSizeExceedsRemainingBytes {
+                ..
+            }
+            | PcapError::TimestampParseError { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// 错误发生的文件内偏移，只有数据包和头部解析错误携带
+    pub fn position(&self) -> Option<u64> {
+        match self {
+            PcapError::InvalidHeaderField {
+                position, ..
+            }
+            | PcapError::CorruptedData { position, .. }
+            | PcapError::ChecksumMismatch {
+                position, ..
+            }
+            | PcapError::InvalidPacketSize {
+                position, ..
+            }
+            | PcapError::PacketSizeExceedsRemainingBytes {
+                position,
+                ..
+            }
+            | PcapError::TimestampParseError {
+                position, ..
+            } => Some(*position),
+            _ => None,
+        }
+    }
+
+    /// 错误相关的文件或目录路径
+    pub fn file(&self) -> Option<&str> {
+        match self {
+            PcapError::FileNotFound(path)
+            | PcapError::DirectoryNotFound(path)
+            | PcapError::DiskFull { path, .. } => {
+                Some(path)
+            }
+            _ => None,
+        }
+    }
+
+    /// 转换为结构化的错误详情
+    pub fn to_detail(&self) -> ErrorDetail {
+        ErrorDetail {
+            code: self.error_code().as_i32(),
+            message: self.to_string(),
+            position: self.position(),
+            file: self.file().map(str::to_string),
+        }
+    }
+
     /// 获取详细错误信息
+    ///
+    /// 按全局语言输出，数值代码便于日志聚合工具按代码归类。
     pub fn detailed_message(&self) -> String {
-        format!(
-            "错误代码: {}, 错误信息: {}",
-            self.error_code(),
-            self
-        )
+        let code = self.error_code();
+        match Locale::current() {
+            Locale::Chinese => format!(
+                "错误代码: {code}({}), 错误信息: {}",
+                code.as_i32(),
+                self.message_zh()
+            ),
+            Locale::English => format!(
+                "error code: {code}({}), message: {}",
+                code.as_i32(),
+                self.message_en()
+            ),
+        }
+    }
+}
+
+/// 结构化的错误详情
+///
+/// `PcapError` 和 `ErrorResult` 序列化后的 JSON 结构固定为
+/// `{"code", "message", "position", "file"}`：`code` 为 `PcapErrorCode` 的稳定数值，
+/// `message` 按全局语言输出，不携带偏移或路径的错误对应字段为 `null`。
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub struct ErrorDetail {
+    /// 错误代码数值
+    pub code: i32,
+    /// 错误信息
+    pub message: String,
+    /// 错误发生的文件内偏移
+    #[serde(default)]
+    pub position: Option<u64>,
+    /// 相关的文件或目录路径
+    #[serde(default)]
+    pub file: Option<String>,
+}
+
+impl ErrorDetail {
+    /// 对应的错误代码，未知的数值返回 `None`
+    pub fn error_code(&self) -> Option<PcapErrorCode> {
+        PcapErrorCode::from_i32(self.code)
+    }
+}
+
+impl From<&PcapError> for ErrorDetail {
+    fn from(error: &PcapError) -> Self {
+        error.to_detail()
+    }
+}
+
+/// 序列化为 `ErrorDetail` 的结构
+impl Serialize for PcapError {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.to_detail().serialize(serializer)
     }
 }
 
@@ -155,11 +585,17 @@ impl From<std::string::FromUtf8Error> for PcapError {
 }
 
 /// 错误结果类型
+///
+/// 序列化为 `{"success", "code", "message", "position", "file"}`，成功时后四个字段为 `null`。
 #[derive(Debug, Clone)]
 pub struct ErrorResult {
     pub success: bool,
     pub error_message: Option<String>,
     pub error_code: Option<PcapErrorCode>,
+    /// 错误发生的文件内偏移
+    pub position: Option<u64>,
+    /// 相关的文件或目录路径
+    pub file: Option<String>,
 }
 
 impl ErrorResult {
@@ -169,6 +605,8 @@ impl ErrorResult {
             success: true,
             error_message: None,
             error_code: None,
+            position: None,
+            file: None,
         }
     }
 
@@ -181,15 +619,46 @@ impl ErrorResult {
             success: false,
             error_message: Some(error_message),
             error_code,
+            position: None,
+            file: None,
         }
     }
 
     /// 从PcapError创建失败结果
     pub fn from_error(error: PcapError) -> Self {
-        Self::failure(
-            error.to_string(),
-            Some(error.error_code()),
-        )
+        Self {
+            position: error.position(),
+            file: error.file().map(str::to_string),
+            ..Self::failure(
+                error.to_string(),
+                Some(error.error_code()),
+            )
+        }
+    }
+}
+
+impl Serialize for ErrorResult {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer
+            .serialize_struct("ErrorResult", 5)?;
+        state.serialize_field("success", &self.success)?;
+        state.serialize_field(
+            "code",
+            &self.error_code.map(PcapErrorCode::as_i32),
+        )?;
+        state.serialize_field(
+            "message",
+            &self.error_message,
+        )?;
+        state
+            .serialize_field("position", &self.position)?;
+        state.serialize_field("file", &self.file)?;
+        state.end()
     }
 }
 
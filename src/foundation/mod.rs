@@ -2,14 +2,33 @@
 //!
 //! 提供整个库的基础设施支持，包括错误类型定义、核心trait接口和通用工具函数。
 
+#[cfg(feature = "std")]
 pub mod error;
+#[cfg(feature = "std")]
+pub mod paths;
+#[cfg(feature = "std")]
+pub mod time_format;
 pub mod types;
+#[cfg(feature = "std")]
 pub mod utils;
 
 // 重新导出核心类型
-pub use error::{PcapError, PcapResult};
-pub use types::{constants, PcapErrorCode};
+#[cfg(feature = "std")]
+pub use error::{
+    ErrorDetail, ErrorResult, PcapError, PcapResult,
+};
+#[cfg(feature = "std")]
+pub use paths::{
+    matches_file_pattern, normalize_path,
+    split_dataset_path, to_extended_length_path,
+    validate_dataset_name,
+};
+#[cfg(feature = "std")]
+pub use time_format::{TimeFormatError, TimeFormatter};
+pub use types::{constants, Locale, PcapErrorCode};
+#[cfg(feature = "std")]
 pub use utils::{
-    binary_converter, calculate_crc32, ByteArrayExtensions,
+    binary_converter, calculate_crc32,
+    calculate_payload_hash, ByteArrayExtensions,
     DateTimeExtensions,
 };
@@ -0,0 +1,245 @@
+//! 路径处理模块
+//!
+//! 统一处理数据集路径的规范化、Windows 扩展长度路径（`\\?\`）以及数据集名称校验，
+//! 保证 Windows 和 Unix 平台上的行为一致。
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::foundation::error::{PcapError, PcapResult};
+
+/// 数据集名称的最大长度（字节）
+pub const MAX_DATASET_NAME_LENGTH: usize = 255;
+
+/// Windows 传统路径长度上限，超过后需要使用扩展长度路径
+pub const WINDOWS_MAX_PATH: usize = 260;
+
+/// 扩展长度路径前缀
+pub const EXTENDED_LENGTH_PREFIX: &str = r"\\?\";
+
+/// 扩展长度 UNC 路径前缀
+const EXTENDED_UNC_PREFIX: &str = r"\\?\UNC\";
+
+/// 文件名中不允许出现的字符（取 Windows 与 Unix 的并集）
+pub(crate) const ILLEGAL_NAME_CHARS: &[char] =
+    &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Windows 保留设备名
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3",
+    "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8",
+    "LPT9",
+];
+
+/// 校验数据集名称
+///
+/// 数据集名称会直接作为目录名使用，因此必须是单个合法的路径组件：
+/// 不能为空、不能包含路径分隔符或非法字符、不能是 Windows 保留设备名，
+/// 也不能以空格或点结尾。
+///
+/// # 错误
+/// 校验失败时返回 `PcapError::InvalidDatasetName`
+pub fn validate_dataset_name(name: &str) -> PcapResult<()> {
+    let invalid = |reason: &str| {
+        Err(PcapError::InvalidDatasetName(format!(
+            "{name:?}: {reason}"
+        )))
+    };
+
+    if name.is_empty() {
+        return invalid("名称不能为空");
+    }
+
+    if name.len() > MAX_DATASET_NAME_LENGTH {
+        return invalid(&format!(
+            "名称长度不能超过{MAX_DATASET_NAME_LENGTH}字节"
+        ));
+    }
+
+    if name == "." || name == ".." {
+        return invalid("名称不能是相对路径标记");
+    }
+
+    if let Some(c) = name.chars().find(|c| {
+        ILLEGAL_NAME_CHARS.contains(c) || c.is_control()
+    }) {
+        return invalid(&format!("包含非法字符 {c:?}"));
+    }
+
+    if name.ends_with(' ') || name.ends_with('.') {
+        return invalid("名称不能以空格或点结尾");
+    }
+
+    let stem = name.split('.').next().unwrap_or(name);
+    if RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+    {
+        return invalid("名称是系统保留设备名");
+    }
+
+    Ok(())
+}
+
+/// 规范化路径
+///
+/// 在不访问文件系统的前提下去除 `.` 组件并折叠 `..` 组件，
+/// 不会把相对路径转换为绝对路径。
+pub fn normalize_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.as_ref().components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                match normalized.components().next_back() {
+                    Some(Component::Normal(_)) => {
+                        normalized.pop();
+                    }
+                    // 根目录之上没有父目录
+                    Some(Component::RootDir)
+                    | Some(Component::Prefix(_)) => {}
+                    _ => normalized.push(".."),
+                }
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+
+    if normalized.as_os_str().is_empty() {
+        normalized.push(".");
+    }
+    normalized
+}
+
+/// 为绝对路径添加 Windows 扩展长度前缀
+///
+/// 已带前缀的路径保持不变；UNC 路径（`\\server\share`）转换为
+/// `\\?\UNC\server\share`。该函数只做字符串转换，在所有平台上可用。
+pub fn extended_length_path_string(path: &str) -> String {
+    if path.starts_with(EXTENDED_LENGTH_PREFIX) {
+        return path.to_string();
+    }
+
+    let path = path.replace('/', "\\");
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        format!("{EXTENDED_UNC_PREFIX}{unc}")
+    } else {
+        format!("{EXTENDED_LENGTH_PREFIX}{path}")
+    }
+}
+
+/// 在需要时将路径转换为 Windows 扩展长度路径
+///
+/// 仅在 Windows 上且路径长度超过 `WINDOWS_MAX_PATH` 时生效，
+/// 其他平台原样返回。
+pub fn to_extended_length_path<P: AsRef<Path>>(
+    path: P,
+) -> PathBuf {
+    let path = path.as_ref();
+
+    #[cfg(windows)]
+    {
+        let text = path.to_string_lossy();
+        if text.len() >= WINDOWS_MAX_PATH
+            && !text.starts_with(EXTENDED_LENGTH_PREFIX)
+        {
+            // 扩展长度路径必须是绝对路径
+            let absolute = if path.is_absolute() {
+                path.to_path_buf()
+            } else {
+                match std::env::current_dir() {
+                    Ok(dir) => {
+                        normalize_path(dir.join(path))
+                    }
+                    Err(_) => return path.to_path_buf(),
+                }
+            };
+            return PathBuf::from(
+                extended_length_path_string(
+                    &absolute.to_string_lossy(),
+                ),
+            );
+        }
+    }
+
+    path.to_path_buf()
+}
+
+/// 构建数据集目录路径
+///
+/// 校验数据集名称，规范化基础路径，并在需要时转换为扩展长度路径。
+pub fn dataset_path<P: AsRef<Path>>(
+    base_path: P,
+    dataset_name: &str,
+) -> PcapResult<PathBuf> {
+    validate_dataset_name(dataset_name)?;
+    let path = normalize_path(
+        base_path.as_ref().join(dataset_name),
+    );
+    Ok(to_extended_length_path(path))
+}
+
+/// 把数据集目录路径拆分为基础路径和数据集名称
+///
+/// 与 `dataset_path` 相反，供接收完整数据集目录路径的接口使用。
+///
+/// # 错误
+/// 路径没有最后一个组件或名称不合法时返回 `PcapError::InvalidDatasetName`
+pub fn split_dataset_path<P: AsRef<Path>>(
+    path: P,
+) -> PcapResult<(PathBuf, String)> {
+    let path = normalize_path(path);
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| {
+            PcapError::InvalidDatasetName(format!(
+                "无法从路径 {path:?} 解析数据集名称"
+            ))
+        })?
+        .to_string();
+    validate_dataset_name(&name)?;
+
+    let base_path = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            parent.to_path_buf()
+        }
+        _ => PathBuf::from("."),
+    };
+    Ok((base_path, name))
+}
+
+/// 文件名是否匹配通配符模式
+///
+/// `*` 匹配任意个字符，`?` 匹配一个字符，其余字符按原样比较（区分大小写）。
+pub fn matches_file_pattern(
+    name: &str,
+    pattern: &str,
+) -> bool {
+    let name: Vec<char> = name.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    let (mut n, mut p) = (0, 0);
+    // 最近一个 `*` 的位置及其当前匹配到的文件名位置，用于回溯
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                n += 1;
+                p += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
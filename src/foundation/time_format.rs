@@ -0,0 +1,383 @@
+//! 时间格式化模块
+//!
+//! `TimeFormatter` 把时间格式化为文件名或报告中的文本，支持三种格式：
+//!
+//! - chrono strftime 格式（包含 `%` 时），例如 `"capture_%Y%m%d_%H%M%S_%9f"`
+//! - 兼容旧配置的 `{}` 模板，`{}` 替换为默认格式的时间文本，例如 `"radar_{}"`
+//! - .NET 风格格式，例如默认的 `"yyMMdd_HHmmss_fffffffff"`：`yyyy`/`yy` 年、`MM`/`M` 月、
+//!   `dd`/`d` 日、`HH`/`H` 时、`mm`/`m` 分、`ss`/`s` 秒、1 到 9 个 `f` 为秒的小数部分
+//!   （截断，不四舍五入），单引号内的文本原样输出，其余字符原样输出
+//!
+//! 格式在创建时校验，错误中带有出错位置（从 0 开始的字符位置）。
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, TimeZone, Timelike, Utc};
+use std::fmt;
+
+use crate::foundation::paths::ILLEGAL_NAME_CHARS;
+use crate::foundation::types::constants;
+
+/// strftime 格式说明符的最大长度，例如 `%-d`、`%.3f`、`%::z`
+const MAX_SPECIFIER_LENGTH: usize = 5;
+
+/// 旧版本的默认文件命名格式
+///
+/// 旧版本不论 `f` 的个数始终输出 9 位纳秒，保存了该格式的配置按当前默认格式生成文件名。
+const LEGACY_DEFAULT_FILE_NAME_FORMAT: &str =
+    "yyMMdd_HHmmss_fffffff";
+
+/// 校验格式说明符输出时使用的样例时间
+const SAMPLE_TIMESTAMP_NS: i64 = 1_704_164_645_123_456_789;
+
+/// 时间格式错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeFormatError {
+    /// 出错位置，从 0 开始的字符位置
+    pub position: usize,
+    /// 错误说明
+    pub message: String,
+}
+
+impl fmt::Display for TimeFormatError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(
+            f,
+            "位置 {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl std::error::Error for TimeFormatError {}
+
+/// 格式片段
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// 原样输出的文本
+    Literal(String),
+    /// 已校验的 strftime 格式说明符
+    Strftime(String),
+    /// 秒的小数部分，截断到指定位数
+    Fraction(usize),
+}
+
+/// 格式片段及其在格式字符串中的起始字符位置
+type Segments = Vec<(usize, Segment)>;
+
+/// 时间格式化器
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeFormatter {
+    pattern: String,
+    segments: Segments,
+}
+
+impl TimeFormatter {
+    /// 解析时间格式
+    pub fn new(
+        pattern: &str,
+    ) -> Result<Self, TimeFormatError> {
+        if pattern.is_empty() {
+            return Err(TimeFormatError {
+                position: 0,
+                message: "格式不能为空".to_string(),
+            });
+        }
+        let segments = if pattern.contains('%') {
+            parse_strftime(pattern)?
+        } else if pattern.contains("{}") {
+            parse_template(pattern)?
+        } else {
+            parse_dotnet(pattern)?
+        };
+        Ok(Self {
+            pattern: pattern.to_string(),
+            segments,
+        })
+    }
+
+    /// 解析用于文件名的时间格式
+    ///
+    /// 在 `new` 的基础上拒绝输出文件名非法字符（`:`、`/` 等）或控制字符的格式，
+    /// 例如 `%T`、`%:z`。旧版本的默认格式 `"yyMMdd_HHmmss_fffffff"`
+    /// 视为当前的默认格式，与旧版本一样输出 9 位纳秒。
+    pub fn for_file_name(
+        pattern: &str,
+    ) -> Result<Self, TimeFormatError> {
+        if pattern == LEGACY_DEFAULT_FILE_NAME_FORMAT {
+            return Ok(Self::default_file_name());
+        }
+        let formatter = Self::new(pattern)?;
+        let sample = sample_time();
+        for (position, segment) in &formatter.segments {
+            let text = format_segment(segment, &sample);
+            if let Some(c) = text.chars().find(|c| {
+                ILLEGAL_NAME_CHARS.contains(c)
+                    || c.is_control()
+            }) {
+                return Err(TimeFormatError {
+                    position: *position,
+                    message: format!(
+                        "输出包含文件名不允许的字符 {c:?}"
+                    ),
+                });
+            }
+        }
+        Ok(formatter)
+    }
+
+    /// 默认的文件命名格式
+    pub fn default_file_name() -> Self {
+        Self::new(constants::DEFAULT_FILE_NAME_FORMAT)
+            .expect("默认文件命名格式有效")
+    }
+
+    /// 原始格式字符串
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// 格式化时间
+    pub fn format<Tz>(&self, time: &DateTime<Tz>) -> String
+    where
+        Tz: TimeZone,
+        Tz::Offset: fmt::Display,
+    {
+        self.segments
+            .iter()
+            .map(|(_, segment)| {
+                format_segment(segment, time)
+            })
+            .collect()
+    }
+
+    /// 以 UTC 格式化纳秒时间戳，用于报告中的数据包时间
+    pub fn format_ns(&self, timestamp_ns: u64) -> String {
+        let time = i64::try_from(timestamp_ns)
+            .map(DateTime::from_timestamp_nanos)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        self.format(&time)
+    }
+}
+
+impl Default for TimeFormatter {
+    fn default() -> Self {
+        Self::default_file_name()
+    }
+}
+
+impl fmt::Display for TimeFormatter {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+fn sample_time() -> DateTime<Utc> {
+    DateTime::from_timestamp_nanos(SAMPLE_TIMESTAMP_NS)
+}
+
+fn format_segment<Tz>(
+    segment: &Segment,
+    time: &DateTime<Tz>,
+) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: fmt::Display,
+{
+    match segment {
+        Segment::Literal(text) => text.clone(),
+        Segment::Strftime(spec) => {
+            time.format(spec).to_string()
+        }
+        Segment::Fraction(digits) => {
+            // 闰秒的纳秒数不小于 1_000_000_000，取余后按秒内的小数输出
+            let nanos = time.nanosecond() % 1_000_000_000;
+            format!("{nanos:09}")[..*digits].to_string()
+        }
+    }
+}
+
+/// 解析 strftime 格式
+///
+/// 每个 `%` 开始的说明符取能被 chrono 识别的最短前缀，找不到时报告该 `%` 的位置。
+fn parse_strftime(
+    pattern: &str,
+) -> Result<Segments, TimeFormatError> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut segments = Segments::new();
+    let mut literal = Literal::default();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '%' {
+            literal.push(i, chars[i]);
+            i += 1;
+            continue;
+        }
+        let max_end =
+            (i + MAX_SPECIFIER_LENGTH).min(chars.len());
+        let spec = (i + 2..=max_end)
+            .map(|end| {
+                chars[i..end].iter().collect::<String>()
+            })
+            .find(|candidate| is_valid_strftime(candidate))
+            .ok_or_else(|| TimeFormatError {
+                position: i,
+                message: format!(
+                    "无效的格式说明符 \"{}\"",
+                    chars[i..]
+                        .iter()
+                        .take(2)
+                        .collect::<String>()
+                ),
+            })?;
+        literal.flush(&mut segments);
+        segments.push((i, Segment::Strftime(spec.clone())));
+        i += spec.chars().count();
+    }
+    literal.flush(&mut segments);
+    Ok(segments)
+}
+
+fn is_valid_strftime(spec: &str) -> bool {
+    let mut items = StrftimeItems::new(spec).peekable();
+    items.peek().is_some()
+        && items.all(|item| !matches!(item, Item::Error))
+}
+
+/// 解析兼容旧配置的 `{}` 模板，`{}` 展开为默认格式
+fn parse_template(
+    pattern: &str,
+) -> Result<Segments, TimeFormatError> {
+    let default_segments =
+        parse_dotnet(constants::DEFAULT_FILE_NAME_FORMAT)?;
+    let mut segments = Segments::new();
+    let mut position = 0;
+    for (index, part) in pattern.split("{}").enumerate() {
+        if index > 0 {
+            segments.extend(default_segments.iter().map(
+                |(_, segment)| (position, segment.clone()),
+            ));
+            position += 2;
+        }
+        if !part.is_empty() {
+            segments.push((
+                position,
+                Segment::Literal(part.to_string()),
+            ));
+            position += part.chars().count();
+        }
+    }
+    Ok(segments)
+}
+
+/// 解析 .NET 风格格式
+fn parse_dotnet(
+    pattern: &str,
+) -> Result<Segments, TimeFormatError> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut segments = Segments::new();
+    let mut literal = Literal::default();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\'' {
+            let close = chars[i + 1..]
+                .iter()
+                .position(|&c| c == '\'')
+                .ok_or_else(|| TimeFormatError {
+                    position: i,
+                    message: "单引号没有闭合".to_string(),
+                })?;
+            for (offset, &c) in chars[i + 1..i + 1 + close]
+                .iter()
+                .enumerate()
+            {
+                literal.push(i + 1 + offset, c);
+            }
+            i += close + 2;
+            continue;
+        }
+        let run = chars[i..]
+            .iter()
+            .take_while(|&&other| other == c)
+            .count();
+        let segment = match (c, run) {
+            ('y', 2) => Segment::Strftime("%y".into()),
+            ('y', 4) => Segment::Strftime("%Y".into()),
+            ('y', _) => {
+                return Err(TimeFormatError {
+                    position: i,
+                    message: "年份只支持 yy 或 yyyy".into(),
+                })
+            }
+            ('f', 1..=9) => Segment::Fraction(run),
+            ('f', _) => {
+                return Err(TimeFormatError {
+                    position: i,
+                    message: "秒的小数部分最多 9 位".into(),
+                })
+            }
+            ('M' | 'd' | 'H' | 'm' | 's', 1 | 2) => {
+                let spec = match c {
+                    'M' => 'm',
+                    'd' => 'd',
+                    'H' => 'H',
+                    'm' => 'M',
+                    _ => 'S',
+                };
+                let pad = if run == 1 { "-" } else { "" };
+                Segment::Strftime(format!("%{pad}{spec}"))
+            }
+            ('M' | 'd' | 'H' | 'm' | 's', _) => {
+                return Err(TimeFormatError {
+                    position: i,
+                    message: format!(
+                        "\"{c}\" 最多重复 2 次"
+                    ),
+                })
+            }
+            _ => {
+                literal.push(i, c);
+                i += 1;
+                continue;
+            }
+        };
+        literal.flush(&mut segments);
+        segments.push((i, segment));
+        i += run;
+    }
+    literal.flush(&mut segments);
+    Ok(segments)
+}
+
+/// 解析过程中累积的文本片段
+#[derive(Default)]
+struct Literal {
+    start: usize,
+    text: String,
+}
+
+impl Literal {
+    fn push(&mut self, position: usize, c: char) {
+        if self.text.is_empty() {
+            self.start = position;
+        }
+        self.text.push(c);
+    }
+
+    fn flush(&mut self, segments: &mut Segments) {
+        if !self.text.is_empty() {
+            segments.push((
+                self.start,
+                Segment::Literal(std::mem::take(
+                    &mut self.text,
+                )),
+            ));
+        }
+    }
+}
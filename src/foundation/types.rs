@@ -2,6 +2,8 @@
 //!
 //! 定义整个库使用的通用类型和常量，为所有层提供基础数据类型支持。
 
+use core::sync::atomic::{AtomicU8, Ordering};
+
 /// PCAP格式常量定义
 pub mod constants {
     /// PCAP文件标识，固定值 0xD4C3B2A1
@@ -13,19 +15,87 @@ pub mod constants {
     /// 次版本号，固定值 0x0004，表示支持纳秒级时间量
     pub const MINOR_VERSION: u16 = 4;
 
+    /// 携带链路类型字段的次版本号，固定值 0x0005
+    pub const LINK_TYPE_MINOR_VERSION: u16 = 5;
+
+    /// 在链路类型之后携带扩展块区域的次版本号，固定值 0x0006
+    pub const EXTENSION_MINOR_VERSION: u16 = 6;
+
     /// 每个PCAP文件最大数据包数量
     pub const DEFAULT_MAX_PACKETS_PER_FILE: usize = 500;
 
     /// 最大缓冲区大小(字节)
     pub const MAX_BUFFER_SIZE: usize = 50 * 1024 * 1024; // 50MB
 
-    /// 默认文件命名格式
+    /// 数据包缓存最大字节数
+    pub const MAX_PACKET_CACHE_BYTES: usize =
+        1024 * 1024 * 1024; // 1GB
+
+    /// 默认最大同时打开的数据文件数
+    pub const DEFAULT_MAX_OPEN_FILES: usize = 4;
+
+    /// 最大同时打开的数据文件数上限
+    pub const MAX_OPEN_FILES: usize = 256;
+
+    /// 读取器默认保留的空闲负载缓冲区数量
+    pub const DEFAULT_BUFFER_POOL_SIZE: usize = 64;
+
+    /// 小数据包阈值（字节），负载小于该值的写入可以合并成块后再提交给操作系统
+    pub const SMALL_WRITE_THRESHOLD: usize = 256;
+
+    /// 重复时间戳策略检查的最近写入时间戳数量
+    pub const DUPLICATE_TIMESTAMP_WINDOW: usize = 4096;
+
+    /// 写入时默认每批合并的索引条目数
+    pub const DEFAULT_INDEX_BATCH_SIZE: usize = 1024;
+
+    /// 默认文件命名格式，见 `TimeFormatter`
     pub const DEFAULT_FILE_NAME_FORMAT: &str =
-        "yyMMdd_HHmmss_fffffff";
+        "yyMMdd_HHmmss_fffffffff";
+}
+
+/// 错误信息的语言
+///
+/// 全局语言由 `Locale::set_global` 设置，影响 `PcapError` 和 `PcapErrorCode` 的
+/// `Display` 输出；默认为英文。错误中携带的详细信息
+/// （如文件路径、解析器给出的原因）按原样输出，不做翻译。
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Default,
+)]
+pub enum Locale {
+    /// 中文
+    Chinese,
+    /// 英文
+    #[default]
+    English,
+}
+
+/// 全局语言，0 为中文，1 为英文
+static GLOBAL_LOCALE: AtomicU8 =
+    AtomicU8::new(Locale::English as u8);
+
+impl Locale {
+    /// 设置全局语言
+    pub fn set_global(locale: Locale) {
+        GLOBAL_LOCALE
+            .store(locale as u8, Ordering::Relaxed);
+    }
+
+    /// 当前的全局语言
+    pub fn current() -> Locale {
+        match GLOBAL_LOCALE.load(Ordering::Relaxed) {
+            0 => Locale::Chinese,
+            _ => Locale::English,
+        }
+    }
 }
 
 /// 错误代码枚举
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// 错误代码的数值是稳定的公开接口，FFI 和 RPC 层可以通过 `as_i32` 和 `from_i32`
+/// 转换；已发布的代码不会改变数值，新版本只会追加代码，因此匹配时需要保留通配分支。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum PcapErrorCode {
     /// 未知错误
     Unknown = 0,
@@ -33,6 +103,8 @@ pub enum PcapErrorCode {
     FileNotFound = 1001,
     /// 目录不存在
     DirectoryNotFound = 1002,
+    /// 磁盘空间不足
+    DiskFull = 1003,
     /// 无效的文件格式
     InvalidFormat = 2001,
     /// 文件头损坏
@@ -41,6 +113,14 @@ pub enum PcapErrorCode {
     CorruptedData = 2003,
     /// 校验和不匹配
     ChecksumMismatch = 2004,
+    /// 负载解码失败
+    DecodeFailed = 2005,
+    /// 头部字段无效
+    InvalidHeaderField = 2006,
+    /// 索引结构无效
+    InvalidIndexSchema = 2007,
+    /// 不支持的文件格式版本
+    UnsupportedVersion = 2008,
     /// 数据包大小无效
     InvalidPacketSize = 3001,
     /// 数据包长度超出文件剩余空间
@@ -51,48 +131,155 @@ pub enum PcapErrorCode {
     InvalidArgument = 3004,
     /// 操作状态无效
     InvalidState = 3005,
+    /// 数据集名称无效
+    InvalidDatasetName = 3006,
+    /// 数据集已被锁定
+    DatasetLocked = 3007,
+    /// 未注册解码器
+    DecoderNotFound = 3008,
+    /// 数据包发送失败
+    SinkFailed = 3009,
+    /// 时间戳重复
+    DuplicateTimestamp = 3010,
+    /// 操作已取消
+    Cancelled = 3011,
 }
 
-impl std::fmt::Display for PcapErrorCode {
-    fn fmt(
-        &self,
-        f: &mut std::fmt::Formatter<'_>,
-    ) -> std::fmt::Result {
+impl PcapErrorCode {
+    /// 全部错误代码，按数值升序排列
+    pub const ALL: [PcapErrorCode; 23] = [
+        PcapErrorCode::Unknown,
+        PcapErrorCode::FileNotFound,
+        PcapErrorCode::DirectoryNotFound,
+        PcapErrorCode::DiskFull,
+        PcapErrorCode::InvalidFormat,
+        PcapErrorCode::CorruptedHeader,
+        PcapErrorCode::CorruptedData,
+        PcapErrorCode::ChecksumMismatch,
+        PcapErrorCode::DecodeFailed,
+        PcapErrorCode::InvalidHeaderField,
+        PcapErrorCode::InvalidIndexSchema,
+        PcapErrorCode::UnsupportedVersion,
+        PcapErrorCode::InvalidPacketSize,
+        PcapErrorCode::PacketSizeExceedsRemainingBytes,
+        PcapErrorCode::TimestampParseError,
+        PcapErrorCode::InvalidArgument,
+        PcapErrorCode::InvalidState,
+        PcapErrorCode::InvalidDatasetName,
+        PcapErrorCode::DatasetLocked,
+        PcapErrorCode::DecoderNotFound,
+        PcapErrorCode::SinkFailed,
+        PcapErrorCode::DuplicateTimestamp,
+        PcapErrorCode::Cancelled,
+    ];
+
+    /// 错误代码的数值
+    #[inline]
+    pub const fn as_i32(self) -> i32 {
+        self as i32
+    }
+
+    /// 根据数值查找错误代码，未知的数值返回 `None`
+    pub fn from_i32(code: i32) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|candidate| candidate.as_i32() == code)
+    }
+}
+
+impl From<PcapErrorCode> for i32 {
+    fn from(code: PcapErrorCode) -> Self {
+        code.as_i32()
+    }
+}
+
+impl TryFrom<i32> for PcapErrorCode {
+    type Error = i32;
+
+    /// 未知的数值原样作为错误返回
+    fn try_from(code: i32) -> Result<Self, Self::Error> {
+        Self::from_i32(code).ok_or(code)
+    }
+}
+
+impl PcapErrorCode {
+    /// 指定语言的错误代码说明
+    pub const fn message(
+        self,
+        locale: Locale,
+    ) -> &'static str {
+        match locale {
+            Locale::Chinese => self.message_zh(),
+            Locale::English => self.message_en(),
+        }
+    }
+
+    /// 中文说明
+    pub const fn message_zh(self) -> &'static str {
+        match self {
+            PcapErrorCode::Unknown => "未知错误",
+            PcapErrorCode::FileNotFound => "文件未找到",
+            PcapErrorCode::DirectoryNotFound => "目录不存在",
+            PcapErrorCode::DiskFull => "磁盘空间不足",
+            PcapErrorCode::InvalidFormat => "无效的文件格式",
+            PcapErrorCode::CorruptedHeader => "文件头损坏",
+            PcapErrorCode::CorruptedData => "数据包损坏",
+            PcapErrorCode::ChecksumMismatch => "校验和不匹配",
+            PcapErrorCode::DecodeFailed => "负载解码失败",
+            PcapErrorCode::InvalidHeaderField => "头部字段无效",
+            PcapErrorCode::InvalidIndexSchema => "索引结构无效",
+            PcapErrorCode::UnsupportedVersion => "不支持的文件格式版本",
+            PcapErrorCode::InvalidPacketSize => "数据包大小无效",
+            PcapErrorCode::PacketSizeExceedsRemainingBytes => "数据包长度超出文件剩余空间",
+            PcapErrorCode::TimestampParseError => "时间戳解析错误",
+            PcapErrorCode::InvalidArgument => "参数无效",
+            PcapErrorCode::InvalidState => "操作状态无效",
+            PcapErrorCode::InvalidDatasetName => "数据集名称无效",
+            PcapErrorCode::DatasetLocked => "数据集已被锁定",
+            PcapErrorCode::DecoderNotFound => "未注册解码器",
+            PcapErrorCode::SinkFailed => "数据包发送失败",
+            PcapErrorCode::DuplicateTimestamp => "时间戳重复",
+            PcapErrorCode::Cancelled => "操作已取消",
+        }
+    }
+
+    /// 英文说明
+    pub const fn message_en(self) -> &'static str {
         match self {
-            PcapErrorCode::Unknown => write!(f, "未知错误"),
-            PcapErrorCode::FileNotFound => {
-                write!(f, "文件未找到")
-            }
-            PcapErrorCode::DirectoryNotFound => {
-                write!(f, "目录不存在")
-            }
-            PcapErrorCode::InvalidFormat => {
-                write!(f, "无效的文件格式")
-            }
-            PcapErrorCode::CorruptedHeader => {
-                write!(f, "文件头损坏")
-            }
-            PcapErrorCode::CorruptedData => {
-                write!(f, "数据包损坏")
-            }
-            PcapErrorCode::ChecksumMismatch => {
-                write!(f, "校验和不匹配")
-            }
-            PcapErrorCode::InvalidPacketSize => {
-                write!(f, "数据包大小无效")
-            }
-            PcapErrorCode::PacketSizeExceedsRemainingBytes => {
-                write!(f, "数据包长度超出文件剩余空间")
-            }
-            PcapErrorCode::TimestampParseError => {
-                write!(f, "时间戳解析错误")
-            }
-            PcapErrorCode::InvalidArgument => {
-                write!(f, "参数无效")
-            }
-            PcapErrorCode::InvalidState => {
-                write!(f, "操作状态无效")
-            }
+            PcapErrorCode::Unknown => "unknown error",
+            PcapErrorCode::FileNotFound => "file not found",
+            PcapErrorCode::DirectoryNotFound => "directory not found",
+            PcapErrorCode::DiskFull => "disk full",
+            PcapErrorCode::InvalidFormat => "invalid file format",
+            PcapErrorCode::CorruptedHeader => "corrupted file header",
+            PcapErrorCode::CorruptedData => "corrupted packet data",
+            PcapErrorCode::ChecksumMismatch => "checksum mismatch",
+            PcapErrorCode::DecodeFailed => "payload decode failed",
+            PcapErrorCode::InvalidHeaderField => "invalid header field",
+            PcapErrorCode::InvalidIndexSchema => "invalid index schema",
+            PcapErrorCode::UnsupportedVersion => "unsupported file format version",
+            PcapErrorCode::InvalidPacketSize => "invalid packet size",
+            PcapErrorCode::PacketSizeExceedsRemainingBytes => "packet length exceeds remaining file bytes",
+            PcapErrorCode::TimestampParseError => "timestamp parse error",
+            PcapErrorCode::InvalidArgument => "invalid argument",
+            PcapErrorCode::InvalidState => "invalid operation state",
+            PcapErrorCode::InvalidDatasetName => "invalid dataset name",
+            PcapErrorCode::DatasetLocked => "dataset locked",
+            PcapErrorCode::DecoderNotFound => "decoder not registered",
+            PcapErrorCode::SinkFailed => "packet sink failed",
+            PcapErrorCode::DuplicateTimestamp => "duplicate timestamp",
+            PcapErrorCode::Cancelled => "operation cancelled",
         }
     }
 }
+
+/// 按全局语言输出说明
+impl core::fmt::Display for PcapErrorCode {
+    fn fmt(
+        &self,
+        f: &mut core::fmt::Formatter<'_>,
+    ) -> core::fmt::Result {
+        f.write_str(self.message(Locale::current()))
+    }
+}
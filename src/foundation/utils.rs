@@ -173,14 +173,32 @@ impl DateTimeExtensions for DateTime<Utc> {
     }
 }
 
+/// 将时长转换为纳秒数，超出 u64 范围时饱和为 `u64::MAX`
+#[inline]
+pub fn duration_to_nanos(
+    duration: std::time::Duration,
+) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
 /// 计算CRC32校验和
+///
+/// 读写路径上最频繁调用的函数，使用硬件加速实现，见 [`crate::core::crc32`]。
 #[inline]
 pub fn calculate_crc32(data: &[u8]) -> u32 {
-    use crc32fast::Hasher;
+    crate::core::crc32(data)
+}
 
-    let mut hasher = Hasher::new();
-    hasher.update(data);
-    hasher.finalize()
+/// 计算负载内容哈希
+///
+/// 取负载 SHA-256 摘要的前 8 字节（大端），写入索引后用于检测重复数据包和比较数据集，
+/// 结果在不同平台和版本间保持稳定。
+pub fn calculate_payload_hash(data: &[u8]) -> u64 {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(data);
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(bytes)
 }
 
 /// 二进制转换工具
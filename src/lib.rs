@@ -26,31 +26,140 @@
 //! - 🔧 **可配置**: 灵活的配置选项
 //! - 📊 **完整功能**: 支持所有PCAP格式特性
 //! - 📋 **索引支持**: 高性能PIDX索引文件处理
+//! - 🔌 **嵌入式支持**: 关闭 `std` 特性后保留不依赖标准库的 [`core`] 编解码模块
 //!
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
 // 分层架构模块声明
+#[cfg(feature = "std")]
 pub mod api;
+#[cfg(feature = "std")]
 pub mod business;
+#[cfg(feature = "std")]
 pub mod data;
 pub mod foundation;
 
+// 核心编解码模块（不依赖标准库）
+pub mod core;
+
+// 文件格式布局模块（不依赖标准库）
+pub mod format;
+
+// 负载解码模块
+#[cfg(feature = "std")]
+pub use business::decode;
+
+// 数据集迁移模块
+#[cfg(feature = "std")]
+pub use api::migrate;
+
+// 导出模块
+#[cfg(feature = "std")]
+pub use api::export;
+
+// 数据集维护模块
+#[cfg(feature = "std")]
+pub use api::maintenance;
+
+// 中断信号处理模块
+#[cfg(feature = "signal")]
+pub use api::signal;
+
+// 远程读取器
+#[cfg(feature = "remote")]
+pub use api::RemotePcapReader;
+
+// 数据集服务模块
+#[cfg(feature = "server")]
+pub use api::server;
+
+// 消息总线输出端
+#[cfg(feature = "kafka")]
+pub use api::sink::KafkaSink;
+#[cfg(feature = "mqtt")]
+pub use api::sink::MqttSink;
+
+// 属性测试生成器模块
+#[cfg(feature = "proptest-support")]
+pub use api::strategies;
+
+// 往返测试工具模块
+#[cfg(feature = "test-util")]
+pub use api::testing;
+
+// WASM 绑定模块
+#[cfg(feature = "wasm")]
+pub use api::wasm;
+
 // 重新导出核心类型和函数
+#[cfg(feature = "std")]
 pub use business::{
-    PacketIndexEntry, PcapFileIndex, PidxIndex,
-    ReaderConfig, WriterConfig,
+    BackfillReport, Bookmark, BookmarkStore, ByteSize,
+    ClockSource, CompactionReport, DatasetLock,
+    DatasetSubset, DecoderRegistry, DiskGuard,
+    DiskGuardAction, DiskSpaceStatus,
+    DuplicateTimestampPolicy, FileFilter, FileUsage,
+    Histogram, HistogramBucket, IndexHealth,
+    IndexHealthEntry, IndexHealthKind, IndexMemoryUsage,
+    IndexQuery, IndexRebuildReason, IndexSchemaError,
+    InjectedClock, Marker, MonotonicClock,
+    PacketIndexEntry, PacketLocation, PayloadDecoder,
+    PayloadDiff, PcapFileIndex, PidxIndex, PlaybackConfig,
+    PooledPacket, RateLimiter, ReaderConfig,
+    RotationPolicy, RotationReason, SeekMode,
+    ShardManifest, SharedIndex, SystemClock, TimeWindow,
+    TriggerConfig, TruncationReport, VerifyMode,
+    WriteVerification, WriterConfig, WriterMetrics,
+    WriterProgress,
 };
+#[cfg(feature = "mmap-index")]
+pub use business::{MmapTimestampIndex, TimestampRecord};
+#[cfg(feature = "std")]
 pub use data::{
-    DataPacket, DataPacketHeader, DatasetInfo, FileInfo,
-    PcapFileHeader,
+    read_blocks_in_range, scan_blocks, validate_file_quick,
+    verify_blocks, BlockFooter, BlockSummary, DataPacket,
+    DataPacketHeader, DatasetInfo, FileInfo, FileStructure,
+    FormatVersion, HeaderAnomaly, HeaderExtension,
+    HeaderFieldError, LibpcapWriter, LinkType,
+    PcapFileHeader, RecordFormat, ValidatedPacket,
+    ValidationStatus,
+};
+#[cfg(feature = "std")]
+pub use foundation::{
+    matches_file_pattern, validate_dataset_name,
+    ErrorDetail, ErrorResult, PcapError, PcapResult,
+    TimeFormatError, TimeFormatter,
 };
-pub use foundation::{PcapError, PcapResult};
 
 // 基础设施层类型导出
-pub use foundation::{constants, PcapErrorCode};
+pub use foundation::{constants, Locale, PcapErrorCode};
+
+// 文件字节序，由核心编解码模块定义
+pub use crate::core::ByteOrder;
 
 // 用户接口层导出（主要API）
 // 索引功能通过 PcapReader.index() 和 PcapWriter.index() 访问
-pub use api::{PcapReader, PcapWriter};
+#[cfg(feature = "regex")]
+pub use api::RegexMatcher;
+#[cfg(feature = "std")]
+pub use api::{
+    Aggregation, BytesMatcher, ChunkIter, CompositeMode,
+    CompositeReader, DatasetDoctor, DatasetMaintenance,
+    DatasetMigrator, DatasetTransformer, DeletionMode,
+    DeletionReport, DoctorReport, ExportErrorPolicy,
+    ExportOptions, ExportSummary, ExtractFormat,
+    ExtractedPacket, Finding, FindingKind, MaskedMatcher,
+    Matcher, MemoryReader, MigrationReport, PacketPair,
+    PacketQuery, PacketSender, PacketSink, PacketSource,
+    PayloadMatch, PcapReader, PcapWriter, PlaybackEngine,
+    PlaybackSummary, QueryDriver, QueryPlan,
+    RateLimitedSink, RelocationReport, ScrubRule, Scrubber,
+    SearchOptions, Severity, SinkReport, SyncedPacket,
+    SyncedReader, TimeCorrection, TimeRangeIter,
+    TransformSummary, TrashEntry, TriggeredWriter, TryRead,
+    UdpSink, WindowAggregate,
+};
 
 // 版本信息
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
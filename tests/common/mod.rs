@@ -6,9 +6,12 @@ use chrono::Utc;
 use std::collections::hash_map::DefaultHasher;
 use std::fs;
 use std::hash::{Hash, Hasher};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use pcapfile_io::{DataPacket, PcapResult};
+use pcapfile_io::{
+    DataPacket, PcapReader, PcapResult, PcapWriter,
+    WriterConfig,
+};
 
 /// 测试输出基础路径
 #[allow(dead_code)]
@@ -197,3 +200,85 @@ pub fn create_packet_info(
             .collect(),
     }
 }
+
+/// 依次写入 (时间戳, 负载)，返回写入的数据包
+#[allow(dead_code)]
+pub fn write_payloads(
+    writer: &mut PcapWriter,
+    packets: impl IntoIterator<Item = (u64, Vec<u8>)>,
+) -> Vec<(u64, Vec<u8>)> {
+    let mut written = Vec::new();
+    for (timestamp_ns, payload) in packets {
+        writer
+            .write_payload(timestamp_ns, &payload)
+            .expect("写入负载失败");
+        written.push((timestamp_ns, payload));
+    }
+    written
+}
+
+/// 以指定配置创建数据集并写入 (时间戳, 负载)，完成写入后返回写入的数据包
+#[allow(dead_code)]
+pub fn write_payload_dataset(
+    base: &Path,
+    name: &str,
+    config: WriterConfig,
+    packets: impl IntoIterator<Item = (u64, Vec<u8>)>,
+) -> Vec<(u64, Vec<u8>)> {
+    let mut writer =
+        PcapWriter::new_with_config(base, name, config)
+            .expect("创建写入器失败");
+    let written = write_payloads(&mut writer, packets);
+    writer.finalize().expect("完成写入失败");
+    written
+}
+
+/// 按文件名排序的数据集数据文件
+#[allow(dead_code)]
+pub fn data_files(dataset: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dataset)
+        .expect("读取数据集目录失败")
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "pcap")
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// 数据集数据文件的总大小（字节）
+#[allow(dead_code)]
+pub fn data_files_size(dataset: &Path) -> u64 {
+    data_files(dataset)
+        .iter()
+        .map(|path| {
+            fs::metadata(path)
+                .expect("读取文件元数据失败")
+                .len()
+        })
+        .sum()
+}
+
+/// 顺序读取数据集的全部数据包，返回 (时间戳, 负载)
+#[allow(dead_code)]
+pub fn read_all(
+    base: &Path,
+    name: &str,
+) -> Vec<(u64, Vec<u8>)> {
+    let mut reader = PcapReader::new(base, name)
+        .expect("创建读取器失败");
+    let mut packets = Vec::new();
+    while let Some(validated) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        assert!(validated.is_valid(), "数据包校验失败");
+        packets.push((
+            validated.packet.get_timestamp_ns(),
+            validated.packet.data,
+        ));
+    }
+    packets
+}
@@ -2,8 +2,10 @@
 //!
 //! 测试无索引写入和读取是否能自动生成索引并验证索引的正确性
 
+use pcapfile_io::business::index::IndexManager;
 use pcapfile_io::{
-    PcapReader, PcapWriter, ReaderConfig, WriterConfig,
+    DataPacket, IndexHealthKind, PcapError, PcapReader,
+    PcapWriter, ReaderConfig, WriterConfig,
 };
 
 mod common;
@@ -61,9 +63,9 @@ fn test_auto_index_with_small_dataset() {
     let index =
         reader.index().get_index().expect("获取索引失败");
 
-    assert_eq!(index.total_packets, PACKET_COUNT as u64);
-    assert!(!index.data_files.files.is_empty());
-    assert_eq!(index.timestamp_index.len(), PACKET_COUNT);
+    assert_eq!(index.total_packets(), PACKET_COUNT as u64);
+    assert!(index.file_count() > 0);
+    assert_eq!(index.timestamp_count(), PACKET_COUNT);
 
     println!("小数据集自动索引测试通过");
 }
@@ -119,9 +121,9 @@ fn test_auto_index_with_multiple_files() {
     let index =
         reader.index().get_index().expect("获取索引失败");
 
-    assert_eq!(index.total_packets, TOTAL_PACKETS as u64);
+    assert_eq!(index.total_packets(), TOTAL_PACKETS as u64);
     // 应该有3个文件：1000 + 1000 + 1000
-    assert_eq!(index.data_files.files.len(), 3);
+    assert_eq!(index.file_count(), 3);
 
     println!("多文件自动索引测试通过");
 }
@@ -180,8 +182,8 @@ fn test_manual_index_generation_after_write() {
     let index =
         reader.index().get_index().expect("获取索引失败");
 
-    assert_eq!(index.total_packets, PACKET_COUNT as u64);
-    assert!(!index.data_files.files.is_empty());
+    assert_eq!(index.total_packets(), PACKET_COUNT as u64);
+    assert!(index.file_count() > 0);
 
     println!("手动索引生成测试通过: {index_path:?}");
 }
@@ -234,15 +236,23 @@ fn test_index_consistency_check() {
         reader.index().get_index().expect("获取索引失败");
 
     // 验证数据包总数
-    assert_eq!(index.total_packets, PACKET_COUNT as u64);
+    assert_eq!(index.total_packets(), PACKET_COUNT as u64);
 
     // 验证时间戳索引
-    assert_eq!(index.timestamp_index.len(), PACKET_COUNT);
+    assert_eq!(index.timestamp_count(), PACKET_COUNT);
+
+    // 时间戳条目按升序遍历
+    let entries: Vec<u64> = index
+        .timestamp_entries()
+        .map(|(ts, _)| ts)
+        .collect();
+    assert!(entries.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(entries.len(), PACKET_COUNT);
 
     // 验证时间戳一致性
     for expected_ts in &expected_timestamps {
         assert!(
-            index.timestamp_index.contains_key(expected_ts),
+            index.contains_timestamp(*expected_ts),
             "索引中缺少时间戳: {expected_ts}"
         );
     }
@@ -256,3 +266,637 @@ fn test_index_consistency_check() {
 
     println!("索引一致性检查通过");
 }
+
+#[test]
+fn test_corrupted_index_quarantine_and_rebuild() {
+    const TEST_NAME: &str =
+        "test_corrupted_index_quarantine_and_rebuild";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+
+    let test_dataset_path = dataset_path.join(TEST_NAME);
+    clean_dataset_directory(&test_dataset_path)
+        .expect("清理测试目录失败");
+
+    const PACKET_COUNT: usize = 100;
+
+    let mut writer =
+        PcapWriter::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapWriter失败");
+    for i in 0..PACKET_COUNT {
+        let packet = create_test_packet(i as u32, 64)
+            .expect("创建测试数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    // 破坏索引文件
+    std::fs::write(
+        test_dataset_path.join(".pidx"),
+        b"<index><broken",
+    )
+    .expect("写入损坏索引失败");
+
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    reader.initialize().expect("损坏索引应自动修复");
+
+    let health = reader.index().health();
+    assert!(health.has(&IndexHealthKind::CorruptedIndex));
+    let quarantined = health.entries()[0]
+        .path
+        .clone()
+        .expect("应记录隔离文件路径");
+    assert!(quarantined.exists(), "损坏索引应被保留");
+
+    let index =
+        reader.index().get_index().expect("获取索引失败");
+    assert_eq!(index.total_packets(), PACKET_COUNT as u64);
+}
+
+#[test]
+fn test_corrupted_index_without_auto_repair() {
+    const TEST_NAME: &str =
+        "test_corrupted_index_without_auto_repair";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+
+    let test_dataset_path = dataset_path.join(TEST_NAME);
+    clean_dataset_directory(&test_dataset_path)
+        .expect("清理测试目录失败");
+
+    let mut writer =
+        PcapWriter::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapWriter失败");
+    let packet = create_test_packet(0, 64)
+        .expect("创建测试数据包失败");
+    writer.write_packet(&packet).expect("写入数据包失败");
+    writer.finalize().expect("完成写入失败");
+
+    std::fs::write(
+        test_dataset_path.join(".pidx"),
+        b"not xml at all",
+    )
+    .expect("写入损坏索引失败");
+
+    let config = ReaderConfig {
+        auto_repair_index: false,
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        &dataset_path,
+        TEST_NAME,
+        config,
+    )
+    .expect("创建PcapReader失败");
+
+    assert!(reader.initialize().is_err());
+    assert!(test_dataset_path.join(".pidx").exists());
+}
+
+/// 写入指定批量大小的数据集，返回完成写入时生成的各文件索引
+fn write_with_index_batch_size(
+    dataset_path: &std::path::Path,
+    test_name: &str,
+    index_batch_size: usize,
+) -> Vec<pcapfile_io::PcapFileIndex> {
+    clean_dataset_directory(dataset_path.join(test_name))
+        .expect("清理测试目录失败");
+    let config = WriterConfig {
+        max_packets_per_file: 100,
+        index_batch_size,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        dataset_path,
+        test_name,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+    for i in 0..250u32 {
+        // 使用固定时间戳，两个数据集的文件内容完全一致
+        let packet = DataPacket::from_timestamp(
+            1_700_000_000,
+            i * 1_000,
+            vec![i as u8; 32 + (i as usize % 17)],
+        )
+        .expect("创建测试数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    writer
+        .index()
+        .get_index()
+        .expect("获取索引失败")
+        .files()
+        .to_vec()
+}
+
+#[test]
+fn test_batched_index_matches_rescanned_index() {
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+
+    // 批量大小不能整除每个文件的数据包数，文件末尾留有未满的批次
+    let batched = write_with_index_batch_size(
+        &dataset_path,
+        "test_batched_index_batched",
+        7,
+    );
+    let rescanned = write_with_index_batch_size(
+        &dataset_path,
+        "test_batched_index_rescanned",
+        0,
+    );
+
+    assert_eq!(batched.len(), 3);
+    assert_eq!(batched.len(), rescanned.len());
+    for (left, right) in batched.iter().zip(&rescanned) {
+        assert_eq!(left.file_hash, right.file_hash);
+        assert_eq!(left.file_size, right.file_size);
+        assert_eq!(left.packet_count, right.packet_count);
+        assert_eq!(
+            left.start_timestamp,
+            right.start_timestamp
+        );
+        assert_eq!(left.end_timestamp, right.end_timestamp);
+        assert_eq!(
+            left.data_packets.len(),
+            right.data_packets.len()
+        );
+        for (a, b) in left
+            .data_packets
+            .iter()
+            .zip(&right.data_packets)
+        {
+            assert_eq!(a.timestamp_ns, b.timestamp_ns);
+            assert_eq!(a.byte_offset, b.byte_offset);
+            assert_eq!(a.packet_size, b.packet_size);
+            assert_eq!(a.packet_index, b.packet_index);
+            assert_eq!(a.global_index, b.global_index);
+        }
+    }
+}
+
+/// 读取索引文件并去掉末尾的校验和注释，编辑后的内容按没有校验和的索引加载
+fn read_editable_index(
+    pidx_path: &std::path::Path,
+) -> String {
+    let content = std::fs::read_to_string(pidx_path)
+        .expect("读取索引失败");
+    let (body, _) = content
+        .split_once("\n<!-- pidx-sha256:")
+        .expect("索引文件应带有校验和");
+    body.to_string()
+}
+
+#[test]
+fn test_validate_index_schema() {
+    const TEST_NAME: &str = "test_validate_index_schema";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+
+    let test_dataset_path = dataset_path.join(TEST_NAME);
+    clean_dataset_directory(&test_dataset_path)
+        .expect("清理测试目录失败");
+
+    let mut writer =
+        PcapWriter::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapWriter失败");
+    for i in 0..3 {
+        let packet = create_test_packet(i, 64)
+            .expect("创建测试数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let pidx_path = test_dataset_path.join(".pidx");
+    IndexManager::validate_index_schema(&pidx_path)
+        .expect("写入器生成的索引应通过校验");
+    let original = read_editable_index(&pidx_path);
+
+    // 按行展开，便于检查报告的行号
+    let pretty = original.replace("><", ">\n<");
+    let schema_error = |xml: &str| {
+        let path = test_dataset_path.join("external.pidx");
+        std::fs::write(&path, xml).expect("写入索引失败");
+        match IndexManager::validate_index_schema(&path) {
+            Err(PcapError::InvalidIndexSchema(error)) => {
+                error
+            }
+            other => {
+                panic!("应报告索引结构错误: {other:?}")
+            }
+        }
+    };
+    let line_of = |xml: &str, needle: &str| {
+        xml[..xml.find(needle).expect("未找到内容")]
+            .matches('\n')
+            .count() as u64
+            + 1
+    };
+
+    // 数据包缺少必需属性
+    let broken =
+        pretty.replacen(" byte_offset=\"16\"", "", 1);
+    let error = schema_error(&broken);
+    assert_eq!(
+        error.element.as_deref(),
+        Some("index/data_files/file/packet")
+    );
+    assert!(error.message.contains("byte_offset"));
+    assert_eq!(error.line, line_of(&broken, "<packet"));
+    assert_eq!(error.column, 1);
+
+    // 属性值不是整数
+    let broken = pretty.replacen(
+        "packet_count=\"",
+        "packet_count=\"x",
+        1,
+    );
+    let error = schema_error(&broken);
+    assert_eq!(
+        error.element.as_deref(),
+        Some("index/data_files/file")
+    );
+    assert!(error.message.contains("packet_count"));
+    assert_eq!(error.line, line_of(&broken, "<file"));
+
+    // 文本内容不是整数
+    let broken = pretty.replacen(
+        "<total_packets>",
+        "<total_packets>many",
+        1,
+    );
+    let error = schema_error(&broken);
+    assert_eq!(
+        error.element.as_deref(),
+        Some("index/total_packets")
+    );
+
+    // 未知元素和缺少的子元素
+    let broken = pretty.replacen(
+        "<description>",
+        "<comment/>\n<description>",
+        1,
+    );
+    let error = schema_error(&broken);
+    assert_eq!(
+        error.element.as_deref(),
+        Some("index/comment")
+    );
+    let start = pretty.find("<created_time>").unwrap();
+    let end = pretty.find("</created_time>").unwrap()
+        + "</created_time>".len();
+    let broken =
+        format!("{}{}", &pretty[..start], &pretty[end..]);
+    let error = schema_error(&broken);
+    assert_eq!(error.element.as_deref(), Some("index"));
+    assert!(error.message.contains("created_time"));
+
+    // XML 语法错误报告出错位置
+    let error = schema_error("<index>\n  <description>");
+    assert_eq!(error.line, 2);
+    assert!(error.to_string().contains("第 2 行"));
+
+    assert!(matches!(
+        IndexManager::validate_index_schema(
+            test_dataset_path.join("missing.pidx")
+        ),
+        Err(PcapError::FileNotFound(_))
+    ));
+
+    // 读取器加载结构无效的索引时在健康报告中给出位置
+    std::fs::remove_file(
+        test_dataset_path.join("external.pidx"),
+    )
+    .expect("删除索引失败");
+    std::fs::write(
+        &pidx_path,
+        pretty.replacen(" hash=\"", " checksum=\"", 1),
+    )
+    .expect("写入索引失败");
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    reader.initialize().expect("损坏索引应自动修复");
+    let entry = &reader.index().health().entries()[0];
+    assert_eq!(entry.kind, IndexHealthKind::CorruptedIndex);
+    assert!(entry
+        .message
+        .contains("<index/data_files/file>"));
+}
+
+#[test]
+fn test_index_compaction() {
+    const TEST_NAME: &str = "test_index_compaction";
+    const PACKET_COUNT: u32 = 30;
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+
+    let test_dataset_path = dataset_path.join(TEST_NAME);
+    clean_dataset_directory(&test_dataset_path)
+        .expect("清理测试目录失败");
+
+    let config = WriterConfig {
+        max_packets_per_file: 10,
+        ..WriterConfig::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        &dataset_path,
+        TEST_NAME,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+    for i in 0..PACKET_COUNT {
+        let packet = create_test_packet(i, 32)
+            .expect("创建测试数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let pidx_path = test_dataset_path.join(".pidx");
+    let original = read_editable_index(&pidx_path);
+
+    // 构造多次局部重建后积累的冗余内容
+    let file_start = original.find("<file ").unwrap();
+    let file_end =
+        original.find("</file>").unwrap() + "</file>".len();
+    let file_element = &original[file_start..file_end];
+    let first_packet = {
+        let start = file_element.find("<packet ").unwrap();
+        let end = start
+            + file_element[start..].find("/>").unwrap()
+            + 2;
+        &file_element[start..end]
+    };
+    let second_packet = {
+        let start = file_element
+            .match_indices("<packet ")
+            .nth(1)
+            .unwrap()
+            .0;
+        let end = start
+            + file_element[start..].find("/>").unwrap()
+            + 2;
+        &file_element[start..end]
+    };
+    // 交换前两个数据包条目并重复记录第一个
+    let garbled = file_element.replacen(
+        &format!("{first_packet}{second_packet}"),
+        &format!(
+            "{second_packet}{first_packet}{first_packet}"
+        ),
+        1,
+    );
+    let file_name = garbled
+        .split("name=\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .unwrap()
+        .to_string();
+    let orphan =
+        garbled.replacen(&file_name, "missing.pcap", 1);
+    let garbage = format!(
+        "{}{garbled}{file_element}{orphan}{}",
+        &original[..file_start],
+        &original[file_end..]
+    );
+    std::fs::write(&pidx_path, garbage)
+        .expect("写入索引失败");
+
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    let report =
+        reader.index_mut().compact().expect("压缩索引失败");
+    assert_eq!(report.orphan_files_removed, 1);
+    assert_eq!(report.duplicate_files_removed, 1);
+    assert_eq!(report.duplicate_entries_removed, 1);
+    assert_eq!(report.files_resorted, 1);
+    assert!(report.bytes_after < report.bytes_before);
+    assert!(!report.is_clean());
+
+    let index =
+        reader.index().get_index().expect("获取索引失败");
+    assert_eq!(index.file_count(), 3);
+    assert_eq!(index.total_packets(), PACKET_COUNT as u64);
+    for file in index.files() {
+        assert!(file
+            .data_packets
+            .windows(2)
+            .all(|pair| pair[0].byte_offset
+                < pair[1].byte_offset));
+    }
+
+    // 压缩后的索引无需重建即可通过有效性检查
+    let mut count = 0;
+    reader.initialize().expect("初始化失败");
+    while reader.read_packet().expect("读取失败").is_some()
+    {
+        count += 1;
+    }
+    assert_eq!(count, PACKET_COUNT);
+    assert!(reader.index().health().is_healthy());
+
+    let report =
+        reader.index_mut().compact().expect("压缩索引失败");
+    assert!(report.is_clean());
+}
+
+/// 启用负载哈希写入数据集，负载由 `payloads` 给出
+fn write_with_payload_hash(
+    dataset_path: &std::path::Path,
+    name: &str,
+    payloads: &[u8],
+) {
+    clean_dataset_directory(dataset_path.join(name))
+        .expect("清理测试目录失败");
+    let config = WriterConfig {
+        index_payload_hash: true,
+        max_packets_per_file: 4,
+        ..WriterConfig::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        dataset_path,
+        name,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+    for (i, value) in payloads.iter().enumerate() {
+        writer
+            .write_payload(
+                1_700_000_000_000_000_000
+                    + i as u64 * 1_000,
+                &[*value; 24],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+#[test]
+fn test_index_payload_hash() {
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+    write_with_payload_hash(
+        &dataset_path,
+        "test_payload_hash_a",
+        &[1, 2, 3, 1, 4, 2, 1, 5],
+    );
+    write_with_payload_hash(
+        &dataset_path,
+        "test_payload_hash_b",
+        &[1, 2, 3, 1, 4, 2, 6, 5],
+    );
+
+    let mut reader_a = PcapReader::new(
+        &dataset_path,
+        "test_payload_hash_a",
+    )
+    .expect("创建PcapReader失败");
+    reader_a.initialize().expect("初始化失败");
+    let query =
+        reader_a.index().query().expect("索引未加载");
+    assert!(query.has_payload_hashes());
+    assert_eq!(
+        query.duplicate_payloads(),
+        vec![vec![0, 3, 6], vec![1, 5]]
+    );
+
+    // 读取器重建索引时保留负载哈希
+    reader_a
+        .index_mut()
+        .rebuild_index()
+        .expect("重建索引失败");
+    let query =
+        reader_a.index().query().expect("索引未加载");
+    assert_eq!(query.duplicate_payloads().len(), 2);
+
+    let mut reader_b = PcapReader::new(
+        &dataset_path,
+        "test_payload_hash_b",
+    )
+    .expect("创建PcapReader失败");
+    reader_b.initialize().expect("初始化失败");
+    let other =
+        reader_b.index().query().expect("索引未加载");
+    let diff = query.diff_payloads(&other);
+    assert_eq!(diff.only_in_self, vec![6]);
+    assert_eq!(diff.only_in_other, vec![6]);
+    assert!(query.diff_payloads(&query).is_identical());
+
+    // 默认不记录负载哈希
+    let name = "test_payload_hash_off";
+    clean_dataset_directory(dataset_path.join(name))
+        .expect("清理测试目录失败");
+    let mut writer = PcapWriter::new(&dataset_path, name)
+        .expect("创建PcapWriter失败");
+    writer
+        .write_payload(1_700_000_000_000_000_000, &[1; 24])
+        .expect("写入负载失败");
+    writer.finalize().expect("完成写入失败");
+    let mut reader = PcapReader::new(&dataset_path, name)
+        .expect("创建PcapReader失败");
+    reader.initialize().expect("初始化失败");
+    assert!(!reader
+        .index()
+        .query()
+        .expect("索引未加载")
+        .has_payload_hashes());
+}
+
+#[test]
+fn test_shared_index_snapshots() {
+    const TEST_NAME: &str = "test_shared_index_snapshots";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+    let test_dataset_path = dataset_path.join(TEST_NAME);
+    clean_dataset_directory(&test_dataset_path)
+        .expect("清理测试目录失败");
+
+    let config = WriterConfig {
+        max_packets_per_file: 50,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        &dataset_path,
+        TEST_NAME,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+    for i in 0..200u32 {
+        let packet = create_test_packet(i, 32)
+            .expect("创建测试数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    let shared = reader.index().shared_index();
+    assert!(!shared.is_loaded());
+    reader.initialize().expect("初始化Reader失败");
+
+    // 快照和共享句柄指向同一份索引，不复制条目
+    let snapshot = reader
+        .index()
+        .index_snapshot()
+        .expect("索引未加载");
+    let published = shared.snapshot().expect("索引未发布");
+    assert!(std::sync::Arc::ptr_eq(&snapshot, &published));
+    assert_eq!(snapshot.total_packets, 200);
+
+    // 其他线程通过句柄读取索引
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                let index =
+                    shared.snapshot().expect("索引未发布");
+                index
+                    .data_files
+                    .files
+                    .iter()
+                    .map(|file| file.packet_count)
+                    .sum::<u64>()
+            })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().expect("线程异常"), 200);
+    }
+
+    // 重建后句柄发布新的索引，已取得的快照保持不变
+    reader
+        .index_mut()
+        .rebuild_index()
+        .expect("重建索引失败");
+    let rebuilt = shared.snapshot().expect("索引未发布");
+    assert!(!std::sync::Arc::ptr_eq(&snapshot, &rebuilt));
+    assert_eq!(rebuilt.total_packets, 200);
+    assert_eq!(snapshot.total_packets, 200);
+
+    let mut count = 0;
+    while reader
+        .read_packet()
+        .expect("读取数据包失败")
+        .is_some()
+    {
+        count += 1;
+    }
+    assert_eq!(count, 200);
+}
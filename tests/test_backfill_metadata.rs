@@ -0,0 +1,208 @@
+//! 文件元数据补全测试
+//!
+//! 模拟早期版本生成的索引缺少文件哈希、大小、数据包数量、时间范围和数据包条目，
+//! 测试补全后的索引与重新生成的索引一致，并且加载时不再整体重建
+
+use pcapfile_io::business::index::IndexManager;
+use pcapfile_io::{
+    PcapError, PcapFileIndex, PcapReader, PcapWriter,
+    WriterConfig,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "legacy";
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+/// 写入跨 3 个文件的数据集，返回生成的文件索引
+fn write_dataset(base: &Path) -> Vec<PcapFileIndex> {
+    let config = WriterConfig {
+        max_packets_per_file: 4,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base, TEST_NAME, config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..10u64 {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + i * 1_000,
+                &[i as u8; 32],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+    drop(writer);
+
+    let mut reader = PcapReader::new(base, TEST_NAME)
+        .expect("创建读取器失败");
+    reader.initialize().expect("初始化失败");
+    reader
+        .index()
+        .get_index()
+        .expect("索引未加载")
+        .files()
+        .to_vec()
+}
+
+/// 去掉文件开始标签中的属性
+fn strip_attribute(
+    xml: &str,
+    file: &PcapFileIndex,
+    attribute: &str,
+    value: &str,
+) -> String {
+    let tag = format!("<file name=\"{}\"", file.file_name);
+    let start = xml.find(&tag).expect("未找到文件");
+    let end = start + xml[start..].find('>').unwrap();
+    let stripped = xml[start..end].replacen(
+        &format!(" {attribute}=\"{value}\""),
+        "",
+        1,
+    );
+    format!("{}{}{}", &xml[..start], stripped, &xml[end..])
+}
+
+/// 构造早期版本的索引：没有结构版本和摘要，第一个文件缺少全部元数据，
+/// 第二个文件缺少数据包数量、时间范围和数据包条目
+fn make_legacy_index(
+    pidx_path: &Path,
+    files: &[PcapFileIndex],
+) {
+    let content = fs::read_to_string(pidx_path)
+        .expect("读取索引失败");
+    let body = &content
+        [..content.rfind("\n<!-- pidx-sha256:").unwrap()];
+    let mut xml =
+        body.replacen(" schema_version=\"2\"", "", 1);
+
+    let first = &files[0];
+    for (attribute, value) in [
+        ("hash", first.file_hash.clone()),
+        ("size", first.file_size.to_string()),
+        ("packet_count", first.packet_count.to_string()),
+        (
+            "start_timestamp",
+            first.start_timestamp.to_string(),
+        ),
+        ("end_timestamp", first.end_timestamp.to_string()),
+    ] {
+        xml =
+            strip_attribute(&xml, first, attribute, &value);
+    }
+
+    let second = &files[1];
+    for (attribute, value) in [
+        ("packet_count", second.packet_count.to_string()),
+        (
+            "start_timestamp",
+            second.start_timestamp.to_string(),
+        ),
+        ("end_timestamp", second.end_timestamp.to_string()),
+    ] {
+        xml = strip_attribute(
+            &xml, second, attribute, &value,
+        );
+    }
+    let tag =
+        format!("<file name=\"{}\"", second.file_name);
+    let start = xml.find(&tag).unwrap();
+    let open_end =
+        start + xml[start..].find('>').unwrap() + 1;
+    let close =
+        open_end + xml[open_end..].find("</file>").unwrap();
+    xml.replace_range(open_end..close, "");
+
+    fs::write(pidx_path, xml).expect("写入索引失败");
+}
+
+#[test]
+fn test_backfill_legacy_file_metadata() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let files = write_dataset(temp_dir.path());
+    assert_eq!(files.len(), 3);
+    let pidx_path =
+        temp_dir.path().join(TEST_NAME).join(".pidx");
+    make_legacy_index(&pidx_path, &files);
+    IndexManager::validate_index_schema(&pidx_path)
+        .expect("缺少文件元数据的索引应通过结构校验");
+
+    let mut manager =
+        IndexManager::new(temp_dir.path(), TEST_NAME)
+            .expect("创建索引管理器失败");
+    let report = manager
+        .backfill_file_metadata()
+        .expect("补全元数据失败");
+    assert_eq!(report.hashes_filled, 1);
+    assert_eq!(report.sizes_filled, 1);
+    assert_eq!(report.packet_counts_filled, 1);
+    assert_eq!(report.time_ranges_filled, 1);
+    assert_eq!(report.files_rescanned, 1);
+    assert_eq!(report.missing_files, 0);
+    assert!(report.schema_upgraded);
+    assert!(!report.is_clean());
+
+    // 补全后的文件元数据与写入器生成的一致
+    let index = manager.get_index().expect("索引未加载");
+    assert!(!index.is_legacy_schema());
+    assert_eq!(index.total_packets(), 10);
+    for (actual, expected) in
+        index.files().iter().zip(&files)
+    {
+        assert_eq!(actual.file_name, expected.file_name);
+        assert_eq!(actual.file_hash, expected.file_hash);
+        assert_eq!(actual.file_size, expected.file_size);
+        assert_eq!(
+            actual.packet_count,
+            expected.packet_count
+        );
+        assert_eq!(
+            actual.start_timestamp,
+            expected.start_timestamp
+        );
+        assert_eq!(
+            actual.end_timestamp,
+            expected.end_timestamp
+        );
+        assert_eq!(
+            actual.data_packets.len(),
+            expected.data_packets.len()
+        );
+    }
+
+    // 改写后的索引直接可用，不再整体重建
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("创建读取器失败");
+    reader.initialize().expect("初始化失败");
+    assert!(reader.index().rebuild_reason().is_none());
+    let mut count = 0;
+    while reader.read_packet().expect("读取失败").is_some()
+    {
+        count += 1;
+    }
+    assert_eq!(count, 10);
+
+    let report = manager
+        .backfill_file_metadata()
+        .expect("补全元数据失败");
+    assert!(report.is_clean());
+}
+
+#[test]
+fn test_backfill_without_index() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    fs::create_dir_all(temp_dir.path().join(TEST_NAME))
+        .expect("创建目录失败");
+    let mut manager =
+        IndexManager::new(temp_dir.path(), TEST_NAME)
+            .expect("创建索引管理器失败");
+    assert!(matches!(
+        manager.backfill_file_metadata(),
+        Err(PcapError::FileNotFound(_))
+    ));
+}
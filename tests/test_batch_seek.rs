@@ -0,0 +1,180 @@
+//! 批量时间戳定位测试
+//!
+//! 测试 `resolve_timestamps` 的结果与逐个按时间戳读取一致（包括无序、重复的请求和稀疏索引），
+//! 以及按文件分组批量读取解析出的位置
+
+use pcapfile_io::{
+    ByteSize, PacketLocation, PcapError, PcapReader,
+    PcapWriter, ReaderConfig, SeekMode, WriterConfig,
+};
+use std::path::Path;
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "batch_seek";
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+/// 跨多个文件、包含重复时间戳和时间间隔的数据包偏移
+const OFFSETS: [u64; 10] =
+    [0, 10, 10, 10, 20, 50, 50, 60, 100, 100];
+
+const MODES: [SeekMode; 4] = [
+    SeekMode::Exact,
+    SeekMode::Floor,
+    SeekMode::Ceiling,
+    SeekMode::Nearest,
+];
+
+fn write_dataset(base: &Path) {
+    let config = WriterConfig {
+        max_packets_per_file: 4,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base, TEST_NAME, config,
+    )
+    .expect("创建写入器失败");
+    for (i, offset) in OFFSETS.iter().enumerate() {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + offset,
+                &[i as u8; 8],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+/// 无序、重复且包含数据集范围之外的请求
+fn requests() -> Vec<u64> {
+    [100, 0, 15, 10, 200, 55, 10, 60, 35, 100, 101]
+        .iter()
+        .map(|offset| BASE_TIMESTAMP + offset)
+        .chain([0, BASE_TIMESTAMP - 1])
+        .collect()
+}
+
+fn assert_matches_single_lookups(reader: &mut PcapReader) {
+    let requests = requests();
+    for mode in MODES {
+        let locations = reader
+            .resolve_timestamps_with_mode(&requests, mode)
+            .expect("批量定位失败");
+        assert_eq!(locations.len(), requests.len());
+        let packets = reader
+            .read_packets_at_locations(&locations)
+            .expect("批量读取失败");
+
+        for ((&request, location), packet) in
+            requests.iter().zip(&locations).zip(&packets)
+        {
+            let expected = reader
+                .read_packet_by_timestamp_with_mode(
+                    request, mode,
+                )
+                .expect("读取失败");
+            assert_eq!(
+                packet.as_ref().map(|p| &p.packet.data),
+                expected.as_ref().map(|p| &p.packet.data),
+                "{mode:?} {request}"
+            );
+            assert_eq!(
+                location.is_some(),
+                packet.is_some()
+            );
+            if let Some(location) = location {
+                assert_eq!(
+                    location.requested_timestamp_ns,
+                    request
+                );
+                assert_eq!(
+                    location.timestamp_ns,
+                    packet
+                        .as_ref()
+                        .unwrap()
+                        .packet
+                        .get_timestamp_ns()
+                );
+                assert_eq!(location.packet_size, 8);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_resolve_timestamps_matches_single_lookups() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path());
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    assert_matches_single_lookups(&mut reader);
+
+    // 内存上限迫使索引进入稀疏模式时结果不变
+    let config = ReaderConfig {
+        max_index_memory: ByteSize::b(1),
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("打开失败");
+    assert_matches_single_lookups(&mut reader);
+}
+
+#[test]
+fn test_resolve_timestamps_locations() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path());
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    let locations = reader
+        .resolve_timestamps(&[
+            BASE_TIMESTAMP + 60,
+            BASE_TIMESTAMP + 5,
+            BASE_TIMESTAMP + 10,
+        ])
+        .expect("批量定位失败");
+
+    // 精确匹配：不存在的时间戳为 None，重复时间戳定位到写入顺序最早的数据包
+    assert!(locations[1].is_none());
+    let first = locations[2].as_ref().unwrap();
+    assert_eq!(first.global_index, 1);
+    assert_eq!(first.file_index, 0);
+    let last = locations[0].as_ref().unwrap();
+    assert_eq!(last.global_index, 7);
+    assert_eq!(last.file_index, 1);
+    assert_ne!(first.file_name, last.file_name);
+
+    let packets = reader
+        .read_packets_by_timestamps(
+            &[BASE_TIMESTAMP + 60, BASE_TIMESTAMP + 5],
+            SeekMode::Exact,
+        )
+        .expect("批量读取失败");
+    assert_eq!(
+        packets[0].as_ref().map(|p| p.packet.data.clone()),
+        Some(vec![7u8; 8])
+    );
+    assert!(packets[1].is_none());
+    assert!(reader
+        .resolve_timestamps(&[])
+        .expect("批量定位失败")
+        .is_empty());
+
+    // 与当前索引不一致的位置被拒绝
+    let stale = PacketLocation {
+        file_name: "missing.pcap".to_string(),
+        ..last.clone()
+    };
+    assert!(matches!(
+        reader.read_packets_at_locations(&[Some(stale)]),
+        Err(PcapError::InvalidArgument(_))
+    ));
+}
@@ -0,0 +1,425 @@
+//! 分块文件布局测试
+//!
+//! 测试分块写入和透明读取、块尾内容、按块尾时间范围跳过块、
+//! 损坏限制在单个块内，以及定位、差值编码、重建索引、实时读取和配置校验
+
+use pcapfile_io::{
+    read_blocks_in_range, scan_blocks, validate_file_quick,
+    verify_blocks, ByteSize, HeaderExtension, MemoryReader,
+    PcapError, PcapReader, PcapWriter, RecordFormat,
+    WriterConfig,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::{
+    data_files, read_all, write_payload_dataset,
+    write_payloads,
+};
+
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+const PACKET_INTERVAL_NS: u64 = 1_000;
+const BLOCK_SIZE: u64 = 4096;
+/// 64 字节负载的记录为 80 字节，每个 4 KiB 的块容纳 50 个
+const PACKETS_PER_BLOCK: u64 = 50;
+
+fn payload(i: u64) -> Vec<u8> {
+    vec![(i % 251) as u8; 64]
+}
+
+fn block_config() -> WriterConfig {
+    WriterConfig {
+        max_packets_per_file: 200,
+        block_size: ByteSize::kib(4),
+        ..Default::default()
+    }
+}
+
+fn packets(
+    range: std::ops::Range<u64>,
+) -> impl Iterator<Item = (u64, Vec<u8>)> {
+    range.map(|i| {
+        (
+            BASE_TIMESTAMP + i * PACKET_INTERVAL_NS,
+            payload(i),
+        )
+    })
+}
+
+fn write_packets(
+    writer: &mut PcapWriter,
+    range: std::ops::Range<u64>,
+) -> Vec<(u64, Vec<u8>)> {
+    write_payloads(writer, packets(range))
+}
+
+fn write_dataset(
+    base: &Path,
+    name: &str,
+    config: WriterConfig,
+    count: u64,
+) -> Vec<(u64, Vec<u8>)> {
+    write_payload_dataset(
+        base,
+        name,
+        config,
+        packets(0..count),
+    )
+}
+
+/// 覆盖文件中指定偏移处的字节
+fn overwrite(path: &Path, offset: u64, bytes: &[u8]) {
+    let mut data = fs::read(path).unwrap();
+    let offset = offset as usize;
+    data[offset..offset + bytes.len()]
+        .copy_from_slice(bytes);
+    fs::write(path, data).unwrap();
+}
+
+#[test]
+fn test_block_layout_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let expected =
+        write_dataset(base, "blocks", block_config(), 500);
+
+    let mut reader =
+        PcapReader::new(base, "blocks").unwrap();
+    assert!(reader.header_extensions().unwrap().contains(
+        &HeaderExtension::BlockSize(BLOCK_SIZE.to_string())
+    ));
+    assert_eq!(
+        reader.get_file_info_list().unwrap().len(),
+        3
+    );
+    assert_eq!(read_all(base, "blocks"), expected);
+}
+
+#[test]
+fn test_block_footers_describe_blocks() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let expected =
+        write_dataset(base, "blocks", block_config(), 170);
+    let files = data_files(&base.join("blocks"));
+    assert_eq!(files.len(), 1);
+
+    // 前三个块写满后填充到块大小，最后一个块的块尾紧跟在记录之后
+    let blocks = scan_blocks(&files[0]).unwrap();
+    assert_eq!(blocks.len(), 4);
+    let data_offset = blocks[0].offset;
+    let mut first = 0;
+    for (index, block) in blocks.iter().enumerate() {
+        let count = block.footer.packet_count as u64;
+        assert_eq!(block.index, index as u64);
+        assert_eq!(
+            block.offset,
+            data_offset + index as u64 * BLOCK_SIZE
+        );
+        assert_eq!(
+            count,
+            PACKETS_PER_BLOCK.min(170 - first)
+        );
+        assert_eq!(
+            block.footer.data_length as u64,
+            count * 80
+        );
+        assert_eq!(
+            block.footer.min_timestamp_ns,
+            expected[first as usize].0
+        );
+        assert_eq!(
+            block.footer.max_timestamp_ns,
+            expected[(first + count - 1) as usize].0
+        );
+        first += count;
+    }
+    assert_eq!(
+        fs::metadata(&files[0]).unwrap().len(),
+        blocks[3].data_end() + 32
+    );
+    assert!(verify_blocks(&files[0]).unwrap().is_empty());
+
+    let structure = validate_file_quick(&files[0]).unwrap();
+    assert_eq!(structure.packet_count, 170);
+    assert_eq!(
+        structure.last_timestamp_ns,
+        Some(expected[169].0)
+    );
+}
+
+#[test]
+fn test_read_blocks_in_range_skips_blocks() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let expected =
+        write_dataset(base, "blocks", block_config(), 200);
+    let files = data_files(&base.join("blocks"));
+
+    // 第 60 到 80 个数据包都在第二个块中
+    let start_ns = expected[60].0;
+    let end_ns = expected[80].0;
+    let blocks = scan_blocks(&files[0]).unwrap();
+    assert_eq!(
+        blocks
+            .iter()
+            .filter(|block| block
+                .footer
+                .overlaps(start_ns, end_ns))
+            .count(),
+        1
+    );
+
+    // 损坏其他块的记录不影响范围读取，说明这些块没有被读取
+    overwrite(&files[0], blocks[0].offset + 8, &[0xFF; 4]);
+    overwrite(&files[0], blocks[3].offset + 8, &[0xFF; 4]);
+    let packets =
+        read_blocks_in_range(&files[0], start_ns, end_ns)
+            .unwrap();
+    assert_eq!(packets.len(), 21);
+    for (packet, (timestamp_ns, payload)) in
+        packets.iter().zip(&expected[60..=80])
+    {
+        assert!(packet.is_valid());
+        assert_eq!(
+            packet.packet.get_timestamp_ns(),
+            *timestamp_ns
+        );
+        assert_eq!(&packet.packet.data, payload);
+    }
+}
+
+#[test]
+fn test_corruption_contained_to_one_block() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let expected =
+        write_dataset(base, "blocks", block_config(), 200);
+    let files = data_files(&base.join("blocks"));
+    let blocks = scan_blocks(&files[0]).unwrap();
+
+    // 第二个块第一个数据包的长度字段损坏，读取时跳过整个块
+    overwrite(&files[0], blocks[1].offset + 8, &[0xFF; 4]);
+    // 第三个块的块尾魔术数损坏，整个块被跳过
+    overwrite(
+        &files[0],
+        blocks[2].offset + BLOCK_SIZE - 32,
+        b"XXXX",
+    );
+
+    let packets = read_all(base, "blocks");
+    let mut survivors = expected[..50].to_vec();
+    survivors.extend_from_slice(&expected[150..]);
+    assert_eq!(packets, survivors);
+
+    let corrupted = verify_blocks(&files[0]).unwrap();
+    assert_eq!(corrupted.len(), 1);
+    assert_eq!(corrupted[0].index, 1);
+    assert_eq!(scan_blocks(&files[0]).unwrap().len(), 3);
+    assert!(validate_file_quick(&files[0]).is_err());
+}
+
+#[test]
+fn test_block_seek_by_position_and_timestamp() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let expected =
+        write_dataset(base, "blocks", block_config(), 500);
+
+    let mut reader =
+        PcapReader::new(base, "blocks").unwrap();
+    // 从块的最后一个数据包开始读取，跨过填充和块尾
+    reader.seek_to_packet(249).unwrap();
+    for (timestamp_ns, payload) in &expected[249..253] {
+        let packet = reader.read_packet().unwrap().unwrap();
+        assert_eq!(
+            packet.packet.get_timestamp_ns(),
+            *timestamp_ns
+        );
+        assert_eq!(&packet.packet.data, payload);
+    }
+
+    let (timestamp_ns, payload) = &expected[399];
+    let packet = reader
+        .read_packet_by_timestamp(*timestamp_ns)
+        .unwrap()
+        .expect("应找到数据包");
+    assert_eq!(&packet.packet.data, payload);
+
+    reader.seek_to_timestamp(expected[99].0).unwrap();
+    for (timestamp_ns, _) in &expected[99..102] {
+        let packet = reader.read_packet().unwrap().unwrap();
+        assert_eq!(
+            packet.packet.get_timestamp_ns(),
+            *timestamp_ns
+        );
+    }
+}
+
+#[test]
+fn test_blocks_with_delta_timestamps() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let config = WriterConfig {
+        record_format: RecordFormat::DeltaTimestamp,
+        ..block_config()
+    };
+    let expected =
+        write_dataset(base, "blocks", config, 400);
+    assert_eq!(read_all(base, "blocks"), expected);
+
+    // 每个块的第一个数据包以 0 为基准，可以单独读取
+    let files = data_files(&base.join("blocks"));
+    let blocks = scan_blocks(&files[0]).unwrap();
+    assert!(blocks.len() > 1);
+    let block = blocks[1];
+    let packets = read_blocks_in_range(
+        &files[0],
+        block.footer.min_timestamp_ns,
+        block.footer.max_timestamp_ns,
+    )
+    .unwrap();
+    assert_eq!(
+        packets.len() as u32,
+        block.footer.packet_count
+    );
+
+    let mut reader =
+        PcapReader::new(base, "blocks").unwrap();
+    reader.seek_to_packet(333).unwrap();
+    let packet = reader.read_packet().unwrap().unwrap();
+    assert_eq!(
+        packet.packet.get_timestamp_ns(),
+        expected[333].0
+    );
+}
+
+#[test]
+fn test_blocks_rebuilt_index_matches_records() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    // 写入时不记录索引，完成写入时重新分析数据文件
+    let config = WriterConfig {
+        index_batch_size: 0,
+        payload_dedup: true,
+        ..block_config()
+    };
+    let expected =
+        write_dataset(base, "blocks", config, 300);
+
+    let mut reader =
+        PcapReader::new(base, "blocks").unwrap();
+    reader.seek_to_packet(275).unwrap();
+    let packet = reader.read_packet().unwrap().unwrap();
+    assert_eq!(packet.packet.data, expected[275].1);
+    assert_eq!(read_all(base, "blocks"), expected);
+}
+
+#[test]
+fn test_blocks_live_reader_reads_open_block() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let config = WriterConfig {
+        index_checkpoint_interval: 1,
+        ..block_config()
+    };
+    let mut writer =
+        PcapWriter::new_with_config(base, "blocks", config)
+            .unwrap();
+    let mut expected = write_packets(&mut writer, 0..70);
+    writer.flush().unwrap();
+
+    // 第二个块还没有块尾
+    let mut reader =
+        PcapReader::new(base, "blocks").unwrap();
+    reader.initialize().unwrap();
+    assert!(reader.is_live());
+    let mut packets = Vec::new();
+    while let Some(packet) = reader.read_packet().unwrap() {
+        packets.push((
+            packet.packet.get_timestamp_ns(),
+            packet.packet.data,
+        ));
+    }
+
+    let more = write_packets(&mut writer, 70..120);
+    writer.flush().unwrap();
+    reader.refresh_index().unwrap();
+    while let Some(packet) = reader.read_packet().unwrap() {
+        packets.push((
+            packet.packet.get_timestamp_ns(),
+            packet.packet.data,
+        ));
+    }
+    writer.finalize().unwrap();
+
+    expected.extend(more);
+    assert_eq!(packets, expected);
+}
+
+#[test]
+fn test_blocks_respect_max_file_size() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    // 块填充和块尾计入文件大小，每个文件不超过上限
+    let config = WriterConfig {
+        max_packets_per_file: 10_000,
+        max_file_size: ByteSize::b(10_000),
+        ..block_config()
+    };
+    let expected =
+        write_dataset(base, "sized", config, 500);
+
+    let files = data_files(&base.join("sized"));
+    assert!(files.len() > 1);
+    for path in &files {
+        let size = fs::metadata(path).unwrap().len();
+        assert!(size <= 10_000, "{path:?}: {size} 字节");
+        assert!(verify_blocks(path).unwrap().is_empty());
+    }
+    assert_eq!(read_all(base, "sized"), expected);
+}
+
+#[test]
+fn test_blocks_reject_oversized_packets() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        "blocks",
+        block_config(),
+    )
+    .unwrap();
+    let error = writer
+        .write_payload(BASE_TIMESTAMP, &[0u8; 4096])
+        .unwrap_err();
+    assert!(
+        matches!(
+            error,
+            PcapError::InvalidPacketSize { .. }
+        ),
+        "超过块容量的数据包应被拒绝: {error:?}"
+    );
+}
+
+#[test]
+fn test_block_layout_config_and_memory_reader() {
+    let config = WriterConfig {
+        block_size: ByteSize::b(1000),
+        ..Default::default()
+    };
+    let error = config.validate().unwrap_err();
+    assert!(error.starts_with("block_size:"), "{error}");
+    assert!(block_config().validate().is_ok());
+
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    write_dataset(base, "blocks", block_config(), 10);
+    let files = data_files(&base.join("blocks"));
+    let mut reader = MemoryReader::new();
+    reader
+        .push_chunk(&fs::read(&files[0]).unwrap())
+        .unwrap();
+    assert!(reader.read_packet().is_err());
+}
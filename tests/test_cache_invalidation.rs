@@ -0,0 +1,124 @@
+//! 缓存失效测试
+//!
+//! 测试文件信息缓存在文件改写、删除以及监视模式下的失效行为
+
+use pcapfile_io::business::cache::FileInfoCache;
+use pcapfile_io::{
+    FileInfo, PcapReader, PcapWriter, ReaderConfig,
+};
+use std::time::Duration;
+use tempfile::TempDir;
+
+mod common;
+use common::create_test_packet;
+
+#[test]
+fn test_file_info_cache_invalidates_on_rewrite() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let file_path = temp_dir.path().join("data.bin");
+    std::fs::write(&file_path, b"original")
+        .expect("写入文件失败");
+
+    let cache = FileInfoCache::new(16);
+    cache.insert(&file_path, FileInfo::new(&file_path));
+    assert!(cache.get(&file_path).is_some());
+
+    // 改写文件（大小变化）后条目失效
+    std::fs::write(&file_path, b"rewritten contents")
+        .expect("改写文件失败");
+    assert!(cache.get(&file_path).is_none());
+    assert_eq!(cache.get_cache_stats().cache_entries, 0);
+
+    // 删除文件后条目失效
+    cache.insert(&file_path, FileInfo::new(&file_path));
+    std::fs::remove_file(&file_path).expect("删除文件失败");
+    assert!(cache.get(&file_path).is_none());
+
+    let statistics =
+        cache.get_statistics().expect("获取统计信息失败");
+    assert_eq!(statistics.invalidated_entries, 2);
+}
+
+#[test]
+fn test_file_info_cache_watch_mode() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let first = temp_dir.path().join("first.bin");
+    let second = temp_dir.path().join("second.bin");
+    std::fs::write(&first, b"first").expect("写入文件失败");
+    std::fs::write(&second, b"second")
+        .expect("写入文件失败");
+
+    let cache = FileInfoCache::new(16);
+    cache.insert(&first, FileInfo::new(&first));
+    cache.insert(&second, FileInfo::new(&second));
+
+    // 未启用监视模式时不扫描目录
+    assert_eq!(cache.poll_changes(), 0);
+
+    cache.watch(temp_dir.path(), Duration::from_secs(3600));
+    assert!(cache.is_watching());
+
+    // 只清除发生变化的文件条目
+    std::fs::remove_file(&second).expect("删除文件失败");
+    assert_eq!(cache.poll_changes(), 1);
+    assert_eq!(cache.get_cache_stats().cache_entries, 1);
+    assert!(cache.get(&first).is_some());
+
+    cache.unwatch();
+    assert!(!cache.is_watching());
+}
+
+#[test]
+fn test_reader_file_info_after_dataset_rewrite() {
+    const TEST_NAME: &str = "cache_rewrite";
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+
+    let mut writer =
+        PcapWriter::new(temp_dir.path(), TEST_NAME)
+            .expect("创建PcapWriter失败");
+    for i in 0..10 {
+        writer
+            .write_packet(
+                &create_test_packet(i, 128)
+                    .expect("创建测试数据包失败"),
+            )
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let config = ReaderConfig {
+        watch_dataset: true,
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("创建PcapReader失败");
+
+    let infos = reader
+        .get_file_info_list()
+        .expect("获取文件信息失败");
+    assert_eq!(infos.len(), 1);
+    let infos = reader
+        .get_file_info_list()
+        .expect("获取文件信息失败");
+    assert_eq!(infos.len(), 1);
+    assert_eq!(reader.get_cache_stats().hit_count, 1);
+
+    // 在原文件末尾追加数据，模拟改写
+    let data_file = &infos[0].file_path;
+    let mut content =
+        std::fs::read(data_file).expect("读取文件失败");
+    content.extend_from_slice(&[0u8; 4]);
+    std::fs::write(data_file, content)
+        .expect("改写文件失败");
+
+    assert_eq!(reader.poll_dataset_changes(), 1);
+    reader.get_file_info_list().expect("获取文件信息失败");
+    assert_eq!(reader.get_cache_stats().hit_count, 1);
+}
@@ -0,0 +1,113 @@
+//! 时钟源测试
+//!
+//! 测试系统时钟、单调时钟、注入时钟和闭包时钟的时间戳，
+//! 以及写入器通过时钟源生成心跳包、事件标记和数据包的时间戳
+
+use pcapfile_io::{
+    ClockSource, DataPacket, InjectedClock, MonotonicClock,
+    PcapReader, PcapWriter, SystemClock, WriterConfig,
+};
+use std::time::Duration;
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "clock";
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+#[test]
+fn test_builtin_clocks() {
+    // 系统时钟在 2023 年之后
+    assert!(SystemClock.now_ns() > BASE_TIMESTAMP);
+
+    let clock = MonotonicClock::with_offset(BASE_TIMESTAMP);
+    let first = clock.now_ns();
+    std::thread::sleep(Duration::from_millis(2));
+    let second = clock.now_ns();
+    assert!(first >= BASE_TIMESTAMP);
+    assert!(second >= first + 1_000_000);
+
+    // 克隆的注入时钟共享同一个时间
+    let clock = InjectedClock::new(BASE_TIMESTAMP);
+    let shared = clock.clone();
+    shared.set_ns(BASE_TIMESTAMP + 10);
+    assert_eq!(clock.now_ns(), BASE_TIMESTAMP + 10);
+    clock.advance_ns(5);
+    assert_eq!(shared.now_ns(), BASE_TIMESTAMP + 15);
+
+    let closure = || BASE_TIMESTAMP + 42;
+    assert_eq!(closure.now_ns(), BASE_TIMESTAMP + 42);
+}
+
+#[test]
+fn test_packet_from_clock() {
+    let clock = InjectedClock::new(BASE_TIMESTAMP + 7);
+    let packet =
+        DataPacket::from_now(&clock, vec![1, 2, 3])
+            .expect("创建数据包失败");
+    assert_eq!(
+        packet.get_timestamp_ns(),
+        BASE_TIMESTAMP + 7
+    );
+    assert!(packet.is_valid());
+
+    let boxed: Box<dyn ClockSource> =
+        Box::new(|| BASE_TIMESTAMP);
+    let packet =
+        DataPacket::from_now(boxed.as_ref(), vec![0])
+            .expect("创建数据包失败");
+    assert_eq!(packet.get_timestamp_ns(), BASE_TIMESTAMP);
+}
+
+#[test]
+fn test_writer_uses_injected_clock() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let config = WriterConfig {
+        heartbeat_interval: Duration::from_millis(1),
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("创建写入器失败");
+    let clock = InjectedClock::new(BASE_TIMESTAMP);
+    writer.set_clock(clock.clone());
+    assert_eq!(writer.clock().now_ns(), BASE_TIMESTAMP);
+
+    writer
+        .write_payload_now(&[1, 2, 3])
+        .expect("写入负载失败");
+    clock.advance_ns(1_000);
+    let marker =
+        writer.write_marker("scene").expect("写入标记失败");
+    assert_eq!(marker.timestamp_ns, BASE_TIMESTAMP + 1_000);
+    assert_eq!(marker.position, 1);
+
+    clock.advance_ns(1_000);
+    std::thread::sleep(Duration::from_millis(5));
+    assert!(writer
+        .write_heartbeat_if_idle()
+        .expect("写入心跳包失败"));
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    let mut timestamps = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取失败")
+    {
+        timestamps.push((
+            packet.get_timestamp_ns(),
+            packet.is_heartbeat(),
+        ));
+    }
+    assert_eq!(
+        timestamps,
+        vec![
+            (BASE_TIMESTAMP, false),
+            (BASE_TIMESTAMP + 2_000, true),
+        ]
+    );
+}
@@ -0,0 +1,219 @@
+//! 组合数据集读取测试
+//!
+//! 测试多个数据集按起始时间戳依次读取、时间范围重叠时拒绝依次读取而允许合并读取，
+//! 以及跨数据集的跳转和按时间范围读取
+
+use pcapfile_io::{
+    CompositeMode, CompositeReader, PacketSource,
+    PcapError, PcapReader, PcapWriter,
+};
+use std::path::Path;
+use tempfile::TempDir;
+
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+/// 写入数据集，负载为数据集名称
+fn write_dataset(base: &Path, name: &str, offsets: &[u64]) {
+    let mut writer = PcapWriter::new(base, name)
+        .expect("创建写入器失败");
+    for offset in offsets {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + offset,
+                name.as_bytes(),
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+fn open(base: &Path, name: &str) -> PcapReader {
+    PcapReader::new(base, name).expect("打开失败")
+}
+
+/// 读取剩余数据包，返回 (时间戳偏移, 数据集名称)
+fn read_all(
+    reader: &mut CompositeReader,
+) -> Vec<(u64, String)> {
+    let mut packets = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取失败")
+    {
+        packets.push((
+            packet.get_timestamp_ns() - BASE_TIMESTAMP,
+            String::from_utf8_lossy(&packet.packet.data)
+                .into_owned(),
+        ));
+    }
+    packets
+}
+
+fn entries(items: &[(u64, &str)]) -> Vec<(u64, String)> {
+    items
+        .iter()
+        .map(|(offset, name)| (*offset, name.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_concatenates_datasets_in_time_order() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path();
+    write_dataset(base, "day2", &[100, 110, 120]);
+    write_dataset(base, "day1", &[0, 10, 20, 100]);
+    write_dataset(base, "empty", &[]);
+
+    // 按起始时间戳排列，与传入顺序无关；边界时间戳相同不视为重叠
+    let mut reader = CompositeReader::new(vec![
+        open(base, "day2"),
+        open(base, "empty"),
+        open(base, "day1"),
+    ])
+    .expect("组合失败");
+    assert_eq!(reader.mode(), CompositeMode::Concatenate);
+    assert_eq!(reader.dataset_count(), 3);
+    assert_eq!(
+        reader.reader(0).map(PcapReader::dataset_name),
+        Some("day1")
+    );
+    assert_eq!(reader.dataset_name(), "day1+day2+empty");
+    assert_eq!(reader.total_packets(), 7);
+    assert_eq!(
+        read_all(&mut reader),
+        entries(&[
+            (0, "day1"),
+            (10, "day1"),
+            (20, "day1"),
+            (100, "day1"),
+            (100, "day2"),
+            (110, "day2"),
+            (120, "day2"),
+        ])
+    );
+    assert_eq!(reader.last_dataset(), Some(1));
+
+    let info =
+        reader.get_dataset_info().expect("获取信息失败");
+    assert_eq!(info.name, "day1+day2+empty");
+    assert_eq!(info.total_packets, 7);
+    assert_eq!(info.start_timestamp, Some(BASE_TIMESTAMP));
+    assert_eq!(
+        info.end_timestamp,
+        Some(BASE_TIMESTAMP + 120)
+    );
+
+    // 跳转跨越数据集边界
+    let actual = reader
+        .seek_to_timestamp(BASE_TIMESTAMP + 50)
+        .expect("跳转失败");
+    assert_eq!(actual, BASE_TIMESTAMP + 100);
+    assert_eq!(
+        read_all(&mut reader),
+        entries(&[
+            (100, "day1"),
+            (100, "day2"),
+            (110, "day2"),
+            (120, "day2"),
+        ])
+    );
+    reader
+        .seek_to_timestamp(BASE_TIMESTAMP + 105)
+        .expect("跳转失败");
+    assert_eq!(
+        reader.read_packets(10).expect("读取失败").len(),
+        2
+    );
+    let error = reader
+        .seek_to_timestamp(BASE_TIMESTAMP + 121)
+        .expect_err("超出时间线");
+    assert!(
+        matches!(error, PcapError::InvalidArgument(_)),
+        "{error}"
+    );
+
+    reader.reset().expect("重置失败");
+    assert_eq!(reader.by_ref().count(), 7);
+
+    let offsets: Vec<u64> = reader
+        .read_packets_by_time_range(
+            BASE_TIMESTAMP + 10,
+            BASE_TIMESTAMP + 110,
+        )
+        .expect("读取失败")
+        .iter()
+        .map(|packet| {
+            packet.get_timestamp_ns() - BASE_TIMESTAMP
+        })
+        .collect();
+    assert_eq!(offsets, vec![10, 20, 100, 100, 110]);
+}
+
+#[test]
+fn test_overlap_requires_merge_mode() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path();
+    write_dataset(base, "left", &[0, 20, 40]);
+    write_dataset(base, "right", &[10, 20, 30, 50]);
+
+    let error = CompositeReader::new(vec![
+        open(base, "left"),
+        open(base, "right"),
+    ])
+    .err()
+    .expect("时间范围重叠");
+    assert!(
+        matches!(error, PcapError::InvalidArgument(_)),
+        "{error}"
+    );
+    assert!(error.to_string().contains("left"), "{error}");
+
+    let mut reader = CompositeReader::with_mode(
+        vec![open(base, "right"), open(base, "left")],
+        CompositeMode::Merge,
+    )
+    .expect("组合失败");
+    assert_eq!(
+        read_all(&mut reader),
+        entries(&[
+            (0, "left"),
+            (10, "right"),
+            (20, "left"),
+            (20, "right"),
+            (30, "right"),
+            (40, "left"),
+            (50, "right"),
+        ])
+    );
+
+    let actual = reader
+        .seek_to_timestamp(BASE_TIMESTAMP + 35)
+        .expect("跳转失败");
+    assert_eq!(actual, BASE_TIMESTAMP + 40);
+    assert_eq!(
+        read_all(&mut reader),
+        entries(&[(40, "left"), (50, "right")])
+    );
+
+    let offsets: Vec<u64> = reader
+        .read_packets_by_time_range(
+            BASE_TIMESTAMP + 15,
+            BASE_TIMESTAMP + 45,
+        )
+        .expect("读取失败")
+        .iter()
+        .map(|packet| {
+            packet.get_timestamp_ns() - BASE_TIMESTAMP
+        })
+        .collect();
+    assert_eq!(offsets, vec![20, 20, 30, 40]);
+
+    let error = CompositeReader::new(Vec::new())
+        .err()
+        .expect("没有数据集");
+    assert!(
+        matches!(error, PcapError::InvalidArgument(_)),
+        "{error}"
+    );
+}
@@ -0,0 +1,273 @@
+//! 配置单位测试
+//!
+//! 测试字节大小和时间长度的解析与格式化、配置通过 serde 读写带单位的字符串，
+//! 以及配置校验错误信息中的字段名
+
+use pcapfile_io::business::units::{
+    format_duration, parse_duration,
+};
+use pcapfile_io::{ByteSize, ReaderConfig, WriterConfig};
+use serde_json::json;
+use std::time::Duration;
+
+#[test]
+fn test_byte_size_parse_and_display() {
+    let cases = [
+        ("4096", 4096),
+        ("512B", 512),
+        ("64MB", 64 * 1024 * 1024),
+        ("64 MiB", 64 * 1024 * 1024),
+        ("8k", 8 * 1024),
+        ("1.5GB", 3 * 512 * 1024 * 1024),
+        (" 2 TiB ", 2 * 1024 * 1024 * 1024 * 1024),
+    ];
+    for (text, bytes) in cases {
+        assert_eq!(
+            text.parse::<ByteSize>().expect(text),
+            ByteSize::b(bytes),
+            "{text}"
+        );
+    }
+    for text in ["", "MB", "64XB", "-1KB", "1..5MB"] {
+        assert!(
+            text.parse::<ByteSize>().is_err(),
+            "{text}"
+        );
+    }
+
+    assert_eq!(ByteSize::mib(64).to_string(), "64MiB");
+    assert_eq!(ByteSize::b(1536).to_string(), "1536B");
+    assert_eq!(ByteSize::kib(1536).to_string(), "1536KiB");
+    assert_eq!(ByteSize::ZERO.to_string(), "0B");
+}
+
+#[test]
+fn test_duration_parse_and_format() {
+    let cases = [
+        ("10s", Duration::from_secs(10)),
+        ("500ms", Duration::from_millis(500)),
+        ("250 us", Duration::from_micros(250)),
+        ("1.5h", Duration::from_secs(5400)),
+        ("2min", Duration::from_secs(120)),
+        ("100ns", Duration::from_nanos(100)),
+    ];
+    for (text, duration) in cases {
+        assert_eq!(
+            parse_duration(text).expect(text),
+            duration,
+            "{text}"
+        );
+    }
+    // 不带单位的数值无法判断单位
+    let error =
+        parse_duration("1000").expect_err("应缺少单位");
+    assert!(error.contains("缺少单位"));
+    assert!(parse_duration("10 fortnights").is_err());
+
+    assert_eq!(
+        format_duration(Duration::from_secs(10)),
+        "10s"
+    );
+    assert_eq!(
+        format_duration(Duration::from_millis(1500)),
+        "1500ms"
+    );
+    assert_eq!(
+        format_duration(Duration::from_secs(7200)),
+        "2h"
+    );
+    assert_eq!(format_duration(Duration::ZERO), "0s");
+}
+
+#[test]
+fn test_config_deserializes_units() {
+    let mut value =
+        serde_json::to_value(WriterConfig::default())
+            .expect("序列化失败");
+    assert_eq!(value["buffer_size"], json!("8KiB"));
+
+    value["buffer_size"] = json!("64KB");
+    value["min_write_block"] = json!("4k");
+    value["max_file_size"] = json!("256 MB");
+    let config: WriterConfig =
+        serde_json::from_value(value.clone())
+            .expect("反序列化失败");
+    assert_eq!(config.buffer_size, ByteSize::kib(64));
+    assert_eq!(config.min_write_block, ByteSize::kib(4));
+    assert_eq!(config.max_file_size, ByteSize::mib(256));
+    assert!(config.validate().is_ok());
+
+    // 整数按字节解析，兼容旧的字段名
+    let object = value.as_object_mut().expect("应为对象");
+    object.remove("max_file_size");
+    object.insert(
+        "max_file_size_bytes".into(),
+        json!(1_048_576),
+    );
+    let config: WriterConfig =
+        serde_json::from_value(value.clone())
+            .expect("反序列化失败");
+    assert_eq!(config.max_file_size, ByteSize::mib(1));
+
+    value["buffer_size"] = json!("64 parsecs");
+    let error =
+        serde_json::from_value::<WriterConfig>(value)
+            .expect_err("无效单位应失败");
+    assert!(error.to_string().contains("64 parsecs"));
+
+    let mut value =
+        serde_json::to_value(ReaderConfig::default())
+            .expect("序列化失败");
+    assert_eq!(value["watch_interval"], json!("1s"));
+    value["watch_interval"] = json!("250ms");
+    value["packet_cache_max_bytes"] = json!("16MB");
+    let config: ReaderConfig =
+        serde_json::from_value(value.clone())
+            .expect("反序列化失败");
+    assert_eq!(
+        config.watch_interval,
+        Duration::from_millis(250)
+    );
+    assert_eq!(
+        config.packet_cache_max_bytes,
+        ByteSize::mib(16)
+    );
+
+    value["watch_interval"] = json!(1000);
+    assert!(serde_json::from_value::<ReaderConfig>(value)
+        .is_err());
+}
+
+#[test]
+fn test_missing_fields_use_defaults() {
+    // 旧版本保存的配置缺少后来新增的字段
+    let mut value =
+        serde_json::to_value(ReaderConfig::default())
+            .expect("序列化失败");
+    let object = value.as_object_mut().expect("应为对象");
+    object.remove("auto_repair_index");
+    object.remove("packet_cache_size");
+    object.remove("packet_cache_max_bytes");
+    object.remove("max_open_files");
+    object.remove("watch_dataset");
+    object.remove("watch_interval");
+    let config: ReaderConfig =
+        serde_json::from_value(value)
+            .expect("缺少字段时应使用默认值");
+    assert!(config.auto_repair_index);
+    assert_eq!(config.packet_cache_size, 0);
+    assert_eq!(
+        config.packet_cache_max_bytes,
+        ByteSize::ZERO
+    );
+    assert_eq!(
+        config.max_open_files,
+        ReaderConfig::default().max_open_files
+    );
+    assert!(!config.watch_dataset);
+    assert_eq!(
+        config.watch_interval,
+        Duration::from_secs(1)
+    );
+
+    // 只包含最初版本字段的读取器配置
+    let config: ReaderConfig = serde_json::from_value(
+        json!({ "buffer_size": 8192, "index_cache_size": 1000 }),
+    )
+    .expect("旧版本配置应能读取");
+    assert_eq!(config.buffer_size, ByteSize::kib(8));
+    assert!(config.validate().is_ok());
+
+    // 只包含最初版本字段的写入器配置
+    let config: WriterConfig =
+        serde_json::from_value(json!({
+            "buffer_size": 8192,
+            "index_cache_size": 1000,
+            "max_packets_per_file": 1000,
+            "max_file_size_bytes": 0,
+            "file_name_format": "yyMMdd_HHmmss_fffffffff",
+            "auto_flush": true,
+        }))
+        .expect("旧版本配置应能读取");
+    assert!(config.publish_progress);
+    assert_eq!(config.index_checkpoint_interval, 0);
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_validation_errors_name_field() {
+    let config = WriterConfig {
+        buffer_size: ByteSize::b(512),
+        ..Default::default()
+    };
+    let error = config.validate().expect_err("缓冲区过小");
+    assert!(error.starts_with("buffer_size:"), "{error}");
+    assert!(error.contains("512B"), "{error}");
+
+    let config = WriterConfig {
+        min_write_block: ByteSize::b(3000),
+        ..Default::default()
+    };
+    let error =
+        config.validate().expect_err("块大小不是2的幂");
+    assert!(
+        error.starts_with("min_write_block:"),
+        "{error}"
+    );
+
+    let config = WriterConfig {
+        max_file_size: ByteSize::b(100),
+        ..Default::default()
+    };
+    let error =
+        config.validate().expect_err("文件大小过小");
+    assert!(error.starts_with("max_file_size:"), "{error}");
+
+    let config = ReaderConfig {
+        packet_cache_max_bytes: ByteSize::gib(64),
+        ..Default::default()
+    };
+    let error =
+        config.validate().expect_err("缓存上限过大");
+    assert!(
+        error.starts_with("packet_cache_max_bytes:"),
+        "{error}"
+    );
+
+    let config = ReaderConfig {
+        watch_dataset: true,
+        watch_interval: Duration::ZERO,
+        ..Default::default()
+    };
+    let error = config.validate().expect_err("扫描间隔为0");
+    assert!(
+        error.starts_with("watch_interval:"),
+        "{error}"
+    );
+
+    let config = ReaderConfig {
+        mmap_timestamp_index: true,
+        ..Default::default()
+    };
+    if cfg!(feature = "mmap-index") {
+        assert!(config.validate().is_ok());
+    } else {
+        let error = config
+            .validate()
+            .expect_err("未启用 mmap-index 特性");
+        assert!(
+            error.starts_with("mmap_timestamp_index:"),
+            "{error}"
+        );
+    }
+    let config = ReaderConfig {
+        skip_files: vec![
+            "*.bad".to_string(),
+            String::new(),
+        ],
+        ..Default::default()
+    };
+    let error =
+        config.validate().expect_err("文件名模式为空");
+    assert!(error.starts_with("skip_files:"), "{error}");
+}
@@ -0,0 +1,602 @@
+//! 核心编解码测试
+//!
+//! 测试不依赖标准库的编解码模块生成的字节流能被完整库读取，反之亦然
+
+use pcapfile_io::core::{
+    self, CodecError, FileHeader, PacketHeader,
+};
+use pcapfile_io::{
+    constants, validate_file_quick, ByteOrder, DataPacket,
+    DataPacketHeader, HeaderExtension, LinkType,
+    MemoryReader, PcapFileHeader, PcapReader, PcapWriter,
+    WriterConfig,
+};
+use std::fs;
+use tempfile::TempDir;
+
+const BASE_SECONDS: u32 = 1_700_000_000;
+
+#[test]
+fn test_core_encoded_stream_is_readable() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let dataset_path = temp_dir.path().join("embedded");
+    fs::create_dir_all(&dataset_path)
+        .expect("创建数据集目录失败");
+
+    // 模拟嵌入式设备：在固定缓冲区中编码文件头和数据包
+    let mut buffer = [0u8; 256];
+    let header = FileHeader::with_link_type(
+        0,
+        LinkType::Can.to_u32(),
+    );
+    let mut length =
+        header.encode(&mut buffer).expect("编码文件头失败");
+    for index in 0..3u8 {
+        length += core::encode_packet(
+            BASE_SECONDS,
+            index as u32 * 100,
+            &[index; 8],
+            &mut buffer[length..],
+        )
+        .expect("编码数据包失败");
+    }
+    fs::write(
+        dataset_path.join("embedded.pcap"),
+        &buffer[..length],
+    )
+    .expect("写入文件失败");
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), "embedded")
+            .expect("创建读取器失败");
+    let packets =
+        reader.read_packets(10).expect("读取数据包失败");
+    assert_eq!(packets.len(), 3);
+    for (index, packet) in packets.iter().enumerate() {
+        assert!(packet.is_valid());
+        assert_eq!(packet.link_type(), LinkType::Can);
+        assert_eq!(
+            packet.packet.data,
+            vec![index as u8; 8]
+        );
+        assert_eq!(
+            packet.get_timestamp_ns(),
+            BASE_SECONDS as u64 * 1_000_000_000
+                + index as u64 * 100
+        );
+    }
+}
+
+#[test]
+fn test_core_decodes_writer_output() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let mut writer =
+        PcapWriter::new(temp_dir.path(), "host")
+            .expect("创建写入器失败");
+    let packet = DataPacket::from_timestamp(
+        BASE_SECONDS,
+        42,
+        b"payload".to_vec(),
+    )
+    .expect("创建数据包失败");
+    writer.write_packet(&packet).expect("写入数据包失败");
+    writer.finalize().expect("完成写入失败");
+
+    let file = fs::read_dir(temp_dir.path().join("host"))
+        .expect("读取目录失败")
+        .map(|entry| entry.expect("读取目录项失败").path())
+        .find(|path| {
+            path.extension().is_some_and(|extension| {
+                extension == "pcap"
+            })
+        })
+        .expect("应存在数据文件");
+    let bytes = fs::read(file).expect("读取文件失败");
+
+    let header =
+        FileHeader::decode(&bytes).expect("解析文件头失败");
+    assert!(header.is_valid());
+    assert_eq!(
+        header.encoded_len(),
+        PcapFileHeader::from(header).header_size()
+    );
+
+    let data = &bytes[header.encoded_len()..];
+    let packet_header = PacketHeader::decode(data)
+        .expect("解析数据包头失败");
+    assert_eq!(
+        packet_header.timestamp_seconds,
+        BASE_SECONDS
+    );
+    assert_eq!(packet_header.timestamp_nanoseconds, 42);
+    assert!(packet_header
+        .matches(&data[core::PACKET_HEADER_SIZE..]));
+
+    // 缓冲区不足时返回错误而不是越界
+    assert_eq!(
+        PacketHeader::decode(&data[..4]),
+        Err(CodecError::BufferTooSmall {
+            required: core::PACKET_HEADER_SIZE,
+            available: 4,
+        })
+    );
+}
+
+#[test]
+fn test_core_accepts_writer_heartbeats() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let mut writer =
+        PcapWriter::new(temp_dir.path(), "host")
+            .expect("创建写入器失败");
+    let base_ns = BASE_SECONDS as u64 * 1_000_000_000;
+    writer
+        .write_heartbeat(base_ns)
+        .expect("写入心跳包失败");
+    writer
+        .write_payload(base_ns + 1, &[])
+        .expect("写入空数据包失败");
+    writer.finalize().expect("完成写入失败");
+
+    let file = fs::read_dir(temp_dir.path().join("host"))
+        .expect("读取目录失败")
+        .map(|entry| entry.expect("读取目录项失败").path())
+        .find(|path| {
+            path.extension().is_some_and(|extension| {
+                extension == "pcap"
+            })
+        })
+        .expect("应存在数据文件");
+    let bytes = fs::read(file).expect("读取文件失败");
+    let header =
+        FileHeader::decode(&bytes).expect("解析文件头失败");
+    let data = &bytes[header.encoded_len()..];
+
+    let heartbeat = PacketHeader::decode(data)
+        .expect("解析心跳包头失败");
+    assert!(heartbeat.is_heartbeat());
+    assert_eq!(
+        heartbeat.checksum,
+        core::HEARTBEAT_CHECKSUM
+    );
+    assert!(heartbeat.matches(&[]));
+    // 心跳包不带负载
+    assert!(!heartbeat.matches(&[0]));
+
+    let empty = PacketHeader::decode(
+        &data[core::PACKET_HEADER_SIZE..],
+    )
+    .expect("解析空数据包头失败");
+    assert!(!empty.is_heartbeat());
+    assert!(empty.matches(&[]));
+    assert_eq!(
+        DataPacketHeader::HEARTBEAT_CHECKSUM,
+        core::HEARTBEAT_CHECKSUM
+    );
+}
+
+/// 逐位计算的参考 CRC32 实现
+fn reference_crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[test]
+fn test_crc32_matches_reference() {
+    assert_eq!(core::crc32(b"123456789"), 0xCBF4_3926);
+    assert_eq!(core::crc32(&[]), 0);
+
+    // 覆盖加速实现的分块边界和未对齐的起始地址
+    let data: Vec<u8> = (0..4096u32)
+        .map(|i| {
+            (i.wrapping_mul(2_654_435_761) >> 13) as u8
+        })
+        .collect();
+    for offset in 0..8 {
+        for length in
+            (0..300).chain([511, 512, 513, 1024, 4000])
+        {
+            let slice = &data[offset..offset + length];
+            assert_eq!(
+                core::crc32(slice),
+                reference_crc32(slice),
+                "偏移 {offset}, 长度 {length}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_big_endian_files() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+
+    // 模拟大端嵌入式设备按大端序编码的文件
+    let dataset_path = temp_dir.path().join("big_endian");
+    fs::create_dir_all(&dataset_path)
+        .expect("创建数据集目录失败");
+    let mut buffer = [0u8; 32];
+    let length = FileHeader::new(0)
+        .with_byte_order(ByteOrder::Big)
+        .encode(&mut buffer)
+        .expect("编码文件头失败");
+    let mut bytes = buffer[..length].to_vec();
+    for index in 0..3u8 {
+        let payload = [index; 8];
+        let header = PacketHeader::for_payload(
+            BASE_SECONDS,
+            index as u32,
+            &payload,
+        )
+        .expect("创建数据包头失败");
+        bytes.extend_from_slice(
+            &header.to_bytes_with_order(ByteOrder::Big),
+        );
+        bytes.extend_from_slice(&payload);
+    }
+    assert_eq!(&bytes[..4], &[0xD4, 0xC3, 0xB2, 0xA1]);
+    fs::write(dataset_path.join("device.pcap"), &bytes)
+        .expect("写入文件失败");
+
+    let header =
+        FileHeader::decode(&bytes).expect("解析文件头失败");
+    assert_eq!(header.byte_order, ByteOrder::Big);
+    assert!(header.is_valid());
+    let strict = PcapFileHeader::parse_strict(&bytes)
+        .expect("严格解析文件头失败");
+    assert_eq!(strict.byte_order, ByteOrder::Big);
+    assert_eq!(strict.minor_version, 4);
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), "big_endian")
+            .expect("创建读取器失败");
+    let packets =
+        reader.read_packets(10).expect("读取数据包失败");
+    assert_eq!(packets.len(), 3);
+    for (index, packet) in packets.iter().enumerate() {
+        assert!(packet.is_valid());
+        assert_eq!(
+            packet.packet.data,
+            vec![index as u8; 8]
+        );
+        assert_eq!(
+            packet.get_timestamp_ns(),
+            BASE_SECONDS as u64 * 1_000_000_000
+                + index as u64
+        );
+    }
+
+    // 写入器按配置的字节序写出，读取时自动识别
+    let config = WriterConfig {
+        byte_order: ByteOrder::Big,
+        link_type: LinkType::Ethernet,
+        ..WriterConfig::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        "written",
+        config,
+    )
+    .expect("创建写入器失败");
+    for index in 0..5u8 {
+        let packet = DataPacket::from_timestamp(
+            BASE_SECONDS,
+            index as u32 * 10,
+            vec![index; 12],
+        )
+        .expect("创建数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let pcap_file =
+        fs::read_dir(temp_dir.path().join("written"))
+            .expect("读取数据集目录失败")
+            .filter_map(|entry| {
+                entry.ok().map(|e| e.path())
+            })
+            .find(|path| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    == Some("pcap")
+            })
+            .expect("未找到数据文件");
+    let written =
+        fs::read(&pcap_file).expect("读取文件失败");
+    assert_eq!(&written[..4], &[0xD4, 0xC3, 0xB2, 0xA1]);
+    let structure = validate_file_quick(&pcap_file)
+        .expect("结构检查失败");
+    assert_eq!(structure.header.byte_order, ByteOrder::Big);
+    assert_eq!(structure.packet_count, 5);
+
+    let mut memory = MemoryReader::from_bytes(written)
+        .expect("解析失败");
+    assert_eq!(memory.byte_order(), ByteOrder::Big);
+    let packets =
+        memory.read_packets(10).expect("读取数据包失败");
+    assert_eq!(packets.len(), 5);
+    assert!(packets.iter().all(|p| p.is_valid()));
+    assert_eq!(packets[3].packet.data, vec![3u8; 12]);
+    assert_eq!(packets[3].link_type(), LinkType::Ethernet);
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), "written")
+            .expect("创建读取器失败");
+    let packets =
+        reader.read_packets(10).expect("读取数据包失败");
+    assert_eq!(packets.len(), 5);
+    assert_eq!(
+        packets[4].get_timestamp_ns(),
+        BASE_SECONDS as u64 * 1_000_000_000 + 40
+    );
+}
+
+#[test]
+fn test_header_extension_blocks() {
+    // 核心模块在固定缓冲区中编码带扩展块的文件头
+    let creator = b"recorder-fw 1.2";
+    let mut buffer = [0u8; 64];
+    let mut header = FileHeader::with_link_type(
+        0,
+        LinkType::Can.to_u32(),
+    );
+    header.minor_version = 6;
+    header.extensions_length =
+        (core::EXTENSION_BLOCK_HEADER_SIZE * 2
+            + creator.len()
+            + 3) as u32;
+    let mut length =
+        header.encode(&mut buffer).expect("编码文件头失败");
+    assert_eq!(length, core::EXTENSIBLE_FILE_HEADER_SIZE);
+    length += core::encode_extension_block(
+        4,
+        creator,
+        ByteOrder::Little,
+        &mut buffer[length..],
+    )
+    .expect("编码扩展块失败");
+    length += core::encode_extension_block(
+        0x8001,
+        &[1, 2, 3],
+        ByteOrder::Little,
+        &mut buffer[length..],
+    )
+    .expect("编码扩展块失败");
+    assert_eq!(length, header.encoded_len());
+
+    let decoded = FileHeader::decode(&buffer[..length])
+        .expect("解析文件头失败");
+    assert!(decoded.is_valid());
+    let blocks: Vec<_> = decoded
+        .extension_blocks(&buffer[..length])
+        .collect::<Result<_, _>>()
+        .expect("遍历扩展块失败");
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].kind, 4);
+    assert_eq!(blocks[0].value, creator);
+
+    // 扩展块区域不完整时报告错误
+    assert!(decoded
+        .extension_blocks(&buffer[..length - 1])
+        .any(|block| block.is_err()));
+    assert!(matches!(
+        core::encode_extension_block(
+            1,
+            &vec![0u8; 70_000],
+            ByteOrder::Little,
+            &mut [0u8; 8],
+        ),
+        Err(CodecError::PayloadTooLarge(70_000))
+    ));
+
+    let parsed =
+        PcapFileHeader::from_bytes(&buffer[..length])
+            .expect("解析文件头失败");
+    assert_eq!(
+        parsed.extensions(),
+        &[
+            HeaderExtension::Creator(
+                "recorder-fw 1.2".to_string()
+            ),
+            HeaderExtension::Custom {
+                kind: 0x8001,
+                value: vec![1, 2, 3],
+            },
+        ]
+    );
+    assert_eq!(parsed.header_size(), length);
+    assert_eq!(
+        parsed.to_bytes(),
+        buffer[..length].to_vec()
+    );
+    assert_eq!(
+        PcapFileHeader::parse_strict(&buffer[..length])
+            .expect("严格解析失败")
+            .extensions(),
+        parsed.extensions()
+    );
+    let error =
+        PcapFileHeader::parse_strict(&buffer[..length - 1])
+            .expect_err("扩展块不完整应失败");
+    assert_eq!(error.field, "bytes");
+}
+
+#[test]
+fn test_reader_exposes_file_headers() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let config = WriterConfig {
+        max_packets_per_file: 2,
+        byte_order: ByteOrder::Big,
+        link_type: LinkType::Udp,
+        ..WriterConfig::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        "headers",
+        config,
+    )
+    .expect("创建写入器失败");
+    for index in 0..4u8 {
+        writer
+            .write_payload(
+                BASE_SECONDS as u64 * 1_000_000_000
+                    + index as u64,
+                &[index; 6],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), "headers")
+            .expect("创建读取器失败");
+    assert!(reader.current_file_header().is_none());
+    reader
+        .read_packet()
+        .expect("读取失败")
+        .expect("缺少数据包");
+    let header = reader
+        .current_file_header()
+        .expect("缺少当前文件头")
+        .clone();
+    assert_eq!(
+        header.magic_number,
+        constants::PCAP_MAGIC_NUMBER
+    );
+    assert_eq!(header.major_version, 2);
+    assert_eq!(header.minor_version, 5);
+    assert_eq!(header.byte_order, ByteOrder::Big);
+    assert_eq!(header.link_type, LinkType::Udp);
+
+    let files = reader
+        .get_file_info_list()
+        .expect("获取文件列表失败");
+    assert_eq!(files.len(), 2);
+    for info in &files {
+        let file_header =
+            info.header.as_ref().expect("缺少文件头");
+        assert_eq!(file_header.byte_order, ByteOrder::Big);
+        assert_eq!(file_header.link_type, LinkType::Udp);
+        assert_eq!(
+            file_header.format_version(),
+            header.format_version()
+        );
+    }
+}
+
+#[test]
+fn test_writer_header_extensions() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let extensions = vec![
+        HeaderExtension::Creator("pcapfile-io".to_string()),
+        HeaderExtension::Compression("none".to_string()),
+    ];
+    let config = WriterConfig {
+        byte_order: ByteOrder::Big,
+        header_extensions: extensions.clone(),
+        ..WriterConfig::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        "extended",
+        config,
+    )
+    .expect("创建写入器失败");
+    for index in 0..4u8 {
+        writer
+            .write_payload(
+                BASE_SECONDS as u64 * 1_000_000_000
+                    + index as u64,
+                &[index; 6],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), "extended")
+            .expect("创建读取器失败");
+    assert_eq!(
+        reader.header_extensions().expect("读取扩展块失败"),
+        extensions
+    );
+    let header =
+        reader.file_header(0).expect("读取文件头失败");
+    assert_eq!(header.minor_version, 6);
+    assert_eq!(
+        header.find_extension(HeaderExtension::COMPRESSION),
+        Some(&extensions[1])
+    );
+    let packets =
+        reader.read_packets(10).expect("读取数据包失败");
+    assert_eq!(packets.len(), 4);
+    assert!(packets.iter().all(|p| p.is_valid()));
+    assert_eq!(packets[2].packet.data, vec![2u8; 6]);
+
+    let pcap_file =
+        fs::read_dir(temp_dir.path().join("extended"))
+            .expect("读取数据集目录失败")
+            .filter_map(|entry| {
+                entry.ok().map(|e| e.path())
+            })
+            .find(|path| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    == Some("pcap")
+            })
+            .expect("未找到数据文件");
+    let structure = validate_file_quick(&pcap_file)
+        .expect("结构检查失败");
+    assert_eq!(
+        structure.header.extensions(),
+        &extensions[..]
+    );
+    assert_eq!(
+        structure.data_offset,
+        structure.header.header_size() as u64
+    );
+    assert_eq!(structure.packet_count, 4);
+
+    // 分块到达的字节流在扩展块完整后才解析文件头
+    let bytes = fs::read(&pcap_file).expect("读取文件失败");
+    let mut memory = MemoryReader::new();
+    memory.push_chunk(&bytes[..30]).expect("追加失败");
+    assert!(memory
+        .read_packet()
+        .expect("读取失败")
+        .is_none());
+    assert!(memory.header().is_none());
+    memory.push_chunk(&bytes[30..]).expect("追加失败");
+    memory.finish();
+    assert_eq!(
+        memory.read_packets(10).expect("读取失败").len(),
+        4
+    );
+    assert_eq!(
+        memory.header().expect("文件头未解析").extensions(),
+        &extensions[..]
+    );
+
+    assert!(WriterConfig {
+        header_extensions: vec![HeaderExtension::Custom {
+            kind: 0x8000,
+            value: vec![0u8; 70_000],
+        }],
+        ..WriterConfig::default()
+    }
+    .validate()
+    .is_err());
+}
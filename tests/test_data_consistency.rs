@@ -2,7 +2,12 @@
 //!
 //! 测试写入和读取的一致性，确保数据完整性和可靠性
 
-use pcapfile_io::{PcapReader, PcapResult, PcapWriter};
+use pcapfile_io::{
+    ByteSize, DataPacket, DataPacketHeader, HeaderAnomaly,
+    LinkType, PcapError, PcapFileHeader, PcapReader,
+    PcapResult, PcapWriter, ReaderConfig, ValidationStatus,
+    VerifyMode, WriteVerification, WriterConfig,
+};
 use std::path::Path;
 use tempfile::TempDir;
 
@@ -372,3 +377,705 @@ fn test_timestamp_consistency() {
         "✅ 时间戳一致性测试通过：{PACKET_COUNT} 个数据包时间戳完全匹配"
     );
 }
+
+#[test]
+fn test_link_type_round_trip() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+
+    // 指定链路类型时写出 2.5 版本的扩展文件头
+    let config = WriterConfig {
+        link_type: LinkType::Can,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base_path,
+        "can_dataset",
+        config,
+    )
+    .expect("创建Writer失败");
+    for i in 0..20 {
+        let packet = create_detailed_test_packet(i, 8)
+            .expect("创建数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(base_path, "can_dataset")
+            .expect("创建Reader失败");
+    let mut count = 0;
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        assert_eq!(packet.link_type(), LinkType::Can);
+        assert!(packet.is_valid());
+        count += 1;
+    }
+    assert_eq!(count, 20);
+
+    // 随机访问同样经过索引中记录的偏移
+    reader.seek_to_packet(10).expect("跳转失败");
+    let packet = reader
+        .read_packet()
+        .expect("读取数据包失败")
+        .expect("应存在数据包");
+    assert_eq!(packet.link_type(), LinkType::Can);
+
+    // 未指定链路类型时保持 2.4 版本的 16 字节文件头
+    let mut writer =
+        PcapWriter::new(base_path, "plain_dataset")
+            .expect("创建Writer失败");
+    let packet = create_detailed_test_packet(0, 8)
+        .expect("创建数据包失败");
+    writer.write_packet(&packet).expect("写入数据包失败");
+    writer.finalize().expect("完成写入失败");
+
+    let file =
+        std::fs::read_dir(base_path.join("plain_dataset"))
+            .expect("读取目录失败")
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .find(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "pcap")
+            })
+            .expect("应存在PCAP文件");
+    let bytes = std::fs::read(file).expect("读取文件失败");
+    let header = PcapFileHeader::from_bytes(&bytes)
+        .expect("解析文件头失败");
+    assert_eq!(header.minor_version, 4);
+    assert_eq!(header.header_size(), 16);
+    assert_eq!(header.link_type, LinkType::Unknown);
+    assert_eq!(
+        bytes.len(),
+        16 + packet.total_size(),
+        "默认文件头应为16字节"
+    );
+}
+
+#[test]
+fn test_write_payload_consistency() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let dataset_name = "payload_consistency";
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_123_456_789;
+
+    let mut writer =
+        PcapWriter::new(base_path, dataset_name)
+            .expect("创建Writer失败");
+    let mut buffer = vec![0u8; 256];
+    for i in 0..100u64 {
+        // 复用同一个缓冲区，模拟采集程序的写入方式
+        buffer.fill(i as u8);
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + i * 1_000,
+                &buffer,
+            )
+            .expect("写入负载失败");
+    }
+
+    // 超出文件格式时间范围的时间戳被拒绝
+    assert!(writer
+        .write_payload(u64::MAX, &buffer)
+        .is_err());
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(base_path, dataset_name)
+            .expect("创建Reader失败");
+    let mut count = 0u64;
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        assert!(packet.is_valid());
+        assert_eq!(
+            packet.get_timestamp_ns(),
+            BASE_TIMESTAMP + count * 1_000
+        );
+        assert_eq!(
+            packet.packet.data,
+            vec![count as u8; 256]
+        );
+        count += 1;
+    }
+    assert_eq!(count, 100);
+}
+
+#[test]
+fn test_packet_restamping() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let dataset_name = "restamped_dataset";
+
+    const NEW_BASE: u64 = 1_600_000_000_000_000_000;
+
+    let mut packets: Vec<DataPacket> = (0..50)
+        .map(|i| {
+            create_detailed_test_packet(i, 64)
+                .expect("创建数据包失败")
+                .with_timestamp(NEW_BASE + i as u64 * 10)
+                .expect("设置时间戳失败")
+        })
+        .collect();
+    let checksums: Vec<u32> =
+        packets.iter().map(|p| p.checksum()).collect();
+
+    // 整体平移到新的时间线，校验和不受影响
+    DataPacket::shift_timestamps(&mut packets, 5_000)
+        .expect("平移时间戳失败");
+    assert!(packets.iter().all(|p| p.is_valid()));
+
+    // 任一数据包越界时整批保持不变
+    assert!(DataPacket::shift_timestamps(
+        &mut packets,
+        i64::MIN
+    )
+    .is_err());
+    assert_eq!(
+        packets[0].get_timestamp_ns(),
+        NEW_BASE + 5_000
+    );
+
+    let mut single = packets[0].clone();
+    single
+        .set_capture_time(
+            chrono::DateTime::from_timestamp(1_000, 42)
+                .expect("构造时间失败"),
+        )
+        .expect("设置捕获时间失败");
+    assert_eq!(
+        single.get_timestamp_ns(),
+        1_000_000_000_042
+    );
+    single.shift_timestamp(-42).expect("平移时间戳失败");
+    assert_eq!(
+        single.get_timestamp_ns(),
+        1_000_000_000_000
+    );
+
+    let mut writer =
+        PcapWriter::new(base_path, dataset_name)
+            .expect("创建Writer失败");
+    writer.write_packets(&packets).expect("写入数据包失败");
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(base_path, dataset_name)
+            .expect("创建Reader失败");
+    let mut index = 0;
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        assert_eq!(
+            packet.get_timestamp_ns(),
+            NEW_BASE + 5_000 + index as u64 * 10
+        );
+        assert_eq!(packet.checksum(), checksums[index]);
+        assert!(packet.is_valid());
+        index += 1;
+    }
+    assert_eq!(index, 50);
+}
+
+#[test]
+fn test_timestamp_conversion_bounds() {
+    use chrono::{DateTime, NaiveDate};
+
+    // 早于 Unix 纪元的时间被拒绝，而不是回绕成 2106 年附近的时间
+    let pre_epoch = DateTime::from_timestamp(-1, 0)
+        .expect("构造时间失败");
+    let error =
+        DataPacket::from_datetime(pre_epoch, vec![1])
+            .expect_err("纪元前的时间应失败");
+    assert!(error.contains("早于 Unix 纪元"), "{error}");
+    let error = DataPacket::try_from_unix_ns(-1, vec![1])
+        .expect_err("负时间戳应失败");
+    assert!(error.contains("早于 Unix 纪元"), "{error}");
+
+    // 超出 32 位秒数和 i64 纳秒范围（2262 年之后）的时间返回错误
+    for year in [2107, 2300] {
+        let time = NaiveDate::from_ymd_opt(year, 1, 1)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .expect("构造时间失败")
+            .and_utc();
+        let error =
+            DataPacket::from_datetime(time, vec![1])
+                .expect_err("超出范围应失败");
+        assert!(error.contains("超出"), "{error}");
+        let mut packet =
+            DataPacket::try_from_unix_ns(0, vec![1])
+                .expect("创建数据包失败");
+        assert!(packet.set_capture_time(time).is_err());
+        assert_eq!(packet.get_timestamp_ns(), 0);
+    }
+    assert!(DataPacket::try_from_unix_ns(
+        i128::from(u64::MAX) + 1,
+        vec![1]
+    )
+    .is_err());
+    let max_ns =
+        u64::from(u32::MAX) * 1_000_000_000 + 999_999_999;
+    let packet = DataPacket::try_from_unix_ns(
+        i128::from(max_ns),
+        vec![1],
+    )
+    .expect("创建数据包失败");
+    assert_eq!(packet.get_timestamp_ns(), max_ns);
+    assert!(DataPacket::try_from_unix_ns(
+        i128::from(max_ns) + 1,
+        vec![1]
+    )
+    .is_err());
+
+    // 闰秒记录为该秒的最后一纳秒，时间戳不回退
+    let leap = NaiveDate::from_ymd_opt(2016, 12, 31)
+        .and_then(|date| {
+            date.and_hms_nano_opt(23, 59, 59, 1_500_000_000)
+        })
+        .expect("构造闰秒失败")
+        .and_utc();
+    let before = DataPacket::from_datetime(
+        leap - chrono::Duration::milliseconds(600),
+        vec![1],
+    )
+    .expect("创建数据包失败");
+    let during = DataPacket::from_datetime(leap, vec![1])
+        .expect("创建数据包失败");
+    let after = DataPacket::try_from_unix_ns(
+        1_483_228_800_000_000_000,
+        vec![1],
+    )
+    .expect("创建数据包失败");
+    assert_eq!(
+        during.get_timestamp_ns(),
+        1_483_228_799_999_999_999
+    );
+    assert!(
+        before.get_timestamp_ns()
+            <= during.get_timestamp_ns()
+    );
+    assert!(
+        during.get_timestamp_ns()
+            < after.get_timestamp_ns()
+    );
+}
+
+/// 按指定验证方式读取，返回每个数据包的（是否有效，是否验证）
+fn read_with_verify_mode(
+    base_path: &Path,
+    dataset_name: &str,
+    mode: VerifyMode,
+) -> Vec<(bool, bool)> {
+    let config = ReaderConfig {
+        verify_checksums: mode,
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        base_path,
+        dataset_name,
+        config,
+    )
+    .expect("创建Reader失败");
+    let mut results = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        results.push((
+            packet.is_valid(),
+            packet.is_checksum_verified(),
+        ));
+    }
+    results
+}
+
+#[test]
+fn test_checksum_verify_modes() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let dataset_name = "verify_modes";
+
+    let mut writer =
+        PcapWriter::new(base_path, dataset_name)
+            .expect("创建Writer失败");
+    for i in 0..8u8 {
+        writer
+            .write_payload(
+                1_700_000_000_000_000_000 + i as u64,
+                &[0xA0 + i; 32],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    // 破坏前两个数据包的负载
+    let pcap_file =
+        std::fs::read_dir(base_path.join(dataset_name))
+            .expect("读取数据集目录失败")
+            .filter_map(|entry| {
+                entry.ok().map(|e| e.path())
+            })
+            .find(|path| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    == Some("pcap")
+            })
+            .expect("未找到数据文件");
+    let mut bytes =
+        std::fs::read(&pcap_file).expect("读取文件失败");
+    for value in [0xA0u8, 0xA1] {
+        let position = bytes
+            .windows(32)
+            .position(|window| {
+                window.iter().all(|&b| b == value)
+            })
+            .expect("未找到负载");
+        bytes[position] ^= 0xFF;
+    }
+    std::fs::write(&pcap_file, &bytes)
+        .expect("写入文件失败");
+
+    let always = read_with_verify_mode(
+        base_path,
+        dataset_name,
+        VerifyMode::Always,
+    );
+    assert_eq!(always.len(), 8);
+    assert_eq!(
+        &always[..3],
+        &[(false, true), (false, true), (true, true)]
+    );
+
+    // 不验证时损坏的数据包也标记为有效，但注明未验证
+    let never = read_with_verify_mode(
+        base_path,
+        dataset_name,
+        VerifyMode::Never,
+    );
+    assert!(never.iter().all(|&r| r == (true, false)));
+
+    // 抽样验证只检测到被抽中的损坏数据包
+    let sampled = read_with_verify_mode(
+        base_path,
+        dataset_name,
+        VerifyMode::Sampled(4),
+    );
+    assert_eq!(sampled[0], (false, true));
+    assert_eq!(sampled[1], (true, false));
+    assert_eq!(sampled[4], (true, true));
+    assert_eq!(sampled.iter().filter(|r| r.1).count(), 2);
+
+    let invalid = ReaderConfig {
+        verify_checksums: VerifyMode::Sampled(0),
+        ..Default::default()
+    };
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn test_pooled_packet_reads() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let dataset_name = "pooled_reads";
+
+    let mut writer =
+        PcapWriter::new(base_path, dataset_name)
+            .expect("创建Writer失败");
+    for i in 0..50u64 {
+        writer
+            .write_payload(
+                1_700_000_000_000_000_000 + i,
+                &vec![i as u8; 200 - i as usize],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(base_path, dataset_name)
+            .expect("创建Reader失败");
+    let mut expected = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        expected.push(packet.packet.data);
+    }
+
+    // 负载逐渐变短，释放后的缓冲区可以直接复用
+    reader.reset().expect("重置失败");
+    let mut previous_buffer = None;
+    let mut count = 0;
+    while let Some(packet) =
+        reader.read_pooled_packet().expect("读取数据包失败")
+    {
+        assert!(packet.is_valid());
+        assert_eq!(packet.packet.data, expected[count]);
+        let buffer = packet.packet.data.as_ptr();
+        if let Some(previous) = previous_buffer {
+            assert_eq!(buffer, previous);
+        }
+        previous_buffer = Some(buffer);
+        count += 1;
+    }
+    assert_eq!(count, 50);
+    assert_eq!(reader.pooled_buffer_count(), 1);
+
+    // 取出的数据包不再归还缓冲区
+    reader.reset().expect("重置失败");
+    let kept = reader
+        .read_pooled_packet()
+        .expect("读取数据包失败")
+        .expect("应存在数据包")
+        .into_inner();
+    assert_eq!(kept.packet.data, expected[0]);
+    assert_eq!(reader.pooled_buffer_count(), 0);
+
+    // 缓冲区池大小为 0 时不保留缓冲区
+    let config = ReaderConfig {
+        buffer_pool_size: 0,
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        base_path,
+        dataset_name,
+        config,
+    )
+    .expect("创建Reader失败");
+    while reader
+        .read_pooled_packet()
+        .expect("读取数据包失败")
+        .is_some()
+    {}
+    assert_eq!(reader.pooled_buffer_count(), 0);
+}
+
+#[test]
+fn test_validation_status_reasons() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let dataset_name = "validation_status";
+
+    let mut writer =
+        PcapWriter::new(base_path, dataset_name)
+            .expect("创建Writer失败");
+    for i in 0..4u8 {
+        writer
+            .write_payload(
+                1_700_000_000_000_000_000 + i as u64,
+                &[0xB0 + i; 32],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let pcap_file =
+        std::fs::read_dir(base_path.join(dataset_name))
+            .expect("读取数据集目录失败")
+            .filter_map(|entry| {
+                entry.ok().map(|e| e.path())
+            })
+            .find(|path| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    == Some("pcap")
+            })
+            .expect("未找到数据文件");
+    let mut bytes =
+        std::fs::read(&pcap_file).expect("读取文件失败");
+    let payload_position = |bytes: &[u8], value: u8| {
+        bytes
+            .windows(32)
+            .position(|window| {
+                window.iter().all(|&b| b == value)
+            })
+            .expect("未找到负载")
+    };
+
+    // 破坏第一个数据包的负载
+    let first = payload_position(&bytes, 0xB0);
+    let original_checksum = u32::from_le_bytes(
+        bytes[first - 4..first].try_into().unwrap(),
+    );
+    bytes[first] ^= 0xFF;
+    let corrupted_checksum =
+        pcapfile_io::core::crc32(&bytes[first..first + 32]);
+
+    // 第二个数据包的纳秒字段超出范围
+    let second = payload_position(&bytes, 0xB1);
+    bytes[second - 12..second - 8]
+        .copy_from_slice(&1_500_000_000u32.to_le_bytes());
+
+    std::fs::write(&pcap_file, &bytes)
+        .expect("写入文件失败");
+
+    let mut reader =
+        PcapReader::new(base_path, dataset_name)
+            .expect("创建Reader失败");
+    let mut statuses = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        assert_eq!(
+            packet.is_valid(),
+            packet.status().is_valid()
+        );
+        statuses.push(packet.status().clone());
+    }
+    assert_eq!(
+        statuses,
+        vec![
+            ValidationStatus::ChecksumMismatch {
+                expected: original_checksum,
+                actual: corrupted_checksum,
+            },
+            ValidationStatus::HeaderAnomalies {
+                anomalies: vec![
+                    HeaderAnomaly::NanosecondsOutOfRange(
+                        1_500_000_000
+                    )
+                ],
+                checksum_verified: true,
+            },
+            ValidationStatus::Valid,
+            ValidationStatus::Valid,
+        ]
+    );
+
+    // 最后一个数据包只保留部分负载
+    bytes.truncate(bytes.len() - 8);
+    std::fs::write(&pcap_file, &bytes)
+        .expect("写入文件失败");
+
+    // 启用抢救后返回不完整数据包的剩余负载
+    let config = ReaderConfig {
+        salvage_truncated_packets: true,
+        verify_checksums: VerifyMode::Never,
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        base_path,
+        dataset_name,
+        config,
+    )
+    .expect("创建Reader失败");
+    let mut statuses = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        statuses.push(packet.status().clone());
+    }
+    assert_eq!(statuses.len(), 4);
+    assert_eq!(statuses[0], ValidationStatus::Unverified);
+    assert!(matches!(
+        statuses[1],
+        ValidationStatus::HeaderAnomalies {
+            checksum_verified: false,
+            ..
+        }
+    ));
+    assert_eq!(
+        statuses[3],
+        ValidationStatus::TruncatedPayload {
+            expected: 32,
+            available: 24,
+        }
+    );
+}
+
+#[test]
+fn test_verify_on_write() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+
+    // 头部校验和与负载不一致的数据包被拒绝，不写入文件
+    let config = WriterConfig {
+        verify_on_write: WriteVerification::Checksum,
+        ..WriterConfig::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base_path, "checksum", config,
+    )
+    .expect("创建Writer失败");
+    let good = DataPacket::from_timestamp(
+        1_700_000_000,
+        0,
+        vec![0x11; 64],
+    )
+    .expect("创建数据包失败");
+    writer.write_packet(&good).expect("写入数据包失败");
+
+    let header = DataPacketHeader::new(
+        1_700_000_000,
+        1,
+        64,
+        good.checksum() ^ 1,
+    )
+    .expect("创建头部失败");
+    let bad = DataPacket::new(header, vec![0x11; 64])
+        .expect("创建数据包失败");
+    assert!(matches!(
+        writer.write_packet(&bad),
+        Err(PcapError::ChecksumMismatch { .. })
+    ));
+    writer
+        .write_payload(
+            1_700_000_000_000_000_002,
+            &[0x22; 64],
+        )
+        .expect("写入负载失败");
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader = PcapReader::new(base_path, "checksum")
+        .expect("创建Reader失败");
+    let packets =
+        reader.read_packets(10).expect("读取数据包失败");
+    assert_eq!(packets.len(), 2);
+    assert!(packets.iter().all(|p| p.is_valid()));
+
+    // 读回校验与合并写入同时启用
+    let config = WriterConfig {
+        verify_on_write: WriteVerification::ReadBack,
+        min_write_block: ByteSize::kib(4),
+        ..WriterConfig::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base_path,
+        "read_back",
+        config,
+    )
+    .expect("创建Writer失败");
+    for i in 0..100u64 {
+        writer
+            .write_payload(
+                1_700_000_000_000_000_000 + i,
+                &vec![i as u8; 16 + i as usize * 4],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(base_path, "read_back")
+            .expect("创建Reader失败");
+    let packets =
+        reader.read_packets(200).expect("读取数据包失败");
+    assert_eq!(packets.len(), 100);
+    for (i, packet) in packets.iter().enumerate() {
+        assert!(packet.is_valid());
+        assert_eq!(
+            packet.packet.data,
+            vec![i as u8; 16 + i * 4]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! 数据集健康检查测试
+//!
+//! 测试健康数据集的报告、各类问题的发现和严重程度、损坏的索引，
+//! 以及报告的 JSON 序列化
+
+use pcapfile_io::{
+    DatasetDoctor, DoctorReport, FindingKind, PcapError,
+    PcapWriter, Severity, WriterConfig,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "doctor";
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+/// 写入数据集，每个文件 3 个数据包，返回按文件名排序的数据文件
+fn write_dataset(
+    base: &Path,
+    name: &str,
+    offsets: &[u64],
+) -> Vec<PathBuf> {
+    let config = WriterConfig {
+        max_packets_per_file: 3,
+        ..Default::default()
+    };
+    let mut writer =
+        PcapWriter::new_with_config(base, name, config)
+            .expect("创建写入器失败");
+    for (i, offset) in offsets.iter().enumerate() {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + offset,
+                &[i as u8; 16],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut files: Vec<PathBuf> =
+        fs::read_dir(base.join(name))
+            .expect("读取目录失败")
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "pcap")
+            })
+            .collect();
+    files.sort();
+    files
+}
+
+fn kinds(report: &DoctorReport) -> Vec<FindingKind> {
+    report
+        .findings
+        .iter()
+        .map(|finding| finding.kind)
+        .collect()
+}
+
+#[test]
+fn test_healthy_dataset() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let files = write_dataset(
+        temp_dir.path(),
+        TEST_NAME,
+        &[0, 10, 20, 30, 40, 50, 60],
+    );
+    assert_eq!(files.len(), 3);
+
+    let report =
+        DatasetDoctor::check(temp_dir.path(), TEST_NAME)
+            .expect("检查失败");
+    assert!(report.is_healthy(), "{report:?}");
+    assert!(report.findings.is_empty(), "{report:?}");
+    assert_eq!(report.max_severity(), None);
+    assert_eq!(report.files_checked, 3);
+    assert_eq!(report.packets_checked, 7);
+    assert!(report.index_path.is_some());
+
+    // 检查不修改数据集
+    fs::remove_file(
+        temp_dir.path().join(TEST_NAME).join(".pidx"),
+    )
+    .expect("删除索引失败");
+    let report =
+        DatasetDoctor::check(temp_dir.path(), TEST_NAME)
+            .expect("检查失败");
+    assert_eq!(
+        kinds(&report),
+        vec![FindingKind::IndexMissing]
+    );
+    assert_eq!(report.findings[0].severity, Severity::Info);
+    assert!(report.is_healthy());
+    assert!(!temp_dir
+        .path()
+        .join(TEST_NAME)
+        .join(".pidx")
+        .exists());
+
+    let error =
+        DatasetDoctor::check(temp_dir.path(), "absent")
+            .expect_err("数据集不存在");
+    assert!(
+        matches!(error, PcapError::DirectoryNotFound(_)),
+        "{error}"
+    );
+}
+
+#[test]
+fn test_reports_dataset_problems() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let files = write_dataset(
+        temp_dir.path(),
+        TEST_NAME,
+        &[0, 10, 20, 30, 40, 50, 60, 70, 80],
+    );
+    let dataset_path = temp_dir.path().join(TEST_NAME);
+
+    // 时间戳回退且与已有文件时间范围重叠的文件
+    let other = write_dataset(
+        temp_dir.path(),
+        "other",
+        &[15, 5, 25, 55],
+    );
+    let foreign = dataset_path.join("zz_foreign.pcap");
+    fs::copy(&other[0], &foreign).expect("复制文件失败");
+
+    // 零字节文件、缺失的文件、被改写的文件
+    let empty = dataset_path.join("zz_empty.pcap");
+    fs::write(&empty, b"").expect("创建空文件失败");
+    fs::remove_file(&files[2]).expect("删除文件失败");
+    fs::copy(&other[1], &files[1]).expect("改写文件失败");
+
+    let report =
+        DatasetDoctor::check(temp_dir.path(), TEST_NAME)
+            .expect("检查失败");
+    assert!(!report.is_healthy());
+    assert_eq!(
+        report.max_severity(),
+        Some(Severity::Error)
+    );
+    assert_eq!(report.files_checked, 4);
+
+    let paths = |kind| -> Vec<PathBuf> {
+        report
+            .findings_of(kind)
+            .filter_map(|finding| finding.path.clone())
+            .collect()
+    };
+    assert_eq!(
+        paths(FindingKind::MissingFile),
+        vec![files[2].clone()]
+    );
+    assert_eq!(
+        paths(FindingKind::EmptyFile),
+        vec![empty.clone()]
+    );
+    assert_eq!(
+        paths(FindingKind::OrphanFile),
+        vec![empty, foreign.clone()]
+    );
+    assert_eq!(
+        paths(FindingKind::IndexStale),
+        vec![files[1].clone()]
+    );
+    assert_eq!(
+        paths(FindingKind::NonMonotonicTimestamps),
+        vec![foreign.clone()]
+    );
+    // 复制进来的文件 [5, 25] 与第一个文件 [0, 20] 重叠
+    assert_eq!(
+        paths(FindingKind::TimeRangeOverlap),
+        vec![foreign]
+    );
+
+    // 按严重程度从重到轻排列
+    let severities: Vec<Severity> = report
+        .findings
+        .iter()
+        .map(|finding| finding.severity)
+        .collect();
+    let mut sorted = severities.clone();
+    sorted.sort_by(|a, b| b.cmp(a));
+    assert_eq!(severities, sorted);
+    assert_eq!(report.count(Severity::Error), 1);
+
+    let json = report.to_json().expect("序列化失败");
+    assert!(json.contains("\"MissingFile\""), "{json}");
+    let parsed: DoctorReport =
+        serde_json::from_str(&json).expect("解析失败");
+    assert_eq!(parsed, report);
+}
+
+#[test]
+fn test_reports_corrupted_index() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path(), TEST_NAME, &[0, 10, 20]);
+    let pidx_path =
+        temp_dir.path().join(TEST_NAME).join(".pidx");
+    fs::write(&pidx_path, b"<broken").expect("写入失败");
+
+    let report =
+        DatasetDoctor::check(temp_dir.path(), TEST_NAME)
+            .expect("检查失败");
+    assert_eq!(
+        kinds(&report),
+        vec![FindingKind::IndexCorrupted]
+    );
+    assert_eq!(report.findings[0].path, Some(pidx_path));
+    assert_eq!(report.index_path, None);
+    assert_eq!(report.packets_checked, 3);
+}
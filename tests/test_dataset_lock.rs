@@ -0,0 +1,80 @@
+//! 数据集锁测试
+//!
+//! 测试写入器独占锁、读取器检测活动写入器以及锁的释放
+
+use pcapfile_io::{
+    DatasetLock, PcapError, PcapErrorCode, PcapReader,
+    PcapWriter,
+};
+use tempfile::TempDir;
+
+mod common;
+use common::create_test_packet;
+
+const TEST_NAME: &str = "locked_dataset";
+
+#[test]
+fn test_second_writer_fails_fast() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+
+    let mut first =
+        PcapWriter::new(temp_dir.path(), TEST_NAME)
+            .expect("创建第一个写入器失败");
+
+    let second =
+        PcapWriter::new(temp_dir.path(), TEST_NAME);
+    match second {
+        Err(err @ PcapError::DatasetLocked(_)) => {
+            assert_eq!(
+                err.error_code(),
+                PcapErrorCode::DatasetLocked
+            );
+        }
+        Err(other) => panic!("错误类型不符: {other}"),
+        Ok(_) => panic!("第二个写入器不应获取到锁"),
+    }
+
+    // 完成写入后释放锁
+    first
+        .write_packet(
+            &create_test_packet(0, 64)
+                .expect("创建测试数据包失败"),
+        )
+        .expect("写入数据包失败");
+    first.finalize().expect("完成写入失败");
+
+    let mut third =
+        PcapWriter::new(temp_dir.path(), TEST_NAME)
+            .expect("锁释放后应能创建写入器");
+    third.finalize().expect("完成写入失败");
+}
+
+#[test]
+fn test_reader_detects_active_writer() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let dataset_path = temp_dir.path().join(TEST_NAME);
+
+    let mut writer =
+        PcapWriter::new(temp_dir.path(), TEST_NAME)
+            .expect("创建写入器失败");
+    assert!(DatasetLock::is_locked(&dataset_path));
+
+    let reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("创建读取器失败");
+    assert!(reader.has_active_writer());
+
+    writer.finalize().expect("完成写入失败");
+    assert!(!reader.has_active_writer());
+
+    // 写入器被丢弃时同样释放锁
+    {
+        let _writer =
+            PcapWriter::new(temp_dir.path(), TEST_NAME)
+                .expect("创建写入器失败");
+        assert!(reader.has_active_writer());
+    }
+    assert!(!reader.has_active_writer());
+}
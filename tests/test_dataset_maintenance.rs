@@ -0,0 +1,453 @@
+//! 数据集维护测试
+//!
+//! 验证重命名和移动数据集后索引描述、分片清单和分片路径随之改写，
+//! 数据可以在新位置正常读取，以及目标冲突、源不存在和正在写入时的错误；
+//! 删除到回收站后恢复、彻底删除和按保留期清理回收站；按时间戳截断数据集。
+
+use pcapfile_io::maintenance::{
+    DEFAULT_TRASH_RETENTION, TRASH_DIR_NAME,
+};
+use pcapfile_io::{
+    DatasetMaintenance, DeletionMode, PcapError,
+    PcapReader, PcapWriter, ShardManifest, WriterConfig,
+};
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+
+const PACKET_COUNT: u64 = 30;
+
+fn write_dataset(
+    base_path: &Path,
+    name: &str,
+    config: WriterConfig,
+) {
+    let mut writer = PcapWriter::new_with_config(
+        base_path, name, config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..PACKET_COUNT {
+        writer
+            .write_payload(
+                1_700_000_000_000_000_000 + i * 1_000,
+                &[i as u8; 24],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+fn small_files() -> WriterConfig {
+    WriterConfig {
+        max_packets_per_file: 10,
+        ..Default::default()
+    }
+}
+
+fn count_packets(base_path: &Path, name: &str) -> u64 {
+    let mut reader =
+        PcapReader::new(base_path, name).expect("打开失败");
+    let mut count = 0;
+    while let Some(packet) =
+        reader.read_packet().expect("读取失败")
+    {
+        assert!(packet.is_valid());
+        count += 1;
+    }
+    count
+}
+
+#[test]
+fn test_rename_rewrites_index_description() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path();
+    write_dataset(base, "capture", small_files());
+
+    let report = DatasetMaintenance::rename(
+        base, "capture", "renamed",
+    )
+    .expect("重命名失败");
+    assert_eq!(report.old_path, base.join("capture"));
+    assert_eq!(report.new_path, base.join("renamed"));
+    assert!(report.index_rewritten);
+    assert!(!report.index_rebuilt);
+    assert!(!report.copied);
+    assert!(!base.join("capture").exists());
+
+    let mut reader =
+        PcapReader::new(base, "renamed").expect("打开失败");
+    reader.initialize().expect("初始化失败");
+    let index =
+        reader.index().get_index().expect("索引未加载");
+    assert_eq!(index.description(), "数据集: renamed");
+    assert_eq!(index.files().len(), 3);
+    drop(reader);
+    assert_eq!(
+        count_packets(base, "renamed"),
+        PACKET_COUNT
+    );
+}
+
+#[test]
+fn test_rename_moves_shard_directories() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path().join("primary");
+    let shard_a = temp_dir.path().join("disk_a");
+    let shard_b = temp_dir.path().join("disk_b");
+    let config = WriterConfig {
+        storage_shards: vec![
+            shard_a.clone(),
+            shard_b.clone(),
+        ],
+        ..small_files()
+    };
+    write_dataset(&base, "sharded", config);
+
+    let report = DatasetMaintenance::rename(
+        &base, "sharded", "archived",
+    )
+    .expect("重命名失败");
+    assert_eq!(report.shards_moved, 2);
+    assert!(report.index_rewritten);
+    assert!(!shard_a.join("sharded").exists());
+    assert!(shard_a.join("archived").is_dir());
+    assert!(shard_b.join("archived").is_dir());
+
+    let manifest =
+        ShardManifest::load(base.join("archived"));
+    assert_eq!(
+        manifest.directories,
+        vec![
+            shard_a.join("archived"),
+            shard_b.join("archived")
+        ]
+    );
+
+    let mut reader = PcapReader::new(&base, "archived")
+        .expect("打开失败");
+    reader.initialize().expect("初始化失败");
+    let index =
+        reader.index().get_index().expect("索引未加载");
+    assert!(index.files().iter().all(|f| {
+        f.shard.as_ref().is_some_and(|shard| {
+            shard.file_name() == Some("archived".as_ref())
+        })
+    }));
+    drop(reader);
+    assert_eq!(
+        count_packets(&base, "archived"),
+        PACKET_COUNT
+    );
+}
+
+#[test]
+fn test_move_to_new_base() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path().join("old_base");
+    let new_base = temp_dir.path().join("nested/new_base");
+    write_dataset(&base, "capture", small_files());
+
+    let report = DatasetMaintenance::move_to(
+        base.join("capture"),
+        &new_base,
+    )
+    .expect("移动失败");
+    assert_eq!(report.new_path, new_base.join("capture"));
+    assert_eq!(report.shards_moved, 0);
+    assert!(!base.join("capture").exists());
+    assert_eq!(
+        count_packets(&new_base, "capture"),
+        PACKET_COUNT
+    );
+}
+
+#[test]
+fn test_relocation_errors() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path();
+    write_dataset(base, "first", small_files());
+    write_dataset(base, "second", small_files());
+
+    // 目标已存在
+    assert!(matches!(
+        DatasetMaintenance::rename(base, "first", "second"),
+        Err(PcapError::InvalidArgument(_))
+    ));
+    // 源不存在
+    assert!(matches!(
+        DatasetMaintenance::rename(
+            base, "missing", "other"
+        ),
+        Err(PcapError::DirectoryNotFound(_))
+    ));
+    // 名称不合法
+    assert!(matches!(
+        DatasetMaintenance::rename(base, "first", "../up"),
+        Err(PcapError::InvalidDatasetName(_))
+    ));
+    assert!(base.join("first").is_dir());
+    assert!(base.join("second").is_dir());
+
+    // 正在写入的数据集不能移动
+    let mut writer = PcapWriter::new(base, "live")
+        .expect("创建写入器失败");
+    writer
+        .write_payload(1, &[0u8; 8])
+        .expect("写入负载失败");
+    assert!(matches!(
+        DatasetMaintenance::rename(base, "live", "moved"),
+        Err(PcapError::DatasetLocked(_))
+    ));
+    writer.finalize().expect("完成写入失败");
+    assert!(!base.join("moved").exists());
+}
+
+#[test]
+fn test_delete_to_trash_and_restore() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path().join("primary");
+    let shard = temp_dir.path().join("disk_a");
+    let config = WriterConfig {
+        storage_shards: vec![shard.clone()],
+        ..small_files()
+    };
+    write_dataset(&base, "capture", config);
+
+    let report = DatasetMaintenance::delete(
+        base.join("capture"),
+        DeletionMode::Trash,
+    )
+    .expect("删除失败");
+    assert_eq!(report.mode, DeletionMode::Trash);
+    assert_eq!(report.shards, 1);
+    assert!(!base.join("capture").exists());
+    assert!(!shard.join("capture").exists());
+    assert!(shard.join(TRASH_DIR_NAME).is_dir());
+
+    let entry = report.trash_entry.expect("缺少回收站条目");
+    assert_eq!(entry.name, "capture");
+    assert!(entry
+        .path
+        .starts_with(base.join(TRASH_DIR_NAME)));
+    assert_eq!(
+        DatasetMaintenance::list_trash(&base)
+            .expect("列出回收站失败"),
+        vec![entry.clone()]
+    );
+
+    DatasetMaintenance::restore(&entry).expect("恢复失败");
+    assert!(shard.join("capture").is_dir());
+    assert!(!base.join(TRASH_DIR_NAME).exists());
+    assert!(!shard.join(TRASH_DIR_NAME).exists());
+    assert_eq!(
+        ShardManifest::load(base.join("capture"))
+            .directories,
+        vec![shard.join("capture")]
+    );
+    assert_eq!(
+        count_packets(&base, "capture"),
+        PACKET_COUNT
+    );
+}
+
+#[test]
+fn test_restore_conflicts_with_new_dataset() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path();
+    write_dataset(base, "capture", small_files());
+    let entry = DatasetMaintenance::delete(
+        base.join("capture"),
+        DeletionMode::Trash,
+    )
+    .expect("删除失败")
+    .trash_entry
+    .expect("缺少回收站条目");
+
+    // 同名数据集重新写入后不能覆盖恢复
+    write_dataset(base, "capture", small_files());
+    assert!(matches!(
+        DatasetMaintenance::restore(&entry),
+        Err(PcapError::InvalidArgument(_))
+    ));
+    assert!(entry.path.is_dir());
+}
+
+#[test]
+fn test_permanent_delete_shreds_files() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path().join("primary");
+    let shard_a = temp_dir.path().join("disk_a");
+    let shard_b = temp_dir.path().join("disk_b");
+    let config = WriterConfig {
+        storage_shards: vec![
+            shard_a.clone(),
+            shard_b.clone(),
+        ],
+        ..small_files()
+    };
+    write_dataset(&base, "capture", config);
+
+    let report = DatasetMaintenance::delete(
+        base.join("capture"),
+        DeletionMode::Permanent,
+    )
+    .expect("删除失败");
+    assert_eq!(report.shards, 2);
+    assert!(report.trash_entry.is_none());
+    // 3 个数据文件、索引、锁文件和分片清单
+    assert!(report.files_shredded >= 4);
+    assert!(report.bytes_shredded > 0);
+    assert!(!base.join("capture").exists());
+    assert!(!shard_a.join("capture").exists());
+    assert!(!shard_b.join("capture").exists());
+    assert!(shard_a.is_dir());
+    assert!(DatasetMaintenance::list_trash(&base)
+        .expect("列出回收站失败")
+        .is_empty());
+}
+
+#[test]
+fn test_purge_trash_respects_retention() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path();
+    write_dataset(base, "first", small_files());
+    write_dataset(base, "second", small_files());
+    for name in ["first", "second"] {
+        DatasetMaintenance::delete(
+            base.join(name),
+            DeletionMode::Trash,
+        )
+        .expect("删除失败");
+    }
+
+    let purged = DatasetMaintenance::purge_trash(
+        base,
+        DEFAULT_TRASH_RETENTION,
+    )
+    .expect("清理回收站失败");
+    assert!(purged.is_empty());
+    assert_eq!(
+        DatasetMaintenance::list_trash(base)
+            .expect("列出回收站失败")
+            .len(),
+        2
+    );
+
+    let purged = DatasetMaintenance::purge_trash(
+        base,
+        Duration::ZERO,
+    )
+    .expect("清理回收站失败");
+    assert_eq!(purged.len(), 2);
+    assert!(!base.join(TRASH_DIR_NAME).exists());
+}
+
+#[test]
+fn test_delete_locked_dataset_fails() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path();
+    let mut writer = PcapWriter::new(base, "live")
+        .expect("创建写入器失败");
+    writer
+        .write_payload(1, &[0u8; 8])
+        .expect("写入负载失败");
+
+    for mode in
+        [DeletionMode::Trash, DeletionMode::Permanent]
+    {
+        assert!(matches!(
+            DatasetMaintenance::delete(
+                base.join("live"),
+                mode
+            ),
+            Err(PcapError::DatasetLocked(_))
+        ));
+    }
+    writer.finalize().expect("完成写入失败");
+    assert!(base.join("live").is_dir());
+}
+
+#[test]
+fn test_truncate_after_timestamp() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path();
+    write_dataset(base, "capture", small_files());
+    let cutoff = 1_700_000_000_000_000_000 + 14 * 1_000;
+
+    // 第一个文件保留，第二个文件截断，第三个文件删除
+    let report = DatasetMaintenance::truncate_after(
+        base.join("capture"),
+        cutoff,
+    )
+    .expect("截断失败");
+    assert_eq!(report.cutoff_ns, cutoff);
+    assert_eq!(report.files_removed, 1);
+    assert_eq!(report.files_truncated, 1);
+    assert_eq!(report.packets_removed, 15);
+    assert_eq!(report.bytes_removed, 15 * (16 + 24));
+    assert!(!report.is_unchanged());
+
+    let mut reader =
+        PcapReader::new(base, "capture").expect("打开失败");
+    reader.initialize().expect("初始化失败");
+    assert!(reader.index().rebuild_reason().is_none());
+    let index = reader.index().get_index().unwrap();
+    assert_eq!(index.file_count(), 2);
+    assert_eq!(index.total_packets(), 15);
+    assert_eq!(index.end_timestamp(), cutoff);
+    let mut last = 0;
+    while let Some(packet) =
+        reader.read_packet().expect("读取失败")
+    {
+        assert!(packet.is_valid());
+        last = packet.get_timestamp_ns();
+    }
+    assert_eq!(last, cutoff);
+
+    // 截断时间戳之后没有数据时不修改数据集
+    let report = DatasetMaintenance::truncate_after(
+        base.join("capture"),
+        cutoff,
+    )
+    .expect("截断失败");
+    assert!(report.is_unchanged());
+    assert_eq!(count_packets(base, "capture"), 15);
+}
+
+#[test]
+fn test_truncate_locked_dataset_fails() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path();
+    let mut writer = PcapWriter::new(base, "live")
+        .expect("创建写入器失败");
+    writer
+        .write_payload(1, &[0u8; 8])
+        .expect("写入负载失败");
+
+    assert!(matches!(
+        DatasetMaintenance::truncate_after(
+            base.join("live"),
+            0
+        ),
+        Err(PcapError::DatasetLocked(_))
+    ));
+    writer.finalize().expect("完成写入失败");
+    assert!(matches!(
+        DatasetMaintenance::truncate_after(
+            base.join("missing"),
+            0
+        ),
+        Err(PcapError::DirectoryNotFound(_))
+    ));
+}
@@ -0,0 +1,102 @@
+//! 数据集路径处理测试
+//!
+//! 测试数据集名称校验、路径规范化和扩展长度路径转换
+
+use pcapfile_io::foundation::paths::{
+    extended_length_path_string, normalize_path,
+};
+use pcapfile_io::{
+    validate_dataset_name, PcapError, PcapErrorCode,
+    PcapReader, PcapWriter,
+};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+#[test]
+fn test_validate_dataset_name() {
+    for name in
+        ["capture_2024", "数据集-01", "a.b", ".hidden"]
+    {
+        assert!(
+            validate_dataset_name(name).is_ok(),
+            "名称应合法: {name}"
+        );
+    }
+
+    for name in [
+        "", ".", "..", "a/b", "a\\b", "a:b", "a*b", "a?b",
+        "name.", "name ", "CON", "nul.txt", "com1", "a\0b",
+    ] {
+        let err = validate_dataset_name(name)
+            .expect_err(&format!("名称应非法: {name:?}"));
+        assert!(matches!(
+            err,
+            PcapError::InvalidDatasetName(_)
+        ));
+        assert_eq!(
+            err.error_code(),
+            PcapErrorCode::InvalidDatasetName
+        );
+    }
+
+    let too_long = "a".repeat(256);
+    assert!(validate_dataset_name(&too_long).is_err());
+}
+
+#[test]
+fn test_invalid_dataset_name_rejected_by_api() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+
+    let result =
+        PcapWriter::new(temp_dir.path(), "../escape");
+    assert!(matches!(
+        result,
+        Err(PcapError::InvalidDatasetName(_))
+    ));
+    assert!(!temp_dir.path().join("../escape").exists());
+
+    let result =
+        PcapReader::new(temp_dir.path(), "bad|name");
+    assert!(matches!(
+        result,
+        Err(PcapError::InvalidDatasetName(_))
+    ));
+}
+
+#[test]
+fn test_normalize_path() {
+    assert_eq!(
+        normalize_path("data/./sets/../capture"),
+        PathBuf::from("data/capture")
+    );
+    assert_eq!(
+        normalize_path("../data/./x/.."),
+        PathBuf::from("../data")
+    );
+    assert_eq!(normalize_path("./"), PathBuf::from("."));
+    assert_eq!(
+        normalize_path("/../data"),
+        PathBuf::from("/data")
+    );
+}
+
+#[test]
+fn test_extended_length_path_string() {
+    assert_eq!(
+        extended_length_path_string(r"C:\data\set"),
+        r"\\?\C:\data\set"
+    );
+    assert_eq!(
+        extended_length_path_string("C:/data/set"),
+        r"\\?\C:\data\set"
+    );
+    assert_eq!(
+        extended_length_path_string(r"\\server\share\set"),
+        r"\\?\UNC\server\share\set"
+    );
+    assert_eq!(
+        extended_length_path_string(r"\\?\C:\data"),
+        r"\\?\C:\data"
+    );
+}
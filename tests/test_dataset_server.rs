@@ -0,0 +1,168 @@
+//! 数据集服务测试
+//!
+//! 启动 HTTP 服务后通过原始 TCP 请求测试数据集列表、时间范围查询和数据包流。
+//! 需要启用 `server` 特性：`cargo test --features server --test test_dataset_server`
+
+#![cfg(feature = "server")]
+
+use pcapfile_io::server::{DatasetServer, PacketRecord};
+use pcapfile_io::{
+    DataPacket, DatasetInfo, ErrorDetail, MemoryReader,
+    PcapErrorCode, PcapWriter,
+};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use tempfile::TempDir;
+
+const BASE_SECONDS: u32 = 1_700_000_000;
+
+fn ts(nanosecond: u32) -> u64 {
+    BASE_SECONDS as u64 * 1_000_000_000 + nanosecond as u64
+}
+
+/// 发送 GET 请求，返回状态码和响应体
+fn get(addr: SocketAddr, path: &str) -> (u16, Vec<u8>) {
+    let mut stream =
+        TcpStream::connect(addr).expect("连接服务失败");
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n"
+    )
+    .expect("发送请求失败");
+    let mut response = Vec::new();
+    stream
+        .read_to_end(&mut response)
+        .expect("读取响应失败");
+
+    let split = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .expect("响应缺少头部结束标记");
+    let head = String::from_utf8_lossy(&response[..split]);
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .expect("无法解析状态码");
+    let body = &response[split + 4..];
+    let body = if head
+        .to_ascii_lowercase()
+        .contains("transfer-encoding: chunked")
+    {
+        decode_chunked(body)
+    } else {
+        body.to_vec()
+    };
+    (status, body)
+}
+
+/// 解码分块传输编码的响应体
+fn decode_chunked(mut body: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .expect("分块长度行不完整");
+        let size = usize::from_str_radix(
+            std::str::from_utf8(&body[..line_end])
+                .expect("分块长度不是文本")
+                .trim(),
+            16,
+        )
+        .expect("无法解析分块长度");
+        if size == 0 {
+            return decoded;
+        }
+        let start = line_end + 2;
+        decoded
+            .extend_from_slice(&body[start..start + size]);
+        body = &body[start + size + 2..];
+    }
+}
+
+#[test]
+fn test_dataset_server_endpoints() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let mut writer = PcapWriter::new(base_path, "sensor")
+        .expect("创建写入器失败");
+    for nanosecond in 0..10u32 {
+        let packet = DataPacket::from_timestamp(
+            BASE_SECONDS,
+            nanosecond * 10,
+            vec![nanosecond as u8; 4],
+        )
+        .expect("创建数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let server = DatasetServer::new(base_path)
+        .with_max_query_packets(3)
+        .spawn("127.0.0.1:0")
+        .expect("启动服务失败");
+    let addr = server.local_addr();
+
+    let (status, body) = get(addr, "/datasets");
+    assert_eq!(status, 200);
+    let names: Vec<String> = serde_json::from_slice(&body)
+        .expect("解析列表失败");
+    assert_eq!(names, vec!["sensor".to_string()]);
+
+    let (status, body) = get(addr, "/datasets/sensor");
+    assert_eq!(status, 200);
+    let info: DatasetInfo = serde_json::from_slice(&body)
+        .expect("解析信息失败");
+    assert_eq!(info.total_packets, 10);
+
+    // 查询结果受服务上限限制
+    let path = format!(
+        "/datasets/sensor/packets?start_ns={}&end_ns={}",
+        ts(20),
+        ts(70)
+    );
+    let (status, body) = get(addr, &path);
+    assert_eq!(status, 200);
+    let records: Vec<PacketRecord> =
+        serde_json::from_slice(&body)
+            .expect("解析数据包失败");
+    let timestamps: Vec<u64> = records
+        .iter()
+        .map(|record| record.timestamp_ns)
+        .collect();
+    assert_eq!(timestamps, vec![ts(20), ts(30), ts(40)]);
+    assert!(records.iter().all(|record| record.is_valid));
+
+    // 数据包流可以直接用内存读取器解析
+    let path = format!(
+        "/datasets/sensor/stream?start_ns={}",
+        ts(50)
+    );
+    let (status, body) = get(addr, &path);
+    assert_eq!(status, 200);
+    let mut reader = MemoryReader::from_bytes(body)
+        .expect("解析数据包流失败");
+    let packets =
+        reader.read_packets(100).expect("读取数据包失败");
+    assert_eq!(packets.len(), 5);
+    assert_eq!(packets[0].get_timestamp_ns(), ts(50));
+    assert_eq!(packets[0].packet.data, vec![5u8; 4]);
+
+    let (status, body) = get(addr, "/datasets/missing");
+    assert_eq!(status, 404);
+    let error: ErrorDetail = serde_json::from_slice(&body)
+        .expect("解析错误响应失败");
+    assert_eq!(
+        error.error_code(),
+        Some(PcapErrorCode::DirectoryNotFound)
+    );
+    assert!(error.file.is_some());
+    let (status, _) = get(addr, "/datasets/..");
+    assert_ne!(status, 200);
+
+    server.shutdown().expect("关闭服务失败");
+}
@@ -0,0 +1,219 @@
+//! 数据集子集测试
+//!
+//! 测试按时间窗口和文件筛选条件打开的受限视图中全局序号、数据包总数和读取进度
+//! 只反映所选部分，以及磁盘索引不受影响和无效子集条件的处理
+
+use pcapfile_io::{
+    DatasetSubset, FileFilter, PcapError, PcapReader,
+    PcapWriter, TimeWindow, WriterConfig,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "subset";
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+/// 写入 20 个数据包，每个文件 5 个，时间间隔 10 纳秒，返回按文件名排序的数据文件
+fn write_dataset(base: &Path) -> Vec<PathBuf> {
+    let config = WriterConfig {
+        max_packets_per_file: 5,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base, TEST_NAME, config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..20u8 {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + u64::from(i) * 10,
+                &[i; 8],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut files: Vec<PathBuf> =
+        fs::read_dir(base.join(TEST_NAME))
+            .expect("读取目录失败")
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "pcap")
+            })
+            .collect();
+    files.sort();
+    files
+}
+
+fn read_markers(reader: &mut PcapReader) -> Vec<u8> {
+    let mut markers = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取失败")
+    {
+        markers.push(packet.packet.data[0]);
+    }
+    markers
+}
+
+#[test]
+fn test_time_window_subset() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path());
+
+    let window = TimeWindow::new(
+        BASE_TIMESTAMP + 45,
+        BASE_TIMESTAMP + 120,
+    );
+    let mut reader = PcapReader::open_subset(
+        temp_dir.path(),
+        TEST_NAME,
+        window,
+    )
+    .expect("打开失败");
+    assert_eq!(reader.subset(), Some(&window.into()));
+    assert_eq!(
+        read_markers(&mut reader),
+        (5..=12).collect::<Vec<u8>>()
+    );
+    assert_eq!(reader.total_packets(), Some(8));
+    assert_eq!(reader.progress(), Some(1.0));
+
+    let info =
+        reader.get_dataset_info().expect("获取信息失败");
+    assert_eq!(info.file_count, 2);
+    assert_eq!(info.total_packets, 8);
+    assert_eq!(
+        info.start_timestamp,
+        Some(BASE_TIMESTAMP + 50)
+    );
+    assert_eq!(
+        info.end_timestamp,
+        Some(BASE_TIMESTAMP + 120)
+    );
+
+    // 全局序号从子集的第一个数据包开始编号
+    let pointer = reader
+        .seek_by_timestamp(BASE_TIMESTAMP + 70)
+        .expect("定位失败")
+        .expect("未找到数据包");
+    assert_eq!(pointer.entry.global_index, 2);
+    // 子集外的时间戳定位到子集边界
+    let pointer = reader
+        .seek_by_timestamp(BASE_TIMESTAMP + 190)
+        .expect("定位失败")
+        .expect("未找到数据包");
+    assert_eq!(pointer.entry.global_index, 7);
+    assert!(reader
+        .read_packet_by_timestamp(BASE_TIMESTAMP + 130)
+        .expect("读取失败")
+        .is_none());
+    reader.seek_to_packet(4).expect("定位失败");
+    assert_eq!(reader.progress(), Some(0.5));
+    assert_eq!(
+        read_markers(&mut reader),
+        vec![9, 10, 11, 12]
+    );
+    reader.reset().expect("重置失败");
+    let packet = reader
+        .read_packet()
+        .expect("读取失败")
+        .expect("没有数据包");
+    assert_eq!(packet.packet.data[0], 5);
+
+    // 磁盘索引仍包含全部数据文件
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    assert_eq!(read_markers(&mut reader).len(), 20);
+    assert_eq!(reader.total_packets(), Some(20));
+    assert!(reader.subset().is_none());
+
+    // 窗口内没有数据包时子集为空
+    let mut reader = PcapReader::open_subset(
+        temp_dir.path(),
+        TEST_NAME,
+        TimeWindow::new(BASE_TIMESTAMP + 191, u64::MAX),
+    )
+    .expect("打开失败");
+    assert!(read_markers(&mut reader).is_empty());
+    assert_eq!(reader.total_packets(), Some(0));
+}
+
+#[test]
+fn test_file_filter_subset() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let files = write_dataset(temp_dir.path());
+    assert_eq!(files.len(), 4);
+    let name = |i: usize| {
+        files[i]
+            .file_name()
+            .expect("缺少文件名")
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    let mut reader = PcapReader::open_subset(
+        temp_dir.path(),
+        TEST_NAME,
+        FileFilter::new([name(1), name(3)]),
+    )
+    .expect("打开失败");
+    assert_eq!(reader.total_packets(), None);
+    assert_eq!(
+        read_markers(&mut reader),
+        vec![5, 6, 7, 8, 9, 15, 16, 17, 18, 19]
+    );
+    assert_eq!(reader.total_packets(), Some(10));
+    let index =
+        reader.index().get_index().expect("索引未加载");
+    assert_eq!(index.file_count(), 2);
+    assert_eq!(index.files()[1].file_name, name(3));
+
+    reader.seek_to_packet(5).expect("定位失败");
+    let packet = reader
+        .read_packet()
+        .expect("读取失败")
+        .expect("没有数据包");
+    assert_eq!(packet.packet.data[0], 15);
+
+    // 通配符选中全部文件
+    let mut reader = PcapReader::open_subset(
+        temp_dir.path(),
+        TEST_NAME,
+        FileFilter::new(["*.pcap"]),
+    )
+    .expect("打开失败");
+    assert_eq!(read_markers(&mut reader).len(), 20);
+}
+
+#[test]
+fn test_rejects_invalid_subset() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path());
+
+    let subsets: [DatasetSubset; 3] = [
+        TimeWindow::new(BASE_TIMESTAMP + 1, BASE_TIMESTAMP)
+            .into(),
+        FileFilter::new(Vec::<String>::new()).into(),
+        FileFilter::new(["*.pcap", ""]).into(),
+    ];
+    for subset in subsets {
+        let error = PcapReader::open_subset(
+            temp_dir.path(),
+            TEST_NAME,
+            subset.clone(),
+        )
+        .err()
+        .expect("应拒绝无效的子集");
+        assert!(
+            matches!(error, PcapError::InvalidArgument(_)),
+            "{subset:?}: {error}"
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! 数据集变换测试
+//!
+//! 测试时间平移工具重写数据包时间戳和索引，且不修改源数据集，写入速率限制，
+//! 以及负载脱敏规则和脱敏变换
+
+use pcapfile_io::{
+    DatasetTransformer, LinkType, PcapError, PcapReader,
+    PcapWriter, RateLimiter, ScrubRule, Scrubber,
+    TimeCorrection, WriterConfig,
+};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+mod common;
+use common::create_test_packet;
+
+const PACKET_COUNT: u32 = 60;
+
+fn write_source(base_path: &Path, name: &str) -> Vec<u64> {
+    let config = WriterConfig {
+        max_packets_per_file: 25,
+        link_type: LinkType::Udp,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base_path, name, config,
+    )
+    .expect("创建写入器失败");
+    let mut timestamps = Vec::new();
+    for i in 0..PACKET_COUNT {
+        let packet = create_test_packet(i, 48)
+            .expect("创建测试数据包失败");
+        timestamps.push(packet.get_timestamp_ns());
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+    timestamps
+}
+
+fn read_timestamps(
+    base_path: &Path,
+    name: &str,
+) -> Vec<u64> {
+    let mut reader = PcapReader::new(base_path, name)
+        .expect("创建读取器失败");
+    let mut timestamps = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        assert!(packet.is_valid());
+        timestamps.push(packet.get_timestamp_ns());
+    }
+    timestamps
+}
+
+#[test]
+fn test_shift_time_rewrites_timestamps_and_index() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let original = write_source(base_path, "source");
+
+    const DELTA: i64 = -3_600_000_000_000;
+    let summary = DatasetTransformer::new()
+        .shift_time(
+            base_path.join("source"),
+            base_path.join("shifted"),
+            DELTA,
+        )
+        .expect("时间平移失败");
+    assert_eq!(summary.packets, PACKET_COUNT as u64);
+    assert_eq!(summary.invalid_packets, 0);
+    assert!(summary.files >= 1);
+
+    let expected: Vec<u64> = original
+        .iter()
+        .map(|ts| ts.checked_add_signed(DELTA).unwrap())
+        .collect();
+    assert_eq!(
+        read_timestamps(base_path, "shifted"),
+        expected
+    );
+    assert_eq!(
+        read_timestamps(base_path, "source"),
+        original
+    );
+
+    // 索引按新的时间戳生成，链路类型沿用源数据集
+    let mut reader = PcapReader::new(base_path, "shifted")
+        .expect("创建读取器失败");
+    let info = reader
+        .get_dataset_info()
+        .expect("获取数据集信息失败");
+    assert_eq!(
+        info.start_timestamp,
+        expected.first().copied()
+    );
+    assert_eq!(
+        info.end_timestamp,
+        expected.last().copied()
+    );
+    reader
+        .seek_to_timestamp(expected[30])
+        .expect("按时间戳定位失败");
+    let packet = reader
+        .read_packet()
+        .expect("读取数据包失败")
+        .expect("应存在数据包");
+    assert_eq!(packet.get_timestamp_ns(), expected[30]);
+    assert_eq!(packet.link_type(), LinkType::Udp);
+}
+
+#[test]
+fn test_shift_time_rejects_invalid_targets() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    write_source(base_path, "source");
+    let transformer = DatasetTransformer::new();
+
+    let result = transformer.shift_time(
+        base_path.join("source"),
+        base_path.join("source"),
+        1,
+    );
+    assert!(matches!(
+        result,
+        Err(PcapError::InvalidArgument(_))
+    ));
+
+    transformer
+        .shift_time(
+            base_path.join("source"),
+            base_path.join("target"),
+            1,
+        )
+        .expect("时间平移失败");
+    let result = transformer.shift_time(
+        base_path.join("source"),
+        base_path.join("target"),
+        1,
+    );
+    assert!(matches!(
+        result,
+        Err(PcapError::InvalidArgument(_))
+    ));
+
+    // 平移后超出时间范围
+    let result = transformer.shift_time(
+        base_path.join("source"),
+        base_path.join("overflow"),
+        i64::MIN,
+    );
+    assert!(matches!(
+        result,
+        Err(PcapError::InvalidArgument(_))
+    ));
+}
+
+#[test]
+fn test_time_correction_interpolation() {
+    let correction = TimeCorrection::new(&[
+        (2_000, 2_100),
+        (1_000, 1_000),
+    ])
+    .expect("创建时间校正失败");
+    assert_eq!(correction.points()[0], (1_000, 1_000));
+
+    // 参考点之间线性插值，两端外推
+    assert_eq!(correction.apply(1_000), Some(1_000));
+    assert_eq!(correction.apply(1_500), Some(1_550));
+    assert_eq!(correction.apply(2_000), Some(2_100));
+    assert_eq!(correction.apply(3_000), Some(3_200));
+    assert_eq!(correction.apply(500), Some(450));
+
+    assert!(TimeCorrection::new(&[]).is_err());
+    assert!(TimeCorrection::new(&[(1, 5), (1, 6)]).is_err());
+    assert!(TimeCorrection::new(&[(1, 5), (2, 4)]).is_err());
+}
+
+#[test]
+fn test_rescale_time_corrects_drift() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let original = write_source(base_path, "source");
+
+    // 本地时钟每记录 1 纳秒实际经过 2 纳秒，整体向后校正 1 秒
+    let first = original[0];
+    let last = *original.last().unwrap();
+    let offset = 1_000_000_000;
+    let reference_points = [
+        (first, first + offset),
+        (last, first + offset + (last - first) * 2),
+    ];
+
+    let summary = DatasetTransformer::new()
+        .rescale_time(
+            base_path.join("source"),
+            base_path.join("corrected"),
+            &reference_points,
+        )
+        .expect("时钟校正失败");
+    assert_eq!(summary.packets, PACKET_COUNT as u64);
+
+    let expected: Vec<u64> = original
+        .iter()
+        .map(|ts| first + offset + (ts - first) * 2)
+        .collect();
+    assert_eq!(
+        read_timestamps(base_path, "corrected"),
+        expected
+    );
+}
+
+#[test]
+fn test_transform_rate_limit() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let original = write_source(base_path, "source");
+
+    // 每秒 1000 个数据包，60 个数据包至少需要 59ms
+    let transformer =
+        DatasetTransformer::new().with_rate_limiter(
+            RateLimiter::new(Some(1000), None),
+        );
+    let started = Instant::now();
+    let summary = transformer
+        .shift_time(
+            base_path.join("source"),
+            base_path.join("limited"),
+            0,
+        )
+        .expect("时间平移失败");
+    assert!(started.elapsed() >= Duration::from_millis(59));
+    assert_eq!(summary.packets, PACKET_COUNT as u64);
+    assert_eq!(
+        read_timestamps(base_path, "limited"),
+        original
+    );
+}
+
+fn read_payloads(
+    base_path: &Path,
+    name: &str,
+) -> Vec<(u64, Vec<u8>)> {
+    let mut reader = PcapReader::new(base_path, name)
+        .expect("创建读取器失败");
+    let mut payloads = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        assert!(packet.is_valid());
+        payloads.push((
+            packet.get_timestamp_ns(),
+            packet.packet.data,
+        ));
+    }
+    payloads
+}
+
+#[test]
+fn test_scrub_rules() {
+    let mut payload = b"abcdef".to_vec();
+    assert!(
+        ScrubRule::ZeroRange(4..100).apply(&mut payload)
+    );
+    assert_eq!(payload, b"abcd\0\0");
+    assert!(
+        !ScrubRule::ZeroRange(10..20).apply(&mut payload)
+    );
+    assert!(!ScrubRule::ZeroRange(4..6).apply(&mut payload));
+
+    let mut payload = b"ab-ab-aab".to_vec();
+    let replace = ScrubRule::Replace {
+        pattern: b"ab".to_vec(),
+        replacement: b"X".to_vec(),
+    };
+    assert!(replace.apply(&mut payload));
+    assert_eq!(payload, b"X-X-aX");
+    assert!(!replace.apply(&mut payload));
+
+    let mut payload = b"abcdef".to_vec();
+    assert!(ScrubRule::KeepFirst(2).apply(&mut payload));
+    assert_eq!(payload, b"ab");
+    assert!(!ScrubRule::KeepFirst(2).apply(&mut payload));
+
+    let scrubber =
+        Scrubber::new().keep_first(3).zero_range(0..1);
+    assert_eq!(scrubber.rules().len(), 2);
+    let mut payload = b"\0bcdef".to_vec();
+    assert!(scrubber.scrub(&mut payload));
+    assert_eq!(payload, b"\0bc");
+    assert!(!scrubber.scrub(&mut payload));
+
+    assert!(Scrubber::new()
+        .replace(b"", b"x")
+        .validate()
+        .is_err());
+    assert_eq!(Scrubber::new().validate(), Ok(()));
+}
+
+#[test]
+fn test_scrub_dataset_payloads() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let config = WriterConfig {
+        max_packets_per_file: 8,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base_path, "source", config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..20u64 {
+        let payload = if i % 5 == 0 {
+            vec![0u8; 3]
+        } else {
+            format!("id{i:02}:token=SECRET;tail")
+                .into_bytes()
+        };
+        writer
+            .write_payload(
+                1_700_000_000_000_000_000 + i,
+                &payload,
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+    let original = read_payloads(base_path, "source");
+
+    let scrubber = Scrubber::new()
+        .replace(b"SECRET", b"***")
+        .zero_range(0..4)
+        .keep_first(12);
+    let summary = DatasetTransformer::new()
+        .scrub(
+            base_path.join("source"),
+            base_path.join("shared"),
+            &scrubber,
+        )
+        .expect("脱敏失败");
+    assert_eq!(summary.packets, 20);
+    assert_eq!(summary.scrubbed_packets, 16);
+    assert_eq!(summary.invalid_packets, 0);
+
+    let scrubbed = read_payloads(base_path, "shared");
+    assert_eq!(scrubbed.len(), original.len());
+    for (
+        (timestamp, payload),
+        (original_ts, original_payload),
+    ) in scrubbed.iter().zip(&original)
+    {
+        assert_eq!(timestamp, original_ts);
+        if original_payload.len() == 3 {
+            assert_eq!(payload, original_payload);
+        } else {
+            assert_eq!(payload, b"\0\0\0\0:token=*");
+        }
+    }
+    // 源数据集保持不变
+    assert_eq!(
+        read_payloads(base_path, "source"),
+        original
+    );
+
+    let result = DatasetTransformer::new().scrub(
+        base_path.join("source"),
+        base_path.join("invalid"),
+        &Scrubber::new().replace(Vec::new(), b"x"),
+    );
+    assert!(matches!(
+        result,
+        Err(PcapError::InvalidArgument(_))
+    ));
+}
@@ -0,0 +1,344 @@
+//! 时间戳差值编码测试
+//!
+//! 测试差值编码记录格式缩小数据包头部、读取时透明还原时间戳，
+//! 以及按位置和时间戳定位、乱序时间戳、重建索引、实时读取和内存读取
+
+use pcapfile_io::{
+    validate_file_quick, HeaderExtension, MemoryReader,
+    PcapReader, PcapWriter, RecordFormat,
+    WriteVerification, WriterConfig,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::{
+    data_files, data_files_size, read_all,
+    write_payload_dataset, write_payloads,
+};
+
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+const PACKET_INTERVAL_NS: u64 = 10_000;
+
+fn small_payload(i: u64) -> Vec<u8> {
+    vec![(i % 251) as u8; 32]
+}
+
+fn delta_config() -> WriterConfig {
+    WriterConfig {
+        max_packets_per_file: 40,
+        record_format: RecordFormat::DeltaTimestamp,
+        ..Default::default()
+    }
+}
+
+fn write_packets(
+    writer: &mut PcapWriter,
+    timestamps: impl IntoIterator<Item = u64>,
+) -> Vec<(u64, Vec<u8>)> {
+    write_payloads(
+        writer,
+        timestamps.into_iter().enumerate().map(
+            |(i, timestamp_ns)| {
+                (timestamp_ns, small_payload(i as u64))
+            },
+        ),
+    )
+}
+
+fn write_dataset(
+    base: &Path,
+    name: &str,
+    config: WriterConfig,
+    count: u64,
+) -> Vec<(u64, Vec<u8>)> {
+    write_payload_dataset(
+        base,
+        name,
+        config,
+        (0..count).map(|i| {
+            (
+                BASE_TIMESTAMP + i * PACKET_INTERVAL_NS,
+                small_payload(i),
+            )
+        }),
+    )
+}
+
+#[test]
+fn test_delta_format_shrinks_packet_headers() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+
+    write_dataset(
+        base,
+        "standard",
+        WriterConfig {
+            max_packets_per_file: 40,
+            ..Default::default()
+        },
+        200,
+    );
+    let expected =
+        write_dataset(base, "delta", delta_config(), 200);
+
+    // 10 微秒的间隔和 32 字节负载的头部只占 8 字节，
+    // 扣除文件头中记录格式扩展块的开销后每个数据包仍至少节省 7 字节
+    let standard_size =
+        data_files_size(&base.join("standard"));
+    let delta_size = data_files_size(&base.join("delta"));
+    assert!(
+        delta_size + 200 * 7 <= standard_size,
+        "差值编码后大小 {delta_size} 应比 {standard_size} 少至少 1400 字节"
+    );
+
+    assert_eq!(read_all(base, "delta"), expected);
+}
+
+#[test]
+fn test_delta_format_recorded_in_header_extension() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    write_dataset(base, "delta", delta_config(), 10);
+    write_dataset(
+        base,
+        "standard",
+        WriterConfig::default(),
+        10,
+    );
+
+    let mut reader =
+        PcapReader::new(base, "delta").unwrap();
+    assert!(reader.header_extensions().unwrap().contains(
+        &HeaderExtension::RecordFormat(
+            RecordFormat::DeltaTimestamp.name().to_string()
+        )
+    ));
+
+    let mut reader =
+        PcapReader::new(base, "standard").unwrap();
+    assert!(!reader
+        .header_extensions()
+        .unwrap()
+        .iter()
+        .any(|extension| matches!(
+            extension,
+            HeaderExtension::RecordFormat(_)
+        )));
+}
+
+#[test]
+fn test_delta_seek_across_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let expected =
+        write_dataset(base, "delta", delta_config(), 100);
+
+    let mut reader =
+        PcapReader::new(base, "delta").unwrap();
+    assert_eq!(
+        reader.get_file_info_list().unwrap().len(),
+        3
+    );
+
+    // 定位到文件中间后顺序读取，时间戳以定位的数据包为基准还原
+    reader.seek_to_packet(57).unwrap();
+    for (timestamp_ns, payload) in &expected[57..63] {
+        let packet = reader.read_packet().unwrap().unwrap();
+        assert_eq!(
+            packet.packet.get_timestamp_ns(),
+            *timestamp_ns
+        );
+        assert_eq!(&packet.packet.data, payload);
+    }
+
+    let (timestamp_ns, payload) = &expected[83];
+    let packet = reader
+        .read_packet_by_timestamp(*timestamp_ns)
+        .unwrap()
+        .expect("应找到数据包");
+    assert_eq!(&packet.packet.data, payload);
+
+    reader.seek_to_timestamp(expected[21].0).unwrap();
+    let packet = reader.read_packet().unwrap().unwrap();
+    assert_eq!(
+        packet.packet.get_timestamp_ns(),
+        expected[21].0
+    );
+    let packet = reader.read_packet().unwrap().unwrap();
+    assert_eq!(
+        packet.packet.get_timestamp_ns(),
+        expected[22].0
+    );
+}
+
+#[test]
+fn test_delta_out_of_order_timestamps() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let mut writer = PcapWriter::new_with_config(
+        base,
+        "delta",
+        delta_config(),
+    )
+    .unwrap();
+    // 时间戳回退产生负的差值，也包含跨度很大的跳变
+    let timestamps = [
+        BASE_TIMESTAMP + 5_000,
+        BASE_TIMESTAMP,
+        BASE_TIMESTAMP + 3_000,
+        BASE_TIMESTAMP + 3_600_000_000_000,
+        BASE_TIMESTAMP + 1,
+        BASE_TIMESTAMP + 1,
+    ];
+    let expected = write_packets(&mut writer, timestamps);
+    writer.finalize().unwrap();
+
+    assert_eq!(read_all(base, "delta"), expected);
+}
+
+#[test]
+fn test_delta_rebuilt_index_matches_records() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    // 写入时不记录索引，完成写入时重新分析数据文件
+    let config = WriterConfig {
+        index_batch_size: 0,
+        ..delta_config()
+    };
+    let expected = write_dataset(base, "delta", config, 60);
+
+    let mut reader =
+        PcapReader::new(base, "delta").unwrap();
+    reader.seek_to_packet(45).unwrap();
+    let packet = reader.read_packet().unwrap().unwrap();
+    assert_eq!(
+        packet.packet.get_timestamp_ns(),
+        expected[45].0
+    );
+    assert_eq!(read_all(base, "delta"), expected);
+}
+
+#[test]
+fn test_delta_with_read_back_and_payload_dedup() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let config = WriterConfig {
+        verify_on_write: WriteVerification::ReadBack,
+        payload_dedup: true,
+        ..delta_config()
+    };
+    let expected = write_dataset(base, "delta", config, 50);
+
+    assert_eq!(read_all(base, "delta"), expected);
+    let mut reader =
+        PcapReader::new(base, "delta").unwrap();
+    reader.seek_to_packet(44).unwrap();
+    let packet = reader.read_packet().unwrap().unwrap();
+    assert_eq!(packet.packet.data, expected[44].1);
+}
+
+#[test]
+fn test_delta_quick_validation_and_memory_reader() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let expected =
+        write_dataset(base, "delta", delta_config(), 40);
+    let files = data_files(&base.join("delta"));
+    assert_eq!(files.len(), 1);
+
+    let structure = validate_file_quick(&files[0]).unwrap();
+    assert_eq!(structure.packet_count, 40);
+    assert_eq!(
+        structure.first_timestamp_ns,
+        Some(expected[0].0)
+    );
+    assert_eq!(
+        structure.last_timestamp_ns,
+        Some(expected[39].0)
+    );
+
+    // 分块追加时头部可能被切开
+    let bytes = fs::read(&files[0]).unwrap();
+    let mut reader = MemoryReader::new();
+    let mut packets = Vec::new();
+    for chunk in bytes.chunks(7) {
+        reader.push_chunk(chunk).unwrap();
+        while let Some(packet) =
+            reader.read_packet().unwrap()
+        {
+            assert!(packet.is_valid());
+            packets.push((
+                packet.packet.get_timestamp_ns(),
+                packet.packet.data,
+            ));
+        }
+    }
+    reader.finish();
+    assert!(reader.read_packet().unwrap().is_none());
+    assert_eq!(packets, expected);
+}
+
+#[test]
+fn test_delta_live_reader_resumes_after_refresh() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let config = WriterConfig {
+        max_packets_per_file: 10,
+        index_checkpoint_interval: 1,
+        ..delta_config()
+    };
+    let mut writer =
+        PcapWriter::new_with_config(base, "delta", config)
+            .unwrap();
+    let mut expected = write_packets(
+        &mut writer,
+        (0..25).map(|i| BASE_TIMESTAMP + i * 1_000),
+    );
+    writer.flush().unwrap();
+
+    let mut reader =
+        PcapReader::new(base, "delta").unwrap();
+    reader.initialize().unwrap();
+    assert!(reader.is_live());
+    let mut packets = Vec::new();
+    while let Some(packet) = reader.read_packet().unwrap() {
+        packets.push((
+            packet.packet.get_timestamp_ns(),
+            packet.packet.data,
+        ));
+    }
+
+    // 刷新后从已读取的最后一个数据包之后继续读取
+    let more = write_packets(
+        &mut writer,
+        (25..30).map(|i| BASE_TIMESTAMP + i * 1_000),
+    );
+    writer.flush().unwrap();
+    reader.refresh_index().unwrap();
+    while let Some(packet) = reader.read_packet().unwrap() {
+        packets.push((
+            packet.packet.get_timestamp_ns(),
+            packet.packet.data,
+        ));
+    }
+    writer.finalize().unwrap();
+
+    expected.extend(more);
+    assert_eq!(packets, expected);
+}
+
+#[test]
+fn test_record_format_names_round_trip() {
+    for format in [
+        RecordFormat::Standard,
+        RecordFormat::DeltaTimestamp,
+    ] {
+        assert_eq!(
+            RecordFormat::from_name(format.name()),
+            Some(format)
+        );
+    }
+    assert_eq!(RecordFormat::from_name("v3"), None);
+}
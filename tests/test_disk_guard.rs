@@ -0,0 +1,93 @@
+//! 磁盘空间保护测试
+//!
+//! 测试写入器在可用空间不足时返回 DiskFull 错误，以及回调决定继续写入的情况
+
+use pcapfile_io::{
+    DiskGuard, DiskGuardAction, PcapError, PcapErrorCode,
+    PcapReader, PcapWriter,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+mod common;
+use common::create_test_packet;
+
+const TEST_NAME: &str = "disk_guard_dataset";
+
+#[test]
+fn test_disk_guard_rejects_write_when_space_low() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+
+    let mut writer = PcapWriter::new(base_path, TEST_NAME)
+        .expect("创建写入器失败");
+    writer.set_disk_guard(u64::MAX);
+    assert_eq!(
+        writer.disk_guard().map(|g| g.min_free_bytes()),
+        Some(u64::MAX)
+    );
+
+    let packet = create_test_packet(0, 64)
+        .expect("创建测试数据包失败");
+    let error = writer
+        .write_packet(&packet)
+        .expect_err("可用空间不足时应拒绝写入");
+    assert!(matches!(error, PcapError::DiskFull { .. }));
+    assert_eq!(error.error_code(), PcapErrorCode::DiskFull);
+
+    // 关闭保护后可以继续写入
+    writer.clear_disk_guard();
+    writer.write_packet(&packet).expect("写入数据包失败");
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader = PcapReader::new(base_path, TEST_NAME)
+        .expect("创建读取器失败");
+    assert_eq!(
+        reader
+            .get_dataset_info()
+            .expect("获取数据集信息失败")
+            .total_packets,
+        1
+    );
+}
+
+#[test]
+fn test_disk_guard_handler_can_continue() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let handler_calls = Arc::clone(&calls);
+
+    let mut writer = PcapWriter::new(base_path, TEST_NAME)
+        .expect("创建写入器失败");
+    writer.set_disk_guard_with(
+        DiskGuard::new(u64::MAX)
+            .with_check_interval(0)
+            .with_handler(move |status| {
+                assert!(
+                    status.available_bytes
+                        < status.required_bytes
+                );
+                handler_calls
+                    .fetch_add(1, Ordering::SeqCst);
+                DiskGuardAction::Continue
+            }),
+    );
+
+    for i in 0..5 {
+        writer
+            .write_packet(
+                &create_test_packet(i, 32)
+                    .expect("创建测试数据包失败"),
+            )
+            .expect("回调要求继续时应允许写入");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    // 创建文件检查一次，每次写入各检查一次
+    assert_eq!(calls.load(Ordering::SeqCst), 6);
+}
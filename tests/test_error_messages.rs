@@ -0,0 +1,92 @@
+//! 错误信息语言测试
+//!
+//! 测试错误和错误代码的中英文信息，以及全局语言切换对 `Display` 输出的影响。
+//! 全局语言是进程级状态，切换语言的断言集中在同一个测试中，避免并行测试互相干扰。
+
+use pcapfile_io::{Locale, PcapError, PcapErrorCode};
+use std::io;
+
+#[test]
+fn test_error_messages_in_both_languages() {
+    let error = PcapError::ChecksumMismatch {
+        expected: "0x1A2B".into(),
+        actual: "0x3C4D".into(),
+        position: 128,
+    };
+    assert_eq!(
+        error.message_zh(),
+        "校验和不匹配: 期望 0x1A2B, 实际 0x3C4D，位置 128"
+    );
+    assert_eq!(
+        error.message_en(),
+        "checksum mismatch: expected 0x1A2B, actual 0x3C4D, at offset 128"
+    );
+
+    let error = PcapError::UnsupportedVersion {
+        major: 2,
+        minor: 9,
+    };
+    assert_eq!(
+        error.message(Locale::English),
+        "unsupported file format version: 2.9"
+    );
+
+    let error = PcapError::from(io::Error::other("boom"));
+    assert_eq!(error.message_en(), "I/O error: boom");
+    assert!(std::error::Error::source(&error).is_some());
+
+    // 每个错误代码都有非空且不同于中文的英文说明
+    for code in PcapErrorCode::ALL {
+        assert!(!code.message_en().is_empty());
+        assert!(code.message_en().is_ascii(), "{code:?}");
+        assert_ne!(code.message_en(), code.message_zh());
+    }
+    assert_eq!(
+        PcapErrorCode::DatasetLocked.message_en(),
+        "dataset locked"
+    );
+}
+
+#[test]
+fn test_global_locale_switch() {
+    let error = PcapError::DatasetLocked("capture".into());
+    assert_eq!(Locale::default(), Locale::English);
+    assert_eq!(Locale::current(), Locale::English);
+    assert_eq!(
+        error.to_string(),
+        "dataset locked by another writer: capture"
+    );
+    assert_eq!(
+        PcapErrorCode::DiskFull.to_string(),
+        "disk full"
+    );
+    assert_eq!(
+        error.detailed_message(),
+        "error code: dataset locked(3007), message: dataset locked by another writer: capture"
+    );
+
+    Locale::set_global(Locale::Chinese);
+    assert_eq!(
+        error.to_string(),
+        "数据集已被其他写入器锁定: capture"
+    );
+    assert_eq!(
+        PcapErrorCode::DiskFull.to_string(),
+        "磁盘空间不足"
+    );
+    assert_eq!(
+        error.detailed_message(),
+        "错误代码: 数据集已被锁定(3007), 错误信息: 数据集已被其他写入器锁定: capture"
+    );
+    // 指定语言的信息不受全局语言影响
+    assert_eq!(
+        error.message_en(),
+        "dataset locked by another writer: capture"
+    );
+
+    Locale::set_global(Locale::English);
+    assert_eq!(
+        PcapErrorCode::DiskFull.to_string(),
+        "disk full"
+    );
+}
@@ -0,0 +1,271 @@
+//! 导出测试
+//!
+//! 验证流式导出核心把数据包写入数据集、标准 libpcap 文件、UDP 和闭包输出端，
+//! 以及各错误策略下的逐输出端错误统计和定期刷新。
+
+use pcapfile_io::{
+    export, ExportErrorPolicy, ExportOptions,
+    LibpcapWriter, LinkType, PacketSink, PcapError,
+    PcapReader, PcapResult, PcapWriter, UdpSink,
+    ValidatedPacket, WriterConfig,
+};
+use std::net::UdpSocket;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+
+mod common;
+use common::{read_all, write_payload_dataset};
+
+const BASE_NS: u64 = 1_700_000_000_123_456_789;
+const PACKET_COUNT: u64 = 20;
+
+fn write_dataset(base_path: &Path) {
+    write_payload_dataset(
+        base_path,
+        "source",
+        WriterConfig::default(),
+        (0..PACKET_COUNT).map(|i| {
+            (BASE_NS + i * 1_000, vec![i as u8; 16])
+        }),
+    );
+}
+
+/// 记录刷新次数的输出端
+#[derive(Default)]
+struct CountingSink {
+    packets: u64,
+    flushes: u64,
+}
+
+impl PacketSink for CountingSink {
+    fn write(
+        &mut self,
+        _packet: &ValidatedPacket,
+    ) -> PcapResult<()> {
+        self.packets += 1;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> PcapResult<()> {
+        self.flushes += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_pipe_to_dataset_writer() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    write_dataset(base_path);
+
+    let mut reader = PcapReader::new(base_path, "source")
+        .expect("打开失败");
+    let mut writer = PcapWriter::new(base_path, "copy")
+        .expect("创建写入器失败");
+    let summary = export::pipe(&mut reader, &mut writer)
+        .expect("导出失败");
+    writer.finalize().expect("完成写入失败");
+
+    assert_eq!(summary.packets_read, PACKET_COUNT);
+    assert_eq!(summary.packets_exported, PACKET_COUNT);
+    assert!(summary.is_clean());
+    assert_eq!(
+        summary.sinks[0].packets_written,
+        PACKET_COUNT
+    );
+    assert_eq!(
+        summary.sinks[0].bytes_written,
+        PACKET_COUNT * 16
+    );
+    assert_eq!(
+        read_all(base_path, "copy"),
+        read_all(base_path, "source")
+    );
+}
+
+#[test]
+fn test_pipe_to_multiple_sinks() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    write_dataset(base_path);
+
+    let pcap_path = base_path.join("export.pcap");
+    let mut libpcap = LibpcapWriter::create(
+        &pcap_path,
+        LinkType::Ethernet,
+    )
+    .expect("创建 libpcap 文件失败");
+    // 第 5 个数据包起失败的输出端
+    let mut written = 0;
+    let mut failing = |_: &ValidatedPacket| {
+        if written == 5 {
+            return Err(PcapError::SinkFailed(
+                "离线".to_string(),
+            ));
+        }
+        written += 1;
+        Ok(())
+    };
+    let mut counting = CountingSink::default();
+
+    let mut reader = PcapReader::new(base_path, "source")
+        .expect("打开失败");
+    let options = ExportOptions {
+        error_policy: ExportErrorPolicy::DetachSink,
+        flush_interval: 8,
+        max_packets: Some(17),
+        ..ExportOptions::default()
+    };
+    let summary = export::pipe_with(
+        &mut reader,
+        &mut [&mut libpcap, &mut failing, &mut counting],
+        &options,
+    )
+    .expect("导出失败");
+
+    assert_eq!(summary.packets_exported, 17);
+    assert!(!summary.is_clean());
+    assert_eq!(summary.error_count(), 1);
+    assert_eq!(summary.sinks[0].packets_written, 17);
+    assert!(summary.sinks[0].is_clean());
+    assert_eq!(summary.sinks[1].packets_written, 5);
+    assert!(summary.sinks[1].detached);
+    assert!(matches!(
+        summary.sinks[1].errors[0],
+        PcapError::SinkFailed(_)
+    ));
+    assert_eq!(summary.sinks[2].packets_written, 17);
+    assert_eq!(counting.packets, 17);
+    // 第 8、16 个数据包后各刷新一次，结束时再刷新一次
+    assert_eq!(counting.flushes, 3);
+
+    // 标准 pcap 文件：纳秒魔术数、以太网链路类型、17 条记录
+    assert_eq!(libpcap.packet_count(), 17);
+    drop(libpcap);
+    let bytes =
+        std::fs::read(&pcap_path).expect("读取失败");
+    assert_eq!(&bytes[0..4], &0xa1b2_3c4du32.to_le_bytes());
+    assert_eq!(&bytes[20..24], &1u32.to_le_bytes());
+    assert_eq!(bytes.len(), 24 + 17 * (16 + 16));
+    let first = &bytes[24..40];
+    assert_eq!(
+        &first[0..4],
+        &((BASE_NS / 1_000_000_000) as u32).to_le_bytes()
+    );
+    assert_eq!(
+        &first[4..8],
+        &((BASE_NS % 1_000_000_000) as u32).to_le_bytes()
+    );
+    assert_eq!(&first[8..12], &16u32.to_le_bytes());
+}
+
+#[test]
+fn test_pipe_error_policies() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    write_dataset(base_path);
+    let mut reader = PcapReader::new(base_path, "source")
+        .expect("打开失败");
+
+    // 默认策略在第一个错误时中止
+    let mut failing = |packet: &ValidatedPacket| {
+        if packet.packet.data[0] == 3 {
+            return Err(PcapError::SinkFailed(
+                "拒绝".to_string(),
+            ));
+        }
+        Ok(())
+    };
+    assert!(matches!(
+        export::pipe(&mut reader, &mut failing),
+        Err(PcapError::SinkFailed(_))
+    ));
+
+    // 继续策略记录每个错误
+    reader.reset().expect("重置失败");
+    let mut odd = |packet: &ValidatedPacket| {
+        if packet.packet.data[0] % 2 == 1 {
+            return Err(PcapError::SinkFailed(
+                "拒绝".to_string(),
+            ));
+        }
+        Ok(())
+    };
+    let summary = export::pipe_with(
+        &mut reader,
+        &mut [&mut odd],
+        &ExportOptions {
+            error_policy: ExportErrorPolicy::Continue,
+            ..ExportOptions::default()
+        },
+    )
+    .expect("导出失败");
+    assert_eq!(summary.packets_exported, PACKET_COUNT);
+    assert_eq!(
+        summary.sinks[0].packets_written,
+        PACKET_COUNT / 2
+    );
+    assert_eq!(
+        summary.error_count(),
+        PACKET_COUNT as usize / 2
+    );
+    assert!(!summary.sinks[0].detached);
+
+    // 所有输出端被摘除后提前结束
+    reader.reset().expect("重置失败");
+    let mut offline = |_: &ValidatedPacket| {
+        Err(PcapError::SinkFailed("离线".to_string()))
+    };
+    let summary = export::pipe_with(
+        &mut reader,
+        &mut [&mut offline],
+        &ExportOptions {
+            error_policy: ExportErrorPolicy::DetachSink,
+            ..ExportOptions::default()
+        },
+    )
+    .expect("导出失败");
+    assert_eq!(summary.packets_read, 1);
+}
+
+#[test]
+fn test_pipe_to_udp() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    write_dataset(base_path);
+
+    let receiver =
+        UdpSocket::bind("127.0.0.1:0").expect("绑定失败");
+    receiver
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .expect("设置超时失败");
+    let mut sink = UdpSink::new(
+        receiver.local_addr().expect("获取地址失败"),
+    )
+    .expect("创建输出端失败");
+
+    let mut reader = PcapReader::new(base_path, "source")
+        .expect("打开失败");
+    let summary = export::pipe_with(
+        &mut reader,
+        &mut [&mut sink],
+        &ExportOptions {
+            max_packets: Some(3),
+            ..ExportOptions::default()
+        },
+    )
+    .expect("导出失败");
+    assert_eq!(summary.packets_exported, 3);
+
+    let mut buffer = [0u8; 64];
+    for i in 0..3u8 {
+        let length =
+            receiver.recv(&mut buffer).expect("接收失败");
+        assert_eq!(&buffer[..length], &[i; 16]);
+    }
+}
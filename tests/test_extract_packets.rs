@@ -0,0 +1,177 @@
+//! 单独提取数据包测试
+//!
+//! 测试把指定序号的数据包分别提取为独立的 libpcap 文件或原始负载与元数据文件，
+//! 以及序号超出范围时不写入任何文件
+
+use pcapfile_io::{
+    ExtractFormat, LinkType, PcapError, PcapReader,
+    PcapWriter, WriterConfig,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "extract";
+const BASE_TIMESTAMP: u64 = 1_700_000_000_123_456_789;
+
+fn payload(i: u64) -> Vec<u8> {
+    vec![i as u8; 8 + i as usize]
+}
+
+fn write_dataset(base: &Path) {
+    let config = WriterConfig {
+        max_packets_per_file: 4,
+        link_type: LinkType::Can,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base, TEST_NAME, config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..10u64 {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + i * 1_000,
+                &payload(i),
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+#[test]
+fn test_extract_packets_to_libpcap_files() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path());
+    let out_dir = temp_dir.path().join("report");
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("创建读取器失败");
+    let extracted = reader
+        .extract_packets(&[9, 0, 5], &out_dir)
+        .expect("提取失败");
+    assert_eq!(extracted.len(), 3);
+
+    for (record, index) in
+        extracted.iter().zip([9u64, 0, 5])
+    {
+        assert_eq!(record.global_index, index);
+        assert_eq!(
+            record.timestamp_ns,
+            BASE_TIMESTAMP + index * 1_000
+        );
+        assert_eq!(record.link_type, LinkType::Can);
+        assert_eq!(
+            record.packet_size as usize,
+            payload(index).len()
+        );
+        assert!(record.is_valid);
+        assert!(record.metadata_path.is_none());
+        assert_eq!(
+            record.path,
+            out_dir.join(format!("packet_{index}.pcap"))
+        );
+
+        let bytes =
+            fs::read(&record.path).expect("读取失败");
+        let u32_at = |offset: usize| {
+            u32::from_le_bytes(
+                bytes[offset..offset + 4]
+                    .try_into()
+                    .unwrap(),
+            )
+        };
+        assert_eq!(u32_at(0), 0xa1b2_3c4d);
+        assert_eq!(u32_at(20), 227);
+        assert_eq!(
+            u64::from(u32_at(24)) * 1_000_000_000
+                + u64::from(u32_at(28)),
+            record.timestamp_ns
+        );
+        assert_eq!(u32_at(32), record.packet_size);
+        assert_eq!(&bytes[40..], payload(index).as_slice());
+    }
+
+    // 不同文件中的数据包记录各自的来源文件
+    let files = reader
+        .get_file_info_list()
+        .expect("获取文件列表失败");
+    assert_eq!(
+        extracted[0].source_file,
+        files[2].file_name
+    );
+    assert_eq!(
+        extracted[1].source_file,
+        files[0].file_name
+    );
+    assert_eq!(
+        extracted[2].source_file,
+        files[1].file_name
+    );
+}
+
+#[test]
+fn test_extract_packets_as_raw_payload() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path());
+    let out_dir = temp_dir.path().join("raw");
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("创建读取器失败");
+    let extracted = reader
+        .extract_packets_with_format(
+            &[3],
+            &out_dir,
+            ExtractFormat::Raw,
+        )
+        .expect("提取失败");
+    let record = &extracted[0];
+    assert_eq!(record.path, out_dir.join("packet_3.bin"));
+    assert_eq!(
+        fs::read(&record.path).expect("读取失败"),
+        payload(3)
+    );
+
+    let metadata_path =
+        record.metadata_path.as_ref().expect("缺少元数据");
+    let metadata: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(metadata_path)
+            .expect("读取失败"),
+    )
+    .expect("解析失败");
+    assert_eq!(metadata["global_index"], 3);
+    assert_eq!(
+        metadata["timestamp_ns"],
+        BASE_TIMESTAMP + 3_000
+    );
+    assert_eq!(metadata["packet_size"], 11);
+    assert_eq!(metadata["checksum"], record.checksum);
+    assert_eq!(metadata["is_valid"], true);
+    assert_eq!(
+        metadata["source_file"],
+        record.source_file.as_str()
+    );
+    assert_eq!(metadata["byte_offset"], record.byte_offset);
+    assert!(metadata.get("path").is_none());
+}
+
+#[test]
+fn test_extract_out_of_range_writes_nothing() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path());
+    let out_dir = temp_dir.path().join("report");
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("创建读取器失败");
+    assert!(matches!(
+        reader.extract_packets(&[1, 10], &out_dir),
+        Err(PcapError::InvalidArgument(_))
+    ));
+    assert!(!out_dir.exists());
+}
@@ -0,0 +1,228 @@
+//! 文件轮换策略测试
+//!
+//! 验证按磁盘大小（含文件头）、原始大小和时间跨度轮换文件，多个条件同时满足时的优先级，
+//! 以及写入器返回的文件信息中携带的轮换策略和关闭原因。
+
+use pcapfile_io::{
+    ByteSize, FileUsage, PcapWriter, RotationPolicy,
+    RotationReason, WriterConfig,
+};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+fn write_packets(
+    base_path: &Path,
+    config: WriterConfig,
+    count: u64,
+    interval_ns: u64,
+    payload_size: usize,
+) -> PcapWriter {
+    let mut writer = PcapWriter::new_with_config(
+        base_path, "rotation", config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..count {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + i * interval_ns,
+                &vec![i as u8; payload_size],
+            )
+            .expect("写入负载失败");
+    }
+    writer
+}
+
+#[test]
+fn test_disk_size_includes_file_header() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let config = WriterConfig {
+        max_packets_per_file: 1000,
+        max_file_size: ByteSize::kib(4),
+        ..Default::default()
+    };
+    let mut writer = write_packets(
+        temp_dir.path(),
+        config,
+        20,
+        1_000,
+        1005,
+    );
+    writer.flush().expect("刷新失败");
+
+    let files = writer.get_file_info_list();
+    // 4 个 1021 字节的记录本身不超过 4096 字节，加上文件头后超过，每个文件只能容纳 3 个
+    assert_eq!(files.len(), 7);
+    for (i, file) in files.iter().enumerate() {
+        let size = fs::metadata(&file.file_path)
+            .expect("读取文件信息失败")
+            .len();
+        assert!(size <= 4096, "{}: {size}", file.file_name);
+        assert_eq!(
+            file.rotation_policy,
+            Some(RotationPolicy {
+                max_packets: 1000,
+                max_duration: Duration::ZERO,
+                max_disk_size: ByteSize::kib(4),
+                max_raw_size: ByteSize::ZERO,
+            })
+        );
+        let expected = (i + 1 < files.len())
+            .then_some(RotationReason::DiskSize);
+        assert_eq!(file.rotation_reason, expected);
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+#[test]
+fn test_raw_size_and_oversized_packet() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let config = WriterConfig {
+        max_file_raw_size: ByteSize::kib(2),
+        ..Default::default()
+    };
+    let mut writer = write_packets(
+        temp_dir.path(),
+        config,
+        4,
+        1_000,
+        1000,
+    );
+    // 超过上限的单个数据包独占一个文件
+    writer
+        .write_payload(
+            BASE_TIMESTAMP + 10_000,
+            &[0u8; 4096],
+        )
+        .expect("写入负载失败");
+    writer.flush().expect("刷新失败");
+
+    let files = writer.get_file_info_list();
+    let reasons: Vec<_> =
+        files.iter().map(|f| f.rotation_reason).collect();
+    assert_eq!(
+        reasons,
+        vec![
+            Some(RotationReason::RawSize),
+            Some(RotationReason::RawSize),
+            None
+        ]
+    );
+    writer.finalize().expect("完成写入失败");
+}
+
+#[test]
+fn test_duration_rotation() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let config = WriterConfig {
+        max_file_duration: Duration::from_millis(10),
+        ..Default::default()
+    };
+    // 每 1 毫秒一个数据包，共 35 毫秒
+    let mut writer = write_packets(
+        temp_dir.path(),
+        config,
+        35,
+        1_000_000,
+        16,
+    );
+    writer.finalize().expect("完成写入失败");
+
+    let files = writer.get_file_info_list();
+    assert_eq!(files.len(), 4);
+    for file in &files[..3] {
+        assert_eq!(file.packet_count, 10);
+        assert_eq!(
+            file.rotation_reason,
+            Some(RotationReason::Duration)
+        );
+        let span = file
+            .end_timestamp
+            .expect("缺少结束时间")
+            - file.start_timestamp.expect("缺少开始时间");
+        assert!(span < 10_000_000);
+    }
+    assert_eq!(files[3].packet_count, 5);
+}
+
+#[test]
+fn test_rotation_precedence() {
+    let policy = RotationPolicy {
+        max_packets: 10,
+        max_duration: Duration::from_secs(1),
+        max_disk_size: ByteSize::kib(4),
+        max_raw_size: ByteSize::kib(2),
+    };
+    let usage = FileUsage {
+        packet_count: 10,
+        disk_bytes: 4000,
+        raw_bytes: 2000,
+        first_timestamp_ns: Some(0),
+    };
+
+    // 全部条件同时满足时按数据包数量、时间跨度、磁盘大小、原始大小的顺序报告
+    assert_eq!(
+        policy.check(&usage, 2_000_000_000, 100, 100),
+        Some(RotationReason::PacketCount)
+    );
+    let usage = FileUsage {
+        packet_count: 9,
+        ..usage
+    };
+    assert_eq!(
+        policy.check(&usage, 2_000_000_000, 100, 100),
+        Some(RotationReason::Duration)
+    );
+    assert_eq!(
+        policy.check(&usage, 1_000, 100, 100),
+        Some(RotationReason::DiskSize)
+    );
+    // 压缩后的磁盘字节数较少时由原始大小触发
+    assert_eq!(
+        policy.check(&usage, 1_000, 100, 10),
+        Some(RotationReason::RawSize)
+    );
+    assert_eq!(policy.check(&usage, 1_000, 10, 10), None);
+
+    // 空文件总是接受下一个数据包
+    let empty = FileUsage {
+        disk_bytes: 4096,
+        ..FileUsage::default()
+    };
+    assert_eq!(
+        policy.check(&empty, 0, 1 << 20, 1 << 20),
+        None
+    );
+}
+
+#[test]
+fn test_rotation_config_serde() {
+    let mut value =
+        serde_json::to_value(WriterConfig::default())
+            .expect("序列化失败");
+    value["max_file_duration"] = "1h".into();
+    value["max_file_raw_size"] = "512MB".into();
+    let config: WriterConfig =
+        serde_json::from_value(value)
+            .expect("反序列化失败");
+    let policy = config.rotation_policy();
+    assert_eq!(
+        policy.max_duration,
+        Duration::from_secs(3600)
+    );
+    assert_eq!(policy.max_raw_size, ByteSize::mib(512));
+    assert!(config.validate().is_ok());
+
+    let config = WriterConfig {
+        max_file_raw_size: ByteSize::b(10),
+        ..Default::default()
+    };
+    let error = config.validate().expect_err("上限过小");
+    assert!(error.starts_with("max_file_raw_size:"));
+}
@@ -0,0 +1,204 @@
+//! 文件格式布局测试
+//!
+//! 按 `format` 模块的布局表从编码后的字节中读取各字段，
+//! 验证布局表与实际的文件头、数据包头和扩展块编码一致
+
+use pcapfile_io::format::{
+    self, FieldLayout, FieldType, FILE_HEADER_FIELDS,
+    PACKET_HEADER_FIELDS,
+};
+use pcapfile_io::{
+    ByteOrder, DataPacketHeader, FormatVersion,
+    HeaderExtension, LinkType, PcapFileHeader,
+};
+
+/// 按布局从字节中读取字段值
+fn read_field(
+    bytes: &[u8],
+    field: &FieldLayout,
+    order: ByteOrder,
+) -> i64 {
+    let raw = &bytes[field.offset..field.end()];
+    match (field.field_type, order) {
+        (FieldType::U16, ByteOrder::Little) => {
+            u16::from_le_bytes(raw.try_into().unwrap())
+                as i64
+        }
+        (FieldType::U16, ByteOrder::Big) => {
+            u16::from_be_bytes(raw.try_into().unwrap())
+                as i64
+        }
+        (FieldType::U32, ByteOrder::Little) => {
+            u32::from_le_bytes(raw.try_into().unwrap())
+                as i64
+        }
+        (FieldType::U32, ByteOrder::Big) => {
+            u32::from_be_bytes(raw.try_into().unwrap())
+                as i64
+        }
+        (FieldType::I32, ByteOrder::Little) => {
+            i32::from_le_bytes(raw.try_into().unwrap())
+                as i64
+        }
+        (FieldType::I32, ByteOrder::Big) => {
+            i32::from_be_bytes(raw.try_into().unwrap())
+                as i64
+        }
+    }
+}
+
+#[test]
+fn test_file_header_layout_matches_encoding() {
+    let versions = [
+        FormatVersion::V2_4,
+        FormatVersion::V2_5,
+        FormatVersion::V2_6,
+    ];
+    for order in [ByteOrder::Little, ByteOrder::Big] {
+        for version in versions {
+            let header = PcapFileHeader::for_version(
+                version,
+                -28_800,
+                LinkType::Can,
+            )
+            .with_byte_order(order);
+            let header = if version == FormatVersion::V2_6 {
+                header.with_extensions(vec![
+                    HeaderExtension::Creator(
+                        "layout".to_string(),
+                    ),
+                ])
+            } else {
+                header
+            };
+            let bytes = header.to_bytes();
+            let minor = version.minor_version();
+            let fixed = format::file_header_size(minor);
+            assert!(header.header_size() >= fixed);
+
+            let fields: Vec<&FieldLayout> =
+                format::file_header_fields(minor).collect();
+            assert_eq!(
+                fields.last().map(|field| field.end()),
+                Some(fixed)
+            );
+            let value = |name: &str| {
+                let field = format::find_field(
+                    FILE_HEADER_FIELDS,
+                    name,
+                )
+                .expect("字段不存在");
+                assert!(field.present_in(minor));
+                read_field(&bytes, field, order)
+            };
+            assert_eq!(
+                value("magic_number"),
+                PcapFileHeader::MAGIC_NUMBER as i64
+            );
+            assert_eq!(value("major_version"), 2);
+            assert_eq!(
+                value("minor_version"),
+                minor as i64
+            );
+            assert_eq!(value("timezone_offset"), -28_800);
+            assert_eq!(
+                value("timestamp_accuracy"),
+                PcapFileHeader::DEFAULT_TIMESTAMP_ACCURACY
+                    as i64
+            );
+            if minor >= format::LINK_TYPE_MINOR_VERSION {
+                assert_eq!(
+                    value("link_type"),
+                    LinkType::Can.to_u32() as i64
+                );
+            }
+            if minor == format::EXTENSION_MINOR_VERSION {
+                assert_eq!(
+                    value("extensions_length") as usize,
+                    bytes.len() - fixed
+                );
+            }
+
+            let magic: [u8; 4] =
+                bytes[..4].try_into().unwrap();
+            let expected = match order {
+                ByteOrder::Little => {
+                    format::MAGIC_BYTES_LITTLE_ENDIAN
+                }
+                ByteOrder::Big => {
+                    format::MAGIC_BYTES_BIG_ENDIAN
+                }
+            };
+            assert_eq!(magic, expected);
+        }
+    }
+    assert_eq!(PcapFileHeader::FIELDS, FILE_HEADER_FIELDS);
+}
+
+#[test]
+fn test_packet_and_extension_layout_matches_encoding() {
+    let payload = b"layout";
+    let header = DataPacketHeader::from_timestamp_ns(
+        1_700_000_000_123_456_789,
+        payload,
+    )
+    .expect("创建头部失败");
+    for order in [ByteOrder::Little, ByteOrder::Big] {
+        let bytes = header.to_bytes_with_order(order);
+        assert_eq!(bytes.len(), format::PACKET_HEADER_SIZE);
+        let values: Vec<i64> = DataPacketHeader::FIELDS
+            .iter()
+            .map(|field| read_field(&bytes, field, order))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                1_700_000_000,
+                123_456_789,
+                payload.len() as i64,
+                header.checksum as i64
+            ]
+        );
+    }
+    assert_eq!(
+        DataPacketHeader::FIELDS,
+        PACKET_HEADER_FIELDS
+    );
+
+    // 扩展块紧跟在 2.6 版本文件头的固定部分之后
+    let extension =
+        HeaderExtension::Creator("layout".to_string());
+    let bytes = PcapFileHeader::new(0)
+        .with_extensions(vec![extension.clone()])
+        .to_bytes();
+    let block =
+        &bytes[format::EXTENSIBLE_FILE_HEADER_SIZE..];
+    let fields = HeaderExtension::FIELDS;
+    assert_eq!(
+        read_field(block, &fields[0], ByteOrder::Little),
+        HeaderExtension::CREATOR as i64
+    );
+    assert_eq!(
+        read_field(block, &fields[1], ByteOrder::Little),
+        extension.value().len() as i64
+    );
+    assert_eq!(
+        &block[format::EXTENSION_BLOCK_HEADER_SIZE..],
+        extension.value()
+    );
+}
+
+#[test]
+fn test_layout_serializes_to_json() {
+    let json = serde_json::to_value(FILE_HEADER_FIELDS)
+        .expect("序列化失败");
+    assert_eq!(json[5]["name"], "link_type");
+    assert_eq!(json[5]["offset"], 16);
+    assert_eq!(json[5]["field_type"], "U32");
+    assert_eq!(json[5]["since_minor_version"], 5);
+    assert!(format::find_field(
+        FILE_HEADER_FIELDS,
+        "absent"
+    )
+    .is_none());
+}
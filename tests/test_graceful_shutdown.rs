@@ -0,0 +1,113 @@
+//! 写入器关闭流程测试
+//!
+//! 测试析构时自动完成写入的开关，以及中断信号处理模块完成已登记的写入器
+
+use pcapfile_io::{PcapReader, PcapWriter};
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::create_test_packet;
+
+const TEST_NAME: &str = "shutdown_dataset";
+
+fn write_packets(writer: &mut PcapWriter, count: u32) {
+    for i in 0..count {
+        writer
+            .write_packet(
+                &create_test_packet(i, 64)
+                    .expect("创建测试数据包失败"),
+            )
+            .expect("写入数据包失败");
+    }
+}
+
+fn count_packets(base_path: &Path) -> u64 {
+    let mut reader = PcapReader::new(base_path, TEST_NAME)
+        .expect("创建读取器失败");
+    let mut count = 0;
+    while reader.read_packet().expect("读取失败").is_some()
+    {
+        count += 1;
+    }
+    count
+}
+
+#[test]
+fn test_drop_finalizes_by_default() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let dataset_path = base_path.join(TEST_NAME);
+
+    {
+        let mut writer =
+            PcapWriter::new(base_path, TEST_NAME)
+                .expect("创建写入器失败");
+        assert!(writer.is_finalize_on_drop());
+        write_packets(&mut writer, 20);
+    }
+
+    assert!(dataset_path.join(".pidx").exists());
+    assert_eq!(count_packets(base_path), 20);
+}
+
+#[test]
+fn test_drop_without_finalize_keeps_data() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let dataset_path = base_path.join(TEST_NAME);
+
+    {
+        let mut writer =
+            PcapWriter::new(base_path, TEST_NAME)
+                .expect("创建写入器失败");
+        writer.finalize_on_drop(false);
+        write_packets(&mut writer, 20);
+        assert!(!writer.is_finalized());
+    }
+
+    // 未生成索引，但缓冲区已刷新，读取器重建索引后可以读到全部数据
+    assert!(!dataset_path.join(".pidx").exists());
+    assert!(!dataset_path.join(".progress").exists());
+    assert_eq!(count_packets(base_path), 20);
+}
+
+#[cfg(feature = "signal")]
+#[test]
+fn test_finalize_registered_writers() {
+    use pcapfile_io::signal;
+    use std::sync::{Arc, Mutex};
+
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+
+    let writer = Arc::new(Mutex::new(
+        PcapWriter::new(base_path, TEST_NAME)
+            .expect("创建写入器失败"),
+    ));
+    {
+        let mut guard = writer.lock().expect("获取锁失败");
+        guard.finalize_on_drop(false);
+        write_packets(&mut guard, 10);
+    }
+    signal::finalize_on_interrupt(&writer)
+        .expect("登记写入器失败");
+
+    assert_eq!(signal::finalize_registered(), 1);
+    assert!(writer
+        .lock()
+        .expect("获取锁失败")
+        .is_finalized());
+    assert!(base_path
+        .join(TEST_NAME)
+        .join(".pidx")
+        .exists());
+
+    // 登记表已清空，重复调用不会再次完成
+    assert_eq!(signal::finalize_registered(), 0);
+    drop(writer);
+    assert_eq!(count_packets(base_path), 10);
+}
@@ -0,0 +1,221 @@
+//! 数据包头部校验和测试
+//!
+//! 测试第 3 版记录格式为每个数据包头部追加校验和、读取时透明识别，
+//! 以及头部损坏时的报告、与分块布局组合时跳过损坏的块和旧格式文件的兼容
+
+use pcapfile_io::{
+    validate_file_quick, ByteSize, HeaderExtension,
+    MemoryReader, PcapError, PcapReader, RecordFormat,
+    WriterConfig,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::{data_files, read_all, write_payload_dataset};
+
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+/// 第 3 版记录格式的头部大小
+const CHECKED_HEADER_SIZE: u64 = 20;
+
+fn payload(i: u64) -> Vec<u8> {
+    vec![(i % 251) as u8; 44]
+}
+
+fn checked_config() -> WriterConfig {
+    WriterConfig {
+        max_packets_per_file: 50,
+        record_format: RecordFormat::CheckedHeader,
+        ..Default::default()
+    }
+}
+
+fn write_dataset(
+    base: &Path,
+    name: &str,
+    config: WriterConfig,
+    count: u64,
+) -> Vec<(u64, Vec<u8>)> {
+    write_payload_dataset(
+        base,
+        name,
+        config,
+        (0..count).map(|i| {
+            (BASE_TIMESTAMP + i * 1_000, payload(i))
+        }),
+    )
+}
+
+/// 文件中第 `index` 个数据包记录的起始偏移
+fn record_offset(
+    path: &Path,
+    index: u64,
+    record_size: u64,
+) -> u64 {
+    let structure = validate_file_quick(path).unwrap();
+    structure.data_offset + index * record_size
+}
+
+/// 翻转文件中指定偏移处字节的最低位
+fn flip_bit(path: &Path, offset: u64) {
+    let mut data = fs::read(path).unwrap();
+    data[offset as usize] ^= 0x01;
+    fs::write(path, data).unwrap();
+}
+
+#[test]
+fn test_checked_header_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let expected = write_dataset(
+        base,
+        "checked",
+        checked_config(),
+        120,
+    );
+
+    let mut reader =
+        PcapReader::new(base, "checked").unwrap();
+    assert!(reader.header_extensions().unwrap().contains(
+        &HeaderExtension::RecordFormat(
+            RecordFormat::CheckedHeader.name().to_string()
+        )
+    ));
+    assert_eq!(
+        reader.get_file_info_list().unwrap().len(),
+        3
+    );
+    assert_eq!(read_all(base, "checked"), expected);
+
+    let files = data_files(&base.join("checked"));
+    let structure = validate_file_quick(&files[0]).unwrap();
+    assert_eq!(structure.packet_count, 50);
+    assert_eq!(
+        fs::metadata(&files[0]).unwrap().len(),
+        structure.data_offset
+            + 50 * (CHECKED_HEADER_SIZE + 44)
+    );
+}
+
+#[test]
+fn test_checked_header_seek_and_lookup() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    // 写入时不记录索引，完成写入时重新分析数据文件
+    let config = WriterConfig {
+        index_batch_size: 0,
+        ..checked_config()
+    };
+    let expected =
+        write_dataset(base, "checked", config, 120);
+
+    let mut reader =
+        PcapReader::new(base, "checked").unwrap();
+    reader.seek_to_packet(77).unwrap();
+    for (timestamp_ns, payload) in &expected[77..80] {
+        let packet = reader.read_packet().unwrap().unwrap();
+        assert_eq!(
+            packet.packet.get_timestamp_ns(),
+            *timestamp_ns
+        );
+        assert_eq!(&packet.packet.data, payload);
+    }
+
+    let (timestamp_ns, payload) = &expected[105];
+    let packet = reader
+        .read_packet_by_timestamp(*timestamp_ns)
+        .unwrap()
+        .expect("应找到数据包");
+    assert_eq!(&packet.packet.data, payload);
+}
+
+#[test]
+fn test_corrupted_length_detected_at_header() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    write_dataset(base, "checked", checked_config(), 50);
+    let files = data_files(&base.join("checked"));
+
+    // 第 10 个数据包的负载长度字段翻转一位，仍在文件范围内
+    let offset = record_offset(
+        &files[0],
+        10,
+        CHECKED_HEADER_SIZE + 44,
+    );
+    flip_bit(&files[0], offset + 8);
+
+    let error = validate_file_quick(&files[0]).unwrap_err();
+    assert!(
+        matches!(
+            error,
+            PcapError::CorruptedData { position, .. }
+                if position == offset
+        ),
+        "应在损坏的头部处报告: {error:?}"
+    );
+
+    let mut reader = MemoryReader::new();
+    reader
+        .push_chunk(&fs::read(&files[0]).unwrap())
+        .unwrap();
+    reader.finish();
+    for _ in 0..10 {
+        assert!(reader.read_packet().unwrap().is_some());
+    }
+    assert!(matches!(
+        reader.read_packet(),
+        Err(PcapError::CorruptedData { .. })
+    ));
+}
+
+#[test]
+fn test_checked_header_with_blocks_skips_corrupted_block() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    // 64 字节的记录，每个 4 KiB 的块容纳 63 个
+    let config = WriterConfig {
+        max_packets_per_file: 200,
+        block_size: ByteSize::kib(4),
+        ..checked_config()
+    };
+    let expected =
+        write_dataset(base, "checked", config, 200);
+    let files = data_files(&base.join("checked"));
+
+    // 第二个块中第 8 个数据包的长度加一后记录仍在块内，只有头部校验和能发现
+    let offset = record_offset(
+        &files[0],
+        7,
+        CHECKED_HEADER_SIZE + 44,
+    ) + 4096;
+    flip_bit(&files[0], offset + 8);
+
+    let packets = read_all(base, "checked");
+    // 损坏的头部之前的数据包正常读取，块中剩余的数据包被跳过
+    let mut survivors = expected[..70].to_vec();
+    survivors.extend_from_slice(&expected[126..]);
+    assert_eq!(packets, survivors);
+}
+
+#[test]
+fn test_standard_files_still_readable() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let expected = write_dataset(
+        base,
+        "standard",
+        WriterConfig::default(),
+        30,
+    );
+
+    // 没有记录格式扩展块的文件按 16 字节头部读取
+    let mut reader =
+        PcapReader::new(base, "standard").unwrap();
+    assert!(reader.header_extensions().unwrap().is_empty());
+    assert_eq!(read_all(base, "standard"), expected);
+    assert_eq!(
+        RecordFormat::from_name("checked-header"),
+        Some(RecordFormat::CheckedHeader)
+    );
+}
@@ -0,0 +1,162 @@
+//! 索引文件校验和测试
+//!
+//! 测试索引文件末尾的摘要在加载时校验，区分内容损坏与索引过时，
+//! 并通过 `rebuild_reason` 报告重新生成索引的原因
+
+use pcapfile_io::{
+    DatasetDoctor, FindingKind, IndexHealthKind,
+    IndexRebuildReason, PcapReader, PcapWriter,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "checksum";
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+/// 以一次写入会话向数据集追加一个文件
+fn write_session(base: &Path, offsets: &[u64]) {
+    let mut writer = PcapWriter::new(base, TEST_NAME)
+        .expect("创建写入器失败");
+    for offset in offsets {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + offset,
+                &offset.to_le_bytes(),
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+fn open(base: &Path) -> PcapReader {
+    let mut reader =
+        PcapReader::new(base, TEST_NAME).expect("打开失败");
+    reader.initialize().expect("初始化失败");
+    reader
+}
+
+/// 改写第一个数据包时间戳的最后一位，XML 仍然有效
+fn tamper(content: &str) -> String {
+    let start = content
+        .find("timestamp_ns=\"")
+        .expect("未找到时间戳")
+        + "timestamp_ns=\"".len();
+    let end = start
+        + content[start..].find('"').expect("属性未结束");
+    let digit = content.as_bytes()[end - 1];
+    let replacement = if digit == b'9' { '8' } else { '9' };
+    format!(
+        "{}{replacement}{}",
+        &content[..end - 1],
+        &content[end..]
+    )
+}
+
+#[test]
+fn test_rebuild_reasons() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path();
+    let pidx_path = base.join(TEST_NAME).join(".pidx");
+    write_session(base, &[0, 10, 20]);
+
+    let content =
+        fs::read_to_string(&pidx_path).expect("读取失败");
+    assert!(
+        content.trim_end().ends_with("-->"),
+        "{content}"
+    );
+    assert!(content.contains("<!-- pidx-sha256:"));
+
+    // 写入器生成的索引直接使用
+    let reader = open(base);
+    assert_eq!(reader.index().rebuild_reason(), None);
+    assert!(reader.index().health().is_healthy());
+
+    // 数据集追加文件后索引过时，重新生成但不隔离
+    write_session(base, &[30, 40]);
+    fs::write(&pidx_path, &content).expect("写入失败");
+    let reader = open(base);
+    assert_eq!(
+        reader.index().rebuild_reason(),
+        Some(&IndexRebuildReason::Stale)
+    );
+    assert!(reader.index().health().is_healthy());
+    assert_eq!(reader.total_packets(), Some(5));
+    let reader = open(base);
+    assert_eq!(reader.index().rebuild_reason(), None);
+
+    fs::remove_file(&pidx_path).expect("删除失败");
+    let mut reader = open(base);
+    assert_eq!(
+        reader.index().rebuild_reason(),
+        Some(&IndexRebuildReason::Missing)
+    );
+    reader.index_mut().rebuild_index().expect("重建失败");
+    assert_eq!(
+        reader.index().rebuild_reason(),
+        Some(&IndexRebuildReason::Forced)
+    );
+}
+
+#[test]
+fn test_checksum_mismatch_is_corruption() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base = temp_dir.path();
+    let pidx_path = base.join(TEST_NAME).join(".pidx");
+    write_session(base, &[0, 10, 20]);
+
+    let content =
+        fs::read_to_string(&pidx_path).expect("读取失败");
+    fs::write(&pidx_path, tamper(&content))
+        .expect("写入失败");
+
+    let report = DatasetDoctor::check(base, TEST_NAME)
+        .expect("检查失败");
+    let findings: Vec<_> = report
+        .findings_of(FindingKind::IndexCorrupted)
+        .collect();
+    assert_eq!(findings.len(), 1, "{report:?}");
+    assert!(
+        findings[0].message.contains("校验和"),
+        "{}",
+        findings[0].message
+    );
+
+    let mut reader = open(base);
+    match reader.index().rebuild_reason() {
+        Some(IndexRebuildReason::Corrupted(problem)) => {
+            assert!(problem.contains("校验和"), "{problem}")
+        }
+        other => panic!("应报告索引损坏: {other:?}"),
+    }
+    let health = reader.index().health();
+    assert!(health.has(&IndexHealthKind::CorruptedIndex));
+    let quarantined = health.entries()[0]
+        .path
+        .clone()
+        .expect("应记录隔离文件路径");
+    assert!(quarantined.exists());
+    let mut count = 0;
+    while reader.read_packet().expect("读取失败").is_some()
+    {
+        count += 1;
+    }
+    assert_eq!(count, 3);
+
+    // 没有校验和的旧版索引跳过校验
+    let content =
+        fs::read_to_string(&pidx_path).expect("读取失败");
+    let (body, _) = content
+        .split_once("\n<!-- pidx-sha256:")
+        .expect("索引文件应带有校验和");
+    fs::write(&pidx_path, body).expect("写入失败");
+    let reader = open(base);
+    assert_eq!(reader.index().rebuild_reason(), None);
+    assert!(reader.index().health().is_healthy());
+    assert!(DatasetDoctor::check(base, TEST_NAME)
+        .expect("检查失败")
+        .is_healthy());
+}
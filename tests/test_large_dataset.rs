@@ -3,7 +3,8 @@
 //! 测试大规模数据（10万个数据包）的写入读取功能和性能
 
 use pcapfile_io::{
-    PcapReader, PcapResult, PcapWriter, WriterConfig,
+    ByteSize, PcapReader, PcapResult, PcapWriter,
+    WriterConfig,
 };
 use std::path::Path;
 use std::time::Instant;
@@ -22,7 +23,7 @@ fn write_large_dataset(
     let config = WriterConfig {
         max_packets_per_file: 2000,
         auto_flush: false,
-        buffer_size: 64 * 1024, // 64KB
+        buffer_size: ByteSize::kib(64),
         index_cache_size: 5000,
         ..Default::default()
     }; // 使用高性能配置
@@ -169,7 +170,7 @@ fn test_large_dataset_memory_usage() {
     let config = WriterConfig {
         max_packets_per_file: 100,
         auto_flush: true,
-        buffer_size: 2048, // 2KB
+        buffer_size: ByteSize::kib(2),
         index_cache_size: 100,
         ..Default::default()
     };
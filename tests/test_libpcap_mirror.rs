@@ -0,0 +1,171 @@
+//! libpcap 副本测试
+//!
+//! 测试写入器在写入数据集的同时生成标准 libpcap 文件，
+//! 以及副本文件无法创建时数据集写入不受影响
+
+use pcapfile_io::{
+    LinkType, PcapReader, PcapWriter, WriterConfig,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "mirror";
+const BASE_TIMESTAMP: u64 = 1_700_000_000_123_456_789;
+
+/// 解析 libpcap 文件，返回链路类型和各数据包的时间戳与负载
+fn parse_libpcap(
+    path: &Path,
+) -> (u32, Vec<(u64, Vec<u8>)>) {
+    let bytes = fs::read(path).expect("读取副本失败");
+    let u32_at = |offset: usize| {
+        u32::from_le_bytes(
+            bytes[offset..offset + 4].try_into().unwrap(),
+        )
+    };
+    assert_eq!(u32_at(0), 0xa1b2_3c4d);
+    let link_type = u32_at(20);
+
+    let mut packets = Vec::new();
+    let mut offset = 24;
+    while offset < bytes.len() {
+        let timestamp_ns = u64::from(u32_at(offset))
+            * 1_000_000_000
+            + u64::from(u32_at(offset + 4));
+        let length = u32_at(offset + 8) as usize;
+        offset += 16;
+        packets.push((
+            timestamp_ns,
+            bytes[offset..offset + length].to_vec(),
+        ));
+        offset += length;
+    }
+    (link_type, packets)
+}
+
+fn write_dataset(
+    base: &Path,
+    mirror: PathBuf,
+) -> Vec<(u64, Vec<u8>)> {
+    let config = WriterConfig {
+        max_packets_per_file: 4,
+        link_type: LinkType::Ethernet,
+        mirror_libpcap: Some(mirror),
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base, TEST_NAME, config,
+    )
+    .expect("创建写入器失败");
+    let mut expected = Vec::new();
+    for i in 0..10u64 {
+        let timestamp_ns = BASE_TIMESTAMP + i * 1_000;
+        let payload = vec![i as u8; 8 + i as usize];
+        writer
+            .write_payload(timestamp_ns, &payload)
+            .expect("写入负载失败");
+        expected.push((timestamp_ns, payload));
+    }
+    // 心跳包不写入副本
+    writer
+        .write_heartbeat(BASE_TIMESTAMP + 20_000)
+        .expect("写入心跳包失败");
+    writer.finalize().expect("完成写入失败");
+    expected
+}
+
+#[test]
+fn test_mirror_writes_standard_pcap() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let mirror_path = temp_dir.path().join("live.pcap");
+    let expected =
+        write_dataset(temp_dir.path(), mirror_path.clone());
+
+    let (link_type, packets) = parse_libpcap(&mirror_path);
+    assert_eq!(link_type, 1);
+    assert_eq!(packets, expected);
+
+    // 数据集仍是主存储，包含全部数据包
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    let info = reader.get_dataset_info().expect("读取失败");
+    assert_eq!(info.total_packets, 11);
+    assert_eq!(info.file_count, 3);
+}
+
+#[test]
+fn test_mirror_flushed_while_recording() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let mirror_path = temp_dir.path().join("live.pcap");
+    let config = WriterConfig {
+        mirror_libpcap: Some(mirror_path.clone()),
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("创建写入器失败");
+    assert!(writer.libpcap_mirror().is_none());
+
+    writer
+        .write_payload(BASE_TIMESTAMP, b"first")
+        .expect("写入负载失败");
+    let mirror =
+        writer.libpcap_mirror().expect("副本未启用");
+    assert_eq!(mirror.path(), mirror_path.as_path());
+    assert_eq!(mirror.packet_count(), 1);
+
+    // 自动刷新时记录期间即可读取副本
+    let (_, packets) = parse_libpcap(&mirror_path);
+    assert_eq!(
+        packets,
+        vec![(BASE_TIMESTAMP, b"first".to_vec())]
+    );
+    writer.finalize().expect("完成写入失败");
+}
+
+#[test]
+fn test_mirror_failure_does_not_stop_recording() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let mirror_path =
+        temp_dir.path().join("absent").join("live.pcap");
+    let expected =
+        write_dataset(temp_dir.path(), mirror_path.clone());
+    assert!(!mirror_path.exists());
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    let mut count = 0;
+    while let Some(packet) =
+        reader.read_packet().expect("读取失败")
+    {
+        if count < expected.len() {
+            assert_eq!(
+                packet.get_timestamp_ns(),
+                expected[count].0
+            );
+        }
+        count += 1;
+    }
+    assert_eq!(count, expected.len() + 1);
+}
+
+#[test]
+fn test_mirror_config_validation() {
+    let config = WriterConfig {
+        mirror_libpcap: Some(PathBuf::new()),
+        ..Default::default()
+    };
+    let error = config.validate().expect_err("路径为空");
+    assert!(
+        error.starts_with("mirror_libpcap:"),
+        "{error}"
+    );
+}
@@ -0,0 +1,131 @@
+//! 内存字节流读取测试
+//!
+//! 测试从完整字节数组和分块追加的字节流中解析数据包
+
+use pcapfile_io::{
+    DataPacket, LinkType, MemoryReader, PcapError,
+    PcapWriter, WriterConfig,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const BASE_SECONDS: u32 = 1_700_000_000;
+const PACKET_COUNT: usize = 20;
+
+/// 写入单文件数据集并返回文件内容
+fn write_file_bytes(base_path: &Path) -> Vec<u8> {
+    let config = WriterConfig {
+        link_type: LinkType::Udp,
+        ..WriterConfig::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base_path, "memory", config,
+    )
+    .expect("创建写入器失败");
+    for index in 0..PACKET_COUNT {
+        let packet = DataPacket::from_timestamp(
+            BASE_SECONDS,
+            index as u32,
+            vec![index as u8; index + 1],
+        )
+        .expect("创建数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let file = fs::read_dir(base_path.join("memory"))
+        .expect("读取目录失败")
+        .map(|entry| entry.expect("读取目录项失败").path())
+        .find(|path| {
+            path.extension().is_some_and(|extension| {
+                extension == "pcap"
+            })
+        })
+        .expect("应存在数据文件");
+    fs::read(file).expect("读取文件失败")
+}
+
+#[test]
+fn test_memory_reader_from_bytes_and_chunks() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let bytes = write_file_bytes(temp_dir.path());
+
+    let mut reader =
+        MemoryReader::from_bytes(bytes.clone())
+            .expect("创建内存读取器失败");
+    assert_eq!(reader.link_type(), LinkType::Udp);
+    let packets: Vec<_> = reader
+        .by_ref()
+        .map(|packet| packet.expect("读取数据包失败"))
+        .collect();
+    assert_eq!(packets.len(), PACKET_COUNT);
+    assert!(packets.iter().all(|packet| packet.is_valid()));
+    assert_eq!(reader.position(), bytes.len() as u64);
+
+    // 按 7 字节分块追加，结果与一次性读取相同
+    let mut chunked = MemoryReader::new();
+    let mut chunked_packets = Vec::new();
+    for chunk in bytes.chunks(7) {
+        chunked.push_chunk(chunk).expect("追加数据块失败");
+        while let Some(packet) =
+            chunked.read_packet().expect("读取数据包失败")
+        {
+            chunked_packets.push(packet);
+        }
+    }
+    chunked.finish();
+    assert!(chunked
+        .read_packet()
+        .expect("读取失败")
+        .is_none());
+    assert_eq!(chunked.buffered_bytes(), 0);
+    assert_eq!(chunked_packets.len(), PACKET_COUNT);
+    for (expected, actual) in
+        packets.iter().zip(&chunked_packets)
+    {
+        assert_eq!(
+            expected.packet.data,
+            actual.packet.data
+        );
+        assert_eq!(
+            expected.get_timestamp_ns(),
+            actual.get_timestamp_ns()
+        );
+    }
+    assert!(chunked.push_chunk(&[0]).is_err());
+}
+
+#[test]
+fn test_memory_reader_rejects_bad_streams() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let bytes = write_file_bytes(temp_dir.path());
+
+    // 最后一个数据包的负载被截断
+    let mut truncated =
+        MemoryReader::from_bytes(&bytes[..bytes.len() - 3])
+            .expect("创建内存读取器失败");
+    let result = truncated.read_packets(PACKET_COUNT);
+    assert!(matches!(
+        result,
+        Err(
+            PcapError::PacketSizeExceedsRemainingBytes { .. }
+        )
+    ));
+    assert_eq!(
+        truncated.packet_count(),
+        PACKET_COUNT as u64 - 1
+    );
+
+    let mut corrupted = bytes.clone();
+    corrupted[0] ^= 0xFF;
+    assert!(matches!(
+        MemoryReader::from_bytes(corrupted),
+        Err(PcapError::CorruptedHeader(_))
+    ));
+    assert!(MemoryReader::from_bytes(&bytes[..8]).is_err());
+}
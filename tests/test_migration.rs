@@ -0,0 +1,209 @@
+//! 数据集迁移测试
+//!
+//! 构造 2.4 版本文件头和旧结构版本索引的数据集，验证读取器仍能读取，
+//! 并通过原地迁移和复制迁移升级到新版本后内容保持不变。
+
+use pcapfile_io::{
+    validate_file_quick, ByteOrder, DatasetMigrator,
+    FormatVersion, LinkType, PcapError, PcapReader,
+    PidxIndex, WriterConfig,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::{data_files, read_all, write_payload_dataset};
+
+const PACKET_COUNT: u64 = 30;
+
+/// 写入 2.4 版本的数据集，并把索引改写为没有版本属性和序号的旧结构
+fn write_legacy_dataset(base_path: &Path, name: &str) {
+    write_payload_dataset(
+        base_path,
+        name,
+        WriterConfig::default(),
+        (0..PACKET_COUNT).map(|i| {
+            (
+                1_700_000_000_000_000_000 + i * 1_000,
+                vec![i as u8; 12],
+            )
+        }),
+    );
+
+    let pidx_path = base_path.join(name).join(".pidx");
+    let content = fs::read_to_string(&pidx_path)
+        .expect("读取索引失败");
+    // 旧结构的索引没有末尾的校验和注释
+    let (xml, _) = content
+        .split_once("\n<!-- pidx-sha256:")
+        .expect("索引文件应带有校验和");
+    let legacy = xml
+        .replace(
+            &format!(
+                " schema_version=\"{}\"",
+                PidxIndex::SCHEMA_VERSION
+            ),
+            "",
+        )
+        .split(" index=\"")
+        .enumerate()
+        .map(|(i, part)| {
+            if i == 0 {
+                part.to_string()
+            } else {
+                // 去掉 index 和 global_index 属性
+                let rest = part
+                    .split_once("\" global_index=\"")
+                    .and_then(|(_, rest)| {
+                        rest.split_once('"')
+                    })
+                    .map(|(_, rest)| rest)
+                    .expect("索引条目格式不符");
+                rest.to_string()
+            }
+        })
+        .collect::<String>();
+    assert!(!legacy.contains("global_index"));
+    fs::write(&pidx_path, legacy).expect("写入索引失败");
+}
+
+#[test]
+fn test_format_version() {
+    assert_eq!(FormatVersion::LATEST, FormatVersion::V2_6);
+    assert!(FormatVersion::V2_4 < FormatVersion::V2_5);
+    assert!(FormatVersion::V2_6.supports_extensions());
+    assert!(!FormatVersion::V2_5.supports_extensions());
+    assert_eq!(
+        FormatVersion::from_version(2, 4),
+        Some(FormatVersion::V2_4)
+    );
+    assert_eq!(FormatVersion::from_version(2, 9), None);
+    assert_eq!(FormatVersion::V2_5.to_string(), "2.5");
+    assert_eq!(FormatVersion::V2_4.header_size(), 16);
+    assert_eq!(FormatVersion::V2_5.header_size(), 20);
+    assert_eq!(FormatVersion::V2_6.header_size(), 24);
+}
+
+#[test]
+fn test_migrate_in_place() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    write_legacy_dataset(base_path, "legacy");
+    let dataset_path = base_path.join("legacy");
+
+    // 读取器直接支持旧版本文件头和旧结构索引
+    let expected = read_all(base_path, "legacy");
+    assert_eq!(expected.len(), PACKET_COUNT as usize);
+    for file in data_files(&dataset_path) {
+        let structure =
+            validate_file_quick(&file).expect("检查失败");
+        assert_eq!(
+            structure.header.format_version(),
+            Some(FormatVersion::V2_4)
+        );
+    }
+
+    let migrator = DatasetMigrator::new()
+        .with_link_type(LinkType::Ethernet);
+    let report = migrator
+        .migrate_in_place(&dataset_path)
+        .expect("迁移失败");
+    assert_eq!(report.files_examined, 1);
+    assert_eq!(report.files_migrated, 1);
+    assert_eq!(report.packets_migrated, PACKET_COUNT);
+    assert_eq!(
+        report.previous_schema_version,
+        Some(PidxIndex::LEGACY_SCHEMA_VERSION)
+    );
+    assert!(report.index_rebuilt);
+
+    for file in data_files(&dataset_path) {
+        let structure =
+            validate_file_quick(&file).expect("检查失败");
+        assert_eq!(
+            structure.header.format_version(),
+            Some(FormatVersion::LATEST)
+        );
+        assert_eq!(
+            structure.header.link_type,
+            LinkType::Ethernet
+        );
+    }
+    assert_eq!(read_all(base_path, "legacy"), expected);
+
+    let mut reader = PcapReader::new(base_path, "legacy")
+        .expect("打开失败");
+    let index = reader
+        .index_mut()
+        .ensure_index()
+        .expect("加载索引失败");
+    assert_eq!(
+        index.schema_version(),
+        PidxIndex::SCHEMA_VERSION
+    );
+    assert!(!index.is_legacy_schema());
+    drop(reader);
+
+    // 已是目标版本时不做改动
+    let report = migrator
+        .migrate_in_place(&dataset_path)
+        .expect("迁移失败");
+    assert!(report.is_up_to_date());
+}
+
+#[test]
+fn test_migrate_to_copy() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    write_legacy_dataset(base_path, "legacy");
+    let expected = read_all(base_path, "legacy");
+    let source_bytes: Vec<Vec<u8>> =
+        data_files(&base_path.join("legacy"))
+            .iter()
+            .map(|file| fs::read(file).expect("读取失败"))
+            .collect();
+
+    let report = DatasetMigrator::new()
+        .with_byte_order(ByteOrder::Big)
+        .migrate_to_copy(
+            base_path.join("legacy"),
+            base_path.join("upgraded"),
+        )
+        .expect("迁移失败");
+    assert_eq!(report.files_migrated, 1);
+    assert!(report.index_rebuilt);
+
+    for file in data_files(&base_path.join("upgraded")) {
+        let structure =
+            validate_file_quick(&file).expect("检查失败");
+        assert_eq!(
+            structure.header.format_version(),
+            Some(FormatVersion::LATEST)
+        );
+        assert_eq!(
+            structure.header.byte_order,
+            ByteOrder::Big
+        );
+    }
+    assert_eq!(read_all(base_path, "upgraded"), expected);
+
+    // 源数据集保持不变
+    let unchanged: Vec<Vec<u8>> =
+        data_files(&base_path.join("legacy"))
+            .iter()
+            .map(|file| fs::read(file).expect("读取失败"))
+            .collect();
+    assert_eq!(unchanged, source_bytes);
+
+    // 目标数据集不为空时拒绝迁移
+    assert!(matches!(
+        DatasetMigrator::new().migrate_to_copy(
+            base_path.join("legacy"),
+            base_path.join("upgraded"),
+        ),
+        Err(PcapError::InvalidArgument(_))
+    ));
+}
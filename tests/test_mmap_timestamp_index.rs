@@ -0,0 +1,223 @@
+//! 内存映射时间戳表测试
+//!
+//! 测试磁盘时间戳表的查找结果与内存索引一致、读取器通过时间戳表定位、
+//! 数据集变化后重新生成过期的表，以及拒绝损坏的表文件
+
+#![cfg(feature = "mmap-index")]
+
+use pcapfile_io::{
+    MmapTimestampIndex, PcapError, PcapReader, PcapWriter,
+    ReaderConfig, SeekMode, WriterConfig,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "mmap";
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+/// 写入跨多个文件、包含重复时间戳和时间间隔的数据集
+fn write_dataset(base: &Path, offsets: &[u64]) {
+    let config = WriterConfig {
+        max_packets_per_file: 4,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base, TEST_NAME, config,
+    )
+    .expect("创建写入器失败");
+    for (i, offset) in offsets.iter().enumerate() {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + offset,
+                &[i as u8; 8],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+const OFFSETS: [u64; 10] =
+    [0, 10, 10, 10, 20, 50, 50, 60, 100, 100];
+
+const MODES: [SeekMode; 4] = [
+    SeekMode::Exact,
+    SeekMode::Floor,
+    SeekMode::Ceiling,
+    SeekMode::Nearest,
+];
+
+#[test]
+fn test_table_matches_memory_index() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path(), &OFFSETS);
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    reader.initialize().expect("初始化失败");
+    let index =
+        reader.index().get_index().expect("索引未加载");
+
+    let path = temp_dir.path().join("table.ptidx");
+    MmapTimestampIndex::write(index, &path)
+        .expect("生成时间戳表失败");
+    let table =
+        MmapTimestampIndex::open(&path).expect("打开失败");
+    assert_eq!(table.len(), OFFSETS.len());
+    assert!(table.is_current(index));
+    assert_eq!(table.path(), path);
+
+    for mode in MODES {
+        for offset in
+            [0, 5, 10, 15, 35, 50, 55, 99, 100, 200]
+        {
+            let ts = BASE_TIMESTAMP + offset;
+            let expected = index
+                .find_packet_with_mode(ts, mode)
+                .map(|pointer| {
+                    (
+                        pointer.file_index as u32,
+                        pointer.entry.byte_offset,
+                    )
+                });
+            let actual = table
+                .find_with_mode(ts, mode)
+                .map(|record| {
+                    (record.file_index, record.byte_offset)
+                });
+            assert_eq!(
+                actual, expected,
+                "{mode:?} {offset}"
+            );
+        }
+    }
+    assert!(table
+        .find_with_mode(BASE_TIMESTAMP - 1, SeekMode::Floor)
+        .is_none());
+
+    let expected: Vec<u64> = index
+        .get_packets_in_range(
+            BASE_TIMESTAMP + 10,
+            BASE_TIMESTAMP + 60,
+        )
+        .iter()
+        .map(|pointer| pointer.entry.global_index)
+        .collect();
+    let actual: Vec<u64> = table
+        .range(BASE_TIMESTAMP + 10, BASE_TIMESTAMP + 60)
+        .map(|record| record.global_index)
+        .collect();
+    assert_eq!(actual, expected);
+    assert_eq!(actual, vec![1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(table.range(u64::MAX, 0).count(), 0);
+}
+
+#[test]
+fn test_reader_seeks_with_table() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path(), &OFFSETS);
+
+    let config = ReaderConfig {
+        mmap_timestamp_index: true,
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config.clone(),
+    )
+    .expect("打开失败");
+    reader.initialize().expect("初始化失败");
+    assert!(reader
+        .index()
+        .get_index()
+        .expect("索引未加载")
+        .is_sparse());
+
+    // 重复时间戳定位到写入顺序最早的数据包
+    let packet = reader
+        .read_packet_by_timestamp_with_mode(
+            BASE_TIMESTAMP + 12,
+            SeekMode::Floor,
+        )
+        .expect("读取失败")
+        .expect("未找到数据包");
+    assert_eq!(packet.packet.data[0], 1);
+    let table_path = reader.index().timestamp_table_path();
+    assert!(table_path.exists());
+
+    let position = reader
+        .seek_to_timestamp_with_mode(
+            BASE_TIMESTAMP + 56,
+            SeekMode::Nearest,
+        )
+        .expect("定位失败");
+    assert_eq!(position, BASE_TIMESTAMP + 60);
+    let packet = reader
+        .read_packet()
+        .expect("读取失败")
+        .expect("没有数据包");
+    assert_eq!(packet.packet.data[0], 7);
+    let pointer = reader
+        .seek_by_timestamp(BASE_TIMESTAMP + 98)
+        .expect("定位失败")
+        .expect("未找到数据包");
+    assert_eq!(pointer.entry.global_index, 8);
+
+    // 数据集追加文件后重新生成过期的表
+    drop(reader);
+    write_dataset(temp_dir.path(), &[200, 230, 230]);
+    let mut reader = PcapReader::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("打开失败");
+    let packet = reader
+        .read_packet_by_timestamp_with_mode(
+            BASE_TIMESTAMP + 230,
+            SeekMode::Exact,
+        )
+        .expect("读取失败")
+        .expect("未找到数据包");
+    assert_eq!(packet.packet.data[0], 1);
+    let table = reader
+        .index_mut()
+        .timestamp_table()
+        .expect("打开时间戳表失败")
+        .expect("时间戳表不可用");
+    assert_eq!(table.len(), OFFSETS.len() + 3);
+}
+
+#[test]
+fn test_rejects_corrupted_table() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let path = temp_dir.path().join("broken.ptidx");
+    fs::write(&path, b"not a timestamp table")
+        .expect("写入文件失败");
+    let error = MmapTimestampIndex::open(&path)
+        .expect_err("应拒绝损坏的表");
+    assert!(
+        matches!(error, PcapError::InvalidFormat(_)),
+        "{error}"
+    );
+
+    // 条目数量与文件大小不一致
+    write_dataset(temp_dir.path(), &OFFSETS);
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    reader.initialize().expect("初始化失败");
+    let index =
+        reader.index().get_index().expect("索引未加载");
+    MmapTimestampIndex::write(index, &path)
+        .expect("生成时间戳表失败");
+    let mut bytes = fs::read(&path).expect("读取文件失败");
+    bytes.truncate(bytes.len() - 1);
+    fs::write(&path, bytes).expect("写入文件失败");
+    assert!(MmapTimestampIndex::open(&path).is_err());
+}
@@ -0,0 +1,152 @@
+//! 文件时间范围重叠测试
+//!
+//! 测试索引检测时间范围重叠的文件并记录到健康报告、按时间范围分段，
+//! 以及按起始时间戳排序文件修正时钟回拨后的读取顺序
+
+use pcapfile_io::{
+    IndexHealthKind, PcapReader, PcapWriter, ReaderConfig,
+    WriterConfig,
+};
+use std::path::Path;
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "overlap";
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+/// 以一次写入会话向数据集追加一个文件
+fn write_session(base: &Path, offsets: &[u64]) {
+    let mut writer = PcapWriter::new_with_config(
+        base,
+        TEST_NAME,
+        WriterConfig::default(),
+    )
+    .expect("创建写入器失败");
+    for offset in offsets {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + offset,
+                &offset.to_le_bytes(),
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+fn read_offsets(reader: &mut PcapReader) -> Vec<u64> {
+    let mut offsets = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取失败")
+    {
+        offsets.push(
+            packet.get_timestamp_ns() - BASE_TIMESTAMP,
+        );
+    }
+    offsets
+}
+
+#[test]
+fn test_detects_overlapping_files() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_session(temp_dir.path(), &[0, 10, 20]);
+    write_session(temp_dir.path(), &[15, 25, 35]);
+    write_session(temp_dir.path(), &[35, 40]);
+    write_session(temp_dir.path(), &[100, 110]);
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    reader.initialize().expect("初始化失败");
+    let index =
+        reader.index().get_index().expect("索引未加载");
+    // 边界时间戳相同的文件不视为重叠
+    assert_eq!(index.overlapping_files(), vec![(0, 1)]);
+    assert_eq!(
+        index.time_segments(),
+        vec![vec![0, 1], vec![2], vec![3]]
+    );
+
+    let health = reader.index().health();
+    assert!(!health.is_healthy());
+    assert!(health.has(&IndexHealthKind::TimeRangeOverlap));
+    let entries = health.entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        entries[0].path.as_deref(),
+        Some(
+            index.files()[1]
+                .resolve_path(
+                    &temp_dir.path().join(TEST_NAME)
+                )
+                .as_path()
+        )
+    );
+
+    // 重叠段内按时间范围读取得到时间顺序
+    let offsets: Vec<u64> = reader
+        .read_packets_by_time_range(
+            BASE_TIMESTAMP,
+            BASE_TIMESTAMP + 35,
+        )
+        .expect("读取失败")
+        .iter()
+        .map(|packet| {
+            packet.get_timestamp_ns() - BASE_TIMESTAMP
+        })
+        .collect();
+    assert_eq!(offsets, vec![0, 10, 15, 20, 25, 35, 35]);
+}
+
+#[test]
+fn test_sort_files_by_time_after_clock_reset() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    // 第二次会话前系统时钟回拨，文件名较晚但时间戳较早
+    write_session(temp_dir.path(), &[100, 110, 120]);
+    write_session(temp_dir.path(), &[0, 10, 20]);
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    assert_eq!(
+        read_offsets(&mut reader),
+        vec![100, 110, 120, 0, 10, 20]
+    );
+    // 时间范围不重叠，健康报告没有问题
+    assert!(reader.index().health().is_healthy());
+
+    let config = ReaderConfig {
+        sort_files_by_time: true,
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("打开失败");
+    assert_eq!(
+        read_offsets(&mut reader),
+        vec![0, 10, 20, 100, 110, 120]
+    );
+    let index =
+        reader.index().get_index().expect("索引未加载");
+    assert_eq!(
+        index.files()[0].start_timestamp,
+        BASE_TIMESTAMP
+    );
+    assert_eq!(
+        index.time_segments(),
+        vec![vec![0], vec![1]]
+    );
+
+    // 排序只影响内存索引，重新生成的索引文件仍按文件名排列
+    reader.index_mut().rebuild_index().expect("重建失败");
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    assert_eq!(
+        read_offsets(&mut reader),
+        vec![100, 110, 120, 0, 10, 20]
+    );
+}
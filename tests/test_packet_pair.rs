@@ -0,0 +1,98 @@
+//! 相邻数据包测试
+//!
+//! 测试 `packet_pair_around` 返回目标时间戳前后相邻的数据包、时间偏移和插值系数，
+//! 包括跨文件、精确命中和超出数据集范围的情况
+
+use pcapfile_io::{
+    PcapReader, PcapWriter, ValidatedPacket, WriterConfig,
+};
+use std::path::Path;
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "packet_pair";
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+/// 每 100 纳秒一个采样，负载为采样序号
+fn write_samples(base: &Path, count: u64) {
+    let config = WriterConfig {
+        max_packets_per_file: 3,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base, TEST_NAME, config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..count {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + i * 100,
+                &[i as u8; 4],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+fn sample(packet: &Option<ValidatedPacket>) -> Option<u8> {
+    packet.as_ref().map(|p| p.packet.data[0])
+}
+
+#[test]
+fn test_packet_pair_between_samples() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_samples(temp_dir.path(), 8);
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+
+    // 第 2 和第 3 个采样分别位于两个文件中
+    let pair = reader
+        .packet_pair_around(BASE_TIMESTAMP + 225)
+        .expect("读取失败");
+    assert_eq!(sample(&pair.before), Some(2));
+    assert_eq!(sample(&pair.after), Some(3));
+    assert_eq!(pair.before_offset_ns(), Some(25));
+    assert_eq!(pair.after_offset_ns(), Some(75));
+    assert_eq!(pair.interpolation_factor(), Some(0.25));
+
+    // 精确命中时该采样作为前一个数据包
+    let pair = reader
+        .packet_pair_around(BASE_TIMESTAMP + 400)
+        .expect("读取失败");
+    assert_eq!(sample(&pair.before), Some(4));
+    assert_eq!(sample(&pair.after), Some(5));
+    assert_eq!(pair.interpolation_factor(), Some(0.0));
+}
+
+#[test]
+fn test_packet_pair_outside_dataset() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_samples(temp_dir.path(), 4);
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+
+    let pair = reader
+        .packet_pair_around(BASE_TIMESTAMP - 1)
+        .expect("读取失败");
+    assert!(pair.before.is_none());
+    assert_eq!(sample(&pair.after), Some(0));
+    assert_eq!(pair.after_offset_ns(), Some(1));
+    assert_eq!(pair.interpolation_factor(), None);
+
+    let pair = reader
+        .packet_pair_around(BASE_TIMESTAMP + 300)
+        .expect("读取失败");
+    assert_eq!(sample(&pair.before), Some(3));
+    assert!(pair.after.is_none());
+
+    let pair = reader
+        .packet_pair_around(u64::MAX)
+        .expect("读取失败");
+    assert_eq!(sample(&pair.before), Some(3));
+    assert!(pair.after.is_none());
+}
@@ -0,0 +1,140 @@
+//! 组合条件查询测试
+//!
+//! 构造链路类型不同的多个文件，验证组合条件的查询结果与逐包过滤一致，
+//! 并检查查询计划选择的候选来源。
+
+use pcapfile_io::{
+    LinkType, PcapReader, PcapWriter, QueryDriver,
+    WriterConfig,
+};
+use tempfile::TempDir;
+
+const BASE_NS: u64 = 1_700_000_000_000_000_000;
+
+/// 写入一批数据包，第 `i` 个数据包的负载大小为 `16 + (i % 8) * 16`
+fn write_batch(
+    base_path: &std::path::Path,
+    link_type: LinkType,
+    first: u64,
+    count: u64,
+) {
+    let config = WriterConfig {
+        max_packets_per_file: 10,
+        link_type,
+        ..WriterConfig::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base_path, "query", config,
+    )
+    .expect("创建写入器失败");
+    for i in first..first + count {
+        let size = 16 + (i % 8) as usize * 16;
+        writer
+            .write_payload(
+                BASE_NS + i * 1_000,
+                &vec![i as u8; size],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+#[test]
+fn test_packet_query() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    write_batch(base_path, LinkType::Udp, 0, 40);
+    write_batch(base_path, LinkType::Can, 40, 40);
+
+    let mut reader = PcapReader::new(base_path, "query")
+        .expect("打开失败");
+    let mut all = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取失败")
+    {
+        all.push(packet);
+    }
+    assert_eq!(all.len(), 80);
+
+    let start = BASE_NS + 10 * 1_000;
+    let end = BASE_NS + 60 * 1_000;
+    let expected: Vec<u64> = all
+        .iter()
+        .enumerate()
+        .filter(|(i, packet)| {
+            let ts = packet.get_timestamp_ns();
+            let size = packet.packet.data.len();
+            *i < 40
+                && (start..=end).contains(&ts)
+                && (64..=112).contains(&size)
+        })
+        .map(|(_, packet)| packet.get_timestamp_ns())
+        .collect();
+    assert!(!expected.is_empty());
+
+    let packets = reader
+        .query()
+        .time_range(start, end)
+        .size_between(64, 112)
+        .channel(LinkType::Udp.to_u32())
+        .run()
+        .expect("查询失败");
+    let timestamps: Vec<u64> = packets
+        .iter()
+        .map(|packet| packet.get_timestamp_ns())
+        .collect();
+    assert_eq!(timestamps, expected);
+    assert!(packets.iter().all(|packet| packet.is_valid));
+
+    // 数量上限保留全局顺序中最前面的数据包
+    let limited = reader
+        .query()
+        .time_range(start, end)
+        .size_between(64, 112)
+        .channel(LinkType::Udp.to_u32())
+        .limit(2)
+        .run()
+        .expect("查询失败");
+    assert_eq!(limited.len(), 2);
+    assert_eq!(limited[0].get_timestamp_ns(), expected[0]);
+    assert_eq!(limited[1].get_timestamp_ns(), expected[1]);
+
+    // 窄时间范围优先使用时间戳索引
+    let plan = reader
+        .query()
+        .time_range(BASE_NS, BASE_NS + 2_000)
+        .size_between(0, 1_000)
+        .explain()
+        .expect("生成计划失败");
+    assert_eq!(plan.driver, QueryDriver::TimeIndex);
+    assert_eq!(plan.estimated_candidates, 3);
+    assert_eq!(plan.matched, 3);
+
+    // 窄大小范围优先使用大小索引
+    let plan = reader
+        .query()
+        .time_range(BASE_NS, BASE_NS + 79_000)
+        .size_between(16, 16)
+        .explain()
+        .expect("生成计划失败");
+    assert_eq!(plan.driver, QueryDriver::SizeIndex);
+    assert_eq!(plan.matched, 10);
+
+    // 通道条件排除文件
+    let plan = reader
+        .query()
+        .channel(LinkType::Can.to_u32())
+        .explain()
+        .expect("生成计划失败");
+    assert_eq!(plan.driver, QueryDriver::FullScan);
+    assert_eq!(plan.files_pruned, 4);
+    assert_eq!(plan.matched, 40);
+
+    assert!(reader
+        .query()
+        .channel(LinkType::Ethernet.to_u32())
+        .run()
+        .expect("查询失败")
+        .is_empty());
+}
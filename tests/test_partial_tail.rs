@@ -0,0 +1,148 @@
+//! 末尾未写完的数据包容忍测试
+//!
+//! 测试启用 `tolerate_partial_tail` 后，文件末尾不完整的数据包记录视为暂时没有更多数据，
+//! 读取位置停在该记录的起始位置，外部程序写完该记录后再次读取即可读出
+
+use pcapfile_io::{
+    DataPacket, PcapReader, PcapWriter, ReaderConfig,
+    WriterConfig,
+};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const DATASET: &str = "growing";
+const BASE_SECONDS: u32 = 1_700_000_000;
+
+fn write_dataset(base: &Path, count: u32) {
+    let mut writer = PcapWriter::new_with_config(
+        base,
+        DATASET,
+        WriterConfig::default(),
+    )
+    .expect("创建写入器失败");
+    for i in 0..count {
+        writer
+            .write_packet(&record_packet(i))
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+fn record_packet(i: u32) -> DataPacket {
+    DataPacket::from_timestamp(
+        BASE_SECONDS + i,
+        0,
+        vec![i as u8; 64],
+    )
+    .unwrap()
+}
+
+fn data_file(base: &Path) -> PathBuf {
+    fs::read_dir(base.join(DATASET))
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "pcap")
+        })
+        .expect("应有数据文件")
+}
+
+/// 模拟外部程序向文件末尾追加字节
+fn append(path: &Path, bytes: &[u8]) {
+    let mut file =
+        OpenOptions::new().append(true).open(path).unwrap();
+    file.write_all(bytes).unwrap();
+}
+
+fn tolerant_reader(base: &Path) -> PcapReader {
+    let config = ReaderConfig {
+        tolerate_partial_tail: true,
+        ..Default::default()
+    };
+    PcapReader::new_with_config(base, DATASET, config)
+        .expect("创建读取器失败")
+}
+
+fn read_until_none(reader: &mut PcapReader) -> Vec<u8> {
+    let mut firsts = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        assert!(packet.is_valid());
+        firsts.push(packet.packet.data[0]);
+    }
+    firsts
+}
+
+#[test]
+fn test_partial_payload_retried_after_completion() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    write_dataset(base, 20);
+    let path = data_file(base);
+
+    // 头部完整、负载只写了一部分
+    let record = record_packet(20).to_bytes();
+    append(&path, &record[..40]);
+
+    let mut reader = tolerant_reader(base);
+    assert_eq!(
+        read_until_none(&mut reader),
+        (0..20).collect::<Vec<u8>>()
+    );
+    assert!(reader.read_packet().unwrap().is_none());
+
+    append(&path, &record[40..]);
+    let packet = reader.read_packet().unwrap().unwrap();
+    assert!(packet.is_valid());
+    assert_eq!(packet.packet.data, vec![20u8; 64]);
+    assert!(reader.read_packet().unwrap().is_none());
+}
+
+#[test]
+fn test_partial_header_retried_after_completion() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    write_dataset(base, 10);
+    let path = data_file(base);
+
+    // 头部只写了一部分
+    let record = record_packet(10).to_bytes();
+    append(&path, &record[..10]);
+
+    let mut reader = tolerant_reader(base);
+    assert_eq!(read_until_none(&mut reader).len(), 10);
+
+    // 写完该记录并继续追加，从不完整记录的起始位置继续读取
+    append(&path, &record[10..]);
+    append(&path, &record_packet(11).to_bytes());
+    assert_eq!(read_until_none(&mut reader), vec![10, 11]);
+}
+
+#[test]
+fn test_tolerance_takes_precedence_over_salvage() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    write_dataset(base, 5);
+    let path = data_file(base);
+    let record = record_packet(5).to_bytes();
+    append(&path, &record[..30]);
+
+    let config = ReaderConfig {
+        tolerate_partial_tail: true,
+        salvage_truncated_packets: true,
+        ..Default::default()
+    };
+    let mut reader =
+        PcapReader::new_with_config(base, DATASET, config)
+            .unwrap();
+    // 不返回截断的负载
+    assert_eq!(read_until_none(&mut reader).len(), 5);
+
+    append(&path, &record[30..]);
+    assert_eq!(read_until_none(&mut reader), vec![5]);
+}
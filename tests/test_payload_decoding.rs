@@ -0,0 +1,172 @@
+//! 负载解码测试
+//!
+//! 测试解码器注册表按链路类型选择解码器，以及内置示例解码器
+
+use pcapfile_io::decode::{
+    LengthPrefix, LengthPrefixedDecoder, UdpDatagram,
+    UdpHeaderStripDecoder,
+};
+use pcapfile_io::{
+    DataPacket, DecoderRegistry, LinkType, PayloadDecoder,
+    PcapError, PcapReader, PcapResult, PcapWriter,
+    WriterConfig,
+};
+use tempfile::TempDir;
+
+/// 自定义解码器：把负载解释为大端 u32 序列
+struct CounterDecoder;
+
+impl PayloadDecoder for CounterDecoder {
+    type Output = Vec<u32>;
+
+    fn decode(
+        &self,
+        packet: &DataPacket,
+    ) -> PcapResult<Vec<u32>> {
+        Ok(packet
+            .data
+            .chunks_exact(4)
+            .map(|chunk| {
+                u32::from_be_bytes([
+                    chunk[0], chunk[1], chunk[2], chunk[3],
+                ])
+            })
+            .collect())
+    }
+}
+
+fn udp_payload(
+    source_port: u16,
+    destination_port: u16,
+    body: &[u8],
+) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&source_port.to_be_bytes());
+    data.extend_from_slice(&destination_port.to_be_bytes());
+    data.extend_from_slice(
+        &((body.len() + 8) as u16).to_be_bytes(),
+    );
+    data.extend_from_slice(&[0, 0]);
+    data.extend_from_slice(body);
+    data
+}
+
+#[test]
+fn test_read_decoded_udp_dataset() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let config = WriterConfig {
+        link_type: LinkType::Udp,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        "udp_dataset",
+        config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..10u16 {
+        let packet = DataPacket::from_timestamp(
+            1_700_000_000,
+            i as u32,
+            udp_payload(5000 + i, 6000, &[i as u8; 4]),
+        )
+        .expect("创建数据包失败");
+        writer.write_packet(&packet).expect("写入失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), "udp_dataset")
+            .expect("创建读取器失败");
+    reader.register_decoder(
+        LinkType::Udp,
+        UdpHeaderStripDecoder,
+    );
+
+    let mut count = 0u16;
+    while let Some(datagram) = reader
+        .read_decoded::<UdpDatagram>()
+        .expect("解码失败")
+    {
+        assert_eq!(datagram.source_port, 5000 + count);
+        assert_eq!(datagram.destination_port, 6000);
+        assert_eq!(datagram.payload, vec![count as u8; 4]);
+        count += 1;
+    }
+    assert_eq!(count, 10);
+
+    // 未登记该输出类型的解码器
+    reader.reset().expect("重置失败");
+    let err = reader
+        .read_decoded::<Vec<Vec<u8>>>()
+        .expect_err("应缺少解码器");
+    assert!(matches!(err, PcapError::DecoderNotFound(_)));
+}
+
+#[test]
+fn test_registry_selection_and_fallback() {
+    let mut registry = DecoderRegistry::new();
+    assert!(registry.is_empty());
+    registry.register(
+        LinkType::Can,
+        LengthPrefixedDecoder::new(LengthPrefix::U8),
+    );
+    registry.register_fallback(CounterDecoder);
+    assert_eq!(registry.len(), 2);
+    assert!(
+        registry.contains::<Vec<Vec<u8>>>(LinkType::Can)
+    );
+    assert!(
+        !registry.contains::<Vec<Vec<u8>>>(LinkType::Udp)
+    );
+    assert!(registry.contains::<Vec<u32>>(LinkType::Udp));
+
+    let packet = DataPacket::from_timestamp(
+        0,
+        0,
+        vec![2, 0xAA, 0xBB, 1, 0xCC],
+    )
+    .expect("创建数据包失败")
+    .with_link_type(LinkType::Can);
+
+    let frames: Vec<Vec<u8>> =
+        registry.decode(&packet).expect("解码失败");
+    assert_eq!(frames, vec![vec![0xAA, 0xBB], vec![0xCC]]);
+
+    let values: Vec<u32> =
+        registry.decode(&packet).expect("解码失败");
+    assert_eq!(values, vec![0x02AA_BB01]);
+}
+
+#[test]
+fn test_builtin_decoder_errors() {
+    let short =
+        DataPacket::from_timestamp(0, 0, vec![0; 4])
+            .expect("创建数据包失败");
+    assert!(matches!(
+        UdpHeaderStripDecoder.decode(&short),
+        Err(PcapError::DecodeFailed(_))
+    ));
+
+    let truncated =
+        DataPacket::from_timestamp(0, 0, vec![0, 5, 1, 2])
+            .expect("创建数据包失败");
+    let decoder =
+        LengthPrefixedDecoder::new(LengthPrefix::U16Be);
+    assert!(matches!(
+        decoder.decode(&truncated),
+        Err(PcapError::DecodeFailed(_))
+    ));
+
+    let framed = DataPacket::from_timestamp(
+        0,
+        0,
+        vec![1, 0, 0, 0, 9, 0, 0, 0, 0],
+    )
+    .expect("创建数据包失败");
+    let frames = LengthPrefixedDecoder::default()
+        .decode(&framed)
+        .expect("解码失败");
+    assert_eq!(frames, vec![vec![9], vec![]]);
+}
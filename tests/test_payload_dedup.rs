@@ -0,0 +1,233 @@
+//! 负载去重存储测试
+//!
+//! 测试启用负载去重后重复负载只保存一次、读取时透明还原，
+//! 以及按位置和时间戳定位、多次写入共享负载存储和配置校验
+
+use pcapfile_io::{
+    HeaderExtension, PcapError, PcapReader, WriterConfig,
+};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+mod common;
+use common::{
+    data_files_size, read_all, write_payload_dataset,
+};
+
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+const PAYLOAD_STORE: &str = ".payloads";
+
+/// 第 `i` 个遥测帧：(时间戳, 三种循环出现的负载之一)
+fn telemetry_frame(i: u64) -> (u64, Vec<u8>) {
+    let kind = (i % 3) as u8;
+    (
+        BASE_TIMESTAMP + i * 1_000,
+        vec![kind; 200 + kind as usize * 50],
+    )
+}
+
+fn dedup_config() -> WriterConfig {
+    WriterConfig {
+        max_packets_per_file: 40,
+        payload_dedup: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_dedup_shrinks_repetitive_dataset() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+
+    write_payload_dataset(
+        base,
+        "plain",
+        WriterConfig {
+            max_packets_per_file: 40,
+            ..Default::default()
+        },
+        (0..120).map(telemetry_frame),
+    );
+    let expected = write_payload_dataset(
+        base,
+        "dedup",
+        dedup_config(),
+        (0..120).map(telemetry_frame),
+    );
+
+    let store_path = base.join("dedup").join(PAYLOAD_STORE);
+    assert!(store_path.is_file(), "应生成负载存储文件");
+    // 三种负载各保存一次
+    let store_size =
+        fs::metadata(&store_path).unwrap().len();
+    assert_eq!(store_size, 3 * 4 + 200 + 250 + 300);
+    assert!(!base
+        .join("plain")
+        .join(PAYLOAD_STORE)
+        .exists());
+
+    let plain_size = data_files_size(&base.join("plain"));
+    let dedup_size =
+        data_files_size(&base.join("dedup")) + store_size;
+    assert!(
+        dedup_size * 4 < plain_size,
+        "去重后大小 {dedup_size} 应远小于 {plain_size}"
+    );
+
+    assert_eq!(read_all(base, "dedup"), expected);
+}
+
+#[test]
+fn test_dedup_files_record_payload_store_extension() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    write_payload_dataset(
+        base,
+        "dedup",
+        dedup_config(),
+        (0..10).map(telemetry_frame),
+    );
+
+    let mut reader =
+        PcapReader::new(base, "dedup").unwrap();
+    let extensions = reader.header_extensions().unwrap();
+    assert!(extensions.contains(
+        &HeaderExtension::PayloadStore(
+            PAYLOAD_STORE.to_string()
+        )
+    ));
+
+    // 索引中的负载长度为原始长度
+    let info = reader.get_dataset_info().unwrap();
+    assert_eq!(info.total_packets, 10);
+    let total: u64 = (0..10)
+        .map(|i| telemetry_frame(i).1.len() as u64)
+        .sum();
+    let packets = read_all(base, "dedup");
+    assert_eq!(
+        packets
+            .iter()
+            .map(|(_, data)| data.len() as u64)
+            .sum::<u64>(),
+        total
+    );
+}
+
+#[test]
+fn test_dedup_seek_across_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let expected = write_payload_dataset(
+        base,
+        "dedup",
+        dedup_config(),
+        (0..100).map(telemetry_frame),
+    );
+
+    let mut reader =
+        PcapReader::new(base, "dedup").unwrap();
+    assert_eq!(
+        reader.get_file_info_list().unwrap().len(),
+        3
+    );
+
+    reader.seek_to_packet(57).unwrap();
+    let packet = reader.read_packet().unwrap().unwrap();
+    assert_eq!(packet.packet.data, expected[57].1);
+    let packet = reader.read_packet().unwrap().unwrap();
+    assert_eq!(packet.packet.data, expected[58].1);
+
+    let (timestamp_ns, payload) = &expected[83];
+    let packet = reader
+        .read_packet_by_timestamp(*timestamp_ns)
+        .unwrap()
+        .expect("应找到数据包");
+    assert_eq!(&packet.packet.data, payload);
+}
+
+#[test]
+fn test_dedup_rebuilt_index_matches_records() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    // 写入时不记录索引，完成写入时重新分析数据文件
+    let config = WriterConfig {
+        index_batch_size: 0,
+        ..dedup_config()
+    };
+    let expected = write_payload_dataset(
+        base,
+        "dedup",
+        config,
+        (0..60).map(telemetry_frame),
+    );
+
+    let mut reader =
+        PcapReader::new(base, "dedup").unwrap();
+    reader.seek_to_packet(45).unwrap();
+    let packet = reader.read_packet().unwrap().unwrap();
+    assert_eq!(packet.packet.data, expected[45].1);
+    assert_eq!(read_all(base, "dedup"), expected);
+}
+
+#[test]
+fn test_dedup_store_shared_across_sessions() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let mut expected = write_payload_dataset(
+        base,
+        "dedup",
+        dedup_config(),
+        (0..30).map(telemetry_frame),
+    );
+    let store_path: PathBuf =
+        base.join("dedup").join(PAYLOAD_STORE);
+    let store_size =
+        fs::metadata(&store_path).unwrap().len();
+
+    // 再次写入同一数据集，相同的负载复用已保存的负载块
+    expected.extend(write_payload_dataset(
+        base,
+        "dedup",
+        dedup_config(),
+        (30..30 + 30).map(telemetry_frame),
+    ));
+    assert_eq!(
+        fs::metadata(&store_path).unwrap().len(),
+        store_size
+    );
+    assert_eq!(read_all(base, "dedup"), expected);
+}
+
+#[test]
+fn test_dedup_missing_store_fails_to_read() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    write_payload_dataset(
+        base,
+        "dedup",
+        dedup_config(),
+        (0..5).map(telemetry_frame),
+    );
+    fs::remove_file(base.join("dedup").join(PAYLOAD_STORE))
+        .unwrap();
+
+    let mut reader =
+        PcapReader::new(base, "dedup").unwrap();
+    let error = reader.read_packet().unwrap_err();
+    assert!(
+        matches!(error, PcapError::FileNotFound(_)),
+        "缺少负载存储时应报告文件不存在: {error:?}"
+    );
+}
+
+#[test]
+fn test_dedup_rejects_storage_shards() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = WriterConfig {
+        storage_shards: vec![temp_dir.path().join("shard")],
+        ..dedup_config()
+    };
+    let error = config.validate().unwrap_err();
+    assert!(error.starts_with("payload_dedup:"), "{error}");
+}
@@ -0,0 +1,264 @@
+//! 负载搜索测试
+//!
+//! 在跨多个文件的数据集中查找字节序列，验证命中位置、时间范围、抽样和数量上限。
+
+use pcapfile_io::{
+    BytesMatcher, MaskedMatcher, Matcher, PcapError,
+    PcapReader, PcapWriter, SearchOptions, WriterConfig,
+};
+use tempfile::TempDir;
+
+const BASE_NS: u64 = 1_700_000_000_000_000_000;
+const PACKET_COUNT: u64 = 60;
+const NEEDLE: &[u8] = b"ID-4242";
+
+/// 每 7 个数据包在负载的不同位置嵌入一次搜索目标
+fn payload(i: u64) -> Vec<u8> {
+    let mut data = vec![b'.'; 40];
+    if i % 7 == 3 {
+        let offset = (i % 5) as usize * 4;
+        data[offset..offset + NEEDLE.len()]
+            .copy_from_slice(NEEDLE);
+    }
+    // 只差最后一个字节的近似内容不应命中
+    if i % 7 == 5 {
+        data[10..10 + NEEDLE.len()]
+            .copy_from_slice(b"ID-4243");
+    }
+    data
+}
+
+fn write_dataset(base_path: &std::path::Path) {
+    let config = WriterConfig {
+        max_packets_per_file: 16,
+        ..WriterConfig::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base_path, "search", config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..PACKET_COUNT {
+        writer
+            .write_payload(BASE_NS + i * 1_000, &payload(i))
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+#[test]
+fn test_search_payload() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path());
+    let mut reader =
+        PcapReader::new(temp_dir.path(), "search")
+            .expect("打开失败");
+
+    let expected: Vec<u64> =
+        (0..PACKET_COUNT).filter(|i| i % 7 == 3).collect();
+    let matches = reader
+        .search_payload(NEEDLE, ..)
+        .expect("搜索失败");
+    assert_eq!(
+        matches
+            .iter()
+            .map(|m| m.global_index)
+            .collect::<Vec<_>>(),
+        expected
+    );
+    for m in &matches {
+        assert_eq!(
+            m.timestamp_ns,
+            BASE_NS + m.global_index * 1_000
+        );
+        assert_eq!(
+            m.payload_offset,
+            (m.global_index % 5) as usize * 4
+        );
+        assert_eq!(
+            m.file_index,
+            (m.global_index / 16) as usize
+        );
+    }
+
+    // 命中位置可以直接用于跳转读取
+    reader
+        .seek_to_packet(matches[2].global_index as usize)
+        .expect("跳转失败");
+    let packet = reader
+        .read_packet()
+        .expect("读取失败")
+        .expect("应存在数据包");
+    assert_eq!(
+        packet.packet.data,
+        payload(matches[2].global_index)
+    );
+
+    // 时间范围（半开区间）
+    let matches = reader
+        .search_payload(
+            NEEDLE,
+            BASE_NS + 10 * 1_000..BASE_NS + 31 * 1_000,
+        )
+        .expect("搜索失败");
+    assert_eq!(
+        matches
+            .iter()
+            .map(|m| m.global_index)
+            .collect::<Vec<_>>(),
+        vec![10, 17, 24]
+    );
+
+    // 抽样、单线程和数量上限
+    let options = SearchOptions {
+        threads: 1,
+        sample_interval: 2,
+        max_matches: Some(3),
+    };
+    let matches = reader
+        .search_payload_with(NEEDLE, .., &options)
+        .expect("搜索失败");
+    assert_eq!(
+        matches
+            .iter()
+            .map(|m| m.global_index)
+            .collect::<Vec<_>>(),
+        vec![10, 24, 38]
+    );
+    let options = SearchOptions {
+        max_matches: Some(2),
+        ..SearchOptions::default()
+    };
+    let matches = reader
+        .search_payload_with(NEEDLE, .., &options)
+        .expect("搜索失败");
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].global_index, 3);
+    assert_eq!(matches[1].global_index, 10);
+
+    assert!(reader
+        .search_payload(b"missing", ..)
+        .expect("搜索失败")
+        .is_empty());
+    assert!(matches!(
+        reader.search_payload(b"", ..),
+        Err(PcapError::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        reader.search_payload_with(
+            NEEDLE,
+            ..,
+            &SearchOptions {
+                sample_interval: 0,
+                ..SearchOptions::default()
+            }
+        ),
+        Err(PcapError::InvalidArgument(_))
+    ));
+}
+
+fn global_indices(
+    matches: &[pcapfile_io::PayloadMatch],
+) -> Vec<u64> {
+    matches.iter().map(|m| m.global_index).collect()
+}
+
+#[test]
+fn test_search_with_matchers() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path());
+    let mut reader =
+        PcapReader::new(temp_dir.path(), "search")
+            .expect("打开失败");
+    let options = SearchOptions::default();
+
+    // 精确匹配器与 search_payload 结果一致
+    let exact = reader
+        .search_payload_matching(
+            &BytesMatcher::new(NEEDLE).expect("创建失败"),
+            ..,
+            &options,
+        )
+        .expect("搜索失败");
+    assert_eq!(
+        exact,
+        reader
+            .search_payload(NEEDLE, ..)
+            .expect("搜索失败")
+    );
+
+    // 掩码忽略最后一个字节，近似内容同样命中
+    let masked = MaskedMatcher::new(
+        NEEDLE,
+        &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00],
+    )
+    .expect("创建失败");
+    let matches = reader
+        .search_payload_matching(&masked, .., &options)
+        .expect("搜索失败");
+    let expected: Vec<u64> = (0..PACKET_COUNT)
+        .filter(|i| i % 7 == 3 || i % 7 == 5)
+        .collect();
+    assert_eq!(global_indices(&matches), expected);
+    assert!(matches
+        .iter()
+        .filter(|m| m.global_index % 7 == 5)
+        .all(|m| m.payload_offset == 10));
+
+    // 闭包作为协议相关的检测器接入
+    let detector = |payload: &[u8]| {
+        payload.iter().position(|byte| *byte == b'3')
+    };
+    let matches = reader
+        .search_payload_matching(&detector, .., &options)
+        .expect("搜索失败");
+    assert_eq!(
+        global_indices(&matches),
+        (0..PACKET_COUNT)
+            .filter(|i| i % 7 == 5)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(detector.find(b"..3"), Some(2));
+
+    assert!(matches!(
+        BytesMatcher::new(b""),
+        Err(PcapError::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        MaskedMatcher::new(NEEDLE, &[0xff]),
+        Err(PcapError::InvalidArgument(_))
+    ));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_search_with_regex() {
+    use pcapfile_io::RegexMatcher;
+
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path());
+    let mut reader =
+        PcapReader::new(temp_dir.path(), "search")
+            .expect("打开失败");
+
+    let matcher = RegexMatcher::new(r"ID-424[0-9]")
+        .expect("编译失败");
+    let matches = reader
+        .search_payload_matching(
+            &matcher,
+            ..,
+            &SearchOptions::default(),
+        )
+        .expect("搜索失败");
+    let expected: Vec<u64> = (0..PACKET_COUNT)
+        .filter(|i| i % 7 == 3 || i % 7 == 5)
+        .collect();
+    assert_eq!(global_indices(&matches), expected);
+
+    assert!(matches!(
+        RegexMatcher::new("ID-(["),
+        Err(PcapError::InvalidArgument(_))
+    ));
+}
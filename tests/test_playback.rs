@@ -0,0 +1,357 @@
+//! 回放引擎测试
+//!
+//! 使用闭包作为输出端，验证回放顺序、时间范围、无效数据包处理、回放节奏和速率限制。
+
+use pcapfile_io::{
+    DataPacket, DatasetInfo, PacketSink, PacketSource,
+    PcapError, PcapReader, PcapResult, PcapWriter,
+    PlaybackConfig, PlaybackEngine, RateLimitedSink,
+    RateLimiter, ValidatedPacket,
+};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+const BASE_SECONDS: u32 = 1_700_000_000;
+
+fn ts(nanosecond: u32) -> u64 {
+    BASE_SECONDS as u64 * 1_000_000_000 + nanosecond as u64
+}
+
+/// 基于内存列表的数据包来源
+struct VecSource {
+    packets: Vec<ValidatedPacket>,
+    position: usize,
+}
+
+impl PacketSource for VecSource {
+    fn dataset_name(&self) -> &str {
+        "memory"
+    }
+
+    fn get_dataset_info(
+        &mut self,
+    ) -> PcapResult<DatasetInfo> {
+        Ok(DatasetInfo::new("memory".to_string(), "."))
+    }
+
+    fn read_packet(
+        &mut self,
+    ) -> PcapResult<Option<ValidatedPacket>> {
+        let packet =
+            self.packets.get(self.position).cloned();
+        self.position += 1;
+        Ok(packet)
+    }
+
+    fn seek_to_timestamp(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> PcapResult<u64> {
+        self.position = self
+            .packets
+            .iter()
+            .position(|p| {
+                p.get_timestamp_ns() >= timestamp_ns
+            })
+            .ok_or_else(|| {
+                PcapError::InvalidArgument(
+                    "时间戳超出范围".to_string(),
+                )
+            })?;
+        Ok(self.packets[self.position].get_timestamp_ns())
+    }
+
+    fn reset(&mut self) -> PcapResult<()> {
+        self.position = 0;
+        Ok(())
+    }
+
+    fn read_packets_by_time_range(
+        &mut self,
+        start_timestamp_ns: u64,
+        end_timestamp_ns: u64,
+    ) -> PcapResult<Vec<ValidatedPacket>> {
+        Ok(self
+            .packets
+            .iter()
+            .filter(|p| {
+                let timestamp = p.get_timestamp_ns();
+                timestamp >= start_timestamp_ns
+                    && timestamp <= end_timestamp_ns
+            })
+            .cloned()
+            .collect())
+    }
+}
+
+fn packet(seconds: u32, nanoseconds: u32) -> DataPacket {
+    DataPacket::from_timestamp(
+        seconds,
+        nanoseconds,
+        vec![nanoseconds as u8; 8],
+    )
+    .expect("创建数据包失败")
+}
+
+#[test]
+fn test_playback_order_and_time_range() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let mut writer = PcapWriter::new(base_path, "playback")
+        .expect("创建写入器失败");
+    for index in 0..10u32 {
+        writer
+            .write_packet(&packet(BASE_SECONDS, index * 10))
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader = PcapReader::new(base_path, "playback")
+        .expect("创建读取器失败");
+    let engine =
+        PlaybackEngine::new_with_config(PlaybackConfig {
+            speed: 0.0,
+            ..PlaybackConfig::default()
+        })
+        .expect("创建回放引擎失败");
+    let mut timestamps = Vec::new();
+    let mut sink = |packet: &ValidatedPacket| {
+        timestamps.push(packet.get_timestamp_ns());
+        Ok(())
+    };
+    let summary = engine
+        .run(&mut reader, &mut sink)
+        .expect("回放失败");
+    assert_eq!(summary.packets, 10);
+    assert_eq!(summary.bytes, 80);
+    assert_eq!(
+        timestamps,
+        (0..10).map(|i| ts(i * 10)).collect::<Vec<_>>()
+    );
+
+    // 只回放时间范围内的数据包
+    let engine =
+        PlaybackEngine::new_with_config(PlaybackConfig {
+            speed: 0.0,
+            start_timestamp_ns: Some(ts(25)),
+            end_timestamp_ns: Some(ts(60)),
+            ..PlaybackConfig::default()
+        })
+        .expect("创建回放引擎失败");
+    timestamps.clear();
+    let mut sink = |packet: &ValidatedPacket| {
+        timestamps.push(packet.get_timestamp_ns());
+        Ok(())
+    };
+    engine.run(&mut reader, &mut sink).expect("回放失败");
+    assert_eq!(
+        timestamps,
+        vec![ts(30), ts(40), ts(50), ts(60)]
+    );
+
+    // 开始时间戳之后没有数据包时不发送任何数据
+    let engine =
+        PlaybackEngine::new_with_config(PlaybackConfig {
+            speed: 0.0,
+            start_timestamp_ns: Some(ts(1_000)),
+            ..PlaybackConfig::default()
+        })
+        .expect("创建回放引擎失败");
+    let summary = engine
+        .run(&mut reader, &mut |_: &ValidatedPacket| {
+            panic!("不应发送数据包")
+        })
+        .expect("回放失败");
+    assert_eq!(summary.packets, 0);
+
+    // 输出端错误会终止回放
+    let engine =
+        PlaybackEngine::new_with_config(PlaybackConfig {
+            speed: 0.0,
+            ..PlaybackConfig::default()
+        })
+        .expect("创建回放引擎失败");
+    let result = engine.run(
+        &mut reader,
+        &mut |_: &ValidatedPacket| {
+            Err(PcapError::SinkFailed("离线".to_string()))
+        },
+    );
+    assert!(matches!(
+        result,
+        Err(PcapError::SinkFailed(_))
+    ));
+}
+
+#[test]
+fn test_playback_skip_invalid_and_pacing() {
+    let mut corrupted = packet(BASE_SECONDS, 20_000_000);
+    corrupted.data[0] ^= 0xFF;
+    let mut source = VecSource {
+        packets: vec![
+            ValidatedPacket::new(
+                packet(BASE_SECONDS, 0),
+                true,
+            ),
+            ValidatedPacket::new(corrupted, false),
+            ValidatedPacket::new(
+                packet(BASE_SECONDS, 40_000_000),
+                true,
+            ),
+        ],
+        position: 0,
+    };
+
+    // 原始间隔 40ms，2 倍速约 20ms
+    let engine =
+        PlaybackEngine::new_with_config(PlaybackConfig {
+            speed: 2.0,
+            ..PlaybackConfig::default()
+        })
+        .expect("创建回放引擎失败");
+    let started = Instant::now();
+    let mut sent = Vec::new();
+    let mut sink = |packet: &ValidatedPacket| {
+        sent.push((
+            packet.get_timestamp_ns(),
+            started.elapsed(),
+        ));
+        Ok(())
+    };
+    let summary = engine
+        .run(&mut source, &mut sink)
+        .expect("回放失败");
+    assert_eq!(summary.packets, 2);
+    assert_eq!(summary.skipped_invalid, 1);
+    assert_eq!(sent[1].0, ts(40_000_000));
+    assert!(sent[1].1 >= Duration::from_millis(20));
+    assert!(sent[1].1 < Duration::from_millis(40));
+
+    // 不跳过时无效数据包同样发送
+    let engine =
+        PlaybackEngine::new_with_config(PlaybackConfig {
+            speed: 0.0,
+            skip_invalid: false,
+            ..PlaybackConfig::default()
+        })
+        .expect("创建回放引擎失败");
+    let summary = engine
+        .run(&mut source, &mut |_: &ValidatedPacket| Ok(()))
+        .expect("回放失败");
+    assert_eq!(summary.packets, 3);
+    assert_eq!(summary.skipped_invalid, 0);
+
+    assert!(PlaybackEngine::new_with_config(
+        PlaybackConfig {
+            speed: -1.0,
+            ..PlaybackConfig::default()
+        }
+    )
+    .is_err());
+}
+
+#[test]
+fn test_rate_limiter_reserve() {
+    // 每包 10ms 与每 8 字节 20ms 取较长者
+    let mut limiter =
+        RateLimiter::new(Some(100), Some(400));
+    let now = Instant::now();
+    assert_eq!(limiter.reserve(8, now), Duration::ZERO);
+    assert_eq!(
+        limiter.reserve(8, now),
+        Duration::from_millis(20)
+    );
+    // 小数据包只受数据包数量限制
+    assert_eq!(
+        limiter.reserve(1, now),
+        Duration::from_millis(40)
+    );
+    assert_eq!(
+        limiter
+            .reserve(8, now + Duration::from_millis(100)),
+        Duration::ZERO
+    );
+
+    limiter.reset();
+    assert_eq!(limiter.reserve(8, now), Duration::ZERO);
+
+    let mut unlimited = RateLimiter::new(Some(0), None);
+    assert!(unlimited.is_unlimited());
+    assert_eq!(unlimited.reserve(8, now), Duration::ZERO);
+    assert_eq!(unlimited.reserve(8, now), Duration::ZERO);
+}
+
+#[test]
+fn test_playback_rate_limit() {
+    // 时间戳相同的数据包，不限速时会一次性发出
+    let packets: Vec<ValidatedPacket> = (0..6)
+        .map(|_| {
+            ValidatedPacket::new(
+                packet(BASE_SECONDS, 0),
+                true,
+            )
+        })
+        .collect();
+    let mut source = VecSource {
+        packets: packets.clone(),
+        position: 0,
+    };
+
+    // 每秒 100 个数据包，6 个数据包至少间隔 50ms
+    let engine =
+        PlaybackEngine::new_with_config(PlaybackConfig {
+            speed: 0.0,
+            max_packets_per_second: Some(100),
+            ..PlaybackConfig::default()
+        })
+        .expect("创建回放引擎失败");
+    let started = Instant::now();
+    let summary = engine
+        .run(&mut source, &mut |_: &ValidatedPacket| Ok(()))
+        .expect("回放失败");
+    assert_eq!(summary.packets, 6);
+    assert!(started.elapsed() >= Duration::from_millis(50));
+    assert!(summary.throttled >= Duration::from_millis(40));
+
+    // 每秒 800 字节，8 字节的数据包同样间隔 10ms
+    let engine =
+        PlaybackEngine::new_with_config(PlaybackConfig {
+            speed: 0.0,
+            max_bytes_per_second: Some(800),
+            ..PlaybackConfig::default()
+        })
+        .expect("创建回放引擎失败");
+    let started = Instant::now();
+    engine
+        .run(&mut source, &mut |_: &ValidatedPacket| Ok(()))
+        .expect("回放失败");
+    assert!(started.elapsed() >= Duration::from_millis(50));
+
+    // 包装输出端用于其他导出流程
+    let mut received = Vec::new();
+    let mut sink = RateLimitedSink::new(
+        |packet: &ValidatedPacket| {
+            received.push(packet.get_timestamp_ns());
+            Ok(())
+        },
+        RateLimiter::new(Some(100), None),
+    );
+    let started = Instant::now();
+    for packet in &packets {
+        sink.write(packet).expect("输出失败");
+    }
+    sink.flush().expect("刷新失败");
+    assert!(started.elapsed() >= Duration::from_millis(50));
+    assert!(sink.throttled() >= Duration::from_millis(40));
+    let _ = sink.into_inner();
+    assert_eq!(received.len(), 6);
+
+    assert!(PlaybackEngine::new_with_config(
+        PlaybackConfig {
+            max_packets_per_second: Some(0),
+            ..PlaybackConfig::default()
+        }
+    )
+    .is_err());
+}
@@ -0,0 +1,161 @@
+//! 属性测试生成器测试
+//!
+//! 用 `strategies` 模块的 proptest 策略和 `Arbitrary` 实现验证文件格式的不变量：
+//! 头部编解码可逆、生成的数据包头部与负载一致、生成的写入器配置有效，
+//! 以及任意配置下写入的数据包序列可以原样读回。
+//! 需要启用 `proptest-support` 特性：
+//! `cargo test --features proptest-support --test test_property_strategies`
+
+#![cfg(feature = "proptest-support")]
+
+use arbitrary::{Arbitrary, Unstructured};
+use pcapfile_io::strategies::{
+    data_packet, data_packet_header, packet_sequence,
+    writer_config, MAX_TIMESTAMP_NS,
+};
+use pcapfile_io::{
+    ByteOrder, DataPacket, DataPacketHeader,
+    DuplicateTimestampPolicy, PcapReader, PcapWriter,
+    WriterConfig,
+};
+use proptest::prelude::*;
+use tempfile::TempDir;
+
+proptest! {
+    #[test]
+    fn header_round_trips(
+        header in data_packet_header(),
+        big_endian in any::<bool>(),
+    ) {
+        let order = if big_endian {
+            ByteOrder::Big
+        } else {
+            ByteOrder::Little
+        };
+        let bytes = header.to_bytes_with_order(order);
+        prop_assert_eq!(
+            bytes.len(),
+            DataPacketHeader::HEADER_SIZE
+        );
+        let decoded = DataPacketHeader::from_bytes_with_order(
+            &bytes, order,
+        )
+        .expect("解析失败");
+        prop_assert_eq!(
+            decoded.timestamp_ns(),
+            header.timestamp_ns()
+        );
+        prop_assert_eq!(
+            decoded.packet_length,
+            header.packet_length
+        );
+        prop_assert_eq!(decoded.checksum, header.checksum);
+    }
+
+    #[test]
+    fn packet_header_matches_payload(
+        packet in data_packet(256),
+    ) {
+        let expected = DataPacketHeader::from_timestamp_ns(
+            packet.get_timestamp_ns(),
+            &packet.data,
+        )
+        .expect("时间戳超出范围");
+        prop_assert!(
+            packet.get_timestamp_ns() <= MAX_TIMESTAMP_NS
+        );
+        prop_assert_eq!(
+            packet.header.packet_length,
+            expected.packet_length
+        );
+        prop_assert_eq!(packet.checksum(), expected.checksum);
+    }
+
+    #[test]
+    fn generated_writer_config_is_valid(
+        config in writer_config(),
+    ) {
+        prop_assert_eq!(config.validate(), Ok(()));
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn written_sequence_reads_back(
+        packets in packet_sequence(40, 128),
+        config in writer_config(),
+    ) {
+        let temp_dir =
+            TempDir::new().expect("创建临时目录失败");
+        let config = WriterConfig {
+            duplicate_timestamp_policy:
+                DuplicateTimestampPolicy::Allow,
+            ..config
+        };
+        let mut writer = PcapWriter::new_with_config(
+            temp_dir.path(),
+            "prop",
+            config,
+        )
+        .expect("创建写入器失败");
+        writer.write_packets(&packets).expect("写入失败");
+        writer.finalize().expect("完成写入失败");
+
+        let mut reader =
+            PcapReader::new(temp_dir.path(), "prop")
+                .expect("打开失败");
+        let mut read = Vec::new();
+        while let Some(packet) =
+            reader.read_packet().expect("读取失败")
+        {
+            read.push(packet.packet);
+        }
+        prop_assert_eq!(read.len(), packets.len());
+        for (expected, actual) in packets.iter().zip(&read) {
+            prop_assert_eq!(
+                actual.get_timestamp_ns(),
+                expected.get_timestamp_ns()
+            );
+            prop_assert_eq!(&actual.data, &expected.data);
+        }
+    }
+}
+
+#[test]
+fn test_arbitrary_implementations() {
+    for seed in 0..64u8 {
+        let bytes: Vec<u8> = (0..512u16)
+            .map(|i| {
+                (i as u8)
+                    .wrapping_mul(seed)
+                    .wrapping_add(seed)
+            })
+            .collect();
+        let mut u = Unstructured::new(&bytes);
+
+        let config = WriterConfig::arbitrary(&mut u)
+            .expect("生成失败");
+        assert_eq!(config.validate(), Ok(()), "{config:?}");
+
+        let header = DataPacketHeader::arbitrary(&mut u)
+            .expect("生成失败");
+        assert!(
+            header.timestamp_nanoseconds < 1_000_000_000
+        );
+
+        let packet = DataPacket::arbitrary(&mut u)
+            .expect("生成失败");
+        assert_eq!(
+            packet.header.packet_length as usize,
+            packet.data.len()
+        );
+        let expected = DataPacketHeader::from_timestamp_ns(
+            packet.get_timestamp_ns(),
+            &packet.data,
+        )
+        .expect("时间戳超出范围");
+        assert_eq!(packet.checksum(), expected.checksum);
+    }
+}
@@ -0,0 +1,312 @@
+//! 只读模式测试
+//!
+//! 验证只读模式下读取器只使用已有的有效索引，索引缺失、损坏或过时时返回明确的错误，
+//! 并且从不生成、隔离或改写索引文件；设置索引目录后索引生成到该目录，数据集目录保持不变。
+
+use pcapfile_io::{
+    PcapError, PcapReader, PcapWriter, ReaderConfig,
+    WriterConfig,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "archive";
+const PACKET_COUNT: u64 = 25;
+
+fn write_dataset(base_path: &Path) -> PathBuf {
+    let config = WriterConfig {
+        max_packets_per_file: 10,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base_path, TEST_NAME, config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..PACKET_COUNT {
+        writer
+            .write_payload(
+                1_700_000_000_000_000_000 + i * 1_000,
+                &[i as u8; 32],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+    base_path.join(TEST_NAME)
+}
+
+fn read_count(reader: &mut PcapReader) -> u64 {
+    let mut count = 0;
+    while reader.read_packet().expect("读取失败").is_some()
+    {
+        count += 1;
+    }
+    count
+}
+
+fn index_dir_entries(index_dir: &Path) -> Vec<String> {
+    if !index_dir.exists() {
+        return Vec::new();
+    }
+    dataset_entries(index_dir)
+}
+
+fn dataset_entries(dataset_path: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dataset_path)
+        .expect("读取目录失败")
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            entry.file_name().to_string_lossy().into_owned()
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_read_only_uses_existing_index() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let dataset_path = write_dataset(temp_dir.path());
+    let index_before = fs::read(dataset_path.join(".pidx"))
+        .expect("读取索引失败");
+
+    let mut reader = PcapReader::open_read_only(
+        temp_dir.path(),
+        TEST_NAME,
+    )
+    .expect("打开失败");
+    assert!(reader.is_read_only());
+    assert!(reader.index().is_read_only());
+    let mut count = 0;
+    while let Some(packet) =
+        reader.read_packet().expect("读取失败")
+    {
+        assert!(packet.is_valid());
+        count += 1;
+    }
+    assert_eq!(count, PACKET_COUNT);
+
+    // 重建索引等写入操作被拒绝，索引文件保持不变
+    assert!(matches!(
+        reader.index_mut().rebuild_index(),
+        Err(PcapError::InvalidState(_))
+    ));
+    assert!(matches!(
+        reader.add_bookmark("start", 0),
+        Err(PcapError::InvalidState(_))
+    ));
+    assert_eq!(
+        fs::read(dataset_path.join(".pidx"))
+            .expect("读取索引失败"),
+        index_before
+    );
+}
+
+#[test]
+fn test_read_only_missing_index() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let dataset_path = write_dataset(temp_dir.path());
+    fs::remove_file(dataset_path.join(".pidx"))
+        .expect("删除索引失败");
+    let entries_before = dataset_entries(&dataset_path);
+
+    let mut reader = PcapReader::open_read_only(
+        temp_dir.path(),
+        TEST_NAME,
+    )
+    .expect("打开失败");
+    match reader.read_packet() {
+        Err(PcapError::FileNotFound(message)) => {
+            assert!(message.contains("只读模式"));
+        }
+        other => panic!("应返回索引不存在错误: {other:?}"),
+    }
+    assert_eq!(
+        dataset_entries(&dataset_path),
+        entries_before
+    );
+
+    // 普通模式仍然自动生成索引
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    reader.initialize().expect("初始化失败");
+    assert!(dataset_path.join(".pidx").exists());
+}
+
+#[test]
+fn test_read_only_corrupted_index_not_quarantined() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let dataset_path = write_dataset(temp_dir.path());
+    fs::write(dataset_path.join(".pidx"), b"not xml")
+        .expect("写入损坏索引失败");
+    let entries_before = dataset_entries(&dataset_path);
+
+    let config = ReaderConfig {
+        read_only: true,
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("打开失败");
+    assert!(matches!(
+        reader.initialize(),
+        Err(PcapError::InvalidFormat(_))
+    ));
+    assert_eq!(
+        dataset_entries(&dataset_path),
+        entries_before
+    );
+}
+
+#[test]
+fn test_read_only_stale_index() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let dataset_path = write_dataset(temp_dir.path());
+    let data_file = dataset_entries(&dataset_path)
+        .into_iter()
+        .find(|name| name.ends_with(".pcap"))
+        .expect("缺少数据文件");
+    fs::remove_file(dataset_path.join(data_file))
+        .expect("删除数据文件失败");
+    let index_before = fs::read(dataset_path.join(".pidx"))
+        .expect("读取索引失败");
+
+    let mut reader = PcapReader::open_read_only(
+        temp_dir.path(),
+        TEST_NAME,
+    )
+    .expect("打开失败");
+    assert!(matches!(
+        reader.initialize(),
+        Err(PcapError::InvalidState(_))
+    ));
+    assert_eq!(
+        fs::read(dataset_path.join(".pidx"))
+            .expect("读取索引失败"),
+        index_before
+    );
+}
+
+#[test]
+fn test_read_only_with_index_dir_generates_external_index()
+{
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let dataset_path = write_dataset(temp_dir.path());
+    fs::remove_file(dataset_path.join(".pidx"))
+        .expect("删除索引失败");
+    let entries_before = dataset_entries(&dataset_path);
+    let index_dir = temp_dir.path().join("index_cache");
+
+    let config = ReaderConfig {
+        read_only: true,
+        index_dir: Some(index_dir.clone()),
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config.clone(),
+    )
+    .expect("打开失败");
+    assert_eq!(read_count(&mut reader), PACKET_COUNT);
+    assert!(reader
+        .index()
+        .index_file_path()
+        .starts_with(&index_dir));
+    drop(reader);
+
+    assert_eq!(
+        dataset_entries(&dataset_path),
+        entries_before
+    );
+    let cached = index_dir_entries(&index_dir);
+    assert_eq!(cached.len(), 1);
+    assert!(cached[0].starts_with("archive-"));
+    assert!(cached[0].ends_with(".pidx"));
+
+    // 再次打开直接使用索引目录中的索引
+    let cached_index = fs::read(index_dir.join(&cached[0]))
+        .expect("读取索引失败");
+    let mut reader = PcapReader::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("打开失败");
+    assert_eq!(read_count(&mut reader), PACKET_COUNT);
+    assert_eq!(
+        fs::read(index_dir.join(&cached[0]))
+            .expect("读取索引失败"),
+        cached_index
+    );
+}
+
+#[test]
+fn test_index_dir_rebuild_leaves_dataset_untouched() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let dataset_path = write_dataset(temp_dir.path());
+    fs::write(dataset_path.join(".pidx"), b"not xml")
+        .expect("写入损坏索引失败");
+    let entries_before = dataset_entries(&dataset_path);
+    let index_dir = temp_dir.path().join("index_cache");
+
+    let config = ReaderConfig {
+        index_dir: Some(index_dir.clone()),
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("打开失败");
+    assert_eq!(read_count(&mut reader), PACKET_COUNT);
+    assert!(!reader.index().health().is_healthy());
+
+    // 损坏的索引没有被隔离，新索引写入索引目录
+    assert_eq!(
+        dataset_entries(&dataset_path),
+        entries_before
+    );
+    assert_eq!(
+        fs::read(dataset_path.join(".pidx"))
+            .expect("读取索引失败"),
+        b"not xml"
+    );
+    assert_eq!(index_dir_entries(&index_dir).len(), 1);
+}
+
+#[test]
+fn test_index_dir_shared_by_same_named_datasets() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let index_dir = temp_dir.path().join("index_cache");
+    for base in ["site_a", "site_b"] {
+        let base_path = temp_dir.path().join(base);
+        let dataset_path = write_dataset(&base_path);
+        fs::remove_file(dataset_path.join(".pidx"))
+            .expect("删除索引失败");
+
+        let config = ReaderConfig {
+            read_only: true,
+            index_dir: Some(index_dir.clone()),
+            ..Default::default()
+        };
+        let mut reader = PcapReader::new_with_config(
+            &base_path, TEST_NAME, config,
+        )
+        .expect("打开失败");
+        assert_eq!(read_count(&mut reader), PACKET_COUNT);
+    }
+    assert_eq!(index_dir_entries(&index_dir).len(), 2);
+}
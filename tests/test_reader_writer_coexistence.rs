@@ -0,0 +1,242 @@
+//! 读写共存测试
+//!
+//! 测试写入器仍在写入时读取器通过实时索引读取数据集，以及写入期间保存的索引快照
+
+use pcapfile_io::{
+    PcapError, PcapReader, PcapWriter, WriterConfig,
+    WriterProgress,
+};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::create_test_packet;
+
+const TEST_NAME: &str = "live_dataset";
+
+fn write_packets(
+    writer: &mut PcapWriter,
+    start: u32,
+    count: u32,
+) {
+    for i in start..start + count {
+        writer
+            .write_packet(
+                &create_test_packet(i, 64)
+                    .expect("创建测试数据包失败"),
+            )
+            .expect("写入数据包失败");
+    }
+}
+
+#[test]
+fn test_reader_follows_active_writer() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let dataset_path = temp_dir.path().join(TEST_NAME);
+
+    let config = WriterConfig {
+        max_packets_per_file: 10,
+        index_checkpoint_interval: 1,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("创建写入器失败");
+    write_packets(&mut writer, 0, 25);
+
+    // 写入器发布了进度标记和已完成文件的检查点
+    let progress = WriterProgress::load(&dataset_path)
+        .expect("应存在写入进度标记");
+    assert_eq!(progress.completed_files.len(), 2);
+    assert!(progress.active_file.is_some());
+    assert!(dataset_path.join(".pidx").exists());
+
+    // 模拟正在写入的文件末尾有一个未写完的数据包
+    let active_path = dataset_path
+        .join(progress.active_file.as_ref().unwrap());
+    let mut active_file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&active_path)
+        .expect("打开活动文件失败");
+    let mut partial = vec![0u8; 16];
+    partial[8..12].copy_from_slice(&100u32.to_le_bytes());
+    partial.extend_from_slice(&[0xAB; 10]);
+    active_file.write_all(&partial).expect("写入失败");
+    drop(active_file);
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("创建读取器失败");
+    reader.initialize().expect("初始化读取器失败");
+    assert!(reader.is_live());
+    assert_eq!(reader.total_packets(), Some(25));
+
+    let mut count = 0;
+    while reader.read_packet().expect("读取失败").is_some()
+    {
+        count += 1;
+    }
+    assert_eq!(count, 25);
+
+    // 写入器继续写入，刷新后从原位置继续读取
+    write_packets(&mut writer, 25, 5);
+    reader.refresh_index().expect("刷新索引失败");
+    assert!(reader.is_live());
+    assert_eq!(reader.total_packets(), Some(30));
+    while reader.read_packet().expect("读取失败").is_some()
+    {
+        count += 1;
+    }
+    assert_eq!(count, 30);
+
+    // 写入器完成后切换为完整索引，进度标记被删除
+    writer.finalize().expect("完成写入失败");
+    assert!(WriterProgress::load(&dataset_path).is_none());
+    reader.refresh_index().expect("刷新索引失败");
+    assert!(!reader.is_live());
+    assert_eq!(reader.total_packets(), Some(30));
+}
+
+#[test]
+fn test_live_index_without_progress_marker() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+
+    let config = WriterConfig {
+        max_packets_per_file: 10,
+        publish_progress: false,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("创建写入器失败");
+    write_packets(&mut writer, 0, 15);
+    assert!(WriterProgress::load(
+        temp_dir.path().join(TEST_NAME)
+    )
+    .is_none());
+
+    // 未发布进度时，最后一个文件被视为正在写入的文件
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("创建读取器失败");
+    reader.initialize().expect("初始化读取器失败");
+    assert!(reader.is_live());
+    assert_eq!(reader.total_packets(), Some(15));
+
+    writer.finalize().expect("完成写入失败");
+}
+
+/// 复制数据集中的数据文件和索引，模拟快照时刻磁盘上的状态
+fn copy_dataset_files(source: &Path, target: &Path) {
+    fs::create_dir_all(target).expect("创建目录失败");
+    for entry in fs::read_dir(source).expect("读取目录失败")
+    {
+        let path = entry.expect("读取目录项失败").path();
+        let name = path.file_name().unwrap().to_owned();
+        let keep = name == ".pidx"
+            || path
+                .extension()
+                .is_some_and(|ext| ext == "pcap");
+        if keep {
+            fs::copy(&path, target.join(name))
+                .expect("复制文件失败");
+        }
+    }
+}
+
+#[test]
+fn test_snapshot_index_during_recording() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let dataset_path = temp_dir.path().join(TEST_NAME);
+
+    for index_batch_size in [1024, 0] {
+        fs::remove_dir_all(&dataset_path).ok();
+        let config = WriterConfig {
+            max_packets_per_file: 10,
+            index_batch_size,
+            ..Default::default()
+        };
+        let mut writer = PcapWriter::new_with_config(
+            temp_dir.path(),
+            TEST_NAME,
+            config,
+        )
+        .expect("创建写入器失败");
+        write_packets(&mut writer, 0, 25);
+
+        // 快照覆盖正在写入的文件中已写入的数据包
+        assert_eq!(
+            writer.snapshot_index().expect("保存快照失败"),
+            25
+        );
+        let snapshot_copy = temp_dir.path().join("copy");
+        fs::remove_dir_all(&snapshot_copy).ok();
+        copy_dataset_files(
+            &dataset_path,
+            &snapshot_copy.join(TEST_NAME),
+        );
+        let index_before = fs::read(
+            snapshot_copy.join(TEST_NAME).join(".pidx"),
+        )
+        .expect("读取索引失败");
+
+        // 快照时刻的数据集可以直接使用快照索引读取，无需重建
+        let mut reader =
+            PcapReader::new(&snapshot_copy, TEST_NAME)
+                .expect("创建读取器失败");
+        let mut count = 0;
+        while reader
+            .read_packet()
+            .expect("读取失败")
+            .is_some()
+        {
+            count += 1;
+        }
+        assert_eq!(count, 25);
+        assert!(reader.index().health().is_healthy());
+        assert_eq!(
+            fs::read(
+                snapshot_copy.join(TEST_NAME).join(".pidx")
+            )
+            .expect("读取索引失败"),
+            index_before
+        );
+
+        // 写入器活动期间读取器复用快照条目
+        let mut live =
+            PcapReader::new(temp_dir.path(), TEST_NAME)
+                .expect("创建读取器失败");
+        live.initialize().expect("初始化读取器失败");
+        assert!(live.is_live());
+        assert_eq!(live.total_packets(), Some(25));
+        drop(live);
+
+        // 快照后继续写入，完成后生成完整索引
+        write_packets(&mut writer, 25, 7);
+        assert_eq!(
+            writer.snapshot_index().expect("保存快照失败"),
+            32
+        );
+        writer.finalize().expect("完成写入失败");
+        assert!(matches!(
+            writer.snapshot_index(),
+            Err(PcapError::InvalidState(_))
+        ));
+        let mut reader =
+            PcapReader::new(temp_dir.path(), TEST_NAME)
+                .expect("创建读取器失败");
+        reader.initialize().expect("初始化读取器失败");
+        assert_eq!(reader.total_packets(), Some(32));
+    }
+}
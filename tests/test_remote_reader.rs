@@ -0,0 +1,125 @@
+//! 远程读取器测试
+//!
+//! 启动数据集服务后通过 `RemotePcapReader` 读取，并与本地 `PcapReader` 的结果对比。
+//! 需要启用 `server` 和 `remote` 特性：
+//! `cargo test --features server,remote --test test_remote_reader`
+
+#![cfg(all(feature = "server", feature = "remote"))]
+
+use pcapfile_io::server::DatasetServer;
+use pcapfile_io::{
+    DataPacket, PacketSource, PcapError, PcapReader,
+    PcapWriter, RemotePcapReader, WriterConfig,
+};
+use tempfile::TempDir;
+
+const BASE_SECONDS: u32 = 1_700_000_000;
+const PACKET_COUNT: u32 = 25;
+
+fn ts(nanosecond: u32) -> u64 {
+    BASE_SECONDS as u64 * 1_000_000_000 + nanosecond as u64
+}
+
+/// 通过通用接口读取全部数据包的时间戳
+fn collect_timestamps<S: PacketSource>(
+    source: &mut S,
+) -> Vec<u64> {
+    source.reset().expect("重置失败");
+    let mut timestamps = Vec::new();
+    while let Some(packet) =
+        source.read_packet().expect("读取数据包失败")
+    {
+        timestamps.push(packet.get_timestamp_ns());
+    }
+    timestamps
+}
+
+#[test]
+fn test_remote_reader_matches_local_reader() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    // 每个文件 10 个数据包，跨越多个文件
+    let config = WriterConfig {
+        max_packets_per_file: 10,
+        ..WriterConfig::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base_path, "remote", config,
+    )
+    .expect("创建写入器失败");
+    for index in 0..PACKET_COUNT {
+        let packet = DataPacket::from_timestamp(
+            BASE_SECONDS,
+            index * 10,
+            vec![index as u8; 16],
+        )
+        .expect("创建数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let server = DatasetServer::new(base_path)
+        .spawn("127.0.0.1:0")
+        .expect("启动服务失败");
+    let base_url =
+        format!("http://{}", server.local_addr());
+
+    let mut local = PcapReader::new(base_path, "remote")
+        .expect("创建本地读取器失败");
+    let mut remote =
+        RemotePcapReader::new(&base_url, "remote")
+            .expect("创建远程读取器失败");
+    assert_eq!(
+        PacketSource::dataset_name(&remote),
+        "remote"
+    );
+    assert_eq!(
+        remote
+            .get_dataset_info()
+            .expect("获取数据集信息失败")
+            .total_packets,
+        PACKET_COUNT as u64
+    );
+
+    let expected = collect_timestamps(&mut local);
+    assert_eq!(expected.len(), PACKET_COUNT as usize);
+    assert_eq!(collect_timestamps(&mut remote), expected);
+    assert_eq!(remote.packets_read(), PACKET_COUNT as u64);
+
+    // 跳转语义与本地读取器一致
+    assert_eq!(
+        remote.seek_to_timestamp(ts(95)).expect("跳转失败"),
+        ts(100)
+    );
+    let packet = remote
+        .read_packet()
+        .expect("读取数据包失败")
+        .expect("应存在数据包");
+    assert_eq!(packet.get_timestamp_ns(), ts(100));
+    assert_eq!(packet.packet.data, vec![10u8; 16]);
+    assert!(matches!(
+        remote.seek_to_timestamp(ts(1_000)),
+        Err(PcapError::InvalidArgument(_))
+    ));
+
+    // 时间范围查询不影响当前读取位置
+    let range = remote
+        .read_packets_by_time_range(ts(30), ts(60))
+        .expect("范围查询失败");
+    assert_eq!(range.len(), 4);
+    assert_eq!(
+        range.len(),
+        local
+            .read_packets_by_time_range(ts(30), ts(60))
+            .expect("范围查询失败")
+            .len()
+    );
+
+    assert!(matches!(
+        RemotePcapReader::new(&base_url, "missing"),
+        Err(PcapError::DirectoryNotFound(_))
+    ));
+}
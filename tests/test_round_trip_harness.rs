@@ -0,0 +1,153 @@
+//! 往返测试工具测试
+//!
+//! 测试 `RoundTripHarness` 的确定性生成、写入读回比较，以及差异报告。
+//! 需要启用 `test-util` 特性：
+//! `cargo test --features test-util --test test_round_trip_harness`
+
+#![cfg(feature = "test-util")]
+
+use pcapfile_io::testing::{
+    compare_packets, PacketMismatch, RoundTripHarness,
+};
+use pcapfile_io::{
+    DataPacket, DataPacketHeader, WriterConfig,
+};
+use tempfile::TempDir;
+
+#[test]
+fn test_generation_is_deterministic() {
+    let harness = RoundTripHarness {
+        packet_count: 50,
+        payload_len: 4..=32,
+        max_interval_ns: 10,
+        ..RoundTripHarness::new(7)
+    };
+    let first = harness.generate().expect("生成失败");
+    let second = harness.generate().expect("生成失败");
+    assert_eq!(first.len(), 50);
+    assert!(compare_packets(&first, &second).is_empty());
+    assert_eq!(
+        first[0].get_timestamp_ns(),
+        harness.start_timestamp_ns
+    );
+    for pair in first.windows(2) {
+        let gap = pair[1].get_timestamp_ns()
+            - pair[0].get_timestamp_ns();
+        assert!((1..=10).contains(&gap), "{gap}");
+    }
+    assert!(first.iter().all(
+        |packet| (4..=32).contains(&packet.data.len())
+    ));
+
+    let other = RoundTripHarness {
+        seed: 8,
+        ..harness.clone()
+    }
+    .generate()
+    .expect("生成失败");
+    assert!(!compare_packets(&first, &other).is_empty());
+
+    let empty_range = RoundTripHarness {
+        #[allow(clippy::reversed_empty_ranges)]
+        payload_len: 10..=5,
+        ..harness
+    };
+    assert!(empty_range.generate().is_err());
+}
+
+#[test]
+fn test_round_trip_across_rotated_files() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let harness = RoundTripHarness {
+        packet_count: 300,
+        writer_config: WriterConfig {
+            max_packets_per_file: 64,
+            ..Default::default()
+        },
+        ..RoundTripHarness::new(42)
+    };
+    let report = harness
+        .assert_round_trip(temp_dir.path(), "harness");
+    assert_eq!(report.seed, 42);
+    assert_eq!(report.written, 300);
+    assert_eq!(report.read, 300);
+    assert!(report.to_string().contains("完全一致"));
+}
+
+fn packet(timestamp_ns: u64, data: &[u8]) -> DataPacket {
+    let header = DataPacketHeader::from_timestamp_ns(
+        timestamp_ns,
+        data,
+    )
+    .expect("创建头部失败");
+    DataPacket::new(header, data.to_vec())
+        .expect("创建数据包失败")
+}
+
+#[test]
+fn test_reports_detailed_differences() {
+    let expected = vec![
+        packet(100, b"abcd"),
+        packet(200, b"efgh"),
+        packet(300, b"ijkl"),
+    ];
+    let mut tampered = packet(200, b"efgh");
+    tampered.data[2] = b'X';
+    let actual = vec![
+        packet(100, b"abcd"),
+        tampered,
+        packet(301, b"ij"),
+    ];
+
+    let mismatches = compare_packets(&expected, &actual);
+    assert_eq!(
+        mismatches[0],
+        PacketMismatch::Payload {
+            index: 1,
+            expected_len: 4,
+            actual_len: 4,
+            first_difference: Some(2),
+        }
+    );
+    assert!(matches!(
+        mismatches[1],
+        PacketMismatch::Timestamp {
+            index: 2,
+            expected: 300,
+            actual: 301,
+        }
+    ));
+    assert!(matches!(
+        mismatches[2],
+        PacketMismatch::Payload {
+            index: 2,
+            first_difference: None,
+            ..
+        }
+    ));
+    assert!(mismatches.iter().any(|m| matches!(
+        m,
+        PacketMismatch::Checksum { .. }
+    )));
+
+    let missing =
+        compare_packets(&expected, &expected[..1]);
+    assert_eq!(
+        missing,
+        vec![
+            PacketMismatch::Missing {
+                index: 1,
+                timestamp_ns: 200
+            },
+            PacketMismatch::Missing {
+                index: 2,
+                timestamp_ns: 300
+            },
+        ]
+    );
+    let extra = compare_packets(&expected[..2], &expected);
+    assert_eq!(extra.len(), 1);
+    assert_eq!(extra[0].index(), 2);
+    assert!(extra[0].to_string().contains("多余"));
+}
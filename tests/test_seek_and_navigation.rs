@@ -1,9 +1,11 @@
 //! 测试新增的定位和导航接口
 
 use pcapfile_io::{
-    DataPacket, PcapReader, PcapWriter, WriterConfig,
+    DataPacket, PcapReader, PcapWriter, ReaderConfig,
+    SeekMode, WriterConfig,
 };
 use std::path::Path;
+use std::time::Duration;
 
 mod common;
 use common::{
@@ -306,3 +308,482 @@ fn test_combined_navigation() {
     reader.reset().expect("重置失败");
     assert_eq!(reader.current_packet_index(), 0);
 }
+
+#[test]
+fn test_index_entries_carry_global_index() {
+    const TEST_NAME: &str = "test_index_global_index";
+    let base_path =
+        setup_test_environment().expect("设置测试环境失败");
+
+    create_test_dataset(&base_path, TEST_NAME)
+        .expect("创建测试数据集失败");
+
+    // 序号应持久化到索引文件中
+    let pidx_content = std::fs::read_to_string(
+        base_path.join(TEST_NAME).join(".pidx"),
+    )
+    .expect("读取索引文件失败");
+    assert!(pidx_content.contains("global_index="));
+
+    let mut reader = PcapReader::new(&base_path, TEST_NAME)
+        .expect("创建Reader失败");
+    reader.initialize().expect("初始化失败");
+
+    let index =
+        reader.index().get_index().expect("获取索引失败");
+    let mut expected_global = 0u64;
+    for file in index.files() {
+        for (i, entry) in
+            file.data_packets.iter().enumerate()
+        {
+            assert_eq!(entry.packet_index, i as u64);
+            assert_eq!(entry.global_index, expected_global);
+            expected_global += 1;
+        }
+    }
+    assert_eq!(expected_global, 100);
+
+    // 跨文件定位（每个文件50个包）
+    let (file_idx, entry) = index
+        .locate_global_index(75)
+        .expect("定位全局序号失败");
+    assert_eq!(file_idx, 1);
+    assert_eq!(entry.packet_index, 25);
+    assert!(index.locate_global_index(100).is_none());
+}
+
+#[test]
+fn test_index_query_facade() {
+    const TEST_NAME: &str = "test_index_query";
+    let base_path =
+        setup_test_environment().expect("设置测试环境失败");
+    create_test_dataset(&base_path, TEST_NAME)
+        .expect("创建测试数据集失败");
+
+    let mut reader = PcapReader::new(&base_path, TEST_NAME)
+        .expect("创建Reader失败");
+    assert!(reader.index().query().is_none());
+    reader.initialize().expect("初始化失败");
+
+    let query = reader.index().query().expect("索引未加载");
+    assert_eq!(query.file_count(), 2);
+    assert_eq!(query.total_packets(), 100);
+    assert!(!query.is_empty());
+    assert_eq!(
+        query.time_range(),
+        Some((1_000_000_000, 1_990_000_000))
+    );
+    assert_eq!(
+        query.packet_counts_per_file(),
+        vec![50, 50]
+    );
+    assert_eq!(query.packet_count_in_file(1), Some(50));
+    assert_eq!(query.packet_count_in_file(2), None);
+    assert_eq!(query.file_start_index(1), Some(50));
+
+    let second_file =
+        query.file_name(1).expect("应存在第二个文件");
+    assert_eq!(query.file_position(second_file), Some(1));
+
+    // 按全局序号和文件内序号取得同一条目
+    let entry = query.nth_packet(75).expect("应存在条目");
+    let in_file = query
+        .nth_packet_in_file(1, 25)
+        .expect("应存在条目");
+    assert_eq!(in_file.global_index, entry.global_index);
+    assert_eq!(in_file.byte_offset, entry.byte_offset);
+    assert_eq!(query.file_index_of(75), Some(1));
+    assert_eq!(query.timestamp_at(75), Some(1_750_000_000));
+    assert_eq!(query.timestamp_at(100), None);
+
+    // 二分查找辅助
+    assert_eq!(
+        query.first_at_or_after(1_745_000_000),
+        Some(75)
+    );
+    assert_eq!(
+        query.last_at_or_before(1_745_000_000),
+        Some(74)
+    );
+    assert_eq!(
+        query.find_global_index(
+            1_750_000_000,
+            SeekMode::Exact
+        ),
+        Some(75)
+    );
+    assert_eq!(
+        query.first_at_or_after(2_000_000_000),
+        None
+    );
+    assert_eq!(
+        query.count_in_range(1_100_000_000, 1_190_000_000),
+        10
+    );
+    assert_eq!(query.count_in_range(5, 1), 0);
+}
+
+#[test]
+fn test_seek_modes() {
+    const TEST_NAME: &str = "test_seek_modes";
+    let base_path =
+        setup_test_environment().expect("设置测试环境失败");
+
+    create_test_dataset(&base_path, TEST_NAME)
+        .expect("创建测试数据集失败");
+
+    let mut reader = PcapReader::new(&base_path, TEST_NAME)
+        .expect("创建Reader失败");
+    reader.initialize().expect("初始化失败");
+
+    let ts = |i: u64| 1_000_000_000 + i * 10_000_000;
+    // 介于第40和41个包之间，更靠近第41个
+    let between = ts(40) + 6_000_000;
+
+    assert!(reader
+        .seek_to_timestamp_with_mode(
+            between,
+            SeekMode::Exact
+        )
+        .is_err());
+
+    let actual = reader
+        .seek_to_timestamp_with_mode(
+            between,
+            SeekMode::Floor,
+        )
+        .expect("Floor跳转失败");
+    assert_eq!(actual, ts(40));
+    assert_eq!(reader.current_packet_index(), 40);
+
+    let actual = reader
+        .seek_to_timestamp_with_mode(
+            between,
+            SeekMode::Ceiling,
+        )
+        .expect("Ceiling跳转失败");
+    assert_eq!(actual, ts(41));
+    assert_eq!(reader.current_packet_index(), 41);
+
+    let actual = reader
+        .seek_to_timestamp_with_mode(
+            between,
+            SeekMode::Nearest,
+        )
+        .expect("Nearest跳转失败");
+    assert_eq!(actual, ts(41));
+
+    // 边界：早于首包时 Floor 无结果，晚于末包时 Ceiling 无结果
+    assert!(reader
+        .seek_to_timestamp_with_mode(0, SeekMode::Floor)
+        .is_err());
+    assert!(reader
+        .seek_to_timestamp_with_mode(
+            u64::MAX,
+            SeekMode::Ceiling
+        )
+        .is_err());
+    let actual = reader
+        .seek_to_timestamp_with_mode(
+            u64::MAX,
+            SeekMode::Nearest,
+        )
+        .expect("Nearest跳转失败");
+    assert_eq!(actual, ts(99));
+}
+
+#[test]
+fn test_read_packet_by_timestamp_modes() {
+    const TEST_NAME: &str = "test_read_by_ts_modes";
+    let base_path =
+        setup_test_environment().expect("设置测试环境失败");
+
+    create_test_dataset(&base_path, TEST_NAME)
+        .expect("创建测试数据集失败");
+
+    let mut reader = PcapReader::new(&base_path, TEST_NAME)
+        .expect("创建Reader失败");
+
+    let ts = |i: u64| 1_000_000_000 + i * 10_000_000;
+    let between = ts(10) + 1;
+
+    assert!(reader
+        .read_packet_by_timestamp(between)
+        .expect("读取失败")
+        .is_none());
+
+    let packet = reader
+        .read_packet_by_timestamp_with_mode(
+            between,
+            SeekMode::Floor,
+        )
+        .expect("读取失败")
+        .expect("应找到数据包");
+    assert_eq!(packet.get_timestamp_ns(), ts(10));
+
+    let packet = reader
+        .read_packet_by_timestamp_with_mode(
+            between,
+            SeekMode::Ceiling,
+        )
+        .expect("读取失败")
+        .expect("应找到数据包");
+    assert_eq!(packet.get_timestamp_ns(), ts(11));
+}
+
+#[test]
+fn test_file_handle_pool_cross_file_access() {
+    const TEST_NAME: &str = "test_file_handle_pool";
+    let base_path =
+        setup_test_environment().expect("设置测试环境失败");
+
+    create_test_dataset(&base_path, TEST_NAME)
+        .expect("创建测试数据集失败");
+
+    let config = ReaderConfig {
+        max_open_files: 2,
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        &base_path, TEST_NAME, config,
+    )
+    .expect("创建Reader失败");
+
+    // 在两个文件之间交替随机访问
+    for i in 0..10u64 {
+        let packet_index =
+            if i % 2 == 0 { i } else { 50 + i };
+        let ts = 1_000_000_000 + packet_index * 10_000_000;
+        let packet = reader
+            .read_packet_by_timestamp(ts)
+            .expect("读取失败")
+            .expect("应找到数据包");
+        assert_eq!(packet.get_timestamp_ns(), ts);
+        assert_eq!(
+            packet.packet.data,
+            format!("Test packet {packet_index}")
+                .into_bytes()
+        );
+    }
+    assert_eq!(reader.open_file_count(), 2);
+
+    // 复用句柄后顺序读取仍从文件开头开始
+    reader.seek_to_packet(50).expect("跳转失败");
+    reader.seek_to_packet(0).expect("跳转失败");
+    reader.seek_to_packet(50).expect("跳转失败");
+    let packet = reader
+        .read_packet()
+        .expect("读取失败")
+        .expect("应有数据包");
+    assert_eq!(
+        packet.get_timestamp_ns(),
+        1_000_000_000 + 50 * 10_000_000
+    );
+    let mut count = 1;
+    while reader.read_packet().expect("读取失败").is_some()
+    {
+        count += 1;
+    }
+    assert_eq!(count, 50);
+
+    // 句柄池上限为1时只保留当前文件
+    let config = ReaderConfig {
+        max_open_files: 1,
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        &base_path, TEST_NAME, config,
+    )
+    .expect("创建Reader失败");
+    reader.seek_to_packet(0).expect("跳转失败");
+    reader.seek_to_packet(60).expect("跳转失败");
+    assert_eq!(reader.open_file_count(), 1);
+}
+
+#[test]
+fn test_relative_time_navigation() {
+    const TEST_NAME: &str = "test_relative_time";
+    let base_path =
+        setup_test_environment().expect("设置测试环境失败");
+    create_test_dataset(&base_path, TEST_NAME)
+        .expect("创建测试数据集失败");
+
+    let mut reader = PcapReader::new(&base_path, TEST_NAME)
+        .expect("创建Reader失败");
+
+    // 跳到起始后 255ms，落在第 26 个数据包（260ms）
+    let actual = reader
+        .seek_to_offset_from_start(Duration::from_millis(
+            255,
+        ))
+        .expect("相对时间跳转失败");
+    assert_eq!(actual, Duration::from_millis(260));
+    assert_eq!(reader.current_packet_index(), 26);
+
+    // 读取 100ms 内的数据包：260ms..360ms 共 10 个，跨越文件边界
+    let packets = reader
+        .read_packets_for(Duration::from_millis(100))
+        .expect("按时长读取失败");
+    assert_eq!(packets.len(), 10);
+    assert_eq!(
+        packets[0].packet.data,
+        b"Test packet 26".to_vec()
+    );
+    assert_eq!(reader.current_packet_index(), 36);
+
+    reader
+        .seek_to_offset_from_start(Duration::from_millis(
+            450,
+        ))
+        .expect("相对时间跳转失败");
+    let packets = reader
+        .read_packets_for(Duration::from_millis(100))
+        .expect("按时长读取失败");
+    assert_eq!(packets.len(), 10);
+    assert_eq!(reader.current_packet_index(), 55);
+
+    // 读到末尾后返回空列表
+    let packets = reader
+        .read_packets_for(Duration::from_secs(60))
+        .expect("按时长读取失败");
+    assert_eq!(packets.len(), 45);
+    assert!(reader
+        .read_packets_for(Duration::from_secs(1))
+        .expect("按时长读取失败")
+        .is_empty());
+
+    // 超出数据集时间范围
+    assert!(reader
+        .seek_to_offset_from_start(Duration::from_secs(10))
+        .is_err());
+    assert_eq!(
+        reader
+            .seek_to_offset_from_start(Duration::ZERO)
+            .expect("相对时间跳转失败"),
+        Duration::ZERO
+    );
+    assert_eq!(reader.current_packet_index(), 0);
+}
+
+#[test]
+fn test_bookmarks() {
+    const TEST_NAME: &str = "test_bookmarks";
+    let base_path =
+        setup_test_environment().expect("设置测试环境失败");
+    create_test_dataset(&base_path, TEST_NAME)
+        .expect("创建测试数据集失败");
+
+    let mut reader = PcapReader::new(&base_path, TEST_NAME)
+        .expect("创建Reader失败");
+    let bookmark = reader
+        .add_bookmark("anomaly", 73)
+        .expect("添加书签失败");
+    assert_eq!(bookmark.timestamp_ns, 1_730_000_000);
+    reader.add_bookmark("start", 0).expect("添加书签失败");
+    assert!(reader.add_bookmark("too_far", 100).is_err());
+    assert!(reader.add_bookmark(" ", 1).is_err());
+
+    // 书签保存在数据集目录中，另一个读取器可以直接使用
+    let mut teammate =
+        PcapReader::new(&base_path, TEST_NAME)
+            .expect("创建Reader失败");
+    let names: Vec<String> = teammate
+        .bookmarks()
+        .expect("读取书签失败")
+        .into_iter()
+        .map(|bookmark| bookmark.name)
+        .collect();
+    assert_eq!(names, vec!["anomaly", "start"]);
+
+    assert_eq!(
+        teammate
+            .seek_to_bookmark("anomaly")
+            .expect("跳转书签失败"),
+        73
+    );
+    let packet = teammate
+        .read_packet()
+        .expect("读取失败")
+        .expect("应存在数据包");
+    assert_eq!(
+        packet.packet.data,
+        b"Test packet 73".to_vec()
+    );
+    assert!(teammate.seek_to_bookmark("missing").is_err());
+
+    assert!(teammate
+        .remove_bookmark("start")
+        .expect("删除书签失败"));
+    assert!(!teammate
+        .remove_bookmark("start")
+        .expect("删除书签失败"));
+    assert_eq!(
+        reader.bookmarks().expect("读取书签失败").len(),
+        1
+    );
+}
+
+#[test]
+fn test_stream_markers() {
+    const TEST_NAME: &str = "test_stream_markers";
+    let base_path =
+        setup_test_environment().expect("设置测试环境失败");
+    clean_dataset_directory(base_path.join(TEST_NAME))
+        .expect("清理数据集目录失败");
+
+    let mut writer = PcapWriter::new(&base_path, TEST_NAME)
+        .expect("创建Writer失败");
+    let write_range =
+        |writer: &mut PcapWriter,
+         range: std::ops::Range<u32>| {
+            for i in range {
+                let packet = DataPacket::from_timestamp(
+                    1,
+                    i * 1_000,
+                    format!("Test packet {}", i)
+                        .into_bytes(),
+                )
+                .expect("创建数据包失败");
+                writer
+                    .write_packet(&packet)
+                    .expect("写入数据包失败");
+            }
+        };
+
+    write_range(&mut writer, 0..10);
+    let started = writer
+        .write_marker("scenario started")
+        .expect("插入标记失败");
+    assert_eq!(started.position, 10);
+    write_range(&mut writer, 10..25);
+    writer
+        .write_marker_at("scenario stopped", 1_000_025_000)
+        .expect("插入标记失败");
+    writer.finalize().expect("完成写入失败");
+    assert!(writer.write_marker("too late").is_err());
+
+    let mut reader = PcapReader::new(&base_path, TEST_NAME)
+        .expect("创建Reader失败");
+    let markers =
+        reader.list_markers().expect("读取标记失败");
+    assert_eq!(markers.len(), 2);
+    assert_eq!(markers[0].label, "scenario started");
+    assert_eq!(markers[1].label, "scenario stopped");
+    assert_eq!(markers[1].position, 25);
+    assert_eq!(markers[1].timestamp_ns, 1_000_025_000);
+
+    reader
+        .seek_to_packet(markers[0].position as usize)
+        .expect("跳转失败");
+    let packet = reader
+        .read_packet()
+        .expect("读取失败")
+        .expect("应存在数据包");
+    assert_eq!(
+        packet.packet.data,
+        b"Test packet 10".to_vec()
+    );
+
+    // 标记不写入数据文件，不影响数据包数量
+    assert_eq!(reader.total_packets(), Some(25));
+}
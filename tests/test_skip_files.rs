@@ -0,0 +1,201 @@
+//! 数据文件排除与隔离测试
+//!
+//! 测试文件头损坏的数据文件被自动隔离并记录在健康报告中、其余文件正常读取，
+//! 以及按文件名模式排除数据文件
+
+use pcapfile_io::{
+    matches_file_pattern, IndexHealthKind, PcapReader,
+    PcapWriter, ReaderConfig, WriterConfig,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "quarantine";
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+/// 写入 9 个数据包，每个文件 3 个，返回按文件名排序的数据文件
+fn write_dataset(base: &Path) -> Vec<PathBuf> {
+    let config = WriterConfig {
+        max_packets_per_file: 3,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base, TEST_NAME, config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..9u8 {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + u64::from(i) * 10,
+                &[i; 8],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut files: Vec<PathBuf> =
+        fs::read_dir(base.join(TEST_NAME))
+            .expect("读取目录失败")
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "pcap")
+            })
+            .collect();
+    files.sort();
+    files
+}
+
+fn read_markers(reader: &mut PcapReader) -> Vec<u8> {
+    let mut markers = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取失败")
+    {
+        markers.push(packet.packet.data[0]);
+    }
+    markers
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .expect("缺少文件名")
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn test_quarantines_corrupted_file() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let files = write_dataset(temp_dir.path());
+    assert_eq!(files.len(), 3);
+    let pidx_path =
+        temp_dir.path().join(TEST_NAME).join(".pidx");
+    fs::remove_file(&pidx_path).expect("删除索引失败");
+
+    // 破坏第二个文件的魔术数
+    let mut bytes = fs::read(&files[1]).expect("读取失败");
+    bytes[..4].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+    fs::write(&files[1], bytes).expect("写入失败");
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    assert_eq!(
+        read_markers(&mut reader),
+        vec![0, 1, 2, 6, 7, 8]
+    );
+    let health = reader.index().health();
+    assert!(health.has(&IndexHealthKind::QuarantinedFile));
+    let entries = health.entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        entries[0].path.as_deref(),
+        Some(files[1].as_path())
+    );
+
+    // 重新打开时隔离的文件不使索引失效，仍记录在健康报告中
+    let modified = fs::metadata(&pidx_path)
+        .and_then(|metadata| metadata.modified())
+        .expect("读取索引信息失败");
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    reader.initialize().expect("初始化失败");
+    assert_eq!(
+        fs::metadata(&pidx_path)
+            .and_then(|metadata| metadata.modified())
+            .expect("读取索引信息失败"),
+        modified
+    );
+    let health = reader.index().health();
+    assert_eq!(health.entries().len(), 1);
+    assert_eq!(
+        health.entries()[0].kind,
+        IndexHealthKind::QuarantinedFile
+    );
+    assert_eq!(
+        reader
+            .index()
+            .get_index()
+            .expect("索引未加载")
+            .files()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn test_skip_files_by_pattern() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let files = write_dataset(temp_dir.path());
+
+    let config = ReaderConfig {
+        skip_files: vec![file_name(&files[0])],
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("打开失败");
+    assert_eq!(
+        read_markers(&mut reader),
+        vec![3, 4, 5, 6, 7, 8]
+    );
+    assert!(reader.index().health().is_healthy());
+
+    // 通配符排除全部数据文件
+    let config = ReaderConfig {
+        skip_files: vec!["*.pcap".to_string()],
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("打开失败");
+    assert!(read_markers(&mut reader).is_empty());
+
+    // 不排除时读取全部数据包
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("打开失败");
+    assert_eq!(read_markers(&mut reader).len(), 9);
+}
+
+#[test]
+fn test_matches_file_pattern() {
+    assert!(matches_file_pattern(
+        "data_001.pcap",
+        "*.pcap"
+    ));
+    assert!(matches_file_pattern(
+        "data_001.pcap",
+        "data_00?.pcap"
+    ));
+    assert!(matches_file_pattern(
+        "data_001.pcap",
+        "*_0*1*"
+    ));
+    assert!(matches_file_pattern("data.pcap", "data.pcap"));
+    assert!(matches_file_pattern("", "*"));
+    assert!(!matches_file_pattern(
+        "data_001.pcap",
+        "data_00?"
+    ));
+    assert!(!matches_file_pattern(
+        "data_001.pcap",
+        "*.PCAP"
+    ));
+    assert!(!matches_file_pattern(
+        "data.pcap",
+        "?data.pcap"
+    ));
+    assert!(!matches_file_pattern("a", ""));
+}
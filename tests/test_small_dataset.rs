@@ -3,8 +3,8 @@
 //! 测试基本的读写功能和索引生成
 
 use pcapfile_io::{
-    PcapReader, PcapResult, PcapWriter, ReaderConfig,
-    WriterConfig,
+    ByteSize, PcapReader, PcapResult, PcapWriter,
+    ReaderConfig, WriterConfig,
 };
 use std::path::Path;
 use tempfile::TempDir;
@@ -251,3 +251,148 @@ fn test_small_dataset_empty_and_edge_cases() {
 
     println!("✅ 空数据集和边界情况测试通过");
 }
+
+#[test]
+fn test_small_dataset_read_chunks_with_budget() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let dataset_name = "chunked_read_test";
+
+    const PACKET_COUNT: usize = 250;
+    const PACKET_SIZE: usize = 100;
+    const BUDGET: usize = 1000;
+
+    let config = WriterConfig {
+        max_packets_per_file: 60,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base_path,
+        dataset_name,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+    for i in 0..PACKET_COUNT {
+        let packet = create_test_packet(i, PACKET_SIZE)
+            .expect("创建数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(base_path, dataset_name)
+            .expect("创建PcapReader失败");
+
+    let mut total = 0;
+    let mut chunk_count = 0;
+    for chunk in reader
+        .read_chunks(BUDGET)
+        .expect("创建分批迭代器失败")
+    {
+        let chunk = chunk.expect("读取批次失败");
+        let bytes: usize =
+            chunk.iter().map(|p| p.packet_length()).sum();
+        assert!(bytes <= BUDGET, "批次超出内存预算");
+        total += chunk.len();
+        chunk_count += 1;
+    }
+
+    assert_eq!(total, PACKET_COUNT);
+    assert_eq!(chunk_count, PACKET_COUNT / 10);
+
+    // 单包超预算时每批仅包含一个数据包
+    reader.reset().expect("重置失败");
+    let chunk =
+        reader.read_chunk(10).expect("读取批次失败");
+    assert_eq!(chunk.len(), 1);
+
+    assert!(reader.read_chunks(0).is_err());
+}
+
+#[test]
+fn test_small_dataset_coalesced_writes() {
+    const BLOCK: usize = 4096;
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let dataset_name = "coalesced_test";
+
+    let config = WriterConfig {
+        min_write_block: ByteSize::from(BLOCK),
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base_path,
+        dataset_name,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+
+    // 小数据包为主，穿插超过阈值的大数据包
+    let sizes: Vec<usize> =
+        (0..400)
+            .map(|i| {
+                if i % 50 == 49 {
+                    1500
+                } else {
+                    40 + i % 20
+                }
+            })
+            .collect();
+    for (i, &size) in sizes.iter().take(290).enumerate() {
+        let packet = create_test_packet(i, size)
+            .expect("创建数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+
+    // 最后一个数据包是小数据包时，磁盘上的数据停在块边界
+    let pcap_file =
+        std::fs::read_dir(base_path.join(dataset_name))
+            .expect("读取数据集目录失败")
+            .filter_map(|entry| {
+                entry.ok().map(|e| e.path())
+            })
+            .find(|path| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    == Some("pcap")
+            })
+            .expect("未找到数据文件");
+    let on_disk = std::fs::metadata(&pcap_file)
+        .expect("读取文件信息失败")
+        .len();
+    assert!(on_disk > 0);
+    assert_eq!(on_disk % BLOCK as u64, 0);
+
+    for (i, &size) in sizes.iter().enumerate().skip(290) {
+        let packet = create_test_packet(i, size)
+            .expect("创建数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(base_path, dataset_name)
+            .expect("创建PcapReader失败");
+    let mut read_sizes = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        assert!(packet.is_valid);
+        read_sizes.push(packet.packet.data.len());
+    }
+    assert_eq!(read_sizes, sizes);
+
+    let invalid = WriterConfig {
+        min_write_block: ByteSize::b(3000),
+        ..Default::default()
+    };
+    assert!(invalid.validate().is_err());
+}
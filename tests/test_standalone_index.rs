@@ -0,0 +1,195 @@
+//! 独立索引模式测试
+//!
+//! 测试没有数据集目录时加载、查询、修改并改写PIDX文件，
+//! 以及需要数据文件的操作在独立索引模式下被拒绝
+
+use pcapfile_io::business::index::IndexManager;
+use pcapfile_io::{
+    PcapError, PcapWriter, SeekMode, WriterConfig,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const TEST_NAME: &str = "recorder";
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+/// 写入跨 3 个文件的数据集，把索引复制到目录服务目录后删除数据集
+fn catalog_index(base: &Path) -> PathBuf {
+    let config = WriterConfig {
+        max_packets_per_file: 4,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base, TEST_NAME, config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..10u64 {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + i * 1_000,
+                &[i as u8; 16],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+    drop(writer);
+
+    let dataset_path = base.join(TEST_NAME);
+    let catalog = base.join("catalog");
+    fs::create_dir_all(&catalog).expect("创建目录失败");
+    let pidx_path = catalog.join("recorder-a.pidx");
+    fs::copy(dataset_path.join(".pidx"), &pidx_path)
+        .expect("复制索引失败");
+    fs::remove_dir_all(&dataset_path)
+        .expect("删除数据集失败");
+    pidx_path
+}
+
+#[test]
+fn test_query_standalone_index() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let pidx_path = catalog_index(temp_dir.path());
+
+    let mut manager =
+        IndexManager::open_standalone(&pidx_path)
+            .expect("打开独立索引失败");
+    assert!(manager.is_standalone());
+    assert_eq!(manager.index_file_path(), pidx_path);
+
+    let query = manager.query().expect("索引未加载");
+    assert_eq!(query.file_count(), 3);
+    assert_eq!(
+        query.packet_counts_per_file(),
+        vec![4, 4, 2]
+    );
+    assert_eq!(
+        query.timestamp_at(5),
+        Some(BASE_TIMESTAMP + 5_000)
+    );
+
+    let pointer = manager
+        .find_packet_with_mode(
+            BASE_TIMESTAMP + 5_500,
+            SeekMode::Floor,
+        )
+        .expect("查找失败")
+        .expect("应找到数据包");
+    assert_eq!(pointer.entry.global_index, 5);
+    assert_eq!(pointer.file_index, 1);
+
+    // 已加载的索引直接可用，不扫描数据文件
+    let index =
+        manager.ensure_index().expect("获取索引失败");
+    assert_eq!(index.total_packets(), 10);
+
+    for result in [
+        manager.rebuild_index().map(|_| ()),
+        manager.compact().map(|_| ()),
+        manager.validate_index().map(|_| ()),
+        manager.needs_rebuild().map(|_| ()),
+    ] {
+        assert!(matches!(
+            result,
+            Err(PcapError::InvalidState(_))
+        ));
+    }
+}
+
+#[test]
+fn test_transform_and_rewrite_standalone_index() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let pidx_path = catalog_index(temp_dir.path());
+
+    let mut manager =
+        IndexManager::open_standalone(&pidx_path)
+            .expect("打开独立索引失败");
+
+    // 移除最早的文件，时间范围、总数和查找表随之更新
+    let removed = manager
+        .transform_index(|index| {
+            index.data_files.files.remove(0).file_name
+        })
+        .expect("修改索引失败");
+    assert!(removed.ends_with(".pcap"));
+    let index = manager.get_index().expect("索引未加载");
+    assert_eq!(index.total_packets(), 6);
+    assert_eq!(
+        index.start_timestamp(),
+        BASE_TIMESTAMP + 4_000
+    );
+    let pointer = manager
+        .find_packet_with_mode(
+            BASE_TIMESTAMP,
+            SeekMode::Ceiling,
+        )
+        .expect("查找失败")
+        .expect("应找到数据包");
+    assert_eq!(pointer.entry.global_index, 0);
+    assert_eq!(
+        pointer.entry.timestamp_ns,
+        BASE_TIMESTAMP + 4_000
+    );
+
+    // 改写原文件，重新打开后内容一致且通过结构校验
+    assert_eq!(
+        manager.save_index().expect("保存索引失败"),
+        pidx_path
+    );
+    IndexManager::validate_index_schema(&pidx_path)
+        .expect("结构校验失败");
+    let reopened =
+        IndexManager::open_standalone(&pidx_path)
+            .expect("重新打开失败");
+    let index = reopened.get_index().expect("索引未加载");
+    assert_eq!(index.total_packets(), 6);
+    assert_eq!(index.file_count(), 2);
+    assert!(index
+        .files()
+        .iter()
+        .all(|file| file.file_name != removed));
+
+    // 另存到其他位置
+    let copy_path = temp_dir.path().join("copy.pidx");
+    reopened
+        .save_index_as(&copy_path)
+        .expect("另存索引失败");
+    let copy = IndexManager::open_standalone(&copy_path)
+        .expect("打开副本失败");
+    assert_eq!(
+        copy.get_index().map(|index| index.total_packets()),
+        Some(6)
+    );
+}
+
+#[test]
+fn test_open_standalone_errors() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    assert!(matches!(
+        IndexManager::open_standalone(
+            temp_dir.path().join("missing.pidx")
+        ),
+        Err(PcapError::FileNotFound(_))
+    ));
+
+    // 内容被改动后摘要不匹配
+    let pidx_path = catalog_index(temp_dir.path());
+    let content =
+        fs::read_to_string(&pidx_path).expect("读取失败");
+    fs::write(
+        &pidx_path,
+        content.replacen(
+            "packet_count=\"4\"",
+            "packet_count=\"5\"",
+            1,
+        ),
+    )
+    .expect("写入失败");
+    assert!(matches!(
+        IndexManager::open_standalone(&pidx_path),
+        Err(PcapError::InvalidFormat(_))
+    ));
+}
@@ -0,0 +1,172 @@
+//! 存储分片测试
+//!
+//! 测试写入器按轮询方式把文件分布到多个存储分片，以及读取器通过索引和分片清单读取
+
+use pcapfile_io::{
+    PcapReader, PcapWriter, ShardManifest, WriterConfig,
+};
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::create_test_packet;
+
+const TEST_NAME: &str = "sharded_dataset";
+
+fn pcap_count(directory: &Path) -> usize {
+    std::fs::read_dir(directory)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| {
+                    entry
+                        .path()
+                        .extension()
+                        .is_some_and(|ext| ext == "pcap")
+                })
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+#[test]
+fn test_files_distributed_across_shards() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path().join("primary");
+    let shard_a = temp_dir.path().join("disk_a");
+    let shard_b = temp_dir.path().join("disk_b");
+
+    let config = WriterConfig {
+        max_packets_per_file: 10,
+        storage_shards: vec![
+            shard_a.clone(),
+            shard_b.clone(),
+        ],
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        &base_path, TEST_NAME, config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..45 {
+        writer
+            .write_packet(
+                &create_test_packet(i, 32)
+                    .expect("创建测试数据包失败"),
+            )
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    // 5 个文件轮流写入两个分片，主目录只保存索引和清单
+    let dataset_path = base_path.join(TEST_NAME);
+    assert_eq!(pcap_count(&dataset_path), 0);
+    assert_eq!(pcap_count(&shard_a.join(TEST_NAME)), 3);
+    assert_eq!(pcap_count(&shard_b.join(TEST_NAME)), 2);
+    assert!(dataset_path.join(".pidx").exists());
+    assert_eq!(
+        ShardManifest::load(&dataset_path)
+            .directories
+            .len(),
+        2
+    );
+
+    // 读取器无需分片配置，依据索引中记录的分片定位文件
+    let mut reader = PcapReader::new(&base_path, TEST_NAME)
+        .expect("创建读取器失败");
+    let files = reader
+        .get_file_info_list()
+        .expect("获取文件列表失败");
+    assert_eq!(files.len(), 5);
+    assert!(files.iter().all(|f| f.file_path.exists()));
+    let index =
+        reader.index().get_index().expect("索引未加载");
+    assert!(index
+        .files()
+        .iter()
+        .all(|f| f.shard.is_some()));
+
+    let mut count = 0u32;
+    while let Some(packet) =
+        reader.read_packet().expect("读取失败")
+    {
+        assert!(packet.is_valid());
+        count += 1;
+    }
+    assert_eq!(count, 45);
+
+    // 随机访问跨分片
+    reader.seek_to_packet(33).expect("跳转失败");
+    assert!(reader
+        .read_packet()
+        .expect("读取失败")
+        .is_some());
+}
+
+#[test]
+fn test_sharded_index_rebuilt_from_manifest() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path().join("primary");
+
+    let config = WriterConfig {
+        max_packets_per_file: 4,
+        storage_shards: vec![
+            temp_dir.path().join("disk_a"),
+            temp_dir.path().join("disk_b"),
+            temp_dir.path().join("disk_c"),
+        ],
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        &base_path, TEST_NAME, config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..20 {
+        writer
+            .write_packet(
+                &create_test_packet(i, 16)
+                    .expect("创建测试数据包失败"),
+            )
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    // 删除索引后重新生成，分片中的文件依据分片清单找回
+    std::fs::remove_file(
+        base_path.join(TEST_NAME).join(".pidx"),
+    )
+    .expect("删除索引失败");
+
+    let mut reader = PcapReader::new(&base_path, TEST_NAME)
+        .expect("创建读取器失败");
+    reader.initialize().expect("初始化失败");
+    assert_eq!(reader.total_packets(), Some(20));
+    assert_eq!(
+        reader
+            .get_file_info_list()
+            .expect("获取文件列表失败")
+            .len(),
+        5
+    );
+}
+
+#[test]
+fn test_invalid_shard_configuration() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let shard = temp_dir.path().join("disk_a");
+
+    let config = WriterConfig {
+        storage_shards: vec![shard.clone(), shard],
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+    assert!(PcapWriter::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config
+    )
+    .is_err());
+}
@@ -0,0 +1,423 @@
+//! 严格解析测试
+//!
+//! 测试严格解析接口报告的字段错误、快速结构检查、任意输入不会导致 panic，
+//! 以及不支持的版本、错误代码的稳定数值、错误分类和结构化序列化
+
+use pcapfile_io::{
+    validate_file_quick, DataPacketHeader, ErrorDetail,
+    ErrorResult, LinkType, MemoryReader, PcapError,
+    PcapErrorCode, PcapFileHeader, PcapReader, PcapWriter,
+};
+use serde_json::json;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// 写入一个单文件数据集，返回数据文件路径
+fn write_single_file(
+    base_path: &std::path::Path,
+    packet_count: u64,
+) -> PathBuf {
+    let mut writer = PcapWriter::new(base_path, "strict")
+        .expect("创建写入器失败");
+    for i in 0..packet_count {
+        writer
+            .write_payload(
+                1_700_000_000_000_000_000 + i * 1_000,
+                &vec![i as u8; 8 + i as usize],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    fs::read_dir(base_path.join("strict"))
+        .expect("读取数据集目录失败")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .find(|path| {
+            path.extension().and_then(|ext| ext.to_str())
+                == Some("pcap")
+        })
+        .expect("未找到数据文件")
+}
+
+#[test]
+fn test_file_header_parse_strict() {
+    let header = PcapFileHeader::with_link_type(
+        0,
+        LinkType::Ethernet,
+    );
+    let bytes = header.to_bytes();
+    let parsed = PcapFileHeader::parse_strict(&bytes)
+        .expect("有效文件头应解析成功");
+    assert_eq!(parsed.link_type, LinkType::Ethernet);
+
+    let error = PcapFileHeader::parse_strict(&bytes[..10])
+        .expect_err("字节数不足应失败");
+    assert_eq!(error.field, "bytes");
+    assert_eq!(error.found, "10 字节");
+
+    // 扩展文件头缺少链路类型字段
+    let error = PcapFileHeader::parse_strict(&bytes[..16])
+        .expect_err("缺少链路类型应失败");
+    assert_eq!(error.field, "bytes");
+
+    let mut corrupted = bytes.clone();
+    corrupted[0] ^= 0xFF;
+    let error = PcapFileHeader::parse_strict(&corrupted)
+        .expect_err("魔术数无效应失败");
+    assert_eq!(error.field, "magic_number");
+    assert_eq!(error.offset, 0);
+    assert_eq!(error.expected, "0xD4C3B2A1");
+
+    let mut corrupted = bytes.clone();
+    corrupted[6] = 9;
+    let error = PcapFileHeader::parse_strict(&corrupted)
+        .expect_err("次版本号无效应失败");
+    assert_eq!(error.field, "minor_version");
+    assert_eq!(error.offset, 6);
+    assert_eq!(error.found, "9");
+    // 宽松解析接受同样的字节
+    assert!(PcapFileHeader::from_bytes(&corrupted).is_ok());
+}
+
+#[test]
+fn test_packet_header_parse_strict() {
+    let header =
+        DataPacketHeader::new(1_700_000_000, 5, 64, 0x1234)
+            .expect("创建头部失败");
+    let parsed =
+        DataPacketHeader::parse_strict(&header.to_bytes())
+            .expect("有效头部应解析成功");
+    assert_eq!(
+        parsed.timestamp_ns(),
+        header.timestamp_ns()
+    );
+    assert_eq!(parsed.packet_length, 64);
+    assert_eq!(parsed.checksum, 0x1234);
+
+    let mut bytes = header.to_bytes();
+    bytes[4..8]
+        .copy_from_slice(&1_000_000_000u32.to_le_bytes());
+    let error = DataPacketHeader::parse_strict(&bytes)
+        .expect_err("纳秒字段超出范围应失败");
+    assert_eq!(error.field, "timestamp_nanoseconds");
+    assert_eq!(error.offset, 4);
+    assert_eq!(error.found, "1000000000");
+    assert!(error
+        .to_string()
+        .contains("timestamp_nanoseconds"));
+}
+
+#[test]
+fn test_validate_file_quick() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let pcap_file = write_single_file(temp_dir.path(), 20);
+
+    let structure =
+        validate_file_quick(&pcap_file).expect("检查失败");
+    assert_eq!(structure.packet_count, 20);
+    assert_eq!(
+        structure.first_timestamp_ns,
+        Some(1_700_000_000_000_000_000)
+    );
+    assert_eq!(
+        structure.last_timestamp_ns,
+        Some(1_700_000_000_000_019_000)
+    );
+    assert_eq!(
+        structure.file_size,
+        fs::metadata(&pcap_file).unwrap().len()
+    );
+
+    // 负载损坏不影响结构检查
+    let original =
+        fs::read(&pcap_file).expect("读取文件失败");
+    let mut bytes = original.clone();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xFF;
+    fs::write(&pcap_file, &bytes).expect("写入文件失败");
+    assert!(validate_file_quick(&pcap_file).is_ok());
+
+    // 第二个数据包头部的纳秒字段超出范围
+    let mut bytes = original.clone();
+    let second = structure.data_offset as usize + 16 + 8;
+    bytes[second + 4..second + 8]
+        .copy_from_slice(&u32::MAX.to_le_bytes());
+    fs::write(&pcap_file, &bytes).expect("写入文件失败");
+    match validate_file_quick(&pcap_file) {
+        Err(PcapError::InvalidHeaderField {
+            error,
+            position,
+        }) => {
+            assert_eq!(
+                error.field,
+                "timestamp_nanoseconds"
+            );
+            assert_eq!(position, second as u64);
+        }
+        other => panic!("应报告头部字段错误: {other:?}"),
+    }
+
+    // 文件在数据包中途结束
+    let mut bytes = original.clone();
+    bytes.truncate(bytes.len() - 3);
+    fs::write(&pcap_file, &bytes).expect("写入文件失败");
+    assert!(matches!(
+        validate_file_quick(&pcap_file),
+        Err(
+            PcapError::PacketSizeExceedsRemainingBytes { .. }
+        )
+    ));
+
+    // 文件在数据包头部中途结束
+    let mut bytes = original;
+    bytes.extend_from_slice(&[0u8; 7]);
+    fs::write(&pcap_file, &bytes).expect("写入文件失败");
+    match validate_file_quick(&pcap_file) {
+        Err(PcapError::InvalidHeaderField {
+            error,
+            ..
+        }) => {
+            assert_eq!(error.field, "bytes");
+        }
+        other => panic!("应报告头部字段错误: {other:?}"),
+    }
+
+    assert!(matches!(
+        validate_file_quick(
+            temp_dir.path().join("missing.pcap")
+        ),
+        Err(PcapError::FileNotFound(_))
+    ));
+}
+
+#[test]
+fn test_strict_parsers_accept_arbitrary_input() {
+    // 简单的线性同余生成器，保证结果可复现
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let mut next = || {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (state >> 33) as u8
+    };
+
+    let valid = PcapFileHeader::new(0).to_bytes();
+    for round in 0..2_000usize {
+        let length = round % 24;
+        let mut bytes: Vec<u8> =
+            (0..length).map(|_| next()).collect();
+        // 一半的输入以有效的魔术数和版本号开头，覆盖更深的检查分支
+        if round % 2 == 0 {
+            let prefix = length.min(8);
+            bytes[..prefix]
+                .copy_from_slice(&valid[..prefix]);
+        }
+        let _ = PcapFileHeader::parse_strict(&bytes);
+        let _ = DataPacketHeader::parse_strict(&bytes);
+    }
+}
+
+#[test]
+fn test_unsupported_version_reported() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let file_path = write_single_file(temp_dir.path(), 4);
+    let mut bytes = fs::read(&file_path).expect("读取失败");
+    // 魔术数有效，次版本号为未知的 2.9
+    bytes[6..8].copy_from_slice(&9u16.to_le_bytes());
+
+    match MemoryReader::from_bytes(bytes.clone()) {
+        Err(PcapError::UnsupportedVersion {
+            major,
+            minor,
+        }) => assert_eq!((major, minor), (2, 9)),
+        other => panic!("应返回版本不支持错误: {other:?}"),
+    }
+    // 索引加载后替换数据文件，打开数据文件时报告版本不支持
+    let mut reader =
+        PcapReader::new(temp_dir.path(), "strict")
+            .expect("打开失败");
+    reader.initialize().expect("初始化失败");
+    fs::write(&file_path, &bytes).expect("写入失败");
+    let error = reader
+        .read_packet()
+        .expect_err("不支持的版本应读取失败");
+    assert_eq!(
+        error.error_code(),
+        PcapErrorCode::UnsupportedVersion
+    );
+
+    // 魔术数无效仍按文件头损坏处理
+    bytes[0] ^= 0xFF;
+    assert!(matches!(
+        MemoryReader::from_bytes(bytes),
+        Err(PcapError::CorruptedHeader(_))
+    ));
+}
+
+#[test]
+fn test_error_code_i32_mapping() {
+    for code in PcapErrorCode::ALL {
+        let value = code.as_i32();
+        assert_eq!(
+            PcapErrorCode::from_i32(value),
+            Some(code)
+        );
+        assert_eq!(i32::from(code), value);
+        assert_eq!(
+            PcapErrorCode::try_from(value),
+            Ok(code)
+        );
+    }
+    // 已发布的代码数值保持不变
+    assert_eq!(PcapErrorCode::Unknown.as_i32(), 0);
+    assert_eq!(PcapErrorCode::DiskFull.as_i32(), 1003);
+    assert_eq!(PcapErrorCode::InvalidFormat.as_i32(), 2001);
+    assert_eq!(
+        PcapErrorCode::UnsupportedVersion.as_i32(),
+        2008
+    );
+    assert_eq!(PcapErrorCode::DatasetLocked.as_i32(), 3007);
+    assert_eq!(PcapErrorCode::Cancelled.as_i32(), 3011);
+
+    assert_eq!(PcapErrorCode::from_i32(-1), None);
+    assert_eq!(PcapErrorCode::from_i32(9999), None);
+    assert_eq!(PcapErrorCode::try_from(4242), Err(4242));
+
+    let error = PcapError::Cancelled("导出任务".into());
+    assert_eq!(
+        error.error_code(),
+        PcapErrorCode::Cancelled
+    );
+    assert_eq!(
+        error.to_string(),
+        "operation cancelled: 导出任务"
+    );
+}
+
+#[test]
+fn test_error_classification() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let file_path = write_single_file(temp_dir.path(), 4);
+    let mut bytes = fs::read(&file_path).expect("读取失败");
+    bytes[0] ^= 0xFF;
+    let error = MemoryReader::from_bytes(bytes)
+        .expect_err("损坏的文件头应失败");
+    assert!(error.is_corruption());
+    assert!(!error.is_retryable());
+
+    let corrupted = [
+        PcapError::CorruptedData {
+            message: "负载截断".into(),
+            position: 24,
+        },
+        PcapError::ChecksumMismatch {
+            expected: "0x1".into(),
+            actual: "0x2".into(),
+            position: 24,
+        },
+        PcapError::Io(io::Error::from(
+            io::ErrorKind::UnexpectedEof,
+        )),
+    ];
+    for error in corrupted {
+        assert!(error.is_corruption(), "{error}");
+        assert!(!error.is_retryable(), "{error}");
+    }
+
+    let transient = [
+        PcapError::Io(io::Error::from(
+            io::ErrorKind::Interrupted,
+        )),
+        PcapError::Io(io::Error::from(
+            io::ErrorKind::TimedOut,
+        )),
+        PcapError::DatasetLocked("capture".into()),
+        PcapError::SinkFailed("连接断开".into()),
+    ];
+    for error in transient {
+        assert!(error.is_retryable(), "{error}");
+        assert!(!error.is_corruption(), "{error}");
+    }
+
+    // 参数错误和版本不支持既不可重试也不属于损坏
+    let fatal = [
+        PcapError::InvalidArgument("缓冲区为0".into()),
+        PcapError::UnsupportedVersion {
+            major: 2,
+            minor: 9,
+        },
+        PcapError::Io(io::Error::from(
+            io::ErrorKind::PermissionDenied,
+        )),
+    ];
+    for error in fatal {
+        assert!(!error.is_retryable(), "{error}");
+        assert!(!error.is_corruption(), "{error}");
+    }
+}
+
+#[test]
+fn test_error_serialization() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let file_path = write_single_file(temp_dir.path(), 4);
+    let bytes = fs::read(&file_path).expect("读取失败");
+    let mut reader =
+        MemoryReader::from_bytes(&bytes[..bytes.len() - 3])
+            .expect("创建内存读取器失败");
+    let error = reader
+        .read_packets(4)
+        .expect_err("截断的负载应失败");
+
+    let value =
+        serde_json::to_value(&error).expect("序列化失败");
+    assert_eq!(
+        value,
+        json!({
+            "code": 3002,
+            "message": error.to_string(),
+            "position": error.position(),
+            "file": null,
+        })
+    );
+    assert!(error.position().is_some());
+
+    let detail: ErrorDetail = serde_json::from_value(value)
+        .expect("反序列化失败");
+    assert_eq!(detail, error.to_detail());
+    assert_eq!(
+        detail.error_code(),
+        Some(
+            PcapErrorCode::PacketSizeExceedsRemainingBytes
+        )
+    );
+
+    let error = PcapError::FileNotFound("a.pcap".into());
+    let result = ErrorResult::from_error(error);
+    assert_eq!(
+        serde_json::to_value(&result).expect("序列化失败"),
+        json!({
+            "success": false,
+            "code": 1001,
+            "message": "file not found: a.pcap",
+            "position": null,
+            "file": "a.pcap",
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(ErrorResult::success())
+            .expect("序列化失败"),
+        json!({
+            "success": true,
+            "code": null,
+            "message": null,
+            "position": null,
+            "file": null,
+        })
+    );
+}
@@ -0,0 +1,128 @@
+//! 多数据集同步读取测试
+//!
+//! 测试多个数据集按对齐后的时间戳合并输出，以及时间偏移和跳转
+
+use pcapfile_io::{DataPacket, PcapWriter, SyncedReader};
+use std::path::Path;
+use tempfile::TempDir;
+
+const BASE_SECONDS: u32 = 1_700_000_000;
+
+/// 写入时间戳为 BASE_SECONDS 秒加给定纳秒数的数据集
+fn write_dataset(
+    base_path: &Path,
+    name: &str,
+    nanos: &[u32],
+) {
+    let mut writer = PcapWriter::new(base_path, name)
+        .expect("创建写入器失败");
+    for &nanosecond in nanos {
+        let packet = DataPacket::from_timestamp(
+            BASE_SECONDS,
+            nanosecond,
+            name.as_bytes().to_vec(),
+        )
+        .expect("创建数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+fn ts(nanosecond: u32) -> u64 {
+    BASE_SECONDS as u64 * 1_000_000_000 + nanosecond as u64
+}
+
+#[test]
+fn test_synced_reader_merges_in_timestamp_order() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    write_dataset(base_path, "camera", &[0, 20, 40, 60]);
+    write_dataset(base_path, "lidar", &[10, 20, 30]);
+
+    let mut reader = SyncedReader::new();
+    let camera = reader
+        .add_dataset(base_path, "camera", 0)
+        .expect("添加数据集失败");
+    let lidar = reader
+        .add_dataset(base_path, "lidar", 0)
+        .expect("添加数据集失败");
+    assert_eq!(reader.dataset_count(), 2);
+    assert_eq!(reader.dataset_name(lidar), Some("lidar"));
+
+    let merged: Vec<(usize, u64)> = reader
+        .by_ref()
+        .map(|packet| {
+            let packet = packet.expect("读取数据包失败");
+            (packet.dataset_id, packet.aligned_timestamp_ns)
+        })
+        .collect();
+    // 时间戳相同时编号较小的数据集在前
+    assert_eq!(
+        merged,
+        vec![
+            (camera, ts(0)),
+            (lidar, ts(10)),
+            (camera, ts(20)),
+            (lidar, ts(20)),
+            (lidar, ts(30)),
+            (camera, ts(40)),
+            (camera, ts(60)),
+        ]
+    );
+
+    // 跳转后从对齐时间戳处继续
+    reader.seek_to_timestamp(ts(25)).expect("跳转失败");
+    let packet = reader
+        .read_packet()
+        .expect("读取数据包失败")
+        .expect("应存在数据包");
+    assert_eq!(
+        (packet.dataset_id, packet.aligned_timestamp_ns),
+        (lidar, ts(30))
+    );
+
+    reader.reset().expect("重置失败");
+    assert_eq!(
+        reader.read_packets(100).expect("读取失败").len(),
+        7
+    );
+}
+
+#[test]
+fn test_synced_reader_applies_offsets() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    write_dataset(base_path, "camera", &[100, 200]);
+    write_dataset(base_path, "lidar", &[0, 50]);
+
+    // lidar 的时钟慢了 120 纳秒
+    let mut reader = SyncedReader::new();
+    reader
+        .add_dataset(base_path, "camera", 0)
+        .expect("添加数据集失败");
+    let lidar = reader
+        .add_dataset(base_path, "lidar", 120)
+        .expect("添加数据集失败");
+    assert_eq!(reader.offset(lidar), Some(120));
+
+    let packets =
+        reader.read_packets(10).expect("读取数据包失败");
+    let order: Vec<u64> = packets
+        .iter()
+        .map(|packet| packet.aligned_timestamp_ns)
+        .collect();
+    assert_eq!(
+        order,
+        vec![ts(100), ts(120), ts(170), ts(200)]
+    );
+
+    // 数据包本身的时间戳不变
+    assert_eq!(packets[1].dataset_id, lidar);
+    assert_eq!(packets[1].packet.get_timestamp_ns(), ts(0));
+
+    assert!(reader.set_offset(5, 0).is_err());
+}
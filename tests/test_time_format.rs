@@ -0,0 +1,213 @@
+//! 时间格式化测试
+//!
+//! 测试 strftime、.NET 风格和 `{}` 模板三种格式的输出，格式错误的位置，
+//! 以及写入器按配置的文件命名格式生成文件名
+
+use chrono::{DateTime, NaiveDate, Utc};
+use pcapfile_io::{
+    PcapReader, PcapWriter, TimeFormatter, WriterConfig,
+};
+use std::fs;
+use tempfile::TempDir;
+
+fn sample_time() -> DateTime<Utc> {
+    NaiveDate::from_ymd_opt(2024, 3, 5)
+        .and_then(|date| {
+            date.and_hms_nano_opt(7, 8, 9, 123_456_789)
+        })
+        .expect("构造时间失败")
+        .and_utc()
+}
+
+#[test]
+fn test_format_patterns() {
+    let time = sample_time();
+    let cases = [
+        (
+            "yyMMdd_HHmmss_fffffffff",
+            "240305_070809_123456789",
+        ),
+        ("yyyy-M-d H.m.s.fff", "2024-3-5 7.8.9.123"),
+        ("'day' dd", "day 05"),
+        (
+            "capture_%Y%m%d_%H%M%S_%9f",
+            "capture_20240305_070809_123456789",
+        ),
+        ("%-d/%-m %%", "5/3 %"),
+        ("radar_{}", "radar_240305_070809_123456789"),
+    ];
+    for (pattern, expected) in cases {
+        let formatter =
+            TimeFormatter::new(pattern).expect(pattern);
+        assert_eq!(formatter.format(&time), expected);
+        assert_eq!(formatter.pattern(), pattern);
+    }
+
+    let formatter =
+        TimeFormatter::new("%Y-%m-%dT%H:%M:%S%.3fZ")
+            .expect("解析失败");
+    assert_eq!(
+        formatter.format_ns(1_709_622_489_123_456_789),
+        "2024-03-05T07:08:09.123Z"
+    );
+    assert_eq!(
+        TimeFormatter::default().format(&time),
+        "240305_070809_123456789"
+    );
+}
+
+#[test]
+fn test_legacy_default_file_name_format() {
+    let time = sample_time();
+    // 旧版本的默认格式用作文件名时与旧版本一样输出 9 位纳秒
+    let formatter = TimeFormatter::for_file_name(
+        "yyMMdd_HHmmss_fffffff",
+    )
+    .expect("解析失败");
+    assert_eq!(
+        formatter.format(&time),
+        "240305_070809_123456789"
+    );
+    assert_eq!(formatter, TimeFormatter::default());
+    assert_eq!(
+        TimeFormatter::new("yyMMdd_HHmmss_fffffff")
+            .expect("解析失败")
+            .format(&time),
+        "240305_070809_1234567"
+    );
+
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let config = WriterConfig {
+        file_name_format: "yyMMdd_HHmmss_fffffff"
+            .to_string(),
+        ..Default::default()
+    };
+    config.validate().expect("旧版本默认格式应有效");
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        "legacy",
+        config,
+    )
+    .expect("创建写入器失败");
+    writer
+        .write_payload(
+            1_700_000_000_000_000_000,
+            &[0u8; 16],
+        )
+        .expect("写入负载失败");
+    writer.finalize().expect("完成写入失败");
+    let names: Vec<String> =
+        fs::read_dir(temp_dir.path().join("legacy"))
+            .expect("读取目录失败")
+            .filter_map(|entry| entry.ok())
+            .map(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .filter(|name| name.ends_with(".pcap"))
+            .collect();
+    // yyMMdd_HHmmss_nnnnnnnnn.pcap
+    assert_eq!(names.len(), 1);
+    assert_eq!(names[0].len(), 28, "{}", names[0]);
+}
+
+#[test]
+fn test_format_error_positions() {
+    let cases = [
+        ("", 0),
+        ("data_%Y%Q", 7),
+        ("%Y_%", 3),
+        ("yyy", 0),
+        ("HH_ffffffffff", 3),
+        ("dd 'open", 3),
+        ("MMM", 0),
+    ];
+    for (pattern, position) in cases {
+        let error =
+            TimeFormatter::new(pattern).expect_err(pattern);
+        assert_eq!(error.position, position, "{pattern}");
+        assert!(
+            error
+                .to_string()
+                .starts_with(&format!("位置 {position}:")),
+            "{error}"
+        );
+    }
+
+    // 文件名格式拒绝输出非法字符的说明符和文本
+    assert!(TimeFormatter::new("%H:%M").is_ok());
+    let error = TimeFormatter::for_file_name("run_%T")
+        .expect_err("%T 包含冒号");
+    assert_eq!(error.position, 4);
+    let error = TimeFormatter::for_file_name("yyMMdd/HH")
+        .expect_err("包含路径分隔符");
+    assert_eq!(error.position, 6);
+
+    let config = WriterConfig {
+        file_name_format: "capture_%Y%m%d_%:z".to_string(),
+        ..Default::default()
+    };
+    let error =
+        config.validate().expect_err("%:z 包含冒号");
+    assert!(
+        error.starts_with("file_name_format:"),
+        "{error}"
+    );
+    assert!(error.contains("位置 15"), "{error}");
+}
+
+#[test]
+fn test_writer_uses_file_name_format() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let config = WriterConfig {
+        file_name_format: "capture_%Y%m%d_%H%M%S_%9f"
+            .to_string(),
+        max_packets_per_file: 2,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        "named",
+        config,
+    )
+    .expect("创建写入器失败");
+    for i in 0..5u64 {
+        writer
+            .write_payload(
+                1_700_000_000_000_000_000 + i,
+                &[i as u8; 16],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let dataset_path = temp_dir.path().join("named");
+    let names: Vec<String> = fs::read_dir(&dataset_path)
+        .expect("读取目录失败")
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            entry.file_name().to_string_lossy().into_owned()
+        })
+        .filter(|name| name.ends_with(".pcap"))
+        .collect();
+    assert_eq!(names.len(), 3);
+    for name in &names {
+        // capture_YYYYmmdd_HHMMSS_nnnnnnnnn.pcap
+        assert!(name.starts_with("capture_"), "{name}");
+        assert_eq!(name.len(), 38, "{name}");
+    }
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), "named")
+            .expect("打开失败");
+    let mut count = 0;
+    while reader.read_packet().expect("读取失败").is_some()
+    {
+        count += 1;
+    }
+    assert_eq!(count, 5);
+}
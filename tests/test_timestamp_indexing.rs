@@ -3,14 +3,18 @@
 //! 测试基于时间戳的索引查找、时间范围读取、跨文件随机访问等功能
 
 use pcapfile_io::{
-    PcapReader, PcapWriter, ReaderConfig, WriterConfig,
+    constants, ByteSize, DataPacket,
+    DuplicateTimestampPolicy, PacketIndexEntry, PcapError,
+    PcapErrorCode, PcapFileIndex, PcapReader, PcapWriter,
+    PidxIndex, ReaderConfig, SeekMode, ValidatedPacket,
+    WriterConfig,
 };
 use std::time::Duration;
 
 mod common;
 use common::{
     clean_dataset_directory, create_test_packet,
-    setup_test_environment,
+    data_files, setup_test_environment,
 };
 
 #[test]
@@ -501,3 +505,879 @@ fn test_timestamp_index_edge_cases() {
 
     println!("✅ 时间戳索引边界情况测试通过");
 }
+
+#[test]
+fn test_iter_time_range_streaming() {
+    const TEST_NAME: &str =
+        "test_iter_time_range_streaming";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+
+    let test_dataset_path = dataset_path.join(TEST_NAME);
+    clean_dataset_directory(&test_dataset_path)
+        .expect("清理测试目录失败");
+
+    const PACKET_COUNT: u64 = 600;
+
+    let config = WriterConfig {
+        max_packets_per_file: 100,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        &dataset_path,
+        TEST_NAME,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+
+    // 使用确定的时间戳：每毫秒一个包
+    for i in 0..PACKET_COUNT {
+        let ts = 2_000_000_000 + i * 1_000_000;
+        let packet = DataPacket::from_timestamp(
+            (ts / 1_000_000_000) as u32,
+            (ts % 1_000_000_000) as u32,
+            vec![i as u8; 32],
+        )
+        .expect("创建测试数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+
+    // 跨越多个文件的范围：第150到第450个包
+    let start = 2_000_000_000 + 150 * 1_000_000;
+    let end = 2_000_000_000 + 450 * 1_000_000;
+
+    let mut iter = reader
+        .iter_time_range(start, end)
+        .expect("创建迭代器失败");
+    assert_eq!(iter.remaining(), 301);
+
+    let first = iter
+        .next()
+        .expect("迭代器不应为空")
+        .expect("读取数据包失败");
+    assert_eq!(first.get_timestamp_ns(), start);
+
+    let mut count = 1;
+    let mut last_ts = first.get_timestamp_ns();
+    for result in iter {
+        let packet = result.expect("读取数据包失败");
+        assert!(packet.get_timestamp_ns() > last_ts);
+        last_ts = packet.get_timestamp_ns();
+        count += 1;
+    }
+    assert_eq!(count, 301);
+    assert_eq!(last_ts, end);
+
+    // 与一次性读取的结果一致
+    let collected = reader
+        .read_packets_by_time_range(start, end)
+        .expect("按时间范围读取失败");
+    assert_eq!(collected.len(), 301);
+}
+
+#[test]
+fn test_packet_cache_for_random_access() {
+    const TEST_NAME: &str =
+        "test_packet_cache_for_random_access";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+
+    let test_dataset_path = dataset_path.join(TEST_NAME);
+    clean_dataset_directory(&test_dataset_path)
+        .expect("清理测试目录失败");
+
+    let mut writer =
+        PcapWriter::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapWriter失败");
+    for i in 0..20u64 {
+        let ts = 3_000_000_000 + i * 1_000_000;
+        let packet = DataPacket::from_timestamp(
+            (ts / 1_000_000_000) as u32,
+            (ts % 1_000_000_000) as u32,
+            vec![i as u8; 64],
+        )
+        .expect("创建测试数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let config = ReaderConfig {
+        packet_cache_size: 4,
+        ..Default::default()
+    };
+    let mut reader = PcapReader::new_with_config(
+        &dataset_path,
+        TEST_NAME,
+        config,
+    )
+    .expect("创建PcapReader失败");
+
+    let target = 3_000_000_000 + 5 * 1_000_000;
+    for _ in 0..3 {
+        let packet = reader
+            .read_packet_by_timestamp(target)
+            .expect("读取数据包失败")
+            .expect("应找到数据包");
+        assert_eq!(packet.get_timestamp_ns(), target);
+        assert_eq!(packet.packet.data, vec![5u8; 64]);
+    }
+
+    let stats = reader.get_packet_cache_stats();
+    assert_eq!(stats.miss_count, 1);
+    assert_eq!(stats.hit_count, 2);
+    assert_eq!(stats.cache_entries, 1);
+
+    // 超过条目上限时淘汰最久未使用的数据包
+    for i in 10..16u64 {
+        let ts = 3_000_000_000 + i * 1_000_000;
+        reader
+            .read_packet_by_timestamp(ts)
+            .expect("读取数据包失败")
+            .expect("应找到数据包");
+    }
+    assert_eq!(
+        reader.get_packet_cache_stats().cache_entries,
+        4
+    );
+
+    // 默认配置下缓存禁用
+    let mut uncached =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    uncached
+        .read_packet_by_timestamp(target)
+        .expect("读取数据包失败");
+    let stats = uncached.get_packet_cache_stats();
+    assert_eq!(stats.hit_count + stats.miss_count, 0);
+}
+
+#[test]
+fn test_files_covering_time_window() {
+    const TEST_NAME: &str =
+        "test_files_covering_time_window";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+    clean_dataset_directory(dataset_path.join(TEST_NAME))
+        .expect("清理测试目录失败");
+
+    // 4 个文件，每个文件 10 个数据包，数据包间隔 1 秒
+    let config = WriterConfig {
+        max_packets_per_file: 10,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        &dataset_path,
+        TEST_NAME,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+    for i in 0..40u32 {
+        let packet = DataPacket::from_timestamp(
+            1_000 + i,
+            0,
+            vec![i as u8; 16],
+        )
+        .expect("创建数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    let files = reader
+        .get_file_info_list()
+        .expect("获取文件列表失败");
+    assert_eq!(files.len(), 4);
+
+    let second = 1_000_000_000u64;
+    let covering = reader
+        .file_covering_timestamp(1_015 * second)
+        .expect("查询失败")
+        .expect("应存在覆盖该时间戳的文件");
+    assert_eq!(covering.file_name, files[1].file_name);
+    assert!(covering.file_path.exists());
+
+    // 文件之间的空隙和数据集范围之外都没有覆盖文件
+    assert!(reader
+        .file_covering_timestamp(1_009 * second + 500)
+        .expect("查询失败")
+        .is_none());
+    assert!(reader
+        .file_covering_timestamp(2_000 * second)
+        .expect("查询失败")
+        .is_none());
+
+    let in_range = reader
+        .files_in_range(1_009 * second, 1_020 * second)
+        .expect("查询失败");
+    let names: Vec<_> = in_range
+        .iter()
+        .map(|f| f.file_name.clone())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            files[0].file_name.clone(),
+            files[1].file_name.clone(),
+            files[2].file_name.clone(),
+        ]
+    );
+
+    assert_eq!(
+        reader
+            .files_in_range(0, u64::MAX)
+            .expect("查询失败")
+            .len(),
+        4
+    );
+    assert!(reader
+        .files_in_range(0, 999 * second)
+        .expect("查询失败")
+        .is_empty());
+}
+
+#[test]
+fn test_read_packets_by_time_range_parallel() {
+    const TEST_NAME: &str = "test_time_range_parallel";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+    clean_dataset_directory(dataset_path.join(TEST_NAME))
+        .expect("清理测试目录失败");
+
+    let config = WriterConfig {
+        max_packets_per_file: 25,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        &dataset_path,
+        TEST_NAME,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+    for i in 0..200u32 {
+        let packet = DataPacket::from_timestamp(
+            1_000 + i / 10,
+            (i % 10) * 100_000_000,
+            i.to_le_bytes().to_vec(),
+        )
+        .expect("创建数据包失败");
+        writer
+            .write_packet(&packet)
+            .expect("写入数据包失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    let start = 1_002_500_000_000u64;
+    let end = 1_017_000_000_000u64;
+    let sequential = reader
+        .read_packets_by_time_range(start, end)
+        .expect("顺序读取失败");
+    assert_eq!(sequential.len(), 146);
+
+    for threads in [0, 1, 3, 16] {
+        let parallel = reader
+            .read_packets_by_time_range_parallel(
+                start, end, threads,
+            )
+            .expect("并行读取失败");
+        assert_eq!(parallel.len(), sequential.len());
+        for (a, b) in parallel.iter().zip(&sequential) {
+            assert_eq!(
+                a.get_timestamp_ns(),
+                b.get_timestamp_ns()
+            );
+            assert_eq!(a.packet.data, b.packet.data);
+            assert!(a.is_valid());
+        }
+    }
+
+    assert!(reader
+        .read_packets_by_time_range_parallel(0, 1, 4)
+        .expect("并行读取失败")
+        .is_empty());
+}
+
+fn write_duplicate_timestamps(
+    dataset_path: &std::path::Path,
+    test_name: &str,
+    policy: DuplicateTimestampPolicy,
+) {
+    let config = WriterConfig {
+        max_packets_per_file: 3,
+        duplicate_timestamp_policy: policy,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        dataset_path,
+        test_name,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+    // 前 4 个数据包时间戳相同，并跨越文件边界
+    for (i, ts) in
+        [5_000u64, 5_000, 5_000, 5_000, 5_001, 6_000]
+            .into_iter()
+            .enumerate()
+    {
+        writer
+            .write_payload(ts, &[i as u8; 16])
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+#[test]
+fn test_duplicate_timestamps_allowed_in_index() {
+    const TEST_NAME: &str =
+        "test_duplicate_timestamps_allowed_in_index";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+    clean_dataset_directory(dataset_path.join(TEST_NAME))
+        .expect("清理测试目录失败");
+    write_duplicate_timestamps(
+        &dataset_path,
+        TEST_NAME,
+        DuplicateTimestampPolicy::Allow,
+    );
+
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    reader.initialize().expect("初始化失败");
+    {
+        let index =
+            reader.index().get_index().expect("索引未加载");
+        assert_eq!(index.timestamp_count(), 6);
+        assert_eq!(index.distinct_timestamp_count(), 3);
+        assert_eq!(index.duplicate_timestamp_count(), 3);
+        let globals: Vec<u64> = index
+            .find_packets_by_timestamp(5_000)
+            .iter()
+            .map(|pointer| pointer.entry.global_index)
+            .collect();
+        assert_eq!(globals, vec![0, 1, 2, 3]);
+        let query =
+            reader.index().query().expect("索引未加载");
+        assert_eq!(
+            query.global_indices_at(5_000),
+            vec![0, 1, 2, 3]
+        );
+    }
+
+    // 每个数据包都能通过时间戳接口读到
+    let packets = reader
+        .read_packets_by_time_range(5_000, 5_000)
+        .expect("按时间范围读取失败");
+    let payloads: Vec<u8> =
+        packets.iter().map(|p| p.packet.data[0]).collect();
+    assert_eq!(payloads, vec![0, 1, 2, 3]);
+    let packets = reader
+        .read_packets_by_time_range(0, u64::MAX)
+        .expect("按时间范围读取失败");
+    assert_eq!(packets.len(), 6);
+}
+
+#[test]
+fn test_sparse_index_under_memory_limit() {
+    const TEST_NAME: &str =
+        "test_sparse_index_under_memory_limit";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+    clean_dataset_directory(dataset_path.join(TEST_NAME))
+        .expect("清理测试目录失败");
+    write_duplicate_timestamps(
+        &dataset_path,
+        TEST_NAME,
+        DuplicateTimestampPolicy::Allow,
+    );
+
+    let mut full =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    full.initialize().expect("初始化失败");
+    let full_usage = full.index().memory_usage();
+    assert!(!full_usage.sparse);
+    assert!(full_usage.lookup_bytes > 0);
+
+    let config = ReaderConfig {
+        max_index_memory: ByteSize::kib(1),
+        ..Default::default()
+    };
+    let mut sparse = PcapReader::new_with_config(
+        &dataset_path,
+        TEST_NAME,
+        config,
+    )
+    .expect("创建PcapReader失败");
+    sparse.initialize().expect("初始化失败");
+    let usage = sparse.index().memory_usage();
+    assert!(usage.sparse);
+    assert_eq!(usage.lookup_bytes, 0);
+    assert!(usage.total_bytes() < full_usage.total_bytes());
+
+    // 稀疏模式下的查询结果与完整索引一致
+    {
+        let index =
+            sparse.index().get_index().expect("索引未加载");
+        assert!(index.is_sparse());
+        assert_eq!(index.timestamp_count(), 6);
+        assert_eq!(index.duplicate_timestamp_count(), 3);
+        assert!(index.contains_timestamp(5_001));
+        assert!(!index.contains_timestamp(5_002));
+        let query =
+            sparse.index().query().expect("索引未加载");
+        assert_eq!(
+            query.global_indices_at(5_000),
+            vec![0, 1, 2, 3]
+        );
+        let entries: Vec<u64> = index
+            .timestamp_entries()
+            .map(|(_, pointer)| pointer.entry.global_index)
+            .collect();
+        assert_eq!(entries, vec![0, 1, 2, 3, 4, 5]);
+    }
+    for mode in [
+        SeekMode::Exact,
+        SeekMode::Floor,
+        SeekMode::Ceiling,
+        SeekMode::Nearest,
+    ] {
+        for ts in [4_999, 5_000, 5_500, 6_000, 7_000] {
+            let expected = full
+                .read_packet_by_timestamp_with_mode(
+                    ts, mode,
+                )
+                .expect("读取失败")
+                .map(|p| p.packet.data[0]);
+            let actual = sparse
+                .read_packet_by_timestamp_with_mode(
+                    ts, mode,
+                )
+                .expect("读取失败")
+                .map(|p| p.packet.data[0]);
+            assert_eq!(actual, expected, "{mode:?} {ts}");
+        }
+    }
+    let payloads: Vec<u8> = sparse
+        .read_packets_by_time_range(5_000, 5_001)
+        .expect("按时间范围读取失败")
+        .iter()
+        .map(|p| p.packet.data[0])
+        .collect();
+    assert_eq!(payloads, vec![0, 1, 2, 3, 4]);
+
+    // 取消上限后重新构建完整的查找表
+    sparse.index_mut().set_max_memory(ByteSize::ZERO);
+    assert!(!sparse.index().memory_usage().sparse);
+}
+
+#[test]
+fn test_duplicate_timestamps_bumped_by_one() {
+    const TEST_NAME: &str =
+        "test_duplicate_timestamps_bumped_by_one";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+    clean_dataset_directory(dataset_path.join(TEST_NAME))
+        .expect("清理测试目录失败");
+    write_duplicate_timestamps(
+        &dataset_path,
+        TEST_NAME,
+        DuplicateTimestampPolicy::BumpByOne,
+    );
+
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    let mut timestamps = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取失败")
+    {
+        timestamps.push(packet.packet.get_timestamp_ns());
+    }
+    // 5_001 已被占用时继续顺延
+    assert_eq!(
+        timestamps,
+        vec![5_000, 5_001, 5_002, 5_003, 5_004, 6_000]
+    );
+    let index =
+        reader.index().get_index().expect("索引未加载");
+    assert_eq!(index.duplicate_timestamp_count(), 0);
+    assert_eq!(index.timestamp_count(), 6);
+}
+
+#[test]
+fn test_duplicate_timestamps_rejected() {
+    const TEST_NAME: &str =
+        "test_duplicate_timestamps_rejected";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+    clean_dataset_directory(dataset_path.join(TEST_NAME))
+        .expect("清理测试目录失败");
+    let config = WriterConfig {
+        duplicate_timestamp_policy:
+            DuplicateTimestampPolicy::Error,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        &dataset_path,
+        TEST_NAME,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+    writer
+        .write_payload(7_000, &[1u8; 8])
+        .expect("写入负载失败");
+    let error = writer
+        .write_payload(7_000, &[2u8; 8])
+        .expect_err("重复时间戳应被拒绝");
+    assert!(matches!(
+        error,
+        PcapError::DuplicateTimestamp {
+            timestamp_ns: 7_000
+        }
+    ));
+    assert_eq!(
+        error.error_code(),
+        PcapErrorCode::DuplicateTimestamp
+    );
+
+    // 被拒绝后写入器仍可继续写入
+    writer
+        .write_payload(7_001, &[3u8; 8])
+        .expect("写入负载失败");
+    writer.finalize().expect("完成写入失败");
+
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    let mut count = 0;
+    while reader.read_packet().expect("读取失败").is_some()
+    {
+        count += 1;
+    }
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn test_duplicate_timestamp_window_is_bounded() {
+    const TEST_NAME: &str =
+        "test_duplicate_timestamp_window_is_bounded";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+    clean_dataset_directory(dataset_path.join(TEST_NAME))
+        .expect("清理测试目录失败");
+    let config = WriterConfig {
+        max_packets_per_file: 10_000,
+        duplicate_timestamp_policy:
+            DuplicateTimestampPolicy::Error,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        &dataset_path,
+        TEST_NAME,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+    let window =
+        constants::DUPLICATE_TIMESTAMP_WINDOW as u64;
+    for i in 0..=window {
+        writer
+            .write_payload(10_000 + i, &[0u8; 8])
+            .expect("写入负载失败");
+    }
+
+    // 最近写入的时间戳仍会被拒绝，超出检查范围的最早时间戳不再记录
+    assert!(matches!(
+        writer.write_payload(10_000 + window, &[1u8; 8]),
+        Err(PcapError::DuplicateTimestamp { .. })
+    ));
+    writer
+        .write_payload(10_000, &[2u8; 8])
+        .expect("超出检查范围的时间戳应可写入");
+    writer.finalize().expect("完成写入失败");
+}
+
+#[test]
+fn test_duplicate_timestamp_check_is_best_effort() {
+    const TEST_NAME: &str =
+        "test_duplicate_timestamp_check_is_best_effort";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+    clean_dataset_directory(dataset_path.join(TEST_NAME))
+        .expect("清理测试目录失败");
+    let config = WriterConfig {
+        max_packets_per_file: 10_000,
+        duplicate_timestamp_policy:
+            DuplicateTimestampPolicy::Error,
+        ..Default::default()
+    };
+    let window =
+        constants::DUPLICATE_TIMESTAMP_WINDOW as u64;
+
+    let mut writer = PcapWriter::new_with_config(
+        &dataset_path,
+        TEST_NAME,
+        config.clone(),
+    )
+    .expect("创建PcapWriter失败");
+    for i in 0..window + 100 {
+        writer
+            .write_payload(20_000 + i, &[0u8; 8])
+            .expect("写入负载失败");
+    }
+    // 原数据包之后超过窗口大小个数据包才到达的重复时间戳不会被发现
+    writer
+        .write_payload(20_000, &[1u8; 8])
+        .expect("超出窗口的重复时间戳按原样写入");
+    writer.finalize().expect("完成写入失败");
+
+    // 新的写入器不了解数据集中已有的时间戳
+    let mut writer = PcapWriter::new_with_config(
+        &dataset_path,
+        TEST_NAME,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+    writer
+        .write_payload(20_001, &[2u8; 8])
+        .expect("与已有数据重复的时间戳按原样写入");
+    writer.finalize().expect("完成写入失败");
+
+    // 完成写入后可以通过索引发现重复
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    reader.initialize().expect("初始化失败");
+    let index =
+        reader.index().get_index().expect("索引未加载");
+    assert_eq!(index.duplicate_timestamp_count(), 2);
+}
+
+#[test]
+fn test_equal_timestamps_returned_in_write_order() {
+    const TEST_NAME: &str =
+        "test_equal_timestamps_returned_in_write_order";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+    clean_dataset_directory(dataset_path.join(TEST_NAME))
+        .expect("清理测试目录失败");
+    let config = WriterConfig {
+        max_packets_per_file: 7,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        &dataset_path,
+        TEST_NAME,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+    // 每 5 个数据包共用一个时间戳，相同时间戳跨越文件边界
+    for i in 0..30u64 {
+        writer
+            .write_payload(
+                1_000 + i / 5 * 1_000,
+                &[i as u8; 12],
+            )
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+
+    let expected: Vec<u8> = (0..30).collect();
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    let payloads = |packets: Vec<ValidatedPacket>| {
+        packets
+            .iter()
+            .map(|p| p.packet.data[0])
+            .collect::<Vec<u8>>()
+    };
+    let packets = reader
+        .read_packets_by_time_range(0, u64::MAX)
+        .expect("按时间范围读取失败");
+    assert_eq!(payloads(packets), expected);
+    let packets = reader
+        .read_packets_by_time_range_parallel(0, u64::MAX, 3)
+        .expect("并行读取失败");
+    assert_eq!(payloads(packets), expected);
+    let packets = reader
+        .read_packets_by_time_range(3_000, 4_000)
+        .expect("按时间范围读取失败");
+    assert_eq!(
+        payloads(packets),
+        (10..20).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_equal_timestamps_ordered_by_file_and_offset() {
+    let entry = |timestamp_ns: u64, byte_offset: u64| {
+        PacketIndexEntry {
+            timestamp_ns,
+            byte_offset,
+            packet_size: 8,
+            packet_index: 0,
+            global_index: 0,
+            payload_hash: None,
+        }
+    };
+    let file =
+        |name: &str, packets: Vec<PacketIndexEntry>| {
+            PcapFileIndex {
+                file_name: name.to_string(),
+                file_hash: String::new(),
+                file_size: 0,
+                packet_count: packets.len() as u64,
+                start_timestamp: 0,
+                end_timestamp: 0,
+                shard: None,
+                data_packets: packets,
+            }
+        };
+    // 第一个文件的条目未按偏移排序，例如手工修改过的索引
+    let mut index = PidxIndex::new(None);
+    index.data_files.files = vec![
+        file(
+            "a.pcap",
+            vec![
+                entry(500, 72),
+                entry(500, 16),
+                entry(400, 44),
+            ],
+        ),
+        file(
+            "b.pcap",
+            vec![entry(500, 16), entry(400, 16)],
+        ),
+    ];
+    index.build_timestamp_index();
+
+    let order: Vec<(usize, u64)> = index
+        .get_packets_in_range(0, u64::MAX)
+        .iter()
+        .map(|pointer| pointer.write_order())
+        .collect();
+    assert_eq!(
+        order,
+        vec![(0, 44), (1, 16), (0, 16), (0, 72), (1, 16)]
+    );
+    let first = index
+        .find_packet_by_timestamp(500)
+        .expect("缺少时间戳");
+    assert_eq!(first.write_order(), (0, 16));
+}
+
+/// 写入时间戳为 0..count 纳秒、每个文件 `per_file` 个数据包的数据集
+fn write_sequential_dataset(
+    dataset_path: &std::path::Path,
+    test_name: &str,
+    count: u64,
+    per_file: usize,
+) {
+    clean_dataset_directory(dataset_path.join(test_name))
+        .expect("清理测试目录失败");
+    let config = WriterConfig {
+        max_packets_per_file: per_file,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        dataset_path,
+        test_name,
+        config,
+    )
+    .expect("创建PcapWriter失败");
+    for i in 0..count {
+        writer
+            .write_payload(i, &i.to_le_bytes())
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+#[test]
+fn test_time_range_fails_when_data_file_missing() {
+    const TEST_NAME: &str = "test_time_range_missing_file";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+    write_sequential_dataset(
+        &dataset_path,
+        TEST_NAME,
+        30,
+        10,
+    );
+
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    reader.initialize().expect("初始化失败");
+    std::fs::remove_file(
+        &data_files(&dataset_path.join(TEST_NAME))[1],
+    )
+    .expect("删除数据文件失败");
+
+    // 无法打开的数据文件不能被当作没有数据
+    assert!(reader
+        .read_packets_by_time_range(0, u64::MAX)
+        .is_err());
+    assert_eq!(
+        reader
+            .read_packets_by_time_range(0, 9)
+            .expect("读取第一个文件失败")
+            .len(),
+        10
+    );
+}
+
+#[test]
+fn test_parallel_time_range_fails_when_data_file_missing() {
+    const TEST_NAME: &str = "test_parallel_missing_file";
+    let dataset_path =
+        setup_test_environment().expect("设置测试环境失败");
+    write_sequential_dataset(
+        &dataset_path,
+        TEST_NAME,
+        40,
+        10,
+    );
+
+    let mut reader =
+        PcapReader::new(&dataset_path, TEST_NAME)
+            .expect("创建PcapReader失败");
+    reader.initialize().expect("初始化失败");
+    std::fs::remove_file(
+        &data_files(&dataset_path.join(TEST_NAME))[2],
+    )
+    .expect("删除数据文件失败");
+
+    for threads in [1, 4] {
+        assert!(reader
+            .read_packets_by_time_range_parallel(
+                0,
+                u64::MAX,
+                threads
+            )
+            .is_err());
+    }
+    assert_eq!(
+        reader
+            .read_packets_by_time_range_parallel(0, 19, 2)
+            .expect("读取前两个文件失败")
+            .len(),
+        20
+    );
+}
@@ -0,0 +1,130 @@
+//! 触发式记录测试
+//!
+//! 测试预缓冲区按时间窗口和字节上限滚动，以及触发前后窗口的写入
+
+use pcapfile_io::{
+    DataPacket, PcapReader, TriggerConfig, TriggeredWriter,
+};
+use std::path::Path;
+use tempfile::TempDir;
+
+const BASE_SECONDS: u32 = 1_700_000_000;
+
+fn packet(nanosecond: u32) -> DataPacket {
+    DataPacket::from_timestamp(
+        BASE_SECONDS,
+        nanosecond,
+        vec![0xAB; 4],
+    )
+    .expect("创建数据包失败")
+}
+
+fn ts(nanosecond: u32) -> u64 {
+    BASE_SECONDS as u64 * 1_000_000_000 + nanosecond as u64
+}
+
+/// 读取数据集中全部数据包的时间戳
+fn read_timestamps(
+    base_path: &Path,
+    name: &str,
+) -> Vec<u64> {
+    let mut reader = PcapReader::new(base_path, name)
+        .expect("创建读取器失败");
+    let mut timestamps = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        timestamps.push(packet.get_timestamp_ns());
+    }
+    timestamps
+}
+
+#[test]
+fn test_triggered_writer_records_windows() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let config = TriggerConfig {
+        pre_trigger_ns: 100,
+        post_trigger_ns: 50,
+        max_buffered_bytes: 0,
+    };
+    let mut writer = TriggeredWriter::new(
+        base_path,
+        "triggered",
+        config,
+    )
+    .expect("创建触发式写入器失败");
+
+    for nanosecond in [0, 50, 100, 150, 200] {
+        writer
+            .write_packet(&packet(nanosecond))
+            .expect("写入数据包失败");
+    }
+    // 只保留最近 100 纳秒内的数据包
+    assert_eq!(writer.buffered_packets(), 3);
+    assert_eq!(writer.buffered_bytes(), 12);
+    assert!(!writer.is_recording());
+
+    writer.trigger().expect("触发失败");
+    assert!(writer.is_recording());
+    assert_eq!(writer.buffered_packets(), 0);
+    assert_eq!(writer.trigger_count(), 1);
+
+    // 240 在触发后窗口内，260 超出窗口后恢复预缓冲
+    for nanosecond in [240, 260, 300] {
+        writer
+            .write_packet(&packet(nanosecond))
+            .expect("写入数据包失败");
+    }
+    assert!(!writer.is_recording());
+    assert_eq!(writer.buffered_packets(), 2);
+    writer.finalize().expect("完成写入失败");
+
+    assert_eq!(
+        read_timestamps(base_path, "triggered"),
+        vec![ts(100), ts(150), ts(200), ts(240)]
+    );
+}
+
+#[test]
+fn test_triggered_writer_limits() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+
+    // 两个窗口都为 0 的配置无效
+    let invalid = TriggerConfig {
+        pre_trigger_ns: 0,
+        post_trigger_ns: 0,
+        max_buffered_bytes: 0,
+    };
+    assert!(invalid.validate().is_err());
+    assert!(TriggeredWriter::new(
+        base_path, "invalid", invalid
+    )
+    .is_err());
+
+    // 字节上限只允许缓冲两个 4 字节的数据包
+    let config = TriggerConfig {
+        max_buffered_bytes: 8,
+        ..TriggerConfig::default()
+    };
+    let mut writer =
+        TriggeredWriter::new(base_path, "limited", config)
+            .expect("创建触发式写入器失败");
+    for nanosecond in [10, 20, 30, 40] {
+        writer
+            .write_packet(&packet(nanosecond))
+            .expect("写入数据包失败");
+    }
+    assert_eq!(writer.buffered_packets(), 2);
+    assert_eq!(writer.buffered_bytes(), 8);
+
+    writer.trigger().expect("触发失败");
+    writer.finalize().expect("完成写入失败");
+    assert_eq!(
+        read_timestamps(base_path, "limited"),
+        vec![ts(30), ts(40)]
+    );
+}
@@ -0,0 +1,122 @@
+//! 非阻塞读取测试
+//!
+//! 测试写入器仍在写入时 `try_read_packet` 在没有新数据包时返回 `WouldBlock`，
+//! 写入器继续写入后自动读取到新数据包，写入器完成后返回 `EndOfDataset`
+
+use pcapfile_io::{
+    PcapReader, PcapWriter, TryRead, WriterConfig,
+};
+use tempfile::TempDir;
+
+mod common;
+use common::create_test_packet;
+
+const TEST_NAME: &str = "poll_dataset";
+
+fn write_packets(
+    writer: &mut PcapWriter,
+    start: u32,
+    count: u32,
+) {
+    for i in start..start + count {
+        writer
+            .write_packet(
+                &create_test_packet(i, 32)
+                    .expect("创建测试数据包失败"),
+            )
+            .expect("写入数据包失败");
+    }
+}
+
+/// 轮询直到不再读取到数据包，返回读取到的数据包数量和最后的状态
+fn drain(reader: &mut PcapReader) -> (usize, TryRead) {
+    let mut count = 0;
+    loop {
+        match reader.try_read_packet().expect("读取失败")
+        {
+            TryRead::Packet(_) => count += 1,
+            status => return (count, status),
+        }
+    }
+}
+
+#[test]
+fn test_try_read_packet_follows_active_writer() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let config = WriterConfig {
+        max_packets_per_file: 10,
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        TEST_NAME,
+        config,
+    )
+    .expect("创建写入器失败");
+    write_packets(&mut writer, 0, 15);
+    writer.flush().expect("刷新失败");
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("创建读取器失败");
+    let (count, status) = drain(&mut reader);
+    assert_eq!(count, 15);
+    assert!(status.is_would_block());
+    assert!(reader.is_live());
+
+    // 没有新数据时重复轮询保持 WouldBlock
+    assert!(reader
+        .try_read_packet()
+        .expect("读取失败")
+        .is_would_block());
+
+    // 写入器继续写入（跨越文件边界），轮询自动读取到新数据包
+    write_packets(&mut writer, 15, 10);
+    writer.flush().expect("刷新失败");
+    let (count, status) = drain(&mut reader);
+    assert_eq!(count, 10);
+    assert!(status.is_would_block());
+
+    // 写入器完成后读完剩余数据包并返回 EndOfDataset
+    write_packets(&mut writer, 25, 3);
+    writer.finalize().expect("完成写入失败");
+    drop(writer);
+    let (count, status) = drain(&mut reader);
+    assert_eq!(count, 3);
+    assert!(status.is_end_of_dataset());
+    assert!(!reader.is_live());
+    assert_eq!(reader.total_packets(), Some(28));
+}
+
+#[test]
+fn test_try_read_packet_on_finished_dataset() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let mut writer =
+        PcapWriter::new(temp_dir.path(), TEST_NAME)
+            .expect("创建写入器失败");
+    write_packets(&mut writer, 0, 5);
+    writer.finalize().expect("完成写入失败");
+    drop(writer);
+
+    let mut reader =
+        PcapReader::new(temp_dir.path(), TEST_NAME)
+            .expect("创建读取器失败");
+    let first = reader
+        .try_read_packet()
+        .expect("读取失败")
+        .into_packet()
+        .expect("应读取到数据包");
+    assert_eq!(
+        first.packet.data,
+        create_test_packet(0, 32).unwrap().data
+    );
+    let (count, status) = drain(&mut reader);
+    assert_eq!(count, 4);
+    assert!(status.is_end_of_dataset());
+    assert!(reader
+        .try_read_packet()
+        .expect("读取失败")
+        .is_end_of_dataset());
+}
@@ -0,0 +1,147 @@
+//! 时间窗口聚合测试
+//!
+//! 写入中间有空档的数据集，验证各窗口的数量、字节数和大小范围，
+//! 以及空窗口、指标选择和参数校验。
+
+use pcapfile_io::{
+    Aggregation, PcapError, PcapReader, PcapWriter,
+    WriterConfig,
+};
+use std::time::Duration;
+use tempfile::TempDir;
+
+const BASE_NS: u64 = 1_700_000_000_000_000_000;
+const MS: u64 = 1_000_000;
+
+/// 第 0 秒内 10 个数据包，空档 2 秒后第 3 秒内 5 个数据包
+fn packets() -> Vec<(u64, usize)> {
+    let mut packets: Vec<(u64, usize)> = (0..10)
+        .map(|i| (BASE_NS + i * 100 * MS, i as usize + 1))
+        .collect();
+    packets.extend((0..5).map(|i| {
+        (
+            BASE_NS + 3_000 * MS + i * 100 * MS,
+            100 + i as usize,
+        )
+    }));
+    packets
+}
+
+fn write_dataset(base_path: &std::path::Path) {
+    let config = WriterConfig {
+        max_packets_per_file: 4,
+        ..WriterConfig::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        base_path, "agg", config,
+    )
+    .expect("创建写入器失败");
+    for (timestamp_ns, size) in packets() {
+        writer
+            .write_payload(timestamp_ns, &vec![0u8; size])
+            .expect("写入负载失败");
+    }
+    writer.finalize().expect("完成写入失败");
+}
+
+#[test]
+fn test_aggregate_windows() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path());
+    let mut reader =
+        PcapReader::new(temp_dir.path(), "agg")
+            .expect("打开失败");
+
+    let windows = reader
+        .aggregate_windows(
+            Duration::from_secs(1),
+            &Aggregation::ALL,
+        )
+        .expect("聚合失败");
+    assert_eq!(windows.len(), 4);
+    for (i, window) in windows.iter().enumerate() {
+        assert_eq!(
+            window.start_ns,
+            BASE_NS + i as u64 * 1_000 * MS
+        );
+        assert_eq!(
+            window.end_ns,
+            window.start_ns + 1_000 * MS
+        );
+    }
+    assert_eq!(windows[0].packet_count, Some(10));
+    assert_eq!(windows[0].total_bytes, Some(55));
+    assert_eq!(windows[0].min_size, Some(1));
+    assert_eq!(windows[0].max_size, Some(10));
+
+    // 空档中的窗口保留，大小范围为空
+    for window in &windows[1..3] {
+        assert_eq!(window.packet_count, Some(0));
+        assert_eq!(window.total_bytes, Some(0));
+        assert_eq!(window.min_size, None);
+        assert_eq!(window.max_size, None);
+    }
+
+    assert_eq!(windows[3].packet_count, Some(5));
+    assert_eq!(windows[3].total_bytes, Some(510));
+    assert_eq!(windows[3].min_size, Some(100));
+    assert_eq!(windows[3].max_size, Some(104));
+
+    // 窗口总数与数据集一致，读取位置不受影响
+    let total: u64 =
+        windows.iter().filter_map(|w| w.packet_count).sum();
+    assert_eq!(total, packets().len() as u64);
+    assert_eq!(reader.current_packet_index(), 0);
+}
+
+#[test]
+fn test_aggregate_windows_selection() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    write_dataset(temp_dir.path());
+    let mut reader =
+        PcapReader::new(temp_dir.path(), "agg")
+            .expect("打开失败");
+
+    // 只请求数量，其余指标为空
+    let windows = reader
+        .aggregate_windows(
+            Duration::from_millis(500),
+            &[Aggregation::Count],
+        )
+        .expect("聚合失败");
+    assert_eq!(windows.len(), 7);
+    assert_eq!(
+        windows
+            .iter()
+            .map(|w| w.packet_count.unwrap_or_default())
+            .collect::<Vec<_>>(),
+        vec![5, 5, 0, 0, 0, 0, 5]
+    );
+    assert!(windows.iter().all(|w| w
+        .total_bytes
+        .is_none()
+        && w.min_size.is_none()
+        && w.max_size.is_none()));
+
+    // 窗口覆盖整个数据集时只有一个窗口
+    let windows = reader
+        .aggregate_windows(
+            Duration::from_secs(3600),
+            &[Aggregation::Bytes, Aggregation::MaxSize],
+        )
+        .expect("聚合失败");
+    assert_eq!(windows.len(), 1);
+    assert_eq!(windows[0].packet_count, None);
+    assert_eq!(windows[0].total_bytes, Some(565));
+    assert_eq!(windows[0].max_size, Some(104));
+
+    assert!(matches!(
+        reader.aggregate_windows(
+            Duration::ZERO,
+            &Aggregation::ALL
+        ),
+        Err(PcapError::InvalidArgument(_))
+    ));
+}
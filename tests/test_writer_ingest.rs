@@ -0,0 +1,208 @@
+//! 通道写入测试
+//!
+//! 多个生产者线程通过有界通道向写入线程提交数据包，验证写入完整性和完成后的数据集信息，
+//! 以及空闲期间写入的心跳包和心跳包与空数据包的区分。
+
+use pcapfile_io::{
+    DataPacket, PcapReader, PcapWriter, WriterConfig,
+};
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+const BASE_SECONDS: u32 = 1_700_000_000;
+const PRODUCERS: u32 = 4;
+const PACKETS_PER_PRODUCER: u32 = 250;
+
+#[test]
+fn test_spawn_ingest_multiple_producers() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let writer = PcapWriter::new(base_path, "ingest")
+        .expect("创建写入器失败");
+    // 容量远小于数据包总数，生产者会被阻塞
+    let (sender, handle) =
+        writer.spawn_ingest(8).expect("启动写入线程失败");
+
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|producer| {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                for index in 0..PACKETS_PER_PRODUCER {
+                    let packet =
+                        DataPacket::from_timestamp(
+                            BASE_SECONDS + index,
+                            producer,
+                            vec![producer as u8; 32],
+                        )
+                        .expect("创建数据包失败");
+                    sender
+                        .send(packet)
+                        .expect("发送数据包失败");
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().expect("生产者线程异常");
+    }
+    drop(sender);
+
+    let info = handle
+        .join()
+        .expect("写入线程异常")
+        .expect("写入失败");
+    let total = (PRODUCERS * PACKETS_PER_PRODUCER) as u64;
+    assert_eq!(info.total_packets, total);
+
+    let mut reader = PcapReader::new(base_path, "ingest")
+        .expect("创建读取器失败");
+    let mut per_producer = vec![0u32; PRODUCERS as usize];
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        assert!(packet.is_valid);
+        let producer = packet.packet.data[0] as usize;
+        // 同一生产者的数据包保持提交顺序
+        assert_eq!(
+            packet.packet.header.timestamp_seconds,
+            BASE_SECONDS + per_producer[producer]
+        );
+        per_producer[producer] += 1;
+    }
+    assert!(per_producer
+        .iter()
+        .all(|&count| count == PACKETS_PER_PRODUCER));
+}
+
+#[test]
+fn test_spawn_ingest_writes_heartbeats_when_idle() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let config = WriterConfig {
+        heartbeat_interval: Duration::from_millis(20),
+        ..Default::default()
+    };
+    let writer = PcapWriter::new_with_config(
+        base_path,
+        "heartbeat",
+        config,
+    )
+    .expect("创建写入器失败");
+    let (sender, handle) =
+        writer.spawn_ingest(8).expect("启动写入线程失败");
+
+    let send = |seconds: u32| {
+        let packet = DataPacket::from_timestamp(
+            seconds,
+            0,
+            vec![7u8; 16],
+        )
+        .expect("创建数据包失败");
+        sender.send(packet).expect("发送数据包失败");
+    };
+    send(BASE_SECONDS);
+    // 空闲期间写入线程按间隔写入心跳包
+    thread::sleep(Duration::from_millis(150));
+    send(BASE_SECONDS + 1);
+    drop(sender);
+    handle
+        .join()
+        .expect("写入线程异常退出")
+        .expect("写入失败");
+
+    let mut reader =
+        PcapReader::new(base_path, "heartbeat")
+            .expect("打开失败");
+    let (mut heartbeats, mut data) = (0, 0);
+    while let Some(packet) =
+        reader.read_packet().expect("读取失败")
+    {
+        assert!(packet.is_valid());
+        if packet.is_heartbeat() {
+            assert_eq!(packet.packet_length(), 0);
+            assert!(packet.packet.header.is_heartbeat());
+            heartbeats += 1;
+        } else {
+            assert_eq!(packet.packet.data, vec![7u8; 16]);
+            data += 1;
+        }
+    }
+    assert_eq!(data, 2);
+    assert!(heartbeats >= 2, "心跳包数量: {heartbeats}");
+}
+
+#[test]
+fn test_write_heartbeat_if_idle() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let mut writer =
+        PcapWriter::new(temp_dir.path(), "manual")
+            .expect("创建写入器失败");
+    // 未配置心跳间隔时不写入
+    thread::sleep(Duration::from_millis(5));
+    assert!(!writer
+        .write_heartbeat_if_idle()
+        .expect("写入心跳包失败"));
+    writer.finalize().expect("完成写入失败");
+
+    let config = WriterConfig {
+        heartbeat_interval: Duration::from_millis(5),
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        "configured",
+        config,
+    )
+    .expect("创建写入器失败");
+    writer
+        .write_payload(1_000, &[1, 2, 3])
+        .expect("写入负载失败");
+    thread::sleep(Duration::from_millis(20));
+    assert!(writer
+        .write_heartbeat_if_idle()
+        .expect("写入心跳包失败"));
+    // 刚写入过心跳包，未到下一个间隔
+    assert!(!writer
+        .write_heartbeat_if_idle()
+        .expect("写入心跳包失败"));
+    writer.finalize().expect("完成写入失败");
+    assert_eq!(writer.get_dataset_info().total_packets, 2);
+}
+
+#[test]
+fn test_empty_packets_are_not_heartbeats() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let base_path = temp_dir.path();
+    let mut writer = PcapWriter::new(base_path, "empty")
+        .expect("创建写入器失败");
+    writer.write_payload(1_000, &[]).expect("写入负载失败");
+    writer.write_heartbeat(2_000).expect("写入心跳包失败");
+    writer
+        .write_packet(
+            &DataPacket::from_timestamp(1, 0, Vec::new())
+                .unwrap(),
+        )
+        .expect("写入数据包失败");
+    let metrics = writer.metrics();
+    assert_eq!(metrics.heartbeat_count, 1);
+    assert_eq!(metrics.packet_sizes.count(), 2);
+    writer.finalize().expect("完成写入失败");
+
+    // 用户写入的空数据包不是心跳包，两者都通过校验
+    let mut reader = PcapReader::new(base_path, "empty")
+        .expect("创建读取器失败");
+    let mut heartbeats = Vec::new();
+    while let Some(packet) =
+        reader.read_packet().expect("读取数据包失败")
+    {
+        assert!(packet.is_valid());
+        assert!(packet.packet.data.is_empty());
+        heartbeats.push(packet.is_heartbeat());
+    }
+    assert_eq!(heartbeats, vec![false, true, false]);
+}
@@ -0,0 +1,131 @@
+//! 写入统计测试
+//!
+//! 测试写入器统计负载大小和时间间隔的分布、心跳包和乱序数据包的计数，
+//! 以及直方图的分桶和分位数估算
+
+use pcapfile_io::{
+    Histogram, HistogramBucket, PcapWriter, WriterConfig,
+};
+use tempfile::TempDir;
+
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+#[test]
+fn test_histogram_buckets_and_quantiles() {
+    let mut histogram = Histogram::new();
+    assert_eq!(histogram.value_at_quantile(0.5), None);
+    assert_eq!(histogram.mean(), None);
+
+    for value in [0, 1, 3, 100, 100, 100, 1000, u64::MAX] {
+        histogram.record(value);
+    }
+    assert_eq!(histogram.count(), 8);
+    assert_eq!(histogram.min(), Some(0));
+    assert_eq!(histogram.max(), Some(u64::MAX));
+    assert_eq!(histogram.sum(), u64::MAX);
+
+    let buckets: Vec<HistogramBucket> =
+        histogram.buckets().collect();
+    let bounds: Vec<(u64, u64, u64)> = buckets
+        .iter()
+        .map(|b| (b.lower, b.upper, b.count))
+        .collect();
+    assert_eq!(
+        bounds,
+        vec![
+            (0, 0, 1),
+            (1, 1, 1),
+            (2, 3, 1),
+            (64, 127, 3),
+            (512, 1023, 1),
+            (1 << 63, u64::MAX, 1),
+        ]
+    );
+
+    // 分位数取所在桶的上界，并限制在最小值和最大值之间
+    assert_eq!(histogram.value_at_quantile(0.0), Some(0));
+    assert_eq!(histogram.value_at_quantile(0.5), Some(127));
+    assert_eq!(
+        histogram.value_at_quantile(0.75),
+        Some(127)
+    );
+    assert_eq!(
+        histogram.value_at_quantile(1.0),
+        Some(u64::MAX)
+    );
+
+    histogram.clear();
+    assert_eq!(histogram.count(), 0);
+    assert!(histogram.buckets().next().is_none());
+}
+
+#[test]
+fn test_writer_metrics() {
+    let temp_dir =
+        TempDir::new().expect("创建临时目录失败");
+    let mut writer = PcapWriter::new_with_config(
+        temp_dir.path(),
+        "metrics",
+        WriterConfig::default(),
+    )
+    .expect("创建写入器失败");
+    assert_eq!(writer.metrics().packet_sizes.count(), 0);
+
+    // 每 1ms 一个 200 字节的数据包，中间插入心跳包
+    for i in 0..10u64 {
+        writer
+            .write_payload(
+                BASE_TIMESTAMP + i * 1_000_000,
+                &[0u8; 200],
+            )
+            .expect("写入负载失败");
+    }
+    writer
+        .write_heartbeat(BASE_TIMESTAMP + 9_500_000)
+        .expect("写入心跳包失败");
+
+    let metrics = writer.metrics();
+    assert_eq!(metrics.packet_sizes.count(), 10);
+    assert_eq!(metrics.packet_sizes.min(), Some(200));
+    assert_eq!(metrics.packet_sizes.mean(), Some(200.0));
+    assert_eq!(metrics.intervals_ns.count(), 9);
+    assert_eq!(metrics.intervals_ns.min(), Some(1_000_000));
+    assert_eq!(metrics.intervals_ns.max(), Some(1_000_000));
+    assert_eq!(metrics.heartbeat_count, 1);
+    assert_eq!(metrics.out_of_order_count, 0);
+
+    // 重置后输入特征改变：数据包变大，间隔变长，出现乱序
+    let previous = writer.reset_metrics();
+    assert_eq!(previous.packet_sizes.count(), 10);
+    assert_eq!(writer.metrics().packet_sizes.count(), 0);
+
+    writer
+        .write_payload(
+            BASE_TIMESTAMP + 19_000_000,
+            &[0u8; 1400],
+        )
+        .expect("写入负载失败");
+    writer
+        .write_payload(
+            BASE_TIMESTAMP + 18_000_000,
+            &[0u8; 1400],
+        )
+        .expect("写入负载失败");
+    let metrics = writer.metrics();
+    assert_eq!(metrics.packet_sizes.min(), Some(1400));
+    // 间隔从重置前的最后一个数据包继续计算
+    assert_eq!(
+        metrics.intervals_ns.max(),
+        Some(10_000_000)
+    );
+    assert_eq!(metrics.intervals_ns.min(), Some(0));
+    assert_eq!(metrics.out_of_order_count, 1);
+    assert_eq!(metrics.heartbeat_count, 0);
+
+    let json =
+        serde_json::to_value(metrics).expect("序列化失败");
+    assert_eq!(json["packet_sizes"]["count"], 2);
+    assert_eq!(json["out_of_order_count"], 1);
+
+    writer.finalize().expect("完成写入失败");
+}